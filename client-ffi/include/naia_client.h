@@ -0,0 +1,109 @@
+/*
+ * C ABI for the naia client, declared by invoking `naia_client_ffi!` in a
+ * Rust `cdylib` or `staticlib` crate, for the protocol of a given Manifest.
+ *
+ * Events & Entities are exchanged as their NaiaId & bytes, as described by
+ * `Manifest::get_protocol_doc()`. Functions returning int32_t return
+ * NAIA_OK, a non-negative value, or one of the NAIA_ERROR_ codes.
+ *
+ * A Client is driven by calling naia_client_poll() in a loop every frame,
+ * until it returns an event of kind NAIA_EVENT_NONE:
+ *
+ *     NaiaClient *client = naia_client_new("127.0.0.1:14191", 0, NULL, 0);
+ *     NaiaClientEvent event;
+ *     while (naia_client_poll(client, &event) == NAIA_OK
+ *            && event.kind != NAIA_EVENT_NONE) {
+ *         if (event.kind == NAIA_EVENT_EVENT) {
+ *             handle_event(event.naia_id, event.data, event.data_len);
+ *         }
+ *     }
+ *     naia_client_free(client);
+ *
+ * A Client must only be used from the thread which created it.
+ */
+
+#ifndef NAIA_CLIENT_H
+#define NAIA_CLIENT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define NAIA_OK 0
+#define NAIA_ERROR_NULL_POINTER -1
+#define NAIA_ERROR_NOT_CONNECTED -2
+#define NAIA_ERROR_UNREGISTERED_EVENT -3
+#define NAIA_ERROR_EVENT_TOO_LARGE -4
+#define NAIA_ERROR_CONGESTED -5
+#define NAIA_ERROR_ENTITY_NOT_IN_SCOPE -6
+#define NAIA_ERROR_OTHER -7
+
+#define NAIA_EVENT_NONE 0
+#define NAIA_EVENT_CONNECTION 1
+#define NAIA_EVENT_DISCONNECTION 2
+#define NAIA_EVENT_CONNECT_FAILED 3
+#define NAIA_EVENT_EVENT 4
+#define NAIA_EVENT_ENTITY_EVENT 5
+#define NAIA_EVENT_CREATE_ENTITY 6
+#define NAIA_EVENT_UPDATE_ENTITY 7
+#define NAIA_EVENT_DELETE_ENTITY 8
+
+/* An opaque handle to a Client */
+typedef struct NaiaClientHandle NaiaClient;
+
+/* An event received by naia_client_poll() */
+typedef struct NaiaClientEvent {
+    /* One of the NAIA_EVENT_ constants */
+    uint8_t kind;
+    /* The NaiaId of the Event, for NAIA_EVENT_EVENT & NAIA_EVENT_ENTITY_EVENT */
+    uint16_t naia_id;
+    /* The key of the Entity concerned, for the Entity kinds, with its index in
+     * bits 8 to 23 & its generation in the lowest 8 bits. The upper 8 bits
+     * are always 0 */
+    uint32_t entity_key;
+    /* The bytes of the Event, valid until the next call to naia_client_poll()
+     * or naia_client_free() */
+    const uint8_t *data;
+    size_t data_len;
+} NaiaClientEvent;
+
+/* Creates a Client which connects to the Server at the given address, such as
+ * "127.0.0.1:14191". The auth Event is given by its NaiaId & bytes, or
+ * omitted if auth_bytes is NULL. Returns NULL if the address can't be parsed,
+ * or the auth Event can't be read */
+NaiaClient *naia_client_new(const char *server_address, uint16_t auth_naia_id,
+                            const uint8_t *auth_bytes, size_t auth_len);
+
+/* Frees a Client, closing its connection */
+void naia_client_free(NaiaClient *client);
+
+/* Receives the next event from the Server into out_event, whose kind is
+ * NAIA_EVENT_NONE once there are no more */
+int32_t naia_client_poll(NaiaClient *client, NaiaClientEvent *out_event);
+
+/* Queues up an Event to be sent to the Server, given its NaiaId & bytes.
+ * Returns the EventId of the Event, or an error code */
+int32_t naia_client_queue_event(NaiaClient *client, uint16_t naia_id,
+                                const uint8_t *bytes, size_t len);
+
+/* Writes the key of every Entity in scope into out_keys, up to capacity keys,
+ * & returns the number of Entities in scope, which may be more than were
+ * written, or an error code */
+int32_t naia_client_entity_keys(NaiaClient *client, uint32_t *out_keys,
+                                size_t capacity);
+
+/* Gets the NaiaId & bytes of the Entity with the given key. The bytes are
+ * valid until the next call to naia_client_get_entity() or
+ * naia_client_free() */
+int32_t naia_client_get_entity(NaiaClient *client, uint32_t entity_key,
+                               uint16_t *out_naia_id, const uint8_t **out_data,
+                               size_t *out_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
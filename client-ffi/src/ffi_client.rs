@@ -0,0 +1,147 @@
+use std::{net::SocketAddr, rc::Rc};
+
+use naia_client::{ClientEvent, NaiaClient};
+use naia_shared::{EntityType, EventId, EventType, LocalEntityKey, Manifest, NaiaError};
+
+/// An event emitted by an FfiClient, with any Event or Entity it concerns
+/// given by its NaiaId & bytes
+#[derive(Debug)]
+pub enum FfiEvent {
+    /// The Client has no new event from the Server
+    None,
+    /// The Client has established a connection with the Server
+    Connection,
+    /// The Client has lost its connection with the Server
+    Disconnection,
+    /// The Client has given up connecting to the Server
+    ConnectFailed,
+    /// An Event sent by the Server, as its NaiaId & bytes
+    Event(u16, Vec<u8>),
+    /// An Event the Server targeted at the Entity with the given key, as its
+    /// NaiaId & bytes
+    EntityEvent(LocalEntityKey, u16, Vec<u8>),
+    /// An Entity has come into scope
+    CreateEntity(LocalEntityKey),
+    /// An Entity in scope has been updated
+    UpdateEntity(LocalEntityKey),
+    /// An Entity has left scope
+    DeleteEntity(LocalEntityKey),
+    /// An event which is not exposed through the C ABI, & should be skipped
+    Unsupported,
+}
+
+/// A Client which sends & receives Events, & reads Entities, as bytes, so
+/// that it can be driven through the C ABI without knowing the types of the
+/// protocol. Bytes are those an Event or Entity writes itself into, without
+/// compression, as described by `Manifest::get_protocol_doc()`
+pub trait FfiClient {
+    /// Receives the next event from the Server, as `NaiaClient::receive()`
+    fn poll(&mut self) -> Result<FfiEvent, NaiaError>;
+    /// Queues up an Event to be sent to the Server, given its NaiaId & bytes.
+    /// Returns `NaiaError::UnregisteredEvent` if the NaiaId isn't registered,
+    /// or the bytes can't be read as that Event
+    fn queue_event(&mut self, naia_id: u16, bytes: &[u8]) -> Result<EventId, NaiaError>;
+    /// Gets the key of every Entity in scope
+    fn get_entity_keys(&self) -> Vec<LocalEntityKey>;
+    /// Gets the NaiaId & bytes of the Entity with the given key, if it is in
+    /// scope
+    fn get_entity(&self, key: LocalEntityKey) -> Option<(u16, Vec<u8>)>;
+}
+
+/// An FfiClient wrapping a NaiaClient of the given protocol
+#[derive(Debug)]
+pub struct TypedFfiClient<T: EventType, U: EntityType> {
+    client: NaiaClient<T, U>,
+    manifest: Rc<Manifest<T, U>>,
+}
+
+impl<T: EventType, U: EntityType> TypedFfiClient<T, U> {
+    /// Creates a new TypedFfiClient, wrapping a NaiaClient created with a
+    /// shared Manifest
+    pub fn new(client: NaiaClient<T, U>, manifest: Rc<Manifest<T, U>>) -> Self {
+        TypedFfiClient { client, manifest }
+    }
+
+    /// Creates a new TypedFfiClient, which connects to the Server at the
+    /// given address, with an auth Event given by its NaiaId & bytes. Returns
+    /// None if the auth Event can't be read
+    pub fn connect(
+        manifest: Manifest<T, U>,
+        server_address: SocketAddr,
+        auth: Option<(u16, &[u8])>,
+    ) -> Option<Self> {
+        let auth = match auth {
+            Some((naia_id, bytes)) => Some(read_event(&manifest, naia_id, bytes)?),
+            None => None,
+        };
+        let manifest = Rc::new(manifest);
+        let client =
+            NaiaClient::new_with_shared_manifest(server_address, manifest.clone(), None, auth);
+        return Some(TypedFfiClient::new(client, manifest));
+    }
+
+    fn write_event(&self, event: &T) -> (u16, Vec<u8>) {
+        let boxed_event = event.get_boxed_event();
+        let naia_id = self.manifest.get_event_naia_id(&event.get_type_id());
+        let mut bytes = Vec::new();
+        boxed_event.write(&mut bytes);
+        return (naia_id, bytes);
+    }
+}
+
+impl<T: EventType, U: EntityType> FfiClient for TypedFfiClient<T, U> {
+    fn poll(&mut self) -> Result<FfiEvent, NaiaError> {
+        match self.client.receive()? {
+            ClientEvent::None => return Ok(FfiEvent::None),
+            ClientEvent::Connection => return Ok(FfiEvent::Connection),
+            ClientEvent::Disconnection => return Ok(FfiEvent::Disconnection),
+            ClientEvent::ConnectFailed(_) => return Ok(FfiEvent::ConnectFailed),
+            ClientEvent::Event(event) => {
+                let (naia_id, bytes) = self.write_event(&event);
+                return Ok(FfiEvent::Event(naia_id, bytes));
+            }
+            ClientEvent::EntityEvent(key, event) => {
+                let (naia_id, bytes) = self.write_event(&event);
+                return Ok(FfiEvent::EntityEvent(key, naia_id, bytes));
+            }
+            ClientEvent::CreateEntity(key) => return Ok(FfiEvent::CreateEntity(key)),
+            ClientEvent::UpdateEntity(key, _) => return Ok(FfiEvent::UpdateEntity(key)),
+            ClientEvent::DeleteEntity(key, _) => return Ok(FfiEvent::DeleteEntity(key)),
+            _ => return Ok(FfiEvent::Unsupported),
+        }
+    }
+
+    fn queue_event(&mut self, naia_id: u16, bytes: &[u8]) -> Result<EventId, NaiaError> {
+        let event =
+            read_event(&self.manifest, naia_id, bytes).ok_or(NaiaError::UnregisteredEvent)?;
+        return self.client.send_event(event.get_boxed_event().as_ref());
+    }
+
+    fn get_entity_keys(&self) -> Vec<LocalEntityKey> {
+        return self
+            .client
+            .all_entities()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+    }
+
+    fn get_entity(&self, key: LocalEntityKey) -> Option<(u16, Vec<u8>)> {
+        let entity_ref = self.client.get_entity(key)?.get_entity_ref();
+        let entity = entity_ref.as_ref().borrow();
+        let naia_id = self.manifest.get_entity_naia_id(&entity.get_type_id());
+        let mut bytes = Vec::new();
+        entity.write(&mut bytes);
+        return Some((naia_id, bytes));
+    }
+}
+
+/// Creates an Event from its NaiaId & bytes, given by C code. Returns None if
+/// the NaiaId isn't registered, or the bytes can't be read as that Event
+fn read_event<T: EventType, U: EntityType>(
+    manifest: &Manifest<T, U>,
+    naia_id: u16,
+    bytes: &[u8],
+) -> Option<T> {
+    return manifest.create_event(naia_id, bytes);
+}
@@ -0,0 +1,298 @@
+use std::{ffi::CStr, os::raw::c_char, ptr, slice};
+
+use naia_shared::{EntityType, EventType, LocalEntityKey, Manifest, NaiaError};
+
+use super::ffi_client::{FfiClient, FfiEvent, TypedFfiClient};
+
+/// Returned by a call which has succeeded
+pub const NAIA_OK: i32 = 0;
+/// Returned when a pointer argument is null
+pub const NAIA_ERROR_NULL_POINTER: i32 = -1;
+/// Returned when the Client is not connected to the Server
+pub const NAIA_ERROR_NOT_CONNECTED: i32 = -2;
+/// Returned when an Event's NaiaId isn't registered, or its bytes can't be
+/// read as that Event
+pub const NAIA_ERROR_UNREGISTERED_EVENT: i32 = -3;
+/// Returned when an Event is larger than `MAX_EVENT_SIZE`
+pub const NAIA_ERROR_EVENT_TOO_LARGE: i32 = -4;
+/// Returned when an unguaranteed Event is dropped because the connection is
+/// congested
+pub const NAIA_ERROR_CONGESTED: i32 = -5;
+/// Returned when there is no Entity with the given key in scope
+pub const NAIA_ERROR_ENTITY_NOT_IN_SCOPE: i32 = -6;
+/// Returned for any other error
+pub const NAIA_ERROR_OTHER: i32 = -7;
+
+/// The kind of a NaiaClientEvent when there is no new event
+pub const NAIA_EVENT_NONE: u8 = 0;
+/// The kind of a NaiaClientEvent when the Client has connected
+pub const NAIA_EVENT_CONNECTION: u8 = 1;
+/// The kind of a NaiaClientEvent when the Client has disconnected
+pub const NAIA_EVENT_DISCONNECTION: u8 = 2;
+/// The kind of a NaiaClientEvent when the Client has given up connecting
+pub const NAIA_EVENT_CONNECT_FAILED: u8 = 3;
+/// The kind of a NaiaClientEvent holding an Event sent by the Server
+pub const NAIA_EVENT_EVENT: u8 = 4;
+/// The kind of a NaiaClientEvent holding an Event targeted at an Entity
+pub const NAIA_EVENT_ENTITY_EVENT: u8 = 5;
+/// The kind of a NaiaClientEvent when an Entity has come into scope
+pub const NAIA_EVENT_CREATE_ENTITY: u8 = 6;
+/// The kind of a NaiaClientEvent when an Entity in scope has been updated
+pub const NAIA_EVENT_UPDATE_ENTITY: u8 = 7;
+/// The kind of a NaiaClientEvent when an Entity has left scope
+pub const NAIA_EVENT_DELETE_ENTITY: u8 = 8;
+
+/// An opaque handle to a Client, owned by C code
+#[allow(missing_debug_implementations)]
+pub struct NaiaClientHandle {
+    client: Box<dyn FfiClient>,
+    event_bytes: Vec<u8>,
+    entity_bytes: Vec<u8>,
+}
+
+/// An event received by `naia_client_poll()`
+#[repr(C)]
+#[derive(Debug)]
+pub struct NaiaClientEvent {
+    /// One of the `NAIA_EVENT_` constants
+    pub kind: u8,
+    /// The NaiaId of the Event, for `NAIA_EVENT_EVENT` &
+    /// `NAIA_EVENT_ENTITY_EVENT`
+    pub naia_id: u16,
+    /// The key of the Entity concerned, for the Entity kinds, see
+    /// `entity_key_to_u32()`
+    pub entity_key: u32,
+    /// The bytes of the Event, valid until the next call to
+    /// `naia_client_poll()` or `naia_client_free()`
+    pub data: *const u8,
+    /// The number of bytes at `data`
+    pub data_len: usize,
+}
+
+/// Packs a LocalEntityKey into the integer which identifies it in C, with its
+/// index in bits 8 to 23, & its generation in the lowest 8 bits. The upper 8
+/// bits are always 0
+pub fn entity_key_to_u32(key: LocalEntityKey) -> u32 {
+    return (u32::from(key.get_index()) << 8) | u32::from(key.get_generation());
+}
+
+/// Unpacks a LocalEntityKey from the integer which identifies it in C
+pub fn entity_key_from_u32(key: u32) -> LocalEntityKey {
+    return LocalEntityKey::new((key >> 8) as u16, key as u8);
+}
+
+fn error_code(error: NaiaError) -> i32 {
+    match error {
+        NaiaError::NotConnected => return NAIA_ERROR_NOT_CONNECTED,
+        NaiaError::UnregisteredEvent => return NAIA_ERROR_UNREGISTERED_EVENT,
+        NaiaError::EventTooLarge(_) => return NAIA_ERROR_EVENT_TOO_LARGE,
+        NaiaError::Congested => return NAIA_ERROR_CONGESTED,
+        NaiaError::EntityNotInScope => return NAIA_ERROR_ENTITY_NOT_IN_SCOPE,
+        _ => return NAIA_ERROR_OTHER,
+    }
+}
+
+/// Creates a Client of the given protocol, which connects to the Server at
+/// the given address, such as "127.0.0.1:14191". The auth Event is given by
+/// its NaiaId & bytes, or omitted if `auth_bytes` is null. Returns null if the
+/// address can't be parsed, or the auth Event can't be read
+///
+/// # Safety
+/// `server_address` must be a null-terminated string, & `auth_bytes`, unless
+/// null, must point to `auth_len` bytes
+pub unsafe fn client_new<T: 'static + EventType, U: 'static + EntityType>(
+    manifest: Manifest<T, U>,
+    server_address: *const c_char,
+    auth_naia_id: u16,
+    auth_bytes: *const u8,
+    auth_len: usize,
+) -> *mut NaiaClientHandle {
+    if server_address.is_null() {
+        return ptr::null_mut();
+    }
+    let server_address = match CStr::from_ptr(server_address).to_str().map(str::parse) {
+        Ok(Ok(server_address)) => server_address,
+        _ => return ptr::null_mut(),
+    };
+    let auth = match auth_bytes.is_null() {
+        true => None,
+        false => Some((auth_naia_id, slice::from_raw_parts(auth_bytes, auth_len))),
+    };
+    let client = match TypedFfiClient::connect(manifest, server_address, auth) {
+        Some(client) => client,
+        None => return ptr::null_mut(),
+    };
+    let handle = NaiaClientHandle {
+        client: Box::new(client),
+        event_bytes: Vec::new(),
+        entity_bytes: Vec::new(),
+    };
+    return Box::into_raw(Box::new(handle));
+}
+
+/// Frees a Client created with `client_new()`, closing its connection
+///
+/// # Safety
+/// `handle` must have been returned by `client_new()`, or be null, & must not
+/// be used afterwards
+pub unsafe fn client_free(handle: *mut NaiaClientHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Receives the next event from the Server into `out_event`, whose kind is
+/// `NAIA_EVENT_NONE` once there are no more. Should be called in a loop every
+/// frame, until then. Returns `NAIA_OK` or an error code
+///
+/// # Safety
+/// `handle` must be a live handle, & `out_event` must point to a
+/// NaiaClientEvent
+pub unsafe fn client_poll(handle: *mut NaiaClientHandle, out_event: *mut NaiaClientEvent) -> i32 {
+    if handle.is_null() || out_event.is_null() {
+        return NAIA_ERROR_NULL_POINTER;
+    }
+    let handle = &mut *handle;
+    let out_event = &mut *out_event;
+    out_event.kind = NAIA_EVENT_NONE;
+    out_event.naia_id = 0;
+    out_event.entity_key = 0;
+    out_event.data = ptr::null();
+    out_event.data_len = 0;
+
+    loop {
+        let event = match handle.client.poll() {
+            Ok(event) => event,
+            Err(error) => return error_code(error),
+        };
+        match event {
+            FfiEvent::None => {}
+            FfiEvent::Connection => out_event.kind = NAIA_EVENT_CONNECTION,
+            FfiEvent::Disconnection => out_event.kind = NAIA_EVENT_DISCONNECTION,
+            FfiEvent::ConnectFailed => out_event.kind = NAIA_EVENT_CONNECT_FAILED,
+            FfiEvent::Event(naia_id, bytes) => {
+                out_event.kind = NAIA_EVENT_EVENT;
+                out_event.naia_id = naia_id;
+                handle.event_bytes = bytes;
+            }
+            FfiEvent::EntityEvent(key, naia_id, bytes) => {
+                out_event.kind = NAIA_EVENT_ENTITY_EVENT;
+                out_event.naia_id = naia_id;
+                out_event.entity_key = entity_key_to_u32(key);
+                handle.event_bytes = bytes;
+            }
+            FfiEvent::CreateEntity(key) => {
+                out_event.kind = NAIA_EVENT_CREATE_ENTITY;
+                out_event.entity_key = entity_key_to_u32(key);
+            }
+            FfiEvent::UpdateEntity(key) => {
+                out_event.kind = NAIA_EVENT_UPDATE_ENTITY;
+                out_event.entity_key = entity_key_to_u32(key);
+            }
+            FfiEvent::DeleteEntity(key) => {
+                out_event.kind = NAIA_EVENT_DELETE_ENTITY;
+                out_event.entity_key = entity_key_to_u32(key);
+            }
+            FfiEvent::Unsupported => continue,
+        }
+        break;
+    }
+    if out_event.kind == NAIA_EVENT_EVENT || out_event.kind == NAIA_EVENT_ENTITY_EVENT {
+        out_event.data = handle.event_bytes.as_ptr();
+        out_event.data_len = handle.event_bytes.len();
+    }
+    return NAIA_OK;
+}
+
+/// Queues up an Event to be sent to the Server, given its NaiaId & bytes.
+/// Returns the EventId of the Event, which is never negative, or an error
+/// code
+///
+/// # Safety
+/// `handle` must be a live handle, & `bytes` must point to `len` bytes
+pub unsafe fn client_queue_event(
+    handle: *mut NaiaClientHandle,
+    naia_id: u16,
+    bytes: *const u8,
+    len: usize,
+) -> i32 {
+    if handle.is_null() || (bytes.is_null() && len > 0) {
+        return NAIA_ERROR_NULL_POINTER;
+    }
+    let bytes: &[u8] = match bytes.is_null() {
+        true => &[],
+        false => slice::from_raw_parts(bytes, len),
+    };
+    match (*handle).client.queue_event(naia_id, bytes) {
+        Ok(event_id) => return i32::from(event_id),
+        Err(error) => return error_code(error),
+    }
+}
+
+/// Writes the key of every Entity in scope into `out_keys`, up to `capacity`
+/// keys, & returns the number of Entities in scope, which may be more than
+/// were written, or an error code
+///
+/// # Safety
+/// `handle` must be a live handle, & `out_keys` must point to room for
+/// `capacity` keys, or be null if `capacity` is 0
+pub unsafe fn client_entity_keys(
+    handle: *mut NaiaClientHandle,
+    out_keys: *mut u32,
+    capacity: usize,
+) -> i32 {
+    if handle.is_null() || (out_keys.is_null() && capacity > 0) {
+        return NAIA_ERROR_NULL_POINTER;
+    }
+    let keys = (*handle).client.get_entity_keys();
+    for (index, key) in keys.iter().take(capacity).enumerate() {
+        *out_keys.add(index) = entity_key_to_u32(*key);
+    }
+    return keys.len() as i32;
+}
+
+/// Gets the NaiaId & bytes of the Entity with the given key. The bytes are
+/// valid until the next call to `client_get_entity()` or `client_free()`.
+/// Returns `NAIA_OK` or an error code
+///
+/// # Safety
+/// `handle` must be a live handle, & the out pointers must be valid
+pub unsafe fn client_get_entity(
+    handle: *mut NaiaClientHandle,
+    entity_key: u32,
+    out_naia_id: *mut u16,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> i32 {
+    if handle.is_null() || out_naia_id.is_null() || out_data.is_null() || out_len.is_null() {
+        return NAIA_ERROR_NULL_POINTER;
+    }
+    let handle = &mut *handle;
+    let (naia_id, bytes) = match handle.client.get_entity(entity_key_from_u32(entity_key)) {
+        Some(entity) => entity,
+        None => return NAIA_ERROR_ENTITY_NOT_IN_SCOPE,
+    };
+    handle.entity_bytes = bytes;
+    *out_naia_id = naia_id;
+    *out_data = handle.entity_bytes.as_ptr();
+    *out_len = handle.entity_bytes.len();
+    return NAIA_OK;
+}
+
+#[cfg(test)]
+mod tests {
+    use naia_shared::LocalEntityKey;
+
+    use super::{entity_key_from_u32, entity_key_to_u32};
+
+    #[test]
+    fn entity_keys_round_trip() {
+        for (index, generation) in [(0, 0), (1, 255), (0xABCD, 0x12), (u16::MAX, u8::MAX)] {
+            let key = LocalEntityKey::new(index, generation);
+            let packed = entity_key_to_u32(key);
+            assert!(packed >> 24 == 0);
+            assert!((packed >> 8) as u16 == index && packed as u8 == generation);
+            assert!(entity_key_from_u32(packed) == key);
+        }
+    }
+}
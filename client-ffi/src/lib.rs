@@ -0,0 +1,107 @@
+//! # Naia Client FFI
+//! A C ABI for the naia client, which sends & receives events, & reads
+//! entities, as bytes, for engine integrations in C & C++.
+//!
+//! The types of a protocol are only known to Rust, so the C functions are
+//! declared by invoking `naia_client_ffi!` in a `cdylib` or `staticlib` crate
+//! which depends on the protocol's shared crate, given its EventType,
+//! EntityType & a function returning its Manifest. C code then includes
+//! `include/naia_client.h`, & reads & writes the bytes of Events & Entities as
+//! described by `Manifest::get_protocol_doc()`.
+
+#![deny(
+    missing_docs,
+    missing_debug_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_import_braces
+)]
+
+mod ffi_client;
+mod handle;
+
+pub use ffi_client::{FfiClient, FfiEvent, TypedFfiClient};
+pub use handle::{
+    client_entity_keys, client_free, client_get_entity, client_new, client_poll,
+    client_queue_event, entity_key_from_u32, entity_key_to_u32, NaiaClientEvent, NaiaClientHandle,
+    NAIA_ERROR_CONGESTED, NAIA_ERROR_ENTITY_NOT_IN_SCOPE, NAIA_ERROR_EVENT_TOO_LARGE,
+    NAIA_ERROR_NOT_CONNECTED, NAIA_ERROR_NULL_POINTER, NAIA_ERROR_OTHER,
+    NAIA_ERROR_UNREGISTERED_EVENT, NAIA_EVENT_CONNECTION, NAIA_EVENT_CONNECT_FAILED,
+    NAIA_EVENT_CREATE_ENTITY, NAIA_EVENT_DELETE_ENTITY, NAIA_EVENT_DISCONNECTION,
+    NAIA_EVENT_ENTITY_EVENT, NAIA_EVENT_EVENT, NAIA_EVENT_NONE, NAIA_EVENT_UPDATE_ENTITY, NAIA_OK,
+};
+
+/// Declares the C functions of `include/naia_client.h` for the protocol made
+/// up of the given EventType, EntityType & function returning its Manifest,
+/// such as `naia_client_ffi!(ExampleEvent, ExampleEntity, manifest_load);`.
+/// Must be invoked once, at the root of a `cdylib` or `staticlib` crate
+#[macro_export]
+macro_rules! naia_client_ffi {
+    ($event_type:ty, $entity_type:ty, $manifest_func:expr) => {
+        /// See `naia_client_ffi::client_new()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_new(
+            server_address: *const ::std::os::raw::c_char,
+            auth_naia_id: u16,
+            auth_bytes: *const u8,
+            auth_len: usize,
+        ) -> *mut $crate::NaiaClientHandle {
+            return $crate::client_new::<$event_type, $entity_type>(
+                $manifest_func(),
+                server_address,
+                auth_naia_id,
+                auth_bytes,
+                auth_len,
+            );
+        }
+
+        /// See `naia_client_ffi::client_free()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_free(handle: *mut $crate::NaiaClientHandle) {
+            $crate::client_free(handle);
+        }
+
+        /// See `naia_client_ffi::client_poll()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_poll(
+            handle: *mut $crate::NaiaClientHandle,
+            out_event: *mut $crate::NaiaClientEvent,
+        ) -> i32 {
+            return $crate::client_poll(handle, out_event);
+        }
+
+        /// See `naia_client_ffi::client_queue_event()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_queue_event(
+            handle: *mut $crate::NaiaClientHandle,
+            naia_id: u16,
+            bytes: *const u8,
+            len: usize,
+        ) -> i32 {
+            return $crate::client_queue_event(handle, naia_id, bytes, len);
+        }
+
+        /// See `naia_client_ffi::client_entity_keys()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_entity_keys(
+            handle: *mut $crate::NaiaClientHandle,
+            out_keys: *mut u32,
+            capacity: usize,
+        ) -> i32 {
+            return $crate::client_entity_keys(handle, out_keys, capacity);
+        }
+
+        /// See `naia_client_ffi::client_get_entity()`
+        #[no_mangle]
+        pub unsafe extern "C" fn naia_client_get_entity(
+            handle: *mut $crate::NaiaClientHandle,
+            entity_key: u32,
+            out_naia_id: *mut u16,
+            out_data: *mut *const u8,
+            out_len: *mut usize,
+        ) -> i32 {
+            return $crate::client_get_entity(handle, entity_key, out_naia_id, out_data, out_len);
+        }
+    };
+}
@@ -3,4 +3,5 @@ pub enum ClientConnectionState {
     AwaitingChallengeResponse,
     AwaitingConnectResponse,
     Connected,
+    Failed,
 }
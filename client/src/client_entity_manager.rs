@@ -1,98 +1,561 @@
 use byteorder::{BigEndian, ReadBytesExt};
 use log::warn;
-use naia_shared::{EntityType, EventType, LocalEntityKey, Manifest, PacketReader, StateMask};
-use std::collections::{HashMap, VecDeque};
+use naia_shared::{
+    decompress, sequence_less_than, Correction, CorrectionCurve, DropLog, DropReason,
+    EntityDebugDump, EntityDebugRecord, EntityDebugStatus, EntityType, EventType, Extrapolation,
+    ExtrapolationModel, LateUpdatePolicy, LateUpdateStats, LocalEntityKey, Manifest, MotionSampler,
+    PacketReader, PredictionKey, PropertyTicks, StateMask, Timer, BATCHED_ENTITY_MESSAGE_FLAG,
+};
+use std::{
+    any::TypeId,
+    collections::{HashMap, VecDeque},
+    io::Cursor,
+    time::Duration,
+};
 
-use super::client_entity_message::ClientEntityMessage;
+use super::{
+    client_entity_message::ClientEntityMessage, incoming_entity_message::IncomingEntityMessage,
+};
 
 #[derive(Debug)]
 pub struct ClientEntityManager<T: EntityType> {
     local_entity_store: HashMap<LocalEntityKey, T>,
+    tombstones: HashMap<LocalEntityKey, Timer>,
+    previous_entity_store: HashMap<LocalEntityKey, T>,
+    keep_previous_values: bool,
+    predicted_entity_store: HashMap<PredictionKey, T>,
+    next_prediction_key: PredictionKey,
+    tombstone_duration: Duration,
     queued_incoming_messages: VecDeque<ClientEntityMessage>,
+    snapshot_total: Option<u32>,
+    snapshot_received: u32,
+    world_synced: bool,
+    pending_batches: HashMap<u16, HashMap<u16, Option<IncomingEntityMessage<T>>>>,
+    batch_boundaries: HashMap<u16, (u16, u16)>,
+    next_batch_index: u16,
+    snapshot_chunks: Vec<Option<Box<[u8]>>>,
+    bulk_snapshot_applied: bool,
+    corrections: HashMap<LocalEntityKey, Correction>,
+    correction_duration: Duration,
+    correction_curve: CorrectionCurve,
+    extrapolations: HashMap<LocalEntityKey, Extrapolation>,
+    extrapolation_model: ExtrapolationModel,
+    max_extrapolation: Duration,
+    property_ticks: HashMap<LocalEntityKey, PropertyTicks>,
+    late_update_policy: LateUpdatePolicy,
+    late_update_policies: HashMap<TypeId, LateUpdatePolicy>,
+    late_entity_store: HashMap<LocalEntityKey, T>,
+    late_update_stats: LateUpdateStats,
 }
 
 impl<U: EntityType> ClientEntityManager<U> {
-    pub fn new() -> Self {
+    pub fn new(
+        tombstone_duration: Duration,
+        keep_previous_values: bool,
+        correction_duration: Duration,
+        correction_curve: CorrectionCurve,
+        extrapolation_model: ExtrapolationModel,
+        max_extrapolation: Duration,
+        late_update_policy: LateUpdatePolicy,
+    ) -> Self {
         ClientEntityManager {
             queued_incoming_messages: VecDeque::new(),
             local_entity_store: HashMap::new(),
+            tombstones: HashMap::new(),
+            previous_entity_store: HashMap::new(),
+            keep_previous_values,
+            predicted_entity_store: HashMap::new(),
+            next_prediction_key: 0,
+            tombstone_duration,
+            snapshot_total: None,
+            snapshot_received: 0,
+            world_synced: false,
+            pending_batches: HashMap::new(),
+            batch_boundaries: HashMap::new(),
+            next_batch_index: 0,
+            snapshot_chunks: Vec::new(),
+            bulk_snapshot_applied: false,
+            corrections: HashMap::new(),
+            correction_duration,
+            correction_curve,
+            extrapolations: HashMap::new(),
+            extrapolation_model,
+            max_extrapolation,
+            property_ticks: HashMap::new(),
+            late_update_policy,
+            late_update_policies: HashMap::new(),
+            late_entity_store: HashMap::new(),
+            late_update_stats: LateUpdateStats::default(),
         }
     }
 
+    /// Reads the Entity messages of a packet, sent at the given Server tick,
+    /// if known. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
     pub fn process_data<T: EventType>(
         &mut self,
         reader: &mut PacketReader,
+        tick: Option<u16>,
         manifest: &Manifest<T, U>,
-    ) {
+        drop_log: &mut DropLog,
+    ) -> bool {
         let buffer = reader.get_buffer();
         let cursor = reader.get_cursor();
 
-        let entity_message_count = cursor.read_u8().unwrap();
+        self.tombstones.retain(|_, timer| !timer.ringing());
+
+        let entity_message_count = match cursor.read_u8() {
+            Ok(entity_message_count) => entity_message_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
         //info!("reading {} entity messages", entity_message_count);
         for _x in 0..entity_message_count {
-            let message_type: u8 = cursor.read_u8().unwrap().into();
-
-            match message_type {
-                0 => {
-                    // Creation
-                    let naia_id: u16 = cursor.read_u16::<BigEndian>().unwrap().into();
-                    let local_key: u16 = cursor.read_u16::<BigEndian>().unwrap().into();
-                    let payload_length: u8 = cursor.read_u8().unwrap().into();
-                    let payload_start_position: usize = cursor.position() as usize;
-                    let payload_end_position: usize =
-                        payload_start_position + (payload_length as usize);
-
-                    let entity_payload = buffer[payload_start_position..payload_end_position]
-                        .to_vec()
-                        .into_boxed_slice();
-
-                    match manifest.create_entity(naia_id, &entity_payload) {
-                        Some(new_entity) => {
-                            if self.local_entity_store.contains_key(&local_key) {
-                                warn!("duplicate local key inserted");
-                            } else {
-                                //info!("creation of entity w/ key of {}", local_key);
-                                self.local_entity_store.insert(local_key, new_entity);
-                                self.queued_incoming_messages
-                                    .push_back(ClientEntityMessage::Create(local_key));
+            let (batch_index, message) = match self.read_message(buffer, cursor, manifest, drop_log)
+            {
+                Some(read) => read,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            match batch_index {
+                Some((batch_index, batch_slot)) => {
+                    // messages of a batch are held until every slot of the batch has been
+                    // filled. Resent duplicates fill the slot they already filled, & messages
+                    // which could not be read still fill theirs, so the batch is not stalled
+                    if !sequence_less_than(batch_index, self.next_batch_index) {
+                        self.pending_batches
+                            .entry(batch_index)
+                            .or_insert_with(HashMap::new)
+                            .entry(batch_slot)
+                            .or_insert(message);
+                    }
+                }
+                None => {
+                    if let Some(message) = message {
+                        self.apply_message(message, tick, manifest, drop_log);
+                    }
+                }
+            }
+        }
+
+        self.apply_complete_batches(manifest, drop_log);
+        return true;
+    }
+
+    // Reads a single Entity message, along with the index of the tick batch it
+    // belongs to & its slot within the batch, if any. Returns None if the packet
+    // ends before the message does. A Creation which could not be read is
+    // recorded in the DropLog & skipped
+    fn read_message<T: EventType>(
+        &mut self,
+        buffer: &[u8],
+        cursor: &mut Cursor<&[u8]>,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> Option<(Option<(u16, u16)>, Option<IncomingEntityMessage<U>>)> {
+        let message_type_byte: u8 = cursor.read_u8().ok()?;
+        let message_type = message_type_byte & !BATCHED_ENTITY_MESSAGE_FLAG;
+        let batch_index: Option<(u16, u16)> = match message_type_byte & BATCHED_ENTITY_MESSAGE_FLAG
+        {
+            0 => None,
+            _ => Some((
+                cursor.read_u16::<BigEndian>().ok()?,
+                cursor.read_u16::<BigEndian>().ok()?,
+            )),
+        };
+
+        let message: Option<IncomingEntityMessage<U>> = match message_type {
+            0 | 5 | 6 | 7 => {
+                // Creation. Types 5 & 7 are of an Entity the Client has predicted, and
+                // types 6 & 7 are of an Entity built from a prefab, with only the
+                // Properties which differ from the prefab written
+                let naia_or_prefab_id: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                let local_key = LocalEntityKey::read(cursor)?;
+                let prediction_key: Option<PredictionKey> = match message_type {
+                    5 | 7 => Some(cursor.read_u16::<BigEndian>().ok()?),
+                    _ => None,
+                };
+                let prefab_state_mask: Option<StateMask> = match message_type {
+                    6 | 7 => Some(StateMask::read(cursor)?),
+                    _ => None,
+                };
+                let payload_length: u8 = cursor.read_u8().ok()?;
+                let payload_start_position: usize = cursor.position() as usize;
+                let payload_end_position: usize =
+                    payload_start_position + (payload_length as usize);
+                if payload_end_position > buffer.len() {
+                    return None;
+                }
+
+                let entity_payload = buffer[payload_start_position..payload_end_position]
+                    .to_vec()
+                    .into_boxed_slice();
+
+                let new_entity = match prefab_state_mask {
+                    Some(state_mask) => {
+                        match manifest.create_entity_from_prefab(naia_or_prefab_id) {
+                            Some(mut new_entity) => {
+                                match new_entity.read_partial(&state_mask, &entity_payload) {
+                                    true => Ok(new_entity),
+                                    false => Err(DropReason::MalformedPayload),
+                                }
                             }
+                            None => Err(DropReason::UnknownNaiaId),
                         }
-                        _ => {}
                     }
+                    None => manifest.read_entity(naia_or_prefab_id, &entity_payload),
+                };
+
+                cursor.set_position(payload_end_position as u64);
 
-                    cursor.set_position(payload_end_position as u64);
+                match new_entity {
+                    Ok(new_entity) => Some(IncomingEntityMessage::Create(
+                        local_key,
+                        prediction_key,
+                        new_entity,
+                    )),
+                    Err(reason) => {
+                        drop_log.record(reason);
+                        None
+                    }
                 }
-                1 => {
-                    // Deletion
-                    let local_key = cursor.read_u16::<BigEndian>().unwrap().into();
-                    self.local_entity_store.remove(&local_key);
-                    self.queued_incoming_messages
-                        .push_back(ClientEntityMessage::Delete(local_key));
+            }
+            1 => {
+                // Deletion
+                let local_key = LocalEntityKey::read(cursor)?;
+                let reason = match cursor.read_u8().ok()? {
+                    0 => None,
+                    _ => Some(cursor.read_u8().ok()?),
+                };
+                Some(IncomingEntityMessage::Delete(local_key, reason))
+            }
+            2 => {
+                // Update
+                let local_key = LocalEntityKey::read(cursor)?;
+                let state_mask: StateMask = StateMask::read(cursor)?;
+                let payload_length: u8 = cursor.read_u8().ok()?;
+                let payload_start_position: usize = cursor.position() as usize;
+                let payload_end_position: usize =
+                    payload_start_position + (payload_length as usize);
+                if payload_end_position > buffer.len() {
+                    return None;
                 }
-                2 => {
-                    // Update
-                    let local_key = cursor.read_u16::<BigEndian>().unwrap().into();
 
-                    if let Some(entity_ref) = self.local_entity_store.get_mut(&local_key) {
-                        let state_mask: StateMask = StateMask::read(cursor);
-                        let payload_length: u8 = cursor.read_u8().unwrap().into();
-                        let payload_start_position: usize = cursor.position() as usize;
-                        let payload_end_position: usize =
-                            payload_start_position + (payload_length as usize);
+                let entity_payload = buffer[payload_start_position..payload_end_position]
+                    .to_vec()
+                    .into_boxed_slice();
+
+                cursor.set_position(payload_end_position as u64);
+
+                Some(IncomingEntityMessage::Update(
+                    local_key,
+                    state_mask,
+                    entity_payload,
+                ))
+            }
+            3 => {
+                // Snapshot Begin
+                let total: u32 = cursor.read_u32::<BigEndian>().ok()?;
+                Some(IncomingEntityMessage::SnapshotBegin(total))
+            }
+            4 => {
+                // Snapshot Complete
+                Some(IncomingEntityMessage::SnapshotComplete)
+            }
+            9 => {
+                // Snapshot Chunk, a piece of a Room snapshot sent in bulk
+                let chunk_index: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                let total_chunks: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                let payload_length: u8 = cursor.read_u8().ok()?;
+                let payload_start_position: usize = cursor.position() as usize;
+                let payload_end_position: usize =
+                    payload_start_position + (payload_length as usize);
+                if payload_end_position > buffer.len() {
+                    return None;
+                }
+
+                let chunk_payload = buffer[payload_start_position..payload_end_position]
+                    .to_vec()
+                    .into_boxed_slice();
+
+                cursor.set_position(payload_end_position as u64);
+
+                Some(IncomingEntityMessage::SnapshotChunk(
+                    chunk_index,
+                    total_chunks,
+                    chunk_payload,
+                ))
+            }
+            8 => {
+                // Tick Complete
+                let completed_batch_index: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                let tick: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                let message_count: u16 = cursor.read_u16::<BigEndian>().ok()?;
+                if !sequence_less_than(completed_batch_index, self.next_batch_index) {
+                    self.batch_boundaries
+                        .insert(completed_batch_index, (tick, message_count));
+                }
+                None
+            }
+            _ => None,
+        };
+
+        return Some((batch_index, message));
+    }
+
+    fn apply_complete_batches<T: EventType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) {
+        while let Some((tick, message_count)) =
+            self.batch_boundaries.get(&self.next_batch_index).copied()
+        {
+            let is_complete = match self.pending_batches.get(&self.next_batch_index) {
+                Some(messages) => (0..message_count).all(|slot| messages.contains_key(&slot)),
+                None => message_count == 0,
+            };
+            if !is_complete {
+                return;
+            }
+
+            self.batch_boundaries.remove(&self.next_batch_index);
+            let mut slots: Vec<(u16, Option<IncomingEntityMessage<U>>)> = self
+                .pending_batches
+                .remove(&self.next_batch_index)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            slots.sort_by_key(|(slot, _)| *slot);
+            let mut messages: Vec<IncomingEntityMessage<U>> = slots
+                .into_iter()
+                .filter_map(|(_, message)| message)
+                .collect();
+            // messages may have arrived out of order, so Creations are applied
+            // before Updates, which are applied before Deletions
+            messages.sort_by_key(|message| message.apply_order());
+            for message in messages {
+                self.apply_message(message, Some(tick), manifest, drop_log);
+            }
 
-                        let entity_payload = buffer[payload_start_position..payload_end_position]
-                            .to_vec()
-                            .into_boxed_slice();
+            self.queued_incoming_messages
+                .push_back(ClientEntityMessage::TickApplied(tick));
+            self.next_batch_index = self.next_batch_index.wrapping_add(1);
+        }
+    }
 
-                        entity_ref.read_partial(&state_mask, &entity_payload);
+    fn apply_message<T: EventType>(
+        &mut self,
+        message: IncomingEntityMessage<U>,
+        tick: Option<u16>,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) {
+        match message {
+            IncomingEntityMessage::Create(local_key, prediction_key, new_entity) => {
+                self.create_entity(local_key, prediction_key, new_entity, tick);
+            }
+            IncomingEntityMessage::SnapshotChunk(chunk_index, total_chunks, chunk_payload) => {
+                // chunks delivered more than once are skipped
+                if self.bulk_snapshot_applied {
+                    return;
+                }
+                if self.snapshot_chunks.len() != total_chunks as usize {
+                    self.snapshot_chunks = vec![None; total_chunks as usize];
+                }
+                if let Some(chunk_slot) = self.snapshot_chunks.get_mut(chunk_index as usize) {
+                    *chunk_slot = Some(chunk_payload);
+                }
+                if self.snapshot_chunks.iter().all(|chunk| chunk.is_some()) {
+                    let mut snapshot_bytes = Vec::<u8>::new();
+                    for chunk in self.snapshot_chunks.drain(..) {
+                        snapshot_bytes.extend_from_slice(&chunk.unwrap());
+                    }
+                    self.bulk_snapshot_applied = true;
+                    self.apply_snapshot(&snapshot_bytes, tick, manifest, drop_log);
+                }
+            }
+            IncomingEntityMessage::Delete(local_key, reason) => {
+                if self.local_entity_store.remove(&local_key).is_some() {
+                    self.previous_entity_store.remove(&local_key);
+                    self.property_ticks.remove(&local_key);
+                    self.late_entity_store.remove(&local_key);
+                    self.corrections.remove(&local_key);
+                    self.extrapolations.remove(&local_key);
+                    self.tombstones
+                        .insert(local_key, Timer::new(self.tombstone_duration));
+                    self.queued_incoming_messages
+                        .push_back(ClientEntityMessage::Delete(local_key, reason));
+                }
+            }
+            IncomingEntityMessage::Update(local_key, mut state_mask, mut entity_payload) => {
+                // late updates addressed to a deleted Entity are skipped
+                if let Some(entity_ref) = self.local_entity_store.get_mut(&local_key) {
+                    if let (Some(tick), Some(property_ticks)) =
+                        (tick, self.property_ticks.get(&local_key))
+                    {
+                        // an Update is late when a newer one of any of its
+                        // Properties has already been applied
+                        let stale_mask = property_ticks.get_stale_mask(&state_mask, tick);
+                        if !stale_mask.is_clear() {
+                            self.late_update_stats.late += 1;
+                            let policy = self
+                                .late_update_policies
+                                .get(&entity_ref.get_type_id())
+                                .copied()
+                                .unwrap_or(self.late_update_policy);
+                            match policy {
+                                LateUpdatePolicy::Discard => {
+                                    self.late_update_stats.discarded += 1;
+                                    return;
+                                }
+                                LateUpdatePolicy::DeliverToApp => {
+                                    let mut late_entity = entity_ref.get_copy();
+                                    if !late_entity.read_partial(&state_mask, &entity_payload) {
+                                        drop_log.record(DropReason::MalformedPayload);
+                                        return;
+                                    }
+                                    self.late_entity_store.insert(local_key, late_entity);
+                                    self.late_update_stats.delivered += 1;
+                                    self.queued_incoming_messages.push_back(
+                                        ClientEntityMessage::LateUpdate(
+                                            local_key, tick, state_mask,
+                                        ),
+                                    );
+                                    return;
+                                }
+                                LateUpdatePolicy::ApplyNewerFields => {
+                                    // the payload holds every Property of the Update, so
+                                    // it's read into a copy, & the fresh Properties are
+                                    // written back out alone
+                                    let mut late_entity = entity_ref.get_copy();
+                                    if !late_entity.read_partial(&state_mask, &entity_payload) {
+                                        drop_log.record(DropReason::MalformedPayload);
+                                        return;
+                                    }
+                                    for index in stale_mask.get_set_indices() {
+                                        state_mask.set_bit(index, false);
+                                    }
+                                    if state_mask.is_clear() {
+                                        self.late_update_stats.discarded += 1;
+                                        return;
+                                    }
+                                    self.late_update_stats.partially_applied += 1;
 
-                        self.queued_incoming_messages
-                            .push_back(ClientEntityMessage::Update(local_key));
+                                    let mut fresh_payload = Vec::<u8>::new();
+                                    late_entity
+                                        .get_entity_ref()
+                                        .borrow()
+                                        .write_partial(&state_mask, &mut fresh_payload);
+                                    entity_payload = fresh_payload.into_boxed_slice();
+                                }
+                            }
+                        }
+                    }
 
-                        cursor.set_position(payload_end_position as u64);
+                    // an Update which could not be read leaves the Entity, its
+                    // previous values & the ticks of its Properties untouched
+                    let previous_entity = match self.keep_previous_values {
+                        true => Some(entity_ref.get_copy()),
+                        false => None,
+                    };
+                    if !entity_ref.read_partial(&state_mask, &entity_payload) {
+                        drop_log.record(DropReason::MalformedPayload);
+                        return;
+                    }
+                    if let Some(previous_entity) = previous_entity {
+                        self.previous_entity_store
+                            .insert(local_key, previous_entity);
                     }
+                    if let (Some(tick), Some(property_ticks)) =
+                        (tick, self.property_ticks.get_mut(&local_key))
+                    {
+                        property_ticks.record(&state_mask, tick);
+                    }
+
+                    self.queued_incoming_messages
+                        .push_back(ClientEntityMessage::Update(local_key, state_mask));
+                } else if !self.tombstones.contains_key(&local_key) {
+                    warn!("received update for unknown entity with key: {}", local_key);
+                }
+            }
+            IncomingEntityMessage::SnapshotBegin(total) => {
+                self.snapshot_total = Some(total);
+            }
+            IncomingEntityMessage::SnapshotComplete => {
+                if !self.world_synced {
+                    self.world_synced = true;
+                    self.queued_incoming_messages
+                        .push_back(ClientEntityMessage::WorldSynced);
+                }
+            }
+        }
+    }
+
+    fn create_entity(
+        &mut self,
+        local_key: LocalEntityKey,
+        prediction_key: Option<PredictionKey>,
+        new_entity: U,
+        tick: Option<u16>,
+    ) {
+        if self.local_entity_store.contains_key(&local_key) {
+            warn!("duplicate local key inserted");
+        } else {
+            //info!("creation of entity w/ key of {}", local_key);
+            let property_count = new_entity.get_entity_ref().borrow().get_property_count();
+            self.property_ticks
+                .insert(local_key, PropertyTicks::new(property_count, tick));
+            self.local_entity_store.insert(local_key, new_entity);
+            if !self.world_synced {
+                self.snapshot_received += 1;
+            }
+            match prediction_key {
+                Some(prediction_key) => {
+                    self.predicted_entity_store.remove(&prediction_key);
+                    self.queued_incoming_messages
+                        .push_back(ClientEntityMessage::ConfirmPredicted(
+                            prediction_key,
+                            local_key,
+                        ));
+                }
+                None => {
+                    self.queued_incoming_messages
+                        .push_back(ClientEntityMessage::Create(local_key));
+                }
+            }
+        }
+    }
+
+    // A Room snapshot sent in bulk is made up of the local key of every Entity
+    // in it which is in scope, followed by the compressed Entities themselves
+    fn apply_snapshot<T: EventType>(
+        &mut self,
+        snapshot_bytes: &[u8],
+        tick: Option<u16>,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) {
+        let snapshot_entities = match read_snapshot(snapshot_bytes) {
+            Some(snapshot_entities) => snapshot_entities,
+            None => {
+                warn!("received malformed room snapshot");
+                drop_log.record(DropReason::MalformedPayload);
+                return;
+            }
+        };
+
+        for (local_key, naia_id, entity_payload) in snapshot_entities {
+            if let Some(local_key) = local_key {
+                match manifest.read_entity(naia_id, &entity_payload) {
+                    Ok(new_entity) => self.create_entity(local_key, None, new_entity, tick),
+                    Err(reason) => drop_log.record(reason),
                 }
-                _ => {}
             }
         }
     }
@@ -104,4 +567,221 @@ impl<U: EntityType> ClientEntityManager<U> {
     pub fn get_local_entity(&self, key: LocalEntityKey) -> Option<&U> {
         return self.local_entity_store.get(&key);
     }
+
+    pub fn get_previous_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.previous_entity_store.get(&key);
+    }
+
+    /// Gets the state an Entity would have had if the most recent late Update
+    /// delivered to the application had been applied
+    pub fn get_late_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.late_entity_store.get(&key);
+    }
+
+    pub fn set_late_update_policy(&mut self, type_id: TypeId, policy: LateUpdatePolicy) {
+        self.late_update_policies.insert(type_id, policy);
+    }
+
+    pub fn get_late_update_stats(&self) -> LateUpdateStats {
+        return self.late_update_stats;
+    }
+
+    pub fn get_all_entities(&self) -> Vec<(LocalEntityKey, &U)> {
+        return self
+            .local_entity_store
+            .iter()
+            .map(|(key, entity)| (*key, entity))
+            .collect();
+    }
+
+    pub fn get_entities_of_type(&self, type_id: TypeId) -> Vec<(LocalEntityKey, &U)> {
+        return self
+            .local_entity_store
+            .iter()
+            .filter(|(_, entity)| entity.get_type_id() == type_id)
+            .map(|(key, entity)| (*key, entity))
+            .collect();
+    }
+
+    pub fn get_entity_count(&self) -> usize {
+        return self.local_entity_store.len();
+    }
+
+    pub fn spawn_predicted_entity(&mut self, entity: U) -> PredictionKey {
+        let prediction_key = self.next_prediction_key;
+        self.next_prediction_key = self.next_prediction_key.wrapping_add(1);
+        self.predicted_entity_store.insert(prediction_key, entity);
+        return prediction_key;
+    }
+
+    pub fn get_predicted_entity(&self, key: PredictionKey) -> Option<&U> {
+        return self.predicted_entity_store.get(&key);
+    }
+
+    pub fn remove_predicted_entity(&mut self, key: PredictionKey) -> Option<U> {
+        return self.predicted_entity_store.remove(&key);
+    }
+
+    /// Returns the number of Entities received so far from the initial world
+    /// snapshot, and the total number of Entities in the snapshot, if known
+    pub fn get_snapshot_progress(&self) -> Option<(u32, u32)> {
+        if let Some(total) = self.snapshot_total {
+            return Some((self.snapshot_received.min(total), total));
+        }
+        return None;
+    }
+
+    pub fn is_world_synced(&self) -> bool {
+        return self.world_synced;
+    }
+
+    /// Records the visual error of an Entity after reconciliation corrected
+    /// it, which is added to any correction still decaying
+    pub fn record_correction(&mut self, key: LocalEntityKey, error: &[f32]) {
+        if !self.local_entity_store.contains_key(&key) {
+            return;
+        }
+        match self.corrections.get_mut(&key) {
+            Some(correction) => correction.add(error),
+            None => {
+                self.corrections.insert(
+                    key,
+                    Correction::new(error, self.correction_duration, self.correction_curve),
+                );
+            }
+        }
+    }
+
+    pub fn get_correction_offset(&self, key: LocalEntityKey) -> Option<&[f32]> {
+        return self
+            .corrections
+            .get(&key)
+            .map(|correction| correction.get_offset());
+    }
+
+    /// Decays every correction by the given duration, discarding those which
+    /// have reached zero
+    pub fn advance_corrections(&mut self, elapsed: Duration) {
+        self.corrections
+            .retain(|_, correction| !correction.advance(elapsed));
+    }
+
+    /// Produces a report of every Entity received from the Server, along with
+    /// the number of Entity messages not yet emitted as events
+    /// Takes a motion sample of an Entity which has been created or updated,
+    /// and extrapolates its position from it
+    pub fn sample_motion(&mut self, key: LocalEntityKey, motion_sampler: &MotionSampler<U>) {
+        let sample = match self
+            .local_entity_store
+            .get(&key)
+            .and_then(|entity| motion_sampler.sample(entity))
+        {
+            Some(sample) => sample,
+            None => return,
+        };
+        match self.extrapolations.get_mut(&key) {
+            Some(extrapolation) => {
+                extrapolation.set_sample(sample, self.correction_duration, self.correction_curve)
+            }
+            None => {
+                self.extrapolations.insert(
+                    key,
+                    Extrapolation::new(sample, self.extrapolation_model, self.max_extrapolation),
+                );
+            }
+        }
+    }
+
+    /// Gets the extrapolated position of an Entity, if it has been sampled
+    pub fn get_extrapolated_position(&self, key: LocalEntityKey) -> Option<Vec<f32>> {
+        return self
+            .extrapolations
+            .get(&key)
+            .map(|extrapolation| extrapolation.get_position());
+    }
+
+    /// Advances the extrapolation of every Entity by the given duration
+    pub fn advance_extrapolations(&mut self, elapsed: Duration) {
+        for extrapolation in self.extrapolations.values_mut() {
+            extrapolation.advance(elapsed);
+        }
+    }
+
+    pub fn debug_dump<T: EventType>(&self, manifest: &Manifest<T, U>) -> EntityDebugDump {
+        let records = self
+            .local_entity_store
+            .iter()
+            .map(|(key, entity)| {
+                let naia_id = manifest.get_entity_naia_id(&entity.get_type_id());
+                EntityDebugRecord {
+                    local_key: *key,
+                    naia_id,
+                    type_name: manifest.get_entity_type_name(naia_id),
+                    status: EntityDebugStatus::Created,
+                    pending_mask: None,
+                    last_sent_mask: None,
+                }
+            })
+            .collect();
+        let batched_count: usize = self
+            .pending_batches
+            .values()
+            .map(|batch| batch.values().filter(|message| message.is_some()).count())
+            .sum();
+        return EntityDebugDump::new(records, self.queued_incoming_messages.len() + batched_count);
+    }
+
+    /// Resets the state tied to the connection with the previous host, while
+    /// keeping every Entity, whose local keys the new host preserves
+    pub fn reset_for_migration(&mut self) {
+        self.queued_incoming_messages.clear();
+        // the new host's ticks are unrelated to the previous host's
+        for property_ticks in self.property_ticks.values_mut() {
+            *property_ticks = PropertyTicks::new(property_ticks.get_property_count(), None);
+        }
+        self.late_entity_store.clear();
+        self.snapshot_total = None;
+        self.snapshot_received = 0;
+        self.pending_batches.clear();
+        self.batch_boundaries.clear();
+        self.next_batch_index = 0;
+        self.snapshot_chunks.clear();
+        self.bulk_snapshot_applied = false;
+    }
+}
+
+// Reads the local key, if in scope, NaiaId & payload of every Entity in a Room
+// snapshot sent in bulk. Returns None if the snapshot is malformed
+fn read_snapshot(snapshot_bytes: &[u8]) -> Option<Vec<(Option<LocalEntityKey>, u16, Vec<u8>)>> {
+    let mut cursor = Cursor::new(snapshot_bytes);
+    let entity_count: u16 = cursor.read_u16::<BigEndian>().ok()?;
+    let mut local_keys: Vec<Option<LocalEntityKey>> = Vec::new();
+    for _ in 0..entity_count {
+        match cursor.read_u8().ok()? {
+            0 => local_keys.push(None),
+            _ => local_keys.push(Some(LocalEntityKey::read(&mut cursor)?)),
+        }
+    }
+
+    let compressed_start_position = cursor.position() as usize;
+    let entity_bytes = decompress(&snapshot_bytes[compressed_start_position..])?;
+
+    let mut entity_cursor = Cursor::new(&entity_bytes[..]);
+    let mut snapshot_entities = Vec::new();
+    for local_key in local_keys {
+        let naia_id: u16 = entity_cursor.read_u16::<BigEndian>().ok()?;
+        let payload_length: u8 = entity_cursor.read_u8().ok()?;
+        let payload_start_position: usize = entity_cursor.position() as usize;
+        let payload_end_position: usize = payload_start_position + (payload_length as usize);
+        if payload_end_position > entity_bytes.len() {
+            return None;
+        }
+        entity_cursor.set_position(payload_end_position as u64);
+        snapshot_entities.push((
+            local_key,
+            naia_id,
+            entity_bytes[payload_start_position..payload_end_position].to_vec(),
+        ));
+    }
+    return Some(snapshot_entities);
 }
@@ -1,8 +1,12 @@
-use naia_shared::LocalEntityKey;
+use naia_shared::{DespawnReason, LocalEntityKey, PredictionKey, StateMask};
 
 #[derive(Debug, Clone)]
 pub enum ClientEntityMessage {
     Create(LocalEntityKey),
-    Update(LocalEntityKey),
-    Delete(LocalEntityKey),
+    ConfirmPredicted(PredictionKey, LocalEntityKey),
+    Update(LocalEntityKey, StateMask),
+    LateUpdate(LocalEntityKey, u16, StateMask),
+    Delete(LocalEntityKey, Option<DespawnReason>),
+    WorldSynced,
+    TickApplied(u16),
 }
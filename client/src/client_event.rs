@@ -1,4 +1,11 @@
-use naia_shared::{EventType, LocalEntityKey};
+use std::{net::SocketAddr, time::Duration};
+
+use naia_shared::{
+    BlobId, ClientId, CompatibilityReport, ConnectionQuality, DespawnReason, DropReason, EventId,
+    EventType, LocalEntityKey, ManifestDescriptor, PredictionKey, RequestId, StateMask,
+};
+
+use super::{connect_failure::ConnectFailure, request_error::RequestError};
 
 /// An Event that is be emitted by the Client, usually as a result of some
 /// communication with the Server
@@ -10,15 +17,133 @@ pub enum ClientEvent<T: EventType> {
     /// Occurs when the Client has lost connection with the Server, usually as a
     /// result of a timeout
     Disconnection,
+    /// Occurs when the Server has violated the protocol while
+    /// `Config::strict_protocol` is enabled, by sending a packet which would
+    /// otherwise be dropped for the given reason. The Server's packets are
+    /// ignored from then on, & a Disconnection event follows
+    ProtocolViolation(DropReason),
+    /// Occurs when the Server has disconnected the Client, such as when the
+    /// same identity has logged in again elsewhere. The Client will not
+    /// attempt to reconnect
+    Kicked,
+    /// Occurs when the Server has begun shutting down, with the reason it
+    /// gave, such as that it's restarting. Messages the Server sent before
+    /// still arrive, & a Kicked event follows once it has closed
+    ServerShutdown(String),
+    /// Occurs when the Server has changed the rate at which it ticks, with
+    /// the Server tick the change took effect at & the new duration of each
+    /// tick. The ticks returned by `NaiaClient::frame_begin()`, the
+    /// interpolation delay & the scheduling of timed Events have already
+    /// adapted, while the Client's tick is brought back in step with the
+    /// Server's input buffer by time dilation
+    TickRateChanged(u16, Duration),
+    /// Occurs when the quality of the connection to the Server, classified
+    /// from its Round Trip Time, jitter & packet loss by the thresholds in
+    /// `Config::connection_quality_thresholds`, has changed & held for
+    /// `CONNECTION_QUALITY_HOLD`. The first classification is emitted as soon
+    /// as Round Trip Time has been measured
+    ConnectionQualityChanged(ConnectionQuality),
+    /// Occurs when the outgoing queues of the connection to the Server have
+    /// grown to `BackpressureThresholds::congested_depth` undelivered
+    /// messages, such as when the Server has stopped acknowledging packets
+    Congested,
+    /// Occurs when the outgoing queues of a congested connection to the
+    /// Server have drained to `BackpressureThresholds::relieved_depth`
+    /// undelivered messages
+    Relieved,
+    /// Occurs when the Server has handed the Client off to another Server, at
+    /// the given address. The Client keeps its Entities while it connects to
+    /// the other Server, and emits a Connection event once it has
+    Handoff(SocketAddr),
+    /// Occurs when the Client has given up connecting to the Server, whether
+    /// the Server could not be reached or refused the connection. The Client
+    /// will not attempt to connect again
+    ConnectFailed(ConnectFailure),
+    /// The application data attached to a heartbeat the Server sent, set with
+    /// `NaiaServer::set_heartbeat_payload()`
+    HeartbeatPayload(Box<[u8]>),
     /// An Event emitted to the Client from the Server
     Event(T),
+    /// An Event another Client sent to this one with
+    /// `NaiaClient::relay_event()`, relayed by the Server, along with the id
+    /// of the Client which sent it
+    RelayedEvent(ClientId, T),
+    /// Occurs when a Client, with the given public id, connects to the
+    /// Server. Upon connecting, this occurs for every Client already
+    /// connected, and for this Client itself
+    ClientJoined(ClientId),
+    /// Occurs when the Client with the given public id disconnects from the
+    /// Server, after which its id may be reused
+    ClientLeft(ClientId),
+    /// Occurs when the Server has registered new Event or Entity types with
+    /// `NaiaServer::register_event_type()` or
+    /// `NaiaServer::register_entity_type()`, which this Client has registered
+    /// too, as described by the ManifestDescriptor
+    ManifestExtended(ManifestDescriptor),
+    /// Occurs when the Server has registered new Event or Entity types which
+    /// this Client hasn't registered, or has registered differently, such as
+    /// when the Client doesn't have the mod adding them. Messages of those
+    /// types can't be read, & will be dropped
+    ManifestMismatch(CompatibilityReport),
+    /// Occurs when an Event sent with `NaiaClient::send_event()` has been
+    /// confirmed as delivered to the Server
+    DeliveryConfirmed(EventId),
+    /// Occurs when an Event sent with `NaiaClient::send_event_with_ttl()` was
+    /// dropped, because its time-to-live ran out before it could be sent
+    EventExpired(EventId),
+    /// An Event which the Server scheduled for a specific Server tick, emitted
+    /// once that tick has been reached
+    TimedEvent(u16, T),
+    /// The Server's response to a request previously sent with
+    /// `NaiaClient::request()`
+    Response(RequestId, T),
+    /// A request previously sent with `NaiaClient::request()` has either timed
+    /// out or been rejected by the Server
+    RequestFailed(RequestId, RequestError),
+    /// A raw payload sent by the Server with `NaiaServer::send_raw()`
+    RawPayload(Box<[u8]>),
+    /// Another chunk of a blob sent by the Server has been received, as (blob
+    /// id, chunks received, total chunks)
+    BlobProgress(BlobId, u16, u16),
+    /// A blob sent by the Server has been completely received
+    BlobReceived(BlobId, Box<[u8]>),
+    /// A blob sent with `NaiaClient::send_blob()` has been completely
+    /// delivered to the Server
+    BlobDelivered(BlobId),
+    /// An Event which the Server sent targeted at the Entity with the given
+    /// key, with `NaiaServer::queue_entity_event()`. Events targeted at an
+    /// Entity which is no longer in scope are dropped
+    EntityEvent(LocalEntityKey, T),
     /// Occurs when an Entity on the Server has come into scope for the Client
     CreateEntity(LocalEntityKey),
+    /// Occurs when an Entity on the Server, which the Client had spawned in
+    /// prediction with `NaiaClient::spawn_predicted_entity()`, has come into
+    /// scope. The predicted Entity has been discarded, and should be replaced
+    /// by the Server's authoritative Entity
+    ConfirmPredictedEntity(PredictionKey, LocalEntityKey),
     /// Occurs when an Entity has had a state change on the Server while in
-    /// scope for the Client
-    UpdateEntity(LocalEntityKey),
-    /// Occurs when an Entity on the Server has left the Client's scope
-    DeleteEntity(LocalEntityKey),
+    /// scope for the Client. The StateMask has a bit set for the index of
+    /// every Property which changed, in the order the Properties are declared
+    /// on the Entity
+    UpdateEntity(LocalEntityKey, StateMask),
+    /// Occurs when an Update of an Entity, sent at the given Server tick,
+    /// arrived after a newer Update of some of its Properties, & the Entity's
+    /// type has the `LateUpdatePolicy::DeliverToApp` policy. The Update isn't
+    /// applied to the Entity, but the state it would have given the Entity is
+    /// retrievable with `NaiaClient::get_late_entity()`
+    LateUpdateEntity(LocalEntityKey, u16, StateMask),
+    /// Occurs when an Entity on the Server has left the Client's scope. Contains
+    /// the reason code the Server gave, if the Entity was deregistered with
+    /// `NaiaServer::deregister_entity_with_reason()`
+    DeleteEntity(LocalEntityKey, Option<DespawnReason>),
+    /// Occurs once every Entity in the initial world snapshot sent by the
+    /// Server has been received
+    WorldSynced,
+    /// Occurs when `Config::atomic_entity_ticks` is enabled, once every Entity
+    /// message sent by the Server during the given Server tick has been
+    /// applied at once. The Entity events emitted since the previous
+    /// TickApplied all belong to that tick
+    TickApplied(u16),
     /// The Client has no new event from the Server
     None,
 }
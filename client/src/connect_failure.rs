@@ -0,0 +1,17 @@
+use naia_shared::CompatibilityReport;
+
+/// The reason the Client has given up connecting to the Server
+#[derive(Debug, Clone)]
+pub enum ConnectFailure {
+    /// The Server did not accept the connection within
+    /// `Config::connect_timeout`, on the first attempt or any of the
+    /// `Config::connect_retries` which followed
+    TimedOut,
+    /// The Server refused the connection, because the auth Event was
+    /// rejected, or identifies a Client which is already connected, or the
+    /// Server is shutting down
+    Refused,
+    /// The Server refused the connection because its Manifest is incompatible
+    /// with the Client's. The report describes every difference found
+    ManifestMismatch(CompatibilityReport),
+}
@@ -0,0 +1,22 @@
+use std::net::SocketAddr;
+
+use log::warn;
+use naia_client_socket::{ClientSocket, Config as SocketConfig};
+use naia_shared::{find_my_ip_address, to_canonical_address, AddressFamily};
+
+/// Connects a socket to the Server at the given address. An IPv4-mapped IPv6
+/// address is connected to over IPv4, as the socket binds to the local
+/// address found with `find_my_ip_address()`, which only reaches Servers of
+/// its own address family
+pub fn connect_socket(server_address: SocketAddr) -> ClientSocket {
+    let server_address = to_canonical_address(server_address);
+    if let Some(local_ip) = find_my_ip_address() {
+        if AddressFamily::of(&local_ip) != AddressFamily::of(&server_address.ip()) {
+            warn!(
+                "local address {} can't reach server at {}, which is of another address family",
+                local_ip, server_address
+            );
+        }
+    }
+    return ClientSocket::connect(server_address, Some(SocketConfig::default()));
+}
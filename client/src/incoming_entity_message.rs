@@ -0,0 +1,27 @@
+use naia_shared::{DespawnReason, EntityType, LocalEntityKey, PredictionKey, StateMask};
+
+/// An Entity message which has been read from an incoming packet, but not yet
+/// applied to the Client's Entities
+#[derive(Debug)]
+pub enum IncomingEntityMessage<T: EntityType> {
+    Create(LocalEntityKey, Option<PredictionKey>, T),
+    Update(LocalEntityKey, StateMask, Box<[u8]>),
+    Delete(LocalEntityKey, Option<DespawnReason>),
+    SnapshotBegin(u32),
+    SnapshotComplete,
+    SnapshotChunk(u16, u16, Box<[u8]>),
+}
+
+impl<T: EntityType> IncomingEntityMessage<T> {
+    /// The order in which messages of the same tick batch are applied
+    pub fn apply_order(&self) -> u8 {
+        match self {
+            IncomingEntityMessage::SnapshotBegin(_) => 0,
+            IncomingEntityMessage::Create(_, _, _) => 1,
+            IncomingEntityMessage::SnapshotChunk(_, _, _) => 1,
+            IncomingEntityMessage::Update(_, _, _) => 2,
+            IncomingEntityMessage::Delete(_, _) => 3,
+            IncomingEntityMessage::SnapshotComplete => 4,
+        }
+    }
+}
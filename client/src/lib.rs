@@ -18,11 +18,38 @@ mod client_connection_state;
 mod client_entity_manager;
 mod client_entity_message;
 mod client_event;
-mod error;
+mod connect_failure;
+mod connect_socket;
+mod incoming_entity_message;
+mod multi_client;
 mod naia_client;
+mod request_error;
 mod server_connection;
+mod server_query;
 
 pub use client_event::ClientEvent;
+pub use connect_failure::ConnectFailure;
+pub use multi_client::{MultiClient, ServerKey};
 pub use naia_client::NaiaClient;
 pub use naia_client_socket::Packet;
-pub use naia_shared::{find_my_ip_address, Config};
+pub use naia_shared::{
+    diff_entity_dumps, find_my_ip_address, find_my_ip_address_in, resolve_address,
+    to_canonical_address, AckLatencyHistogram, AddressFamily, BackpressureThresholds, BlobId,
+    Capabilities, ClientId, ClientRosterChange, CollectionChange, CollectionRevision,
+    CompatibilityReport, Config, ConnectionQuality, ConnectionQualityThresholds, ConnectionStats,
+    CorrectionCurve, CustomSection, DespawnReason, DropReason, DynamicEvent, DynamicValue,
+    EntityDebugDump, EntityDebugRecord, EntityDebugStatus, EntityDiff, EventId, EventQueueStats,
+    ExtrapolationModel, LateUpdatePolicy, LateUpdateStats, LinkConditionerConfig, ManagerType,
+    MotionSample, NaiaError, PacketFraming, PacketFramingFactory, PredictionKey, PrefabId,
+    ProtocolDocFormat, QualityThreshold, RandomSource, ReplicatedAnimation, ReplicatedCollection,
+    ReplicatedTransform, RequestId, RigidBodyState, RollbackHistory, RttStats, SectionFraming,
+    ServerListPage, ServerStatus, StateMask, TransformAxes, ACK_LATENCY_BUCKET_COUNT,
+    ACK_LATENCY_BUCKET_WIDTH, CONNECTION_QUALITY_HOLD, DROP_WARNING_INTERVAL,
+    INTERPOLATION_DELAY_HOLD, MAX_CUSTOM_SECTION_SIZE, MAX_EVENT_SIZE, MAX_HEARTBEAT_PAYLOAD_SIZE,
+    MAX_RAW_PAYLOAD_SIZE, MAX_REGISTERED_STATUS_SIZE, MAX_TICKS_PER_FRAME,
+    MAX_UNCONNECTED_DATA_SIZE, MTU_SIZE, REPLAY_WINDOW_SIZE,
+};
+#[cfg(feature = "rapier")]
+pub use naia_shared::{read_rigid_body_state, write_rigid_body_state, RapierBridge};
+pub use request_error::RequestError;
+pub use server_query::{ServerQuery, ServerQueryResponse, SERVER_QUERY_TIMEOUT};
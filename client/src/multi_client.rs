@@ -0,0 +1,308 @@
+use std::{net::SocketAddr, rc::Rc};
+
+use naia_shared::{
+    Config, EntityBuilder, EntityType, EventBuilder, EventType, Manifest, NaiaError,
+};
+
+use super::{client_event::ClientEvent, naia_client::NaiaClient};
+
+/// Identifies one of the Servers a MultiClient is connected to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ServerKey(u32);
+
+/// Maintains connections to several Servers at once, such as a gameplay
+/// Server & a chat Server, which all share one Manifest. The events of every
+/// connection are received as a single stream, each tagged with the key of
+/// the Server it came from. Each connection has a socket, Config & tick clock
+/// of its own, as a client socket only exchanges packets with the Server it
+/// was connected to
+#[derive(Debug)]
+pub struct MultiClient<T: EventType, U: EntityType> {
+    manifest: Rc<Manifest<T, U>>,
+    next_server_key: u32,
+    clients: Vec<(ServerKey, NaiaClient<T, U>)>,
+    // the index of the connection polled first by the next call to receive()
+    next_polled: usize,
+}
+
+impl<T: EventType, U: EntityType> MultiClient<T, U> {
+    /// Create a new MultiClient, given the Manifest shared by every Server it
+    /// connects to
+    pub fn new(manifest: Manifest<T, U>) -> Self {
+        return MultiClient::new_with_shared_manifest(Rc::new(manifest));
+    }
+
+    /// Create a new MultiClient, given a Manifest which is also shared with a
+    /// Server in the same process
+    pub fn new_with_shared_manifest(manifest: Rc<Manifest<T, U>>) -> Self {
+        MultiClient {
+            manifest,
+            next_server_key: 0,
+            clients: Vec::new(),
+            next_polled: 0,
+        }
+    }
+
+    /// Starts connecting to another Server, given its address, an optional
+    /// Config, and an optional Authentication event, returning the key of the
+    /// Server
+    pub fn connect(
+        &mut self,
+        server_address: SocketAddr,
+        config: Option<Config>,
+        auth: Option<T>,
+    ) -> ServerKey {
+        let server_key = ServerKey(self.next_server_key);
+        self.next_server_key = self.next_server_key.wrapping_add(1);
+        let client = NaiaClient::new_with_shared_manifest(
+            server_address,
+            self.manifest.clone(),
+            config,
+            auth,
+        );
+        self.clients.push((server_key, client));
+        return server_key;
+    }
+
+    /// Closes the connection to the Server associated with the given key.
+    /// Returns the Client of the connection, or None if there is no such
+    /// Server
+    pub fn remove(&mut self, server_key: &ServerKey) -> Option<NaiaClient<T, U>> {
+        let index = self.clients.iter().position(|(key, _)| key == server_key)?;
+        return Some(self.clients.remove(index).1);
+    }
+
+    /// Must be called regularly, performs updates to the connection to every
+    /// Server, and retrieves the next event any of them sent, along with the
+    /// key of the Server. Returns None once no connection has a new event.
+    /// The connections are polled in turn, starting after the one which gave
+    /// the last event, so that a busy Server can't starve the others
+    pub fn receive(&mut self) -> Option<(ServerKey, Result<ClientEvent<T>, NaiaError>)> {
+        for _ in 0..self.clients.len() {
+            let index = self.next_polled % self.clients.len();
+            self.next_polled = index + 1;
+            let (server_key, client) = &mut self.clients[index];
+            match client.receive() {
+                Ok(ClientEvent::None) => continue,
+                result => return Some((*server_key, result)),
+            }
+        }
+        return None;
+    }
+
+    /// Registers an Event type in the Manifest shared by every Server under
+    /// the given NaiaId after startup, as described in
+    /// `NaiaClient::register_event_type()`. Returns an error if the NaiaId or
+    /// the type has already been registered, or if the Manifest is shared
+    /// with a Server in the same process
+    pub fn register_event_type(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) -> Result<(), NaiaError> {
+        return self.with_manifest_mut(|manifest| {
+            manifest.try_register_event_with_id(naia_id, event_builder)
+        });
+    }
+
+    /// Registers an Entity type in the Manifest shared by every Server under
+    /// the given NaiaId after startup, as described in
+    /// `NaiaClient::register_entity_type()`. Returns an error if the NaiaId or
+    /// the type has already been registered, or if the Manifest is shared
+    /// with a Server in the same process
+    pub fn register_entity_type(
+        &mut self,
+        naia_id: u16,
+        entity_builder: Box<dyn EntityBuilder<U>>,
+    ) -> Result<(), NaiaError> {
+        return self.with_manifest_mut(|manifest| {
+            manifest.try_register_entity_with_id(naia_id, entity_builder)
+        });
+    }
+
+    // Every connection holds the shared Manifest, so each is handed an empty
+    // one while the shared Manifest is changed, & the shared one back after
+    fn with_manifest_mut(
+        &mut self,
+        change: impl FnOnce(&mut Manifest<T, U>) -> Result<(), NaiaError>,
+    ) -> Result<(), NaiaError> {
+        let placeholder = Rc::new(Manifest::new());
+        for (_, client) in self.clients.iter_mut() {
+            client.replace_manifest(placeholder.clone());
+        }
+        let result = match Rc::get_mut(&mut self.manifest) {
+            Some(manifest) => change(manifest),
+            None => Err(NaiaError::ManifestShared),
+        };
+        for (_, client) in self.clients.iter_mut() {
+            client.replace_manifest(self.manifest.clone());
+        }
+        return result;
+    }
+
+    /// Gets the Client of the connection to the Server associated with the
+    /// given key
+    pub fn get(&self, server_key: &ServerKey) -> Option<&NaiaClient<T, U>> {
+        return self
+            .clients
+            .iter()
+            .find(|(key, _)| key == server_key)
+            .map(|(_, client)| client);
+    }
+
+    /// Gets the Client of the connection to the Server associated with the
+    /// given key, to send Events or read Entities
+    pub fn get_mut(&mut self, server_key: &ServerKey) -> Option<&mut NaiaClient<T, U>> {
+        return self
+            .clients
+            .iter_mut()
+            .find(|(key, _)| key == server_key)
+            .map(|(_, client)| client);
+    }
+
+    /// Gets the keys of every Server, in the order they were connected to
+    pub fn server_keys(&self) -> Vec<ServerKey> {
+        return self.clients.iter().map(|(key, _)| *key).collect();
+    }
+
+    /// Gets the Manifest shared by every Server
+    pub fn get_manifest(&self) -> &Manifest<T, U> {
+        return &self.manifest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use naia_example_shared::{manifest_load, AuthEvent, ExampleEntity, ExampleEvent, StringEvent};
+    use naia_server::{find_my_ip_address, Config, NaiaServer, ServerEvent, UserKey};
+    use naia_shared::{Manifest, NaiaError};
+
+    use super::{super::client_event::ClientEvent, MultiClient, ServerKey};
+
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.tick_interval = Duration::from_millis(10);
+        config.heartbeat_interval = Duration::from_millis(50);
+        config.disconnection_timeout_duration = Duration::from_millis(500);
+        return config;
+    }
+
+    // Polls the Server until its next tick, greeting each Client which
+    // connects with the given message, then sends what was queued. Returns the number of Clients which
+    // have disconnected
+    async fn poll_server(
+        server: &mut NaiaServer<ExampleEvent, ExampleEntity>,
+        greeting: &str,
+        users: &mut Vec<UserKey>,
+    ) -> usize {
+        let mut disconnections = 0;
+        loop {
+            match server.receive().await {
+                Ok(ServerEvent::Connection(user_key)) => {
+                    server
+                        .queue_event(&user_key, &StringEvent::new(greeting.to_string()))
+                        .unwrap();
+                    users.push(user_key);
+                }
+                Ok(ServerEvent::Disconnection(_, _)) => disconnections += 1,
+                Ok(ServerEvent::Tick) => {
+                    server.tick().await.unwrap();
+                    return disconnections;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Drains the MultiClient, collecting the messages of the Events received
+    // from each Server
+    fn poll_client(
+        client: &mut MultiClient<ExampleEvent, ExampleEntity>,
+        messages: &mut HashMap<ServerKey, Vec<String>>,
+    ) {
+        while let Some((server_key, result)) = client.receive() {
+            if let Ok(ClientEvent::Event(ExampleEvent::StringEvent(event))) = result {
+                messages
+                    .entry(server_key)
+                    .or_default()
+                    .push(event.message.get().clone());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn receives_the_events_of_every_server_tagged_by_key() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address_a = SocketAddr::new(ip_address, 14291);
+        let address_b = SocketAddr::new(ip_address, 14292);
+        let mut server_a = NaiaServer::new(address_a, manifest_load(), Some(test_config())).await;
+        let mut server_b = NaiaServer::new(address_b, manifest_load(), Some(test_config())).await;
+        let mut users_a = Vec::new();
+        let mut users_b = Vec::new();
+
+        let mut client = MultiClient::new(manifest_load());
+        let key_a = client.connect(address_a, Some(test_config()), None);
+        let key_b = client.connect(address_b, Some(test_config()), None);
+
+        let mut messages = HashMap::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while messages.len() < 2 && Instant::now() < deadline {
+            poll_server(&mut server_a, "from a", &mut users_a).await;
+            poll_server(&mut server_b, "from b", &mut users_b).await;
+            poll_client(&mut client, &mut messages);
+        }
+        assert!(messages[&key_a] == vec!["from a".to_string()]);
+        assert!(messages[&key_b] == vec!["from b".to_string()]);
+
+        // disconnecting from one Server leaves the other connected
+        client.remove(&key_b);
+        assert!(client.server_keys() == vec![key_a]);
+        server_a
+            .queue_event(&users_a[0], &StringEvent::new("still from a".to_string()))
+            .unwrap();
+        let mut disconnections = 0;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while (disconnections == 0 || messages[&key_a].len() < 2) && Instant::now() < deadline {
+            assert!(poll_server(&mut server_a, "from a", &mut users_a).await == 0);
+            disconnections += poll_server(&mut server_b, "from b", &mut users_b).await;
+            poll_client(&mut client, &mut messages);
+        }
+        assert!(disconnections == 1);
+        assert!(messages[&key_a] == vec!["from a".to_string(), "still from a".to_string()]);
+        assert!(messages[&key_b].len() == 1);
+    }
+
+    #[test]
+    fn registers_types_in_the_manifest_shared_by_every_server() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let mut manifest = Manifest::<ExampleEvent, ExampleEntity>::new();
+        manifest.register_auth_event_with_id(0, AuthEvent::get_builder());
+        let mut client = MultiClient::new(manifest);
+        client.connect(SocketAddr::new(ip_address, 14293), None, None);
+        client.connect(SocketAddr::new(ip_address, 14294), None, None);
+
+        assert!(client
+            .register_event_type(1, StringEvent::get_builder())
+            .is_ok());
+        assert!(client.get_manifest().get_event_descriptor(1).is_some());
+        assert!(client
+            .register_event_type(2, StringEvent::get_builder())
+            .is_err());
+
+        // a Manifest shared with a Server can't be changed
+        let manifest = Rc::new(manifest_load());
+        let mut client = MultiClient::new_with_shared_manifest(manifest.clone());
+        client.connect(SocketAddr::new(ip_address, 14295), None, None);
+        assert!(matches!(
+            client.register_event_type(2, AuthEvent::get_builder()),
+            Err(NaiaError::ManifestShared)
+        ));
+    }
+}
@@ -1,15 +1,23 @@
-use std::net::SocketAddr;
+use std::{any::TypeId, collections::HashMap, net::SocketAddr, rc::Rc, time::Duration};
 
-use byteorder::{BigEndian, WriteBytesExt};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
-use naia_client_socket::{ClientSocket, Config as SocketConfig, MessageSender, SocketEvent};
+use log::warn;
+use naia_client_socket::{ClientSocket, MessageSender, SocketEvent};
 pub use naia_shared::{
-    Config, EntityType, Event, EventType, LocalEntityKey, ManagerType, Manifest, PacketReader,
-    PacketType, PacketWriter, Timer, Timestamp,
+    time_dilation_scale, AckLatencyHistogram, BlobId, BlobMessage, Capabilities, ClientId,
+    ClientRosterChange, Config, Congestion, ConnectionQuality, ConnectionStats, CustomSection,
+    CustomSections, DropReason, EntityBuilder, EntityDebugDump, EntityType, Event, EventBuilder,
+    EventId, EventMiddleware, EventQueueStats, EventType, InterpolationDelay, LateUpdatePolicy,
+    LateUpdateStats, LinkConditioner, LocalEntityKey, ManagerType, Manifest, ManifestDescriptor,
+    MotionSample, MotionSampler, NaiaError, PacketFramingFactory, PacketReader, PacketType,
+    PacketWriter, PredictionKey, RequestId, RpcMessage, RttStats, TickAccumulator, Timer,
+    Timestamp,
 };
 
 use super::{
-    client_entity_message::ClientEntityMessage, client_event::ClientEvent, error::NaiaClientError,
+    client_entity_message::ClientEntityMessage, client_event::ClientEvent,
+    connect_failure::ConnectFailure, connect_socket::connect_socket, request_error::RequestError,
     server_connection::ServerConnection, Packet,
 };
 use crate::client_connection_state::{
@@ -20,7 +28,7 @@ use crate::client_connection_state::{
 /// entities that are synced with the server
 #[derive(Debug)]
 pub struct NaiaClient<T: EventType, U: EntityType> {
-    manifest: Manifest<T, U>,
+    manifest: Rc<Manifest<T, U>>,
     server_address: SocketAddr,
     config: Config,
     socket: ClientSocket,
@@ -30,7 +38,24 @@ pub struct NaiaClient<T: EventType, U: EntityType> {
     pre_connection_digest: Option<Box<[u8]>>,
     handshake_timer: Timer,
     connection_state: ClientConnectionState,
+    connect_timer: Timer,
+    connect_retry_timer: Option<Timer>,
+    connect_retries_made: u16,
     auth_event: Option<T>,
+    loading: bool,
+    next_request_id: RequestId,
+    pending_requests: HashMap<RequestId, Timer>,
+    migration_token: Option<u64>,
+    migrated_connection: Option<ServerConnection<T, U>>,
+    link_conditioner: Option<LinkConditioner>,
+    tick_accumulator: TickAccumulator,
+    interpolation_delay: InterpolationDelay,
+    custom_sections: CustomSections<SocketAddr>,
+    packet_framing: Option<PacketFramingFactory>,
+    incoming_event_middleware: EventMiddleware<SocketAddr, T>,
+    outgoing_event_middleware: EventMiddleware<SocketAddr, T>,
+    motion_sampler: Option<MotionSampler<U>>,
+    late_update_policies: HashMap<TypeId, LateUpdatePolicy>,
 }
 
 impl<T: EventType, U: EntityType> NaiaClient<T, U> {
@@ -41,6 +66,23 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
         manifest: Manifest<T, U>,
         config: Option<Config>,
         auth: Option<T>,
+    ) -> Self {
+        return NaiaClient::new_with_shared_manifest(
+            server_address,
+            Rc::new(manifest),
+            config,
+            auth,
+        );
+    }
+
+    /// Create a new client, given the server's address, a manifest shared
+    /// with other clients, an optional Config, and an optional Authentication
+    /// event. Used by MultiClient, to connect to several Servers at once
+    pub fn new_with_shared_manifest(
+        server_address: SocketAddr,
+        manifest: Rc<Manifest<T, U>>,
+        config: Option<Config>,
+        auth: Option<T>,
     ) -> Self {
         let mut config = match config {
             Some(config) => config,
@@ -48,15 +90,26 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
         };
         config.heartbeat_interval /= 2;
 
-        let socket_config = SocketConfig::default();
-        let mut client_socket = ClientSocket::connect(server_address, Some(socket_config));
+        if let Some(auth_event) = &auth {
+            let naia_id = manifest.get_event_naia_id(&auth_event.get_type_id());
+            if !manifest.is_auth_event(naia_id) {
+                warn!("auth event has not been registered with Manifest::register_auth_event(), and will be rejected by the Server");
+            }
+        }
+
+        let mut client_socket = connect_socket(server_address);
+        let link_conditioner = config.link_conditioner.as_ref().map(LinkConditioner::new);
+        let tick_accumulator = TickAccumulator::new(config.tick_interval);
+        let interpolation_delay =
+            InterpolationDelay::new(config.tick_interval, config.interpolation_delay_hysteresis);
 
         let mut handshake_timer = Timer::new(config.send_handshake_interval);
         handshake_timer.ring_manual();
         let message_sender = client_socket.get_sender();
+        let connect_timer = Timer::new(config.connect_timeout);
 
         NaiaClient {
-            server_address,
+            server_address: client_socket.server_address(),
             manifest,
             socket: client_socket,
             sender: message_sender,
@@ -66,59 +119,247 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
             pre_connection_timestamp: None,
             pre_connection_digest: None,
             connection_state: AwaitingChallengeResponse,
+            connect_timer,
+            connect_retry_timer: None,
+            connect_retries_made: 0,
             auth_event: auth,
+            loading: false,
+            next_request_id: 0,
+            pending_requests: HashMap::new(),
+            migration_token: None,
+            migrated_connection: None,
+            link_conditioner,
+            tick_accumulator,
+            interpolation_delay,
+            custom_sections: CustomSections::new(),
+            packet_framing: None,
+            incoming_event_middleware: EventMiddleware::new(),
+            outgoing_event_middleware: EventMiddleware::new(),
+            motion_sampler: None,
+            late_update_policies: HashMap::new(),
         }
     }
 
     /// Must be called regularly, performs updates to the connection, and
     /// retrieves event/entity updates sent by the Server
-    pub fn receive(&mut self) -> Result<ClientEvent<T>, NaiaClientError> {
+    pub fn receive(&mut self) -> Result<ClientEvent<T>, NaiaError> {
         // send handshakes, send heartbeats, timeout if need be
         match &mut self.server_connection {
             Some(connection) => {
-                if connection.should_drop() {
+                if let Some(reason) = connection.take_protocol_violation() {
+                    return Ok(ClientEvent::ProtocolViolation(reason));
+                }
+                if connection.should_drop() || connection.has_protocol_violation() {
                     self.server_connection = None;
-                    self.pre_connection_timestamp = None;
-                    self.pre_connection_digest = None;
-                    self.connection_state = AwaitingChallengeResponse;
+                    self.start_connecting();
+                    self.pending_requests.clear();
                     return Ok(ClientEvent::Disconnection);
                 }
                 if connection.should_send_heartbeat() {
-                    NaiaClient::internal_send_with_connection(
+                    NaiaClient::internal_send_heartbeat(
                         &mut self.sender,
                         connection,
-                        PacketType::Heartbeat,
-                        Packet::empty(),
-                    );
+                        NaiaClient::<T, U>::heartbeat_type(self.loading),
+                    )?;
+                }
+                // send a packet, unless we're loading, in which case sending data would
+                // signal to the Server that we're ready
+                if !self.loading {
+                    if let Some(payload) =
+                        connection.get_outgoing_packet(&self.manifest, &mut self.custom_sections)
+                    {
+                        if let Err(error) = self.sender.send(Packet::new_raw(payload)) {
+                            return Err(NaiaError::Wrapped(error));
+                        }
+                        connection.mark_sent();
+                    }
+                }
+                // classify connection quality
+                if let Some(quality) = connection.update_quality() {
+                    return Ok(ClientEvent::ConnectionQualityChanged(quality));
+                }
+                // measure outgoing queue depth
+                match connection.update_backpressure() {
+                    Some(Congestion::Congested) => return Ok(ClientEvent::Congested),
+                    Some(Congestion::Relieved) => return Ok(ClientEvent::Relieved),
+                    None => {}
                 }
-                // send a packet
-                if let Some(payload) = connection.get_outgoing_packet(&self.manifest) {
-                    self.sender
-                        .send(Packet::new_raw(payload))
-                        .expect("send failed!");
-                    connection.mark_sent();
+                // apply packets held back to restore their order, which are due
+                connection.process_reordered_data(&self.manifest, &mut self.custom_sections);
+                // receive change to the connected clients, before any event
+                // relayed from a client which just joined
+                match connection.get_roster_change() {
+                    Some(ClientRosterChange::Joined(client_id)) => {
+                        return Ok(ClientEvent::ClientJoined(client_id));
+                    }
+                    Some(ClientRosterChange::Left(client_id)) => {
+                        return Ok(ClientEvent::ClientLeft(client_id));
+                    }
+                    None => {}
+                }
+                // the Server is shutting down
+                if let Some(reason) = connection.get_shutdown() {
+                    return Ok(ClientEvent::ServerShutdown(reason));
+                }
+                // the Server has changed its tick rate
+                if let Some(change) = connection.get_tick_rate_change() {
+                    self.tick_accumulator
+                        .set_tick_interval(change.tick_interval);
+                    self.interpolation_delay
+                        .set_tick_interval(change.tick_interval);
+                    return Ok(ClientEvent::TickRateChanged(
+                        change.tick,
+                        change.tick_interval,
+                    ));
+                }
+                // receive types registered by the Server since connecting
+                if let Some(delta) = connection.get_manifest_delta() {
+                    if let Some(report) = self.manifest.get_descriptor().compare_delta(&delta) {
+                        return Ok(ClientEvent::ManifestMismatch(report));
+                    }
+                    // describe the types as registered locally, with their names
+                    let local_delta = ManifestDescriptor {
+                        events: delta
+                            .events
+                            .iter()
+                            .filter_map(|event| self.manifest.get_event_descriptor(event.naia_id))
+                            .collect(),
+                        entities: delta
+                            .entities
+                            .iter()
+                            .filter_map(|entity| {
+                                self.manifest.get_entity_descriptor(entity.naia_id)
+                            })
+                            .collect(),
+                    };
+                    return Ok(ClientEvent::ManifestExtended(local_delta));
                 }
                 // receive event
-                if let Some(event) = connection.get_incoming_event() {
-                    return Ok(ClientEvent::Event(event));
+                while let Some(event) = connection.get_incoming_event() {
+                    if let Some(event) = self
+                        .incoming_event_middleware
+                        .apply(&self.server_address, event)
+                    {
+                        return Ok(ClientEvent::Event(event));
+                    }
+                }
+                // receive event relayed from another client
+                if let Some((sender_id, event)) = connection.get_incoming_relay_event() {
+                    return Ok(ClientEvent::RelayedEvent(sender_id, event));
+                }
+                // receive delivery receipt
+                if let Some(event_id) = connection.get_delivered_event_id() {
+                    return Ok(ClientEvent::DeliveryConfirmed(event_id));
+                }
+                // receive expiration notice
+                if let Some(event_id) = connection.get_expired_event_id() {
+                    return Ok(ClientEvent::EventExpired(event_id));
+                }
+                // receive raw payload
+                if let Some(payload) = connection.get_incoming_raw_payload() {
+                    return Ok(ClientEvent::RawPayload(payload));
+                }
+                // receive blob transfer notification
+                if let Some(message) = connection.get_blob_message() {
+                    match message {
+                        BlobMessage::Progress(blob_id, received_chunks, total_chunks) => {
+                            return Ok(ClientEvent::BlobProgress(
+                                blob_id,
+                                received_chunks,
+                                total_chunks,
+                            ));
+                        }
+                        BlobMessage::Received(blob_id, data) => {
+                            return Ok(ClientEvent::BlobReceived(blob_id, data));
+                        }
+                        BlobMessage::Delivered(blob_id) => {
+                            return Ok(ClientEvent::BlobDelivered(blob_id));
+                        }
+                    }
+                }
+                // receive timed event which has come due
+                if let Some((tick, event)) = connection.get_due_timed_event() {
+                    return Ok(ClientEvent::TimedEvent(tick, event));
+                }
+                // receive rpc message
+                while let Some(rpc) = connection.get_incoming_rpc() {
+                    // responses to requests which have already timed out are discarded
+                    if self.pending_requests.remove(&rpc.request_id()).is_none() {
+                        continue;
+                    }
+                    match rpc {
+                        RpcMessage::Response(request_id, event) => {
+                            return Ok(ClientEvent::Response(request_id, event));
+                        }
+                        RpcMessage::Error(request_id) => {
+                            return Ok(ClientEvent::RequestFailed(
+                                request_id,
+                                RequestError::Rejected,
+                            ));
+                        }
+                        RpcMessage::Request(_, _) => {}
+                    }
+                }
+                // time out requests
+                let mut timed_out_request: Option<RequestId> = None;
+                for (request_id, timer) in self.pending_requests.iter() {
+                    if timer.ringing() {
+                        timed_out_request = Some(*request_id);
+                        break;
+                    }
+                }
+                if let Some(request_id) = timed_out_request {
+                    self.pending_requests.remove(&request_id);
+                    return Ok(ClientEvent::RequestFailed(
+                        request_id,
+                        RequestError::Timeout,
+                    ));
                 }
                 // receive entity message
                 if let Some(message) = connection.get_incoming_entity_message() {
                     match message {
                         ClientEntityMessage::Create(local_key) => {
+                            if let Some(motion_sampler) = &self.motion_sampler {
+                                connection.sample_motion(local_key, motion_sampler);
+                            }
                             return Ok(ClientEvent::CreateEntity(local_key));
                         }
-                        ClientEntityMessage::Delete(local_key) => {
-                            return Ok(ClientEvent::DeleteEntity(local_key));
+                        ClientEntityMessage::ConfirmPredicted(prediction_key, local_key) => {
+                            return Ok(ClientEvent::ConfirmPredictedEntity(
+                                prediction_key,
+                                local_key,
+                            ));
+                        }
+                        ClientEntityMessage::Delete(local_key, reason) => {
+                            return Ok(ClientEvent::DeleteEntity(local_key, reason));
                         }
-                        ClientEntityMessage::Update(local_key) => {
-                            return Ok(ClientEvent::UpdateEntity(local_key));
+                        ClientEntityMessage::Update(local_key, state_mask) => {
+                            if let Some(motion_sampler) = &self.motion_sampler {
+                                connection.sample_motion(local_key, motion_sampler);
+                            }
+                            return Ok(ClientEvent::UpdateEntity(local_key, state_mask));
+                        }
+                        ClientEntityMessage::LateUpdate(local_key, tick, state_mask) => {
+                            return Ok(ClientEvent::LateUpdateEntity(local_key, tick, state_mask));
+                        }
+                        ClientEntityMessage::WorldSynced => {
+                            return Ok(ClientEvent::WorldSynced);
+                        }
+                        ClientEntityMessage::TickApplied(tick) => {
+                            return Ok(ClientEvent::TickApplied(tick));
                         }
                     }
                 }
+                // receive entity event, after the messages which create entities
+                if let Some((local_key, event)) = connection.get_incoming_entity_event() {
+                    return Ok(ClientEvent::EntityEvent(local_key, event));
+                }
             }
             None => {
-                if self.handshake_timer.ringing() {
+                if self.update_connect_attempt() {
+                    return Ok(ClientEvent::ConnectFailed(ConnectFailure::TimedOut));
+                }
+                if self.connect_retry_timer.is_none() && self.handshake_timer.ringing() {
                     match self.connection_state {
                         ClientConnectionState::AwaitingChallengeResponse => {
                             if self.pre_connection_timestamp.is_none() {
@@ -134,7 +375,7 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
                                 &mut self.sender,
                                 PacketType::ClientChallengeRequest,
                                 Packet::new(timestamp_bytes),
-                            );
+                            )?;
                         }
                         ClientConnectionState::AwaitingConnectResponse => {
                             // write timestamp & digest into payload
@@ -147,6 +388,22 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
                             {
                                 payload_bytes.push(*digest_byte);
                             }
+                            // write manifest descriptor, so the Server can verify compatibility
+                            self.manifest.get_descriptor().write(&mut payload_bytes);
+                            // write migration token, if migrating from a previous host
+                            match self.migration_token {
+                                Some(migration_token) => {
+                                    payload_bytes.write_u8(1).unwrap();
+                                    payload_bytes
+                                        .write_u64::<BigEndian>(migration_token)
+                                        .unwrap();
+                                }
+                                None => {
+                                    payload_bytes.write_u8(0).unwrap();
+                                }
+                            }
+                            // offer the optional features which are enabled
+                            Capabilities::offered(&self.config).write(&mut payload_bytes);
                             // write auth event object if there is one
                             if let Some(auth_event) = &mut self.auth_event {
                                 let type_id = auth_event.get_type_id();
@@ -158,7 +415,7 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
                                 &mut self.sender,
                                 PacketType::ClientConnectRequest,
                                 Packet::new(payload_bytes),
-                            );
+                            )?;
                         }
                         _ => {}
                     }
@@ -169,26 +426,82 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
         }
 
         // receive from socket
-        let mut output: Option<Result<ClientEvent<T>, NaiaClientError>> = None;
+        let mut output: Option<Result<ClientEvent<T>, NaiaError>> = None;
         while output.is_none() {
             match self.socket.receive() {
                 Ok(event) => match event {
                     SocketEvent::Packet(packet) => {
-                        let packet_type = PacketType::get_from_packet(packet.payload());
+                        if let Some(link_conditioner) = &mut self.link_conditioner {
+                            if link_conditioner.should_drop_incoming() {
+                                continue;
+                            }
+                        }
+
+                        // a packet too short to hold its header is dropped, &
+                        // counted against the connection, if there is one
+                        let packet_type = match PacketType::get_from_packet(packet.payload()) {
+                            Some(packet_type) => packet_type,
+                            None => {
+                                if let Some(server_connection) = self.server_connection.as_mut() {
+                                    server_connection.record_drop(DropReason::OversizedLength);
+                                    if let Some(reason) =
+                                        server_connection.take_protocol_violation()
+                                    {
+                                        output = Some(Ok(ClientEvent::ProtocolViolation(reason)));
+                                    }
+                                }
+                                continue;
+                            }
+                        };
 
                         let server_connection_wrapper = self.server_connection.as_mut();
                         if let Some(server_connection) = server_connection_wrapper {
+                            if server_connection.has_protocol_violation() {
+                                continue;
+                            }
                             server_connection.mark_heard();
                             let mut payload =
                                 server_connection.process_incoming_header(packet.payload());
+                            if let Some(reason) = server_connection.take_protocol_violation() {
+                                output = Some(Ok(ClientEvent::ProtocolViolation(reason)));
+                                continue;
+                            }
 
                             match packet_type {
                                 PacketType::Data => {
-                                    server_connection
-                                        .process_incoming_data(&self.manifest, &mut payload);
+                                    server_connection.process_incoming_data(
+                                        &self.manifest,
+                                        &mut self.custom_sections,
+                                        &mut payload,
+                                    );
                                     continue;
                                 }
                                 PacketType::Heartbeat => {
+                                    let payload = server_connection.read_heartbeat_clock(&payload);
+                                    if !payload.is_empty() {
+                                        output = Some(Ok(ClientEvent::HeartbeatPayload(payload)));
+                                    }
+                                    continue;
+                                }
+                                PacketType::Handoff => {
+                                    let mut reader = PacketReader::new(&payload);
+                                    let address =
+                                        naia_shared::utils::read_socket_address(&mut reader);
+                                    let transfer_token =
+                                        reader.get_cursor().read_u64::<BigEndian>().ok();
+                                    if let (Some(address), Some(transfer_token)) =
+                                        (address, transfer_token)
+                                    {
+                                        self.migrate_host(address, transfer_token);
+                                        output = Some(Ok(ClientEvent::Handoff(address)));
+                                    }
+                                    continue;
+                                }
+                                PacketType::Kick => {
+                                    self.server_connection = None;
+                                    self.pending_requests.clear();
+                                    self.connection_state = ClientConnectionState::Failed;
+                                    output = Some(Ok(ClientEvent::Kicked));
                                     continue;
                                 }
                                 _ => {}
@@ -201,33 +514,141 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
                                     {
                                         if let Some(my_timestamp) = self.pre_connection_timestamp {
                                             let payload =
-                                                naia_shared::utils::read_headerless_payload(
+                                                match naia_shared::utils::read_headerless_payload(
                                                     packet.payload(),
-                                                );
+                                                ) {
+                                                    Some(payload) => payload,
+                                                    None => continue,
+                                                };
                                             let mut reader = PacketReader::new(&payload);
                                             let payload_timestamp = Timestamp::read(&mut reader);
 
+                                            let digest_bytes: Option<Vec<u8>> =
+                                                (0..32).map(|_| reader.read_u8()).collect();
+
                                             if my_timestamp == payload_timestamp {
-                                                let mut digest_bytes: Vec<u8> = Vec::new();
-                                                for _ in 0..32 {
-                                                    digest_bytes.push(reader.read_u8());
-                                                }
-                                                self.pre_connection_digest =
-                                                    Some(digest_bytes.into_boxed_slice());
-                                                self.connection_state =
+                                                if let Some(digest_bytes) = digest_bytes {
+                                                    self.pre_connection_digest =
+                                                        Some(digest_bytes.into_boxed_slice());
+                                                    self.connection_state =
                                                     ClientConnectionState::AwaitingConnectResponse;
+                                                }
                                             }
                                         }
                                     }
 
                                     continue;
                                 }
+                                PacketType::ManifestMismatch => {
+                                    if self.connection_state
+                                        == ClientConnectionState::AwaitingConnectResponse
+                                    {
+                                        let payload =
+                                            match naia_shared::utils::read_headerless_payload(
+                                                packet.payload(),
+                                            ) {
+                                                Some(payload) => payload,
+                                                None => continue,
+                                            };
+                                        // the mismatch carries the timestamp of the request,
+                                        // so that it can't be forged by another host
+                                        let my_timestamp = match self.pre_connection_timestamp {
+                                            Some(my_timestamp) => my_timestamp,
+                                            None => continue,
+                                        };
+                                        if payload.len() < 8 {
+                                            continue;
+                                        }
+                                        let mut reader = PacketReader::new(&payload);
+                                        if Timestamp::read(&mut reader) != my_timestamp {
+                                            continue;
+                                        }
+                                        // a descriptor compatible with this Client's is
+                                        // no reason to stop connecting
+                                        let report = match ManifestDescriptor::read(&mut reader)
+                                            .and_then(|server_descriptor| {
+                                                self.manifest
+                                                    .get_descriptor()
+                                                    .compare(&server_descriptor)
+                                            }) {
+                                            Some(report) => report,
+                                            None => continue,
+                                        };
+                                        self.connection_state = ClientConnectionState::Failed;
+                                        output = Some(Ok(ClientEvent::ConnectFailed(
+                                            ConnectFailure::ManifestMismatch(report),
+                                        )));
+                                    }
+                                    continue;
+                                }
+                                PacketType::ServerRejectResponse => {
+                                    if self.connection_state
+                                        == ClientConnectionState::AwaitingConnectResponse
+                                    {
+                                        let payload =
+                                            match naia_shared::utils::read_headerless_payload(
+                                                packet.payload(),
+                                            ) {
+                                                Some(payload) => payload,
+                                                None => continue,
+                                            };
+                                        // the rejection carries the timestamp of the request,
+                                        // so that it can't be forged by another host
+                                        if let Some(my_timestamp) = self.pre_connection_timestamp {
+                                            if payload.len() >= 8
+                                                && Timestamp::read(&mut PacketReader::new(&payload))
+                                                    == my_timestamp
+                                            {
+                                                self.connection_state =
+                                                    ClientConnectionState::Failed;
+                                                output = Some(Ok(ClientEvent::ConnectFailed(
+                                                    ConnectFailure::Refused,
+                                                )));
+                                            }
+                                        }
+                                    }
+                                    continue;
+                                }
                                 PacketType::ServerConnectResponse => {
-                                    self.server_connection = Some(ServerConnection::new(
-                                        self.server_address,
-                                        &self.config,
-                                    ));
+                                    let mut server_connection =
+                                        ServerConnection::new(self.server_address, &self.config);
+                                    if let Some(previous_connection) =
+                                        self.migrated_connection.take()
+                                    {
+                                        server_connection.carry_over(previous_connection);
+                                    }
+                                    for (type_id, policy) in self.late_update_policies.iter() {
+                                        server_connection.set_late_update_policy(*type_id, *policy);
+                                    }
+                                    let payload = match naia_shared::utils::read_headerless_payload(
+                                        packet.payload(),
+                                    ) {
+                                        Some(payload) => payload,
+                                        None => continue,
+                                    };
+                                    // the features the Server agreed on, of those offered
+                                    let mut reader = PacketReader::new(&payload);
+                                    let capabilities = Capabilities::read(&mut reader)
+                                        .unwrap_or_else(Capabilities::none)
+                                        .intersection(Capabilities::offered(&self.config));
+                                    server_connection.set_capabilities(capabilities);
+                                    // followed by the public id the Server assigned the Client
+                                    let mut id_bytes = payload.get(4..6).unwrap_or(&[]);
+                                    if let Ok(client_id) = id_bytes.read_u16::<BigEndian>() {
+                                        server_connection.set_client_id(client_id);
+                                    }
+                                    server_connection
+                                        .set_packet_framing(self.packet_framing.clone());
+                                    // the Server sends its tick rate if it has changed
+                                    // from the one configured
+                                    self.tick_accumulator
+                                        .set_tick_interval(self.config.tick_interval);
+                                    self.interpolation_delay
+                                        .set_tick_interval(self.config.tick_interval);
+                                    self.migration_token = None;
+                                    self.server_connection = Some(server_connection);
                                     self.connection_state = ClientConnectionState::Connected;
+                                    self.connect_retries_made = 0;
                                     output = Some(Ok(ClientEvent::Connection));
                                     continue;
                                 }
@@ -241,7 +662,7 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
                     }
                 },
                 Err(error) => {
-                    output = Some(Err(NaiaClientError::Wrapped(Box::new(error))));
+                    output = Some(Err(NaiaError::Wrapped(Box::new(error))));
                     continue;
                 }
             }
@@ -249,11 +670,408 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
         return output.unwrap();
     }
 
-    /// Queues up an Event to be sent to the Server
-    pub fn send_event(&mut self, event: &impl Event<T>) {
+    /// Migrates the Client to a new host, which has imported the state of the
+    /// current one with `NaiaServer::import_host_state()`. The migration
+    /// token is the one given to this Client by the current host with
+    /// `NaiaServer::get_migration_token()`. Entities are kept across the
+    /// migration, with the same LocalEntityKeys, and a
+    /// `ClientEvent::Connection` is emitted once the new host has accepted
+    /// the Client
+    pub fn migrate_host(&mut self, server_address: SocketAddr, migration_token: u64) {
+        if let Some(connection) = self.server_connection.take() {
+            self.migrated_connection = Some(connection);
+        }
+        self.migration_token = Some(migration_token);
+
+        self.socket = connect_socket(server_address);
+        self.sender = self.socket.get_sender();
+        self.server_address = self.socket.server_address();
+
+        self.start_connecting();
+        self.pending_requests.clear();
+    }
+
+    // Starts a fresh series of attempts to connect to the Server
+    fn start_connecting(&mut self) {
+        self.connect_retries_made = 0;
+        self.connect_retry_timer = None;
+        self.start_connect_attempt();
+    }
+
+    fn start_connect_attempt(&mut self) {
+        self.pre_connection_timestamp = None;
+        self.pre_connection_digest = None;
+        self.connection_state = AwaitingChallengeResponse;
+        self.handshake_timer.ring_manual();
+        self.connect_timer.reset();
+    }
+
+    // Retries connecting once the backoff has elapsed, and times out the
+    // current attempt. Returns true if the Client has given up connecting
+    fn update_connect_attempt(&mut self) -> bool {
+        match self.connection_state {
+            ClientConnectionState::AwaitingChallengeResponse
+            | ClientConnectionState::AwaitingConnectResponse => {}
+            _ => return false,
+        }
+        if let Some(retry_timer) = &self.connect_retry_timer {
+            if retry_timer.ringing() {
+                self.connect_retry_timer = None;
+                self.start_connect_attempt();
+            }
+            return false;
+        }
+        if !self.connect_timer.ringing() {
+            return false;
+        }
+        if let Some(retries) = self.config.connect_retries {
+            if self.connect_retries_made >= retries {
+                self.connection_state = ClientConnectionState::Failed;
+                return true;
+            }
+        }
+        let doublings = self.connect_retries_made.min(5) as u32;
+        self.connect_retries_made = self.connect_retries_made.saturating_add(1);
+        self.connect_retry_timer = Some(Timer::new(
+            self.config.connect_retry_backoff * 2u32.pow(doublings),
+        ));
+        return false;
+    }
+
+    /// Queues up an Event to be sent to the Server. Returns an EventId, which
+    /// will be emitted in a `ClientEvent::DeliveryConfirmed` once the Event has
+    /// been delivered, or an error if the Client is not connected, or the
+    /// Event can't be sent, as described in `Manifest::validate_event()`. An
+    /// Event which isn't guaranteed is dropped if the connection is congested
+    /// & `BackpressureThresholds::drop_unguaranteed_events` is set
+    pub fn send_event(&mut self, event: &dyn Event<T>) -> Result<EventId, NaiaError> {
+        return self.internal_send_event(event, None);
+    }
+
+    /// Queues up an Event to be sent to the Server, like `send_event()`, but
+    /// drops it if it can't be sent within the given time-to-live, so that a
+    /// stale Event (such as an old position) isn't delivered uselessly late. A
+    /// guaranteed Event whose packet is lost is not resent after its
+    /// time-to-live either. The EventId of a dropped Event is emitted in a
+    /// `ClientEvent::EventExpired`
+    pub fn send_event_with_ttl(
+        &mut self,
+        event: &dyn Event<T>,
+        ttl: Duration,
+    ) -> Result<EventId, NaiaError> {
+        return self.internal_send_event(event, Some(ttl));
+    }
+
+    fn internal_send_event(
+        &mut self,
+        event: &dyn Event<T>,
+        ttl: Option<Duration>,
+    ) -> Result<EventId, NaiaError> {
+        let filtered_event;
+        let event: &dyn Event<T> = match self.outgoing_event_middleware.is_empty() {
+            true => event,
+            false => {
+                match self
+                    .outgoing_event_middleware
+                    .apply(&self.server_address, event.get_typed_copy())
+                {
+                    Some(typed_event) => filtered_event = typed_event.get_boxed_event(),
+                    None => return Err(NaiaError::FilteredOut),
+                }
+                filtered_event.as_ref()
+            }
+        };
+        self.manifest.validate_event(event)?;
         if let Some(connection) = &mut self.server_connection {
-            connection.queue_event(event);
+            if connection.should_drop_unguaranteed() && !Event::is_guaranteed(event) {
+                return Err(NaiaError::Congested);
+            }
+            return match ttl {
+                Some(ttl) => Ok(connection.queue_event_with_ttl(event, ttl)),
+                None => Ok(connection.queue_event(event)),
+            };
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Appends a function to the chain every Event received from the Server
+    /// passes through, in the order added, before it is emitted as a
+    /// `ClientEvent::Event`. Each function is given the Server's address &
+    /// the Event, and can observe it, return a changed Event, or return None
+    /// to drop it, so that concerns like logging, analytics or profanity
+    /// filtering can be kept out of the game's event handling
+    pub fn add_incoming_event_middleware(
+        &mut self,
+        middleware_func: Rc<Box<dyn Fn(&SocketAddr, T) -> Option<T>>>,
+    ) {
+        self.incoming_event_middleware.push(middleware_func);
+    }
+
+    /// Appends a function to the chain every Event sent with `send_event()`
+    /// or `send_event_with_ttl()` passes through, in the order added. Each
+    /// function can observe the Event, return a changed Event, or return
+    /// None to drop it, in which case sending the Event returns
+    /// `NaiaError::FilteredOut`
+    pub fn add_outgoing_event_middleware(
+        &mut self,
+        middleware_func: Rc<Box<dyn Fn(&SocketAddr, T) -> Option<T>>>,
+    ) {
+        self.outgoing_event_middleware.push(middleware_func);
+    }
+
+    /// Queues up an opaque byte payload (for example, a voice frame) to be
+    /// attached to the next outgoing packet to the Server, bypassing the
+    /// Manifest. Raw payloads are unreliable, and at most
+    /// `Config::raw_payload_budget` bytes of them are attached to any single
+    /// packet. Payloads which wait more than a second to be sent are dropped.
+    /// Returns an error if the Client is not connected, or if the payload is
+    /// larger than `MAX_RAW_PAYLOAD_SIZE`, or too large for its length byte
+    /// to fit in the budget along with it.
+    pub fn send_raw(&mut self, payload: &[u8]) -> Result<(), NaiaError> {
+        if let Some(connection) = &mut self.server_connection {
+            if !connection.queue_raw_payload(payload) {
+                return Err(NaiaError::PayloadTooLarge(payload.len()));
+            }
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Registers an application-defined section of data packets under the
+    /// given id, whose bytes are written & read by the given CustomSection.
+    /// The Server must register a section under the same id to read it, and
+    /// sections with an id the receiver hasn't registered are skipped. The
+    /// remote host passed to the CustomSection is the Server's address.
+    /// Returns false if a section with the same id has already been registered
+    pub fn register_custom_section(
+        &mut self,
+        section_id: u8,
+        section: Box<dyn CustomSection<SocketAddr>>,
+    ) -> bool {
+        return self.custom_sections.register(section_id, section);
+    }
+
+    // Replaces the Manifest the Client reads & writes with, returning the
+    // previous one. Used by MultiClient to release the Manifest it shares
+    // with every connection, so that types can be registered in it
+    pub(crate) fn replace_manifest(&mut self, manifest: Rc<Manifest<T, U>>) -> Rc<Manifest<T, U>> {
+        return std::mem::replace(&mut self.manifest, manifest);
+    }
+
+    /// Registers an Event type in the Manifest under the given NaiaId after
+    /// startup, such as one added by a mod, so that it can be read when the
+    /// Server registers it too. Returns an error if the NaiaId or the type
+    /// has already been registered, or if the Manifest is shared with
+    /// another Client or a Server in the same process
+    pub fn register_event_type(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) -> Result<(), NaiaError> {
+        return Rc::get_mut(&mut self.manifest)
+            .ok_or(NaiaError::ManifestShared)?
+            .try_register_event_with_id(naia_id, event_builder);
+    }
+
+    /// Registers an Entity type in the Manifest under the given NaiaId after
+    /// startup, such as one added by a mod, so that it can be read when the
+    /// Server registers it too. Returns an error if the NaiaId or the type
+    /// has already been registered, or if the Manifest is shared with
+    /// another Client or a Server in the same process
+    pub fn register_entity_type(
+        &mut self,
+        naia_id: u16,
+        entity_builder: Box<dyn EntityBuilder<U>>,
+    ) -> Result<(), NaiaError> {
+        return Rc::get_mut(&mut self.manifest)
+            .ok_or(NaiaError::ManifestShared)?
+            .try_register_entity_with_id(naia_id, entity_builder);
+    }
+
+    /// Sets how outgoing data packets to the Server are assembled from the
+    /// serialized Events, RPC messages, commands, etc. waiting to be sent,
+    /// for example to order sections differently or interleave items by
+    /// priority. None restores the default SectionFraming
+    pub fn set_packet_framing(&mut self, packet_framing: Option<PacketFramingFactory>) {
+        if let Some(connection) = &mut self.server_connection {
+            connection.set_packet_framing(packet_framing.clone());
+        }
+        self.packet_framing = packet_framing;
+    }
+
+    /// Sets a small piece of application data (for example, which menu the
+    /// player is in) to attach to every heartbeat sent to the Server, which
+    /// emits it in a `ServerEvent::HeartbeatPayload`. Heartbeats are only sent
+    /// when nothing else has been sent for `Config::heartbeat_interval`, so
+    /// this lets idle connections keep exchanging presence data without
+    /// sending Events. Returns an error if the Client is not connected, or if
+    /// the payload is larger than `MAX_HEARTBEAT_PAYLOAD_SIZE`
+    pub fn set_heartbeat_payload(&mut self, payload: &[u8]) -> Result<(), NaiaError> {
+        if let Some(connection) = &mut self.server_connection {
+            if !connection.set_heartbeat_payload(payload) {
+                return Err(NaiaError::PayloadTooLarge(payload.len()));
+            }
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Queues up a large blob of data to be sent to the Server. The blob is
+    /// split into chunks, which are sent as the bandwidth limit in the Config
+    /// allows, and retransmitted if lost. Returns None if the Client is not
+    /// connected, or if the blob is empty or too large.
+    pub fn send_blob(&mut self, data: &[u8]) -> Option<BlobId> {
+        if let Some(connection) = &mut self.server_connection {
+            return connection.queue_blob(data);
+        }
+        return None;
+    }
+
+    /// Get the progress of a blob being sent to the Server, as a tuple of
+    /// (chunks delivered, total chunks). Returns None once the blob has been
+    /// completely delivered.
+    pub fn get_blob_send_progress(&self, blob_id: BlobId) -> Option<(u16, u16)> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_outgoing_blob_progress(blob_id);
+        }
+        return None;
+    }
+
+    /// Sends a request to the Server, returning the RequestId that the
+    /// eventual `ClientEvent::Response` or `ClientEvent::RequestFailed` will be
+    /// correlated with. Returns an error if the Client is not connected, or
+    /// the Event can't be sent, as described in `Manifest::validate_event()`
+    pub fn request(&mut self, event: &impl Event<T>) -> Result<RequestId, NaiaError> {
+        self.manifest.validate_event(event)?;
+        if let Some(connection) = &mut self.server_connection {
+            let request_id = self.next_request_id;
+            self.next_request_id = self.next_request_id.wrapping_add(1);
+            connection.queue_rpc(RpcMessage::Request(request_id, event));
+            self.pending_requests
+                .insert(request_id, Timer::new(self.config.request_timeout));
+            return Ok(request_id);
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Sends an Event to the Client with the given id, as returned by
+    /// `get_client_id()` on that Client, to be relayed by the Server, which
+    /// may filter it out with `NaiaServer::on_relay_event()`. The recipient
+    /// receives it in a `ClientEvent::RelayedEvent`. Returns an error if the
+    /// Client is not connected, or the Event can't be sent, as described in
+    /// `Manifest::validate_event()`
+    pub fn relay_event(
+        &mut self,
+        recipient_id: ClientId,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        self.manifest.validate_event(event)?;
+        if let Some(connection) = &mut self.server_connection {
+            connection.queue_relay_event(recipient_id, event);
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Enter loading mode. While loading, the Server holds back Entity
+    /// messages, and the application should call `keep_alive()` periodically
+    /// (for example, in between loading assets) so the connection does not
+    /// time out. Call `finish_loading()` when ready. Returns an error if the
+    /// Server could not be told
+    pub fn begin_loading(&mut self) -> Result<(), NaiaError> {
+        self.loading = true;
+        if let Some(connection) = &mut self.server_connection {
+            return NaiaClient::internal_send_heartbeat(
+                &mut self.sender,
+                connection,
+                PacketType::LoadingHeartbeat,
+            );
+        }
+        return Ok(());
+    }
+
+    /// Leave loading mode, signaling to the Server that it can resume sending
+    /// Entity messages. Returns an error if the Server could not be told
+    pub fn finish_loading(&mut self) -> Result<(), NaiaError> {
+        self.loading = false;
+        if let Some(connection) = &mut self.server_connection {
+            return NaiaClient::internal_send_heartbeat(
+                &mut self.sender,
+                connection,
+                PacketType::Heartbeat,
+            );
+        }
+        return Ok(());
+    }
+
+    /// Returns whether the Client is currently in loading mode
+    pub fn is_loading(&self) -> bool {
+        return self.loading;
+    }
+
+    /// A minimal version of `receive()`, which sends heartbeats and acks
+    /// incoming packets, but does not emit any events. Incoming Events &
+    /// Entity messages are queued, and will be returned by `receive()` once
+    /// the application has finished loading.
+    pub fn keep_alive(&mut self) -> Result<(), NaiaError> {
+        if let Some(connection) = &mut self.server_connection {
+            if connection.should_send_heartbeat() {
+                NaiaClient::internal_send_heartbeat(
+                    &mut self.sender,
+                    connection,
+                    NaiaClient::<T, U>::heartbeat_type(self.loading),
+                )?;
+            }
+            connection.process_reordered_data(&self.manifest, &mut self.custom_sections);
+
+            loop {
+                match self.socket.receive() {
+                    Ok(SocketEvent::Packet(packet)) => {
+                        if let Some(link_conditioner) = &mut self.link_conditioner {
+                            if link_conditioner.should_drop_incoming() {
+                                continue;
+                            }
+                        }
+                        if connection.has_protocol_violation() {
+                            continue;
+                        }
+                        let packet_type = match PacketType::get_from_packet(packet.payload()) {
+                            Some(packet_type) => packet_type,
+                            None => {
+                                connection.record_drop(DropReason::OversizedLength);
+                                continue;
+                            }
+                        };
+                        connection.mark_heard();
+                        let mut payload = connection.process_incoming_header(packet.payload());
+                        if packet_type == PacketType::Data {
+                            connection.process_incoming_data(
+                                &self.manifest,
+                                &mut self.custom_sections,
+                                &mut payload,
+                            );
+                        } else if packet_type == PacketType::Heartbeat {
+                            connection.read_heartbeat_clock(&payload);
+                        }
+                    }
+                    Ok(SocketEvent::None) => {
+                        break;
+                    }
+                    Err(error) => {
+                        return Err(NaiaError::Wrapped(Box::new(error)));
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn heartbeat_type(loading: bool) -> PacketType {
+        if loading {
+            return PacketType::LoadingHeartbeat;
         }
+        return PacketType::Heartbeat;
     }
 
     fn internal_send_with_connection(
@@ -261,24 +1079,40 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
         connection: &mut ServerConnection<T, U>,
         packet_type: PacketType,
         packet: Packet,
-    ) {
+    ) -> Result<(), NaiaError> {
         let new_payload = connection.process_outgoing_header(packet_type, packet.payload());
-        sender
-            .send(Packet::new_raw(new_payload))
-            .expect("send failed!");
+        if let Err(error) = sender.send(Packet::new_raw(new_payload)) {
+            return Err(NaiaError::Wrapped(error));
+        }
         connection.mark_sent();
+        return Ok(());
+    }
+
+    fn internal_send_heartbeat(
+        sender: &mut MessageSender,
+        connection: &mut ServerConnection<T, U>,
+        packet_type: PacketType,
+    ) -> Result<(), NaiaError> {
+        let heartbeat_payload = connection.get_heartbeat_payload().to_vec();
+        return NaiaClient::internal_send_with_connection(
+            sender,
+            connection,
+            packet_type,
+            Packet::new(heartbeat_payload),
+        );
     }
 
     fn internal_send_connectionless(
         sender: &mut MessageSender,
         packet_type: PacketType,
         packet: Packet,
-    ) {
+    ) -> Result<(), NaiaError> {
         let new_payload =
             naia_shared::utils::write_connectionless_payload(packet_type, packet.payload());
-        sender
-            .send(Packet::new_raw(new_payload))
-            .expect("send failed!");
+        if let Err(error) = sender.send(Packet::new_raw(new_payload)) {
+            return Err(NaiaError::Wrapped(error));
+        }
+        return Ok(());
     }
 
     /// Get the address currently associated with the Server
@@ -289,15 +1123,467 @@ impl<T: EventType, U: EntityType> NaiaClient<T, U> {
     /// Get a reference to an Entity currently in scope for the Client, given
     /// that Entity's Key
     pub fn get_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.server_connection.as_ref()?.get_local_entity(key);
+    }
+
+    /// Get a copy of an Entity as it was before its most recent Update, to
+    /// compare against the Entity's current values. Returns None if
+    /// `Config::keep_previous_entity_values` is not enabled, or the Entity has
+    /// not been updated since it came into scope
+    pub fn get_previous_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_previous_entity(key);
+        }
+        return None;
+    }
+
+    /// Get a copy of an Entity with its most recent late Update applied, which
+    /// was delivered as a `ClientEvent::LateUpdateEntity` rather than applied
+    /// to the Entity itself. Returns None if no late Update of the Entity has
+    /// been delivered
+    pub fn get_late_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.server_connection.as_ref()?.get_late_entity(key);
+    }
+
+    /// Set what the Client does with Updates of Entities of type E which
+    /// arrive after a newer Update of the same Properties, in place of
+    /// `Config::late_update_policy`
+    pub fn set_late_update_policy<E: 'static>(&mut self, policy: LateUpdatePolicy) {
+        let type_id = TypeId::of::<E>();
+        self.late_update_policies.insert(type_id, policy);
+        if let Some(connection) = &mut self.server_connection {
+            connection.set_late_update_policy(type_id, policy);
+        }
+    }
+
+    /// Get counts of the Entity Updates received from the Server after a newer
+    /// Update of the same Properties, & how they were handled. Returns None if
+    /// not connected
+    pub fn get_late_update_stats(&self) -> Option<LateUpdateStats> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_late_update_stats());
+    }
+
+    /// Get every Entity currently in scope for the Client, along with its key.
+    /// Returns an empty list if the Client is not connected
+    pub fn all_entities(&self) -> Vec<(LocalEntityKey, &U)> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_all_entities();
+        }
+        return Vec::new();
+    }
+
+    /// Get every Entity of the given type currently in scope for the Client,
+    /// along with its key. Returns an empty list if the Client is not
+    /// connected
+    pub fn entities_of_type<E: 'static>(&self) -> Vec<(LocalEntityKey, &U)> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_entities_of_type(TypeId::of::<E>());
+        }
+        return Vec::new();
+    }
+
+    /// Get the number of Entities currently in scope for the Client
+    pub fn entity_count(&self) -> usize {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_entity_count();
+        }
+        return 0;
+    }
+
+    /// Get the number of Entities of the given type currently in scope for the
+    /// Client
+    pub fn entity_count_of_type<E: 'static>(&self) -> usize {
+        return self.entities_of_type::<E>().len();
+    }
+
+    /// Produces a report of every Entity received from the Server, to be
+    /// compared with `NaiaServer::debug_dump_entities()` using
+    /// `diff_entity_dumps()` when debugging replication mismatches. Returns
+    /// None if the Client is not connected
+    pub fn debug_dump_entities(&self) -> Option<EntityDebugDump> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.debug_dump_entities(&self.manifest));
+    }
+
+    /// Records the visual error of a predicted Entity, after reconciliation
+    /// moved it to a corrected position, as the position it was rendered at
+    /// minus the corrected position, along each axis. The error becomes an
+    /// offset, returned by `get_correction_offset()`, which decays to zero
+    /// over `Config::correction_duration` as `frame_begin()` is called, so the
+    /// Entity glides to its corrected position instead of snapping to it. A
+    /// correction made while another is still decaying is added to it
+    pub fn record_correction(&mut self, key: &LocalEntityKey, error: &[f32]) {
+        if let Some(connection) = &mut self.server_connection {
+            connection.record_correction(*key, error);
+        }
+    }
+
+    /// Registers a closure which takes a motion sample of a remote Entity,
+    /// its position along each axis along with the derivatives of the
+    /// position such as velocity, whenever the Entity is created or updated,
+    /// or returns None for Entities which shouldn't be extrapolated. Between
+    /// & beyond updates, the position is extrapolated from the most recent
+    /// sample with `Config::extrapolation_model` as `frame_begin()` is
+    /// called, for at most `Config::max_extrapolation` past the update. When
+    /// a new sample arrives, the Entity blends onto it over
+    /// `Config::correction_duration`, rather than snapping to it
+    pub fn on_sample_motion(&mut self, sample_func: Rc<Box<dyn Fn(&U) -> Option<MotionSample>>>) {
+        self.motion_sampler = Some(MotionSampler::new(sample_func));
+    }
+
+    /// Gets the extrapolated position of a remote Entity, which should be
+    /// used when rendering it. Returns None if the Entity has not been
+    /// sampled, see `on_sample_motion()`
+    pub fn get_extrapolated_position(&self, key: &LocalEntityKey) -> Option<Vec<f32>> {
         return self
             .server_connection
             .as_ref()
-            .unwrap()
-            .get_local_entity(key);
+            .and_then(|connection| connection.get_extrapolated_position(*key));
+    }
+
+    /// Gets the current correction offset of an Entity, which should be added
+    /// to its position when rendering it. Returns None if the Entity has no
+    /// correction decaying
+    pub fn get_correction_offset(&self, key: &LocalEntityKey) -> Option<&[f32]> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_correction_offset(*key));
+    }
+
+    /// Spawn an Entity locally, in prediction of the Server spawning it (for
+    /// example, a fired projectile), to avoid waiting a round trip. The
+    /// returned PredictionKey should be sent to the Server, which links its
+    /// Entity with `NaiaServer::confirm_predicted_entity()`. Returns None if
+    /// the Client is not connected
+    pub fn spawn_predicted_entity(&mut self, entity: U) -> Option<PredictionKey> {
+        if let Some(connection) = &mut self.server_connection {
+            return Some(connection.spawn_predicted_entity(entity));
+        }
+        return None;
+    }
+
+    /// Get a reference to an Entity spawned in prediction, which has not yet
+    /// been confirmed by the Server
+    pub fn get_predicted_entity(&self, key: PredictionKey) -> Option<&U> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_predicted_entity(key);
+        }
+        return None;
+    }
+
+    /// Remove an Entity spawned in prediction, for example if the Server has
+    /// not confirmed it in time
+    pub fn remove_predicted_entity(&mut self, key: PredictionKey) -> Option<U> {
+        if let Some(connection) = &mut self.server_connection {
+            return connection.remove_predicted_entity(key);
+        }
+        return None;
+    }
+
+    /// Get the progress of the initial world snapshot, as a tuple of
+    /// (Entities received, total Entities). Returns None if the Client is not
+    /// connected, or the Server has not yet begun sending the snapshot
+    pub fn get_world_sync_progress(&self) -> Option<(u32, u32)> {
+        if let Some(connection) = &self.server_connection {
+            return connection.get_snapshot_progress();
+        }
+        return None;
+    }
+
+    /// Returns whether the initial world snapshot has been completely received
+    pub fn is_world_synced(&self) -> bool {
+        if let Some(connection) = &self.server_connection {
+            return connection.is_world_synced();
+        }
+        return false;
     }
 
     /// Get the current measured Round Trip Time to the Server
     pub fn get_rtt(&self) -> f32 {
         return self.server_connection.as_ref().unwrap().get_rtt();
     }
+
+    /// Get the min, median, 95th percentile & max of the most recent Round
+    /// Trip Time samples to the Server, over the number of samples given by
+    /// `Config::rtt_history_size`. Returns None if not connected, or if no
+    /// sample has been taken yet
+    pub fn get_rtt_stats(&self) -> Option<RttStats> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_rtt_stats());
+    }
+
+    /// Get statistics about the packets received from the Server which were
+    /// dropped, in whole or in part, for being malformed. Returns None if not
+    /// connected
+    pub fn get_connection_stats(&self) -> Option<ConnectionStats> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_connection_stats());
+    }
+
+    /// Get statistics about the outgoing messages of the given kind, such as
+    /// `ManagerType::Event` or `ManagerType::Rpc`, which have yet to be
+    /// delivered to the Server: how many are queued & in flight, how long the
+    /// oldest has been waiting, & how many have been retransmitted. Returns
+    /// None if not connected
+    pub fn get_event_queue_stats(&self, manager_type: ManagerType) -> Option<EventQueueStats> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_event_queue_stats(manager_type));
+    }
+
+    /// Get statistics about the outgoing Events of type E which have yet to be
+    /// delivered to the Server, however they were sent, so that a class of
+    /// Events which is backing up can be throttled. Returns None if not
+    /// connected
+    pub fn get_event_type_queue_stats<E: 'static>(&self) -> Option<EventQueueStats> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_event_type_queue_stats(TypeId::of::<E>()));
+    }
+
+    /// Get the fraction of the most recent packets sent to the Server which
+    /// were lost, from 0 to 1. Returns None if not connected, or if no sent
+    /// packet has been acked or dropped yet
+    pub fn get_packet_loss(&self) -> Option<f32> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_packet_loss());
+    }
+
+    /// Get the histogram of the time between sending each packet to the
+    /// Server & receiving its ack, or None if not connected
+    pub fn get_ack_latency(&self) -> Option<&AckLatencyHistogram> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_ack_latency());
+    }
+
+    /// Clear the histogram of ack latencies, so that it can be sampled over a
+    /// chosen period
+    pub fn clear_ack_latency(&mut self) {
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.clear_ack_latency();
+        }
+    }
+
+    /// Get the quality of the connection to the Server, as last emitted in a
+    /// `ClientEvent::ConnectionQualityChanged`. Returns None if not connected,
+    /// or if it has not been classified yet
+    pub fn get_connection_quality(&self) -> Option<ConnectionQuality> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_quality());
+    }
+
+    /// Returns whether the outgoing queues of the connection to the Server are
+    /// congested, as last emitted in a `ClientEvent::Congested` or
+    /// `ClientEvent::Relieved`
+    pub fn is_congested(&self) -> bool {
+        return self
+            .server_connection
+            .as_ref()
+            .map_or(false, |connection| connection.is_congested());
+    }
+
+    /// Gets the public id the Server assigned the Client when it connected,
+    /// which other Clients address the Events they relay to it by, or None
+    /// if the Client is not connected
+    pub fn get_client_id(&self) -> Option<ClientId> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_client_id());
+    }
+
+    /// Gets the public ids of every Client connected to the Server, this
+    /// Client included, in ascending order, as last told by the Server
+    pub fn get_connected_clients(&self) -> Vec<ClientId> {
+        return self
+            .server_connection
+            .as_ref()
+            .map_or(Vec::new(), |connection| {
+                connection.get_client_roster().get_client_ids()
+            });
+    }
+
+    /// Returns whether the Client with the given public id is connected to
+    /// the Server, as last told by the Server
+    pub fn is_client_connected(&self, client_id: ClientId) -> bool {
+        return self.server_connection.as_ref().map_or(false, |connection| {
+            connection.get_client_roster().contains(client_id)
+        });
+    }
+
+    /// Get the optional features agreed on with the Server during the
+    /// handshake, being those which both hosts enabled. Returns None if not
+    /// connected
+    pub fn get_capabilities(&self) -> Option<Capabilities> {
+        return self
+            .server_connection
+            .as_ref()
+            .map(|connection| connection.get_capabilities());
+    }
+
+    /// Returns whether packets to & from the Server are written with compact
+    /// headers, which is the case only if `Config::compact_headers` is set &
+    /// the Server accepted the request during the handshake
+    pub fn has_compact_headers(&self) -> bool {
+        return self
+            .server_connection
+            .as_ref()
+            .map_or(false, |connection| connection.has_compact_headers());
+    }
+
+    /// Should be called at the beginning of every rendering frame, given the
+    /// time elapsed since the previous frame. Returns the number of fixed
+    /// network ticks, of the length given by `NaiaClient::get_tick_interval()`,
+    /// which should be simulated during the frame, so that an application
+    /// rendering at a high frame rate can process network ticks at a lower
+    /// rate. At most `MAX_TICKS_PER_FRAME` are returned, so a long stall is
+    /// not followed by a burst of ticks
+    pub fn frame_begin(&mut self, frame_duration: Duration) -> u16 {
+        let rtt_stats = self.get_rtt_stats();
+        self.interpolation_delay
+            .update(frame_duration, rtt_stats.as_ref());
+        let (time_dilation, clock_skew) = self
+            .server_connection
+            .as_ref()
+            .map_or((0, 0.0), |connection| {
+                (connection.get_time_dilation(), connection.get_clock_skew())
+            });
+        // ticks follow the Server's clock, which drifts from the Client's
+        self.tick_accumulator
+            .set_time_scale(time_dilation_scale(time_dilation) * (1.0 + clock_skew as f32));
+        let tick_count = self.tick_accumulator.frame_begin(frame_duration);
+        if let Some(connection) = &mut self.server_connection {
+            connection.advance_client_tick(tick_count, self.config.input_buffer_target_depth);
+            connection.advance_corrections(frame_duration);
+            connection.advance_extrapolations(frame_duration);
+        }
+        return tick_count;
+    }
+
+    /// Sends a command for the Client's current tick, which the Server receives
+    /// as a `ServerEvent::Command` along with that tick. Commands are not
+    /// guaranteed, but each is sent in every packet until one carrying it is
+    /// acknowledged, up to the most recent `Config::command_redundancy`
+    /// commands, so that a single lost packet doesn't lose a tick's command.
+    /// Sending another command in the same tick replaces the first. Returns an
+    /// error if the Client is not connected, if there is no Client tick yet,
+    /// see `get_client_tick()`, or if the command can't be sent, as described
+    /// in `Manifest::validate_event()`
+    pub fn send_command(&mut self, command: &impl Event<T>) -> Result<(), NaiaError> {
+        self.manifest.validate_event(command)?;
+        if let Some(connection) = &mut self.server_connection {
+            if let Some(client_tick) = connection.get_client_tick() {
+                connection.queue_command(client_tick, command);
+                return Ok(());
+            }
+            return Err(NaiaError::NoClientTick);
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Gets the tick the Client is simulating, which runs ahead of the Server
+    /// so that input sent for it arrives before the Server reaches it. It
+    /// advances with the ticks returned by `frame_begin()`, which are slightly
+    /// sped up or slowed down as the Server requests, to keep the input
+    /// arriving `Config::input_buffer_target_depth` ticks early. Returns None
+    /// until connected & `frame_begin()` has been called
+    pub fn get_client_tick(&self) -> Option<u16> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_client_tick());
+    }
+
+    /// Gets the Server's tick written in the most recently received packet,
+    /// for example to advance a `ReplicatedAnimation` between updates.
+    /// Returns None until a packet carrying the tick has been received
+    pub fn get_server_tick(&self) -> Option<u16> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_server_tick());
+    }
+
+    /// Gets the current time of the Server's clock, which counts up from when
+    /// the Server started, as estimated from the clock carried by its
+    /// heartbeats. The estimate follows both the offset of the Client's clock
+    /// from the Server's & the drift between the two, see
+    /// `Config::clock_sync_interval`. Returns None until a heartbeat has been
+    /// received
+    pub fn get_server_time(&self) -> Option<Duration> {
+        return self
+            .server_connection
+            .as_ref()
+            .and_then(|connection| connection.get_server_time());
+    }
+
+    /// Gets how much faster the Server's clock runs than the Client's, as a
+    /// fraction of its rate, such as 0.00005 for 50 parts per million. The
+    /// ticks returned by `frame_begin()` are sped up or slowed down by it, on
+    /// top of the time dilation asked for by the Server. Returns 0 until
+    /// enough heartbeats have been received, or if not connected
+    pub fn get_clock_skew(&self) -> f64 {
+        return self
+            .server_connection
+            .as_ref()
+            .map_or(0.0, |connection| connection.get_clock_skew());
+    }
+
+    /// Gets the rate the Server has asked the Client's tick clock to run at,
+    /// relative to real time, such as 1.02 for 2% faster
+    pub fn get_time_dilation(&self) -> f32 {
+        return time_dilation_scale(
+            self.server_connection
+                .as_ref()
+                .map_or(0, |connection| connection.get_time_dilation()),
+        );
+    }
+
+    /// Gets the duration of each tick, which is `Config::tick_interval` until
+    /// the Server changes its tick rate, as emitted in a
+    /// `ClientEvent::TickRateChanged`
+    pub fn get_tick_interval(&self) -> Duration {
+        return self.tick_accumulator.get_tick_interval();
+    }
+
+    /// Gets how far the current frame is between the last tick returned by
+    /// `frame_begin()` & the next one, from 0 to 1. Rendering should
+    /// interpolate between the state of the two ticks by this fraction
+    pub fn get_interpolation_fraction(&self) -> f32 {
+        return self.tick_accumulator.get_interpolation_fraction();
+    }
+
+    /// Gets how far behind the most recently received state the application
+    /// should render, for the state to interpolate toward to have nearly
+    /// always arrived. It is one tick, plus the jitter of the connection, and
+    /// is adjusted by `frame_begin()`, rising immediately as jitter grows but
+    /// falling only once it has stayed lower for `INTERPOLATION_DELAY_HOLD`
+    pub fn get_interpolation_delay(&self) -> Duration {
+        return self.interpolation_delay.get_delay();
+    }
+
+    /// Should be called when rendering found that the state to interpolate
+    /// toward had not yet arrived, which raises the interpolation delay
+    /// immediately. The extra delay decays once underruns stop
+    pub fn report_interpolation_underrun(&mut self) {
+        self.interpolation_delay.record_underrun();
+    }
 }
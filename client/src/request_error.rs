@@ -0,0 +1,8 @@
+/// The reason an RPC request sent to the Server has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestError {
+    /// No response was received within the configured request timeout
+    Timeout,
+    /// The Server has rejected the request
+    Rejected,
+}
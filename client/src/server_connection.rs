@@ -1,9 +1,16 @@
-use std::net::SocketAddr;
+use std::{any::TypeId, collections::HashSet, net::SocketAddr, time::Duration};
+
+use byteorder::{BigEndian, ReadBytesExt};
 
 use naia_shared::{
-    AckManager, Config, Connection, EntityType, Event, EventManager, EventType, LocalEntityKey,
-    ManagerType, Manifest, PacketReader, PacketType, PacketWriter, RttTracker, SequenceNumber,
-    Timer,
+    sequence_greater_than, AckLatencyHistogram, AckManager, BackpressureMonitor, BlobId,
+    BlobManager, BlobMessage, Capabilities, ClientId, ClientRoster, ClientRosterChange, ClockSync,
+    Config, Congestion, Connection, ConnectionQuality, ConnectionQualityMonitor, ConnectionStats,
+    CustomSections, DropLog, DropReason, EntityDebugDump, EntityType, Event, EventId, EventManager,
+    EventQueueStats, EventType, LateUpdatePolicy, LateUpdateStats, LocalEntityKey, ManagerType,
+    Manifest, ManifestDescriptor, MotionSampler, PacketFramingFactory, PacketReader, PacketType,
+    PacketWriter, PredictionKey, RawPayloadManager, ReorderBuffer, ReplayWindow, RpcMessage,
+    RttStats, RttTracker, SequenceNumber, StallMonitor, TickRateChange, Timer,
 };
 
 use super::{
@@ -14,6 +21,22 @@ use super::{
 pub struct ServerConnection<T: EventType, U: EntityType> {
     connection: Connection<T>,
     entity_manager: ClientEntityManager<U>,
+    tick_interval: Duration,
+    scheduled_events: Vec<(Timer, u16, T)>,
+    raw_payload_budget: usize,
+    event_packet_share: f32,
+    entity_packet_share: f32,
+    client_tick: Option<u16>,
+    packet_framing: Option<PacketFramingFactory>,
+    strict_protocol: bool,
+    protocol_violation_reported: bool,
+    client_id: ClientId,
+    client_roster: ClientRoster,
+    clock_sync: ClockSync,
+    reorder_buffer: Option<ReorderBuffer>,
+    manifest_revisions: HashSet<u32>,
+    shutdown_received: bool,
+    tick_rate_revision: u32,
 }
 
 impl<T: EventType, U: EntityType> ServerConnection<T, U> {
@@ -29,25 +52,126 @@ impl<T: EventType, U: EntityType> ServerConnection<T, U> {
                 Timer::new(heartbeat_interval),
                 Timer::new(timeout_duration),
                 AckManager::new(),
-                RttTracker::new(rtt_smoothing_factor, rtt_max_value),
-                EventManager::new(),
+                RttTracker::new(rtt_smoothing_factor, rtt_max_value, config.rtt_history_size),
+                EventManager::new(config.command_redundancy),
+                BlobManager::new(config.blob_bandwidth_limit),
+                RawPayloadManager::new(config.raw_payload_budget),
+                ConnectionQualityMonitor::new(config.connection_quality_thresholds),
+                DropLog::new(address, config.log_dropped_packets),
+                BackpressureMonitor::new(config.backpressure_thresholds),
+                StallMonitor::new(config.stall_thresholds),
+                config.replay_protection.then(ReplayWindow::new),
+            ),
+            entity_manager: ClientEntityManager::new(
+                config.entity_tombstone_duration,
+                config.keep_previous_entity_values,
+                config.correction_duration,
+                config.correction_curve,
+                config.extrapolation_model,
+                config.max_extrapolation,
+                config.late_update_policy,
             ),
-            entity_manager: ClientEntityManager::new(),
+            tick_interval: config.tick_interval,
+            scheduled_events: Vec::new(),
+            raw_payload_budget: config.raw_payload_budget,
+            event_packet_share: config.event_packet_share,
+            entity_packet_share: config.entity_packet_share,
+            client_tick: None,
+            packet_framing: None,
+            strict_protocol: config.strict_protocol,
+            protocol_violation_reported: false,
+            client_id: 0,
+            client_roster: ClientRoster::new(),
+            clock_sync: ClockSync::new(),
+            reorder_buffer: config.reorder_buffer_duration.map(ReorderBuffer::new),
+            manifest_revisions: HashSet::new(),
+            shutdown_received: false,
+            tick_rate_revision: 0,
         };
     }
 
-    pub fn get_outgoing_packet(&mut self, manifest: &Manifest<T, U>) -> Option<Box<[u8]>> {
-        if self.connection.has_outgoing_events() {
-            let mut writer = PacketWriter::new();
+    /// Carries the Entities & the record of received Events over from the
+    /// connection with a previous host
+    pub fn carry_over(&mut self, mut previous: ServerConnection<T, U>) {
+        previous.entity_manager.reset_for_migration();
+        self.entity_manager = previous.entity_manager;
+        self.connection
+            .carry_over_received_events(&mut previous.connection);
+    }
+
+    pub fn get_outgoing_packet(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<SocketAddr>,
+    ) -> Option<Box<[u8]>> {
+        let address = self.connection.get_address();
+        if self.connection.has_new_outgoing_command()
+            || custom_sections.has_outgoing(&address)
+            || self.connection.has_outgoing_events()
+            || self.connection.has_outgoing_rpcs()
+            || self.connection.has_outgoing_relay_events()
+            || self.connection.has_outgoing_blob_chunks()
+            || self.connection.has_outgoing_raw_payloads()
+        {
+            let mut writer = PacketWriter::from_factory(self.packet_framing.as_ref());
+            writer.set_packet_shares(self.event_packet_share, self.entity_packet_share);
 
             let next_packet_index: u16 = self.get_next_packet_index();
+
+            // commands are written first, newest first, so that the command of
+            // the current tick always fits
+            for (tick, command) in self.connection.get_outgoing_commands(next_packet_index) {
+                if !writer.write_command(manifest, tick, &command) {
+                    break;
+                }
+            }
+
             while let Some(popped_event) = self.connection.pop_outgoing_event(next_packet_index) {
-                if !writer.write_event(manifest, &popped_event) {
+                if !writer.write_event(manifest, popped_event.0, &popped_event.1) {
                     self.connection
                         .unpop_outgoing_event(next_packet_index, &popped_event);
                     break;
                 }
             }
+            while let Some(popped_rpc) = self.connection.pop_outgoing_rpc(next_packet_index) {
+                if !writer.write_rpc(manifest, &popped_rpc) {
+                    self.connection
+                        .unpop_outgoing_rpc(next_packet_index, &popped_rpc);
+                    break;
+                }
+            }
+            while let Some(popped_relay_event) =
+                self.connection.pop_outgoing_relay_event(next_packet_index)
+            {
+                let (event_id, recipient_id, event) = &popped_relay_event;
+                if !writer.write_relay_event(manifest, *event_id, *recipient_id, event) {
+                    self.connection
+                        .unpop_outgoing_relay_event(next_packet_index, &popped_relay_event);
+                    break;
+                }
+            }
+
+            while let Some(popped_raw_payload) = self.connection.pop_outgoing_raw_payload() {
+                if !writer.write_raw_payload(&popped_raw_payload, self.raw_payload_budget) {
+                    self.connection
+                        .unpop_outgoing_raw_payload(popped_raw_payload);
+                    break;
+                }
+            }
+
+            custom_sections.write_outgoing(&address, &mut writer);
+
+            // blob chunks are written last, so they only use the space other
+            // messages have left in the packet
+            while let Some(popped_chunk) =
+                self.connection.pop_outgoing_blob_chunk(next_packet_index)
+            {
+                if !writer.write_blob_chunk(&popped_chunk) {
+                    self.connection
+                        .unpop_outgoing_blob_chunk(next_packet_index, &popped_chunk);
+                    break;
+                }
+            }
 
             if writer.has_bytes() {
                 // Get bytes from writer
@@ -66,21 +190,258 @@ impl<T: EventType, U: EntityType> ServerConnection<T, U> {
         return self.entity_manager.pop_incoming_message();
     }
 
-    pub fn process_incoming_data(&mut self, manifest: &Manifest<T, U>, data: &mut [u8]) {
+    /// Applies the data of the packet whose header was just processed, or with
+    /// `Config::reorder_buffer_duration` set, holds it until every earlier
+    /// packet has been applied
+    pub fn process_incoming_data(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<SocketAddr>,
+        data: &mut [u8],
+    ) {
+        let tick = self.connection.get_remote_tick();
+        match (
+            &mut self.reorder_buffer,
+            self.connection.get_remote_sequence(),
+        ) {
+            (Some(reorder_buffer), Some(sequence)) => {
+                reorder_buffer.push(sequence, tick, data.to_vec().into_boxed_slice());
+                self.process_reordered_data(manifest, custom_sections);
+            }
+            _ => self.process_data(manifest, custom_sections, tick, data),
+        }
+    }
+
+    /// Applies the data of held packets which are ready, whether every packet
+    /// before them has been applied, or they've been held for
+    /// `Config::reorder_buffer_duration`
+    pub fn process_reordered_data(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<SocketAddr>,
+    ) {
+        while let Some((tick, mut data)) = self
+            .reorder_buffer
+            .as_mut()
+            .and_then(|reorder_buffer| reorder_buffer.pop_ready())
+        {
+            self.process_data(manifest, custom_sections, tick, &mut data);
+        }
+    }
+
+    fn process_data(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<SocketAddr>,
+        tick: Option<u16>,
+        data: &mut [u8],
+    ) {
+        let address = self.connection.get_address();
         let mut reader = PacketReader::new(data);
-        let start_manager_type: ManagerType = reader.read_u8().into();
-        if start_manager_type == ManagerType::Event {
-            self.connection.process_event_data(&mut reader, manifest);
+        while let Some(manager_type_byte) = reader.read_u8() {
+            let manager_type: ManagerType = manager_type_byte.into();
+            let readable = match manager_type {
+                ManagerType::Event => self.connection.process_event_data(&mut reader, manifest),
+                ManagerType::Rpc => self.connection.process_rpc_data(&mut reader, manifest),
+                ManagerType::Blob => self.connection.process_blob_data(&mut reader),
+                ManagerType::Raw => self.connection.process_raw_data(&mut reader),
+                ManagerType::TimedEvent => self
+                    .connection
+                    .process_timed_event_data(&mut reader, manifest),
+                ManagerType::Entity => self.entity_manager.process_data(
+                    &mut reader,
+                    tick,
+                    manifest,
+                    self.connection.get_drop_log_mut(),
+                ),
+                ManagerType::EntityEvent => self
+                    .connection
+                    .process_entity_event_data(&mut reader, manifest),
+                ManagerType::RelayEvent => self
+                    .connection
+                    .process_relay_event_data(&mut reader, manifest),
+                ManagerType::ClientRoster => self.connection.process_roster_data(&mut reader),
+                ManagerType::ManifestDelta => {
+                    self.connection.process_manifest_delta_data(&mut reader)
+                }
+                ManagerType::Shutdown => self.connection.process_shutdown_data(&mut reader),
+                ManagerType::TickRate => self.connection.process_tick_rate_change_data(&mut reader),
+                ManagerType::Custom => {
+                    let readable = custom_sections.process_data(&address, &mut reader);
+                    if !readable {
+                        self.connection.record_drop(DropReason::OversizedLength);
+                    }
+                    readable
+                }
+                _ => {
+                    self.connection.record_drop(DropReason::UnknownManagerType);
+                    false
+                }
+            };
+            if !readable {
+                break;
+            }
         }
-        if reader.has_more() {
-            self.entity_manager.process_data(&mut reader, manifest);
+    }
+
+    /// Gets the next Event targeted at an Entity, along with the Entity's key.
+    /// Events targeted at an Entity which isn't in scope, such as one deleted
+    /// since the Event was sent, are dropped
+    pub fn get_incoming_entity_event(&mut self) -> Option<(LocalEntityKey, T)> {
+        while let Some((local_key, event)) = self.connection.get_incoming_entity_event() {
+            if self.entity_manager.get_local_entity(local_key).is_some() {
+                return Some((local_key, event));
+            }
         }
+        return None;
+    }
+
+    /// Queues an Event to be relayed by the Server to the Client with the
+    /// given id
+    pub fn queue_relay_event(&mut self, recipient_id: ClientId, event: &dyn Event<T>) {
+        return self.connection.queue_relay_event(recipient_id, event);
+    }
+
+    /// Gets the next Event relayed by the Server from another Client, along
+    /// with the id of the Client which sent it
+    pub fn get_incoming_relay_event(&mut self) -> Option<(ClientId, T)> {
+        return self.connection.get_incoming_relay_event();
+    }
+
+    /// Applies the next received change to the set of connected Clients
+    /// which has an effect, returning it. Changes which arrive after a newer
+    /// change for the same Client are discarded
+    pub fn get_roster_change(&mut self) -> Option<ClientRosterChange> {
+        while let Some((revision, change)) = self.connection.get_incoming_roster_change() {
+            if self.client_roster.apply(revision, change) {
+                return Some(change);
+            }
+        }
+        return None;
+    }
+
+    /// Gets the next description of types the Server has registered since
+    /// the Client connected. A delta which arrives again, having been resent
+    /// after its acknowledgement was lost, is discarded
+    pub fn get_manifest_delta(&mut self) -> Option<ManifestDescriptor> {
+        while let Some((revision, delta)) = self.connection.get_incoming_manifest_delta() {
+            if self.manifest_revisions.insert(revision) {
+                return Some(delta);
+            }
+        }
+        return None;
+    }
+
+    /// Gets the reason the Server has given for shutting down, once. A notice
+    /// which arrives again, having been resent after its acknowledgement was
+    /// lost, is discarded
+    pub fn get_shutdown(&mut self) -> Option<String> {
+        while let Some(reason) = self.connection.get_incoming_shutdown() {
+            if !self.shutdown_received {
+                self.shutdown_received = true;
+                return Some(reason);
+            }
+        }
+        return None;
+    }
+
+    /// Gets the next change to the rate at which the Server ticks, which is
+    /// applied to the scheduling of timed Events. A change which arrives after
+    /// a newer one, or again after its acknowledgement was lost, is discarded
+    pub fn get_tick_rate_change(&mut self) -> Option<TickRateChange> {
+        while let Some(change) = self.connection.get_incoming_tick_rate_change() {
+            if change.revision > self.tick_rate_revision {
+                self.tick_rate_revision = change.revision;
+                self.tick_interval = change.tick_interval;
+                return Some(change);
+            }
+        }
+        return None;
+    }
+
+    pub fn get_client_roster(&self) -> &ClientRoster {
+        return &self.client_roster;
+    }
+
+    /// Gets the next timed Event whose scheduled Server tick has been reached,
+    /// along with that tick
+    pub fn get_due_timed_event(&mut self) -> Option<(u16, T)> {
+        // schedule newly received timed events, estimating when the Server will
+        // reach the scheduled tick, given that the Event was sent half a round
+        // trip ago
+        while let Some((tick, ticks_remaining, event)) = self.connection.get_incoming_timed_event()
+        {
+            let half_rtt = Duration::from_secs_f32(self.connection.get_rtt() / 2000.0);
+            let delay = (self.tick_interval * ticks_remaining.max(0) as u32)
+                .checked_sub(half_rtt)
+                .unwrap_or_else(|| Duration::from_secs(0));
+            self.scheduled_events.push((Timer::new(delay), tick, event));
+        }
+
+        let index = self
+            .scheduled_events
+            .iter()
+            .position(|(timer, _, _)| timer.ringing())?;
+        let (_, tick, event) = self.scheduled_events.remove(index);
+        return Some((tick, event));
     }
 
     pub fn get_local_entity(&self, key: LocalEntityKey) -> Option<&U> {
         return self.entity_manager.get_local_entity(key);
     }
 
+    pub fn get_previous_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.entity_manager.get_previous_entity(key);
+    }
+
+    pub fn get_all_entities(&self) -> Vec<(LocalEntityKey, &U)> {
+        return self.entity_manager.get_all_entities();
+    }
+
+    pub fn get_late_entity(&self, key: LocalEntityKey) -> Option<&U> {
+        return self.entity_manager.get_late_entity(key);
+    }
+
+    pub fn set_late_update_policy(&mut self, type_id: TypeId, policy: LateUpdatePolicy) {
+        self.entity_manager.set_late_update_policy(type_id, policy);
+    }
+
+    pub fn get_late_update_stats(&self) -> LateUpdateStats {
+        return self.entity_manager.get_late_update_stats();
+    }
+
+    pub fn debug_dump_entities(&self, manifest: &Manifest<T, U>) -> EntityDebugDump {
+        return self.entity_manager.debug_dump(manifest);
+    }
+
+    pub fn get_entities_of_type(&self, type_id: TypeId) -> Vec<(LocalEntityKey, &U)> {
+        return self.entity_manager.get_entities_of_type(type_id);
+    }
+
+    pub fn get_entity_count(&self) -> usize {
+        return self.entity_manager.get_entity_count();
+    }
+
+    pub fn spawn_predicted_entity(&mut self, entity: U) -> PredictionKey {
+        return self.entity_manager.spawn_predicted_entity(entity);
+    }
+
+    pub fn get_predicted_entity(&self, key: PredictionKey) -> Option<&U> {
+        return self.entity_manager.get_predicted_entity(key);
+    }
+
+    pub fn remove_predicted_entity(&mut self, key: PredictionKey) -> Option<U> {
+        return self.entity_manager.remove_predicted_entity(key);
+    }
+
+    pub fn get_snapshot_progress(&self) -> Option<(u32, u32)> {
+        return self.entity_manager.get_snapshot_progress();
+    }
+
+    pub fn is_world_synced(&self) -> bool {
+        return self.entity_manager.is_world_synced();
+    }
+
     // Pass-through methods to underlying common connection
 
     pub fn mark_sent(&mut self) {
@@ -100,7 +461,133 @@ impl<T: EventType, U: EntityType> ServerConnection<T, U> {
     }
 
     pub fn process_incoming_header(&mut self, payload: &[u8]) -> Box<[u8]> {
-        return self.connection.process_incoming_header(payload, &mut None);
+        let stripped_payload = self.connection.process_incoming_header(payload, &mut None);
+        // packets without data, such as heartbeats, take up a sequence number
+        // too, which held packets shouldn't wait for
+        if let (Some(reorder_buffer), Some(sequence)) = (
+            &mut self.reorder_buffer,
+            self.connection.get_remote_sequence(),
+        ) {
+            if PacketType::get_from_packet(payload) != Some(PacketType::Data) {
+                reorder_buffer.mark_received(sequence);
+            }
+        }
+        return stripped_payload;
+    }
+
+    /// Advances the Client's tick by the given number of ticks, which is
+    /// written into the header of every outgoing packet. The tick is synced to
+    /// run ahead of the Server's by the round trip time plus the target depth
+    /// of the Server's input buffer, whenever it isn't ahead of the Server's
+    /// most recently received tick
+    pub fn advance_client_tick(&mut self, tick_count: u16, target_depth: u16) {
+        let server_tick = match self.connection.get_remote_tick() {
+            Some(server_tick) => server_tick,
+            None => return,
+        };
+        if self.tick_interval == Duration::from_secs(0) {
+            return;
+        }
+        let client_tick = match self.client_tick {
+            Some(client_tick) if sequence_greater_than(client_tick, server_tick) => {
+                client_tick.wrapping_add(tick_count)
+            }
+            _ => {
+                // the Server's tick was sent half a round trip ago, and what is
+                // sent now arrives half a round trip from now
+                let tick_ms = self.tick_interval.as_secs_f32() * 1000.0;
+                let latency_ticks = (self.connection.get_rtt() / tick_ms).ceil() as u16;
+                server_tick
+                    .wrapping_add(latency_ticks)
+                    .wrapping_add(target_depth)
+            }
+        };
+        self.client_tick = Some(client_tick);
+        self.connection.set_host_tick(Some(client_tick));
+    }
+
+    pub fn record_correction(&mut self, key: LocalEntityKey, error: &[f32]) {
+        self.entity_manager.record_correction(key, error);
+    }
+
+    pub fn get_correction_offset(&self, key: LocalEntityKey) -> Option<&[f32]> {
+        return self.entity_manager.get_correction_offset(key);
+    }
+
+    pub fn advance_corrections(&mut self, elapsed: Duration) {
+        self.entity_manager.advance_corrections(elapsed);
+    }
+
+    pub fn sample_motion(&mut self, key: LocalEntityKey, motion_sampler: &MotionSampler<U>) {
+        self.entity_manager.sample_motion(key, motion_sampler);
+    }
+
+    pub fn get_extrapolated_position(&self, key: LocalEntityKey) -> Option<Vec<f32>> {
+        return self.entity_manager.get_extrapolated_position(key);
+    }
+
+    pub fn advance_extrapolations(&mut self, elapsed: Duration) {
+        self.entity_manager.advance_extrapolations(elapsed);
+    }
+
+    pub fn queue_command(&mut self, tick: u16, command: &impl Event<T>) {
+        return self.connection.queue_command(tick, command);
+    }
+
+    pub fn get_client_tick(&self) -> Option<u16> {
+        return self.client_tick;
+    }
+
+    pub fn get_server_tick(&self) -> Option<u16> {
+        return self.connection.get_remote_tick();
+    }
+
+    /// Reads the Server's clock from the front of a heartbeat's payload, &
+    /// records it to estimate the Server's time. Returns the rest of the
+    /// payload, which is the application's
+    pub fn read_heartbeat_clock(&mut self, payload: &[u8]) -> Box<[u8]> {
+        let mut reader = PacketReader::new(payload);
+        let server_clock = match reader.get_cursor().read_u64::<BigEndian>() {
+            Ok(server_clock) => server_clock,
+            Err(_) => return Box::new([]),
+        };
+        self.clock_sync.record(
+            Duration::from_micros(server_clock),
+            self.connection.get_rtt(),
+        );
+        return payload[8..].to_vec().into_boxed_slice();
+    }
+
+    pub fn get_server_time(&self) -> Option<Duration> {
+        return self.clock_sync.get_remote_time();
+    }
+
+    pub fn get_clock_skew(&self) -> f64 {
+        return self.clock_sync.get_skew();
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.connection.set_capabilities(capabilities);
+    }
+
+    pub fn get_capabilities(&self) -> Capabilities {
+        return self.connection.get_capabilities();
+    }
+
+    pub fn has_compact_headers(&self) -> bool {
+        return self.connection.has_compact_headers();
+    }
+
+    pub fn set_client_id(&mut self, client_id: ClientId) {
+        self.client_id = client_id;
+    }
+
+    pub fn get_client_id(&self) -> ClientId {
+        return self.client_id;
+    }
+
+    pub fn get_time_dilation(&self) -> i8 {
+        return self.connection.get_remote_time_dilation();
     }
 
     pub fn process_outgoing_header(
@@ -117,15 +604,138 @@ impl<T: EventType, U: EntityType> ServerConnection<T, U> {
         return self.connection.get_next_packet_index();
     }
 
-    pub fn queue_event(&mut self, event: &impl Event<T>) {
+    pub fn queue_event(&mut self, event: &dyn Event<T>) -> EventId {
         return self.connection.queue_event(event);
     }
 
+    pub fn queue_event_with_ttl(&mut self, event: &dyn Event<T>, ttl: Duration) -> EventId {
+        return self.connection.queue_event_with_ttl(event, ttl);
+    }
+
     pub fn get_incoming_event(&mut self) -> Option<T> {
         return self.connection.get_incoming_event();
     }
 
+    pub fn get_delivered_event_id(&mut self) -> Option<EventId> {
+        return self.connection.get_delivered_event_id();
+    }
+
+    pub fn get_expired_event_id(&mut self) -> Option<EventId> {
+        return self.connection.get_expired_event_id();
+    }
+
+    pub fn queue_raw_payload(&mut self, payload: &[u8]) -> bool {
+        return self.connection.queue_raw_payload(payload);
+    }
+
+    pub fn get_incoming_raw_payload(&mut self) -> Option<Box<[u8]>> {
+        return self.connection.get_incoming_raw_payload();
+    }
+
+    pub fn queue_blob(&mut self, data: &[u8]) -> Option<BlobId> {
+        return self.connection.queue_blob(data);
+    }
+
+    pub fn get_outgoing_blob_progress(&self, blob_id: BlobId) -> Option<(u16, u16)> {
+        return self.connection.get_outgoing_blob_progress(blob_id);
+    }
+
+    pub fn get_blob_message(&mut self) -> Option<BlobMessage> {
+        return self.connection.get_blob_message();
+    }
+
+    pub fn queue_rpc(&mut self, rpc: RpcMessage<&dyn Event<T>>) {
+        return self.connection.queue_rpc(rpc);
+    }
+
+    pub fn get_incoming_rpc(&mut self) -> Option<RpcMessage<T>> {
+        return self.connection.get_incoming_rpc();
+    }
+
     pub fn get_rtt(&self) -> f32 {
         return self.connection.get_rtt();
     }
+
+    pub fn get_rtt_stats(&self) -> Option<RttStats> {
+        return self.connection.get_rtt_stats();
+    }
+
+    pub fn record_drop(&mut self, reason: DropReason) {
+        self.connection.record_drop(reason);
+    }
+
+    pub fn get_connection_stats(&self) -> ConnectionStats {
+        return self.connection.get_connection_stats();
+    }
+
+    pub fn get_event_queue_stats(&self, manager_type: ManagerType) -> EventQueueStats {
+        return self.connection.get_event_queue_stats(manager_type);
+    }
+
+    pub fn get_event_type_queue_stats(&self, type_id: TypeId) -> EventQueueStats {
+        return self.connection.get_event_type_queue_stats(type_id);
+    }
+
+    /// Returns whether the remote host has violated the protocol, when
+    /// `Config::strict_protocol` is enabled, after which its packets are
+    /// ignored until the connection is closed
+    pub fn has_protocol_violation(&self) -> bool {
+        return self.strict_protocol && self.connection.get_first_drop().is_some();
+    }
+
+    /// Gets the reason the remote host has violated the protocol, when
+    /// `Config::strict_protocol` is enabled, only the first time it is called
+    /// after the violation
+    pub fn take_protocol_violation(&mut self) -> Option<DropReason> {
+        if !self.strict_protocol || self.protocol_violation_reported {
+            return None;
+        }
+        let reason = self.connection.get_first_drop()?;
+        self.protocol_violation_reported = true;
+        return Some(reason);
+    }
+
+    pub fn set_packet_framing(&mut self, packet_framing: Option<PacketFramingFactory>) {
+        self.packet_framing = packet_framing;
+    }
+
+    pub fn set_heartbeat_payload(&mut self, payload: &[u8]) -> bool {
+        return self.connection.set_heartbeat_payload(payload);
+    }
+
+    pub fn get_heartbeat_payload(&self) -> &[u8] {
+        return self.connection.get_heartbeat_payload();
+    }
+
+    pub fn get_packet_loss(&self) -> Option<f32> {
+        return self.connection.get_packet_loss();
+    }
+
+    pub fn get_ack_latency(&self) -> &AckLatencyHistogram {
+        return self.connection.get_ack_latency();
+    }
+
+    pub fn clear_ack_latency(&mut self) {
+        self.connection.clear_ack_latency();
+    }
+
+    pub fn update_quality(&mut self) -> Option<ConnectionQuality> {
+        return self.connection.update_quality();
+    }
+
+    pub fn get_quality(&self) -> Option<ConnectionQuality> {
+        return self.connection.get_quality();
+    }
+
+    pub fn update_backpressure(&mut self) -> Option<Congestion> {
+        return self.connection.update_backpressure(0);
+    }
+
+    pub fn is_congested(&self) -> bool {
+        return self.connection.is_congested();
+    }
+
+    pub fn should_drop_unguaranteed(&self) -> bool {
+        return self.connection.should_drop_unguaranteed();
+    }
 }
@@ -0,0 +1,228 @@
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use naia_client_socket::{ClientSocket, MessageSender, Packet, SocketEvent};
+use naia_shared::{
+    Instant, NaiaError, PacketReader, PacketType, ServerListPage, ServerStatus, UnconnectedKind,
+    UnconnectedMessage, MAX_UNCONNECTED_MESSAGE_SIZE,
+};
+
+use super::connect_socket::connect_socket;
+
+/// How long a query waits for the Server to answer before it is considered
+/// lost
+pub const SERVER_QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// An answer to a query made with a ServerQuery, along with the token the
+/// query was given
+#[derive(Debug)]
+pub enum ServerQueryResponse {
+    /// The Server answered a ping, after the given Round Trip Time
+    Pong(u32, Duration),
+    /// The Server answered a status query with the status set with
+    /// `NaiaServer::set_server_status()`, after the given Round Trip Time
+    Status(u32, ServerStatus, Duration),
+    /// The Server application answered a message with
+    /// `NaiaServer::send_unconnected_response()`
+    Message(u32, Box<[u8]>),
+    /// A master server answered a server list query with a page of its list,
+    /// after the given Round Trip Time
+    ServerList(u32, ServerListPage, Duration),
+    /// The Server answered a public address query with the address it
+    /// received the query from, after the given Round Trip Time
+    PublicAddress(u32, SocketAddr, Duration),
+    /// The Server did not answer within `SERVER_QUERY_TIMEOUT`
+    TimedOut(u32),
+}
+
+/// Exchanges small out-of-band messages with a Server without connecting to
+/// it, so that a server browser can ping a Server, or query its status, in a
+/// single round trip. A Server only answers a few requests per second from
+/// each IP address, as set by `Config::unconnected_request_rate`
+#[derive(Debug)]
+pub struct ServerQuery {
+    socket: ClientSocket,
+    sender: MessageSender,
+    next_token: u32,
+    pending_queries: HashMap<u32, (UnconnectedKind, Instant)>,
+}
+
+impl ServerQuery {
+    /// Creates a new ServerQuery, given the Server's address
+    pub fn new(server_address: SocketAddr) -> Self {
+        let mut socket = connect_socket(server_address);
+        let sender = socket.get_sender();
+        ServerQuery {
+            socket,
+            sender,
+            next_token: 0,
+            pending_queries: HashMap::new(),
+        }
+    }
+
+    /// Get the address of the Server being queried
+    pub fn server_address(&self) -> SocketAddr {
+        return self.socket.server_address();
+    }
+
+    /// Pings the Server, returning the token of the query, or an error if the
+    /// query could not be sent
+    pub fn ping(&mut self) -> Result<u32, NaiaError> {
+        return self.send_query(UnconnectedKind::Ping, &[], 0);
+    }
+
+    /// Queries the Server's status, such as its name, map & user count,
+    /// returning the token of the query. The query is padded to the size of
+    /// the largest possible answer, as the Server never answers with more
+    /// bytes than it received
+    pub fn request_status(&mut self) -> Result<u32, NaiaError> {
+        return self.send_query(UnconnectedKind::Status, &[], MAX_UNCONNECTED_MESSAGE_SIZE);
+    }
+
+    /// Queries the list of Servers registered with a master server, enabled
+    /// with `NaiaServer::enable_master_server()`, returning the token of the
+    /// query. The list is answered a page at a time, starting at the given
+    /// index; the page tells the total number of Servers listed, so the next
+    /// page can be requested. The query is padded like a status query
+    pub fn request_server_list(&mut self, start: u16) -> Result<u32, NaiaError> {
+        return self.send_query(
+            UnconnectedKind::List,
+            &start.to_be_bytes(),
+            MAX_UNCONNECTED_MESSAGE_SIZE,
+        );
+    }
+
+    /// Asks the Server, acting as a reflector, for the address it receives
+    /// this ServerQuery's packets from, returning the token of the query.
+    /// Behind a NAT, this is the public address others can reach this host
+    /// at, as long as the NAT maps every destination to the same address.
+    /// The query is padded like a status query
+    pub fn discover_public_address(&mut self) -> Result<u32, NaiaError> {
+        return self.send_query(UnconnectedKind::Reflect, &[], MAX_UNCONNECTED_MESSAGE_SIZE);
+    }
+
+    /// Sends a message to the Server application, which emits it in a
+    /// `ServerEvent::UnconnectedMessage`, and returns the token of the query.
+    /// Returns an error if the data is longer than `MAX_UNCONNECTED_DATA_SIZE`
+    pub fn send_message(&mut self, data: &[u8]) -> Result<u32, NaiaError> {
+        return self.send_query(UnconnectedKind::Message, data, 0);
+    }
+
+    /// Returns the number of queries which have yet to be answered or to time
+    /// out
+    pub fn pending_count(&self) -> usize {
+        return self.pending_queries.len();
+    }
+
+    /// Must be called regularly, returns the next answer received from the
+    /// Server, or the next query which has timed out
+    pub fn receive(&mut self) -> Option<ServerQueryResponse> {
+        loop {
+            let packet = match self.socket.receive() {
+                Ok(SocketEvent::Packet(packet)) => packet,
+                Ok(SocketEvent::None) => break,
+                // packets from other addresses are reported as errors, and
+                // are left for the next call
+                Err(_) => break,
+            };
+            if PacketType::get_from_packet(packet.payload())
+                != Some(PacketType::UnconnectedResponse)
+            {
+                continue;
+            }
+            let payload = match naia_shared::utils::read_headerless_payload(packet.payload()) {
+                Some(payload) => payload,
+                None => continue,
+            };
+            let mut reader = PacketReader::new(&payload);
+            let response = match UnconnectedMessage::read(&mut reader) {
+                Some(response) => response,
+                None => continue,
+            };
+            let sent_at = match self.pending_queries.get(&response.token) {
+                Some((kind, sent_at)) if *kind == response.kind => sent_at,
+                _ => continue,
+            };
+            let elapsed = sent_at.elapsed();
+            let rtt = Duration::new(elapsed.as_secs(), elapsed.subsec_nanos());
+            self.pending_queries.remove(&response.token);
+
+            match response.kind {
+                UnconnectedKind::Ping => {
+                    return Some(ServerQueryResponse::Pong(response.token, rtt));
+                }
+                UnconnectedKind::Status => {
+                    let mut status_reader = PacketReader::new(&response.data);
+                    if let Some(status) = ServerStatus::read(&mut status_reader) {
+                        return Some(ServerQueryResponse::Status(response.token, status, rtt));
+                    }
+                }
+                UnconnectedKind::Message => {
+                    return Some(ServerQueryResponse::Message(response.token, response.data));
+                }
+                UnconnectedKind::List => {
+                    let mut page_reader = PacketReader::new(&response.data);
+                    if let Some(page) = ServerListPage::read(&mut page_reader) {
+                        return Some(ServerQueryResponse::ServerList(response.token, page, rtt));
+                    }
+                }
+                UnconnectedKind::Reflect => {
+                    let mut address_reader = PacketReader::new(&response.data);
+                    if let Some(address) =
+                        naia_shared::utils::read_socket_address(&mut address_reader)
+                    {
+                        return Some(ServerQueryResponse::PublicAddress(
+                            response.token,
+                            address,
+                            rtt,
+                        ));
+                    }
+                }
+                // never queried
+                UnconnectedKind::Register | UnconnectedKind::Deregister => {}
+            }
+        }
+
+        let timed_out_token = self
+            .pending_queries
+            .iter()
+            .find(|(_, (_, sent_at))| {
+                let elapsed = sent_at.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos()) >= SERVER_QUERY_TIMEOUT
+            })
+            .map(|(token, _)| *token);
+        if let Some(token) = timed_out_token {
+            self.pending_queries.remove(&token);
+            return Some(ServerQueryResponse::TimedOut(token));
+        }
+        return None;
+    }
+
+    fn send_query(
+        &mut self,
+        kind: UnconnectedKind,
+        data: &[u8],
+        padded_size: usize,
+    ) -> Result<u32, NaiaError> {
+        let token = self.next_token;
+        let query = match UnconnectedMessage::new(kind, token, data) {
+            Some(query) => query,
+            None => return Err(NaiaError::PayloadTooLarge(data.len())),
+        };
+        self.next_token = self.next_token.wrapping_add(1);
+
+        let mut query_bytes = Vec::new();
+        query.write(&mut query_bytes);
+        if query_bytes.len() < padded_size {
+            query_bytes.resize(padded_size, 0);
+        }
+        let payload = naia_shared::utils::write_connectionless_payload(
+            PacketType::UnconnectedRequest,
+            &query_bytes,
+        );
+        if let Err(error) = self.sender.send(Packet::new_raw(payload)) {
+            return Err(NaiaError::Wrapped(error));
+        }
+        self.pending_queries.insert(token, (kind, Instant::now()));
+        return Ok(token);
+    }
+}
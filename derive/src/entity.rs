@@ -30,9 +30,18 @@ pub fn entity_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let state_mask_size: u8 = (((properties.len() - 1) / 8) + 1) as u8;
 
+    let unguaranteed_properties = utils::get_properties_with_attribute(&input, "unguaranteed");
+    let get_unguaranteed_state_mask_method =
+        get_unguaranteed_state_mask_method(&enum_name, state_mask_size, &unguaranteed_properties);
+
+    let entity_name_string = entity_name.to_string();
+    let property_count = properties.len() as u8;
+    let version = utils::get_version(&input);
+    let properties_method = utils::get_properties_method(&properties);
+
     let gen = quote! {
         use std::{any::{TypeId}, rc::Rc, cell::RefCell, io::Cursor};
-        use naia_shared::{StateMask, EntityBuilder, EntityMutator, PropertyIo};
+        use naia_shared::{StateMask, EntityBuilder, EntityMutator, PropertyDescriptor, PropertyIo};
         #property_enum
         pub struct #entity_builder_name {
             type_id: TypeId,
@@ -41,9 +50,19 @@ pub fn entity_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             fn get_type_id(&self) -> TypeId {
                 return self.type_id;
             }
-            fn build(&self, buffer: &[u8]) -> #type_name {
+            fn build(&self, buffer: &[u8]) -> Option<#type_name> {
                 return #entity_name::read_to_type(buffer);
             }
+            fn get_type_name(&self) -> &'static str {
+                return #entity_name_string;
+            }
+            fn get_property_count(&self) -> u8 {
+                return #property_count;
+            }
+            fn get_version(&self) -> u8 {
+                return #version;
+            }
+            #properties_method
         }
         impl #entity_name {
             pub fn get_builder() -> Box<dyn EntityBuilder<#type_name>> {
@@ -59,6 +78,8 @@ pub fn entity_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
         impl Entity<#type_name> for #entity_name {
             fn get_state_mask_size(&self) -> u8 { #state_mask_size }
+            fn get_property_count(&self) -> u8 { #property_count }
+            #get_unguaranteed_state_mask_method
             fn get_type_id(&self) -> TypeId {
                 return TypeId::of::<#entity_name>();
             }
@@ -104,6 +125,38 @@ fn get_property_enum(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -> Toke
     };
 }
 
+fn get_unguaranteed_state_mask_method(
+    enum_name: &Ident,
+    state_mask_size: u8,
+    unguaranteed_properties: &Vec<Ident>,
+) -> TokenStream {
+    let mut output = quote! {};
+
+    for field_name in unguaranteed_properties.iter() {
+        let uppercase_variant_name = Ident::new(
+            field_name.to_string().to_uppercase().as_str(),
+            Span::call_site(),
+        );
+
+        let new_output_right = quote! {
+            state_mask.set_bit(#enum_name::#uppercase_variant_name as u8, true);
+        };
+        let new_output_result = quote! {
+            #output
+            #new_output_right
+        };
+        output = new_output_result;
+    }
+
+    return quote! {
+        fn get_unguaranteed_state_mask(&self) -> StateMask {
+            let mut state_mask = StateMask::new(#state_mask_size);
+            #output
+            return state_mask;
+        }
+    };
+}
+
 fn get_set_mutator_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
     let mut output = quote! {};
 
@@ -194,7 +247,7 @@ fn get_read_to_type_method(
 
         let new_output_right = quote! {
             let mut #field_name = Property::<#field_type>::new(Default::default(), #enum_name::#uppercase_variant_name as u8);
-            #field_name.read(read_cursor);
+            #field_name.read(read_cursor)?;
         };
         let new_output_result = quote! {
             #prop_reads
@@ -204,13 +257,13 @@ fn get_read_to_type_method(
     }
 
     return quote! {
-        fn read_to_type(buffer: &[u8]) -> #type_name {
+        fn read_to_type(buffer: &[u8]) -> Option<#type_name> {
             let read_cursor = &mut Cursor::new(buffer);
             #prop_reads
 
-            return #type_name::#entity_name(Rc::new(RefCell::new(#entity_name {
+            return Some(#type_name::#entity_name(Rc::new(RefCell::new(#entity_name {
                 #prop_names
-            })));
+            }))));
         }
     };
 }
@@ -269,14 +322,29 @@ fn get_write_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>)
 }
 
 fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
+    let mut checks = quote! {};
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
+    for (field_name, field_type) in properties.iter() {
         let uppercase_variant_name = Ident::new(
             field_name.to_string().to_uppercase().as_str(),
             Span::call_site(),
         );
 
+        let new_check_right = quote! {
+            if let Some(true) = state_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
+                let mut #field_name = Property::<#field_type>::new(Default::default(), 0);
+                if #field_name.read(read_cursor).is_none() {
+                    return false;
+                }
+            }
+        };
+        let new_check_result = quote! {
+            #checks
+            #new_check_right
+        };
+        checks = new_check_result;
+
         let new_output_right = quote! {
             if let Some(true) = state_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
                 PropertyIo::read(&mut self.#field_name, read_cursor);
@@ -289,10 +357,15 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
         output = new_output_result;
     }
 
+    // every Property is read once before any is updated, so that a malformed
+    // payload leaves the Entity untouched
     return quote! {
-        fn read_partial(&mut self, state_mask: &StateMask, buffer: &[u8]) {
+        fn read_partial(&mut self, state_mask: &StateMask, buffer: &[u8]) -> bool {
+            let read_cursor = &mut Cursor::new(buffer);
+            #checks
             let read_cursor = &mut Cursor::new(buffer);
             #output
+            return true;
         }
     };
 }
@@ -301,6 +374,7 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
 //#[derive(Entity)]
 //#[type_name = "ExampleEntity"]
 //pub struct PointEntity {
+//    #[unguaranteed]
 //    pub x: Property<u8>,
 //    pub y: Property<u8>,
 //}
@@ -315,12 +389,27 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
 //    type_id: TypeId,
 //}
 //impl EntityBuilder<ExampleEntity> for PointEntityBuilder {
-//    fn build(&self, buffer: &[u8]) -> ExampleEntity {
+//    fn build(&self, buffer: &[u8]) -> Option<ExampleEntity> {
 //        return PointEntity::read_to_type(buffer);
 //    }
 //    fn get_type_id(&self) -> TypeId {
 //        return self.type_id;
 //    }
+//    fn get_type_name(&self) -> &'static str {
+//        return "PointEntity";
+//    }
+//    fn get_property_count(&self) -> u8 {
+//        return 3;
+//    }
+//    fn get_version(&self) -> u8 {
+//        return 0;
+//    }
+//    fn get_properties(&self) -> Vec<PropertyDescriptor> {
+//        return vec![
+//            PropertyDescriptor::new::<u8>("x", "u8"),
+//            PropertyDescriptor::new::<u8>("y", "u8"),
+//        ];
+//    }
 //}
 //impl PointEntity {
 //    pub fn get_builder() -> Box<dyn EntityBuilder<ExampleEntity>> {
@@ -337,23 +426,28 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
 //            y: Property::<u8>::new(y, PointEntityProp::Y as u8),
 //        }
 //    }
-//    fn read_to_type(buffer: &[u8]) -> ExampleEntity {
+//    fn read_to_type(buffer: &[u8]) -> Option<ExampleEntity> {
 //        let read_cursor = &mut Cursor::new(buffer);
 //        let mut x = Property::<u8>::new(Default::default(), PointEntityProp::X
-// as u8);        x.read(read_cursor);
+// as u8);        x.read(read_cursor)?;
 //        let mut y = Property::<u8>::new(Default::default(), PointEntityProp::Y
-// as u8);        y.read(read_cursor);
+// as u8);        y.read(read_cursor)?;
 //
-//        return ExampleEntity::PointEntity(Rc::new(RefCell::new(PointEntity {
+//        return Some(ExampleEntity::PointEntity(Rc::new(RefCell::new(PointEntity {
 //            x,
 //            y,
-//        })));
+//        }))));
 //    }
 //}
 //impl Entity<ExampleEntity> for PointEntity {
 //    fn get_state_mask_size(&self) -> u8 {
 //        1
 //    }
+//    fn get_unguaranteed_state_mask(&self) -> StateMask {
+//        let mut state_mask = StateMask::new(1);
+//        state_mask.set_bit(PointEntityProp::X as u8, true);
+//        return state_mask;
+//    }
 //    fn get_typed_copy(&self) -> ExampleEntity {
 //        let copied_entity = PointEntity::new_complete(*self.x.get(),
 // *self.y.get()).wrap();        return
@@ -373,7 +467,20 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
 //            PropertyIo::write(&self.y, buffer);
 //        }
 //    }
-//    fn read_partial(&mut self, state_mask: &StateMask, buffer: &[u8]) {
+//    fn read_partial(&mut self, state_mask: &StateMask, buffer: &[u8]) -> bool {
+//        let read_cursor = &mut Cursor::new(buffer);
+//        if let Some(true) = state_mask.get_bit(PointEntityProp::X as u8) {
+//            let mut x = Property::<u8>::new(Default::default(), 0);
+//            if x.read(read_cursor).is_none() {
+//                return false;
+//            }
+//        }
+//        if let Some(true) = state_mask.get_bit(PointEntityProp::Y as u8) {
+//            let mut y = Property::<u8>::new(Default::default(), 0);
+//            if y.read(read_cursor).is_none() {
+//                return false;
+//            }
+//        }
 //        let read_cursor = &mut Cursor::new(buffer);
 //        if let Some(true) = state_mask.get_bit(PointEntityProp::X as u8) {
 //            PropertyIo::read(&mut self.x, read_cursor);
@@ -381,6 +488,7 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
 //        if let Some(true) = state_mask.get_bit(PointEntityProp::Y as u8) {
 //            PropertyIo::read(&mut self.y, read_cursor);
 //        }
+//        return true;
 //    }
 //    fn set_mutator(&mut self, mutator: &Rc<RefCell<dyn EntityMutator>>) {
 //        self.x.set_mutator(mutator);
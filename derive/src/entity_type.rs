@@ -8,15 +8,33 @@ pub fn entity_type_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStre
     let type_name = input.ident;
 
     let variants = get_variants(&type_name, &input.data);
+    let type_id_variants = get_type_id_variants(&type_name, &input.data);
+    let copy_variants = get_copy_variants(&type_name, &input.data);
+    let entity_ref_variants = get_entity_ref_variants(&type_name, &input.data);
 
     let gen = quote! {
         use naia_shared::{EntityType, Entity, StateMask};
         impl EntityType for #type_name {
-            fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]) {
+            fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]) -> bool {
                 match self {
                     #variants
                 }
             }
+            fn get_type_id(&self) -> std::any::TypeId {
+                match self {
+                    #type_id_variants
+                }
+            }
+            fn get_copy(&self) -> #type_name {
+                match self {
+                    #copy_variants
+                }
+            }
+            fn get_entity_ref(&self) -> std::rc::Rc<std::cell::RefCell<dyn Entity<#type_name>>> {
+                match self {
+                    #entity_ref_variants
+                }
+            }
         }
     };
 
@@ -31,7 +49,76 @@ fn get_variants(type_name: &Ident, data: &Data) -> TokenStream {
                 let variant_name = &variant.ident;
                 let new_output_right = quote! {
                     #type_name::#variant_name(identity) => {
-                        identity.as_ref().borrow_mut().read_partial(state_mask, bytes);
+                        return identity.as_ref().borrow_mut().read_partial(state_mask, bytes);
+                    }
+                };
+                let new_output_result = quote! {
+                    #output
+                    #new_output_right
+                };
+                output = new_output_result;
+            }
+            output
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn get_type_id_variants(type_name: &Ident, data: &Data) -> TokenStream {
+    match *data {
+        Data::Enum(ref data) => {
+            let mut output = quote! {};
+            for variant in data.variants.iter() {
+                let variant_name = &variant.ident;
+                let new_output_right = quote! {
+                    #type_name::#variant_name(identity) => {
+                        return identity.as_ref().borrow().get_type_id();
+                    }
+                };
+                let new_output_result = quote! {
+                    #output
+                    #new_output_right
+                };
+                output = new_output_result;
+            }
+            output
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn get_copy_variants(type_name: &Ident, data: &Data) -> TokenStream {
+    match *data {
+        Data::Enum(ref data) => {
+            let mut output = quote! {};
+            for variant in data.variants.iter() {
+                let variant_name = &variant.ident;
+                let new_output_right = quote! {
+                    #type_name::#variant_name(identity) => {
+                        return identity.as_ref().borrow().get_typed_copy();
+                    }
+                };
+                let new_output_result = quote! {
+                    #output
+                    #new_output_right
+                };
+                output = new_output_result;
+            }
+            output
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn get_entity_ref_variants(type_name: &Ident, data: &Data) -> TokenStream {
+    match *data {
+        Data::Enum(ref data) => {
+            let mut output = quote! {};
+            for variant in data.variants.iter() {
+                let variant_name = &variant.ident;
+                let new_output_right = quote! {
+                    #type_name::#variant_name(identity) => {
+                        return identity.clone();
                     }
                 };
                 let new_output_result = quote! {
@@ -54,11 +141,32 @@ fn get_variants(type_name: &Ident, data: &Data) -> TokenStream {
 
 ////TO THIS
 //impl EntityType for ExampleEntity {
-//    fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]) {
+//    fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]) -> bool {
 //        match self {
 //            ExampleEntity::PointEntity(identity) => {
-//                identity.as_ref().borrow_mut().read_partial(state_mask,
+//                return identity.as_ref().borrow_mut().read_partial(state_mask,
 // bytes);            }
 //        }
 //    }
+//    fn get_type_id(&self) -> std::any::TypeId {
+//        match self {
+//            ExampleEntity::PointEntity(identity) => {
+//                return identity.as_ref().borrow().get_type_id();
+//            }
+//        }
+//    }
+//    fn get_copy(&self) -> ExampleEntity {
+//        match self {
+//            ExampleEntity::PointEntity(identity) => {
+//                return identity.as_ref().borrow().get_typed_copy();
+//            }
+//        }
+//    }
+//    fn get_entity_ref(&self) -> Rc<RefCell<dyn Entity<ExampleEntity>>> {
+//        match self {
+//            ExampleEntity::PointEntity(identity) => {
+//                return identity.clone();
+//            }
+//        }
+//    }
 //}
@@ -23,9 +23,16 @@ pub fn event_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let read_to_type_method = get_read_to_type_method(&type_name, event_name, &properties);
 
+    let event_name_string = event_name.to_string();
+    let property_count = properties.len() as u8;
+    let version = utils::get_version(&input);
+    let properties_method = utils::get_properties_method(&properties);
+
+    let merge_methods = get_merge_methods(&type_name, event_name, &input);
+
     let gen = quote! {
         use std::{any::TypeId, io::Cursor};
-        use naia_shared::{EventBuilder, PropertyIo};
+        use naia_shared::{EventBuilder, PropertyDescriptor, PropertyIo};
         pub struct #event_builder_name {
             type_id: TypeId,
         }
@@ -33,9 +40,19 @@ pub fn event_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             fn get_type_id(&self) -> TypeId {
                 return self.type_id;
             }
-            fn build(&self, buffer: &[u8]) -> #type_name {
+            fn build(&self, buffer: &[u8]) -> Option<#type_name> {
                 return #event_name::read_to_type(buffer);
             }
+            fn get_type_name(&self) -> &'static str {
+                return #event_name_string;
+            }
+            fn get_property_count(&self) -> u8 {
+                return #property_count;
+            }
+            fn get_version(&self) -> u8 {
+                return #version;
+            }
+            #properties_method
         }
         impl #event_name {
             pub fn get_builder() -> Box<dyn EventBuilder<#type_name>> {
@@ -57,12 +74,30 @@ pub fn event_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             fn get_type_id(&self) -> TypeId {
                 return TypeId::of::<#event_name>();
             }
+            #merge_methods
         }
     };
 
     proc_macro::TokenStream::from(gen)
 }
 
+fn get_merge_methods(type_name: &Ident, event_name: &Ident, input: &DeriveInput) -> TokenStream {
+    if !utils::has_attribute(input, "mergeable") {
+        return quote! {};
+    }
+    return quote! {
+        fn is_mergeable(&self) -> bool {
+            return true;
+        }
+        fn merge(&mut self, later: &#type_name) -> bool {
+            if let #type_name::#event_name(later) = later {
+                return #event_name::merge(self, later);
+            }
+            return false;
+        }
+    };
+}
+
 fn get_new_complete_method(event_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
     let mut args = quote! {};
     for (field_name, field_type) in properties.iter() {
@@ -117,7 +152,7 @@ fn get_read_to_type_method(
     for (field_name, field_type) in properties.iter() {
         let new_output_right = quote! {
             let mut #field_name = Property::<#field_type>::new(Default::default(), 0);
-            #field_name.read(read_cursor);
+            #field_name.read(read_cursor)?;
         };
         let new_output_result = quote! {
             #prop_reads
@@ -127,13 +162,13 @@ fn get_read_to_type_method(
     }
 
     return quote! {
-        fn read_to_type(buffer: &[u8]) -> #type_name {
+        fn read_to_type(buffer: &[u8]) -> Option<#type_name> {
             let read_cursor = &mut Cursor::new(buffer);
             #prop_reads
 
-            return #type_name::#event_name(#event_name {
+            return Some(#type_name::#event_name(#event_name {
                 #prop_names
-            });
+            }));
         }
     };
 }
@@ -155,9 +190,25 @@ fn get_read_to_type_method(
 //        return self.type_id;
 //    }
 //
-//    fn build(&self, buffer: &[u8]) -> ExampleEvent {
+//    fn build(&self, buffer: &[u8]) -> Option<ExampleEvent> {
 //        return StringEvent::read_to_type(buffer);
 //    }
+//
+//    fn get_type_name(&self) -> &'static str {
+//        return "StringEvent";
+//    }
+//
+//    fn get_property_count(&self) -> u8 {
+//        return 1;
+//    }
+//
+//    fn get_version(&self) -> u8 {
+//        return 0;
+//    }
+//
+//    fn get_properties(&self) -> Vec<PropertyDescriptor> {
+//        return vec![PropertyDescriptor::new::<String>("message", "String")];
+//    }
 //}
 //
 //impl StringEvent {
@@ -173,14 +224,14 @@ fn get_read_to_type_method(
 //        }
 //    }
 //
-//    fn read_to_type(buffer: &[u8]) -> ExampleEvent {
+//    fn read_to_type(buffer: &[u8]) -> Option<ExampleEvent> {
 //        let read_cursor = &mut Cursor::new(buffer);
 //        let mut message = Property::<String>::new(Default::default(), 0);
-//        message.read(read_cursor);
+//        message.read(read_cursor)?;
 //
-//        return ExampleEvent::StringEvent(StringEvent {
+//        return Some(ExampleEvent::StringEvent(StringEvent {
 //            message,
-//        });
+//        }));
 //    }
 //}
 //impl Event<ExampleEvent> for StringEvent {
@@ -9,6 +9,7 @@ pub fn event_type_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
     let write_variants = get_write_variants(&type_name, &input.data);
     let get_type_id_variants = get_type_id_variants(&type_name, &input.data);
+    let get_boxed_event_variants = get_boxed_event_variants(&type_name, &input.data);
 
     let gen = quote! {
         use std::any::TypeId;
@@ -24,6 +25,11 @@ pub fn event_type_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                     #get_type_id_variants
                 }
             }
+            fn get_boxed_event(&self) -> Box<dyn Event<Self>> {
+                match self {
+                    #get_boxed_event_variants
+                }
+            }
         }
     };
 
@@ -76,6 +82,29 @@ fn get_type_id_variants(type_name: &Ident, data: &Data) -> TokenStream {
     }
 }
 
+fn get_boxed_event_variants(type_name: &Ident, data: &Data) -> TokenStream {
+    match *data {
+        Data::Enum(ref data) => {
+            let mut output = quote! {};
+            for variant in data.variants.iter() {
+                let variant_name = &variant.ident;
+                let new_output_right = quote! {
+                    #type_name::#variant_name(identity) => {
+                        return Box::new(identity.clone());
+                    }
+                };
+                let new_output_result = quote! {
+                    #output
+                    #new_output_right
+                };
+                output = new_output_result;
+            }
+            output
+        }
+        _ => unimplemented!(),
+    }
+}
+
 ////FROM THIS
 //#[derive(EventType, Clone)]
 //pub enum ExampleEvent {
@@ -105,4 +134,14 @@ fn get_type_id_variants(type_name: &Ident, data: &Data) -> TokenStream {
 //            }
 //        }
 //    }
+//    fn get_boxed_event(&self) -> Box<dyn Event<Self>> {
+//        match self {
+//            ExampleEvent::StringEvent(identity) => {
+//                return Box::new(identity.clone());
+//            }
+//            ExampleEvent::AuthEvent(identity) => {
+//                return Box::new(identity.clone());
+//            }
+//        }
+//    }
 //}
@@ -33,14 +33,21 @@ pub fn event_type_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
     event_type_impl(input)
 }
 
-/// Derives the Event trait for a given struct
-#[proc_macro_derive(Event, attributes(type_name))]
+/// Derives the Event trait for a given struct. An optional #[version = N]
+/// attribute should be incremented whenever Properties are appended to the
+/// Event. An Event marked with #[mergeable] must implement
+/// `fn merge(&mut self, later: &Self) -> bool`, which is used to merge a later
+/// Event of the same type into it, while both are queued
+#[proc_macro_derive(Event, attributes(type_name, mergeable, version))]
 pub fn event_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     event_impl(input)
 }
 
-/// Derives the Entity trait for a given struct
-#[proc_macro_derive(Entity, attributes(type_name))]
+/// Derives the Entity trait for a given struct. Properties marked with
+/// #[unguaranteed] are not retransmitted when an update of them is dropped. An
+/// optional #[version = N] attribute should be incremented whenever Properties
+/// are appended to the Entity
+#[proc_macro_derive(Entity, attributes(type_name, unguaranteed, version))]
 pub fn entity_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     entity_impl(input)
 }
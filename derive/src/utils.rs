@@ -28,6 +28,24 @@ pub fn get_properties(input: &DeriveInput) -> Vec<(Ident, Type)> {
     fields
 }
 
+pub fn get_properties_with_attribute(input: &DeriveInput, attribute: &str) -> Vec<Ident> {
+    let mut fields = Vec::new();
+
+    if let Data::Struct(data_struct) = &input.data {
+        if let Fields::Named(fields_named) = &data_struct.fields {
+            for field in fields_named.named.iter() {
+                if let Some(property_name) = &field.ident {
+                    if field.attrs.iter().any(|attr| attr.path.is_ident(attribute)) {
+                        fields.push(property_name.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fields
+}
+
 pub fn get_type_name(input: &DeriveInput, type_type: &str) -> Ident {
     let mut type_name_option: Option<Ident> = None;
 
@@ -60,6 +78,28 @@ pub fn get_type_name(input: &DeriveInput, type_type: &str) -> Ident {
     );
 }
 
+pub fn get_version(input: &DeriveInput) -> u8 {
+    let mut version: u8 = 0;
+
+    for option in input.attrs.iter() {
+        if let Ok(Meta::NameValue(meta_name_value)) = option.parse_meta() {
+            if meta_name_value.path.is_ident("version") {
+                if let Lit::Int(lit) = meta_name_value.lit {
+                    version = lit
+                        .base10_parse::<u8>()
+                        .expect("#[version = N] requires a version number between 0 & 255");
+                }
+            }
+        }
+    }
+
+    return version;
+}
+
+pub fn has_attribute(input: &DeriveInput, attribute: &str) -> bool {
+    return input.attrs.iter().any(|attr| attr.path.is_ident(attribute));
+}
+
 pub fn get_write_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
     let mut output = quote! {};
 
@@ -80,3 +120,26 @@ pub fn get_write_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
         }
     };
 }
+
+pub fn get_properties_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
+    let mut output = quote! {};
+
+    for (field_name, field_type) in properties.iter() {
+        let name_string = field_name.to_string();
+        let type_string = quote! { #field_type }.to_string().replace(' ', "");
+        let new_output_right = quote! {
+            PropertyDescriptor::new::<#field_type>(#name_string, #type_string),
+        };
+        let new_output_result = quote! {
+            #output
+            #new_output_right
+        };
+        output = new_output_result;
+    }
+
+    return quote! {
+        fn get_properties(&self) -> Vec<PropertyDescriptor> {
+            return vec![#output];
+        }
+    };
+}
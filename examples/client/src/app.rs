@@ -1,8 +1,8 @@
-use log::info;
+use log::{info, warn};
 
 use std::{net::SocketAddr, time::Duration};
 
-use naia_client::{ClientEvent, Config, NaiaClient};
+use naia_client::{ClientEvent, Config, ConnectFailure, NaiaClient};
 
 use naia_example_shared::{manifest_load, AuthEvent, ExampleEntity, ExampleEvent, StringEvent};
 
@@ -70,6 +70,41 @@ impl App {
                     ClientEvent::Disconnection => {
                         info!("Client disconnected from: {}", self.client.server_address());
                     }
+                    ClientEvent::ProtocolViolation(reason) => {
+                        info!("Server violated the protocol: {:?}", reason);
+                    }
+                    ClientEvent::Kicked => {
+                        info!("Client was kicked from: {}", self.client.server_address());
+                    }
+                    ClientEvent::ServerShutdown(reason) => {
+                        info!("Server is shutting down: {}", reason);
+                    }
+                    ClientEvent::TickRateChanged(tick, tick_interval) => {
+                        info!("Server ticks every {:?} from tick {}", tick_interval, tick);
+                    }
+                    ClientEvent::ConnectionQualityChanged(quality) => {
+                        info!("Client connection is {:?}", quality);
+                    }
+                    ClientEvent::EventExpired(event_id) => {
+                        info!("Client event {} expired", event_id);
+                    }
+                    ClientEvent::Congested => {
+                        info!("Client connection is congested");
+                    }
+                    ClientEvent::Relieved => {
+                        info!("Client connection is relieved");
+                    }
+                    ClientEvent::Handoff(address) => {
+                        info!("Client handed off to: {}", address);
+                    }
+                    ClientEvent::ConnectFailed(failure) => match failure {
+                        ConnectFailure::ManifestMismatch(report) => {
+                            info!("Client could not connect. {}", report);
+                        }
+                        failure => {
+                            info!("Client could not connect: {:?}", failure);
+                        }
+                    },
                     ClientEvent::Event(event_type) => match event_type {
                         ExampleEvent::StringEvent(string_event) => {
                             let message = string_event.message.get();
@@ -80,11 +115,55 @@ impl App {
                             info!("Client send: {}", new_message);
 
                             let string_event = StringEvent::new(new_message);
-                            self.client.send_event(&string_event);
+                            if let Err(err) = self.client.send_event(&string_event) {
+                                info!("Client Error: {}", err);
+                            }
                             self.server_event_count += 1;
                         }
                         _ => {}
                     },
+                    ClientEvent::DeliveryConfirmed(_)
+                    | ClientEvent::RawPayload(_)
+                    | ClientEvent::HeartbeatPayload(_) => {}
+                    ClientEvent::BlobProgress(_, _, _) | ClientEvent::BlobDelivered(_) => {}
+                    ClientEvent::BlobReceived(blob_id, data) => {
+                        info!("Client received blob {} ({} bytes)", blob_id, data.len());
+                    }
+                    ClientEvent::TimedEvent(tick, _) => {
+                        info!("Client received event scheduled for tick {}", tick);
+                    }
+                    ClientEvent::EntityEvent(local_key, _) => {
+                        info!("Client received event for entity {}", local_key);
+                    }
+                    ClientEvent::RelayedEvent(sender_id, _) => {
+                        info!("Client received event relayed from client {}", sender_id);
+                    }
+                    ClientEvent::ClientJoined(client_id) => {
+                        info!("Client {} joined", client_id);
+                    }
+                    ClientEvent::ClientLeft(client_id) => {
+                        info!("Client {} left", client_id);
+                    }
+                    ClientEvent::ManifestExtended(delta) => {
+                        info!(
+                            "Server registered {} new event & {} new entity types",
+                            delta.events.len(),
+                            delta.entities.len()
+                        );
+                    }
+                    ClientEvent::ManifestMismatch(report) => {
+                        warn!("Server registered incompatible types. {}", report);
+                    }
+                    ClientEvent::Response(request_id, event_type) => match event_type {
+                        ExampleEvent::StringEvent(string_event) => {
+                            let message = string_event.message.get();
+                            info!("Client received response to {}: {}", request_id, message);
+                        }
+                        _ => {}
+                    },
+                    ClientEvent::RequestFailed(request_id, error) => {
+                        info!("Client request {} failed: {:?}", request_id, error);
+                    }
                     ClientEvent::CreateEntity(local_key) => {
                         if let Some(entity) = self.client.get_entity(local_key) {
                             match entity {
@@ -101,7 +180,13 @@ impl App {
                             }
                         }
                     }
-                    ClientEvent::UpdateEntity(local_key) => {
+                    ClientEvent::ConfirmPredictedEntity(prediction_key, local_key) => {
+                        info!(
+                            "predicted entity {} confirmed with key: {}",
+                            prediction_key, local_key
+                        );
+                    }
+                    ClientEvent::UpdateEntity(local_key, _) => {
                         if let Some(entity) = self.client.get_entity(local_key) {
                             match entity {
                                 ExampleEntity::PointEntity(point_entity) => {
@@ -117,9 +202,21 @@ impl App {
                             }
                         }
                     }
-                    ClientEvent::DeleteEntity(local_key) => {
+                    ClientEvent::LateUpdateEntity(local_key, tick, _) => {
+                        info!(
+                            "late update of entity with key: {}, from tick {}",
+                            local_key, tick
+                        );
+                    }
+                    ClientEvent::DeleteEntity(local_key, _) => {
                         info!("deletion of point entity with key: {}", local_key);
                     }
+                    ClientEvent::WorldSynced => {
+                        info!("Client world synced");
+                    }
+                    ClientEvent::TickApplied(tick) => {
+                        info!("Client applied entity messages of tick {}", tick);
+                    }
                     ClientEvent::None => {
                         //info!("Client non-event");
                     }
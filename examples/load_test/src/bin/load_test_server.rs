@@ -0,0 +1,175 @@
+#[macro_use]
+extern crate log;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use naia_server::{
+    find_my_ip_address, Config, EntityKey, LinkConditionerConfig, NaiaServer, ServerEvent, UserKey,
+};
+
+use naia_example_shared::{manifest_load, ExampleEvent, PointEntity};
+use naia_load_test::{format_cpu, get_arg, CpuMeter, DEFAULT_SERVER_PORT};
+
+// A headless Server which echoes every Event back to its sender, counts the
+// per-tick commands it receives, and replicates a number of Entities to each
+// connected Client, which are mutated every tick
+#[tokio::main]
+async fn main() {
+    simple_logger::init_with_level(log::Level::Info).expect("A logger was already initialized");
+
+    let port: u16 = get_arg("port", DEFAULT_SERVER_PORT);
+    let entities_per_user: usize = get_arg("entities", 8);
+    let tick_ms: u64 = get_arg("tick-ms", 50);
+    let loss: f32 = get_arg("loss", 0.0);
+    let dual_stack: bool = get_arg("dual-stack", false);
+    let loopback_port: u16 = get_arg("loopback-port", 0);
+
+    let server_address =
+        SocketAddr::new(find_my_ip_address().expect("can't find ip address"), port);
+
+    let mut config = Config::default();
+    config.tick_interval = Duration::from_millis(tick_ms);
+    // allow Clients which request compact headers to use them
+    config.compact_headers = true;
+    // listen over both IPv4 & IPv6
+    config.dual_stack = dual_stack;
+    if loss > 0.0 {
+        config.link_conditioner = Some(LinkConditionerConfig {
+            incoming_packet_loss: loss,
+            seed: 1,
+        });
+    }
+
+    let mut server = NaiaServer::new(server_address, manifest_load(), Some(config)).await;
+    server.on_scope_entity(Rc::new(Box::new(|_, _, _, _| true)));
+    // a second endpoint, for bots running on the same host
+    if loopback_port != 0 {
+        let loopback_address = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), loopback_port);
+        server.add_endpoint(loopback_address).await;
+        info!("Load Test Server also listening at {}", loopback_address);
+    }
+
+    info!(
+        "Load Test Server listening at {}, with {} entities per user & a {}ms tick",
+        server_address, entities_per_user, tick_ms
+    );
+
+    let room_key = server.create_room();
+    let mut user_entities: HashMap<UserKey, Vec<(EntityKey, Rc<RefCell<PointEntity>>)>> =
+        HashMap::new();
+
+    let mut cpu_meter = CpuMeter::new();
+    let mut last_report = Instant::now();
+    let mut events_received: u64 = 0;
+    let mut events_echoed: u64 = 0;
+    let mut commands_received: u64 = 0;
+    let mut ticks: u64 = 0;
+    let mut bytes_sent: usize = 0;
+
+    loop {
+        match server.receive().await {
+            Ok(event) => match event {
+                ServerEvent::Connection(user_key) => {
+                    server.room_add_user(&room_key, &user_key);
+                    let mut entities = Vec::new();
+                    for index in 0..entities_per_user {
+                        let entity = PointEntity::new(index as u8, 0, "load", "test").wrap();
+                        let entity_key = server.register_entity(entity.clone());
+                        server.room_add_entity(&room_key, &entity_key);
+                        entities.push((entity_key, entity));
+                    }
+                    user_entities.insert(user_key, entities);
+                }
+                ServerEvent::Disconnection(user_key, _) => {
+                    if let Some(entities) = user_entities.remove(&user_key) {
+                        for (entity_key, _) in entities {
+                            server.deregister_entity(entity_key);
+                        }
+                    }
+                }
+                ServerEvent::Event(user_key, event_type) => {
+                    events_received += 1;
+                    if let ExampleEvent::StringEvent(string_event) = event_type {
+                        if server.queue_event(&user_key, &string_event).is_ok() {
+                            events_echoed += 1;
+                        }
+                    }
+                }
+                ServerEvent::Command(_, _, _) | ServerEvent::MissingCommand(_, _) => {
+                    commands_received += 1;
+                }
+                ServerEvent::Tick => {
+                    for entities in user_entities.values() {
+                        for (_, entity) in entities {
+                            entity.borrow_mut().step();
+                        }
+                    }
+                    match server.tick().await {
+                        Ok(Some(stats)) => {
+                            bytes_sent += stats.bytes_sent;
+                            ticks += 1;
+                        }
+                        Ok(None) => {}
+                        Err(error) => {
+                            info!("Load Test Server Error: {}", error);
+                        }
+                    }
+
+                    let elapsed = last_report.elapsed();
+                    if elapsed >= Duration::from_secs(1) {
+                        let seconds = elapsed.as_secs_f32();
+                        let leads: Vec<f32> = user_entities
+                            .keys()
+                            .filter_map(|user_key| server.get_client_tick_lead(user_key))
+                            .collect();
+                        let average_lead = match leads.len() {
+                            0 => 0.0,
+                            count => leads.iter().sum::<f32>() / count as f32,
+                        };
+                        let commands_recovered: u64 = user_entities
+                            .keys()
+                            .filter_map(|user_key| server.get_command_stats(user_key))
+                            .map(|stats| stats.recovered)
+                            .sum();
+                        let (commands_missing, commands_late) = user_entities
+                            .keys()
+                            .filter_map(|user_key| server.get_command_buffer_stats(user_key))
+                            .fold((0, 0), |(missing, late), stats| {
+                                (missing + stats.missing, late + stats.late)
+                            });
+                        info!(
+                            "users: {} | ticks/s: {:.1} | events in/s: {:.1} | events out/s: {:.1} | commands/s: {:.1} | commands recovered: {} | late: {} | missing: {} | kB out/s: {:.1} | client tick lead: {:.2} | cpu: {}",
+                            user_entities.len(),
+                            ticks as f32 / seconds,
+                            events_received as f32 / seconds,
+                            events_echoed as f32 / seconds,
+                            commands_received as f32 / seconds,
+                            commands_recovered,
+                            commands_late,
+                            commands_missing,
+                            bytes_sent as f32 / seconds / 1000.0,
+                            average_lead,
+                            format_cpu(cpu_meter.sample()),
+                        );
+                        last_report = Instant::now();
+                        events_received = 0;
+                        events_echoed = 0;
+                        commands_received = 0;
+                        ticks = 0;
+                        bytes_sent = 0;
+                    }
+                }
+                _ => {}
+            },
+            Err(error) => {
+                info!("Load Test Server Error: {}", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+#[macro_use]
+extern crate log;
+
+use std::{
+    net::SocketAddr,
+    thread,
+    time::{Duration, Instant},
+};
+
+use naia_client::{find_my_ip_address, ClientEvent, Config, NaiaClient, RttStats};
+
+use naia_example_shared::{manifest_load, ExampleEntity, ExampleEvent, StringEvent};
+use naia_load_test::{format_cpu, get_arg, CpuMeter, DEFAULT_SERVER_PORT};
+
+struct Bot {
+    client: NaiaClient<ExampleEvent, ExampleEntity>,
+    connected: bool,
+}
+
+#[derive(Default)]
+struct Totals {
+    events_sent: u64,
+    events_confirmed: u64,
+    echoes_received: u64,
+    entities_created: u64,
+    entity_updates: u64,
+}
+
+// A swarm of bot Clients, driven from a single thread, which each send a
+// number of Events to the load test Server every tick, and count the echoes &
+// Entity updates they receive back
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).expect("A logger was already initialized");
+
+    let client_count: usize = get_arg("clients", 10);
+    let events_per_tick: usize = get_arg("events", 1);
+    let payload_length: usize = get_arg("payload", 16);
+    let tick_ms: u64 = get_arg("tick-ms", 50);
+    let seconds: u64 = get_arg("seconds", 30);
+    let compact_headers: bool = get_arg("compact-headers", false);
+    let server_address: SocketAddr = get_arg(
+        "server",
+        SocketAddr::new(
+            find_my_ip_address().expect("can't find ip address"),
+            DEFAULT_SERVER_PORT,
+        ),
+    );
+
+    info!(
+        "Load Test Swarm of {} clients, sending {} events of {} bytes every {}ms to {}, for {}s",
+        client_count, events_per_tick, payload_length, tick_ms, server_address, seconds
+    );
+
+    let mut config = Config::default();
+    config.tick_interval = Duration::from_millis(tick_ms);
+    config.compact_headers = compact_headers;
+
+    let mut bots: Vec<Bot> = (0..client_count)
+        .map(|_| Bot {
+            client: NaiaClient::new(server_address, manifest_load(), Some(config.clone()), None),
+            connected: false,
+        })
+        .collect();
+
+    let payload: String = "x".repeat(payload_length);
+    let start = Instant::now();
+    let mut last_frame = Instant::now();
+    let mut last_report = Instant::now();
+    let mut cpu_meter = CpuMeter::new();
+    let mut window = Totals::default();
+    let mut totals = Totals::default();
+
+    while start.elapsed() < Duration::from_secs(seconds) {
+        // send traffic, on each Bot's own tick clock, which the Server keeps
+        // running slightly ahead of its own
+        let frame_duration = last_frame.elapsed();
+        last_frame = Instant::now();
+        for bot in bots.iter_mut() {
+            let tick_count = bot.client.frame_begin(frame_duration);
+            if bot.connected && tick_count > 0 {
+                // there is no Client tick until the Server's tick has been heard
+                let _ = bot.client.send_command(&StringEvent::new(payload.clone()));
+                for _ in 0..events_per_tick * tick_count as usize {
+                    let event = StringEvent::new(payload.clone());
+                    if bot.client.send_event(&event).is_ok() {
+                        window.events_sent += 1;
+                    }
+                }
+            }
+        }
+
+        // receive traffic
+        for bot in bots.iter_mut() {
+            loop {
+                match bot.client.receive() {
+                    Ok(ClientEvent::None) => break,
+                    Ok(ClientEvent::Connection) => bot.connected = true,
+                    Ok(ClientEvent::Disconnection) => bot.connected = false,
+                    Ok(ClientEvent::DeliveryConfirmed(_)) => window.events_confirmed += 1,
+                    Ok(ClientEvent::Event(_)) => window.echoes_received += 1,
+                    Ok(ClientEvent::CreateEntity(_)) => window.entities_created += 1,
+                    Ok(ClientEvent::UpdateEntity(_, _)) => window.entity_updates += 1,
+                    Ok(_) => {}
+                    Err(error) => {
+                        info!("Load Test Client Error: {}", error);
+                        break;
+                    }
+                }
+            }
+        }
+
+        // report
+        let elapsed = last_report.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let connected_bots: Vec<&Bot> = bots.iter().filter(|bot| bot.connected).collect();
+            let rtt_stats: Vec<RttStats> = connected_bots
+                .iter()
+                .filter_map(|bot| bot.client.get_rtt_stats())
+                .collect();
+            let (median_rtt, worst_p95_rtt) = match rtt_stats.len() {
+                0 => (0.0, 0.0),
+                count => (
+                    rtt_stats.iter().map(|stats| stats.median).sum::<f32>() / count as f32,
+                    rtt_stats.iter().map(|stats| stats.p95).fold(0.0, f32::max),
+                ),
+            };
+            let seconds = elapsed.as_secs_f32();
+            info!(
+                "connected: {}/{} | events sent/s: {:.1} | confirmed/s: {:.1} | echoes/s: {:.1} | entity updates/s: {:.1} | rtt median: {:.1}ms | worst rtt p95: {:.1}ms | cpu: {}",
+                connected_bots.len(),
+                client_count,
+                window.events_sent as f32 / seconds,
+                window.events_confirmed as f32 / seconds,
+                window.echoes_received as f32 / seconds,
+                window.entity_updates as f32 / seconds,
+                median_rtt,
+                worst_p95_rtt,
+                format_cpu(cpu_meter.sample()),
+            );
+            totals.events_sent += window.events_sent;
+            totals.events_confirmed += window.events_confirmed;
+            totals.echoes_received += window.echoes_received;
+            totals.entities_created += window.entities_created;
+            totals.entity_updates += window.entity_updates;
+            window = Totals::default();
+            last_report = Instant::now();
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    totals.events_sent += window.events_sent;
+    totals.events_confirmed += window.events_confirmed;
+    totals.echoes_received += window.echoes_received;
+    totals.entities_created += window.entities_created;
+    totals.entity_updates += window.entity_updates;
+
+    // Events which are still in flight when the test ends count as lost
+    let loss = match totals.events_sent {
+        0 => 0.0,
+        sent => 100.0 * (1.0 - totals.events_confirmed as f32 / sent as f32),
+    };
+    info!(
+        "Load Test finished. events sent: {} | confirmed: {} | echoes: {} | entities created: {} | entity updates: {} | unconfirmed: {:.2}%",
+        totals.events_sent,
+        totals.events_confirmed,
+        totals.echoes_received,
+        totals.entities_created,
+        totals.entity_updates,
+        loss,
+    );
+}
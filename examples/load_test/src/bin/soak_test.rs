@@ -0,0 +1,381 @@
+#[macro_use]
+extern crate log;
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    process,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use naia_client::{ClientEvent, NaiaClient};
+use naia_server::{
+    find_my_ip_address, Config, EntityKey, LinkConditionerConfig, NaiaServer, ServerEvent, UserKey,
+};
+
+use naia_example_shared::{manifest_load, ExampleEntity, ExampleEvent, PointEntity};
+use naia_load_test::{get_arg, DEFAULT_SERVER_PORT};
+
+// What a Client sees of the world, published by the Client thread so that the
+// Server can check it against its own state
+#[derive(Clone, Default)]
+struct ClientView {
+    connected: bool,
+    // the `y` Property of every Entity, by the id in the Entity's name. `x` is
+    // unguaranteed, so a dropped update of it is never resent, and it isn't
+    // expected to converge
+    entities: HashMap<u32, u8>,
+    max_key_index: u16,
+}
+
+enum Phase {
+    Connecting,
+    Chaos(u64),
+    Settle(u64),
+}
+
+// A small xorshift generator, so that a run can be reproduced from its seed
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        Random { state: seed.max(1) }
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return (self.state >> 32) as usize % bound.max(1);
+    }
+}
+
+// A long-running test which alternates between a chaos phase, where Entities
+// are randomly spawned, despawned & mutated while packets are dropped in both
+// directions by the link conditioner, and a settle phase, where nothing
+// changes and every Client's view must converge to the Server's state. Exits
+// with an error as soon as an invariant is violated
+#[tokio::main]
+async fn main() {
+    simple_logger::init_with_level(log::Level::Info).expect("A logger was already initialized");
+
+    let port: u16 = get_arg("port", DEFAULT_SERVER_PORT + 1);
+    let client_count: usize = get_arg("clients", 4);
+    let max_entities: usize = get_arg("max-entities", 64);
+    let operations_per_tick: usize = get_arg("operations", 4);
+    let chaos_ticks: u64 = get_arg("chaos-ticks", 200);
+    let settle_ticks: u64 = get_arg("settle-ticks", 250);
+    let loss: f32 = get_arg("loss", 0.1);
+    let seed: u64 = get_arg("seed", 1);
+    let tick_ms: u64 = get_arg("tick-ms", 20);
+    let hours: f32 = get_arg("hours", 1.0);
+
+    let server_address =
+        SocketAddr::new(find_my_ip_address().expect("can't find ip address"), port);
+
+    let mut config = Config::default();
+    config.tick_interval = Duration::from_millis(tick_ms);
+    config.heartbeat_interval = Duration::from_millis(250);
+    config.link_conditioner = Some(LinkConditionerConfig {
+        incoming_packet_loss: loss,
+        seed,
+    });
+
+    // Keys are only recycled once their tombstone expires, so a Client should
+    // never see a key beyond the most Entities which can be alive at once,
+    // plus those despawned within a couple of tombstone durations
+    let tombstone_ticks = (config.entity_tombstone_duration.as_millis() as u64 / tick_ms) as usize;
+    let key_bound = max_entities + operations_per_tick * tombstone_ticks * 2;
+
+    info!(
+        "Soak Test of {} clients & up to {} entities, with {}% loss, seed {}, for {} hours",
+        client_count,
+        max_entities,
+        loss * 100.0,
+        seed,
+        hours
+    );
+
+    let mut server = NaiaServer::new(server_address, manifest_load(), Some(config.clone())).await;
+    server.on_scope_entity(Rc::new(Box::new(|_, _, _, _| true)));
+    let room_key = server.create_room();
+
+    let views = Arc::new(Mutex::new(vec![ClientView::default(); client_count]));
+    let running = Arc::new(AtomicBool::new(true));
+    let client_thread = {
+        let views = views.clone();
+        let running = running.clone();
+        thread::spawn(move || run_clients(server_address, config, views, running))
+    };
+
+    let duration = Duration::from_secs_f32(hours * 3600.0);
+    let start = Instant::now();
+    let mut random = Random::new(seed);
+    let mut phase = Phase::Connecting;
+    let mut users: Vec<UserKey> = Vec::new();
+    let mut entities: BTreeMap<u32, (EntityKey, Rc<RefCell<PointEntity>>)> = BTreeMap::new();
+    let mut next_id: u32 = 0;
+    let mut cycle: u64 = 0;
+    let mut spawned: u64 = 0;
+    let mut despawned: u64 = 0;
+    let mut peak_undelivered: usize = 0;
+
+    loop {
+        match server.receive().await {
+            Ok(ServerEvent::Connection(user_key)) => {
+                server.room_add_user(&room_key, &user_key);
+                users.push(user_key);
+            }
+            Ok(ServerEvent::Disconnection(user_key, _)) => {
+                users.retain(|key| *key != user_key);
+            }
+            Ok(ServerEvent::Tick) => {
+                if let Phase::Chaos(_) = phase {
+                    for _ in 0..operations_per_tick {
+                        match random.below(3) {
+                            0 if entities.len() < max_entities => {
+                                let id = next_id;
+                                next_id += 1;
+                                let entity = PointEntity::new(
+                                    random.below(20) as u8,
+                                    random.below(256) as u8,
+                                    &id.to_string(),
+                                    "soak",
+                                )
+                                .wrap();
+                                let entity_key = server.register_entity(entity.clone());
+                                server.room_add_entity(&room_key, &entity_key);
+                                entities.insert(id, (entity_key, entity));
+                                spawned += 1;
+                            }
+                            1 if !entities.is_empty() => {
+                                let id =
+                                    *entities.keys().nth(random.below(entities.len())).unwrap();
+                                let (entity_key, _) = entities.remove(&id).unwrap();
+                                server.deregister_entity(entity_key);
+                                despawned += 1;
+                            }
+                            2 if !entities.is_empty() => {
+                                let index = random.below(entities.len());
+                                let (_, entity) = entities.values().nth(index).unwrap();
+                                let mut entity = entity.borrow_mut();
+                                entity.x.set(random.below(20) as u8);
+                                entity.y.set(random.below(256) as u8);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                if let Err(error) = server.tick().await {
+                    info!("Soak Test Server Error: {}", error);
+                }
+
+                for user_key in users.iter() {
+                    if let Some(count) = server.get_undelivered_message_count(user_key) {
+                        peak_undelivered = peak_undelivered.max(count);
+                    }
+                }
+
+                let client_views = views.lock().unwrap().clone();
+                for (index, view) in client_views.iter().enumerate() {
+                    if view.max_key_index as usize >= key_bound {
+                        fail(&format!(
+                            "client {} saw local key index {}, beyond the bound of {}, keys are leaking",
+                            index, view.max_key_index, key_bound
+                        ));
+                    }
+                }
+
+                phase = match phase {
+                    Phase::Connecting => {
+                        if users.len() == client_count {
+                            info!("All {} clients connected, starting chaos", client_count);
+                            Phase::Chaos(0)
+                        } else {
+                            Phase::Connecting
+                        }
+                    }
+                    Phase::Chaos(ticks) if ticks + 1 >= chaos_ticks => Phase::Settle(0),
+                    Phase::Chaos(ticks) => Phase::Chaos(ticks + 1),
+                    Phase::Settle(ticks) => {
+                        match check_convergence(&server, &users, &entities, &client_views) {
+                            Ok(()) => {
+                                cycle += 1;
+                                let max_key_index = client_views
+                                    .iter()
+                                    .map(|view| view.max_key_index)
+                                    .max()
+                                    .unwrap_or(0);
+                                info!(
+                                    "cycle {} converged after {} settle ticks | entities: {} | spawned: {} | despawned: {} | max key index: {}/{} | peak undelivered: {} | elapsed: {}s",
+                                    cycle,
+                                    ticks + 1,
+                                    entities.len(),
+                                    spawned,
+                                    despawned,
+                                    max_key_index,
+                                    key_bound,
+                                    peak_undelivered,
+                                    start.elapsed().as_secs(),
+                                );
+                                peak_undelivered = 0;
+                                if start.elapsed() >= duration {
+                                    break;
+                                }
+                                Phase::Chaos(0)
+                            }
+                            Err(reason) if ticks + 1 >= settle_ticks => {
+                                fail(&format!(
+                                    "cycle {} did not converge within {} ticks: {}",
+                                    cycle + 1,
+                                    settle_ticks,
+                                    reason
+                                ));
+                            }
+                            Err(_) => Phase::Settle(ticks + 1),
+                        }
+                    }
+                };
+            }
+            Ok(_) => {}
+            Err(error) => {
+                info!("Soak Test Server Error: {}", error);
+            }
+        }
+    }
+
+    running.store(false, Ordering::Relaxed);
+    client_thread.join().expect("client thread panicked");
+    info!(
+        "Soak Test passed {} cycles in {}s",
+        cycle,
+        start.elapsed().as_secs()
+    );
+}
+
+// Checks that every Client is connected & sees exactly the Entities of the
+// Server, and that no messages remain undelivered
+fn check_convergence(
+    server: &NaiaServer<ExampleEvent, ExampleEntity>,
+    users: &[UserKey],
+    entities: &BTreeMap<u32, (EntityKey, Rc<RefCell<PointEntity>>)>,
+    client_views: &[ClientView],
+) -> Result<(), String> {
+    if users.len() != client_views.len() {
+        return Err(format!(
+            "{} of {} clients are connected",
+            users.len(),
+            client_views.len()
+        ));
+    }
+
+    let expected: HashMap<u32, u8> = entities
+        .iter()
+        .map(|(id, (_, entity))| (*id, *entity.borrow().y.get()))
+        .collect();
+    for (index, view) in client_views.iter().enumerate() {
+        if !view.connected {
+            return Err(format!("client {} is disconnected", index));
+        }
+        if view.entities != expected {
+            let mut missing: Vec<&u32> = expected
+                .keys()
+                .filter(|id| !view.entities.contains_key(id))
+                .collect();
+            let mut stale: Vec<&u32> = view
+                .entities
+                .keys()
+                .filter(|id| !expected.contains_key(id))
+                .collect();
+            missing.sort();
+            stale.sort();
+            let differing = expected
+                .iter()
+                .filter(|(id, y)| view.entities.get(id).map_or(false, |view_y| view_y != *y))
+                .count();
+            return Err(format!(
+                "client {} is missing entities {:?}, still has despawned entities {:?}, and has {} stale values",
+                index, missing, stale, differing
+            ));
+        }
+    }
+
+    for user_key in users.iter() {
+        if let Some(count) = server.get_undelivered_message_count(user_key) {
+            if count != 0 {
+                return Err(format!("{} messages are undelivered", count));
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+fn fail(reason: &str) -> ! {
+    error!("Soak Test failed: {}", reason);
+    process::exit(1);
+}
+
+// Drives every Client from a single thread, publishing what each of them sees
+fn run_clients(
+    server_address: SocketAddr,
+    config: Config,
+    views: Arc<Mutex<Vec<ClientView>>>,
+    running: Arc<AtomicBool>,
+) {
+    let client_count = views.lock().unwrap().len();
+    let mut clients: Vec<NaiaClient<ExampleEvent, ExampleEntity>> = (0..client_count)
+        .map(|index| {
+            // every Client drops a different sequence of packets
+            let mut config = config.clone();
+            if let Some(link_conditioner) = &mut config.link_conditioner {
+                link_conditioner.seed += index as u64 + 1;
+            }
+            NaiaClient::new(server_address, manifest_load(), Some(config), None)
+        })
+        .collect();
+    let mut local_views = vec![ClientView::default(); client_count];
+
+    while running.load(Ordering::Relaxed) {
+        for (client, view) in clients.iter_mut().zip(local_views.iter_mut()) {
+            loop {
+                match client.receive() {
+                    Ok(ClientEvent::None) => break,
+                    Ok(ClientEvent::Connection) => view.connected = true,
+                    Ok(ClientEvent::Disconnection) => view.connected = false,
+                    Ok(_) => {}
+                    Err(error) => {
+                        info!("Soak Test Client Error: {}", error);
+                        break;
+                    }
+                }
+            }
+
+            view.entities.clear();
+            for (local_key, entity) in client.all_entities() {
+                view.max_key_index = view.max_key_index.max(local_key.get_index());
+                match entity {
+                    ExampleEntity::PointEntity(point_entity) => {
+                        let point_entity = point_entity.borrow();
+                        if let Ok(id) = point_entity.name.get().first.parse() {
+                            view.entities.insert(id, *point_entity.y.get());
+                        }
+                    }
+                }
+            }
+        }
+
+        *views.lock().unwrap() = local_views.clone();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
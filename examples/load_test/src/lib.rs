@@ -0,0 +1,82 @@
+//! # Naia Load Test
+//! Helpers shared by the headless echo Server & the swarm of bot Clients used
+//! to measure how much traffic naia can sustain
+
+use std::{
+    env,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// The port the load test Server listens at, unless otherwise specified
+pub const DEFAULT_SERVER_PORT: u16 = 14192;
+
+/// Gets the value of a command line argument given as `--name value`, or the
+/// default if it was not given or could not be parsed
+pub fn get_arg<T: FromStr>(name: &str, default: T) -> T {
+    let flag = format!("--{}", name);
+    let args: Vec<String> = env::args().collect();
+    for (index, arg) in args.iter().enumerate() {
+        if *arg == flag {
+            if let Some(value) = args.get(index + 1).and_then(|value| value.parse().ok()) {
+                return value;
+            }
+        }
+    }
+    return default;
+}
+
+/// Measures the CPU usage of the current process between samples
+pub struct CpuMeter {
+    last_cpu_time: Option<Duration>,
+    last_instant: Instant,
+}
+
+impl CpuMeter {
+    /// Creates a new CpuMeter, which measures from the moment it is created
+    pub fn new() -> Self {
+        CpuMeter {
+            last_cpu_time: get_process_cpu_time(),
+            last_instant: Instant::now(),
+        }
+    }
+
+    /// Gets the percentage of a single core the process has used since the
+    /// last sample. Returns None on platforms where this can't be measured
+    pub fn sample(&mut self) -> Option<f32> {
+        let cpu_time = get_process_cpu_time();
+        let elapsed = self.last_instant.elapsed();
+        let output = match (cpu_time, self.last_cpu_time) {
+            (Some(cpu_time), Some(last_cpu_time)) if elapsed.as_secs_f32() > 0.0 => Some(
+                (cpu_time.checked_sub(last_cpu_time)?.as_secs_f32() / elapsed.as_secs_f32())
+                    * 100.0,
+            ),
+            _ => None,
+        };
+        self.last_cpu_time = cpu_time;
+        self.last_instant = Instant::now();
+        return output;
+    }
+}
+
+/// Formats a CPU usage sample for a report
+pub fn format_cpu(sample: Option<f32>) -> String {
+    match sample {
+        Some(percent) => format!("{:.1}%", percent),
+        None => "n/a".to_string(),
+    }
+}
+
+// The user & system time of the process, read from /proc/self/stat. These are
+// measured in clock ticks, which are 1/100th of a second on Linux
+fn get_process_cpu_time() -> Option<Duration> {
+    const CLOCK_TICKS_PER_SECOND: u64 = 100;
+
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // the process name may contain spaces, so fields are counted from after it
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 2..].split_whitespace().collect();
+    let user_ticks: u64 = fields.get(11)?.parse().ok()?;
+    let system_ticks: u64 = fields.get(12)?.parse().ok()?;
+    let ticks = user_ticks + system_ticks;
+    return Some(Duration::from_millis(ticks * 1000 / CLOCK_TICKS_PER_SECOND));
+}
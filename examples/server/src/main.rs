@@ -3,7 +3,7 @@ extern crate log;
 
 use simple_logger;
 
-use naia_server::{find_my_ip_address, Config, NaiaServer, ServerEvent, UserKey};
+use naia_server::{find_my_ip_address, Config, NaiaServer, ServerEvent, ServerStatus, UserKey};
 
 use naia_example_shared::{manifest_load, ExampleEntity, ExampleEvent, PointEntity, StringEvent};
 
@@ -47,6 +47,15 @@ async fn main() {
     // can receive updates from
     let main_room_key = server.create_room();
 
+    // Answered to server browsers querying with `ServerQuery::request_status()`,
+    // without them connecting
+    server.set_server_status(ServerStatus {
+        name: "Naia Example".to_string(),
+        map: "main".to_string(),
+        properties: vec![("mode".to_string(), "points".to_string())],
+        ..ServerStatus::default()
+    });
+
     // Create 4 PointEntities, with a range of X values
     let mut point_entities: Vec<Rc<RefCell<PointEntity>>> = Vec::new();
 
@@ -92,6 +101,48 @@ async fn main() {
                     ServerEvent::Disconnection(_, user) => {
                         info!("Naia Server disconnected from: {:?}", user.address);
                     }
+                    ServerEvent::Shutdown => {
+                        info!("Naia Server has shut down");
+                    }
+                    ServerEvent::ProtocolViolation(user_key, reason) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!(
+                                "Naia Server protocol violation from {}: {:?}",
+                                user.address, reason
+                            );
+                        }
+                    }
+                    ServerEvent::ConnectionQualityChanged(user_key, quality) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!(
+                                "Naia Server connection to {} is {:?}",
+                                user.address, quality
+                            );
+                        }
+                    }
+                    ServerEvent::EventExpired(user_key, event_id) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!("Naia Server event {} to {} expired", event_id, user.address);
+                        }
+                    }
+                    ServerEvent::Congested(user_key) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!("Naia Server connection to {} is congested", user.address);
+                        }
+                    }
+                    ServerEvent::Relieved(user_key) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!("Naia Server connection to {} is relieved", user.address);
+                        }
+                    }
+                    ServerEvent::LinkStateChanged(user_key, link_state) => {
+                        if let Some(user) = server.get_user(&user_key) {
+                            info!("Naia Server link to {} is {:?}", user.address, link_state);
+                        }
+                    }
+                    ServerEvent::LoadSheddingChanged(tier) => {
+                        info!("Naia Server load shedding tier is {:?}", tier);
+                    }
                     ServerEvent::Event(user_key, event_type) => {
                         if let Some(user) = server.get_user(&user_key) {
                             match event_type {
@@ -103,6 +154,47 @@ async fn main() {
                             }
                         }
                     }
+                    ServerEvent::Command(_user_key, _tick, _command) => {
+                        // The Client's input for the given tick would be applied here
+                    }
+                    ServerEvent::MissingCommand(_user_key, _tick) => {
+                        // A neutral input would be applied here
+                    }
+                    ServerEvent::DeliveryConfirmed(_, _)
+                    | ServerEvent::RawPayload(_, _)
+                    | ServerEvent::HeartbeatPayload(_, _) => {}
+                    ServerEvent::BlobProgress(_, _, _, _) | ServerEvent::BlobDelivered(_, _) => {}
+                    ServerEvent::BlobReceived(_, blob_id, data) => {
+                        info!(
+                            "Naia Server received blob {} ({} bytes)",
+                            blob_id,
+                            data.len()
+                        );
+                    }
+                    ServerEvent::Request(user_key, request_id, event_type) => {
+                        match event_type {
+                            ExampleEvent::StringEvent(string_event) => {
+                                // echo the request back as the response
+                                if let Err(error) =
+                                    server.respond(&user_key, request_id, &string_event)
+                                {
+                                    info!("Naia Server Error: {}", error);
+                                }
+                            }
+                            _ => {
+                                if let Err(error) = server.reject_request(&user_key, request_id) {
+                                    info!("Naia Server Error: {}", error);
+                                }
+                            }
+                        }
+                    }
+                    ServerEvent::EntityEnteredScope(_user_key, _entity_key) => {
+                        // An Event giving context to the Entity could be queued for the User here
+                    }
+                    ServerEvent::EntityLeftScope(_user_key, _entity_key) => {}
+                    ServerEvent::UnconnectedMessage(address, token, data) => {
+                        server.send_unconnected_response(address, token, &data);
+                    }
                     ServerEvent::Tick => {
                         // Game logic, updating of the world, should happen here
 
@@ -117,7 +209,9 @@ async fn main() {
                             info!("Naia Server send -> {}: {}", user.address, new_message);
 
                             let string_event = StringEvent::new(new_message);
-                            server.queue_event(&user_key, &string_event);
+                            if let Err(error) = server.queue_event(&user_key, &string_event) {
+                                info!("Naia Server Error: {}", error);
+                            }
                         }
 
                         // Iterate through Point Entities, marching them from (0,0) to (20, N)
@@ -126,9 +220,11 @@ async fn main() {
                         }
 
                         // VERY IMPORTANT! Calling this actually sends all Entity/Event data packets
-                        // to all Clients that require it. If you don't call this method, the Server
-                        // will never communicate with it's connected Clients
-                        server.send_all_updates().await;
+                        // to all Clients that require it, once per tick. If you don't call this
+                        // method, the Server will never communicate with it's connected Clients
+                        if let Err(error) = server.tick().await {
+                            info!("Naia Server Error: {}", error);
+                        }
 
                         tick_count += 1;
                     }
@@ -4,9 +4,10 @@ use naia_shared::Manifest;
 pub fn manifest_load() -> Manifest<ExampleEvent, ExampleEntity> {
     let mut manifest = Manifest::<ExampleEvent, ExampleEntity>::new();
 
-    manifest.register_event(AuthEvent::get_builder());
-    manifest.register_event(StringEvent::get_builder());
-    manifest.register_entity(PointEntity::get_builder());
+    // explicit ids keep the protocol compatible as types are added or reordered
+    manifest.register_auth_event_with_id(0, AuthEvent::get_builder());
+    manifest.register_event_with_id(1, StringEvent::get_builder());
+    manifest.register_entity_with_id(0, PointEntity::get_builder());
 
     manifest
 }
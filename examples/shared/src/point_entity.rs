@@ -15,6 +15,9 @@ pub struct Name {
 #[derive(Entity)]
 #[type_name = "ExampleEntity"]
 pub struct PointEntity {
+    // X changes every step, so there's no sense in retransmitting a dropped update,
+    // the next update will supersede it
+    #[unguaranteed]
     pub x: Property<u8>,
     pub y: Property<u8>,
     pub name: Property<Name>,
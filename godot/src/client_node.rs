@@ -0,0 +1,260 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+use godot::{classes::PackedScene, prelude::*};
+
+use naia_client_ffi::{entity_key_to_u32, FfiClient, FfiEvent};
+use naia_shared::LocalEntityKey;
+
+use super::{create_client, entity_node::NaiaEntityNode};
+
+/// A node which connects to a Server with the protocol registered with
+/// `register_protocol()`, & receives from it every frame. Events are emitted
+/// as signals, & each Entity in scope is mirrored by a `NaiaEntity` child
+#[derive(GodotClass)]
+#[class(base = Node, rename = NaiaClient)]
+pub struct NaiaClientNode {
+    base: Base<Node>,
+    client: Option<Box<dyn FfiClient>>,
+    connected: bool,
+    entities: HashMap<u32, Gd<NaiaEntityNode>>,
+    /// Scenes to instantiate under the `NaiaEntity` node of each Entity,
+    /// keyed by the NaiaId of the Entity's type
+    #[export]
+    entity_scenes: Dictionary,
+}
+
+#[godot_api]
+impl INode for NaiaClientNode {
+    fn init(base: Base<Node>) -> Self {
+        NaiaClientNode {
+            base,
+            client: None,
+            connected: false,
+            entities: HashMap::new(),
+            entity_scenes: Dictionary::new(),
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        self.receive_all();
+    }
+}
+
+#[godot_api]
+impl NaiaClientNode {
+    /// Emitted when the connection to the Server has been established
+    #[signal]
+    fn connected();
+
+    /// Emitted when the connection to the Server has been lost, after every
+    /// `NaiaEntity` child has been freed
+    #[signal]
+    fn disconnected();
+
+    /// Emitted when the Server could not be reached, or refused the
+    /// connection
+    #[signal]
+    fn connect_failed();
+
+    /// Emitted for each Event sent by the Server
+    #[signal]
+    fn event_received(naia_id: i64, data: PackedByteArray);
+
+    /// Emitted for each Event the Server targeted at an Entity
+    #[signal]
+    fn entity_event_received(entity: Gd<NaiaEntityNode>, naia_id: i64, data: PackedByteArray);
+
+    /// Emitted when an Entity has come into scope, once its `NaiaEntity` node
+    /// has been added
+    #[signal]
+    fn entity_created(entity: Gd<NaiaEntityNode>);
+
+    /// Emitted when an Entity in scope has been updated
+    #[signal]
+    fn entity_updated(entity: Gd<NaiaEntityNode>);
+
+    /// Emitted when an Entity has left scope, before its `NaiaEntity` node is
+    /// freed
+    #[signal]
+    fn entity_deleted(entity: Gd<NaiaEntityNode>);
+
+    /// Connects to the Server at the given address, such as
+    /// "127.0.0.1:14191", with an auth Event given by its NaiaId & bytes, or
+    /// no auth Event if the bytes are empty. Returns false if the address
+    /// can't be parsed, no protocol has been registered, or the auth Event
+    /// can't be read
+    #[func]
+    fn connect_to_server(
+        &mut self,
+        address: GString,
+        auth_naia_id: i64,
+        auth_data: PackedByteArray,
+    ) -> bool {
+        let server_address: SocketAddr = match address.to_string().parse() {
+            Ok(server_address) => server_address,
+            Err(_) => return false,
+        };
+        let auth = match auth_data.is_empty() {
+            true => None,
+            false => Some((auth_naia_id as u16, auth_data.as_slice())),
+        };
+        self.disconnect_from_server();
+        self.client = create_client(server_address, auth);
+        return self.client.is_some();
+    }
+
+    /// Stops receiving from the Server & frees every `NaiaEntity` child. The
+    /// Server isn't notified, & times the connection out
+    #[func]
+    fn disconnect_from_server(&mut self) {
+        self.client = None;
+        self.connected = false;
+        self.clear_entities();
+    }
+
+    /// Returns whether the connection to the Server is established
+    #[func]
+    fn is_connected_to_server(&self) -> bool {
+        return self.connected;
+    }
+
+    /// Queues up an Event to be sent to the Server, given its NaiaId & bytes.
+    /// Returns its EventId, or -1 if it can't be sent
+    #[func]
+    fn queue_event(&mut self, naia_id: i64, data: PackedByteArray) -> i64 {
+        let client = match self.client.as_mut() {
+            Some(client) => client,
+            None => return -1,
+        };
+        match client.queue_event(naia_id as u16, data.as_slice()) {
+            Ok(event_id) => return i64::from(event_id),
+            Err(error) => {
+                godot_warn!("naia: can't send Event {}: {}", naia_id, error);
+                return -1;
+            }
+        }
+    }
+
+    /// Gets the `NaiaEntity` node of the Entity with the given key, if it is
+    /// in scope
+    #[func]
+    fn get_entity_node(&self, entity_key: i64) -> Option<Gd<NaiaEntityNode>> {
+        return self.entities.get(&(entity_key as u32)).cloned();
+    }
+
+    fn receive_all(&mut self) {
+        loop {
+            let client = match self.client.as_mut() {
+                Some(client) => client,
+                None => return,
+            };
+            let event = match client.poll() {
+                Ok(event) => event,
+                Err(error) => {
+                    godot_warn!("naia: {}", error);
+                    return;
+                }
+            };
+            match event {
+                FfiEvent::None => return,
+                FfiEvent::Connection => {
+                    self.connected = true;
+                    self.base_mut().emit_signal("connected", &[]);
+                }
+                FfiEvent::Disconnection => {
+                    self.connected = false;
+                    self.clear_entities();
+                    self.base_mut().emit_signal("disconnected", &[]);
+                }
+                FfiEvent::ConnectFailed => {
+                    self.client = None;
+                    self.base_mut().emit_signal("connect_failed", &[]);
+                }
+                FfiEvent::Event(naia_id, bytes) => {
+                    let args = [
+                        i64::from(naia_id).to_variant(),
+                        PackedByteArray::from(bytes.as_slice()).to_variant(),
+                    ];
+                    self.base_mut().emit_signal("event_received", &args);
+                }
+                FfiEvent::EntityEvent(key, naia_id, bytes) => {
+                    if let Some(entity) = self.entities.get(&entity_key_to_u32(key)).cloned() {
+                        let args = [
+                            entity.to_variant(),
+                            i64::from(naia_id).to_variant(),
+                            PackedByteArray::from(bytes.as_slice()).to_variant(),
+                        ];
+                        self.base_mut().emit_signal("entity_event_received", &args);
+                    }
+                }
+                FfiEvent::CreateEntity(key) => self.create_entity(key),
+                FfiEvent::UpdateEntity(key) => self.update_entity(key),
+                FfiEvent::DeleteEntity(key) => self.delete_entity(key),
+                FfiEvent::Unsupported => {}
+            }
+        }
+    }
+
+    fn create_entity(&mut self, key: LocalEntityKey) {
+        let (naia_id, bytes) = match self
+            .client
+            .as_ref()
+            .and_then(|client| client.get_entity(key))
+        {
+            Some(entity) => entity,
+            None => return,
+        };
+        let entity_key = entity_key_to_u32(key);
+        let mut entity = NaiaEntityNode::new_alloc();
+        entity.set_name(&GString::from(format!("Entity{}", entity_key).as_str()));
+        {
+            let mut entity_bind = entity.bind_mut();
+            entity_bind.entity_key = i64::from(entity_key);
+            entity_bind.set_state(naia_id, &bytes, false);
+        }
+        let scene = self
+            .entity_scenes
+            .get(i64::from(naia_id))
+            .and_then(|scene| scene.try_to::<Gd<PackedScene>>().ok());
+        if let Some(instance) = scene.and_then(|scene| scene.instantiate()) {
+            entity.add_child(&instance);
+        }
+
+        self.base_mut().add_child(&entity);
+        self.entities.insert(entity_key, entity.clone());
+        self.base_mut()
+            .emit_signal("entity_created", &[entity.to_variant()]);
+    }
+
+    fn update_entity(&mut self, key: LocalEntityKey) {
+        let (naia_id, bytes) = match self
+            .client
+            .as_ref()
+            .and_then(|client| client.get_entity(key))
+        {
+            Some(entity) => entity,
+            None => return,
+        };
+        let mut entity = match self.entities.get(&entity_key_to_u32(key)).cloned() {
+            Some(entity) => entity,
+            None => return,
+        };
+        entity.bind_mut().set_state(naia_id, &bytes, true);
+        self.base_mut()
+            .emit_signal("entity_updated", &[entity.to_variant()]);
+    }
+
+    fn delete_entity(&mut self, key: LocalEntityKey) {
+        if let Some(mut entity) = self.entities.remove(&entity_key_to_u32(key)) {
+            self.base_mut()
+                .emit_signal("entity_deleted", &[entity.to_variant()]);
+            entity.queue_free();
+        }
+    }
+
+    fn clear_entities(&mut self) {
+        for (_, mut entity) in self.entities.drain() {
+            entity.queue_free();
+        }
+    }
+}
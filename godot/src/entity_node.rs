@@ -0,0 +1,50 @@
+use godot::prelude::*;
+
+/// A node mirroring an Entity in scope for a `NaiaClient` node, of which it
+/// is a child. Its properties hold the Entity's key, NaiaId & bytes, which
+/// are replaced each time the Entity is updated
+#[derive(GodotClass)]
+#[class(base = Node, rename = NaiaEntity)]
+pub struct NaiaEntityNode {
+    base: Base<Node>,
+    /// The key of the Entity, with its index in bits 8 to 23 & its
+    /// generation in the lowest 8 bits
+    #[var]
+    pub(crate) entity_key: i64,
+    /// The NaiaId of the Entity's type
+    #[var]
+    naia_id: i64,
+    /// The bytes of the Entity, as described by
+    /// `Manifest::get_protocol_doc()`
+    #[var]
+    data: PackedByteArray,
+}
+
+#[godot_api]
+impl INode for NaiaEntityNode {
+    fn init(base: Base<Node>) -> Self {
+        NaiaEntityNode {
+            base,
+            entity_key: 0,
+            naia_id: 0,
+            data: PackedByteArray::new(),
+        }
+    }
+}
+
+#[godot_api]
+impl NaiaEntityNode {
+    /// Emitted after the Entity has been updated, & its data replaced
+    #[signal]
+    fn updated();
+
+    /// Sets the state of the Entity, emitting `updated` unless the Entity
+    /// has only just come into scope
+    pub(crate) fn set_state(&mut self, naia_id: u16, data: &[u8], notify: bool) {
+        self.naia_id = i64::from(naia_id);
+        self.data = PackedByteArray::from(data);
+        if notify {
+            self.base_mut().emit_signal("updated", &[]);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! # Naia Godot
+//! Exposes the naia client to Godot 4 through GDExtension, mapping replicated
+//! entities to nodes & events to signals.
+//!
+//! A `NaiaClient` node connects to a Server, & emits a signal for every event
+//! it receives. Each Entity in scope is mirrored by a `NaiaEntity` child node,
+//! which can instantiate a scene registered for the Entity's type. Events &
+//! Entities are exchanged as their NaiaId & bytes, as described by
+//! `Manifest::get_protocol_doc()`, through `naia_client_ffi::FfiClient`.
+//!
+//! The protocol is only known to Rust, so a GDExtension library registers it
+//! when Godot loads the library's scene level:
+//!
+//! ```ignore
+//! struct GameExtension;
+//!
+//! #[gdextension]
+//! unsafe impl ExtensionLibrary for GameExtension {
+//!     fn on_level_init(level: InitLevel) {
+//!         if level == InitLevel::Scene {
+//!             naia_godot::register_protocol(manifest_load);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! This crate is left out of the naia workspace's default members, so that
+//! building naia doesn't require the godot crate unless the whole workspace is
+//! built. The Server isn't exposed, as it is driven by an async runtime, which
+//! should run outside of Godot's main loop.
+
+#![deny(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_import_braces
+)]
+
+use std::{cell::RefCell, net::SocketAddr};
+
+use naia_client_ffi::{FfiClient, TypedFfiClient};
+use naia_shared::{EntityType, EventType, Manifest};
+
+// the GodotClass derive generates undocumented accessors for the properties
+// of each class
+#[allow(missing_docs)]
+mod client_node;
+#[allow(missing_docs)]
+mod entity_node;
+
+pub use client_node::NaiaClientNode;
+pub use entity_node::NaiaEntityNode;
+
+type ClientFactory = Box<dyn Fn(SocketAddr, Option<(u16, &[u8])>) -> Option<Box<dyn FfiClient>>>;
+
+thread_local! {
+    static CLIENT_FACTORY: RefCell<Option<ClientFactory>> = RefCell::new(None);
+}
+
+/// Registers the protocol which `NaiaClient` nodes connect with, given a
+/// function returning its Manifest. Must be called on Godot's main thread,
+/// before any `NaiaClient` node connects
+pub fn register_protocol<T: 'static + EventType, U: 'static + EntityType>(
+    manifest_func: fn() -> Manifest<T, U>,
+) {
+    let factory: ClientFactory = Box::new(move |server_address, auth| {
+        let client: Box<dyn FfiClient> = Box::new(TypedFfiClient::connect(
+            manifest_func(),
+            server_address,
+            auth,
+        )?);
+        return Some(client);
+    });
+    CLIENT_FACTORY.with(|client_factory| *client_factory.borrow_mut() = Some(factory));
+}
+
+// Creates a client of the registered protocol, or returns None if no protocol
+// has been registered, or the auth Event can't be read
+fn create_client(
+    server_address: SocketAddr,
+    auth: Option<(u16, &[u8])>,
+) -> Option<Box<dyn FfiClient>> {
+    return CLIENT_FACTORY.with(|client_factory| {
+        let client_factory = client_factory.borrow();
+        return client_factory.as_ref()?(server_address, auth);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use naia_example_shared::manifest_load;
+
+    use super::{create_client, register_protocol};
+
+    #[test]
+    fn creates_clients_of_the_registered_protocol() {
+        let server_address = "127.0.0.1:14191".parse().unwrap();
+        assert!(create_client(server_address, None).is_none());
+
+        register_protocol(manifest_load);
+        let client = create_client(server_address, None);
+        assert!(client.unwrap().get_entity_keys().is_empty());
+
+        // an auth Event of an unregistered type can't be read
+        assert!(create_client(server_address, Some((u16::MAX, &[]))).is_none());
+    }
+}
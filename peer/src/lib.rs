@@ -0,0 +1,25 @@
+//! # Naia Peer
+//! A peer for small peer-to-peer games, which runs both a naia server & naia
+//! clients, sending/receiving events to/from every other peer of a full mesh.
+
+#![deny(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_import_braces
+)]
+
+#[macro_use]
+extern crate log;
+
+pub use naia_client::{ClientEvent, ConnectFailure, NaiaClient};
+pub use naia_server::{NaiaServer, ServerEvent, UserKey};
+pub use naia_shared::{assign_peer_ids, Config, NaiaError, PeerId};
+
+mod naia_peer;
+mod peer_event;
+
+pub use naia_peer::NaiaPeer;
+pub use peer_event::PeerEvent;
@@ -0,0 +1,468 @@
+use std::{collections::VecDeque, net::SocketAddr, rc::Rc};
+
+use naia_client::{ClientEvent, MultiClient, NaiaClient, ServerKey};
+use naia_server::{NaiaServer, ServerEvent, UserKey};
+use naia_shared::{
+    assign_peer_ids, to_canonical_address, Config, EntityType, Event, EventId, EventType, Manifest,
+    NaiaError, PacketReader, PeerId, MAX_RAW_PAYLOAD_SIZE,
+};
+
+use super::peer_event::PeerEvent;
+
+// The first byte of every raw payload exchanged between peers
+const APP_PAYLOAD_TAG: u8 = 0;
+const HELLO_TAG: u8 = 1;
+const WELCOME_TAG: u8 = 2;
+
+enum PeerLink {
+    // The connection this peer made to the other peer, & whether the other
+    // peer has welcomed it
+    Outgoing(ServerKey, bool),
+    // The connection the other peer made to this peer, once it has said hello
+    Incoming(UserKey),
+}
+
+/// A peer of a full mesh, for small peer-to-peer games. Each peer runs a
+/// Server, which the peers with higher ids connect to, & connects to the
+/// peers with lower ids as a Client, so that every pair of peers shares one
+/// connection. Every peer is given the addresses of all peers, from which the
+/// same ids are assigned on each of them
+pub struct NaiaPeer<T: EventType, U: EntityType> {
+    peer_id: PeerId,
+    peer_addresses: Vec<(PeerId, SocketAddr)>,
+    config: Option<Config>,
+    server: NaiaServer<T, U>,
+    clients: MultiClient<T, U>,
+    links: Vec<(PeerId, PeerLink)>,
+    outstanding_events: VecDeque<PeerEvent<T>>,
+}
+
+impl<T: EventType, U: EntityType> NaiaPeer<T, U> {
+    /// Create a new peer, given an address to listen at, the addresses of
+    /// every peer of the mesh, an Event/Entity manifest shared by the Server &
+    /// Clients of the peer, and an optional Config
+    pub async fn new(
+        address: SocketAddr,
+        peer_addresses: &[SocketAddr],
+        manifest: Manifest<T, U>,
+        config: Option<Config>,
+    ) -> Self {
+        let mut addresses = peer_addresses.to_vec();
+        addresses.push(address);
+        let peer_addresses = assign_peer_ids(&addresses);
+        let canonical_address = to_canonical_address(address);
+        let peer_id = peer_addresses
+            .iter()
+            .find(|(_, peer_address)| *peer_address == canonical_address)
+            .map(|(peer_id, _)| *peer_id)
+            .expect("too many peer addresses");
+
+        let manifest = Rc::new(manifest);
+        let server =
+            NaiaServer::new_with_shared_manifest(address, manifest.clone(), config.clone()).await;
+
+        NaiaPeer {
+            peer_id,
+            peer_addresses,
+            config,
+            server,
+            clients: MultiClient::new_with_shared_manifest(manifest),
+            links: Vec::new(),
+            outstanding_events: VecDeque::new(),
+        }
+    }
+
+    /// Starts connecting to every peer with a lower id which this peer isn't
+    /// already connected or connecting to, given an optional Authentication
+    /// event. The peers with higher ids connect to this peer when they call
+    /// this. Call again to reconnect after a `PeerEvent::ConnectFailed`
+    pub fn connect_to_mesh(&mut self, auth: Option<T>) {
+        for (peer_id, address) in self.peer_addresses.clone() {
+            if self.peer_id.connects_to(&peer_id) && self.get_link(&peer_id).is_none() {
+                let server_key = self
+                    .clients
+                    .connect(address, self.config.clone(), auth.clone());
+                self.links
+                    .push((peer_id, PeerLink::Outgoing(server_key, false)));
+            }
+        }
+    }
+
+    /// Must be called regularly, maintains the connections to and receives
+    /// messages from all other peers. The connections this peer made are
+    /// polled between the events of its Server, so at least once per tick
+    pub async fn receive(&mut self) -> Result<PeerEvent<T>, NaiaError> {
+        loop {
+            if let Some(event) = self.outstanding_events.pop_front() {
+                return Ok(event);
+            }
+
+            while let Some((server_key, result)) = self.clients.receive() {
+                match result {
+                    Ok(event) => self.handle_client_event(server_key, event),
+                    Err(error) => return Err(error),
+                }
+            }
+            if !self.outstanding_events.is_empty() {
+                continue;
+            }
+
+            match self.server.receive().await {
+                Ok(event) => self.handle_server_event(event),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Queues up an Event to be sent to the given peer, returning its EventId
+    /// on the connection to the peer. Returns an error if this peer is not
+    /// connected to the other peer, or the Event can't be sent
+    pub fn send_event(
+        &mut self,
+        peer_id: &PeerId,
+        event: &impl Event<T>,
+    ) -> Result<EventId, NaiaError> {
+        match self.get_link(peer_id) {
+            Some(PeerLink::Outgoing(server_key, true)) => {
+                let server_key = *server_key;
+                if let Some(client) = self.clients.get_mut(&server_key) {
+                    return client.send_event(event);
+                }
+                return Err(NaiaError::NotConnected);
+            }
+            Some(PeerLink::Incoming(user_key)) => {
+                let user_key = *user_key;
+                return self.server.queue_event(&user_key, event);
+            }
+            _ => return Err(NaiaError::NotConnected),
+        }
+    }
+
+    /// Queues up an Event to be sent to every connected peer. Returns an error
+    /// if the Event can't be sent
+    pub fn broadcast_event(&mut self, event: &impl Event<T>) -> Result<(), NaiaError> {
+        for peer_id in self.connected_peers() {
+            self.send_event(&peer_id, event)?;
+        }
+        return Ok(());
+    }
+
+    /// Queues up an opaque byte payload to be sent to the given peer, as with
+    /// `NaiaServer::send_raw()`. One byte of each raw payload is used by the
+    /// peer, so the payload may be at most `MAX_RAW_PAYLOAD_SIZE - 1` bytes.
+    /// Returns an error if this peer is not connected to the other peer, or if
+    /// the payload is too large
+    pub fn send_raw(&mut self, peer_id: &PeerId, payload: &[u8]) -> Result<(), NaiaError> {
+        if payload.len() >= MAX_RAW_PAYLOAD_SIZE {
+            return Err(NaiaError::PayloadTooLarge(payload.len()));
+        }
+        let mut tagged_payload = vec![APP_PAYLOAD_TAG];
+        tagged_payload.extend_from_slice(payload);
+        match self.get_link(peer_id) {
+            Some(PeerLink::Outgoing(server_key, true)) => {
+                let server_key = *server_key;
+                if let Some(client) = self.clients.get_mut(&server_key) {
+                    return client.send_raw(&tagged_payload);
+                }
+                return Err(NaiaError::NotConnected);
+            }
+            Some(PeerLink::Incoming(user_key)) => {
+                let user_key = *user_key;
+                return self.server.send_raw(&user_key, &tagged_payload);
+            }
+            _ => return Err(NaiaError::NotConnected),
+        }
+    }
+
+    /// Sends all Entity updates & queued Events to the peers which connected
+    /// to this peer. Should be called after every Tick event. Returns the
+    /// first error met sending a packet
+    pub async fn send_all_updates(&mut self) -> Result<(), NaiaError> {
+        return self.server.send_all_updates().await;
+    }
+
+    /// Gets the id of this peer
+    pub fn get_peer_id(&self) -> PeerId {
+        return self.peer_id;
+    }
+
+    /// Gets the ids of every other peer of the mesh
+    pub fn peer_ids(&self) -> Vec<PeerId> {
+        return self
+            .peer_addresses
+            .iter()
+            .map(|(peer_id, _)| *peer_id)
+            .filter(|peer_id| *peer_id != self.peer_id)
+            .collect();
+    }
+
+    /// Gets the address of the given peer
+    pub fn get_peer_address(&self, peer_id: &PeerId) -> Option<SocketAddr> {
+        return self
+            .peer_addresses
+            .iter()
+            .find(|(id, _)| id == peer_id)
+            .map(|(_, address)| *address);
+    }
+
+    /// Returns whether this peer is connected to the given peer
+    pub fn is_connected(&self, peer_id: &PeerId) -> bool {
+        match self.get_link(peer_id) {
+            Some(PeerLink::Outgoing(_, welcomed)) => return *welcomed,
+            Some(PeerLink::Incoming(_)) => return true,
+            None => return false,
+        }
+    }
+
+    /// Gets the ids of every peer this peer is connected to
+    pub fn connected_peers(&self) -> Vec<PeerId> {
+        return self
+            .links
+            .iter()
+            .map(|(peer_id, _)| *peer_id)
+            .filter(|peer_id| self.is_connected(peer_id))
+            .collect();
+    }
+
+    /// Gets the Server of this peer, which the peers with higher ids are
+    /// connected to
+    pub fn get_server(&self) -> &NaiaServer<T, U> {
+        return &self.server;
+    }
+
+    /// Gets the Server of this peer, to register Entities, manage rooms, or
+    /// authenticate other peers with `NaiaServer::on_auth()`
+    pub fn get_server_mut(&mut self) -> &mut NaiaServer<T, U> {
+        return &mut self.server;
+    }
+
+    /// Gets the UserKey of the given peer, if it has a lower id & is connected
+    /// to this peer's Server
+    pub fn get_user_key(&self, peer_id: &PeerId) -> Option<UserKey> {
+        match self.get_link(peer_id) {
+            Some(PeerLink::Incoming(user_key)) => return Some(*user_key),
+            _ => return None,
+        }
+    }
+
+    /// Gets the Client of the connection this peer made to the given peer, if
+    /// it has a lower id
+    pub fn get_client(&self, peer_id: &PeerId) -> Option<&NaiaClient<T, U>> {
+        match self.get_link(peer_id) {
+            Some(PeerLink::Outgoing(server_key, _)) => return self.clients.get(server_key),
+            _ => return None,
+        }
+    }
+
+    /// Gets the Client of the connection this peer made to the given peer, if
+    /// it has a lower id, to read the other peer's Entities
+    pub fn get_client_mut(&mut self, peer_id: &PeerId) -> Option<&mut NaiaClient<T, U>> {
+        match self.get_link(peer_id) {
+            Some(PeerLink::Outgoing(server_key, _)) => {
+                let server_key = *server_key;
+                return self.clients.get_mut(&server_key);
+            }
+            _ => return None,
+        }
+    }
+
+    // Private methods
+
+    fn get_link(&self, peer_id: &PeerId) -> Option<&PeerLink> {
+        return self
+            .links
+            .iter()
+            .find(|(id, _)| id == peer_id)
+            .map(|(_, link)| link);
+    }
+
+    fn remove_link(&mut self, peer_id: &PeerId) {
+        self.links.retain(|(id, _)| id != peer_id);
+    }
+
+    fn get_outgoing_peer(&self, server_key: &ServerKey) -> Option<(PeerId, bool)> {
+        for (peer_id, link) in self.links.iter() {
+            if let PeerLink::Outgoing(link_server_key, welcomed) = link {
+                if link_server_key == server_key {
+                    return Some((*peer_id, *welcomed));
+                }
+            }
+        }
+        return None;
+    }
+
+    fn set_welcomed(&mut self, peer_id: &PeerId, welcomed: bool) {
+        for (id, link) in self.links.iter_mut() {
+            if let PeerLink::Outgoing(_, link_welcomed) = link {
+                if id == peer_id {
+                    *link_welcomed = welcomed;
+                }
+            }
+        }
+    }
+
+    fn get_incoming_peer(&self, user_key: &UserKey) -> Option<PeerId> {
+        for (peer_id, link) in self.links.iter() {
+            if let PeerLink::Incoming(link_user_key) = link {
+                if link_user_key == user_key {
+                    return Some(*peer_id);
+                }
+            }
+        }
+        return None;
+    }
+
+    fn handle_client_event(&mut self, server_key: ServerKey, event: ClientEvent<T>) {
+        let (peer_id, welcomed) = match self.get_outgoing_peer(&server_key) {
+            Some((peer_id, welcomed)) => (peer_id, welcomed),
+            None => return,
+        };
+        match event {
+            ClientEvent::Connection => {
+                self.send_hellos();
+            }
+            ClientEvent::Disconnection => {
+                // the Client reconnects by itself, & says hello again once it has
+                if welcomed {
+                    self.set_welcomed(&peer_id, false);
+                    self.outstanding_events
+                        .push_back(PeerEvent::Disconnection(peer_id));
+                }
+            }
+            ClientEvent::ConnectFailed(failure) => {
+                self.remove_link(&peer_id);
+                self.clients.remove(&server_key);
+                self.outstanding_events
+                    .push_back(PeerEvent::ConnectFailed(peer_id, failure));
+            }
+            ClientEvent::RawPayload(payload) => match payload.first() {
+                Some(&WELCOME_TAG) => {
+                    self.receive_welcome(peer_id, welcomed);
+                }
+                Some(&APP_PAYLOAD_TAG) => {
+                    self.receive_welcome(peer_id, welcomed);
+                    self.outstanding_events
+                        .push_back(PeerEvent::RawPayload(peer_id, payload[1..].into()));
+                }
+                _ => {}
+            },
+            ClientEvent::Event(event) => {
+                self.receive_welcome(peer_id, welcomed);
+                self.outstanding_events
+                    .push_back(PeerEvent::Event(peer_id, event));
+            }
+            event => {
+                self.outstanding_events
+                    .push_back(PeerEvent::Client(peer_id, event));
+            }
+        }
+    }
+
+    fn handle_server_event(&mut self, event: ServerEvent<T>) {
+        match event {
+            ServerEvent::RawPayload(user_key, payload) => {
+                match (payload.first(), self.get_incoming_peer(&user_key)) {
+                    (Some(&HELLO_TAG), _) => {
+                        self.receive_hello(user_key, &payload[1..]);
+                    }
+                    (Some(&APP_PAYLOAD_TAG), Some(peer_id)) => {
+                        self.outstanding_events
+                            .push_back(PeerEvent::RawPayload(peer_id, payload[1..].into()));
+                    }
+                    _ => {
+                        self.outstanding_events.push_back(PeerEvent::Server(
+                            ServerEvent::RawPayload(user_key, payload),
+                        ));
+                    }
+                }
+            }
+            ServerEvent::Event(user_key, event) => match self.get_incoming_peer(&user_key) {
+                Some(peer_id) => {
+                    self.outstanding_events
+                        .push_back(PeerEvent::Event(peer_id, event));
+                }
+                None => {
+                    self.outstanding_events
+                        .push_back(PeerEvent::Server(ServerEvent::Event(user_key, event)));
+                }
+            },
+            ServerEvent::Disconnection(user_key, user) => match self.get_incoming_peer(&user_key) {
+                Some(peer_id) => {
+                    self.remove_link(&peer_id);
+                    self.outstanding_events
+                        .push_back(PeerEvent::Disconnection(peer_id));
+                }
+                None => {
+                    self.outstanding_events.push_back(PeerEvent::Server(
+                        ServerEvent::Disconnection(user_key, user),
+                    ));
+                }
+            },
+            ServerEvent::Tick => {
+                // hellos are unreliable, so they are repeated until welcomed
+                self.send_hellos();
+                self.outstanding_events.push_back(PeerEvent::Tick);
+            }
+            event => {
+                self.outstanding_events.push_back(PeerEvent::Server(event));
+            }
+        }
+    }
+
+    fn send_hellos(&mut self) {
+        let mut hello = vec![HELLO_TAG];
+        self.peer_id.write(&mut hello);
+        for (_, link) in self.links.iter() {
+            if let PeerLink::Outgoing(server_key, false) = link {
+                if let Some(client) = self.clients.get_mut(server_key) {
+                    // a connection which hasn't been established yet is
+                    // greeted on a later Tick
+                    let _ = client.send_raw(&hello);
+                }
+            }
+        }
+    }
+
+    // The other peer only sends to a connection once it has welcomed it, but
+    // the welcome may arrive after what was sent with it
+    fn receive_welcome(&mut self, peer_id: PeerId, welcomed: bool) {
+        if !welcomed {
+            self.set_welcomed(&peer_id, true);
+            self.outstanding_events
+                .push_back(PeerEvent::Connection(peer_id));
+        }
+    }
+
+    fn receive_hello(&mut self, user_key: UserKey, data: &[u8]) {
+        let mut reader = PacketReader::new(data);
+        let peer_id = match PeerId::read(&mut reader) {
+            Some(peer_id) => peer_id,
+            None => return,
+        };
+        if !peer_id.connects_to(&self.peer_id) || self.get_peer_address(&peer_id).is_none() {
+            warn!("received hello from unexpected peer: {:?}", peer_id);
+            return;
+        }
+
+        match self.get_link(&peer_id) {
+            // the welcome was lost, so the hello was repeated
+            Some(PeerLink::Incoming(linked_user_key)) if *linked_user_key == user_key => {}
+            Some(_) => {
+                // the peer has reconnected before its previous connection timed out
+                self.remove_link(&peer_id);
+                self.links.push((peer_id, PeerLink::Incoming(user_key)));
+                self.outstanding_events
+                    .push_back(PeerEvent::Disconnection(peer_id));
+                self.outstanding_events
+                    .push_back(PeerEvent::Connection(peer_id));
+            }
+            None => {
+                self.links.push((peer_id, PeerLink::Incoming(user_key)));
+                self.outstanding_events
+                    .push_back(PeerEvent::Connection(peer_id));
+            }
+        }
+        // the User has just said hello, so it is connected
+        let _ = self.server.send_raw(&user_key, &[WELCOME_TAG]);
+    }
+}
@@ -0,0 +1,30 @@
+use naia_client::{ClientEvent, ConnectFailure};
+use naia_server::ServerEvent;
+use naia_shared::{EventType, PeerId};
+
+/// An Event that is emitted as a result of some communication with another
+/// peer, or a Tick event
+pub enum PeerEvent<T: EventType> {
+    /// Occurs when a connection with another peer has been established, and
+    /// both peers have identified each other
+    Connection(PeerId),
+    /// Occurs when the connection with another peer has been lost, usually as
+    /// the result of a timeout
+    Disconnection(PeerId),
+    /// Occurs when this peer has given up connecting to another peer. Call
+    /// `NaiaPeer::connect_to_mesh()` to try again
+    ConnectFailed(PeerId, ConnectFailure),
+    /// An Event emitted to this peer from another peer
+    Event(PeerId, T),
+    /// A raw payload sent by another peer with `NaiaPeer::send_raw()`
+    RawPayload(PeerId, Box<[u8]>),
+    /// Any other event of this peer's Server, such as an Entity coming into
+    /// scope for another peer, or any event of a Client which is not a peer
+    Server(ServerEvent<T>),
+    /// Any other event of the connection this peer made to another peer, such
+    /// as the creation of one of the other peer's Entities
+    Client(PeerId, ClientEvent<T>),
+    /// A Tick Event of this peer's Server, the duration between Tick events is
+    /// defined in the Config object passed to the peer on initialization
+    Tick,
+}
@@ -0,0 +1,86 @@
+use std::{cell::RefCell, rc::Rc};
+
+use naia_shared::{Entity, EntityType, Event, EventType, NaiaError};
+
+use super::{
+    entities::entity_key::entity_key::EntityKey, host_event::HostEvent, naia_server::NaiaServer,
+    user::user_key::UserKey,
+};
+
+/// A simulated Client, for AI players & server-side tests, which the
+/// application drives alongside the Server rather than over a socket. A
+/// BotClient is a local User of the Server, so the Server handles it like any
+/// other Client: it is added to Rooms, sent Events, & its Events & commands
+/// are received as ServerEvents. Its replicated view is made up of the
+/// Entities in its scope, which it shares with the Server
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BotClient {
+    user_key: UserKey,
+}
+
+impl BotClient {
+    /// Connects a new BotClient to the given Server. A Connection event is
+    /// emitted for it like for any Client
+    pub fn connect<T: EventType, U: EntityType>(server: &mut NaiaServer<T, U>) -> Self {
+        BotClient {
+            user_key: server.connect_local_user(),
+        }
+    }
+
+    /// Disconnects the BotClient, emitting a Disconnection event for it
+    pub fn disconnect<T: EventType, U: EntityType>(&self, server: &mut NaiaServer<T, U>) {
+        server.disconnect_local_user(&self.user_key);
+    }
+
+    /// Gets the UserKey of the BotClient, by which the Server knows it
+    pub fn get_user_key(&self) -> UserKey {
+        return self.user_key;
+    }
+
+    /// Sends an Event to the Server, as described in
+    /// `NaiaServer::send_local_event()`
+    pub fn send_event<T: EventType, U: EntityType>(
+        &self,
+        server: &mut NaiaServer<T, U>,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        return server.send_local_event(&self.user_key, event);
+    }
+
+    /// Injects an input for the Server's next tick, as described in
+    /// `NaiaServer::send_local_command()`
+    pub fn send_command<T: EventType, U: EntityType>(
+        &self,
+        server: &mut NaiaServer<T, U>,
+        command: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        return server.send_local_command(&self.user_key, command);
+    }
+
+    /// Receives the next event for the BotClient, or None if there is no new
+    /// event, mirroring `NaiaClient::receive()`
+    pub fn receive<T: EventType, U: EntityType>(
+        &self,
+        server: &mut NaiaServer<T, U>,
+    ) -> Option<HostEvent<T>> {
+        return server.receive_local(&self.user_key);
+    }
+
+    /// Gets the keys of every Entity in the BotClient's scope
+    pub fn entity_keys<T: EventType, U: EntityType>(
+        &self,
+        server: &NaiaServer<T, U>,
+    ) -> Vec<EntityKey> {
+        return server.get_local_entity_keys(&self.user_key);
+    }
+
+    /// Given an EntityKey, gets a reference to an Entity in the BotClient's
+    /// scope
+    pub fn get_entity<'s, T: EventType, U: EntityType>(
+        &self,
+        server: &'s NaiaServer<T, U>,
+        key: &EntityKey,
+    ) -> Option<&'s Rc<RefCell<dyn Entity<U>>>> {
+        return server.get_local_entity(&self.user_key, key);
+    }
+}
@@ -1,19 +1,64 @@
-use std::{cell::RefCell, net::SocketAddr, rc::Rc};
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use byteorder::{BigEndian, WriteBytesExt};
 
 use naia_shared::{
-    AckManager, Config, Connection, Entity, EntityType, Event, EventManager, EventType,
-    ManagerType, Manifest, PacketReader, PacketType, PacketWriter, RttTracker, SequenceNumber,
-    Timer,
+    AckLatencyHistogram, AckManager, BackpressureMonitor, BlobId, BlobManager, BlobMessage,
+    Capabilities, ClientId, ClientRosterChange, CommandStats, Config, Congestion, Connection,
+    ConnectionQuality, ConnectionQualityMonitor, ConnectionStats, CustomSections, DespawnReason,
+    DropLog, DropReason, Entity, EntityDebugDump, EntityType, Event, EventClone, EventId,
+    EventManager, EventQueueStats, EventType, LinkState, LocalEntityKey, ManagerType, Manifest,
+    ManifestDescriptor, PacketFramingFactory, PacketReader, PacketType, PacketWriter,
+    PredictionKey, PrefabId, RawPayloadManager, ReplayWindow, RpcMessage, RttStats, RttTracker,
+    SequenceNumber, StallMonitor, TickRateChange, TimeDilation, Timer,
 };
 
-use super::entities::{
-    entity_key::entity_key::EntityKey, entity_packet_writer::EntityPacketWriter,
-    mut_handler::MutHandler, server_entity_manager::ServerEntityManager,
+use super::{
+    command_buffer::{CommandBuffer, CommandBufferStats},
+    entities::{
+        entity_key::entity_key::EntityKey, entity_packet_writer::EntityPacketWriter,
+        mut_handler::MutHandler, server_entity_manager::ServerEntityManager,
+    },
+    missing_command_policy::MissingCommandPolicy,
+    pause_policy::PausePolicy,
+    replication_profiler::ReplicationProfiler,
+    room_snapshot::RoomSnapshot,
+    tick_stats::TickTimings,
+    user::user_key::UserKey,
 };
 
 pub struct ClientConnection<T: EventType, U: EntityType> {
     connection: Connection<T>,
     entity_manager: ServerEntityManager<U>,
+    loading: bool,
+    replication_pause: Option<PausePolicy>,
+    resync_on_resume: bool,
+    raw_payload_budget: usize,
+    event_packet_share: f32,
+    entity_packet_share: f32,
+    handoff_payload: Option<Box<[u8]>>,
+    time_dilation: TimeDilation,
+    command_buffer: CommandBuffer<T>,
+    packet_framing: Option<PacketFramingFactory>,
+    strict_protocol: bool,
+    protocol_violation_reported: bool,
+    held_entity_events: VecDeque<(EntityKey, LocalEntityKey, Box<dyn Event<T>>)>,
+    client_id: ClientId,
+    roster_revision: u32,
+    manifest_revision: u32,
+    tick_rate_revision: u32,
+    client_data: HashMap<TypeId, Box<dyn Any>>,
+    connected_at: Instant,
+    migrated: bool,
+    auth_event: Option<T>,
+    clock_sync_timer: Option<Timer>,
 }
 
 impl<T: EventType, U: EntityType> ClientConnection<T, U> {
@@ -21,6 +66,7 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         address: SocketAddr,
         mut_handler: Option<&Rc<RefCell<MutHandler>>>,
         config: &Config,
+        client_id: ClientId,
     ) -> Self {
         let heartbeat_interval = config.heartbeat_interval;
         let timeout_duration = config.disconnection_timeout_duration;
@@ -33,35 +79,216 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
                 Timer::new(heartbeat_interval),
                 Timer::new(timeout_duration),
                 AckManager::new(),
-                RttTracker::new(rtt_smoothing_factor, rtt_max_value),
-                EventManager::new(),
+                RttTracker::new(rtt_smoothing_factor, rtt_max_value, config.rtt_history_size),
+                EventManager::new(config.command_redundancy),
+                BlobManager::new(config.blob_bandwidth_limit),
+                RawPayloadManager::new(config.raw_payload_budget),
+                ConnectionQualityMonitor::new(config.connection_quality_thresholds),
+                DropLog::new(address, config.log_dropped_packets),
+                BackpressureMonitor::new(config.backpressure_thresholds),
+                StallMonitor::new(config.stall_thresholds),
+                config.replay_protection.then(ReplayWindow::new),
             ),
-            entity_manager: ServerEntityManager::new(address, mut_handler.unwrap()),
+            entity_manager: ServerEntityManager::new(
+                address,
+                mut_handler.unwrap(),
+                config.entity_tombstone_duration,
+                config.atomic_entity_ticks,
+            ),
+            loading: false,
+            replication_pause: None,
+            resync_on_resume: false,
+            raw_payload_budget: config.raw_payload_budget,
+            event_packet_share: config.event_packet_share,
+            entity_packet_share: config.entity_packet_share,
+            handoff_payload: None,
+            time_dilation: TimeDilation::new(config.input_buffer_target_depth),
+            command_buffer: CommandBuffer::new(),
+            packet_framing: None,
+            strict_protocol: config.strict_protocol,
+            protocol_violation_reported: false,
+            held_entity_events: VecDeque::new(),
+            client_id,
+            roster_revision: 0,
+            manifest_revision: 0,
+            tick_rate_revision: 0,
+            client_data: HashMap::new(),
+            connected_at: Instant::now(),
+            migrated: false,
+            auth_event: None,
+            clock_sync_timer: config.clock_sync_interval.map(Timer::new),
         };
     }
 
-    pub fn get_outgoing_packet(&mut self, manifest: &Manifest<T, U>) -> Option<Box<[u8]>> {
-        if self.connection.has_outgoing_events() || self.entity_manager.has_outgoing_messages() {
-            let mut writer = PacketWriter::new();
+    pub fn get_outgoing_packet(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<UserKey>,
+        user_key: &UserKey,
+        current_tick: u16,
+        mut profiler: Option<&mut ReplicationProfiler>,
+    ) -> Option<Box<[u8]>> {
+        self.release_entity_events();
+        let send_entities = !self.loading
+            && self.replication_pause.is_none()
+            && self.entity_manager.has_outgoing_messages();
+        if self.connection.has_outgoing_events()
+            || self.connection.has_outgoing_rpcs()
+            || self.connection.has_outgoing_timed_events()
+            || self.connection.has_outgoing_entity_events()
+            || self.connection.has_outgoing_relay_events()
+            || self.connection.has_outgoing_roster_changes()
+            || self.connection.has_outgoing_manifest_deltas()
+            || self.connection.has_outgoing_shutdowns()
+            || self.connection.has_outgoing_tick_rate_changes()
+            || self.connection.has_outgoing_blob_chunks()
+            || self.connection.has_outgoing_raw_payloads()
+            || custom_sections.has_outgoing(user_key)
+            || send_entities
+        {
+            let mut writer = PacketWriter::from_factory(self.packet_framing.as_ref());
+            writer.set_packet_shares(self.event_packet_share, self.entity_packet_share);
 
             let next_packet_index: u16 = self.get_next_packet_index();
+            while let Some(popped_manifest_delta) = self
+                .connection
+                .pop_outgoing_manifest_delta(next_packet_index)
+            {
+                let (revision, delta) = &popped_manifest_delta;
+                if !writer.write_manifest_delta(*revision, delta) {
+                    self.connection
+                        .unpop_outgoing_manifest_delta(next_packet_index, popped_manifest_delta);
+                    break;
+                }
+            }
+            while let Some(popped_tick_rate_change) = self
+                .connection
+                .pop_outgoing_tick_rate_change(next_packet_index)
+            {
+                if !writer.write_tick_rate_change(&popped_tick_rate_change) {
+                    self.connection.unpop_outgoing_tick_rate_change(
+                        next_packet_index,
+                        popped_tick_rate_change,
+                    );
+                    break;
+                }
+            }
             while let Some(popped_event) = self.connection.pop_outgoing_event(next_packet_index) {
-                if !writer.write_event(manifest, &popped_event) {
+                if !writer.write_event(manifest, popped_event.0, &popped_event.1) {
                     self.connection
                         .unpop_outgoing_event(next_packet_index, &popped_event);
                     break;
                 }
             }
-            while let Some(popped_entity_message) =
-                self.entity_manager.pop_outgoing_message(next_packet_index)
+            while let Some(popped_rpc) = self.connection.pop_outgoing_rpc(next_packet_index) {
+                if !writer.write_rpc(manifest, &popped_rpc) {
+                    self.connection
+                        .unpop_outgoing_rpc(next_packet_index, &popped_rpc);
+                    break;
+                }
+            }
+            while let Some(popped_timed_event) =
+                self.connection.pop_outgoing_timed_event(next_packet_index)
+            {
+                let (event_id, tick, event) = &popped_timed_event;
+                if !writer.write_timed_event(manifest, *event_id, current_tick, *tick, event) {
+                    self.connection
+                        .unpop_outgoing_timed_event(next_packet_index, &popped_timed_event);
+                    break;
+                }
+            }
+            if send_entities {
+                while let Some((batch_index, popped_entity_message)) =
+                    self.entity_manager.pop_outgoing_message(next_packet_index)
+                {
+                    let bytes_before = writer.bytes_number();
+                    if !EntityPacketWriter::write_entity_message(
+                        &mut writer,
+                        manifest,
+                        batch_index,
+                        &popped_entity_message,
+                    ) {
+                        self.entity_manager.unpop_outgoing_message(
+                            next_packet_index,
+                            batch_index,
+                            &popped_entity_message,
+                        );
+                        break;
+                    }
+                    if let Some(profiler) = profiler.as_deref_mut() {
+                        if let Some(entity_key) = popped_entity_message.get_entity_key() {
+                            let naia_id = popped_entity_message
+                                .get_type_id()
+                                .map(|type_id| manifest.get_entity_naia_id(&type_id));
+                            profiler.record_message(
+                                entity_key,
+                                naia_id,
+                                writer.bytes_number() - bytes_before,
+                            );
+                        }
+                    }
+                }
+            }
+            while let Some(popped_entity_event) =
+                self.connection.pop_outgoing_entity_event(next_packet_index)
+            {
+                let (event_id, local_key, event) = &popped_entity_event;
+                if !writer.write_entity_event(manifest, *event_id, *local_key, event) {
+                    self.connection
+                        .unpop_outgoing_entity_event(next_packet_index, &popped_entity_event);
+                    break;
+                }
+            }
+            while let Some(popped_relay_event) =
+                self.connection.pop_outgoing_relay_event(next_packet_index)
+            {
+                let (event_id, sender_id, event) = &popped_relay_event;
+                if !writer.write_relay_event(manifest, *event_id, *sender_id, event) {
+                    self.connection
+                        .unpop_outgoing_relay_event(next_packet_index, &popped_relay_event);
+                    break;
+                }
+            }
+            while let Some(popped_roster_change) = self
+                .connection
+                .pop_outgoing_roster_change(next_packet_index)
             {
-                if !EntityPacketWriter::write_entity_message(
-                    &mut writer,
-                    manifest,
-                    &popped_entity_message,
-                ) {
-                    self.entity_manager
-                        .unpop_outgoing_message(next_packet_index, &popped_entity_message);
+                let (revision, change) = &popped_roster_change;
+                if !writer.write_roster_change(*revision, change) {
+                    self.connection
+                        .unpop_outgoing_roster_change(next_packet_index, popped_roster_change);
+                    break;
+                }
+            }
+
+            while let Some(popped_raw_payload) = self.connection.pop_outgoing_raw_payload() {
+                if !writer.write_raw_payload(&popped_raw_payload, self.raw_payload_budget) {
+                    self.connection
+                        .unpop_outgoing_raw_payload(popped_raw_payload);
+                    break;
+                }
+            }
+
+            custom_sections.write_outgoing(user_key, &mut writer);
+
+            while let Some(popped_shutdown) =
+                self.connection.pop_outgoing_shutdown(next_packet_index)
+            {
+                if !writer.write_shutdown(&popped_shutdown) {
+                    self.connection
+                        .unpop_outgoing_shutdown(next_packet_index, popped_shutdown);
+                    break;
+                }
+            }
+
+            // blob chunks are written last, so they only use the space other
+            // messages have left in the packet
+            while let Some(popped_chunk) =
+                self.connection.pop_outgoing_blob_chunk(next_packet_index)
+            {
+                if !writer.write_blob_chunk(&popped_chunk) {
+                    self.connection
+                        .unpop_outgoing_blob_chunk(next_packet_index, &popped_chunk);
                     break;
                 }
             }
@@ -72,6 +299,9 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
 
                 // Add header to it
                 let payload = self.process_outgoing_header(PacketType::Data, &out_bytes);
+                if let Some(profiler) = profiler {
+                    profiler.record_packet(*user_key);
+                }
                 return Some(payload);
             }
         }
@@ -79,15 +309,39 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         return None;
     }
 
-    pub fn process_incoming_data(&mut self, manifest: &Manifest<T, U>, data: &mut [u8]) {
+    pub fn process_incoming_data(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        custom_sections: &mut CustomSections<UserKey>,
+        user_key: &UserKey,
+        data: &mut [u8],
+    ) {
         let mut reader = PacketReader::new(data);
-        while reader.has_more() {
-            let manager_type: ManagerType = reader.read_u8().into();
-            match manager_type {
-                ManagerType::Event => {
-                    self.connection.process_event_data(&mut reader, manifest);
+        while let Some(manager_type_byte) = reader.read_u8() {
+            let manager_type: ManagerType = manager_type_byte.into();
+            let readable = match manager_type {
+                ManagerType::Event => self.connection.process_event_data(&mut reader, manifest),
+                ManagerType::Rpc => self.connection.process_rpc_data(&mut reader, manifest),
+                ManagerType::Blob => self.connection.process_blob_data(&mut reader),
+                ManagerType::Raw => self.connection.process_raw_data(&mut reader),
+                ManagerType::Command => self.connection.process_command_data(&mut reader, manifest),
+                ManagerType::RelayEvent => self
+                    .connection
+                    .process_relay_event_data(&mut reader, manifest),
+                ManagerType::Custom => {
+                    let readable = custom_sections.process_data(user_key, &mut reader);
+                    if !readable {
+                        self.connection.record_drop(DropReason::OversizedLength);
+                    }
+                    readable
                 }
-                _ => {}
+                _ => {
+                    self.connection.record_drop(DropReason::UnknownManagerType);
+                    false
+                }
+            };
+            if !readable {
+                break;
             }
         }
     }
@@ -96,20 +350,160 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         return self.entity_manager.has_entity(key);
     }
 
-    pub fn add_entity(&mut self, key: &EntityKey, entity: &Rc<RefCell<dyn Entity<U>>>) {
-        self.entity_manager.add_entity(key, entity);
+    pub fn add_entity(
+        &mut self,
+        key: &EntityKey,
+        entity: &Rc<RefCell<dyn Entity<U>>>,
+        prefab_id: Option<PrefabId>,
+    ) -> bool {
+        return self.entity_manager.add_entity(key, entity, prefab_id);
+    }
+
+    pub fn add_entities_from_snapshot(
+        &mut self,
+        snapshot: &RoomSnapshot,
+        entities: Vec<(usize, EntityKey, Rc<RefCell<dyn Entity<U>>>)>,
+    ) -> Vec<EntityKey> {
+        return self
+            .entity_manager
+            .add_entities_from_snapshot(snapshot, entities);
+    }
+
+    pub fn get_local_entity_keys(&self) -> Vec<(EntityKey, LocalEntityKey, bool)> {
+        return self.entity_manager.get_local_keys();
+    }
+
+    pub fn restore_local_entity_keys(
+        &mut self,
+        local_keys: Vec<(EntityKey, LocalEntityKey, bool)>,
+    ) {
+        self.entity_manager.restore_local_keys(local_keys);
+    }
+
+    pub fn get_next_batch_index(&self) -> u16 {
+        return self.entity_manager.get_next_batch_index();
+    }
+
+    pub fn restore_next_batch_index(&mut self, next_batch_index: u16) {
+        self.entity_manager
+            .restore_next_batch_index(next_batch_index);
     }
 
-    pub fn remove_entity(&mut self, key: &EntityKey) {
-        self.entity_manager.remove_entity(key);
+    pub fn is_snapshot_pending(&self) -> bool {
+        return self.entity_manager.is_snapshot_pending();
     }
 
-    pub fn collect_entity_updates(&mut self) {
-        self.entity_manager.collect_entity_updates();
+    pub fn set_entity_prediction_key(&mut self, key: &EntityKey, prediction_key: PredictionKey) {
+        self.entity_manager.set_prediction_key(key, prediction_key);
+    }
+
+    pub fn remove_entity(&mut self, key: &EntityKey, reason: Option<DespawnReason>) -> bool {
+        return self.entity_manager.remove_entity(key, reason);
+    }
+
+    pub fn collect_entity_updates(
+        &mut self,
+        priority_func: &dyn Fn(&EntityKey) -> f32,
+        keyframe_interval_func: &dyn Fn(&EntityKey) -> Option<u16>,
+        max_updates: Option<u16>,
+        current_tick: u16,
+        timings: &mut TickTimings,
+    ) -> (usize, usize) {
+        // while the Client is loading, or replication is paused, mutations either
+        // accumulate in the state masks and are collected once the Client is
+        // ready again, or are discarded
+        if self.replication_pause == Some(PausePolicy::Discard) {
+            self.entity_manager.discard_entity_updates();
+        }
+        // while the link is degraded or stalled, mutations accumulate until
+        // the next tick updates are sent on
+        if !self.connection.should_send_entity_updates(current_tick) {
+            return (0, 0);
+        }
+        if !self.loading && self.replication_pause.is_none() {
+            if self.entity_manager.is_snapshot_pending() {
+                self.entity_manager.begin_snapshot();
+            }
+            let update_counts = self.entity_manager.collect_entity_updates(
+                priority_func,
+                keyframe_interval_func,
+                max_updates,
+                timings,
+            );
+            self.entity_manager.end_tick(current_tick);
+            return update_counts;
+        }
+        return (0, 0);
+    }
+
+    pub fn set_loading(&mut self, loading: bool) {
+        self.loading = loading;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        return self.loading;
+    }
+
+    pub fn pause_replication(&mut self, policy: PausePolicy) {
+        if policy == PausePolicy::Discard {
+            self.resync_on_resume = true;
+        }
+        self.replication_pause = Some(policy);
+    }
+
+    pub fn resume_replication(&mut self) {
+        if self.replication_pause.is_none() {
+            return;
+        }
+        self.replication_pause = None;
+        if self.resync_on_resume {
+            self.resync_on_resume = false;
+            self.entity_manager.mark_all_mutated();
+        }
+    }
+
+    pub fn is_replication_paused(&self) -> bool {
+        return self.replication_pause.is_some();
     }
 
     // Pass-through methods to underlying common connection
 
+    /// Begins handing the Client off to the Server at the given address, after
+    /// which the Client is only sent the address & transfer token, until it
+    /// leaves
+    pub fn begin_handoff(&mut self, address: &SocketAddr, transfer_token: u64) {
+        let mut payload = Vec::new();
+        naia_shared::utils::write_socket_address(address, &mut payload);
+        payload.write_u64::<BigEndian>(transfer_token).unwrap();
+        self.handoff_payload = Some(payload.into_boxed_slice());
+    }
+
+    pub fn get_handoff_payload(&self) -> Option<Box<[u8]>> {
+        return self.handoff_payload.clone();
+    }
+
+    pub fn get_undelivered_message_count(&self) -> usize {
+        let (_, undelivered_events) = self.connection.get_undelivered_events();
+        return undelivered_events.len() + self.entity_manager.get_undelivered_message_count();
+    }
+
+    pub fn debug_dump_entities(&self, manifest: &Manifest<T, U>) -> EntityDebugDump {
+        return self.entity_manager.debug_dump(manifest);
+    }
+
+    pub fn get_undelivered_events(&self) -> (EventId, Vec<(EventId, Rc<Box<dyn Event<T>>>)>) {
+        return self.connection.get_undelivered_events();
+    }
+
+    pub fn restore_undelivered_events(
+        &mut self,
+        next_event_id: EventId,
+        events: Vec<(EventId, Box<dyn Event<T>>)>,
+    ) {
+        self.connection
+            .restore_undelivered_events(next_event_id, events);
+    }
+
     pub fn mark_sent(&mut self) {
         return self.connection.mark_sent();
     }
@@ -118,6 +512,22 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         return self.connection.should_send_heartbeat();
     }
 
+    /// Returns whether a heartbeat should be sent to keep the Client's estimate
+    /// of the Server's clock current, even though other data has been sent
+    pub fn should_sync_clock(&self) -> bool {
+        return self
+            .clock_sync_timer
+            .as_ref()
+            .map_or(false, |timer| timer.ringing());
+    }
+
+    /// Records that a heartbeat carrying the Server's clock has been sent
+    pub fn mark_clock_synced(&mut self) {
+        if let Some(timer) = &mut self.clock_sync_timer {
+            timer.reset();
+        }
+    }
+
     pub fn mark_heard(&mut self) {
         return self.connection.mark_heard();
     }
@@ -126,10 +536,96 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         return self.connection.should_drop();
     }
 
-    pub fn process_incoming_header(&mut self, payload: &[u8]) -> Box<[u8]> {
-        return self
+    pub fn process_incoming_header(&mut self, payload: &[u8], current_tick: u16) -> Box<[u8]> {
+        let payload = self
             .connection
             .process_incoming_header(payload, &mut Some(&mut self.entity_manager));
+        if let Some(client_tick) = self.connection.get_remote_tick() {
+            let lead = client_tick.wrapping_sub(current_tick) as i16;
+            self.time_dilation.record_lead(lead);
+            self.connection
+                .set_time_dilation(self.time_dilation.get_factor());
+        }
+        return payload;
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.connection.set_capabilities(capabilities);
+    }
+
+    pub fn get_capabilities(&self) -> Capabilities {
+        return self.connection.get_capabilities();
+    }
+
+    pub fn has_compact_headers(&self) -> bool {
+        return self.connection.has_compact_headers();
+    }
+
+    pub fn set_current_tick(&mut self, current_tick: u16) {
+        self.connection.set_host_tick(Some(current_tick));
+    }
+
+    /// Moves the commands which have arrived into the command buffer, given
+    /// the Server's current tick
+    pub fn buffer_incoming_commands(&mut self, current_tick: u16) {
+        while let Some((tick, command)) = self.connection.get_incoming_command() {
+            self.command_buffer.insert(tick, command, current_tick);
+        }
+    }
+
+    pub fn is_receiving_commands(&self) -> bool {
+        return self.command_buffer.is_active();
+    }
+
+    pub fn pop_command(&mut self, tick: u16, policy: MissingCommandPolicy) -> Option<T> {
+        return self.command_buffer.pop(tick, policy);
+    }
+
+    pub fn get_command_buffer_stats(&self) -> CommandBufferStats {
+        return self.command_buffer.get_stats();
+    }
+
+    pub fn get_command_stats(&self) -> CommandStats {
+        return self.connection.get_command_stats();
+    }
+
+    pub fn get_event_queue_stats(&self, manager_type: ManagerType) -> EventQueueStats {
+        return self.connection.get_event_queue_stats(manager_type);
+    }
+
+    pub fn get_event_type_queue_stats(&self, type_id: TypeId) -> EventQueueStats {
+        return self.connection.get_event_type_queue_stats(type_id);
+    }
+
+    pub fn record_drop(&mut self, reason: DropReason) {
+        self.connection.record_drop(reason);
+    }
+
+    pub fn get_connection_stats(&self) -> ConnectionStats {
+        return self.connection.get_connection_stats();
+    }
+
+    /// Returns whether the remote host has violated the protocol, when
+    /// `Config::strict_protocol` is enabled, after which its packets are
+    /// ignored until the connection is closed
+    pub fn has_protocol_violation(&self) -> bool {
+        return self.strict_protocol && self.connection.get_first_drop().is_some();
+    }
+
+    /// Gets the reason the remote host has violated the protocol, when
+    /// `Config::strict_protocol` is enabled, only the first time it is called
+    /// after the violation
+    pub fn take_protocol_violation(&mut self) -> Option<DropReason> {
+        if !self.strict_protocol || self.protocol_violation_reported {
+            return None;
+        }
+        let reason = self.connection.get_first_drop()?;
+        self.protocol_violation_reported = true;
+        return Some(reason);
+    }
+
+    pub fn get_client_tick_lead(&self) -> Option<f32> {
+        return self.time_dilation.get_average_lead();
     }
 
     pub fn process_outgoing_header(
@@ -146,14 +642,239 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
         return self.connection.get_next_packet_index();
     }
 
-    pub fn queue_event(&mut self, event: &impl Event<T>) {
+    pub fn queue_event(&mut self, event: &dyn Event<T>) -> EventId {
         return self.connection.queue_event(event);
     }
 
+    pub fn queue_event_with_ttl(&mut self, event: &dyn Event<T>, ttl: Duration) -> EventId {
+        return self.connection.queue_event_with_ttl(event, ttl);
+    }
+
+    pub fn queue_boxed_event(&mut self, event: Box<dyn Event<T>>) -> EventId {
+        return self.connection.queue_boxed_event(event);
+    }
+
     pub fn get_incoming_event(&mut self) -> Option<T> {
         return self.connection.get_incoming_event();
     }
 
+    pub fn get_delivered_event_id(&mut self) -> Option<EventId> {
+        return self.connection.get_delivered_event_id();
+    }
+
+    pub fn get_expired_event_id(&mut self) -> Option<EventId> {
+        return self.connection.get_expired_event_id();
+    }
+
+    pub fn queue_timed_event(&mut self, tick: u16, event: &impl Event<T>) {
+        return self.connection.queue_timed_event(tick, event);
+    }
+
+    /// Queues an Event targeted at the given Entity, returning false if the
+    /// Entity isn't in scope for the Client. Until the Client has confirmed
+    /// the Entity's creation, the Event is held back, so that it doesn't
+    /// arrive before the Entity does
+    pub fn queue_entity_event(&mut self, key: &EntityKey, event: &dyn Event<T>) -> bool {
+        match self.entity_manager.get_local_key(key) {
+            Some((local_key, true)) => self.connection.queue_entity_event(local_key, event),
+            Some((local_key, false)) => {
+                self.held_entity_events
+                    .push_back((*key, local_key, EventClone::clone_box(event)));
+            }
+            None => return false,
+        }
+        return true;
+    }
+
+    // Queues the held Entity Events whose Entity the Client has since
+    // confirmed the creation of, and drops those whose Entity has left scope
+    fn release_entity_events(&mut self) {
+        let entity_manager = &self.entity_manager;
+        let connection = &mut self.connection;
+        self.held_entity_events.retain(|(key, local_key, event)| {
+            match entity_manager.get_local_key(key) {
+                Some((current_local_key, created)) if current_local_key == *local_key => {
+                    if created {
+                        connection.queue_entity_event(*local_key, event.as_ref());
+                    }
+                    return !created;
+                }
+                _ => return false,
+            }
+        });
+    }
+
+    /// Gets the public id of the Client, which other Clients address the
+    /// Events they want relayed to it by
+    pub fn get_client_id(&self) -> ClientId {
+        return self.client_id;
+    }
+
+    /// Gets the revisions of the Client's roster, of the Server's Manifest &
+    /// of the Server's tick rate last sent to the Client
+    pub fn get_revisions(&self) -> (u32, u32, u32) {
+        return (
+            self.roster_revision,
+            self.manifest_revision,
+            self.tick_rate_revision,
+        );
+    }
+
+    /// Restores the revisions a previous Server process had sent the Client,
+    /// so that the changes sent next aren't discarded as stale
+    pub fn restore_revisions(&mut self, revisions: (u32, u32, u32)) {
+        let (roster_revision, manifest_revision, tick_rate_revision) = revisions;
+        self.roster_revision = roster_revision;
+        self.manifest_revision = manifest_revision;
+        self.tick_rate_revision = tick_rate_revision;
+    }
+
+    pub fn get_sequence_state(&self) -> (u16, u16, u32) {
+        return self.connection.get_sequence_state();
+    }
+
+    pub fn resume_sequences(
+        &mut self,
+        local_sequence: u16,
+        remote_sequence: u16,
+        ack_bitfield: u32,
+    ) {
+        return self
+            .connection
+            .resume_sequences(local_sequence, remote_sequence, ack_bitfield);
+    }
+
+    pub fn get_connected_at(&self) -> Instant {
+        return self.connected_at;
+    }
+
+    pub fn set_migrated(&mut self) {
+        self.migrated = true;
+    }
+
+    pub fn is_migrated(&self) -> bool {
+        return self.migrated;
+    }
+
+    pub fn set_auth_event(&mut self, auth_event: T) {
+        self.auth_event = Some(auth_event);
+    }
+
+    pub fn get_auth_event(&self) -> Option<&T> {
+        return self.auth_event.as_ref();
+    }
+
+    /// Attaches application data of the given type to the connection,
+    /// returning the data of the same type it replaced, if any
+    pub fn set_client_data<D: Any>(&mut self, data: D) -> Option<D> {
+        return self
+            .client_data
+            .insert(TypeId::of::<D>(), Box::new(data))
+            .and_then(|replaced| replaced.downcast::<D>().ok())
+            .map(|replaced| *replaced);
+    }
+
+    pub fn get_client_data<D: Any>(&self) -> Option<&D> {
+        return self
+            .client_data
+            .get(&TypeId::of::<D>())
+            .and_then(|data| data.downcast_ref::<D>());
+    }
+
+    pub fn get_client_data_mut<D: Any>(&mut self) -> Option<&mut D> {
+        return self
+            .client_data
+            .get_mut(&TypeId::of::<D>())
+            .and_then(|data| data.downcast_mut::<D>());
+    }
+
+    pub fn remove_client_data<D: Any>(&mut self) -> Option<D> {
+        return self
+            .client_data
+            .remove(&TypeId::of::<D>())
+            .and_then(|data| data.downcast::<D>().ok())
+            .map(|data| *data);
+    }
+
+    /// Queues a change to the set of connected Clients, at the next revision
+    /// of the Client's roster
+    pub fn queue_roster_change(&mut self, change: ClientRosterChange) {
+        self.roster_revision = self.roster_revision.wrapping_add(1);
+        return self
+            .connection
+            .queue_roster_change(self.roster_revision, change);
+    }
+
+    /// Queues a description of types registered in the Server's Manifest
+    /// since the Client connected, at the next revision of the Manifest
+    pub fn queue_manifest_delta(&mut self, delta: ManifestDescriptor) {
+        self.manifest_revision = self.manifest_revision.wrapping_add(1);
+        return self
+            .connection
+            .queue_manifest_delta(self.manifest_revision, delta);
+    }
+
+    /// Queues a change to the rate at which the Server ticks, which took
+    /// effect at the given tick, at the next revision of the tick rate
+    pub fn queue_tick_rate_change(&mut self, tick: u16, tick_interval: Duration) {
+        self.tick_rate_revision = self.tick_rate_revision.wrapping_add(1);
+        return self.connection.queue_tick_rate_change(TickRateChange {
+            revision: self.tick_rate_revision,
+            tick,
+            tick_interval,
+        });
+    }
+
+    /// Queues a notice that the Server is shutting down, with the reason
+    pub fn queue_shutdown(&mut self, reason: String) {
+        return self.connection.queue_shutdown(reason);
+    }
+
+    /// Returns whether every Event & notice queued for the Client has been
+    /// delivered, with nothing left to send
+    pub fn is_flushed(&self) -> bool {
+        return self.connection.get_undelivered_count() == 0;
+    }
+
+    /// Queues an Event relayed from the Client with the given id
+    pub fn queue_relay_event(&mut self, sender_id: ClientId, event: &dyn Event<T>) {
+        return self.connection.queue_relay_event(sender_id, event);
+    }
+
+    /// Gets the next Event the Client has asked to be relayed, along with
+    /// the id of the Client it is addressed to
+    pub fn get_incoming_relay_event(&mut self) -> Option<(ClientId, T)> {
+        return self.connection.get_incoming_relay_event();
+    }
+
+    pub fn queue_raw_payload(&mut self, payload: &[u8]) -> bool {
+        return self.connection.queue_raw_payload(payload);
+    }
+
+    pub fn get_incoming_raw_payload(&mut self) -> Option<Box<[u8]>> {
+        return self.connection.get_incoming_raw_payload();
+    }
+
+    pub fn queue_blob(&mut self, data: &[u8]) -> Option<BlobId> {
+        return self.connection.queue_blob(data);
+    }
+
+    pub fn get_outgoing_blob_progress(&self, blob_id: BlobId) -> Option<(u16, u16)> {
+        return self.connection.get_outgoing_blob_progress(blob_id);
+    }
+
+    pub fn get_blob_message(&mut self) -> Option<BlobMessage> {
+        return self.connection.get_blob_message();
+    }
+
+    pub fn queue_rpc(&mut self, rpc: RpcMessage<&dyn Event<T>>) {
+        return self.connection.queue_rpc(rpc);
+    }
+
+    pub fn get_incoming_rpc(&mut self) -> Option<RpcMessage<T>> {
+        return self.connection.get_incoming_rpc();
+    }
+
     pub fn get_address(&self) -> SocketAddr {
         return self.connection.get_address();
     }
@@ -161,4 +882,62 @@ impl<T: EventType, U: EntityType> ClientConnection<T, U> {
     pub fn get_rtt(&self) -> f32 {
         return self.connection.get_rtt();
     }
+
+    pub fn get_rtt_stats(&self) -> Option<RttStats> {
+        return self.connection.get_rtt_stats();
+    }
+
+    pub fn set_packet_framing(&mut self, packet_framing: Option<PacketFramingFactory>) {
+        self.packet_framing = packet_framing;
+    }
+
+    pub fn set_heartbeat_payload(&mut self, payload: &[u8]) -> bool {
+        return self.connection.set_heartbeat_payload(payload);
+    }
+
+    pub fn get_heartbeat_payload(&self) -> &[u8] {
+        return self.connection.get_heartbeat_payload();
+    }
+
+    pub fn get_packet_loss(&self) -> Option<f32> {
+        return self.connection.get_packet_loss();
+    }
+
+    pub fn get_ack_latency(&self) -> &AckLatencyHistogram {
+        return self.connection.get_ack_latency();
+    }
+
+    pub fn clear_ack_latency(&mut self) {
+        self.connection.clear_ack_latency();
+    }
+
+    pub fn update_quality(&mut self) -> Option<ConnectionQuality> {
+        return self.connection.update_quality();
+    }
+
+    pub fn get_quality(&self) -> Option<ConnectionQuality> {
+        return self.connection.get_quality();
+    }
+
+    pub fn update_backpressure(&mut self) -> Option<Congestion> {
+        return self
+            .connection
+            .update_backpressure(self.entity_manager.get_undelivered_message_count());
+    }
+
+    pub fn is_congested(&self) -> bool {
+        return self.connection.is_congested();
+    }
+
+    pub fn should_drop_unguaranteed(&self) -> bool {
+        return self.connection.should_drop_unguaranteed();
+    }
+
+    pub fn update_link_state(&mut self) -> Option<LinkState> {
+        return self.connection.update_link_state();
+    }
+
+    pub fn get_link_state(&self) -> LinkState {
+        return self.connection.get_link_state();
+    }
 }
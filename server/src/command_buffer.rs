@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use naia_shared::EventType;
+
+use super::missing_command_policy::MissingCommandPolicy;
+
+/// The most ticks ahead of the Server which a command is buffered for. Commands
+/// further ahead are discarded, so a misbehaving Client can't grow the buffer
+const MAX_COMMAND_LEAD: i16 = 128;
+
+/// Statistics about how the commands of a Client arrive, relative to the tick
+/// the Server applies them at
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CommandBufferStats {
+    /// The number of commands which arrived before their tick
+    pub on_time: u64,
+    /// The number of commands which arrived once their tick had already been
+    /// processed, and were discarded
+    pub late: u64,
+    /// How many ticks ahead of their tick the on-time commands arrived, on
+    /// average
+    pub average_lead: f32,
+    /// The fewest ticks ahead of its tick any command arrived, which is
+    /// negative if a command arrived late
+    pub min_lead: i16,
+    /// The number of ticks processed since the Client's first command arrived
+    pub ticks: u64,
+    /// The number of those ticks which had no command from the Client
+    pub missing: u64,
+}
+
+impl CommandBufferStats {
+    /// Gets the fraction of ticks which had no command from the Client, from 0
+    /// to 1
+    pub fn missing_fraction(&self) -> f32 {
+        if self.ticks == 0 {
+            return 0.0;
+        }
+        return self.missing as f32 / self.ticks as f32;
+    }
+}
+
+/// Holds the commands received from a Client until the Server reaches the tick
+/// each was sent for
+#[derive(Debug)]
+pub struct CommandBuffer<T: EventType> {
+    commands: HashMap<u16, T>,
+    last_command: Option<T>,
+    active: bool,
+    stats: CommandBufferStats,
+    lead_sum: f64,
+}
+
+impl<T: EventType> CommandBuffer<T> {
+    pub fn new() -> Self {
+        CommandBuffer {
+            commands: HashMap::new(),
+            last_command: None,
+            active: false,
+            stats: CommandBufferStats::default(),
+            lead_sum: 0.0,
+        }
+    }
+
+    /// Buffers the command of a tick, given the Server's tick when it arrived.
+    /// The command of the current tick has already been processed, so it is
+    /// late
+    pub fn insert(&mut self, tick: u16, command: T, current_tick: u16) {
+        let lead = tick.wrapping_sub(current_tick) as i16;
+        if !self.active || lead < self.stats.min_lead {
+            self.stats.min_lead = lead;
+        }
+        self.active = true;
+
+        if lead <= 0 {
+            self.stats.late += 1;
+            return;
+        }
+        if lead > MAX_COMMAND_LEAD {
+            return;
+        }
+        self.stats.on_time += 1;
+        self.lead_sum += lead as f64;
+        self.stats.average_lead = (self.lead_sum / self.stats.on_time as f64) as f32;
+        self.commands.insert(tick, command);
+    }
+
+    /// Returns whether any command has arrived yet. Ticks are only processed
+    /// once one has
+    pub fn is_active(&self) -> bool {
+        return self.active;
+    }
+
+    /// Takes the command of the given tick, which the Server is now processing.
+    /// If it hasn't arrived, the policy determines whether the last command is
+    /// repeated, or None is returned
+    pub fn pop(&mut self, tick: u16, policy: MissingCommandPolicy) -> Option<T> {
+        self.stats.ticks += 1;
+
+        // commands for ticks which have passed can no longer be applied
+        self.commands
+            .retain(|command_tick, _| command_tick.wrapping_sub(tick) as i16 >= 0);
+
+        match self.commands.remove(&tick) {
+            Some(command) => {
+                self.last_command = Some(command.clone());
+                return Some(command);
+            }
+            None => {
+                self.stats.missing += 1;
+                match policy {
+                    MissingCommandPolicy::RepeatLast => return self.last_command.clone(),
+                    MissingCommandPolicy::Neutral => return None,
+                }
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> CommandBufferStats {
+        return self.stats;
+    }
+}
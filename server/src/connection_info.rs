@@ -0,0 +1,54 @@
+use std::{net::SocketAddr, time::Instant};
+
+use naia_shared::{Capabilities, ClientId};
+
+/// The transport Clients connect to the Server over, chosen with the
+/// `use-udp` or `use-webrtc` feature
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transport {
+    /// Plain UDP sockets, for native Clients
+    Udp,
+    /// WebRTC data channels, for Clients in the browser
+    WebRtc,
+}
+
+impl Transport {
+    /// Gets the transport the Server was built with
+    pub fn current() -> Self {
+        #[cfg(feature = "use-udp")]
+        return Transport::Udp;
+        #[cfg(feature = "use-webrtc")]
+        return Transport::WebRtc;
+    }
+}
+
+/// Metadata about the connection of a Client, returned by
+/// `NaiaServer::get_connection_info()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConnectionInfo {
+    /// The address of the Client
+    pub address: SocketAddr,
+    /// The index of the endpoint the Client connected through, 0 being the
+    /// address the Server was created with
+    pub endpoint: usize,
+    /// The address of the endpoint the Client connected through
+    pub endpoint_address: SocketAddr,
+    /// The transport the Client connected over
+    pub transport: Transport,
+    /// When the connection was established
+    pub connected_at: Instant,
+    /// The public id assigned to the Client
+    pub client_id: ClientId,
+    /// Whether packets to & from the Client are written with compact headers,
+    /// as negotiated during the handshake
+    pub compact_headers: bool,
+    /// The optional features agreed on with the Client during the handshake
+    pub capabilities: Capabilities,
+    /// Whether the Client presented a migration token, carrying its state
+    /// over from a previous host
+    pub migrated: bool,
+    /// Whether the Client was authorized by the closure given to
+    /// `NaiaServer::on_auth()`, in which case its auth Event can be retrieved
+    /// with `NaiaServer::get_auth_event()`
+    pub authenticated: bool,
+}
@@ -0,0 +1,15 @@
+/// Determines what the Server does when a Client authenticates with an
+/// identity, as given by the closure registered with
+/// `NaiaServer::on_identity()`, which another connected Client already has
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DuplicateLoginPolicy {
+    /// The new Client is refused, with a `ConnectFailure::Refused`, & the
+    /// Client already connected is kept
+    RejectNew,
+    /// Every Client already connected with the identity is kicked, as with
+    /// `NaiaServer::kick_user()`, & the new Client is accepted
+    KickOld,
+    /// The new Client is accepted, & the Clients already connected with the
+    /// identity are kept
+    AllowBoth,
+}
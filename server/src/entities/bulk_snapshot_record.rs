@@ -0,0 +1,30 @@
+use std::collections::HashSet;
+
+use super::entity_key::entity_key::EntityKey;
+
+/// Tracks the delivery of a Room snapshot sent in bulk to a Client. The
+/// Entities in it are only considered created once every chunk has been
+/// delivered
+#[derive(Debug)]
+pub struct BulkSnapshotRecord {
+    pub entity_keys: Vec<EntityKey>,
+    total_chunks: u16,
+    delivered_chunks: HashSet<u16>,
+}
+
+impl BulkSnapshotRecord {
+    pub fn new(entity_keys: Vec<EntityKey>, total_chunks: u16) -> BulkSnapshotRecord {
+        BulkSnapshotRecord {
+            entity_keys,
+            total_chunks,
+            delivered_chunks: HashSet::new(),
+        }
+    }
+
+    /// Records the delivery of a chunk, returning whether every chunk has now
+    /// been delivered
+    pub fn notify_chunk_delivered(&mut self, chunk_index: u16) -> bool {
+        self.delivered_chunks.insert(chunk_index);
+        return self.delivered_chunks.len() == self.total_chunks as usize;
+    }
+}
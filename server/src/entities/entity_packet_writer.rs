@@ -1,6 +1,8 @@
 use byteorder::{BigEndian, WriteBytesExt};
 
-use naia_shared::{EntityType, EventType, Manifest, PacketWriter, MTU_SIZE};
+use naia_shared::{
+    EntityType, EventType, ManagerType, Manifest, PacketWriter, BATCHED_ENTITY_MESSAGE_FLAG,
+};
 
 use super::server_entity_message::ServerEntityMessage;
 
@@ -10,19 +12,52 @@ pub struct EntityPacketWriter {}
 
 impl EntityPacketWriter {
     /// Given a general PacketWriter, the manifest, and a buffered
-    /// EntityMessage, actually write Entity data into the packet
+    /// EntityMessage along with the index of the tick batch it belongs to & its
+    /// slot within that batch, actually write Entity data into the packet
     pub fn write_entity_message<T: EventType, U: EntityType>(
         packet_writer: &mut PacketWriter,
         manifest: &Manifest<T, U>,
+        batch_index: Option<(u16, u16)>,
         message: &ServerEntityMessage<U>,
     ) -> bool {
         let mut entity_total_bytes = Vec::<u8>::new();
 
+        //Write entity message type, with the high bit flagging that the index of
+        // the message's tick batch & its slot within the batch follow
+        match batch_index {
+            Some((batch_index, batch_slot)) => {
+                entity_total_bytes
+                    .write_u8(message.write_message_type() | BATCHED_ENTITY_MESSAGE_FLAG)
+                    .unwrap(); // write entity message type
+                entity_total_bytes
+                    .write_u16::<BigEndian>(batch_index)
+                    .unwrap(); // write batch index
+                entity_total_bytes
+                    .write_u16::<BigEndian>(batch_slot)
+                    .unwrap(); // write batch slot
+            }
+            None => {
+                entity_total_bytes
+                    .write_u8(message.write_message_type())
+                    .unwrap(); // write entity message type
+            }
+        }
+
         match message {
-            ServerEntityMessage::Create(_, local_key, entity) => {
-                //write entity payload
+            ServerEntityMessage::Create(_, local_key, entity, prediction_key, prefab_id) => {
+                //write entity payload, which for an Entity created from a prefab only
+                // contains the Properties which differ from the prefab
+                let prefab_diff = prefab_id.and_then(|prefab_id| {
+                    manifest.get_prefab_diff(prefab_id, &*entity.as_ref().borrow())
+                });
                 let mut entity_payload_bytes = Vec::<u8>::new();
-                entity.as_ref().borrow().write(&mut entity_payload_bytes);
+                match &prefab_diff {
+                    Some(diff_mask) => entity
+                        .as_ref()
+                        .borrow()
+                        .write_partial(diff_mask, &mut entity_payload_bytes),
+                    None => entity.as_ref().borrow().write(&mut entity_payload_bytes),
+                }
                 if entity_payload_bytes.len() > 255 {
                     error!(
                         "cannot encode an entity with more than 255 bytes, need to implement this"
@@ -30,28 +65,44 @@ impl EntityPacketWriter {
                 }
 
                 //Write entity "header" (entity id & payload length)
-                entity_total_bytes
-                    .write_u8(message.write_message_type())
-                    .unwrap(); // write entity message type
-
-                let type_id = entity.as_ref().borrow().get_type_id();
-                let naia_id = manifest.get_entity_naia_id(&type_id); // get naia id
-                entity_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
-                entity_total_bytes
-                    .write_u16::<BigEndian>(*local_key)
-                    .unwrap(); //write local key
+                match prefab_id {
+                    Some(prefab_id) => {
+                        entity_total_bytes
+                            .write_u16::<BigEndian>(*prefab_id)
+                            .unwrap(); // write prefab id
+                    }
+                    None => {
+                        let type_id = entity.as_ref().borrow().get_type_id();
+                        let naia_id = manifest.get_entity_naia_id(&type_id); // get naia id
+                        entity_total_bytes.write_u16::<BigEndian>(naia_id).unwrap();
+                        // write naia id
+                    }
+                }
+                local_key.write(&mut entity_total_bytes); //write local key
+                if let Some(prediction_key) = prediction_key {
+                    entity_total_bytes
+                        .write_u16::<BigEndian>(*prediction_key)
+                        .unwrap(); //write prediction key
+                }
+                if let Some(mut diff_mask) = prefab_diff {
+                    diff_mask.write(&mut entity_total_bytes); // write state mask
+                }
                 entity_total_bytes
                     .write_u8(entity_payload_bytes.len() as u8)
                     .unwrap(); // write payload length
                 entity_total_bytes.append(&mut entity_payload_bytes); // write payload
             }
-            ServerEntityMessage::Delete(_, local_key) => {
-                entity_total_bytes
-                    .write_u8(message.write_message_type())
-                    .unwrap(); //Write entity message type
-                entity_total_bytes
-                    .write_u16::<BigEndian>(*local_key)
-                    .unwrap(); //write local key
+            ServerEntityMessage::Delete(_, local_key, reason) => {
+                local_key.write(&mut entity_total_bytes); //write local key
+                match reason {
+                    Some(reason) => {
+                        entity_total_bytes.write_u8(1).unwrap(); //write reason flag
+                        entity_total_bytes.write_u8(*reason).unwrap(); //write reason
+                    }
+                    None => {
+                        entity_total_bytes.write_u8(0).unwrap(); //write reason flag
+                    }
+                }
             }
             ServerEntityMessage::Update(_, local_key, state_mask, entity) => {
                 //write entity payload
@@ -67,13 +118,7 @@ impl EntityPacketWriter {
                 }
 
                 //Write entity "header" (entity id & payload length)
-                entity_total_bytes
-                    .write_u8(message.write_message_type())
-                    .unwrap(); // write entity message type
-
-                entity_total_bytes
-                    .write_u16::<BigEndian>(*local_key)
-                    .unwrap(); //write local key
+                local_key.write(&mut entity_total_bytes); //write local key
                 state_mask
                     .as_ref()
                     .borrow_mut()
@@ -83,21 +128,29 @@ impl EntityPacketWriter {
                     .unwrap(); // write payload length
                 entity_total_bytes.append(&mut entity_payload_bytes); // write payload
             }
+            ServerEntityMessage::SnapshotBegin(total) => {
+                entity_total_bytes.write_u32::<BigEndian>(*total).unwrap(); //write entity total
+            }
+            ServerEntityMessage::SnapshotComplete => {}
+            ServerEntityMessage::SnapshotChunk(chunk_index, total_chunks, data) => {
+                entity_total_bytes
+                    .write_u16::<BigEndian>(*chunk_index)
+                    .unwrap(); //write chunk index
+                entity_total_bytes
+                    .write_u16::<BigEndian>(*total_chunks)
+                    .unwrap(); //write total chunks
+                entity_total_bytes.write_u8(data.len() as u8).unwrap(); //write payload length
+                entity_total_bytes.extend_from_slice(data); //write payload
+            }
+            ServerEntityMessage::TickComplete(batch_index, tick, count) => {
+                entity_total_bytes
+                    .write_u16::<BigEndian>(*batch_index)
+                    .unwrap(); //write batch index
+                entity_total_bytes.write_u16::<BigEndian>(*tick).unwrap(); //write tick
+                entity_total_bytes.write_u16::<BigEndian>(*count).unwrap(); //write message count
+            }
         }
 
-        let mut hypothetical_next_payload_size =
-            packet_writer.bytes_number() + entity_total_bytes.len();
-        if packet_writer.entity_message_count == 0 {
-            hypothetical_next_payload_size += 2;
-        }
-        if hypothetical_next_payload_size < MTU_SIZE {
-            packet_writer.entity_message_count += 1;
-            packet_writer
-                .entity_working_bytes
-                .append(&mut entity_total_bytes);
-            return true;
-        } else {
-            return false;
-        }
+        return packet_writer.write_item(ManagerType::Entity, entity_total_bytes);
     }
 }
@@ -6,7 +6,12 @@ use naia_shared::{LocalEntityKey, StateMask};
 pub struct EntityRecord {
     pub local_key: LocalEntityKey,
     state_mask: Rc<RefCell<StateMask>>,
+    unguaranteed_state_mask: StateMask,
     pub status: LocalEntityStatus,
+    pub priority_accumulator: f32,
+    pub keyframe_interval: Option<u16>,
+    pub ticks_since_keyframe: u16,
+    pub keyframe_due: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -17,15 +22,28 @@ pub enum LocalEntityStatus {
 }
 
 impl EntityRecord {
-    pub fn new(local_key: LocalEntityKey, state_mask_size: u8) -> EntityRecord {
+    pub fn new(
+        local_key: LocalEntityKey,
+        state_mask_size: u8,
+        unguaranteed_state_mask: StateMask,
+    ) -> EntityRecord {
         EntityRecord {
             local_key,
             state_mask: Rc::new(RefCell::new(StateMask::new(state_mask_size))),
+            unguaranteed_state_mask,
             status: LocalEntityStatus::Creating,
+            priority_accumulator: 0.0,
+            keyframe_interval: None,
+            ticks_since_keyframe: 0,
+            keyframe_due: false,
         }
     }
 
     pub fn get_state_mask(&self) -> &Rc<RefCell<StateMask>> {
         return &self.state_mask;
     }
+
+    pub fn get_unguaranteed_state_mask(&self) -> &StateMask {
+        return &self.unguaranteed_state_mask;
+    }
 }
@@ -1,3 +1,4 @@
+pub(crate) mod bulk_snapshot_record;
 pub(crate) mod entity_key;
 pub(crate) mod entity_packet_writer;
 pub(crate) mod entity_record;
@@ -2,20 +2,36 @@ use std::{
     borrow::Borrow,
     cell::RefCell,
     clone::Clone,
-    collections::{HashMap, VecDeque},
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
     net::SocketAddr,
     rc::Rc,
+    time::{Duration, Instant},
 };
 
+use byteorder::{BigEndian, WriteBytesExt};
 use slotmap::SparseSecondaryMap;
 
 use super::{
+    bulk_snapshot_record::BulkSnapshotRecord,
     entity_key::entity_key::EntityKey,
     entity_record::{EntityRecord, LocalEntityStatus},
     mut_handler::MutHandler,
     server_entity_message::ServerEntityMessage,
 };
-use naia_shared::{Entity, EntityNotifiable, EntityType, LocalEntityKey, StateMask};
+use crate::{room_snapshot::RoomSnapshot, tick_stats::TickTimings};
+use naia_shared::{
+    DespawnReason, Entity, EntityDebugDump, EntityDebugRecord, EntityDebugStatus, EntityNotifiable,
+    EntityType, EventType, LocalEntityKey, Manifest, PredictionKey, PrefabId, StateMask, Timer,
+};
+
+/// The maximum number of bytes of a Room snapshot which are sent in a single
+/// message
+const SNAPSHOT_CHUNK_SIZE: usize = 255;
+
+/// The least a mutated Entity's accumulated priority grows each tick, so that
+/// an Entity with a priority of 0 is still updated eventually
+const MIN_PRIORITY_INCREMENT: f32 = 0.01;
 
 /// Manages Entities for a given Client connection and keeps them in sync on the
 /// Client
@@ -24,29 +40,46 @@ pub struct ServerEntityManager<T: EntityType> {
     address: SocketAddr,
     local_entity_store: SparseSecondaryMap<EntityKey, Rc<RefCell<dyn Entity<T>>>>,
     local_to_global_key_map: HashMap<LocalEntityKey, EntityKey>,
-    recycled_local_keys: Vec<LocalEntityKey>,
-    next_new_local_key: LocalEntityKey,
+    recycled_local_keys: VecDeque<(LocalEntityKey, Timer)>,
+    tombstone_duration: Duration,
+    next_new_local_key: u16,
     entity_records: SparseSecondaryMap<EntityKey, EntityRecord>,
-    queued_messages: VecDeque<ServerEntityMessage<T>>,
-    sent_messages: HashMap<u16, Vec<ServerEntityMessage<T>>>,
+    pending_prediction_keys: HashMap<EntityKey, PredictionKey>,
+    queued_messages: VecDeque<(Option<(u16, u16)>, ServerEntityMessage<T>)>,
+    sent_messages: HashMap<u16, Vec<(Option<(u16, u16)>, ServerEntityMessage<T>)>>,
     sent_updates: HashMap<u16, HashMap<EntityKey, Rc<RefCell<StateMask>>>>,
     last_update_packet_index: u16,
     last_last_update_packet_index: u16,
     mut_handler: Rc<RefCell<MutHandler>>,
     last_popped_state_mask: StateMask,
+    snapshot_pending: bool,
+    snapshot_keys: Option<HashSet<EntityKey>>,
+    bulk_snapshot: Option<BulkSnapshotRecord>,
+    restored_local_keys: HashMap<EntityKey, LocalEntityKey>,
+    tick_batching: bool,
+    next_batch_index: u16,
 }
 
 impl<T: EntityType> ServerEntityManager<T> {
-    /// Create a new ServerEntityManager, given the client's address and a
-    /// reference to a MutHandler associated with the Client
-    pub fn new(address: SocketAddr, mut_handler: &Rc<RefCell<MutHandler>>) -> Self {
+    /// Create a new ServerEntityManager, given the client's address, a
+    /// reference to a MutHandler associated with the Client, the duration
+    /// for which the keys of deleted Entities are retired, and whether Entity
+    /// messages should be grouped into per-tick batches
+    pub fn new(
+        address: SocketAddr,
+        mut_handler: &Rc<RefCell<MutHandler>>,
+        tombstone_duration: Duration,
+        tick_batching: bool,
+    ) -> Self {
         ServerEntityManager {
             address,
             local_entity_store: SparseSecondaryMap::new(),
             local_to_global_key_map: HashMap::new(),
-            recycled_local_keys: Vec::new(),
+            recycled_local_keys: VecDeque::new(),
+            tombstone_duration,
             next_new_local_key: 0,
             entity_records: SparseSecondaryMap::new(),
+            pending_prediction_keys: HashMap::new(),
             queued_messages: VecDeque::new(),
             sent_messages: HashMap::new(),
             sent_updates: HashMap::<u16, HashMap<EntityKey, Rc<RefCell<StateMask>>>>::new(),
@@ -54,6 +87,12 @@ impl<T: EntityType> ServerEntityManager<T> {
             last_last_update_packet_index: 0,
             mut_handler: mut_handler.clone(),
             last_popped_state_mask: StateMask::new(0),
+            snapshot_pending: true,
+            snapshot_keys: None,
+            bulk_snapshot: None,
+            restored_local_keys: HashMap::new(),
+            tick_batching,
+            next_batch_index: 0,
         }
     }
 
@@ -61,29 +100,80 @@ impl<T: EntityType> ServerEntityManager<T> {
         return self.queued_messages.len() != 0;
     }
 
-    pub fn pop_outgoing_message(&mut self, packet_index: u16) -> Option<ServerEntityMessage<T>> {
+    /// Gets the number of Entity messages which are queued, or have been sent
+    /// without yet being acknowledged
+    pub fn get_undelivered_message_count(&self) -> usize {
+        let sent_count: usize = self.sent_messages.values().map(|list| list.len()).sum();
+        return self.queued_messages.len() + sent_count;
+    }
+
+    /// Produces a report of every Entity in scope for the Client, with the
+    /// Properties mutated since last sent & those sent without yet being
+    /// acknowledged, along with the number of undelivered Entity messages
+    pub fn debug_dump<E: EventType>(&self, manifest: &Manifest<E, T>) -> EntityDebugDump {
+        let mut records = Vec::new();
+        for (global_key, record) in self.entity_records.iter() {
+            let naia_id = match self.local_entity_store.get(global_key) {
+                Some(entity) => {
+                    manifest.get_entity_naia_id(&entity.as_ref().borrow().get_type_id())
+                }
+                None => continue,
+            };
+            let pending_mask = record.get_state_mask().as_ref().borrow().clone();
+            let mut last_sent_mask = StateMask::new(pending_mask.byte_number());
+            for sent_updates_map in self.sent_updates.values() {
+                if let Some(sent_mask) = sent_updates_map.get(&global_key) {
+                    last_sent_mask.or(&sent_mask.as_ref().borrow());
+                }
+            }
+            let status = match record.status {
+                LocalEntityStatus::Creating => EntityDebugStatus::Creating,
+                LocalEntityStatus::Created => EntityDebugStatus::Created,
+                LocalEntityStatus::Deleting => EntityDebugStatus::Deleting,
+            };
+            records.push(EntityDebugRecord {
+                local_key: record.local_key,
+                naia_id,
+                type_name: manifest.get_entity_type_name(naia_id),
+                status,
+                pending_mask: Some(pending_mask),
+                last_sent_mask: Some(last_sent_mask),
+            });
+        }
+        return EntityDebugDump::new(records, self.get_undelivered_message_count());
+    }
+
+    pub fn pop_outgoing_message(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(Option<(u16, u16)>, ServerEntityMessage<T>)> {
         match self.queued_messages.pop_front() {
-            Some(message) => {
+            Some((batch_index, message)) => {
                 if !self.sent_messages.contains_key(&packet_index) {
-                    let sent_messages_list: Vec<ServerEntityMessage<T>> = Vec::new();
+                    let sent_messages_list: Vec<(Option<(u16, u16)>, ServerEntityMessage<T>)> =
+                        Vec::new();
                     self.sent_messages.insert(packet_index, sent_messages_list);
                 }
 
                 if let Some(sent_messages_list) = self.sent_messages.get_mut(&packet_index) {
-                    sent_messages_list.push(message.clone());
+                    sent_messages_list.push((batch_index, message.clone()));
                 }
 
                 //clear state mask of entity if need be
                 match &message {
-                    ServerEntityMessage::Create(global_key, _, _) => {
-                        if let Some(record) = self.entity_records.get(*global_key) {
-                            self.last_popped_state_mask =
-                                record.get_state_mask().as_ref().borrow().clone();
+                    ServerEntityMessage::Create(global_key, local_key, _, _, _) => {
+                        // the resend of a Create whose Entity has since come back into
+                        // scope with a new local key leaves the new record's state alone
+                        if self.is_current_record(global_key, local_key) {
+                            if let Some(record) = self.entity_records.get(*global_key) {
+                                self.last_popped_state_mask =
+                                    record.get_state_mask().as_ref().borrow().clone();
+                            }
+                            self.mut_handler
+                                .as_ref()
+                                .borrow_mut()
+                                .clear_state(&self.address, global_key);
                         }
-                        self.mut_handler
-                            .as_ref()
-                            .borrow_mut()
-                            .clear_state(&self.address, global_key);
                     }
                     ServerEntityMessage::Update(global_key, local_key, state_mask, entity) => {
                         // previously the state mask was the CURRENT state mask for the entity,
@@ -104,25 +194,32 @@ impl<T: EntityType> ServerEntityManager<T> {
                             sent_updates_map.insert(*global_key, locked_state_mask.clone());
                         }
 
-                        // having copied the state mask for this update, clear the state
-                        self.last_popped_state_mask = state_mask.as_ref().borrow().clone();
-                        self.mut_handler
-                            .as_ref()
-                            .borrow_mut()
-                            .clear_state(&self.address, global_key);
+                        // having copied the state mask for this update, clear the state,
+                        // unless this is the resend of a dropped update, which does not
+                        // carry the Entity's current state mask
+                        if self.is_current_state_mask(global_key, state_mask) {
+                            self.last_popped_state_mask = state_mask.as_ref().borrow().clone();
+                            self.mut_handler
+                                .as_ref()
+                                .borrow_mut()
+                                .clear_state(&self.address, global_key);
+                        }
 
                         // return new Update message to be written
-                        return Some(ServerEntityMessage::Update(
-                            *global_key,
-                            *local_key,
-                            locked_state_mask,
-                            entity.clone(),
+                        return Some((
+                            batch_index,
+                            ServerEntityMessage::Update(
+                                *global_key,
+                                *local_key,
+                                locked_state_mask,
+                                entity.clone(),
+                            ),
                         ));
                     }
                     _ => {}
                 }
 
-                return Some(message);
+                return Some((batch_index, message));
             }
             None => {
                 return None;
@@ -130,7 +227,12 @@ impl<T: EntityType> ServerEntityManager<T> {
         }
     }
 
-    pub fn unpop_outgoing_message(&mut self, packet_index: u16, message: &ServerEntityMessage<T>) {
+    pub fn unpop_outgoing_message(
+        &mut self,
+        packet_index: u16,
+        batch_index: Option<(u16, u16)>,
+        message: &ServerEntityMessage<T>,
+    ) {
         info!("unpopping");
         if let Some(sent_messages_list) = self.sent_messages.get_mut(&packet_index) {
             sent_messages_list.pop();
@@ -140,14 +242,16 @@ impl<T: EntityType> ServerEntityManager<T> {
         }
 
         match &message {
-            ServerEntityMessage::Create(global_key, _, _) => {
-                self.mut_handler.as_ref().borrow_mut().set_state(
-                    &self.address,
-                    global_key,
-                    &self.last_popped_state_mask,
-                );
+            ServerEntityMessage::Create(global_key, local_key, _, _, _) => {
+                if self.is_current_record(global_key, local_key) {
+                    self.mut_handler.as_ref().borrow_mut().set_state(
+                        &self.address,
+                        global_key,
+                        &self.last_popped_state_mask,
+                    );
+                }
             }
-            ServerEntityMessage::Update(global_key, local_key, _, entity) => {
+            ServerEntityMessage::Update(global_key, local_key, state_mask, entity) => {
                 if let Some(sent_updates_map) = self.sent_updates.get_mut(&packet_index) {
                     sent_updates_map.remove(global_key);
                     if sent_updates_map.len() == 0 {
@@ -156,6 +260,14 @@ impl<T: EntityType> ServerEntityManager<T> {
                 }
 
                 self.last_update_packet_index = self.last_last_update_packet_index;
+
+                // the resend of a dropped update did not clear the Entity's state
+                if !self.is_current_state_mask(global_key, state_mask) {
+                    self.queued_messages
+                        .push_front((batch_index, message.clone()));
+                    return;
+                }
+
                 self.mut_handler.as_ref().borrow_mut().set_state(
                     &self.address,
                     global_key,
@@ -173,121 +285,600 @@ impl<T: EntityType> ServerEntityManager<T> {
                     original_state_mask,
                     entity.clone(),
                 );
-                self.queued_messages.push_front(cloned_message);
+                self.queued_messages
+                    .push_front((batch_index, cloned_message));
                 return;
             }
             _ => {}
         }
 
-        self.queued_messages.push_front(message.clone());
+        self.queued_messages
+            .push_front((batch_index, message.clone()));
+    }
+
+    fn is_current_record(&self, key: &EntityKey, local_key: &LocalEntityKey) -> bool {
+        return match self.entity_records.get(*key) {
+            Some(record) => record.local_key == *local_key,
+            None => false,
+        };
+    }
+
+    fn is_current_state_mask(&self, key: &EntityKey, state_mask: &Rc<RefCell<StateMask>>) -> bool {
+        if let Some(record) = self.entity_records.get(*key) {
+            return Rc::ptr_eq(record.get_state_mask(), state_mask);
+        }
+        return false;
     }
 
     pub fn has_entity(&self, key: &EntityKey) -> bool {
         return self.local_entity_store.contains_key(*key);
     }
 
-    pub fn add_entity(&mut self, key: &EntityKey, entity: &Rc<RefCell<dyn Entity<T>>>) {
+    /// Brings an Entity into scope for the Client, returning whether it was
+    /// not already in scope
+    pub fn add_entity(
+        &mut self,
+        key: &EntityKey,
+        entity: &Rc<RefCell<dyn Entity<T>>>,
+        prefab_id: Option<PrefabId>,
+    ) -> bool {
         if !self.local_entity_store.contains_key(*key) {
             self.local_entity_store.insert(*key, entity.clone());
-            let local_key = self.get_new_local_key();
+            let restored_local_key = self.restored_local_keys.remove(key);
+            let local_key = match restored_local_key {
+                Some(local_key) => local_key,
+                None => self.get_new_local_key(),
+            };
             self.local_to_global_key_map.insert(local_key, *key);
             let state_mask_size = entity.as_ref().borrow().get_state_mask_size();
-            let entity_record = EntityRecord::new(local_key, state_mask_size);
+            let unguaranteed_state_mask = entity.as_ref().borrow().get_unguaranteed_state_mask();
+            let mut entity_record =
+                EntityRecord::new(local_key, state_mask_size, unguaranteed_state_mask);
             self.mut_handler.as_ref().borrow_mut().register_mask(
                 &self.address,
                 &key,
                 entity_record.get_state_mask(),
             );
+            // the Client already has an Entity received from a previous host, which may
+            // have missed changes made since, so one complete update of it is sent
+            if restored_local_key.is_some() {
+                entity_record.status = LocalEntityStatus::Created;
+                {
+                    let mut state_mask = entity_record.get_state_mask().as_ref().borrow_mut();
+                    for property_index in 0..entity.as_ref().borrow().get_property_count() {
+                        state_mask.set_bit(property_index, true);
+                    }
+                }
+                self.entity_records.insert(*key, entity_record);
+                return true;
+            }
             self.entity_records.insert(*key, entity_record);
-            self.queued_messages.push_back(ServerEntityMessage::Create(
-                *key,
-                local_key,
-                entity.clone(),
+            let prediction_key = self.pending_prediction_keys.remove(key);
+            self.queued_messages.push_back((
+                None,
+                ServerEntityMessage::Create(
+                    *key,
+                    local_key,
+                    entity.clone(),
+                    prediction_key,
+                    prefab_id,
+                ),
+            ));
+            return true;
+        }
+        return false;
+    }
+
+    /// Brings Entities into scope for the Client from a cached Room snapshot,
+    /// which is sent in bulk rather than as a creation per Entity. Each Entity
+    /// is given along with its position in the snapshot, and the Properties
+    /// which have changed since the snapshot was taken are sent as Updates
+    /// once the snapshot has been delivered. Only the initial world snapshot
+    /// can be sent this way, so returns the keys of the Entities which were
+    /// added, if any
+    pub fn add_entities_from_snapshot(
+        &mut self,
+        snapshot: &RoomSnapshot,
+        entities: Vec<(usize, EntityKey, Rc<RefCell<dyn Entity<T>>>)>,
+    ) -> Vec<EntityKey> {
+        let mut added_keys = Vec::new();
+        if !self.snapshot_pending || self.bulk_snapshot.is_some() {
+            return added_keys;
+        }
+
+        let mut local_keys: Vec<Option<LocalEntityKey>> =
+            vec![None; snapshot.get_entity_keys().len()];
+        for (index, key, entity) in entities {
+            if self.local_entity_store.contains_key(key)
+                || self.restored_local_keys.contains_key(&key)
+                || index >= local_keys.len()
+            {
+                continue;
+            }
+            self.local_entity_store.insert(key, entity.clone());
+            let local_key = self.get_new_local_key();
+            self.local_to_global_key_map.insert(local_key, key);
+            let state_mask_size = entity.as_ref().borrow().get_state_mask_size();
+            let unguaranteed_state_mask = entity.as_ref().borrow().get_unguaranteed_state_mask();
+            let entity_record =
+                EntityRecord::new(local_key, state_mask_size, unguaranteed_state_mask);
+            entity_record
+                .get_state_mask()
+                .as_ref()
+                .borrow_mut()
+                .copy_contents(&snapshot.get_diff(index, &*entity.as_ref().borrow()));
+            self.mut_handler.as_ref().borrow_mut().register_mask(
+                &self.address,
+                &key,
+                entity_record.get_state_mask(),
+            );
+            self.entity_records.insert(key, entity_record);
+            local_keys[index] = Some(local_key);
+            added_keys.push(key);
+        }
+
+        if added_keys.is_empty() {
+            return added_keys;
+        }
+
+        // write the local key of every Entity in the snapshot which is in scope,
+        // followed by the snapshot itself
+        let mut payload_bytes = Vec::<u8>::new();
+        payload_bytes
+            .write_u16::<BigEndian>(local_keys.len() as u16)
+            .unwrap(); // write entity count
+        for local_key in local_keys.iter() {
+            match local_key {
+                Some(local_key) => {
+                    payload_bytes.write_u8(1).unwrap(); // write in scope flag
+                    local_key.write(&mut payload_bytes); // write local key
+                }
+                None => {
+                    payload_bytes.write_u8(0).unwrap(); // write in scope flag
+                }
+            }
+        }
+        payload_bytes.extend_from_slice(snapshot.get_compressed_bytes());
+
+        let total_chunks = (payload_bytes.len() + SNAPSHOT_CHUNK_SIZE - 1) / SNAPSHOT_CHUNK_SIZE;
+        for (chunk_index, chunk_bytes) in payload_bytes.chunks(SNAPSHOT_CHUNK_SIZE).enumerate() {
+            self.queued_messages.push_back((
+                None,
+                ServerEntityMessage::SnapshotChunk(
+                    chunk_index as u16,
+                    total_chunks as u16,
+                    Rc::new(chunk_bytes.to_vec().into_boxed_slice()),
+                ),
             ));
         }
+        self.bulk_snapshot = Some(BulkSnapshotRecord::new(
+            added_keys.clone(),
+            total_chunks as u16,
+        ));
+
+        return added_keys;
     }
 
-    pub fn remove_entity(&mut self, key: &EntityKey) {
+    /// Takes an Entity out of scope for the Client, returning whether it was
+    /// in scope
+    pub fn remove_entity(&mut self, key: &EntityKey, reason: Option<DespawnReason>) -> bool {
         if let Some(entity_record) = self.entity_records.get_mut(*key) {
             if entity_record.status != LocalEntityStatus::Deleting {
                 entity_record.status = LocalEntityStatus::Deleting;
-                self.queued_messages
-                    .push_back(ServerEntityMessage::Delete(*key, entity_record.local_key));
+                self.queued_messages.push_back((
+                    None,
+                    ServerEntityMessage::Delete(*key, entity_record.local_key, reason),
+                ));
+                return true;
             }
         }
+        return false;
     }
 
-    fn get_new_local_key(&mut self) -> u16 {
-        if let Some(local_key) = self.recycled_local_keys.pop() {
-            return local_key;
+    /// Gets the index of the next tick batch to be sent to the Client
+    pub fn get_next_batch_index(&self) -> u16 {
+        return self.next_batch_index;
+    }
+
+    /// Restores the index of the next tick batch a previous host would have
+    /// sent the Client, which the Client is waiting on before it applies any
+    /// later batch
+    pub fn restore_next_batch_index(&mut self, next_batch_index: u16) {
+        self.next_batch_index = next_batch_index;
+    }
+
+    /// Gets the local key of every Entity in scope for the Client, along with
+    /// whether the Client has confirmed the Entity's creation
+    pub fn get_local_keys(&self) -> Vec<(EntityKey, LocalEntityKey, bool)> {
+        return self
+            .entity_records
+            .iter()
+            .filter(|(_, record)| record.status != LocalEntityStatus::Deleting)
+            .map(|(key, record)| {
+                (
+                    key,
+                    record.local_key,
+                    record.status == LocalEntityStatus::Created,
+                )
+            })
+            .collect();
+    }
+
+    /// Gets the local key of the given Entity, if it is in scope for the
+    /// Client, along with whether the Client has confirmed its creation
+    pub fn get_local_key(&self, key: &EntityKey) -> Option<(LocalEntityKey, bool)> {
+        let record = self.entity_records.get(*key)?;
+        if record.status == LocalEntityStatus::Deleting {
+            return None;
+        }
+        return Some((
+            record.local_key,
+            record.status == LocalEntityStatus::Created,
+        ));
+    }
+
+    /// Restores the local keys of Entities which the Client received from a
+    /// previous host. Entities the Client has confirmed keep their local key
+    /// once they come into scope, while the rest are deleted on the Client,
+    /// and will be created anew
+    pub fn restore_local_keys(&mut self, local_keys: Vec<(EntityKey, LocalEntityKey, bool)>) {
+        for (key, local_key, confirmed) in local_keys {
+            self.next_new_local_key = self
+                .next_new_local_key
+                .max(local_key.get_index().wrapping_add(1));
+            if confirmed {
+                self.restored_local_keys.insert(key, local_key);
+            } else {
+                self.delete_restored_local_key(key, local_key);
+            }
+        }
+    }
+
+    fn delete_restored_local_key(&mut self, key: EntityKey, local_key: LocalEntityKey) {
+        self.queued_messages
+            .push_back((None, ServerEntityMessage::Delete(key, local_key, None)));
+        self.recycled_local_keys
+            .push_back((local_key, Timer::new(self.tombstone_duration)));
+    }
+
+    /// Links an Entity to one the Client has spawned in prediction, so that the
+    /// Entity's creation message will carry the Client's PredictionKey
+    pub fn set_prediction_key(&mut self, key: &EntityKey, prediction_key: PredictionKey) {
+        if !self.entity_records.contains_key(*key) {
+            self.pending_prediction_keys.insert(*key, prediction_key);
+            return;
+        }
+        for (_, message) in self.queued_messages.iter_mut() {
+            if let ServerEntityMessage::Create(global_key, _, _, message_prediction_key, _) =
+                message
+            {
+                if global_key == key {
+                    *message_prediction_key = Some(prediction_key);
+                    return;
+                }
+            }
         }
+        warn!("cannot link an entity to a predicted one, after its creation has been sent");
+    }
 
-        let output = self.next_new_local_key;
+    fn get_new_local_key(&mut self) -> LocalEntityKey {
+        // keys of deleted Entities are only reused once their tombstone has expired
+        if let Some((_, tombstone_timer)) = self.recycled_local_keys.front() {
+            if tombstone_timer.ringing() {
+                let (local_key, _) = self.recycled_local_keys.pop_front().unwrap();
+                return local_key.next_generation();
+            }
+        }
+
+        let output = LocalEntityKey::new(self.next_new_local_key, 0);
         self.next_new_local_key += 1;
         return output;
     }
 
-    pub fn collect_entity_updates(&mut self) {
+    /// Returns whether the initial world snapshot has yet to be started
+    pub fn is_snapshot_pending(&self) -> bool {
+        return self.snapshot_pending;
+    }
+
+    /// Begins the initial world snapshot, which consists of every Entity
+    /// currently in scope. The Client is told the total number of Entities up
+    /// front, and is sent a completion message once every Create has been
+    /// delivered
+    pub fn begin_snapshot(&mut self) {
+        self.snapshot_pending = false;
+
+        // Entities received from a previous host which have not come into scope
+        // are deleted on the Client
+        let restored_local_keys: Vec<(EntityKey, LocalEntityKey)> =
+            self.restored_local_keys.drain().collect();
+        for (key, local_key) in restored_local_keys {
+            self.delete_restored_local_key(key, local_key);
+        }
+
+        let mut snapshot_keys = HashSet::new();
         for (key, record) in self.entity_records.iter() {
+            if record.status == LocalEntityStatus::Creating {
+                snapshot_keys.insert(key);
+            }
+        }
+
+        self.queued_messages.push_front((
+            None,
+            ServerEntityMessage::SnapshotBegin(snapshot_keys.len() as u32),
+        ));
+        self.snapshot_keys = Some(snapshot_keys);
+        self.check_snapshot_complete();
+    }
+
+    fn check_snapshot_complete(&mut self) {
+        if let Some(snapshot_keys) = &mut self.snapshot_keys {
+            let entity_records = &self.entity_records;
+            snapshot_keys.retain(|key| match entity_records.get(*key) {
+                Some(record) => record.status == LocalEntityStatus::Creating,
+                None => false,
+            });
+            if snapshot_keys.is_empty() {
+                self.snapshot_keys = None;
+                self.queued_messages
+                    .push_back((None, ServerEntityMessage::SnapshotComplete));
+            }
+        }
+    }
+
+    /// Queues up Update messages for mutated Entities. Each mutated Entity's
+    /// priority is added to its accumulator every time this is called, so
+    /// Entities which have waited longer, or which are more important to the
+    /// Client, are sent first. If `max_updates` is given, only that many
+    /// Entities are updated, and the rest keep accumulating for next time.
+    /// Entities given a keyframe interval are sent as a stream of keyframes
+    /// & deltas, see `update_keyframe()`. Returns the number of Entities
+    /// whose updates were collected, and the number which were deferred
+    pub fn collect_entity_updates(
+        &mut self,
+        priority_func: &dyn Fn(&EntityKey) -> f32,
+        keyframe_interval_func: &dyn Fn(&EntityKey) -> Option<u16>,
+        max_updates: Option<u16>,
+        timings: &mut TickTimings,
+    ) -> (usize, usize) {
+        let scan_start = Instant::now();
+        let mut dirty_keys: Vec<EntityKey> = Vec::new();
+        for (key, record) in self.entity_records.iter_mut() {
+            if record.status == LocalEntityStatus::Created {
+                if let Some(entity) = self.local_entity_store.get(key) {
+                    record.keyframe_interval = keyframe_interval_func(&key);
+                    Self::update_keyframe(record, entity.as_ref().borrow().get_property_count());
+                }
+            }
             if record.status == LocalEntityStatus::Created
                 && !record.get_state_mask().as_ref().borrow().is_clear()
             {
+                dirty_keys.push(key);
+            }
+        }
+
+        let prioritize_start = Instant::now();
+        timings.dirty_scan += prioritize_start.duration_since(scan_start);
+        let mut dirty_entities: Vec<(EntityKey, f32)> = Vec::new();
+        for key in dirty_keys {
+            if let Some(record) = self.entity_records.get_mut(key) {
+                record.priority_accumulator += priority_func(&key).max(MIN_PRIORITY_INCREMENT);
+                dirty_entities.push((key, record.priority_accumulator));
+            }
+        }
+        dirty_entities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        let dirty_count = dirty_entities.len();
+        if let Some(max_updates) = max_updates {
+            dirty_entities.truncate(max_updates as usize);
+        }
+        let collected_count = dirty_entities.len();
+        timings.prioritize += prioritize_start.elapsed();
+
+        for (key, _) in dirty_entities {
+            if let Some(record) = self.entity_records.get_mut(key) {
                 if let Some(entity_ref) = self.local_entity_store.get(key) {
-                    self.queued_messages.push_back(ServerEntityMessage::Update(
-                        key,
-                        record.local_key,
-                        record.get_state_mask().clone(),
-                        entity_ref.clone(),
+                    record.priority_accumulator = 0.0;
+                    self.queued_messages.push_back((
+                        None,
+                        ServerEntityMessage::Update(
+                            key,
+                            record.local_key,
+                            record.get_state_mask().clone(),
+                            entity_ref.clone(),
+                        ),
                     ));
                 }
             }
         }
+
+        return (collected_count, dirty_count - collected_count);
+    }
+
+    // An Entity with a keyframe interval of N is sent in full every N ticks,
+    // a keyframe, while the updates in between are deltas of only the
+    // Properties which changed since the previous tick. Dropped keyframes &
+    // deltas are not retransmitted, the Client instead keeps the last
+    // keyframe with the deltas it has received since, until the next keyframe
+    // brings it back in sync. A keyframe is skipped if the Entity hasn't
+    // changed and nothing has been dropped since the last one
+    fn update_keyframe(record: &mut EntityRecord, property_count: u8) {
+        let keyframe_interval = match record.keyframe_interval {
+            Some(keyframe_interval) => keyframe_interval.max(1),
+            None => {
+                record.ticks_since_keyframe = 0;
+                record.keyframe_due = false;
+                return;
+            }
+        };
+        record.ticks_since_keyframe = record.ticks_since_keyframe.saturating_add(1);
+        if record.ticks_since_keyframe < keyframe_interval {
+            return;
+        }
+        record.ticks_since_keyframe = 0;
+        let send_keyframe =
+            record.keyframe_due || !record.get_state_mask().as_ref().borrow().is_clear();
+        record.keyframe_due = false;
+        if send_keyframe {
+            let mut state_mask = record.get_state_mask().as_ref().borrow_mut();
+            for property_index in 0..property_count {
+                state_mask.set_bit(property_index, true);
+            }
+        }
+    }
+
+    /// Clears the state mask of every Entity, discarding any mutations which
+    /// have not yet been collected
+    pub fn discard_entity_updates(&mut self) {
+        for (_, record) in self.entity_records.iter() {
+            record.get_state_mask().as_ref().borrow_mut().clear();
+        }
+    }
+
+    /// Marks every Property of every Entity the Client has received as
+    /// mutated, so that the next collection of updates sends the Client the
+    /// full state of those Entities
+    pub fn mark_all_mutated(&mut self) {
+        for (key, record) in self.entity_records.iter() {
+            if record.status == LocalEntityStatus::Created {
+                if let Some(entity) = self.local_entity_store.get(key) {
+                    let property_count = entity.as_ref().borrow().get_property_count();
+                    let mut state_mask = record.get_state_mask().as_ref().borrow_mut();
+                    for property_index in 0..property_count {
+                        state_mask.set_bit(property_index, true);
+                    }
+                }
+            }
+        }
+    }
+
+    /// If tick batching is enabled, assigns every Entity message queued since
+    /// the last tick to a slot of a new batch, followed by a message telling the
+    /// Client the tick and number of messages in the batch, so that the Client
+    /// can apply the whole batch at once
+    pub fn end_tick(&mut self, tick: u16) {
+        if !self.tick_batching {
+            return;
+        }
+
+        let batch_index = self.next_batch_index;
+        let mut message_count: u16 = 0;
+        for (message_batch_index, _) in self.queued_messages.iter_mut() {
+            if message_batch_index.is_none() {
+                *message_batch_index = Some((batch_index, message_count));
+                message_count += 1;
+            }
+        }
+
+        if message_count > 0 {
+            self.next_batch_index = self.next_batch_index.wrapping_add(1);
+            self.queued_messages.push_back((
+                None,
+                ServerEntityMessage::TickComplete(batch_index, tick, message_count),
+            ));
+        }
     }
 }
 
 impl<T: EntityType> EntityNotifiable for ServerEntityManager<T> {
     fn notify_packet_delivered(&mut self, packet_index: u16) {
         if let Some(delivered_messages_list) = self.sent_messages.get(&packet_index) {
-            for delivered_message in delivered_messages_list.into_iter() {
+            for (_, delivered_message) in delivered_messages_list.into_iter() {
                 match delivered_message {
-                    ServerEntityMessage::Create(global_key, _, _) => {
+                    ServerEntityMessage::Create(global_key, local_key, _, _, _) => {
                         if let Some(entity_record) = self.entity_records.get_mut(*global_key) {
-                            // update entity record status
-                            entity_record.status = LocalEntityStatus::Created;
+                            // update entity record status, unless the Entity has since
+                            // been deleted, or come back into scope with a new local key
+                            if entity_record.local_key == *local_key
+                                && entity_record.status == LocalEntityStatus::Creating
+                            {
+                                entity_record.status = LocalEntityStatus::Created;
+                            }
                         }
                     }
-                    ServerEntityMessage::Delete(global_key_ref, local_key) => {
+                    ServerEntityMessage::Delete(global_key_ref, local_key, _) => {
                         let global_key = *global_key_ref;
-                        if let Some(_) = self.entity_records.get(global_key) {
+                        // the Entity may have come back into scope with a new local key
+                        if self.is_current_record(&global_key, local_key) {
                             // actually delete the entity from local records
                             self.mut_handler
                                 .as_ref()
                                 .borrow_mut()
                                 .deregister_mask(&self.address, global_key_ref);
                             self.local_entity_store.remove(global_key);
-                            self.local_to_global_key_map.remove(local_key);
-                            self.recycled_local_keys.push(*local_key);
                             self.entity_records.remove(global_key);
                         }
+                        // the record of a deregistered Entity is replaced once a new
+                        // Entity takes its slot, so the local key is recycled even if
+                        // the record is gone
+                        if self.local_to_global_key_map.get(local_key) == Some(global_key_ref) {
+                            self.local_to_global_key_map.remove(local_key);
+                            self.recycled_local_keys
+                                .push_back((*local_key, Timer::new(self.tombstone_duration)));
+                        }
                     }
                     ServerEntityMessage::Update(_, _, _, _) => {
                         self.sent_updates.remove(&packet_index);
                     }
+                    ServerEntityMessage::SnapshotChunk(chunk_index, _, _) => {
+                        let mut bulk_snapshot_delivered = false;
+                        if let Some(bulk_snapshot) = &mut self.bulk_snapshot {
+                            bulk_snapshot_delivered =
+                                bulk_snapshot.notify_chunk_delivered(*chunk_index);
+                        }
+                        if bulk_snapshot_delivered {
+                            let bulk_snapshot = self.bulk_snapshot.take().unwrap();
+                            for global_key in bulk_snapshot.entity_keys {
+                                if let Some(entity_record) = self.entity_records.get_mut(global_key)
+                                {
+                                    if entity_record.status == LocalEntityStatus::Creating {
+                                        entity_record.status = LocalEntityStatus::Created;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    ServerEntityMessage::SnapshotBegin(_)
+                    | ServerEntityMessage::SnapshotComplete
+                    | ServerEntityMessage::TickComplete(_, _, _) => {}
                 }
             }
 
             self.sent_messages.remove(&packet_index);
+            self.check_snapshot_complete();
         }
     }
 
     fn notify_packet_dropped(&mut self, dropped_packet_index: u16) {
         if let Some(dropped_messages_list) = self.sent_messages.get(&dropped_packet_index) {
-            for dropped_message in dropped_messages_list.into_iter() {
+            for (batch_index, dropped_message) in dropped_messages_list.into_iter() {
                 match dropped_message {
-                    ServerEntityMessage::Create(_, _, _) | ServerEntityMessage::Delete(_, _) => {
-                        self.queued_messages.push_back(dropped_message.clone());
+                    ServerEntityMessage::Create(global_key, local_key, _, _, _) => {
+                        // a Create is only resent while the Entity is still being created,
+                        // as a resend arriving after the Entity's Delete would leave the
+                        // Client with a copy of an Entity which no longer exists. A Create
+                        // belonging to a batch is always resent in its slot, as the Client
+                        // is waiting on it to apply the batch, & applies the batch holding
+                        // the Entity's Delete only after it
+                        let is_creating = match self.entity_records.get(*global_key) {
+                            Some(record) => {
+                                record.local_key == *local_key
+                                    && record.status == LocalEntityStatus::Creating
+                            }
+                            None => false,
+                        };
+                        if is_creating || batch_index.is_some() {
+                            self.queued_messages
+                                .push_back((*batch_index, dropped_message.clone()));
+                        }
                     }
-                    ServerEntityMessage::Update(global_key, _, _, _) => {
+                    ServerEntityMessage::Delete(_, _, _)
+                    | ServerEntityMessage::SnapshotBegin(_)
+                    | ServerEntityMessage::SnapshotComplete
+                    | ServerEntityMessage::SnapshotChunk(_, _, _)
+                    | ServerEntityMessage::TickComplete(_, _, _) => {
+                        self.queued_messages
+                            .push_back((*batch_index, dropped_message.clone()));
+                    }
+                    ServerEntityMessage::Update(global_key, local_key, _, entity) => {
                         if let Some(state_mask_map) = self.sent_updates.get(&dropped_packet_index) {
                             if let Some(state_mask) = state_mask_map.get(global_key) {
                                 let mut new_state_mask = state_mask.as_ref().borrow().clone();
@@ -311,9 +902,37 @@ impl<T: EntityType> EntityNotifiable for ServerEntityManager<T> {
                                 }
 
                                 if let Some(record) = self.entity_records.get_mut(*global_key) {
-                                    let mut current_state_mask =
-                                        record.get_state_mask().as_ref().borrow_mut();
-                                    current_state_mask.or(new_state_mask.borrow());
+                                    // unguaranteed Properties are not retransmitted, nor is
+                                    // anything of an Entity streamed as keyframes & deltas,
+                                    // which is brought back in sync by its next keyframe
+                                    new_state_mask.nand(record.get_unguaranteed_state_mask());
+                                    if record.keyframe_interval.is_some() {
+                                        record.keyframe_due = true;
+                                        new_state_mask.clear();
+                                    }
+
+                                    if batch_index.is_none() {
+                                        let mut current_state_mask =
+                                            record.get_state_mask().as_ref().borrow_mut();
+                                        current_state_mask.or(new_state_mask.borrow());
+                                    }
+                                } else {
+                                    new_state_mask.clear();
+                                }
+
+                                // an update belonging to a batch is resent in that same batch,
+                                // even if there is nothing left to update, as the Client is
+                                // waiting on it to apply the batch
+                                if batch_index.is_some() {
+                                    self.queued_messages.push_back((
+                                        *batch_index,
+                                        ServerEntityMessage::Update(
+                                            *global_key,
+                                            *local_key,
+                                            Rc::new(RefCell::new(new_state_mask)),
+                                            entity.clone(),
+                                        ),
+                                    ));
                                 }
                             }
                         }
@@ -326,3 +945,109 @@ impl<T: EntityType> EntityNotifiable for ServerEntityManager<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        collections::{HashMap, HashSet},
+        rc::Rc,
+        time::Duration,
+    };
+
+    use naia_example_shared::{ExampleEntity, PointEntity};
+    use naia_shared::{Entity, EntityNotifiable};
+    use slotmap::SlotMap;
+
+    use super::{EntityKey, MutHandler, ServerEntityManager, ServerEntityMessage};
+
+    type SentMessages = Vec<(Option<(u16, u16)>, ServerEntityMessage<ExampleEntity>)>;
+
+    fn send_packet(
+        manager: &mut ServerEntityManager<ExampleEntity>,
+        packet_index: u16,
+    ) -> SentMessages {
+        let mut messages = Vec::new();
+        while let Some(message) = manager.pop_outgoing_message(packet_index) {
+            messages.push(message);
+        }
+        return messages;
+    }
+
+    // Gets the indices of the batches a Client receiving the given packets
+    // could apply, having received every slot of them
+    fn complete_batches(packets: &[&SentMessages]) -> Vec<u16> {
+        let mut message_counts: HashMap<u16, u16> = HashMap::new();
+        let mut slots: HashSet<(u16, u16)> = HashSet::new();
+        for packet in packets {
+            for (batch_index, message) in packet.iter() {
+                if let Some(batch_index) = batch_index {
+                    slots.insert(*batch_index);
+                }
+                if let ServerEntityMessage::TickComplete(batch_index, _, message_count) = message {
+                    message_counts.insert(*batch_index, *message_count);
+                }
+            }
+        }
+        let mut batches: Vec<u16> = message_counts
+            .into_iter()
+            .filter(|(batch_index, message_count)| {
+                (0..*message_count).all(|slot| slots.contains(&(*batch_index, slot)))
+            })
+            .map(|(batch_index, _)| batch_index)
+            .collect();
+        batches.sort();
+        return batches;
+    }
+
+    #[test]
+    fn resends_dropped_batched_creates_of_removed_entities() {
+        let mut_handler = MutHandler::new();
+        let mut manager = ServerEntityManager::<ExampleEntity>::new(
+            "127.0.0.1:14191".parse().unwrap(),
+            &mut_handler,
+            Duration::from_secs(0),
+            true,
+        );
+        let mut entity_keys = SlotMap::<EntityKey, ()>::with_key();
+        let (key, later_key) = (entity_keys.insert(()), entity_keys.insert(()));
+        mut_handler.borrow_mut().register_entity(&key);
+        mut_handler.borrow_mut().register_entity(&later_key);
+        let entity: Rc<RefCell<dyn Entity<ExampleEntity>>> =
+            PointEntity::new(0, 0, "a", "b").wrap();
+
+        manager.add_entity(&key, &entity, None);
+        manager.end_tick(1);
+        let create_packet = send_packet(&mut manager, 0);
+        manager.remove_entity(&key, None);
+        manager.end_tick(2);
+        let delete_packet = send_packet(&mut manager, 1);
+
+        // dropped while the Entity is being deleted
+        manager.notify_packet_dropped(0);
+        let create_resend = send_packet(&mut manager, 2);
+        assert!(matches!(
+            create_resend[0],
+            (Some((0, 0)), ServerEntityMessage::Create(..))
+        ));
+
+        // dropped once the Entity's record is gone
+        manager.notify_packet_delivered(1);
+        manager.notify_packet_dropped(2);
+        let create_resend = send_packet(&mut manager, 3);
+        assert!(matches!(
+            create_resend[0],
+            (Some((0, 0)), ServerEntityMessage::Create(..))
+        ));
+        manager.notify_packet_delivered(3);
+
+        manager.add_entity(&later_key, &entity, None);
+        manager.end_tick(3);
+        let later_packet = send_packet(&mut manager, 4);
+
+        assert!(complete_batches(&[&create_packet]) == vec![0]);
+        assert!(
+            complete_batches(&[&delete_packet, &create_resend, &later_packet]) == vec![0, 1, 2]
+        );
+    }
+}
@@ -1,26 +1,66 @@
-use naia_shared::{Entity, EntityType, LocalEntityKey, StateMask};
-use std::{cell::RefCell, rc::Rc};
+use naia_shared::{
+    DespawnReason, Entity, EntityType, LocalEntityKey, PredictionKey, PrefabId, StateMask,
+};
+use std::{any::TypeId, cell::RefCell, rc::Rc};
 
 use super::entity_key::entity_key::EntityKey;
 
 #[derive(Debug)]
 pub enum ServerEntityMessage<T: EntityType> {
-    Create(EntityKey, LocalEntityKey, Rc<RefCell<dyn Entity<T>>>),
+    Create(
+        EntityKey,
+        LocalEntityKey,
+        Rc<RefCell<dyn Entity<T>>>,
+        Option<PredictionKey>,
+        Option<PrefabId>,
+    ),
     Update(
         EntityKey,
         LocalEntityKey,
         Rc<RefCell<StateMask>>,
         Rc<RefCell<dyn Entity<T>>>,
     ),
-    Delete(EntityKey, LocalEntityKey),
+    Delete(EntityKey, LocalEntityKey, Option<DespawnReason>),
+    SnapshotBegin(u32),
+    SnapshotComplete,
+    SnapshotChunk(u16, u16, Rc<Box<[u8]>>),
+    TickComplete(u16, u16, u16),
 }
 
 impl<T: EntityType> ServerEntityMessage<T> {
     pub fn write_message_type(&self) -> u8 {
         match self {
-            ServerEntityMessage::Create(_, _, _) => 0,
-            ServerEntityMessage::Delete(_, _) => 1,
+            ServerEntityMessage::Create(_, _, _, None, None) => 0,
+            ServerEntityMessage::Delete(_, _, _) => 1,
             ServerEntityMessage::Update(_, _, _, _) => 2,
+            ServerEntityMessage::SnapshotBegin(_) => 3,
+            ServerEntityMessage::SnapshotComplete => 4,
+            ServerEntityMessage::Create(_, _, _, Some(_), None) => 5,
+            ServerEntityMessage::Create(_, _, _, None, Some(_)) => 6,
+            ServerEntityMessage::Create(_, _, _, Some(_), Some(_)) => 7,
+            ServerEntityMessage::TickComplete(_, _, _) => 8,
+            ServerEntityMessage::SnapshotChunk(_, _, _) => 9,
+        }
+    }
+
+    /// Gets the Entity the message concerns, if any
+    pub fn get_entity_key(&self) -> Option<EntityKey> {
+        match self {
+            ServerEntityMessage::Create(key, _, _, _, _)
+            | ServerEntityMessage::Update(key, _, _, _)
+            | ServerEntityMessage::Delete(key, _, _) => Some(*key),
+            _ => None,
+        }
+    }
+
+    /// Gets the type of the Entity the message carries, if any
+    pub fn get_type_id(&self) -> Option<TypeId> {
+        match self {
+            ServerEntityMessage::Create(_, _, entity, _, _)
+            | ServerEntityMessage::Update(_, _, _, entity) => {
+                Some(entity.as_ref().borrow().get_type_id())
+            }
+            _ => None,
         }
     }
 }
@@ -28,15 +68,23 @@ impl<T: EntityType> ServerEntityMessage<T> {
 impl<T: EntityType> Clone for ServerEntityMessage<T> {
     fn clone(&self) -> Self {
         match self {
-            ServerEntityMessage::Create(gk, lk, e) => {
-                ServerEntityMessage::Create(gk.clone(), lk.clone(), e.clone())
+            ServerEntityMessage::Create(gk, lk, e, pk, pi) => {
+                ServerEntityMessage::Create(gk.clone(), lk.clone(), e.clone(), *pk, *pi)
             }
-            ServerEntityMessage::Delete(gk, lk) => {
-                ServerEntityMessage::Delete(gk.clone(), lk.clone())
+            ServerEntityMessage::Delete(gk, lk, r) => {
+                ServerEntityMessage::Delete(gk.clone(), lk.clone(), *r)
             }
             ServerEntityMessage::Update(gk, lk, sm, e) => {
                 ServerEntityMessage::Update(gk.clone(), lk.clone(), sm.clone(), e.clone())
             }
+            ServerEntityMessage::SnapshotBegin(total) => ServerEntityMessage::SnapshotBegin(*total),
+            ServerEntityMessage::SnapshotComplete => ServerEntityMessage::SnapshotComplete,
+            ServerEntityMessage::SnapshotChunk(chunk_index, total_chunks, data) => {
+                ServerEntityMessage::SnapshotChunk(*chunk_index, *total_chunks, data.clone())
+            }
+            ServerEntityMessage::TickComplete(batch_index, tick, count) => {
+                ServerEntityMessage::TickComplete(*batch_index, *tick, *count)
+            }
         }
     }
 }
@@ -0,0 +1,11 @@
+use naia_shared::{Event, EventType};
+
+/// The outcome of validating an Event received from a Client, before it is
+/// handed to the application
+pub enum EventValidation<T: EventType> {
+    /// The Event is valid, and will be emitted as a ServerEvent::Event
+    Accept,
+    /// The Event is discarded. Optionally, an Event can be sent back to the
+    /// Client to inform it of the rejection
+    Reject(Option<Box<dyn Event<T>>>),
+}
@@ -0,0 +1,26 @@
+use naia_shared::DespawnReason;
+
+use super::entities::entity_key::entity_key::EntityKey;
+
+/// An Event that is emitted to a local User, such as the host player of a
+/// ListenServer or a BotClient, mirroring the ClientEvent a remote Client
+/// would receive. A local User shares the Server's memory, so its Entities
+/// are identified by their EntityKey
+pub enum HostEvent<T> {
+    /// An Event emitted to the local User from the Server
+    Event(T),
+    /// An Event which the Server scheduled for a specific Server tick, with
+    /// `NaiaServer::queue_event_at_tick()`, emitted once that tick has been
+    /// reached
+    TimedEvent(u16, T),
+    /// An Event which the Server sent targeted at the Entity with the given
+    /// key, with `NaiaServer::queue_entity_event()`
+    EntityEvent(EntityKey, T),
+    /// Occurs when an Entity on the Server has come into scope for the local
+    /// User
+    CreateEntity(EntityKey),
+    /// Occurs when an Entity on the Server has left the local User's scope.
+    /// Contains the reason code the Server gave, if the Entity was
+    /// deregistered with `NaiaServer::deregister_entity_with_reason()`
+    DeleteEntity(EntityKey, Option<DespawnReason>),
+}
@@ -0,0 +1,345 @@
+use std::{io::Cursor, net::SocketAddr};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use naia_shared::{
+    utils::{read_socket_address, read_string, write_socket_address, write_string},
+    ClientId, EventId, EventType, LocalEntityKey, PacketReader, PrefabId, Timestamp,
+};
+
+use super::{entities::entity_key::entity_key::EntityKey, room::room_key::RoomKey};
+
+/// The version of the format written by `NaiaServer::save_world()`, which is
+/// incremented whenever the format changes, so that older saves are rejected
+pub const WORLD_SAVE_VERSION: u8 = 3;
+
+/// The version of the format written by `NaiaServer::export_live_state()`,
+/// which is incremented whenever the format changes, so that older exports
+/// are rejected
+pub const LIVE_STATE_VERSION: u8 = 4;
+
+/// An Entity exported from a host, as (key on the old host, prefab id, naia
+/// id, payload)
+pub type HostStateEntity = (u64, Option<PrefabId>, u16, Box<[u8]>);
+
+/// A Room exported from a host, as (key on the old host, keys of the Entities
+/// in the Room on the old host, migration tokens of the Users in the Room)
+pub type HostStateRoom = (u64, Vec<u64>, Vec<u64>);
+
+/// A guaranteed Event which had yet to be delivered to a User, as (event id,
+/// naia id, payload)
+pub type HostStateEvent = (EventId, u16, Box<[u8]>);
+
+/// A connected User exported from a host, as (migration token, local key of
+/// every Entity in the User's scope by the Entity's key on the old host, along
+/// with whether the User had confirmed the Entity's creation, the id of the
+/// next Event to be sent to the User, undelivered Events)
+pub type HostStateUser = (
+    u64,
+    Vec<(u64, LocalEntityKey, bool)>,
+    EventId,
+    Vec<HostStateEvent>,
+);
+
+/// The authoritative state of a Server, which can be transferred to another
+/// host so that it can take over. Keys are written in their FFI form, as they
+/// are only meaningful to the old host
+#[derive(Debug)]
+pub struct HostState {
+    pub entities: Vec<HostStateEntity>,
+    pub rooms: Vec<HostStateRoom>,
+    pub users: Vec<HostStateUser>,
+}
+
+impl HostState {
+    /// Writes the HostState into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes
+            .write_u32::<BigEndian>(self.entities.len() as u32)
+            .unwrap();
+        for (key, prefab_id, naia_id, payload) in self.entities.iter() {
+            out_bytes.write_u64::<BigEndian>(*key).unwrap();
+            match prefab_id {
+                Some(prefab_id) => {
+                    out_bytes.write_u8(1).unwrap();
+                    out_bytes.write_u16::<BigEndian>(*prefab_id).unwrap();
+                }
+                None => {
+                    out_bytes.write_u8(0).unwrap();
+                }
+            }
+            out_bytes.write_u16::<BigEndian>(*naia_id).unwrap();
+            out_bytes
+                .write_u16::<BigEndian>(payload.len() as u16)
+                .unwrap();
+            out_bytes.extend_from_slice(payload);
+        }
+
+        out_bytes
+            .write_u32::<BigEndian>(self.rooms.len() as u32)
+            .unwrap();
+        for (key, entity_keys, migration_tokens) in self.rooms.iter() {
+            out_bytes.write_u64::<BigEndian>(*key).unwrap();
+            out_bytes
+                .write_u32::<BigEndian>(entity_keys.len() as u32)
+                .unwrap();
+            for entity_key in entity_keys.iter() {
+                out_bytes.write_u64::<BigEndian>(*entity_key).unwrap();
+            }
+            out_bytes
+                .write_u32::<BigEndian>(migration_tokens.len() as u32)
+                .unwrap();
+            for migration_token in migration_tokens.iter() {
+                out_bytes.write_u64::<BigEndian>(*migration_token).unwrap();
+            }
+        }
+
+        out_bytes
+            .write_u32::<BigEndian>(self.users.len() as u32)
+            .unwrap();
+        for (migration_token, local_keys, next_event_id, events) in self.users.iter() {
+            out_bytes.write_u64::<BigEndian>(*migration_token).unwrap();
+            out_bytes
+                .write_u32::<BigEndian>(local_keys.len() as u32)
+                .unwrap();
+            for (entity_key, local_key, confirmed) in local_keys.iter() {
+                out_bytes.write_u64::<BigEndian>(*entity_key).unwrap();
+                local_key.write(out_bytes);
+                out_bytes.write_u8(*confirmed as u8).unwrap();
+            }
+            out_bytes.write_u16::<BigEndian>(*next_event_id).unwrap();
+            out_bytes
+                .write_u32::<BigEndian>(events.len() as u32)
+                .unwrap();
+            for (event_id, naia_id, payload) in events.iter() {
+                out_bytes.write_u16::<BigEndian>(*event_id).unwrap();
+                out_bytes.write_u16::<BigEndian>(*naia_id).unwrap();
+                out_bytes
+                    .write_u16::<BigEndian>(payload.len() as u16)
+                    .unwrap();
+                out_bytes.extend_from_slice(payload);
+            }
+        }
+    }
+
+    /// Reads a HostState from incoming bytes. Returns None if the bytes are
+    /// malformed
+    pub fn read(bytes: &[u8]) -> Option<HostState> {
+        let mut cursor = Cursor::new(bytes);
+
+        let entity_count = cursor.read_u32::<BigEndian>().ok()?;
+        let mut entities = Vec::new();
+        for _ in 0..entity_count {
+            let key = cursor.read_u64::<BigEndian>().ok()?;
+            let prefab_id = match cursor.read_u8().ok()? {
+                0 => None,
+                _ => Some(cursor.read_u16::<BigEndian>().ok()?),
+            };
+            let naia_id = cursor.read_u16::<BigEndian>().ok()?;
+            let payload = read_payload(&mut cursor, bytes)?;
+            entities.push((key, prefab_id, naia_id, payload));
+        }
+
+        let room_count = cursor.read_u32::<BigEndian>().ok()?;
+        let mut rooms = Vec::new();
+        for _ in 0..room_count {
+            let key = cursor.read_u64::<BigEndian>().ok()?;
+            let room_entity_count = cursor.read_u32::<BigEndian>().ok()?;
+            let mut entity_keys = Vec::new();
+            for _ in 0..room_entity_count {
+                entity_keys.push(cursor.read_u64::<BigEndian>().ok()?);
+            }
+            let room_user_count = cursor.read_u32::<BigEndian>().ok()?;
+            let mut migration_tokens = Vec::new();
+            for _ in 0..room_user_count {
+                migration_tokens.push(cursor.read_u64::<BigEndian>().ok()?);
+            }
+            rooms.push((key, entity_keys, migration_tokens));
+        }
+
+        let user_count = cursor.read_u32::<BigEndian>().ok()?;
+        let mut users = Vec::new();
+        for _ in 0..user_count {
+            let migration_token = cursor.read_u64::<BigEndian>().ok()?;
+            let local_key_count = cursor.read_u32::<BigEndian>().ok()?;
+            let mut local_keys = Vec::new();
+            for _ in 0..local_key_count {
+                let entity_key = cursor.read_u64::<BigEndian>().ok()?;
+                let local_key = LocalEntityKey::read(&mut cursor)?;
+                let confirmed = cursor.read_u8().ok()? != 0;
+                local_keys.push((entity_key, local_key, confirmed));
+            }
+            let next_event_id = cursor.read_u16::<BigEndian>().ok()?;
+            let event_count = cursor.read_u32::<BigEndian>().ok()?;
+            let mut events = Vec::new();
+            for _ in 0..event_count {
+                let event_id = cursor.read_u16::<BigEndian>().ok()?;
+                let naia_id = cursor.read_u16::<BigEndian>().ok()?;
+                let payload = read_payload(&mut cursor, bytes)?;
+                events.push((event_id, naia_id, payload));
+            }
+            users.push((migration_token, local_keys, next_event_id, events));
+        }
+
+        return Some(HostState {
+            entities,
+            rooms,
+            users,
+        });
+    }
+}
+
+/// The state of the connection to a Client, exported so that a replacement
+/// Server process on the same address can adopt it without the Client
+/// reconnecting. The Entities & Events of the User are exported in a
+/// HostStateUser, with the same migration token
+#[derive(Debug)]
+pub struct HostStateConnection {
+    pub migration_token: u64,
+    pub user_key: u64,
+    pub address: SocketAddr,
+    pub endpoint: usize,
+    pub timestamp: Timestamp,
+    pub client_id: ClientId,
+    pub capabilities: u32,
+    /// The index of the next outgoing packet, the most recent incoming packet
+    /// & the ack bitfield of those before it
+    pub sequence_state: (u16, u16, u32),
+    /// The revisions of the Client's roster, of the Manifest & of the tick
+    /// rate last sent
+    pub revisions: (u32, u32, u32),
+    /// The index of the next tick batch of Entity messages to be sent
+    pub next_batch_index: u16,
+    /// The auth Event the Client connected with, as (naia id, payload)
+    pub auth_event: Option<(u16, Box<[u8]>)>,
+    pub identity: Option<String>,
+}
+
+impl HostStateConnection {
+    /// Writes the HostStateConnection into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes
+            .write_u64::<BigEndian>(self.migration_token)
+            .unwrap();
+        out_bytes.write_u64::<BigEndian>(self.user_key).unwrap();
+        write_socket_address(&self.address, out_bytes);
+        out_bytes
+            .write_u16::<BigEndian>(self.endpoint as u16)
+            .unwrap();
+        self.timestamp.write(out_bytes);
+        out_bytes.write_u16::<BigEndian>(self.client_id).unwrap();
+        out_bytes.write_u32::<BigEndian>(self.capabilities).unwrap();
+        let (local_sequence, remote_sequence, ack_bitfield) = self.sequence_state;
+        out_bytes.write_u16::<BigEndian>(local_sequence).unwrap();
+        out_bytes.write_u16::<BigEndian>(remote_sequence).unwrap();
+        out_bytes.write_u32::<BigEndian>(ack_bitfield).unwrap();
+        let (roster_revision, manifest_revision, tick_rate_revision) = self.revisions;
+        out_bytes.write_u32::<BigEndian>(roster_revision).unwrap();
+        out_bytes.write_u32::<BigEndian>(manifest_revision).unwrap();
+        out_bytes
+            .write_u32::<BigEndian>(tick_rate_revision)
+            .unwrap();
+        out_bytes
+            .write_u16::<BigEndian>(self.next_batch_index)
+            .unwrap();
+        match &self.auth_event {
+            Some((naia_id, payload)) => {
+                out_bytes.write_u8(1).unwrap();
+                out_bytes.write_u16::<BigEndian>(*naia_id).unwrap();
+                out_bytes
+                    .write_u16::<BigEndian>(payload.len() as u16)
+                    .unwrap();
+                out_bytes.extend_from_slice(payload);
+            }
+            None => {
+                out_bytes.write_u8(0).unwrap();
+            }
+        }
+        match &self.identity {
+            Some(identity) => {
+                out_bytes.write_u8(1).unwrap();
+                write_string(identity, out_bytes);
+            }
+            None => {
+                out_bytes.write_u8(0).unwrap();
+            }
+        }
+    }
+
+    /// Reads a HostStateConnection from incoming bytes. Returns None if the
+    /// bytes are malformed
+    pub fn read(reader: &mut PacketReader) -> Option<HostStateConnection> {
+        let cursor = reader.get_cursor();
+        let migration_token = cursor.read_u64::<BigEndian>().ok()?;
+        let user_key = cursor.read_u64::<BigEndian>().ok()?;
+        let address = read_socket_address(reader)?;
+        let cursor = reader.get_cursor();
+        let endpoint = cursor.read_u16::<BigEndian>().ok()? as usize;
+        let timestamp_position = cursor.position() as usize;
+        if reader.get_buffer().len() < timestamp_position + 8 {
+            return None;
+        }
+        let timestamp = Timestamp::read(reader);
+        let cursor = reader.get_cursor();
+        let client_id = cursor.read_u16::<BigEndian>().ok()?;
+        let capabilities = cursor.read_u32::<BigEndian>().ok()?;
+        let sequence_state = (
+            cursor.read_u16::<BigEndian>().ok()?,
+            cursor.read_u16::<BigEndian>().ok()?,
+            cursor.read_u32::<BigEndian>().ok()?,
+        );
+        let revisions = (
+            cursor.read_u32::<BigEndian>().ok()?,
+            cursor.read_u32::<BigEndian>().ok()?,
+            cursor.read_u32::<BigEndian>().ok()?,
+        );
+        let next_batch_index = cursor.read_u16::<BigEndian>().ok()?;
+        let auth_event = match cursor.read_u8().ok()? {
+            0 => None,
+            _ => {
+                let naia_id = cursor.read_u16::<BigEndian>().ok()?;
+                let bytes = reader.get_buffer();
+                let payload = read_payload(reader.get_cursor(), bytes)?;
+                Some((naia_id, payload))
+            }
+        };
+        let identity = match reader.get_cursor().read_u8().ok()? {
+            0 => None,
+            _ => Some(read_string(reader)?),
+        };
+        return Some(HostStateConnection {
+            migration_token,
+            user_key,
+            address,
+            endpoint,
+            timestamp,
+            client_id,
+            capabilities,
+            sequence_state,
+            revisions,
+            next_batch_index,
+            auth_event,
+            identity,
+        });
+    }
+}
+
+/// A User exported from a previous host, waiting to connect with its migration
+/// token. Its keys have been mapped to those of the imported Entities & Rooms
+pub struct MigratedUser<T: EventType> {
+    pub local_keys: Vec<(EntityKey, LocalEntityKey, bool)>,
+    pub room_keys: Vec<RoomKey>,
+    pub next_event_id: EventId,
+    pub events: Vec<(EventId, T)>,
+}
+
+fn read_payload(cursor: &mut Cursor<&[u8]>, bytes: &[u8]) -> Option<Box<[u8]>> {
+    let payload_length = cursor.read_u16::<BigEndian>().ok()? as usize;
+    let payload_start_position = cursor.position() as usize;
+    let payload = bytes
+        .get(payload_start_position..payload_start_position + payload_length)?
+        .to_vec()
+        .into_boxed_slice();
+    cursor.set_position((payload_start_position + payload_length) as u64);
+    return Some(payload);
+}
@@ -0,0 +1,19 @@
+use std::collections::HashMap;
+
+use super::{
+    entities::entity_key::entity_key::EntityKey, room::room_key::RoomKey, user::user_key::UserKey,
+};
+
+/// The keys of the Entities, Rooms & Users created by
+/// `NaiaServer::import_host_state()` or `NaiaServer::import_live_state()`, by
+/// their keys on the previous host
+#[derive(Debug)]
+pub struct ImportedKeys {
+    /// The new key of each imported Entity, by its key on the previous host
+    pub entity_keys: HashMap<EntityKey, EntityKey>,
+    /// The new key of each imported Room, by its key on the previous host
+    pub room_keys: HashMap<RoomKey, RoomKey>,
+    /// The new key of each User whose connection was adopted with
+    /// `NaiaServer::import_live_state()`, by its key on the previous host
+    pub user_keys: HashMap<UserKey, UserKey>,
+}
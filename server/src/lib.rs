@@ -24,17 +24,69 @@ compile_error!("Naia Server can only use UDP or WebRTC, you must pick one");
 #[cfg(all(not(feature = "use-udp"), not(feature = "use-webrtc")))]
 compile_error!("Naia Server requires either the 'use-udp' or 'use-webrtc' feature to be enabled, you must pick one.");
 
-pub use naia_shared::{find_my_ip_address, Config, Entity, EntityType};
+pub use naia_shared::{
+    diff_entity_dumps, find_my_ip_address, find_my_ip_address_in, resolve_address,
+    to_canonical_address, AckLatencyHistogram, AddressFamily, BackpressureThresholds, BlobId,
+    Capabilities, ClientId, ClientRosterChange, CollectionChange, CollectionRevision, CommandStats,
+    Config, ConnectionQuality, ConnectionQualityThresholds, ConnectionStats, CustomSection,
+    DespawnReason, DropReason, DynamicEvent, DynamicValue, Entity, EntityDebugDump,
+    EntityDebugRecord, EntityDebugStatus, EntityDiff, EntityType, EventId, EventQueueStats,
+    LinkConditionerConfig, LinkState, ManagerType, NaiaError, PacketFraming, PacketFramingFactory,
+    PredictionKey, PrefabId, ProtocolDocFormat, QualityThreshold, RandomSource,
+    ReplicatedAnimation, ReplicatedCollection, ReplicatedTransform, RequestId, RigidBodyState,
+    RollbackHistory, RttStats, SectionFraming, ServerListPage, ServerStatus, StallThresholds,
+    TransformAxes, ACK_LATENCY_BUCKET_COUNT, ACK_LATENCY_BUCKET_WIDTH, CONNECTION_QUALITY_HOLD,
+    DROP_WARNING_INTERVAL, MAX_CUSTOM_SECTION_SIZE, MAX_EVENT_SIZE, MAX_HEARTBEAT_PAYLOAD_SIZE,
+    MAX_RAW_PAYLOAD_SIZE, MAX_REGISTERED_STATUS_SIZE, MAX_SHUTDOWN_REASON_LENGTH,
+    MAX_UNCONNECTED_DATA_SIZE, MTU_SIZE, REPLAY_WINDOW_SIZE,
+};
+#[cfg(feature = "rapier")]
+pub use naia_shared::{read_rigid_body_state, write_rigid_body_state, RapierBridge};
 
+mod bot_client;
 mod client_connection;
+mod command_buffer;
+mod connection_info;
+mod duplicate_login_policy;
 mod entities;
-mod error;
+mod event_validation;
+mod host_event;
+mod host_state;
+mod imported_keys;
+mod listen_server;
+mod load_shedding;
+mod local_user;
+mod master_server;
+mod missing_command_policy;
 mod naia_server;
+mod pause_policy;
+mod random;
+mod replication_profiler;
+mod request_rate_limiter;
 mod room;
+mod room_snapshot;
+mod server_endpoints;
 mod server_event;
+mod tick_stats;
 mod user;
 
+pub use bot_client::BotClient;
+pub use command_buffer::CommandBufferStats;
+pub use connection_info::{ConnectionInfo, Transport};
+pub use duplicate_login_policy::DuplicateLoginPolicy;
+pub use entities::entity_key::entity_key::EntityKey;
+pub use event_validation::EventValidation;
+pub use host_event::HostEvent;
+pub use imported_keys::ImportedKeys;
+pub use listen_server::ListenServer;
+pub use load_shedding::{LoadSheddingTier, LOAD_SHEDDING_HYSTERESIS};
+pub use missing_command_policy::MissingCommandPolicy;
 pub use naia_server::NaiaServer;
+pub use pause_policy::PausePolicy;
+pub use replication_profiler::{
+    EntityReplicationCost, ReplicationCost, ReplicationProfile, TypeReplicationCost,
+};
 pub use room::room_key::RoomKey;
 pub use server_event::ServerEvent;
+pub use tick_stats::{TickStats, TickTimings};
 pub use user::user_key::UserKey;
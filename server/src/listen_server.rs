@@ -0,0 +1,94 @@
+use std::{cell::RefCell, net::SocketAddr, rc::Rc};
+
+use naia_shared::{Config, Entity, EntityType, Event, EventType, Manifest, NaiaError};
+
+use super::{
+    entities::entity_key::entity_key::EntityKey, host_event::HostEvent, naia_server::NaiaServer,
+    server_event::ServerEvent, user::user_key::UserKey,
+};
+
+/// A Server which one of the players hosts, for co-op games, running the
+/// Server & the host player's Client in one process. Remote Clients connect
+/// to it as to any Server, while the host player is a local User of the
+/// Server: it shares the Server's Entities rather than having them
+/// replicated, & its Events are handed over in memory, without touching a
+/// socket. Every other method of the Server is reached through
+/// `ListenServer::get_server_mut()`
+pub struct ListenServer<T: EventType, U: EntityType> {
+    server: NaiaServer<T, U>,
+    host_user_key: UserKey,
+}
+
+impl<T: EventType, U: EntityType> ListenServer<T, U> {
+    /// Create a new ListenServer, given an address for remote Clients to
+    /// connect to, an Event/Entity manifest, and an optional Config. The host
+    /// player is connected right away, & its Connection event is the first
+    /// event received
+    pub async fn new(
+        address: SocketAddr,
+        manifest: Manifest<T, U>,
+        config: Option<Config>,
+    ) -> Self {
+        let mut server = NaiaServer::new(address, manifest, config).await;
+        let host_user_key = server.connect_local_user();
+        ListenServer {
+            server,
+            host_user_key,
+        }
+    }
+
+    /// Must be called regularly, maintains connection to and receives
+    /// messages from all remote Clients, & receives the Events the host
+    /// player sent, as `NaiaServer::receive()` does
+    pub async fn receive(&mut self) -> Result<ServerEvent<T>, NaiaError> {
+        return self.server.receive().await;
+    }
+
+    /// Sends all Entity updates & queued Events to the remote Clients. Should
+    /// be called after every Tick event. The host player is not sent
+    /// anything, as it shares the Server's Entities
+    pub async fn send_all_updates(&mut self) -> Result<(), NaiaError> {
+        return self.server.send_all_updates().await;
+    }
+
+    /// Gets the UserKey of the host player, by which the Server queues Events
+    /// for it, & adds it to Rooms, like any other User
+    pub fn get_host_user_key(&self) -> UserKey {
+        return self.host_user_key;
+    }
+
+    /// Sends an Event to the Server from the host player, which is received
+    /// as a ServerEvent::Event, as described in
+    /// `NaiaServer::send_local_event()`
+    pub fn host_send_event(&mut self, event: &impl Event<T>) -> Result<(), NaiaError> {
+        return self.server.send_local_event(&self.host_user_key, event);
+    }
+
+    /// Receives the next event for the host player, or None if there is no
+    /// new event, mirroring `NaiaClient::receive()`
+    pub fn host_receive(&mut self) -> Option<HostEvent<T>> {
+        return self.server.receive_local(&self.host_user_key);
+    }
+
+    /// Gets the keys of every Entity in scope for the host player
+    pub fn host_entity_keys(&self) -> Vec<EntityKey> {
+        return self.server.get_local_entity_keys(&self.host_user_key);
+    }
+
+    /// Given an EntityKey, gets a reference to an Entity in scope for the host
+    /// player. The Entity is the Server's own, so it is always up to date
+    pub fn host_get_entity(&self, key: &EntityKey) -> Option<&Rc<RefCell<dyn Entity<U>>>> {
+        return self.server.get_local_entity(&self.host_user_key, key);
+    }
+
+    /// Gets the Server which the host player is a local User of
+    pub fn get_server(&self) -> &NaiaServer<T, U> {
+        return &self.server;
+    }
+
+    /// Gets the Server which the host player is a local User of, to register
+    /// Entities, manage Rooms, or queue Events for any User
+    pub fn get_server_mut(&mut self) -> &mut NaiaServer<T, U> {
+        return &mut self.server;
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::{Duration, Instant};
+
+/// How far the load must fall below the threshold of the active tier of load
+/// shedding before the tier is left, so that a load hovering around the
+/// threshold doesn't switch tiers every tick
+pub const LOAD_SHEDDING_HYSTERESIS: f32 = 0.1;
+
+// The weight of each tick's load in the smoothed load
+const LOAD_SMOOTHING_FACTOR: f32 = 0.05;
+
+/// A tier of load shedding, which the Server enters once its load reaches the
+/// tier's threshold, reducing the work done for every Client alike
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoadSheddingTier {
+    /// The load at which the tier is entered, as the fraction of time, from 0
+    /// to 1, the Server spends busy rather than waiting on its socket
+    pub load: f32,
+    /// The Entity updates of each Client are collected only once in this many
+    /// ticks, with Clients spread evenly across the ticks. Mutations which
+    /// occur in between are merged into the next update
+    pub update_interval: u16,
+    /// Entities whose priority for a User, as evaluated by the closure
+    /// registered with `NaiaServer::on_entity_priority()`, is below this leave
+    /// the User's scope. With a priority which falls off with distance, this
+    /// shrinks the radius Entities are in scope within. 0 keeps every Entity
+    pub min_scope_priority: f32,
+}
+
+/// Measures the load of the Server, & chooses the tier of load shedding to
+/// apply to it
+#[derive(Debug)]
+pub struct LoadShedder {
+    tiers: Vec<LoadSheddingTier>,
+    tier: Option<usize>,
+    load: f32,
+    idle: Duration,
+    last_tick: Option<Instant>,
+}
+
+impl LoadShedder {
+    /// Creates a new LoadShedder, with no tiers, so that it only measures load
+    pub fn new() -> Self {
+        LoadShedder {
+            tiers: Vec::new(),
+            tier: None,
+            load: 0.0,
+            idle: Duration::from_secs(0),
+            last_tick: None,
+        }
+    }
+
+    /// Sets the tiers of load shedding, which are sorted by their load
+    /// thresholds. Returns whether the active tier was left as a result
+    pub fn set_tiers(&mut self, mut tiers: Vec<LoadSheddingTier>) -> bool {
+        tiers.sort_by(|a, b| {
+            a.load
+                .partial_cmp(&b.load)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.tiers = tiers;
+        return self.tier.take().is_some();
+    }
+
+    /// Records time spent waiting on the socket
+    pub fn record_idle(&mut self, idle: Duration) {
+        self.idle += idle;
+    }
+
+    /// Measures the load over the tick which has just elapsed, & moves to
+    /// another tier if the smoothed load calls for it. Returns the new tier,
+    /// or None once shedding has stopped, if the tier has changed
+    pub fn record_tick(&mut self) -> Option<Option<usize>> {
+        let now = Instant::now();
+        let last_tick = self.last_tick.replace(now);
+        let idle = std::mem::replace(&mut self.idle, Duration::from_secs(0));
+        let elapsed = match last_tick {
+            Some(last_tick) => now.duration_since(last_tick),
+            None => return None,
+        };
+        if elapsed == Duration::from_secs(0) {
+            return None;
+        }
+        let tick_load = (1.0 - idle.as_secs_f32() / elapsed.as_secs_f32()).max(0.0);
+        self.load += (tick_load - self.load) * LOAD_SMOOTHING_FACTOR;
+        return self.update_tier();
+    }
+
+    // Moves to another tier if the smoothed load calls for it, returning the
+    // new tier if it has changed
+    fn update_tier(&mut self) -> Option<Option<usize>> {
+        let previous_tier = self.tier;
+        while let Some(tier) = self.tier {
+            if self.load >= self.tiers[tier].load - LOAD_SHEDDING_HYSTERESIS {
+                break;
+            }
+            self.tier = tier.checked_sub(1);
+        }
+        loop {
+            let next_tier = self.tier.map_or(0, |tier| tier + 1);
+            match self.tiers.get(next_tier) {
+                Some(tier) if self.load >= tier.load => self.tier = Some(next_tier),
+                _ => break,
+            }
+        }
+        if self.tier == previous_tier {
+            return None;
+        }
+        return Some(self.tier);
+    }
+
+    /// Gets the smoothed load, from 0 to 1
+    pub fn get_load(&self) -> f32 {
+        return self.load;
+    }
+
+    /// Gets the index of the active tier, or None if not shedding load
+    pub fn get_tier_index(&self) -> Option<usize> {
+        return self.tier;
+    }
+
+    /// Gets the active tier, or None if not shedding load
+    pub fn get_tier(&self) -> Option<&LoadSheddingTier> {
+        return self.tier.and_then(|tier| self.tiers.get(tier));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LoadShedder, LoadSheddingTier};
+
+    fn tier(load: f32, update_interval: u16) -> LoadSheddingTier {
+        return LoadSheddingTier {
+            load,
+            update_interval,
+            min_scope_priority: 0.0,
+        };
+    }
+
+    fn shedder_at(shedder: &mut LoadShedder, load: f32) -> Option<Option<usize>> {
+        shedder.load = load;
+        return shedder.update_tier();
+    }
+
+    #[test]
+    fn enters_tiers_at_their_thresholds() {
+        let mut shedder = LoadShedder::new();
+        assert!(!shedder.set_tiers(vec![tier(0.9, 4), tier(0.7, 2)]));
+
+        assert!(shedder_at(&mut shedder, 0.5).is_none());
+        assert!(shedder.get_tier().is_none());
+
+        // tiers are sorted by their thresholds
+        assert!(shedder_at(&mut shedder, 0.75) == Some(Some(0)));
+        assert!(shedder.get_tier().unwrap().update_interval == 2);
+        assert!(shedder_at(&mut shedder, 0.95) == Some(Some(1)));
+        assert!(shedder.get_tier().unwrap().update_interval == 4);
+
+        // a load past several thresholds enters the highest at once
+        let mut shedder = LoadShedder::new();
+        shedder.set_tiers(vec![tier(0.7, 2), tier(0.9, 4)]);
+        assert!(shedder_at(&mut shedder, 0.95) == Some(Some(1)));
+    }
+
+    #[test]
+    fn leaves_tiers_with_hysteresis() {
+        let mut shedder = LoadShedder::new();
+        shedder.set_tiers(vec![tier(0.7, 2), tier(0.9, 4)]);
+        assert!(shedder_at(&mut shedder, 0.95) == Some(Some(1)));
+
+        // a load just below the threshold keeps the tier
+        assert!(shedder_at(&mut shedder, 0.85).is_none());
+        assert!(shedder.get_tier_index() == Some(1));
+
+        // a load below the threshold by more than the hysteresis leaves it
+        assert!(shedder_at(&mut shedder, 0.75) == Some(Some(0)));
+        assert!(shedder_at(&mut shedder, 0.65).is_none());
+        assert!(shedder_at(&mut shedder, 0.55) == Some(None));
+
+        // a load far below every threshold leaves every tier at once
+        assert!(shedder_at(&mut shedder, 0.95) == Some(Some(1)));
+        assert!(shedder_at(&mut shedder, 0.1) == Some(None));
+
+        // replacing the tiers leaves the active one
+        assert!(shedder_at(&mut shedder, 0.75) == Some(Some(0)));
+        assert!(shedder.set_tiers(vec![tier(0.8, 2)]));
+        assert!(shedder.get_tier().is_none());
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{hash_set::Iter, HashSet, VecDeque};
+
+use naia_shared::{sequence_greater_than, DespawnReason, EventId, EventType};
+
+use super::{
+    command_buffer::{CommandBuffer, CommandBufferStats},
+    entities::entity_key::entity_key::EntityKey,
+    host_event::HostEvent,
+    missing_command_policy::MissingCommandPolicy,
+};
+
+// A User in the same process as the Server, such as the host player of a
+// ListenServer or a BotClient, which has no connection. The Entities in its scope are shared
+// with the Server rather than replicated, & Events sent to it are handed over
+// as they are queued
+pub struct LocalUser<T: EventType> {
+    entities: HashSet<EntityKey>,
+    outstanding_events: VecDeque<HostEvent<T>>,
+    timed_events: Vec<(u16, T)>,
+    command_buffer: CommandBuffer<T>,
+    next_event_id: EventId,
+}
+
+impl<T: EventType> LocalUser<T> {
+    pub fn new() -> Self {
+        LocalUser {
+            entities: HashSet::new(),
+            outstanding_events: VecDeque::new(),
+            timed_events: Vec::new(),
+            command_buffer: CommandBuffer::new(),
+            next_event_id: 0,
+        }
+    }
+
+    pub fn has_entity(&self, key: &EntityKey) -> bool {
+        return self.entities.contains(key);
+    }
+
+    pub fn entities_iter(&self) -> Iter<'_, EntityKey> {
+        return self.entities.iter();
+    }
+
+    // Returns whether the Entity wasn't already in scope
+    pub fn add_entity(&mut self, key: &EntityKey) -> bool {
+        if !self.entities.insert(*key) {
+            return false;
+        }
+        self.outstanding_events
+            .push_back(HostEvent::CreateEntity(*key));
+        return true;
+    }
+
+    // Returns whether the Entity was in scope
+    pub fn remove_entity(&mut self, key: &EntityKey, reason: Option<DespawnReason>) -> bool {
+        if !self.entities.remove(key) {
+            return false;
+        }
+        self.outstanding_events
+            .push_back(HostEvent::DeleteEntity(*key, reason));
+        return true;
+    }
+
+    pub fn queue_event(&mut self, event: T) -> EventId {
+        let event_id = self.next_event_id;
+        self.next_event_id = self.next_event_id.wrapping_add(1);
+        self.outstanding_events.push_back(HostEvent::Event(event));
+        return event_id;
+    }
+
+    // Returns whether the Entity is in scope
+    pub fn queue_entity_event(&mut self, key: &EntityKey, event: T) -> bool {
+        if !self.entities.contains(key) {
+            return false;
+        }
+        self.outstanding_events
+            .push_back(HostEvent::EntityEvent(*key, event));
+        return true;
+    }
+
+    pub fn queue_timed_event(&mut self, tick: u16, event: T) {
+        self.timed_events.push((tick, event));
+    }
+
+    // Hands over the timed Events whose tick has been reached
+    pub fn set_current_tick(&mut self, current_tick: u16) {
+        let mut index = 0;
+        while index < self.timed_events.len() {
+            if sequence_greater_than(self.timed_events[index].0, current_tick) {
+                index += 1;
+                continue;
+            }
+            let (tick, event) = self.timed_events.remove(index);
+            self.outstanding_events
+                .push_back(HostEvent::TimedEvent(tick, event));
+        }
+    }
+
+    pub fn queue_command(&mut self, tick: u16, command: T, current_tick: u16) {
+        self.command_buffer.insert(tick, command, current_tick);
+    }
+
+    pub fn is_receiving_commands(&self) -> bool {
+        return self.command_buffer.is_active();
+    }
+
+    pub fn pop_command(&mut self, tick: u16, policy: MissingCommandPolicy) -> Option<T> {
+        return self.command_buffer.pop(tick, policy);
+    }
+
+    pub fn get_command_buffer_stats(&self) -> CommandBufferStats {
+        return self.command_buffer.get_stats();
+    }
+
+    pub fn pop_event(&mut self) -> Option<HostEvent<T>> {
+        return self.outstanding_events.pop_front();
+    }
+}
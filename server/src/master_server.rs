@@ -0,0 +1,184 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use ring::hmac;
+
+use naia_shared::{
+    utils::write_socket_address, PacketReader, RandomSource, ServerListPage, ServerStatus, Timer,
+    MAX_REGISTERED_STATUS_SIZE,
+};
+
+use super::random::generate_hmac_key;
+
+// The most Servers a master server lists at once
+const MAX_REGISTERED_SERVERS: usize = 65535;
+
+/// The list of Servers which have registered with this Server, when it acts as
+/// a master server for server browsers
+#[derive(Debug)]
+pub struct MasterServer {
+    challenge_key: hmac::Key,
+    registration_timeout: Duration,
+    servers: Vec<(SocketAddr, ServerStatus, Instant)>,
+}
+
+impl MasterServer {
+    /// Creates a new MasterServer, which forgets a Server once it hasn't
+    /// registered for the given duration, & signs its challenges with a key
+    /// drawn from the given RandomSource
+    pub fn new(registration_timeout: Duration, random_source: Option<&RandomSource>) -> Self {
+        MasterServer {
+            challenge_key: generate_hmac_key(random_source),
+            registration_timeout,
+            servers: Vec::new(),
+        }
+    }
+
+    /// Handles a registration from the given address. Returns the challenge to
+    /// send back if the registration did not carry it, in which case the
+    /// Server is not listed until it registers again with the challenge
+    pub fn register(&mut self, address: SocketAddr, data: &[u8]) -> Option<u64> {
+        let mut reader = PacketReader::new(data);
+        let challenge = reader.get_cursor().read_u64::<BigEndian>().ok()?;
+        let expected_challenge = self.get_challenge(&address);
+        if challenge != expected_challenge {
+            return Some(expected_challenge);
+        }
+        let status = ServerStatus::read(&mut reader)?;
+        if status.get_size()? > MAX_REGISTERED_STATUS_SIZE {
+            return None;
+        }
+
+        let now = Instant::now();
+        match self
+            .servers
+            .iter_mut()
+            .find(|(server_address, _, _)| *server_address == address)
+        {
+            Some(server) => {
+                server.1 = status;
+                server.2 = now;
+            }
+            None => {
+                if self.servers.len() < MAX_REGISTERED_SERVERS {
+                    self.servers.push((address, status, now));
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Handles a deregistration from the given address, which must carry the
+    /// same challenge as its registrations
+    pub fn deregister(&mut self, address: SocketAddr, data: &[u8]) {
+        let mut reader = PacketReader::new(data);
+        if let Ok(challenge) = reader.get_cursor().read_u64::<BigEndian>() {
+            if challenge == self.get_challenge(&address) {
+                self.servers
+                    .retain(|(server_address, _, _)| *server_address != address);
+            }
+        }
+    }
+
+    /// Gets the page of the list of registered Servers which starts at the
+    /// given index
+    pub fn get_page(&mut self, start: u16) -> ServerListPage {
+        self.remove_expired();
+        let list: Vec<(SocketAddr, ServerStatus)> = self
+            .servers
+            .iter()
+            .map(|(address, status, _)| (*address, status.clone()))
+            .collect();
+        return ServerListPage::build(&list, start);
+    }
+
+    /// Gets the address & status of every registered Server, in the order
+    /// they first registered
+    pub fn get_servers(&mut self) -> Vec<(SocketAddr, ServerStatus)> {
+        self.remove_expired();
+        return self
+            .servers
+            .iter()
+            .map(|(address, status, _)| (*address, status.clone()))
+            .collect();
+    }
+
+    fn remove_expired(&mut self) {
+        let registration_timeout = self.registration_timeout;
+        self.servers
+            .retain(|(_, _, registered_at)| registered_at.elapsed() < registration_timeout);
+    }
+
+    // Derived from the address, so that no state is kept for a Server until
+    // it has proven it can receive at the address
+    fn get_challenge(&self, address: &SocketAddr) -> u64 {
+        let mut address_bytes = Vec::new();
+        write_socket_address(address, &mut address_bytes);
+        let tag = hmac::sign(&self.challenge_key, &address_bytes);
+        let mut challenge_bytes = [0; 8];
+        challenge_bytes.copy_from_slice(&tag.as_ref()[..8]);
+        return u64::from_be_bytes(challenge_bytes);
+    }
+}
+
+/// The registration of this Server with a master server, which is renewed
+/// periodically
+#[derive(Debug)]
+pub struct MasterRegistration {
+    master_address: SocketAddr,
+    timer: Timer,
+    challenge: u64,
+}
+
+impl MasterRegistration {
+    /// Creates a new MasterRegistration, given the address of the master
+    /// server & how often to renew the registration
+    pub fn new(master_address: SocketAddr, interval: Duration) -> Self {
+        let mut timer = Timer::new(interval);
+        timer.ring_manual();
+        MasterRegistration {
+            master_address,
+            timer,
+            challenge: 0,
+        }
+    }
+
+    /// Gets the address of the master server
+    pub fn get_master_address(&self) -> SocketAddr {
+        return self.master_address;
+    }
+
+    /// Returns whether the registration should be renewed
+    pub fn should_register(&self) -> bool {
+        return self.timer.ringing();
+    }
+
+    /// Writes the data of a registration carrying the given status
+    pub fn write_registration(&mut self, status: &ServerStatus) -> Vec<u8> {
+        self.timer.reset();
+        let mut data = Vec::new();
+        data.write_u64::<BigEndian>(self.challenge).unwrap();
+        status.write(&mut data);
+        return data;
+    }
+
+    /// Writes the data of a deregistration
+    pub fn write_deregistration(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_u64::<BigEndian>(self.challenge).unwrap();
+        return data;
+    }
+
+    /// Records the challenge sent back by the master server, and registers
+    /// again at once
+    pub fn receive_challenge(&mut self, data: &[u8]) {
+        let mut reader = PacketReader::new(data);
+        if let Ok(challenge) = reader.get_cursor().read_u64::<BigEndian>() {
+            self.challenge = challenge;
+            self.timer.ring_manual();
+        }
+    }
+}
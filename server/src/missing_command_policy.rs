@@ -0,0 +1,12 @@
+/// Determines what the Server emits for a tick when the command of a Client
+/// for that tick has not arrived in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MissingCommandPolicy {
+    /// The Client's most recent command is emitted again as a
+    /// `ServerEvent::Command`, as a held input is more likely to continue than
+    /// to be released
+    RepeatLast,
+    /// A `ServerEvent::MissingCommand` is emitted, so that the application can
+    /// apply a neutral input
+    Neutral,
+}
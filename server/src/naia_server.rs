@@ -1,32 +1,59 @@
 use std::{
+    any::{Any, TypeId},
     cell::RefCell,
-    collections::{HashMap, VecDeque},
-    net::SocketAddr,
+    collections::{HashMap, HashSet, VecDeque},
+    net::{Ipv4Addr, SocketAddr},
     rc::Rc,
+    time::{Duration, Instant},
 };
 
-use byteorder::{BigEndian, ReadBytesExt};
-use log::info;
-use ring::{hmac, rand};
-use slotmap::DenseSlotMap;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use log::warn;
+use ring::hmac;
+use slotmap::{DenseSlotMap, KeyData, SparseSecondaryMap};
 
-use naia_server_socket::{
-    Config as SocketConfig, MessageSender, Packet, ServerSocket, ServerSocketTrait, SocketEvent,
-};
+use naia_server_socket::{Config as SocketConfig, Packet, SocketEvent};
 pub use naia_shared::{
-    Config, Connection, Entity, EntityMutator, EntityType, Event, EventType, Instant, ManagerType,
-    Manifest, PacketReader, PacketType, Timer, Timestamp,
+    to_canonical_address, AckLatencyHistogram, AddressFamily, BlobId, BlobMessage, Capabilities,
+    ClientId, ClientRosterChange, CommandStats, Config, Congestion, Connection, ConnectionQuality,
+    ConnectionStats, CustomSection, CustomSections, DespawnReason, DropReason, Entity,
+    EntityBuilder, EntityDebugDump, EntityMutator, EntityType, Event, EventBuilder, EventId,
+    EventMiddleware, EventQueueStats, EventType, LinkConditioner, LinkState, ManagerType, Manifest,
+    ManifestDescriptor, NaiaError, PacketFramingFactory, PacketReader, PacketType, PredictionKey,
+    PrefabId, RandomSource, RequestId, RpcMessage, RttStats, ServerStatus, TickAccumulator, Timer,
+    Timestamp, UnconnectedKind, UnconnectedMessage, MAX_REGISTERED_STATUS_SIZE,
+    MAX_SHUTDOWN_REASON_LENGTH,
 };
 
 use super::{
     client_connection::ClientConnection,
+    command_buffer::CommandBufferStats,
+    connection_info::{ConnectionInfo, Transport},
+    duplicate_login_policy::DuplicateLoginPolicy,
     entities::{
         entity_key::entity_key::EntityKey, mut_handler::MutHandler,
         server_entity_mutator::ServerEntityMutator,
     },
-    error::NaiaServerError,
+    event_validation::EventValidation,
+    host_event::HostEvent,
+    host_state::{
+        HostState, HostStateConnection, HostStateEntity, HostStateUser, MigratedUser,
+        LIVE_STATE_VERSION, WORLD_SAVE_VERSION,
+    },
+    imported_keys::ImportedKeys,
+    load_shedding::{LoadShedder, LoadSheddingTier},
+    local_user::LocalUser,
+    master_server::{MasterRegistration, MasterServer},
+    missing_command_policy::MissingCommandPolicy,
+    pause_policy::PausePolicy,
+    random::{fill_random, generate_hmac_key},
+    replication_profiler::{ReplicationProfile, ReplicationProfiler},
+    request_rate_limiter::RequestRateLimiter,
     room::{room_key::RoomKey, Room},
+    room_snapshot::RoomSnapshot,
+    server_endpoints::{EndpointSender, ServerEndpoints},
     server_event::ServerEvent,
+    tick_stats::{TickStats, TickTimings},
     user::{user_key::UserKey, User},
 };
 
@@ -35,20 +62,72 @@ use super::{
 /// those entities are in-scope
 pub struct NaiaServer<T: EventType, U: EntityType> {
     config: Config,
-    manifest: Manifest<T, U>,
-    socket: ServerSocket,
-    sender: MessageSender,
+    manifest: Rc<Manifest<T, U>>,
+    manifest_descriptor: ManifestDescriptor,
+    endpoints: ServerEndpoints,
+    sender: EndpointSender,
     global_entity_store: DenseSlotMap<EntityKey, Rc<RefCell<dyn Entity<U>>>>,
+    entity_prefab_ids: SparseSecondaryMap<EntityKey, PrefabId>,
+    entity_keyframe_intervals: SparseSecondaryMap<EntityKey, u16>,
     scope_entity_func: Option<Rc<Box<dyn Fn(&RoomKey, &UserKey, &EntityKey, U) -> bool>>>,
+    entity_priority_func: Option<Rc<Box<dyn Fn(&UserKey, &EntityKey, U) -> f32>>>,
     auth_func: Option<Rc<Box<dyn Fn(&UserKey, &T) -> bool>>>,
+    identity_func: Option<Rc<Box<dyn Fn(&UserKey, &T) -> Option<String>>>>,
+    duplicate_login_policy: DuplicateLoginPolicy,
+    user_identities: HashMap<UserKey, String>,
+    identity_users: HashMap<String, Vec<UserKey>>,
+    kicked_users: HashSet<UserKey>,
+    shutdown_timer: Option<Timer>,
+    shutdown_pending: bool,
+    shut_down: bool,
+    event_validation_func: Option<Rc<Box<dyn Fn(&UserKey, &T) -> EventValidation<T>>>>,
+    relay_filter_func: Option<Rc<Box<dyn Fn(&UserKey, &UserKey, &T) -> bool>>>,
+    incoming_event_middleware: EventMiddleware<UserKey, T>,
+    outgoing_event_middleware: EventMiddleware<UserKey, T>,
     mut_handler: Rc<RefCell<MutHandler>>,
     users: DenseSlotMap<UserKey, User>,
     rooms: DenseSlotMap<RoomKey, Room>,
+    room_snapshots: SparseSecondaryMap<RoomKey, RoomSnapshot>,
+    snapshot_cache_timer: Option<Timer>,
     address_to_user_key_map: HashMap<SocketAddr, UserKey>,
     client_connections: HashMap<UserKey, ClientConnection<T, U>>,
+    client_ids: HashMap<ClientId, UserKey>,
+    next_client_id: ClientId,
+    local_users: HashMap<UserKey, LocalUser<T>>,
     outstanding_disconnects: VecDeque<UserKey>,
+    outstanding_scope_events: VecDeque<ServerEvent<T>>,
+    outstanding_local_events: VecDeque<ServerEvent<T>>,
+    outstanding_tick_events: VecDeque<ServerEvent<T>>,
+    missing_command_policy: MissingCommandPolicy,
     heartbeat_timer: Timer,
     connection_hash_key: hmac::Key,
+    current_tick: u16,
+    last_sent_tick: u16,
+    tick_interval: Duration,
+    // paces the Server's ticks against those of the socket, which run at the
+    // fastest rate, Config::tick_interval
+    tick_accumulator: TickAccumulator,
+    load_shedder: LoadShedder,
+    // time spent in receive() since the last tick was sent, not waiting on the
+    // socket, & when receive() last began or resumed such work. It's recorded
+    // before every wait, so a call cancelled while waiting, such as by a
+    // timeout, is still counted
+    receive_busy: Duration,
+    receive_resumed: Option<Instant>,
+    last_tick_timings: TickTimings,
+    replication_profiler: Option<ReplicationProfiler>,
+    migration_tokens: HashMap<UserKey, u64>,
+    migrated_users: HashMap<u64, MigratedUser<T>>,
+    link_conditioner: Option<LinkConditioner>,
+    custom_sections: CustomSections<UserKey>,
+    packet_framing: Option<PacketFramingFactory>,
+    server_status: ServerStatus,
+    unconnected_rate_limiter: RequestRateLimiter,
+    outgoing_unconnected_responses: VecDeque<Packet>,
+    master_server: Option<MasterServer>,
+    master_registration: Option<MasterRegistration>,
+    random_source: Option<RandomSource>,
+    started_at: Instant,
 }
 
 impl<T: EventType, U: EntityType> NaiaServer<T, U> {
@@ -58,6 +137,17 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
         address: SocketAddr,
         manifest: Manifest<T, U>,
         config: Option<Config>,
+    ) -> Self {
+        return NaiaServer::new_with_shared_manifest(address, Rc::new(manifest), config).await;
+    }
+
+    /// Create a new Server, given an address to listen at, an Event/Entity
+    /// manifest shared with Clients in the same process, and an optional
+    /// Config. Used by NaiaPeer, which runs both roles at once
+    pub async fn new_with_shared_manifest(
+        address: SocketAddr,
+        manifest: Rc<Manifest<T, U>>,
+        config: Option<Config>,
     ) -> Self {
         let mut config = match config {
             Some(config) => config,
@@ -65,62 +155,215 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
         };
         config.heartbeat_interval /= 2;
 
+        let tick_interval = config.tick_interval;
         let mut socket_config = SocketConfig::default();
-        socket_config.tick_interval = config.tick_interval;
-        let mut server_socket = ServerSocket::listen(address, Some(socket_config)).await;
+        socket_config.tick_interval = tick_interval;
+        let listen_address = match config.dual_stack {
+            true => SocketAddr::new(AddressFamily::Ipv6.unspecified(), address.port()),
+            false => address,
+        };
+        let mut endpoints = ServerEndpoints::listen(listen_address, socket_config).await;
 
-        let sender = server_socket.get_sender();
+        let sender = EndpointSender::new(endpoints.get_sender());
         let clients_map = HashMap::new();
         let heartbeat_timer = Timer::new(config.heartbeat_interval);
 
-        let connection_hash_key =
-            hmac::Key::generate(hmac::HMAC_SHA256, &rand::SystemRandom::new()).unwrap();
+        let random_source = config.random_source.clone();
+        let connection_hash_key = generate_hmac_key(random_source.as_ref());
+
+        let manifest_descriptor = manifest.get_descriptor();
+        let link_conditioner = config.link_conditioner.as_ref().map(LinkConditioner::new);
+
+        let unconnected_rate_limiter = RequestRateLimiter::new(
+            config.unconnected_request_rate,
+            config.unconnected_request_burst,
+        );
+
+        let snapshot_cache_timer = config.snapshot_cache_interval.map(|interval| {
+            let mut timer = Timer::new(interval);
+            timer.ring_manual();
+            timer
+        });
 
         NaiaServer {
             manifest,
+            manifest_descriptor,
             global_entity_store: DenseSlotMap::with_key(),
+            entity_prefab_ids: SparseSecondaryMap::new(),
+            entity_keyframe_intervals: SparseSecondaryMap::new(),
             scope_entity_func: None,
+            entity_priority_func: None,
             auth_func: None,
+            identity_func: None,
+            duplicate_login_policy: DuplicateLoginPolicy::AllowBoth,
+            user_identities: HashMap::new(),
+            identity_users: HashMap::new(),
+            kicked_users: HashSet::new(),
+            shutdown_timer: None,
+            shutdown_pending: false,
+            shut_down: false,
+            event_validation_func: None,
+            relay_filter_func: None,
+            incoming_event_middleware: EventMiddleware::new(),
+            outgoing_event_middleware: EventMiddleware::new(),
             mut_handler: MutHandler::new(),
-            socket: server_socket,
+            endpoints,
             sender,
             config,
             users: DenseSlotMap::with_key(),
             rooms: DenseSlotMap::with_key(),
+            room_snapshots: SparseSecondaryMap::new(),
+            snapshot_cache_timer,
             connection_hash_key,
             client_connections: clients_map,
+            client_ids: HashMap::new(),
+            next_client_id: 0,
+            local_users: HashMap::new(),
             address_to_user_key_map: HashMap::new(),
             outstanding_disconnects: VecDeque::new(),
+            outstanding_scope_events: VecDeque::new(),
+            outstanding_local_events: VecDeque::new(),
+            outstanding_tick_events: VecDeque::new(),
+            missing_command_policy: MissingCommandPolicy::RepeatLast,
             heartbeat_timer,
+            current_tick: 0,
+            last_sent_tick: 0,
+            tick_interval,
+            tick_accumulator: TickAccumulator::new(tick_interval),
+            load_shedder: LoadShedder::new(),
+            receive_busy: Duration::from_secs(0),
+            receive_resumed: None,
+            last_tick_timings: TickTimings::default(),
+            replication_profiler: None,
+            migration_tokens: HashMap::new(),
+            migrated_users: HashMap::new(),
+            link_conditioner,
+            custom_sections: CustomSections::new(),
+            packet_framing: None,
+            server_status: ServerStatus::default(),
+            unconnected_rate_limiter,
+            outgoing_unconnected_responses: VecDeque::new(),
+            master_server: None,
+            master_registration: None,
+            random_source,
+            started_at: Instant::now(),
         }
     }
 
     /// Must be called regularly, maintains connection to and receives messages
     /// from all Clients
-    pub async fn receive(&mut self) -> Result<ServerEvent<T>, NaiaServerError> {
-        let mut output: Option<Result<ServerEvent<T>, NaiaServerError>> = None;
+    pub async fn receive(&mut self) -> Result<ServerEvent<T>, NaiaError> {
+        self.receive_resumed = Some(Instant::now());
+        let result = self.receive_event().await;
+        self.record_receive_busy();
+        return result;
+    }
+
+    fn record_receive_busy(&mut self) {
+        if let Some(resumed) = self.receive_resumed.take() {
+            self.receive_busy += resumed.elapsed();
+        }
+    }
+
+    async fn receive_event(&mut self) -> Result<ServerEvent<T>, NaiaError> {
+        let mut output: Option<Result<ServerEvent<T>, NaiaError>> = None;
         while output.is_none() {
             // heartbeats
             if self.heartbeat_timer.ringing() {
                 self.heartbeat_timer.reset();
 
+                let mut send_error: Option<NaiaError> = None;
+                let server_clock = self.started_at.elapsed().as_micros() as u64;
+
                 for (user_key, connection) in self.client_connections.iter_mut() {
                     if let Some(user) = self.users.get(*user_key) {
                         if connection.should_drop() {
-                            self.outstanding_disconnects.push_back(*user_key);
-                        } else if connection.should_send_heartbeat() {
+                            if !self.outstanding_disconnects.contains(user_key) {
+                                self.outstanding_disconnects.push_back(*user_key);
+                            }
+                        } else if connection.should_send_heartbeat()
+                            || connection.should_sync_clock()
+                        {
                             // Don't try to refactor this to self.internal_send, doesn't seem to
                             // work cause of iter_mut()
-                            let payload =
-                                connection.process_outgoing_header(PacketType::Heartbeat, &[]);
-                            self.sender
+                            // the Server's clock, in microseconds, precedes the application's
+                            // payload
+                            let mut heartbeat_payload = Vec::new();
+                            heartbeat_payload
+                                .write_u64::<BigEndian>(server_clock)
+                                .unwrap();
+                            heartbeat_payload.extend_from_slice(connection.get_heartbeat_payload());
+                            let payload = connection
+                                .process_outgoing_header(PacketType::Heartbeat, &heartbeat_payload);
+                            if let Err(error) = self
+                                .sender
                                 .send(Packet::new_raw(user.address, payload))
                                 .await
-                                .expect("send failed!");
+                            {
+                                send_error.get_or_insert(NaiaError::Wrapped(error));
+                            }
                             connection.mark_sent();
+                            connection.mark_clock_synced();
                         }
                     }
                 }
+                if let Some(error) = send_error {
+                    return Err(error);
+                }
+            }
+
+            // responses to unconnected messages, queued by the application
+            while let Some(response) = self.outgoing_unconnected_responses.pop_front() {
+                NaiaServer::<T, U>::internal_send_connectionless(
+                    &mut self.sender,
+                    PacketType::UnconnectedResponse,
+                    response,
+                )
+                .await?;
+            }
+
+            // renew the registration with a master server
+            let should_register = self
+                .master_registration
+                .as_ref()
+                .map_or(false, |registration| registration.should_register());
+            if should_register {
+                let status = self.get_current_status();
+                if let Some(registration) = &mut self.master_registration {
+                    let registration_data = registration.write_registration(&status);
+                    let master_address = registration.get_master_address();
+                    NaiaServer::<T, U>::internal_send_unconnected_request(
+                        &mut self.sender,
+                        master_address,
+                        UnconnectedKind::Register,
+                        registration_data,
+                    )
+                    .await?;
+                }
+            }
+
+            // the commands of a tick, followed by the tick itself
+            if let Some(tick_event) = self.outstanding_tick_events.pop_front() {
+                output = Some(Ok(tick_event));
+                continue;
+            }
+
+            // close, once every Client has been sent everything queued for it, or
+            // the grace period of the shutdown is over
+            if let Some(shutdown_timer) = &self.shutdown_timer {
+                let flushed = self
+                    .client_connections
+                    .values()
+                    .all(|connection| connection.is_flushed());
+                if flushed || shutdown_timer.ringing() {
+                    self.shutdown_timer = None;
+                    self.shut_down = true;
+                    self.shutdown_pending = true;
+                    let user_keys: Vec<UserKey> = self.users.keys().collect();
+                    for user_key in user_keys.iter() {
+                        self.kick_user(user_key);
+                    }
+                }
             }
 
             // timeouts
@@ -133,25 +376,205 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                 self.address_to_user_key_map.remove(&address);
                 let user_clone = self.users.get(user_key).unwrap().clone();
                 self.users.remove(user_key);
-                self.client_connections.remove(&user_key);
+                if let Some(mut connection) = self.client_connections.remove(&user_key) {
+                    // a kicked Client is told, so that it doesn't try to reconnect. If the
+                    // packet is lost, the Client times out instead
+                    if self.kicked_users.remove(&user_key) {
+                        let payload = connection.process_outgoing_header(PacketType::Kick, &[]);
+                        let _ = self.sender.send(Packet::new_raw(address, payload)).await;
+                    }
+                    let client_id = connection.get_client_id();
+                    self.client_ids.remove(&client_id);
+                    for (_, other_connection) in self.client_connections.iter_mut() {
+                        other_connection.queue_roster_change(ClientRosterChange::Left(client_id));
+                    }
+                }
+                self.local_users.remove(&user_key);
+                self.migration_tokens.remove(&user_key);
+                self.kicked_users.remove(&user_key);
+                if let Some(identity) = self.user_identities.remove(&user_key) {
+                    if let Some(user_keys) = self.identity_users.get_mut(&identity) {
+                        user_keys.retain(|other_key| *other_key != user_key);
+                        if user_keys.is_empty() {
+                            self.identity_users.remove(&identity);
+                        }
+                    }
+                }
                 output = Some(Ok(ServerEvent::Disconnection(user_key, user_clone)));
                 continue;
             }
 
-            for (address, connection) in self.client_connections.iter_mut() {
-                //receive events from anyone
-                if let Some(something) = connection.get_incoming_event() {
-                    output = Some(Ok(ServerEvent::Event(*address, something)));
-                    continue;
+            // every User has been disconnected, so the Server has shut down
+            if self.shutdown_pending {
+                self.shutdown_pending = false;
+                output = Some(Ok(ServerEvent::Shutdown));
+                continue;
+            }
+
+            // entity scope changes
+            if let Some(scope_event) = self.outstanding_scope_events.pop_front() {
+                output = Some(Ok(scope_event));
+                continue;
+            }
+
+            // connections of & events from local Users, & connections adopted
+            // from a previous Server process
+            if let Some(local_event) = self.outstanding_local_events.pop_front() {
+                output = Some(Ok(local_event));
+                continue;
+            }
+
+            // relay events between clients, which the application never sees
+            self.relay_events();
+
+            //receive events from anyone
+            let mut incoming_event: Option<ServerEvent<T>> = None;
+            for (user_key, connection) in self.client_connections.iter_mut() {
+                while let Some(something) = connection.get_incoming_event() {
+                    let something = match self.incoming_event_middleware.apply(user_key, something)
+                    {
+                        Some(something) => something,
+                        None => continue,
+                    };
+                    let validation = match &self.event_validation_func {
+                        Some(validation_func) => {
+                            (validation_func.as_ref().as_ref())(user_key, &something)
+                        }
+                        None => EventValidation::Accept,
+                    };
+                    match validation {
+                        EventValidation::Accept => {
+                            incoming_event = Some(ServerEvent::Event(*user_key, something));
+                            break;
+                        }
+                        EventValidation::Reject(rejection_event) => {
+                            if let Some(rejection_event) = rejection_event {
+                                connection.queue_boxed_event(rejection_event);
+                            }
+                        }
+                    }
+                }
+                if incoming_event.is_some() {
+                    break;
+                }
+                while let Some(rpc) = connection.get_incoming_rpc() {
+                    if let RpcMessage::Request(request_id, something) = rpc {
+                        let validation = match &self.event_validation_func {
+                            Some(validation_func) => {
+                                (validation_func.as_ref().as_ref())(user_key, &something)
+                            }
+                            None => EventValidation::Accept,
+                        };
+                        match validation {
+                            EventValidation::Accept => {
+                                incoming_event =
+                                    Some(ServerEvent::Request(*user_key, request_id, something));
+                                break;
+                            }
+                            EventValidation::Reject(rejection_event) => {
+                                connection.queue_rpc(RpcMessage::Error(request_id));
+                                if let Some(rejection_event) = rejection_event {
+                                    connection.queue_boxed_event(rejection_event);
+                                }
+                            }
+                        }
+                    }
+                }
+                if incoming_event.is_some() {
+                    break;
+                }
+            }
+            if let Some(event) = incoming_event {
+                output = Some(Ok(event));
+                continue;
+            }
+
+            //receive delivery receipts
+            let mut delivery_receipt: Option<ServerEvent<T>> = None;
+            for (user_key, connection) in self.client_connections.iter_mut() {
+                if let Some(event_id) = connection.get_delivered_event_id() {
+                    delivery_receipt = Some(ServerEvent::DeliveryConfirmed(*user_key, event_id));
+                    break;
+                }
+            }
+            if let Some(event) = delivery_receipt {
+                output = Some(Ok(event));
+                continue;
+            }
+
+            //receive expiration notices
+            let mut expiration: Option<ServerEvent<T>> = None;
+            for (user_key, connection) in self.client_connections.iter_mut() {
+                if let Some(event_id) = connection.get_expired_event_id() {
+                    expiration = Some(ServerEvent::EventExpired(*user_key, event_id));
+                    break;
+                }
+            }
+            if let Some(event) = expiration {
+                output = Some(Ok(event));
+                continue;
+            }
+
+            //receive raw payloads
+            let mut raw_payload: Option<ServerEvent<T>> = None;
+            for (user_key, connection) in self.client_connections.iter_mut() {
+                if let Some(payload) = connection.get_incoming_raw_payload() {
+                    raw_payload = Some(ServerEvent::RawPayload(*user_key, payload));
+                    break;
+                }
+            }
+            if let Some(event) = raw_payload {
+                output = Some(Ok(event));
+                continue;
+            }
+
+            //receive blob transfer notifications
+            let mut blob_event: Option<ServerEvent<T>> = None;
+            for (user_key, connection) in self.client_connections.iter_mut() {
+                if let Some(message) = connection.get_blob_message() {
+                    blob_event = Some(match message {
+                        BlobMessage::Progress(blob_id, received_chunks, total_chunks) => {
+                            ServerEvent::BlobProgress(
+                                *user_key,
+                                blob_id,
+                                received_chunks,
+                                total_chunks,
+                            )
+                        }
+                        BlobMessage::Received(blob_id, data) => {
+                            ServerEvent::BlobReceived(*user_key, blob_id, data)
+                        }
+                        BlobMessage::Delivered(blob_id) => {
+                            ServerEvent::BlobDelivered(*user_key, blob_id)
+                        }
+                    });
+                    break;
                 }
             }
+            if let Some(event) = blob_event {
+                output = Some(Ok(event));
+                continue;
+            }
 
-            //receive socket events
-            match self.socket.receive().await {
-                Ok(event) => {
+            //receive socket events, measuring the time spent waiting on them
+            self.record_receive_busy();
+            let wait_start = Instant::now();
+            let socket_result = self.endpoints.receive().await;
+            let wait_duration = wait_start.elapsed();
+            self.load_shedder.record_idle(wait_duration);
+            self.receive_resumed = Some(Instant::now());
+            match socket_result {
+                Ok((endpoint, event)) => {
                     match event {
                         SocketEvent::Packet(packet) => {
+                            if let Some(link_conditioner) = &mut self.link_conditioner {
+                                if link_conditioner.should_drop_incoming() {
+                                    continue;
+                                }
+                            }
+
                             let address = packet.address();
+                            self.sender.record_route(address, endpoint);
                             if let Some(user_key) = self.address_to_user_key_map.get(&address) {
                                 match self.client_connections.get_mut(&user_key) {
                                     Some(connection) => {
@@ -161,13 +584,40 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                 }
                             }
 
-                            let packet_type = PacketType::get_from_packet(packet.payload());
+                            // a packet too short to hold its header is dropped, &
+                            // counted against the connection, if there is one
+                            let packet_type = match PacketType::get_from_packet(packet.payload()) {
+                                Some(packet_type) => packet_type,
+                                None => {
+                                    if let Some(user_key) =
+                                        self.address_to_user_key_map.get(&address)
+                                    {
+                                        if let Some(connection) =
+                                            self.client_connections.get_mut(user_key)
+                                        {
+                                            connection.record_drop(DropReason::OversizedLength);
+                                            if let Some(reason) =
+                                                connection.take_protocol_violation()
+                                            {
+                                                self.outstanding_disconnects.push_back(*user_key);
+                                                output = Some(Ok(ServerEvent::ProtocolViolation(
+                                                    *user_key, reason,
+                                                )));
+                                            }
+                                        }
+                                    }
+                                    continue;
+                                }
+                            };
 
                             match packet_type {
                                 PacketType::ClientChallengeRequest => {
-                                    let payload = naia_shared::utils::read_headerless_payload(
+                                    let payload = match naia_shared::utils::read_headerless_payload(
                                         packet.payload(),
-                                    );
+                                    ) {
+                                        Some(payload) => payload,
+                                        None => continue,
+                                    };
                                     let mut reader = PacketReader::new(&payload);
                                     let timestamp = Timestamp::read(&mut reader);
 
@@ -188,14 +638,17 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                         PacketType::ServerChallengeResponse,
                                         Packet::new(address, payload_bytes),
                                     )
-                                    .await;
+                                    .await?;
 
                                     continue;
                                 }
                                 PacketType::ClientConnectRequest => {
-                                    let payload = naia_shared::utils::read_headerless_payload(
+                                    let payload = match naia_shared::utils::read_headerless_payload(
                                         packet.payload(),
-                                    );
+                                    ) {
+                                        Some(payload) => payload,
+                                        None => continue,
+                                    };
                                     let mut reader = PacketReader::new(&payload);
                                     let timestamp = Timestamp::read(&mut reader);
 
@@ -213,7 +666,7 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                                     &mut connection,
                                                     &mut self.sender,
                                                 )
-                                                .await;
+                                                .await?;
                                                 continue;
                                             } else {
                                                 self.outstanding_disconnects.push_back(*user_key);
@@ -228,10 +681,11 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                         // server instance
                                         let mut timestamp_bytes: Vec<u8> = Vec::new();
                                         timestamp.write(&mut timestamp_bytes);
-                                        let mut digest_bytes: Vec<u8> = Vec::new();
-                                        for _ in 0..32 {
-                                            digest_bytes.push(reader.read_u8());
-                                        }
+                                        let digest_bytes: Vec<u8> =
+                                            match (0..32).map(|_| reader.read_u8()).collect() {
+                                                Some(digest_bytes) => digest_bytes,
+                                                None => continue,
+                                            };
                                         if !hmac::verify(
                                             &self.connection_hash_key,
                                             &timestamp_bytes,
@@ -242,57 +696,224 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                             continue;
                                         }
 
-                                        let user = User::new(address, timestamp);
+                                        // Refuse new Clients once shutting down
+                                        if self.is_shutting_down() {
+                                            NaiaServer::<T, U>::internal_send_connectionless(
+                                                &mut self.sender,
+                                                PacketType::ServerRejectResponse,
+                                                Packet::new(address, timestamp_bytes),
+                                            )
+                                            .await?;
+                                            continue;
+                                        }
+
+                                        // Verify that the Client's Manifest matches ours
+                                        let client_descriptor =
+                                            match ManifestDescriptor::read(&mut reader) {
+                                                Some(descriptor) => descriptor,
+                                                None => continue,
+                                            };
+                                        if let Some(report) =
+                                            self.manifest_descriptor.compare(&client_descriptor)
+                                        {
+                                            warn!(
+                                                "rejected connection from {}. {}",
+                                                address, report
+                                            );
+                                            // the timestamp of the request precedes the
+                                            // descriptor, so that it can't be forged by
+                                            // another host
+                                            let mut payload_bytes = timestamp_bytes.clone();
+                                            self.manifest_descriptor.write(&mut payload_bytes);
+                                            NaiaServer::<T, U>::internal_send_connectionless(
+                                                &mut self.sender,
+                                                PacketType::ManifestMismatch,
+                                                Packet::new(address, payload_bytes),
+                                            )
+                                            .await?;
+                                            continue;
+                                        }
+
+                                        // Clients migrating from a previous host present
+                                        // the token they were given by it
+                                        let migration_token = match reader.read_u8() {
+                                            Some(0) => None,
+                                            None => continue,
+                                            Some(_) => {
+                                                match reader.get_cursor().read_u64::<BigEndian>() {
+                                                    Ok(token) => Some(token),
+                                                    Err(_) => continue,
+                                                }
+                                            }
+                                        };
+
+                                        // Optional features are used if the Client offers
+                                        // them & this Server enables them too
+                                        let capabilities = match Capabilities::read(&mut reader) {
+                                            Some(requested) => Capabilities::offered(&self.config)
+                                                .intersection(requested),
+                                            None => continue,
+                                        };
+
+                                        let user = User::new(address, endpoint, timestamp);
                                         let user_key = self.users.insert(user);
 
                                         // Call auth function if there is one
+                                        let mut accepted_auth_event: Option<T> = None;
                                         if let Some(auth_func) = &self.auth_func {
                                             let buffer = reader.get_buffer();
                                             let cursor = reader.get_cursor();
-                                            let naia_id_result = cursor.read_u16::<BigEndian>();
-                                            if naia_id_result.is_err() {
+                                            let authorized = match cursor.read_u16::<BigEndian>() {
+                                                Ok(naia_id) => {
+                                                    let event_payload = buffer
+                                                        [cursor.position() as usize..buffer.len()]
+                                                        .to_vec()
+                                                        .into_boxed_slice();
+                                                    match self
+                                                        .manifest
+                                                        .create_auth_event(naia_id, &event_payload)
+                                                    {
+                                                        Some(auth_event) => {
+                                                            let authorized =
+                                                                (auth_func.as_ref().as_ref())(
+                                                                    &user_key,
+                                                                    &auth_event,
+                                                                );
+                                                            accepted_auth_event = Some(auth_event);
+                                                            authorized
+                                                        }
+                                                        None => false,
+                                                    }
+                                                }
+                                                Err(_) => false,
+                                            };
+                                            if !authorized {
                                                 self.users.remove(user_key);
+                                                // tell the Client, with the timestamp it sent, so
+                                                // that it stops trying to connect
+                                                NaiaServer::<T, U>::internal_send_connectionless(
+                                                    &mut self.sender,
+                                                    PacketType::ServerRejectResponse,
+                                                    Packet::new(address, timestamp_bytes),
+                                                )
+                                                .await?;
                                                 continue;
                                             }
-                                            let naia_id: u16 = naia_id_result.unwrap().into();
-                                            let event_payload = buffer
-                                                [cursor.position() as usize..buffer.len()]
-                                                .to_vec()
-                                                .into_boxed_slice();
-
-                                            match self
-                                                .manifest
-                                                .create_event(naia_id, &event_payload)
-                                            {
-                                                Some(new_entity) => {
-                                                    if !(auth_func.as_ref().as_ref())(
-                                                        &user_key,
-                                                        &new_entity,
-                                                    ) {
+                                        }
+
+                                        // Apply the duplicate login policy, if the accepted
+                                        // auth Event identifies a Client already connected
+                                        let identity =
+                                            match (&self.identity_func, &accepted_auth_event) {
+                                                (Some(identity_func), Some(auth_event)) => {
+                                                    (identity_func.as_ref().as_ref())(
+                                                        &user_key, auth_event,
+                                                    )
+                                                }
+                                                _ => None,
+                                            };
+                                        if let Some(identity) = identity {
+                                            let connected_users = self
+                                                .identity_users
+                                                .get(&identity)
+                                                .cloned()
+                                                .unwrap_or_default();
+                                            if !connected_users.is_empty() {
+                                                match self.duplicate_login_policy {
+                                                    DuplicateLoginPolicy::RejectNew => {
                                                         self.users.remove(user_key);
+                                                        NaiaServer::<T, U>::internal_send_connectionless(
+                                                            &mut self.sender,
+                                                            PacketType::ServerRejectResponse,
+                                                            Packet::new(address, timestamp_bytes),
+                                                        )
+                                                        .await?;
                                                         continue;
                                                     }
-                                                }
-                                                _ => {
-                                                    self.users.remove(user_key);
-                                                    continue;
+                                                    DuplicateLoginPolicy::KickOld => {
+                                                        for old_user_key in connected_users {
+                                                            self.kick_user(&old_user_key);
+                                                        }
+                                                    }
+                                                    DuplicateLoginPolicy::AllowBoth => {}
                                                 }
                                             }
+                                            self.identity_users
+                                                .entry(identity.clone())
+                                                .or_insert_with(Vec::new)
+                                                .push(user_key);
+                                            self.user_identities.insert(user_key, identity);
                                         }
 
                                         self.address_to_user_key_map.insert(address, user_key);
 
                                         // Success! Create new connection
+                                        let client_id = self.next_unused_client_id();
+                                        self.client_ids.insert(client_id, user_key);
                                         let mut new_connection = ClientConnection::new(
                                             address,
                                             Some(&self.mut_handler),
                                             &self.config,
+                                            client_id,
                                         );
-                                        NaiaServer::<T, U>::send_connect_accept_message(
+                                        if let Some(migrated_user) = migration_token
+                                            .and_then(|token| self.migrated_users.remove(&token))
+                                        {
+                                            new_connection.set_migrated();
+                                            new_connection.restore_local_entity_keys(
+                                                migrated_user.local_keys,
+                                            );
+                                            for room_key in migrated_user.room_keys.iter() {
+                                                self.room_add_user(room_key, &user_key);
+                                            }
+                                            new_connection.restore_undelivered_events(
+                                                migrated_user.next_event_id,
+                                                migrated_user
+                                                    .events
+                                                    .iter()
+                                                    .map(|(event_id, event)| {
+                                                        (*event_id, event.get_boxed_event())
+                                                    })
+                                                    .collect(),
+                                            );
+                                        }
+                                        if let Some(auth_event) = accepted_auth_event {
+                                            new_connection.set_auth_event(auth_event);
+                                        }
+                                        new_connection.set_current_tick(self.current_tick);
+                                        if self.tick_interval != self.config.tick_interval {
+                                            new_connection.queue_tick_rate_change(
+                                                self.current_tick,
+                                                self.tick_interval,
+                                            );
+                                        }
+                                        new_connection.set_capabilities(capabilities);
+                                        new_connection
+                                            .set_packet_framing(self.packet_framing.clone());
+                                        // a Client which doesn't receive the response resends
+                                        // its request, which is answered again
+                                        let _ = NaiaServer::<T, U>::send_connect_accept_message(
                                             &mut new_connection,
                                             &mut self.sender,
                                         )
                                         .await;
+                                        // tell every Client of the new one, and the new Client
+                                        // of every Client, itself included
+                                        for (_, other_connection) in
+                                            self.client_connections.iter_mut()
+                                        {
+                                            other_connection.queue_roster_change(
+                                                ClientRosterChange::Joined(client_id),
+                                            );
+                                            new_connection.queue_roster_change(
+                                                ClientRosterChange::Joined(
+                                                    other_connection.get_client_id(),
+                                                ),
+                                            );
+                                        }
+                                        new_connection.queue_roster_change(
+                                            ClientRosterChange::Joined(client_id),
+                                        );
                                         self.client_connections.insert(user_key, new_connection);
                                         output = Some(Ok(ServerEvent::Connection(user_key)));
                                         continue;
@@ -304,12 +925,34 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                     {
                                         match self.client_connections.get_mut(user_key) {
                                             Some(connection) => {
+                                                if connection.has_protocol_violation() {
+                                                    continue;
+                                                }
+                                                connection.set_loading(false);
                                                 let mut payload = connection
-                                                    .process_incoming_header(packet.payload());
+                                                    .process_incoming_header(
+                                                        packet.payload(),
+                                                        self.current_tick,
+                                                    );
                                                 connection.process_incoming_data(
                                                     &self.manifest,
+                                                    &mut self.custom_sections,
+                                                    user_key,
                                                     &mut payload,
                                                 );
+                                                if let Some(reason) =
+                                                    connection.take_protocol_violation()
+                                                {
+                                                    self.outstanding_disconnects
+                                                        .push_back(*user_key);
+                                                    output =
+                                                        Some(Ok(ServerEvent::ProtocolViolation(
+                                                            *user_key, reason,
+                                                        )));
+                                                    continue;
+                                                }
+                                                connection
+                                                    .buffer_incoming_commands(self.current_tick);
                                                 continue;
                                             }
                                             None => {
@@ -327,10 +970,72 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                     {
                                         match self.client_connections.get_mut(user_key) {
                                             Some(connection) => {
+                                                if connection.has_protocol_violation() {
+                                                    continue;
+                                                }
+                                                connection.set_loading(false);
                                                 // Still need to do this so that proper notify
                                                 // events fire based on the heartbeat header
-                                                connection
-                                                    .process_incoming_header(packet.payload());
+                                                let payload = connection.process_incoming_header(
+                                                    packet.payload(),
+                                                    self.current_tick,
+                                                );
+                                                if let Some(reason) =
+                                                    connection.take_protocol_violation()
+                                                {
+                                                    self.outstanding_disconnects
+                                                        .push_back(*user_key);
+                                                    output =
+                                                        Some(Ok(ServerEvent::ProtocolViolation(
+                                                            *user_key, reason,
+                                                        )));
+                                                    continue;
+                                                }
+                                                if !payload.is_empty() {
+                                                    output =
+                                                        Some(Ok(ServerEvent::HeartbeatPayload(
+                                                            *user_key, payload,
+                                                        )));
+                                                }
+                                                continue;
+                                            }
+                                            None => {
+                                                warn!("received heartbeat from unauthenticated client: {}", address);
+                                            }
+                                        }
+                                    }
+                                }
+                                PacketType::LoadingHeartbeat => {
+                                    if let Some(user_key) =
+                                        self.address_to_user_key_map.get(&address)
+                                    {
+                                        match self.client_connections.get_mut(user_key) {
+                                            Some(connection) => {
+                                                if connection.has_protocol_violation() {
+                                                    continue;
+                                                }
+                                                connection.set_loading(true);
+                                                let payload = connection.process_incoming_header(
+                                                    packet.payload(),
+                                                    self.current_tick,
+                                                );
+                                                if let Some(reason) =
+                                                    connection.take_protocol_violation()
+                                                {
+                                                    self.outstanding_disconnects
+                                                        .push_back(*user_key);
+                                                    output =
+                                                        Some(Ok(ServerEvent::ProtocolViolation(
+                                                            *user_key, reason,
+                                                        )));
+                                                    continue;
+                                                }
+                                                if !payload.is_empty() {
+                                                    output =
+                                                        Some(Ok(ServerEvent::HeartbeatPayload(
+                                                            *user_key, payload,
+                                                        )));
+                                                }
                                                 continue;
                                             }
                                             None => {
@@ -339,11 +1044,197 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                         }
                                     }
                                 }
+                                PacketType::UnconnectedRequest => {
+                                    if !self.unconnected_rate_limiter.allow(address.ip()) {
+                                        continue;
+                                    }
+                                    let payload = match naia_shared::utils::read_headerless_payload(
+                                        packet.payload(),
+                                    ) {
+                                        Some(payload) => payload,
+                                        None => continue,
+                                    };
+                                    let mut reader = PacketReader::new(&payload);
+                                    let request = match UnconnectedMessage::read(&mut reader) {
+                                        Some(request) => request,
+                                        None => continue,
+                                    };
+                                    let response_data = match request.kind {
+                                        UnconnectedKind::Ping => Vec::new(),
+                                        UnconnectedKind::Reflect => {
+                                            let mut address_bytes = Vec::new();
+                                            naia_shared::utils::write_socket_address(
+                                                &to_canonical_address(address),
+                                                &mut address_bytes,
+                                            );
+                                            address_bytes
+                                        }
+                                        UnconnectedKind::Status => {
+                                            let mut status_bytes = Vec::new();
+                                            self.get_current_status().write(&mut status_bytes);
+                                            status_bytes
+                                        }
+                                        UnconnectedKind::Message => {
+                                            output = Some(Ok(ServerEvent::UnconnectedMessage(
+                                                address,
+                                                request.token,
+                                                request.data,
+                                            )));
+                                            continue;
+                                        }
+                                        UnconnectedKind::Register => {
+                                            let master_server = match &mut self.master_server {
+                                                Some(master_server) => master_server,
+                                                None => continue,
+                                            };
+                                            match master_server.register(
+                                                to_canonical_address(address),
+                                                &request.data,
+                                            ) {
+                                                Some(challenge) => challenge.to_be_bytes().to_vec(),
+                                                None => continue,
+                                            }
+                                        }
+                                        UnconnectedKind::Deregister => {
+                                            if let Some(master_server) = &mut self.master_server {
+                                                master_server.deregister(
+                                                    to_canonical_address(address),
+                                                    &request.data,
+                                                );
+                                            }
+                                            continue;
+                                        }
+                                        UnconnectedKind::List => {
+                                            let master_server = match &mut self.master_server {
+                                                Some(master_server) => master_server,
+                                                None => continue,
+                                            };
+                                            let start = match request.data.get(0..2) {
+                                                Some(start_bytes) => u16::from_be_bytes([
+                                                    start_bytes[0],
+                                                    start_bytes[1],
+                                                ]),
+                                                None => continue,
+                                            };
+                                            let mut page_bytes = Vec::new();
+                                            master_server.get_page(start).write(&mut page_bytes);
+                                            page_bytes
+                                        }
+                                    };
+                                    let mut response_bytes = Vec::new();
+                                    UnconnectedMessage {
+                                        kind: request.kind,
+                                        token: request.token,
+                                        data: response_data.into_boxed_slice(),
+                                    }
+                                    .write(&mut response_bytes);
+
+                                    // a response is never larger than its request, so that
+                                    // requests with a forged address can't be used to
+                                    // amplify traffic toward another host
+                                    if response_bytes.len() > payload.len() {
+                                        continue;
+                                    }
+                                    NaiaServer::<T, U>::internal_send_connectionless(
+                                        &mut self.sender,
+                                        PacketType::UnconnectedResponse,
+                                        Packet::new(address, response_bytes),
+                                    )
+                                    .await?;
+                                    continue;
+                                }
+                                PacketType::UnconnectedResponse => {
+                                    // the challenge of a master server this Server is
+                                    // registering with
+                                    if let Some(registration) = &mut self.master_registration {
+                                        if address != registration.get_master_address() {
+                                            continue;
+                                        }
+                                        let payload =
+                                            match naia_shared::utils::read_headerless_payload(
+                                                packet.payload(),
+                                            ) {
+                                                Some(payload) => payload,
+                                                None => continue,
+                                            };
+                                        let mut reader = PacketReader::new(&payload);
+                                        if let Some(response) =
+                                            UnconnectedMessage::read(&mut reader)
+                                        {
+                                            if response.kind == UnconnectedKind::Register {
+                                                registration.receive_challenge(&response.data);
+                                            }
+                                        }
+                                    }
+                                    continue;
+                                }
                                 _ => {}
                             }
                         }
                         SocketEvent::Tick => {
-                            output = Some(Ok(ServerEvent::Tick));
+                            if self.tick_accumulator.frame_begin(self.config.tick_interval) == 0 {
+                                continue;
+                            }
+                            self.current_tick = self.current_tick.wrapping_add(1);
+                            if let Some(tier) = self.load_shedder.record_tick() {
+                                self.outstanding_tick_events
+                                    .push_back(ServerEvent::LoadSheddingChanged(tier));
+                            }
+                            if let Some(profiler) = self.replication_profiler.as_mut() {
+                                profiler.update();
+                            }
+                            self.sender
+                                .remove_stale_routes(self.config.disconnection_timeout_duration);
+                            for (user_key, local_user) in self.local_users.iter_mut() {
+                                local_user.set_current_tick(self.current_tick);
+                                if local_user.is_receiving_commands() {
+                                    let tick = self.current_tick;
+                                    let command_event = match local_user
+                                        .pop_command(tick, self.missing_command_policy)
+                                    {
+                                        Some(command) => {
+                                            ServerEvent::Command(*user_key, tick, command)
+                                        }
+                                        None => ServerEvent::MissingCommand(*user_key, tick),
+                                    };
+                                    self.outstanding_tick_events.push_back(command_event);
+                                }
+                            }
+                            for (user_key, connection) in self.client_connections.iter_mut() {
+                                connection.set_current_tick(self.current_tick);
+                                if let Some(quality) = connection.update_quality() {
+                                    self.outstanding_tick_events.push_back(
+                                        ServerEvent::ConnectionQualityChanged(*user_key, quality),
+                                    );
+                                }
+                                match connection.update_backpressure() {
+                                    Some(Congestion::Congested) => self
+                                        .outstanding_tick_events
+                                        .push_back(ServerEvent::Congested(*user_key)),
+                                    Some(Congestion::Relieved) => self
+                                        .outstanding_tick_events
+                                        .push_back(ServerEvent::Relieved(*user_key)),
+                                    None => {}
+                                }
+                                if let Some(link_state) = connection.update_link_state() {
+                                    self.outstanding_tick_events.push_back(
+                                        ServerEvent::LinkStateChanged(*user_key, link_state),
+                                    );
+                                }
+                                if connection.is_receiving_commands() {
+                                    let tick = self.current_tick;
+                                    let command_event = match connection
+                                        .pop_command(tick, self.missing_command_policy)
+                                    {
+                                        Some(command) => {
+                                            ServerEvent::Command(*user_key, tick, command)
+                                        }
+                                        None => ServerEvent::MissingCommand(*user_key, tick),
+                                    };
+                                    self.outstanding_tick_events.push_back(command_event);
+                                }
+                            }
+                            self.outstanding_tick_events.push_back(ServerEvent::Tick);
                             continue;
                         }
                     }
@@ -363,7 +1254,7 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                     //                        }
                     //                    }
 
-                    output = Some(Err(NaiaServerError::Wrapped(Box::new(error))));
+                    output = Some(Err(NaiaError::Wrapped(Box::new(error))));
                     continue;
                 }
             }
@@ -373,66 +1264,812 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
 
     async fn send_connect_accept_message(
         connection: &mut ClientConnection<T, U>,
-        sender: &mut MessageSender,
-    ) {
-        let payload = connection.process_outgoing_header(PacketType::ServerConnectResponse, &[]);
-        match sender
+        sender: &mut EndpointSender,
+    ) -> Result<(), NaiaError> {
+        // tell the Client which of the features it offered were agreed on, and
+        // the public id it has been assigned
+        let mut accept_payload = Vec::new();
+        connection.get_capabilities().write(&mut accept_payload);
+        accept_payload
+            .write_u16::<BigEndian>(connection.get_client_id())
+            .unwrap();
+        let payload =
+            connection.process_outgoing_header(PacketType::ServerConnectResponse, &accept_payload);
+        if let Err(error) = sender
             .send(Packet::new_raw(connection.get_address(), payload))
             .await
         {
-            Ok(_) => {}
-            Err(err) => {
-                info!("send error! {}", err);
-            }
+            return Err(NaiaError::Wrapped(error));
         }
         connection.mark_sent();
+        return Ok(());
     }
 
     /// Queues up an Event to be sent to the Client associated with a given
-    /// UserKey
-    pub fn queue_event(&mut self, user_key: &UserKey, event: &impl Event<T>) {
-        if let Some(connection) = self.client_connections.get_mut(user_key) {
-            connection.queue_event(event);
-        }
+    /// UserKey. Returns an EventId, which will be emitted in a
+    /// `ServerEvent::DeliveryConfirmed` once the Event has been delivered, or
+    /// an error if there is no such Client, or the Event can't be sent, as
+    /// described in `Manifest::validate_event()`. An Event which isn't
+    /// guaranteed is dropped if the connection is congested &
+    /// `BackpressureThresholds::drop_unguaranteed_events` is set
+    pub fn queue_event(
+        &mut self,
+        user_key: &UserKey,
+        event: &impl Event<T>,
+    ) -> Result<EventId, NaiaError> {
+        return self.internal_queue_event(user_key, event, None);
     }
 
-    /// Sends all Entity/Event messages to all Clients. If you don't call this
-    /// method, the Server will never communicate with it's connected
-    /// Clients
-    pub async fn send_all_updates(&mut self) {
-        // update entity scopes
-        self.update_entity_scopes();
+    /// Queues up an Event to be sent to the Client associated with a given
+    /// UserKey, like `queue_event()`, but drops it if it can't be sent within
+    /// the given time-to-live, so that a stale Event (such as an old position)
+    /// isn't delivered uselessly late. A guaranteed Event whose packet is lost
+    /// is not resent after its time-to-live either. The EventId of a dropped
+    /// Event is emitted in a `ServerEvent::EventExpired`
+    pub fn queue_event_with_ttl(
+        &mut self,
+        user_key: &UserKey,
+        event: &impl Event<T>,
+        ttl: Duration,
+    ) -> Result<EventId, NaiaError> {
+        return self.internal_queue_event(user_key, event, Some(ttl));
+    }
 
-        // loop through all connections, send packet
-        for (user_key, connection) in self.client_connections.iter_mut() {
-            if let Some(user) = self.users.get(*user_key) {
-                connection.collect_entity_updates();
-                while let Some(payload) = connection.get_outgoing_packet(&self.manifest) {
-                    match self
-                        .sender
-                        .send(Packet::new_raw(user.address, payload))
-                        .await
-                    {
-                        Ok(_) => {}
-                        Err(err) => {
-                            info!("send error! {}", err);
-                        }
-                    }
-                    connection.mark_sent();
+    fn internal_queue_event(
+        &mut self,
+        user_key: &UserKey,
+        event: &impl Event<T>,
+        ttl: Option<Duration>,
+    ) -> Result<EventId, NaiaError> {
+        let filtered_event;
+        let event: &dyn Event<T> = match self.outgoing_event_middleware.is_empty() {
+            true => event,
+            false => {
+                match self
+                    .outgoing_event_middleware
+                    .apply(user_key, event.get_typed_copy())
+                {
+                    Some(typed_event) => filtered_event = typed_event.get_boxed_event(),
+                    None => return Err(NaiaError::FilteredOut),
                 }
+                filtered_event.as_ref()
+            }
+        };
+        self.manifest.validate_event(event)?;
+        if let Some(local_user) = self.local_users.get_mut(user_key) {
+            return Ok(local_user.queue_event(event.get_typed_copy()));
+        }
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            if connection.should_drop_unguaranteed() && !Event::is_guaranteed(event) {
+                return Err(NaiaError::Congested);
             }
+            return match ttl {
+                Some(ttl) => Ok(connection.queue_event_with_ttl(event, ttl)),
+                None => Ok(connection.queue_event(event)),
+            };
         }
+        return Err(NaiaError::NotConnected);
     }
 
-    /// Register an Entity with the Server, whereby the Server will sync the
-    /// state of the Entity to all connected Clients for which the Entity is
-    /// in scope. Gives back an EntityKey which can be used to get the reference
-    /// to the Entity from the Server once again
-    pub fn register_entity(&mut self, entity: Rc<RefCell<dyn Entity<U>>>) -> EntityKey {
-        let new_mutator_ref: Rc<RefCell<ServerEntityMutator>> =
-            Rc::new(RefCell::new(ServerEntityMutator::new(&self.mut_handler)));
-        entity
-            .as_ref()
+    /// Queues up an Event to be sent to the Client associated with a given
+    /// UserKey, which will be handed to the Client application once the given
+    /// Server tick is reached. The Event is sent right away, so it should
+    /// arrive ahead of time, and it is retransmitted if lost. Events arriving
+    /// after the scheduled tick are handed to the application immediately.
+    /// Returns an error if there is no such Client, or the Event can't be
+    /// sent, as described in `Manifest::validate_event()`
+    pub fn queue_event_at_tick(
+        &mut self,
+        user_key: &UserKey,
+        tick: u16,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        self.manifest.validate_event(event)?;
+        if let Some(local_user) = self.local_users.get_mut(user_key) {
+            local_user.queue_timed_event(tick, event.get_typed_copy());
+            return Ok(());
+        }
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.queue_timed_event(tick, event);
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Queues up an Event to be sent to the Client associated with a given
+    /// UserKey, targeted at the given Entity, such as an effect to be played
+    /// on it. The Client receives it in a `ClientEvent::EntityEvent`, along
+    /// with the Entity's LocalEntityKey. The Event is held back until the
+    /// Client has the Entity, and is dropped if the Entity leaves scope for
+    /// the Client before then. Returns an error if there is no such Client,
+    /// the Entity isn't in scope for the Client, or the Event can't be sent,
+    /// as described in `Manifest::validate_event()`
+    pub fn queue_entity_event(
+        &mut self,
+        user_key: &UserKey,
+        entity_key: &EntityKey,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        self.manifest.validate_event(event)?;
+        if let Some(local_user) = self.local_users.get_mut(user_key) {
+            if !local_user.queue_entity_event(entity_key, event.get_typed_copy()) {
+                return Err(NaiaError::EntityNotInScope);
+            }
+            return Ok(());
+        }
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            if !connection.queue_entity_event(entity_key, event) {
+                return Err(NaiaError::EntityNotInScope);
+            }
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Gets the public id assigned to the Client associated with the given
+    /// UserKey, which other Clients address the Events they relay to it by,
+    /// see `NaiaClient::relay_event()`
+    pub fn get_client_id(&self, user_key: &UserKey) -> Option<ClientId> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_client_id());
+    }
+
+    /// Gets the public ids of every connected Client, in ascending order.
+    /// Every Client is told of the others' ids as they join & leave, see
+    /// `ClientEvent::ClientJoined`
+    pub fn get_client_ids(&self) -> Vec<ClientId> {
+        let mut output: Vec<ClientId> = self.client_ids.keys().copied().collect();
+        output.sort_unstable();
+        return output;
+    }
+
+    /// Gets the UserKey of the Client which has been assigned the given
+    /// public id
+    pub fn get_user_key_by_client_id(&self, client_id: ClientId) -> Option<UserKey> {
+        return self.client_ids.get(&client_id).copied();
+    }
+
+    // Forwards the Events Clients have asked to be relayed to their
+    // recipients, dropping those addressed to a Client which isn't connected,
+    // or which the relay filter rejects
+    fn relay_events(&mut self) {
+        let mut relayed_events: Vec<(UserKey, ClientId, ClientId, T)> = Vec::new();
+        for (user_key, connection) in self.client_connections.iter_mut() {
+            let sender_id = connection.get_client_id();
+            while let Some((recipient_id, event)) = connection.get_incoming_relay_event() {
+                relayed_events.push((*user_key, sender_id, recipient_id, event));
+            }
+        }
+
+        for (sender_key, sender_id, recipient_id, event) in relayed_events {
+            let recipient_key = match self.client_ids.get(&recipient_id) {
+                Some(recipient_key) => *recipient_key,
+                None => continue,
+            };
+            if let Some(filter_func) = &self.relay_filter_func {
+                if !(filter_func.as_ref().as_ref())(&sender_key, &recipient_key, &event) {
+                    continue;
+                }
+            }
+            if let Some(connection) = self.client_connections.get_mut(&recipient_key) {
+                connection.queue_relay_event(sender_id, event.get_boxed_event().as_ref());
+            }
+        }
+    }
+
+    // Gets the next public id which isn't assigned to a connected Client
+    fn next_unused_client_id(&mut self) -> ClientId {
+        while self.client_ids.contains_key(&self.next_client_id) {
+            self.next_client_id = self.next_client_id.wrapping_add(1);
+        }
+        let client_id = self.next_client_id;
+        self.next_client_id = self.next_client_id.wrapping_add(1);
+        return client_id;
+    }
+
+    /// Gets the current tick of the Server, which is incremented every time a
+    /// Tick event is emitted
+    pub fn get_current_tick(&self) -> u16 {
+        return self.current_tick;
+    }
+
+    /// Changes the rate at which the Server ticks, such as to lower it while
+    /// under load. `Config::tick_interval` is the fastest rate, at which the
+    /// socket's timer runs, so a shorter interval is raised to it, & a longer
+    /// one which isn't a whole number of timer intervals is kept on average.
+    /// The change takes effect from the current tick, & is sent to every
+    /// Client, which emits a `ClientEvent::TickRateChanged` once it has
+    /// adapted its tick clock, interpolation delay & the scheduling of timed
+    /// Events. Clients which connect later are sent the rate as they connect
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        let tick_interval = tick_interval.max(self.config.tick_interval);
+        if tick_interval == self.tick_interval {
+            return;
+        }
+        self.tick_interval = tick_interval;
+        self.tick_accumulator.set_tick_interval(tick_interval);
+        for (_, connection) in self.client_connections.iter_mut() {
+            connection.queue_tick_rate_change(self.current_tick, tick_interval);
+        }
+    }
+
+    /// Sets the tiers of load shedding the Server enters as its load rises,
+    /// such as under CPU pressure. Load is the fraction of time the Server
+    /// spends busy, in its own work & the application's, rather than waiting
+    /// on its socket, smoothed over several ticks. Once it reaches a tier's
+    /// threshold, the tier reduces the frequency of Entity updates & the
+    /// scope of every Client alike, so that no Client is starved in favor of
+    /// another, until the load has fallen `LOAD_SHEDDING_HYSTERESIS` below
+    /// it. A `ServerEvent::LoadSheddingChanged` is emitted whenever the tier
+    /// changes, including when the tiers are replaced while one is active
+    pub fn set_load_shedding_tiers(&mut self, tiers: Vec<LoadSheddingTier>) {
+        if self.load_shedder.set_tiers(tiers) {
+            self.outstanding_tick_events
+                .push_back(ServerEvent::LoadSheddingChanged(None));
+        }
+    }
+
+    /// Gets the load of the Server, from 0 to 1, as measured for load
+    /// shedding, see `NaiaServer::set_load_shedding_tiers()`
+    pub fn get_load(&self) -> f32 {
+        return self.load_shedder.get_load();
+    }
+
+    /// Gets the index of the active tier of load shedding, in those set with
+    /// `NaiaServer::set_load_shedding_tiers()` sorted by load, or None if
+    /// load isn't being shed
+    pub fn get_load_shedding_tier(&self) -> Option<usize> {
+        return self.load_shedder.get_tier_index();
+    }
+
+    /// Gets how long the Server spent in each phase of the last tick sent, by
+    /// `NaiaServer::tick()` or `NaiaServer::send_all_updates()`. The same
+    /// timings are given in the TickStats returned by `NaiaServer::tick()`
+    pub fn get_tick_timings(&self) -> TickTimings {
+        return self.last_tick_timings;
+    }
+
+    /// Starts profiling the cost of replicating each Entity, recording the
+    /// bytes, packets & messages it consumes for each Client over windows of
+    /// the given length. Profiling adds a little work to every Entity message
+    /// sent, so it's meant to be enabled while investigating bandwidth use.
+    /// Enabling the profiler again restarts it
+    pub fn enable_replication_profiler(&mut self, window: Duration) {
+        self.replication_profiler = Some(ReplicationProfiler::new(window));
+    }
+
+    /// Stops profiling the cost of replicating each Entity, discarding what
+    /// has been recorded
+    pub fn disable_replication_profiler(&mut self) {
+        self.replication_profiler = None;
+    }
+
+    /// Gets a report of the Entities & types which cost the most to replicate,
+    /// by bytes, keeping the given number of each, over the last completed
+    /// window of the profiler. Returns None if the profiler isn't enabled, see
+    /// `NaiaServer::enable_replication_profiler()`, or no window has completed
+    /// yet
+    pub fn get_replication_profile(&self, top_count: usize) -> Option<ReplicationProfile> {
+        let mut profile = self.replication_profiler.as_ref()?.get_profile(top_count)?;
+        for type_cost in profile.types.iter_mut() {
+            type_cost.type_name = self.manifest.get_entity_type_name(type_cost.naia_id);
+        }
+        return Some(profile);
+    }
+
+    /// Gets the current duration of each tick, as set with
+    /// `NaiaServer::set_tick_interval()`, or `Config::tick_interval` if it
+    /// hasn't been changed
+    pub fn get_tick_interval(&self) -> Duration {
+        return self.tick_interval;
+    }
+
+    /// Queues up an opaque byte payload (for example, a voice frame) to be
+    /// attached to the next outgoing packet to the Client associated with a
+    /// given UserKey, bypassing the Manifest. Raw payloads are unreliable, and
+    /// at most `Config::raw_payload_budget` bytes of them are attached to any
+    /// single packet. Payloads which wait more than a second to be sent are
+    /// dropped. Returns an error if there is no such Client, or if the payload
+    /// is larger than `MAX_RAW_PAYLOAD_SIZE`, or too large for its length
+    /// byte to fit in the budget along with it.
+    pub fn send_raw(&mut self, user_key: &UserKey, payload: &[u8]) -> Result<(), NaiaError> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            if !connection.queue_raw_payload(payload) {
+                return Err(NaiaError::PayloadTooLarge(payload.len()));
+            }
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Registers an application-defined section of data packets under the
+    /// given id, whose bytes are written & read by the given CustomSection.
+    /// Clients must register a section under the same id to read it, and
+    /// sections with an id the receiver hasn't registered are skipped. The
+    /// remote host passed to the CustomSection is the UserKey of the Client.
+    /// Returns false if a section with the same id has already been registered
+    pub fn register_custom_section(
+        &mut self,
+        section_id: u8,
+        section: Box<dyn CustomSection<UserKey>>,
+    ) -> bool {
+        return self.custom_sections.register(section_id, section);
+    }
+
+    /// Registers an Event type in the Manifest under the given NaiaId after
+    /// startup, such as one added by a mod, & sends a description of it to
+    /// every connected Client. Clients which have also registered the type
+    /// receive a `ClientEvent::ManifestExtended`, & the others a
+    /// `ClientEvent::ManifestMismatch`. Returns an error if the NaiaId or the
+    /// type has already been registered, or if the Manifest is shared with a
+    /// Client in the same process
+    pub fn register_event_type(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) -> Result<(), NaiaError> {
+        let manifest = Rc::get_mut(&mut self.manifest).ok_or(NaiaError::ManifestShared)?;
+        manifest.try_register_event_with_id(naia_id, event_builder)?;
+        let delta = ManifestDescriptor {
+            events: manifest.get_event_descriptor(naia_id).into_iter().collect(),
+            entities: Vec::new(),
+        };
+        self.queue_manifest_delta(delta);
+        return Ok(());
+    }
+
+    /// Registers an Entity type in the Manifest under the given NaiaId after
+    /// startup, such as one added by a mod, & sends a description of it to
+    /// every connected Client. Clients which have also registered the type
+    /// receive a `ClientEvent::ManifestExtended`, & the others a
+    /// `ClientEvent::ManifestMismatch`. Returns an error if the NaiaId or the
+    /// type has already been registered, or if the Manifest is shared with a
+    /// Client in the same process
+    pub fn register_entity_type(
+        &mut self,
+        naia_id: u16,
+        entity_builder: Box<dyn EntityBuilder<U>>,
+    ) -> Result<(), NaiaError> {
+        let manifest = Rc::get_mut(&mut self.manifest).ok_or(NaiaError::ManifestShared)?;
+        manifest.try_register_entity_with_id(naia_id, entity_builder)?;
+        let delta = ManifestDescriptor {
+            events: Vec::new(),
+            entities: manifest
+                .get_entity_descriptor(naia_id)
+                .into_iter()
+                .collect(),
+        };
+        self.queue_manifest_delta(delta);
+        return Ok(());
+    }
+
+    // Updates the cached description of the Manifest, which Clients connecting
+    // from now on are checked against, & sends the newly registered types to
+    // the Clients already connected
+    fn queue_manifest_delta(&mut self, delta: ManifestDescriptor) {
+        self.manifest_descriptor = self.manifest.get_descriptor();
+        for connection in self.client_connections.values_mut() {
+            connection.queue_manifest_delta(delta.clone());
+        }
+    }
+
+    /// Sets how outgoing data packets to every Client are assembled from the
+    /// serialized Events, Entity messages, RPC messages, etc. waiting to be
+    /// sent, for example to order sections differently or interleave items by
+    /// priority. None restores the default SectionFraming
+    pub fn set_packet_framing(&mut self, packet_framing: Option<PacketFramingFactory>) {
+        for connection in self.client_connections.values_mut() {
+            connection.set_packet_framing(packet_framing.clone());
+        }
+        self.packet_framing = packet_framing;
+    }
+
+    /// Attaches application data, such as an account id, permissions or
+    /// session info, to the connection of the Client associated with a given
+    /// UserKey. One value of each type can be attached, & setting another
+    /// replaces it. The data lives as long as the connection, and is dropped
+    /// by the time `ServerEvent::Disconnection` is emitted for the Client.
+    /// Returns the data of the same type which was replaced, if any, or an
+    /// error if there is no such Client
+    pub fn set_client_data<D: Any>(
+        &mut self,
+        user_key: &UserKey,
+        data: D,
+    ) -> Result<Option<D>, NaiaError> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            return Ok(connection.set_client_data(data));
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Gets the application data of the given type attached to the
+    /// connection of the Client associated with a given UserKey, see
+    /// `set_client_data()`
+    pub fn client_data<D: Any>(&self, user_key: &UserKey) -> Option<&D> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_client_data::<D>());
+    }
+
+    /// Gets a mutable reference to the application data of the given type
+    /// attached to the connection of the Client associated with a given
+    /// UserKey, see `set_client_data()`
+    pub fn client_data_mut<D: Any>(&mut self, user_key: &UserKey) -> Option<&mut D> {
+        return self
+            .client_connections
+            .get_mut(user_key)
+            .and_then(|connection| connection.get_client_data_mut::<D>());
+    }
+
+    /// Detaches the application data of the given type from the connection
+    /// of the Client associated with a given UserKey, returning it
+    pub fn remove_client_data<D: Any>(&mut self, user_key: &UserKey) -> Option<D> {
+        return self
+            .client_connections
+            .get_mut(user_key)
+            .and_then(|connection| connection.remove_client_data::<D>());
+    }
+
+    /// Sets a small piece of application data to attach to every heartbeat
+    /// sent to the Client associated with a given UserKey, which emits it in a
+    /// `ClientEvent::HeartbeatPayload`. Heartbeats are only sent when nothing
+    /// else has been sent for `Config::heartbeat_interval`. Returns an error
+    /// if there is no such Client, or if the payload is larger than
+    /// `MAX_HEARTBEAT_PAYLOAD_SIZE`
+    pub fn set_heartbeat_payload(
+        &mut self,
+        user_key: &UserKey,
+        payload: &[u8],
+    ) -> Result<(), NaiaError> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            if !connection.set_heartbeat_payload(payload) {
+                return Err(NaiaError::PayloadTooLarge(payload.len()));
+            }
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Sets the status sent in answer to status queries made without a
+    /// connection, such as with `ServerQuery::request_status()`, so that
+    /// server browsers can list this Server. The user count is filled in when
+    /// each query is answered. Returns false, keeping the previous status, if
+    /// the status is too large to be sent, as described in
+    /// `ServerStatus::get_size()`
+    pub fn set_server_status(&mut self, status: ServerStatus) -> bool {
+        if status.get_size().is_none() {
+            return false;
+        }
+        self.server_status = status;
+        return true;
+    }
+
+    /// Gets the status sent in answer to status queries
+    pub fn get_server_status(&self) -> &ServerStatus {
+        return &self.server_status;
+    }
+
+    /// Listens for Clients on another address, such as a second port, or a
+    /// loopback address for local bots, returning the index of the endpoint.
+    /// Clients of every endpoint are handled by this Server alike, and each
+    /// User is tagged with the endpoint it connected through, retrieved with
+    /// `NaiaServer::get_user_endpoint()`. All endpoints use the transport
+    /// the Server was built with, UDP or WebRTC
+    pub async fn add_endpoint(&mut self, address: SocketAddr) -> usize {
+        let mut socket_config = SocketConfig::default();
+        socket_config.tick_interval = self.config.tick_interval;
+        let (index, sender) = self.endpoints.add(address, socket_config).await;
+        self.sender.add(sender);
+        return index;
+    }
+
+    /// Gets the address the endpoint at the given index listens on
+    pub fn get_endpoint_address(&self, index: usize) -> Option<SocketAddr> {
+        return self.endpoints.get_address(index);
+    }
+
+    /// Gets the number of endpoints this Server listens on
+    pub fn get_endpoint_count(&self) -> usize {
+        return self.endpoints.len();
+    }
+
+    /// Makes this Server act as a master server, which lists the Servers
+    /// registered with it using `NaiaServer::register_with_master()`, and
+    /// answers server browsers querying the list with
+    /// `ServerQuery::request_server_list()`. A Server is removed from the list
+    /// once it hasn't renewed its registration for the given duration, which
+    /// should be a few times the interval the Servers register at
+    pub fn enable_master_server(&mut self, registration_timeout: Duration) {
+        self.master_server = Some(MasterServer::new(
+            registration_timeout,
+            self.random_source.as_ref(),
+        ));
+    }
+
+    /// Gets the address & status of every Server registered with this Server,
+    /// in the order they first registered. Returns an empty list unless
+    /// `NaiaServer::enable_master_server()` has been called
+    pub fn get_registered_servers(&mut self) -> Vec<(SocketAddr, ServerStatus)> {
+        return match &mut self.master_server {
+            Some(master_server) => master_server.get_servers(),
+            None => Vec::new(),
+        };
+    }
+
+    /// Registers this Server, along with its status, with the master server
+    /// at the given address, renewing the registration at the given interval.
+    /// The master server lists this Server at the address it receives the
+    /// registration from, so it must be reachable by Clients there. The
+    /// status must be no larger than `MAX_REGISTERED_STATUS_SIZE`
+    pub fn register_with_master(&mut self, master_address: SocketAddr, interval: Duration) {
+        if let Some(status_size) = self.server_status.get_size() {
+            if status_size > MAX_REGISTERED_STATUS_SIZE {
+                warn!(
+                    "server status is too large to be listed by a master server: {} bytes",
+                    status_size
+                );
+            }
+        }
+        self.master_registration = Some(MasterRegistration::new(master_address, interval));
+    }
+
+    /// Removes this Server from the list of the master server it registered
+    /// with, and stops renewing the registration. Returns an error if the
+    /// deregistration could not be sent
+    pub async fn deregister_from_master(&mut self) -> Result<(), NaiaError> {
+        if let Some(registration) = self.master_registration.take() {
+            return NaiaServer::<T, U>::internal_send_unconnected_request(
+                &mut self.sender,
+                registration.get_master_address(),
+                UnconnectedKind::Deregister,
+                registration.write_deregistration(),
+            )
+            .await;
+        }
+        return Ok(());
+    }
+
+    /// Answers an unconnected message, emitted in a
+    /// `ServerEvent::UnconnectedMessage`, with the given data, which is sent
+    /// without a connection. Returns false if the data is longer than
+    /// `MAX_UNCONNECTED_DATA_SIZE`
+    pub fn send_unconnected_response(
+        &mut self,
+        address: SocketAddr,
+        token: u32,
+        data: &[u8],
+    ) -> bool {
+        let response = match UnconnectedMessage::new(UnconnectedKind::Message, token, data) {
+            Some(response) => response,
+            None => return false,
+        };
+        let mut response_bytes = Vec::new();
+        response.write(&mut response_bytes);
+        self.outgoing_unconnected_responses
+            .push_back(Packet::new(address, response_bytes));
+        return true;
+    }
+
+    /// Queues up a large blob of data to be sent to the Client associated with
+    /// a given UserKey. The blob is split into chunks, which are sent as the
+    /// bandwidth limit in the Config allows, and retransmitted if lost. Returns
+    /// None if there is no such Client, or if the blob is empty or too large.
+    pub fn send_blob(&mut self, user_key: &UserKey, data: &[u8]) -> Option<BlobId> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            return connection.queue_blob(data);
+        }
+        return None;
+    }
+
+    /// Get the progress of a blob being sent to a Client, as a tuple of
+    /// (chunks delivered, total chunks). Returns None once the blob has been
+    /// completely delivered.
+    pub fn get_blob_send_progress(
+        &self,
+        user_key: &UserKey,
+        blob_id: BlobId,
+    ) -> Option<(u16, u16)> {
+        if let Some(connection) = self.client_connections.get(user_key) {
+            return connection.get_outgoing_blob_progress(blob_id);
+        }
+        return None;
+    }
+
+    /// Queues up a response to a request received from a Client, which will be
+    /// correlated with the original request on the Client. Returns an error
+    /// if there is no such Client, or the Event can't be sent, as described
+    /// in `Manifest::validate_event()`
+    pub fn respond(
+        &mut self,
+        user_key: &UserKey,
+        request_id: RequestId,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        self.manifest.validate_event(event)?;
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.queue_rpc(RpcMessage::Response(request_id, event));
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Rejects a request received from a Client, which will fail on the Client
+    /// with `RequestError::Rejected`. Returns an error if there is no such
+    /// Client
+    pub fn reject_request(
+        &mut self,
+        user_key: &UserKey,
+        request_id: RequestId,
+    ) -> Result<(), NaiaError> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.queue_rpc(RpcMessage::Error(request_id));
+            return Ok(());
+        }
+        return Err(NaiaError::NotConnected);
+    }
+
+    /// Sends all Entity/Event messages to all Clients. If you don't call this
+    /// method, the Server will never communicate with it's connected
+    /// Clients. Returns the first error met sending a packet, after trying
+    /// every Client
+    pub async fn send_all_updates(&mut self) -> Result<(), NaiaError> {
+        self.send_updates().await?;
+        return Ok(());
+    }
+
+    /// Sends all Entity/Event messages to all Clients, at most once per tick
+    /// of the rate given by `NaiaServer::set_tick_interval()`. This can be called as
+    /// often as is convenient, such as after every call to `receive()`, and
+    /// only does any work once a new `ServerEvent::Tick` has been received.
+    /// Scopes Entities, collects the updates of mutated Entities by priority,
+    /// and sends packets to every Client in one call. Returns statistics about
+    /// the tick, or None if no tick has elapsed since the last call, or the
+    /// first error met sending a packet, after trying every Client
+    pub async fn tick(&mut self) -> Result<Option<TickStats>, NaiaError> {
+        if self.last_sent_tick == self.current_tick {
+            return Ok(None);
+        }
+        self.last_sent_tick = self.current_tick;
+        return Ok(Some(self.send_updates().await?));
+    }
+
+    async fn send_updates(&mut self) -> Result<TickStats, NaiaError> {
+        let start = Instant::now();
+        let mut stats = TickStats::default();
+        stats.tick = self.current_tick;
+        let mut timings = TickTimings::default();
+        timings.receive = self.receive_busy;
+        self.receive_busy = Duration::from_secs(0);
+
+        // refresh the snapshot of every Room, if it's time to
+        if let Some(timer) = &mut self.snapshot_cache_timer {
+            if timer.ringing() {
+                timer.reset();
+                self.refresh_room_snapshots();
+            }
+        }
+
+        // update entity scopes
+        let scope_event_count = self.outstanding_scope_events.len();
+        self.update_entity_scopes();
+        stats.scope_changes = self.outstanding_scope_events.len() - scope_event_count;
+        timings.scope = start.elapsed();
+
+        // loop through all connections, send packet
+        let max_updates = self.config.max_entity_updates_per_tick;
+        let shed_update_interval = self
+            .load_shedder
+            .get_tier()
+            .map_or(1, |tier| tier.update_interval.max(1));
+        let mut send_error: Option<NaiaError> = None;
+        for (user_key, connection) in self.client_connections.iter_mut() {
+            if let Some(user) = self.users.get(*user_key) {
+                stats.user_count += 1;
+                // a Client being handed off to another Server is only told where to go
+                if let Some(handoff_payload) = connection.get_handoff_payload() {
+                    let serialize_start = Instant::now();
+                    let payload =
+                        connection.process_outgoing_header(PacketType::Handoff, &handoff_payload);
+                    stats.packets_sent += 1;
+                    stats.bytes_sent += payload.len();
+                    let send_start = Instant::now();
+                    timings.serialize += send_start.duration_since(serialize_start);
+                    if let Err(error) = self
+                        .sender
+                        .send(Packet::new_raw(user.address, payload))
+                        .await
+                    {
+                        send_error.get_or_insert(NaiaError::Wrapped(error));
+                    }
+                    timings.send += send_start.elapsed();
+                    connection.mark_sent();
+                    continue;
+                }
+                // while shedding load, each Client's updates are collected on
+                // its own share of the ticks, offset by its id
+                if self.current_tick.wrapping_add(connection.get_client_id()) % shed_update_interval
+                    != 0
+                {
+                    stats.shed_users += 1;
+                } else {
+                    let global_entity_store = &self.global_entity_store;
+                    let entity_priority_func = &self.entity_priority_func;
+                    let entity_keyframe_intervals = &self.entity_keyframe_intervals;
+                    let (entity_updates, deferred_entity_updates) = connection
+                        .collect_entity_updates(
+                            &|entity_key: &EntityKey| -> f32 {
+                                if let Some(priority_func) = entity_priority_func {
+                                    if let Some(entity) = global_entity_store.get(*entity_key) {
+                                        return (priority_func.as_ref().as_ref())(
+                                            user_key,
+                                            entity_key,
+                                            entity.as_ref().borrow().get_typed_copy(),
+                                        );
+                                    }
+                                }
+                                return 1.0;
+                            },
+                            &|entity_key: &EntityKey| -> Option<u16> {
+                                return entity_keyframe_intervals.get(*entity_key).copied();
+                            },
+                            max_updates,
+                            self.current_tick,
+                            &mut timings,
+                        );
+                    stats.entity_updates += entity_updates;
+                    stats.deferred_entity_updates += deferred_entity_updates;
+                }
+                loop {
+                    let serialize_start = Instant::now();
+                    let outgoing_packet = connection.get_outgoing_packet(
+                        &self.manifest,
+                        &mut self.custom_sections,
+                        user_key,
+                        self.current_tick,
+                        self.replication_profiler.as_mut(),
+                    );
+                    let send_start = Instant::now();
+                    timings.serialize += send_start.duration_since(serialize_start);
+                    let payload = match outgoing_packet {
+                        Some(payload) => payload,
+                        None => break,
+                    };
+                    stats.packets_sent += 1;
+                    stats.bytes_sent += payload.len();
+                    if let Err(error) = self
+                        .sender
+                        .send(Packet::new_raw(user.address, payload))
+                        .await
+                    {
+                        send_error.get_or_insert(NaiaError::Wrapped(error));
+                    }
+                    timings.send += send_start.elapsed();
+                    connection.mark_sent();
+                }
+            }
+        }
+
+        stats.duration = start.elapsed();
+        stats.timings = timings;
+        self.last_tick_timings = timings;
+        if let Some(error) = send_error {
+            return Err(error);
+        }
+        return Ok(stats);
+    }
+
+    /// Register an Entity with the Server, whereby the Server will sync the
+    /// state of the Entity to all connected Clients for which the Entity is
+    /// in scope. Gives back an EntityKey which can be used to get the reference
+    /// to the Entity from the Server once again
+    pub fn register_entity(&mut self, entity: Rc<RefCell<dyn Entity<U>>>) -> EntityKey {
+        let new_mutator_ref: Rc<RefCell<ServerEntityMutator>> =
+            Rc::new(RefCell::new(ServerEntityMutator::new(&self.mut_handler)));
+        entity
+            .as_ref()
             .borrow_mut()
             .set_mutator(&to_entity_mutator(&new_mutator_ref));
         let entity_key = self.global_entity_store.insert(entity.clone());
@@ -444,11 +2081,117 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
         return entity_key;
     }
 
+    /// Register an Entity which has been created from the prefab registered
+    /// with `Manifest::register_prefab()` under the given PrefabId, typically
+    /// with some Properties overridden. When the Entity comes into scope for a
+    /// Client, only the Properties which differ from the prefab are written.
+    /// If the prefab does not exist, or is of a different type than the
+    /// Entity, the Entity is registered as if by `register_entity()`.
+    pub fn register_entity_from_prefab(
+        &mut self,
+        prefab_id: PrefabId,
+        entity: Rc<RefCell<dyn Entity<U>>>,
+    ) -> EntityKey {
+        let valid_prefab = self
+            .manifest
+            .get_prefab_diff(prefab_id, &*entity.as_ref().borrow())
+            .is_some();
+        let entity_key = self.register_entity(entity);
+        if valid_prefab {
+            self.entity_prefab_ids.insert(entity_key, prefab_id);
+        } else {
+            warn!(
+                "prefab {} does not exist for the registered entity's type",
+                prefab_id
+            );
+        }
+        return entity_key;
+    }
+
     /// Deregisters an Entity with the Server, deleting local copies of the
     /// Entity on each Client
     pub fn deregister_entity(&mut self, key: EntityKey) {
+        self.remove_entity(key, None);
+    }
+
+    /// Links a registered Entity to one which the Client associated with the
+    /// given UserKey has spawned locally, in prediction of the Server spawning
+    /// it. The PredictionKey is typically sent to the Server within the Event
+    /// which caused the spawn. When the Entity comes into scope for the User,
+    /// the Client replaces its predicted Entity with the Server's, emitting a
+    /// `ClientEvent::ConfirmPredictedEntity`. This must be called before the
+    /// Entity's creation has been sent to the Client.
+    pub fn confirm_predicted_entity(
+        &mut self,
+        user_key: &UserKey,
+        entity_key: &EntityKey,
+        prediction_key: PredictionKey,
+    ) {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.set_entity_prediction_key(entity_key, prediction_key);
+        }
+    }
+
+    /// Deregisters an Entity with the Server, sending a deletion along with
+    /// the given reason code to every Client which currently has the Entity in
+    /// scope
+    pub fn deregister_entity_with_reason(&mut self, key: EntityKey, reason: DespawnReason) {
+        self.remove_entity(key, Some(reason));
+    }
+
+    // Deletes an Entity on every Client which has it in scope, and removes it
+    // from every Room, so that no Client is left holding a copy of it
+    fn remove_entity(&mut self, key: EntityKey, reason: Option<DespawnReason>) {
+        for (user_key, connection) in self.client_connections.iter_mut() {
+            if connection.remove_entity(&key, reason) {
+                self.outstanding_scope_events
+                    .push_back(ServerEvent::EntityLeftScope(*user_key, key));
+            }
+        }
+        for (user_key, local_user) in self.local_users.iter_mut() {
+            if local_user.remove_entity(&key, reason) {
+                self.outstanding_scope_events
+                    .push_back(ServerEvent::EntityLeftScope(*user_key, key));
+            }
+        }
+        for (_, room) in self.rooms.iter_mut() {
+            if room.has_entity(&key) {
+                room.remove_entity(&key);
+            }
+        }
         self.mut_handler.borrow_mut().deregister_entity(&key);
         self.global_entity_store.remove(key);
+        self.entity_prefab_ids.remove(key);
+        self.entity_keyframe_intervals.remove(key);
+        if let Some(profiler) = self.replication_profiler.as_mut() {
+            profiler.remove_entity(&key);
+        }
+    }
+
+    /// Streams updates of a registered Entity as keyframes & deltas, which
+    /// suits Entities that change every tick. Every `keyframe_interval` ticks
+    /// the whole Entity is sent, and the ticks in between only send the
+    /// Properties which changed since the previous tick. Dropped updates are
+    /// not retransmitted, a Client which misses one keeps the last keyframe
+    /// with the deltas received since, until the next keyframe brings it back
+    /// in sync. Given None, the Entity's updates are sent as usual
+    pub fn set_entity_keyframe_interval(
+        &mut self,
+        key: &EntityKey,
+        keyframe_interval: Option<u16>,
+    ) {
+        if !self.global_entity_store.contains_key(*key) {
+            return;
+        }
+        match keyframe_interval {
+            Some(keyframe_interval) => {
+                self.entity_keyframe_intervals
+                    .insert(*key, keyframe_interval.max(1));
+            }
+            None => {
+                self.entity_keyframe_intervals.remove(*key);
+            }
+        }
     }
 
     /// Given an EntityKey, get a reference to a registered Entity being tracked
@@ -467,6 +2210,7 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
     /// Deletes the Room associated with a given RoomKey on the Server
     pub fn delete_room(&mut self, key: RoomKey) {
         self.rooms.remove(key);
+        self.room_snapshots.remove(key);
     }
 
     /// Gets a Room given an associated RoomKey
@@ -518,45 +2262,1089 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
         self.scope_entity_func = Some(scope_func);
     }
 
+    /// Registers a closure which is used to evaluate the priority of sending
+    /// an update of a mutated Entity to a given User, for example based on the
+    /// Entity's distance to the User's player.
+    ///
+    /// Each tick, the priority is added to an accumulator for every mutated
+    /// Entity, and Entities are updated in order of their accumulated
+    /// priority, which is then reset. Combined with
+    /// `Config::max_entity_updates_per_tick`, this means low priority Entities
+    /// are updated less often, but never starve. Priority defaults to 1.0, and
+    /// values below 0.01, including 0.0 & negative values, are treated as
+    /// 0.01, so that even those Entities are updated eventually.
+    pub fn on_entity_priority(
+        &mut self,
+        priority_func: Rc<Box<dyn Fn(&UserKey, &EntityKey, U) -> f32>>,
+    ) {
+        self.entity_priority_func = Some(priority_func);
+    }
+
     /// Registers a closure which will be called during the handshake process
     /// with a new Client
     ///
     /// The Event evaluated in this closure should match the Event used
-    /// client-side in the NaiaClient::new() method
+    /// client-side in the NaiaClient::new() method, and must be registered
+    /// with Manifest::register_auth_event(). Any other Event sent during the
+    /// handshake is rejected without being deserialized.
     pub fn on_auth(&mut self, auth_func: Rc<Box<dyn Fn(&UserKey, &T) -> bool>>) {
         self.auth_func = Some(auth_func);
     }
 
-    /// Get the current measured Round Trip Time to the Server
-    pub fn get_rtt(&mut self, user_key: &UserKey) -> Option<f32> {
-        if let Some(connection) = self.client_connections.get_mut(user_key) {
-            return Some(connection.get_rtt());
-        }
-        return None;
+    /// Registers a closure which gets the identity of a new Client, such as
+    /// an account id, from the auth Event it connected with, or None if it
+    /// has none. When a Client authenticates with an identity which another
+    /// connected Client already has, the policy set with
+    /// `NaiaServer::set_duplicate_login_policy()` is applied. Requires an
+    /// auth closure, registered with `NaiaServer::on_auth()`
+    pub fn on_identity(&mut self, identity_func: Rc<Box<dyn Fn(&UserKey, &T) -> Option<String>>>) {
+        self.identity_func = Some(identity_func);
     }
 
-    /// Iterate through all currently connected Users
-    pub fn users_iter(&self) -> slotmap::dense::Iter<UserKey, User> {
-        return self.users.iter();
+    /// Sets what is done when a Client authenticates with an identity which
+    /// another connected Client already has, which is
+    /// `DuplicateLoginPolicy::AllowBoth` by default
+    pub fn set_duplicate_login_policy(&mut self, policy: DuplicateLoginPolicy) {
+        self.duplicate_login_policy = policy;
     }
 
-    /// Get a User, given the associated UserKey
-    pub fn get_user(&self, user_key: &UserKey) -> Option<&User> {
-        return self.users.get(*user_key);
+    /// Gets the identity of a User, as given by the closure registered with
+    /// `NaiaServer::on_identity()`
+    pub fn get_user_identity(&self, user_key: &UserKey) -> Option<&str> {
+        return self.user_identities.get(user_key).map(String::as_str);
     }
 
-    fn update_entity_scopes(&mut self) {
-        for (room_key, room) in self.rooms.iter_mut() {
-            while let Some((removed_user, removed_entity)) = room.pop_removal_queue() {
-                if let Some(user_connection) = self.client_connections.get_mut(&removed_user) {
-                    user_connection.remove_entity(&removed_entity);
-                }
-            }
+    /// Disconnects a User, emitting a Disconnection event for it. A Client is
+    /// told it has been kicked, & doesn't attempt to reconnect
+    pub fn kick_user(&mut self, user_key: &UserKey) {
+        if !self.users.contains_key(*user_key) || self.outstanding_disconnects.contains(user_key) {
+            return;
+        }
+        if self.client_connections.contains_key(user_key) {
+            self.kicked_users.insert(*user_key);
+        }
+        self.outstanding_disconnects.push_back(*user_key);
+    }
 
-            if let Some(scope_func) = &self.scope_entity_func {
-                for user_key in room.users_iter() {
+    /// Shuts the Server down gracefully. The reason, cut short to
+    /// `MAX_SHUTDOWN_REASON_LENGTH` bytes, is sent to every Client, which
+    /// emits it as a `ClientEvent::ServerShutdown`, & new Clients are
+    /// refused. Once every Client has received everything queued for it, or
+    /// the grace period is over, every User is kicked, as with
+    /// `NaiaServer::kick_user()`, & a `ServerEvent::Shutdown` is emitted.
+    /// Packets must keep being sent with `NaiaServer::send_all_updates()` or
+    /// `NaiaServer::tick()` until then
+    pub fn shutdown(&mut self, reason: &str, grace_period: Duration) {
+        if self.is_shutting_down() {
+            return;
+        }
+        let mut length = reason.len().min(MAX_SHUTDOWN_REASON_LENGTH);
+        while !reason.is_char_boundary(length) {
+            length -= 1;
+        }
+        for (_, connection) in self.client_connections.iter_mut() {
+            connection.queue_shutdown(reason[..length].to_string());
+        }
+        self.shutdown_timer = Some(Timer::new(grace_period));
+    }
+
+    /// Returns whether `NaiaServer::shutdown()` has been called, after which
+    /// new Clients are refused
+    pub fn is_shutting_down(&self) -> bool {
+        return self.shutdown_timer.is_some() || self.shut_down;
+    }
+
+    /// Registers a closure which is used to validate every Event received
+    /// from a Client, before it reaches the application. This provides a
+    /// single place to integrate anti-cheat checks or schema validation.
+    ///
+    /// Rejected Events are discarded, and the closure may optionally give an
+    /// Event to send back to the Client
+    pub fn on_validate_event(
+        &mut self,
+        validation_func: Rc<Box<dyn Fn(&UserKey, &T) -> EventValidation<T>>>,
+    ) {
+        self.event_validation_func = Some(validation_func);
+    }
+
+    /// Registers a closure which is used to filter every Event one Client
+    /// sends to another with `NaiaClient::relay_event()`, given the UserKeys
+    /// of the sender & the recipient, so that the Server stays in control of
+    /// which Clients can talk to each other. Events for which the closure
+    /// returns false are dropped. Without a filter, every relayed Event is
+    /// forwarded
+    pub fn on_relay_event(&mut self, filter_func: Rc<Box<dyn Fn(&UserKey, &UserKey, &T) -> bool>>) {
+        self.relay_filter_func = Some(filter_func);
+    }
+
+    /// Appends a function to the chain every Event received from a Client
+    /// passes through, in the order added, before it is validated &
+    /// emitted as a `ServerEvent::Event`. Each function can observe the
+    /// Event, return a changed Event, or return None to drop it, so that
+    /// concerns like logging, analytics or profanity filtering can be kept
+    /// out of the game's event handling
+    pub fn add_incoming_event_middleware(
+        &mut self,
+        middleware_func: Rc<Box<dyn Fn(&UserKey, T) -> Option<T>>>,
+    ) {
+        self.incoming_event_middleware.push(middleware_func);
+    }
+
+    /// Appends a function to the chain every Event queued for a Client with
+    /// `queue_event()` or `queue_event_with_ttl()` passes through, in the
+    /// order added. Each function can observe the Event, return a changed
+    /// Event, or return None to drop it, in which case queueing the Event
+    /// returns `NaiaError::FilteredOut`
+    pub fn add_outgoing_event_middleware(
+        &mut self,
+        middleware_func: Rc<Box<dyn Fn(&UserKey, T) -> Option<T>>>,
+    ) {
+        self.outgoing_event_middleware.push(middleware_func);
+    }
+
+    /// Get the current measured Round Trip Time to the Server
+    pub fn get_rtt(&mut self, user_key: &UserKey) -> Option<f32> {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            return Some(connection.get_rtt());
+        }
+        return None;
+    }
+
+    /// Get the min, median, 95th percentile & max of the most recent Round
+    /// Trip Time samples to the Client associated with the given UserKey,
+    /// over the number of samples given by `Config::rtt_history_size`. Returns
+    /// None if no sample has been taken yet
+    pub fn get_rtt_stats(&self, user_key: &UserKey) -> Option<RttStats> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_rtt_stats());
+    }
+
+    /// Get the fraction of the most recent packets sent to the Client
+    /// associated with the given UserKey which were lost, from 0 to 1. Returns
+    /// None if no sent packet has been acked or dropped yet
+    pub fn get_packet_loss(&self, user_key: &UserKey) -> Option<f32> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_packet_loss());
+    }
+
+    /// Get the histogram of the time between sending each packet to the
+    /// Client associated with the given UserKey & receiving its ack
+    pub fn get_ack_latency(&self, user_key: &UserKey) -> Option<&AckLatencyHistogram> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_ack_latency());
+    }
+
+    /// Clear the histogram of ack latencies of the Client associated with the
+    /// given UserKey, so that it can be sampled over a chosen period
+    pub fn clear_ack_latency(&mut self, user_key: &UserKey) {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.clear_ack_latency();
+        }
+    }
+
+    /// Get the quality of the connection to the Client associated with the
+    /// given UserKey, as last emitted in a
+    /// `ServerEvent::ConnectionQualityChanged`. Returns None if it has not
+    /// been classified yet
+    pub fn get_connection_quality(&self, user_key: &UserKey) -> Option<ConnectionQuality> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_quality());
+    }
+
+    /// Returns whether the outgoing queues of the connection to the Client
+    /// associated with the given UserKey are congested, as last emitted in a
+    /// `ServerEvent::Congested` or `ServerEvent::Relieved`
+    pub fn is_congested(&self, user_key: &UserKey) -> bool {
+        return self
+            .client_connections
+            .get(user_key)
+            .map_or(false, |connection| connection.is_congested());
+    }
+
+    /// Gets the state of the link to the Client associated with the given
+    /// UserKey, as last emitted in a `ServerEvent::LinkStateChanged`
+    pub fn get_link_state(&self, user_key: &UserKey) -> Option<LinkState> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_link_state());
+    }
+
+    /// Get statistics about the commands received from the Client associated
+    /// with the given UserKey, including how many ticks were only received
+    /// thanks to the redundant copies sent in later packets
+    pub fn get_command_stats(&self, user_key: &UserKey) -> Option<CommandStats> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_command_stats());
+    }
+
+    /// Get statistics about the outgoing messages of the given kind, such as
+    /// `ManagerType::Event` or `ManagerType::Rpc`, which have yet to be
+    /// delivered to the Client associated with the given UserKey: how many
+    /// are queued & in flight, how long the oldest has been waiting, & how
+    /// many have been retransmitted
+    pub fn get_event_queue_stats(
+        &self,
+        user_key: &UserKey,
+        manager_type: ManagerType,
+    ) -> Option<EventQueueStats> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_event_queue_stats(manager_type));
+    }
+
+    /// Get statistics about the outgoing Events of type E which have yet to be
+    /// delivered to the Client associated with the given UserKey, however
+    /// they were sent, so that a class of Events which is backing up can be
+    /// throttled
+    pub fn get_event_type_queue_stats<E: 'static>(
+        &self,
+        user_key: &UserKey,
+    ) -> Option<EventQueueStats> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_event_type_queue_stats(TypeId::of::<E>()));
+    }
+
+    /// Get statistics about the packets received from the Client associated
+    /// with the given UserKey which were dropped, in whole or in part, for
+    /// being malformed
+    pub fn get_connection_stats(&self, user_key: &UserKey) -> Option<ConnectionStats> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_connection_stats());
+    }
+
+    /// Get statistics about how early or late the commands of the Client
+    /// associated with the given UserKey arrive, relative to the tick each is
+    /// applied at, and what fraction of ticks had no command
+    pub fn get_command_buffer_stats(&self, user_key: &UserKey) -> Option<CommandBufferStats> {
+        if let Some(local_user) = self.local_users.get(user_key) {
+            return Some(local_user.get_command_buffer_stats());
+        }
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_command_buffer_stats());
+    }
+
+    /// Sets what is emitted for a tick when a Client's command for it has not
+    /// arrived in time, which is `MissingCommandPolicy::RepeatLast` by
+    /// default
+    pub fn set_missing_command_policy(&mut self, policy: MissingCommandPolicy) {
+        self.missing_command_policy = policy;
+    }
+
+    /// Get how many ticks ahead of the Server the packets of the Client
+    /// associated with the given UserKey arrive, smoothed over recent
+    /// packets. The Client's tick clock is sped up or slowed down to keep this
+    /// at `Config::input_buffer_target_depth`. Returns None if the Client has
+    /// not yet sent its tick, which it does once it calls
+    /// `NaiaClient::frame_begin()`
+    pub fn get_client_tick_lead(&self, user_key: &UserKey) -> Option<f32> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_client_tick_lead());
+    }
+
+    /// Returns whether the Client associated with the given UserKey is
+    /// currently in loading mode. Entity messages for that Client are held
+    /// back until it is ready again
+    pub fn is_user_loading(&self, user_key: &UserKey) -> bool {
+        if let Some(connection) = self.client_connections.get(user_key) {
+            return connection.is_loading();
+        }
+        return false;
+    }
+
+    /// Pauses the replication of Entities to the Client associated with the
+    /// given UserKey, for example while they're in a menu, without tearing
+    /// down the connection. Events are still sent while paused. The
+    /// PausePolicy determines whether Entity updates made in the meantime are
+    /// buffered or discarded
+    pub fn pause_replication(&mut self, user_key: &UserKey, policy: PausePolicy) {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.pause_replication(policy);
+        }
+    }
+
+    /// Resumes the replication of Entities to the Client associated with the
+    /// given UserKey. If any Entity updates were discarded while paused, the
+    /// Client is sent the full state of every Entity in its scope
+    pub fn resume_replication(&mut self, user_key: &UserKey) {
+        if let Some(connection) = self.client_connections.get_mut(user_key) {
+            connection.resume_replication();
+        }
+    }
+
+    /// Returns whether the replication of Entities to the Client associated
+    /// with the given UserKey is currently paused
+    pub fn is_replication_paused(&self, user_key: &UserKey) -> bool {
+        if let Some(connection) = self.client_connections.get(user_key) {
+            return connection.is_replication_paused();
+        }
+        return false;
+    }
+
+    /// Gets the number of guaranteed Event & Entity messages for the Client
+    /// associated with the given UserKey which are queued, or have been sent
+    /// without yet being acknowledged. Useful for checking that the backlog
+    /// of a Client does not grow without bound
+    pub fn get_undelivered_message_count(&self, user_key: &UserKey) -> Option<usize> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.get_undelivered_message_count());
+    }
+
+    /// Produces a report of every Entity in scope for the Client associated
+    /// with the given UserKey, to be compared with
+    /// `NaiaClient::debug_dump_entities()` using `diff_entity_dumps()` when
+    /// debugging replication mismatches. Returns None if there is no such
+    /// Client
+    pub fn debug_dump_entities(&self, user_key: &UserKey) -> Option<EntityDebugDump> {
+        return self
+            .client_connections
+            .get(user_key)
+            .map(|connection| connection.debug_dump_entities(&self.manifest));
+    }
+
+    /// Connects a local User, in the same process as the Server, such as the
+    /// host player of a ListenServer, or a BotClient. A local User has no connection: the
+    /// Entities in its scope are shared with the Server rather than
+    /// replicated, & Events are handed over in memory, with
+    /// `NaiaServer::send_local_event()` & `NaiaServer::receive_local()`. A
+    /// Connection event is emitted for it like for any Client, so it can be
+    /// added to Rooms
+    pub fn connect_local_user(&mut self) -> UserKey {
+        let address = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+        let user_key = self.users.insert(User::new(address, 0, Timestamp::now()));
+        self.local_users.insert(user_key, LocalUser::new());
+        self.outstanding_local_events
+            .push_back(ServerEvent::Connection(user_key));
+        return user_key;
+    }
+
+    /// Disconnects a local User, emitting a Disconnection event for it
+    pub fn disconnect_local_user(&mut self, user_key: &UserKey) {
+        if self.local_users.contains_key(user_key)
+            && !self.outstanding_disconnects.contains(user_key)
+        {
+            self.outstanding_disconnects.push_back(*user_key);
+        }
+    }
+
+    /// Returns whether the given User is a local User, connected with
+    /// `NaiaServer::connect_local_user()`
+    pub fn is_local_user(&self, user_key: &UserKey) -> bool {
+        return self.local_users.contains_key(user_key);
+    }
+
+    /// Sends an Event to the Server from a local User, which is emitted as a
+    /// ServerEvent::Event after passing through the incoming middleware &
+    /// validation, as an Event from a Client would. The rejection Event of
+    /// an Event which isn't valid is handed straight back to the local User.
+    /// Returns an error if there is no such local User, or the Event is
+    /// filtered out or rejected
+    pub fn send_local_event(
+        &mut self,
+        user_key: &UserKey,
+        event: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        let local_user = match self.local_users.get_mut(user_key) {
+            Some(local_user) => local_user,
+            None => return Err(NaiaError::NotConnected),
+        };
+        let event = match self
+            .incoming_event_middleware
+            .apply(user_key, event.get_typed_copy())
+        {
+            Some(event) => event,
+            None => return Err(NaiaError::FilteredOut),
+        };
+        let validation = match &self.event_validation_func {
+            Some(validation_func) => (validation_func.as_ref().as_ref())(user_key, &event),
+            None => EventValidation::Accept,
+        };
+        match validation {
+            EventValidation::Accept => {
+                self.outstanding_local_events
+                    .push_back(ServerEvent::Event(*user_key, event));
+                return Ok(());
+            }
+            EventValidation::Reject(rejection_event) => {
+                if let Some(rejection_event) = rejection_event {
+                    local_user.queue_event(rejection_event.get_typed_copy());
+                }
+                return Err(NaiaError::FilteredOut);
+            }
+        }
+    }
+
+    /// Sends a command to the Server from a local User, for the Server's next
+    /// tick, which is emitted as a ServerEvent::Command just before the Tick
+    /// event of that tick, as the command of a Client would. Sending another
+    /// command before then replaces the first. Once a local User has sent a
+    /// command, the missing command policy applies to every tick it doesn't
+    /// send one for. Returns an error if there is no such local User
+    pub fn send_local_command(
+        &mut self,
+        user_key: &UserKey,
+        command: &impl Event<T>,
+    ) -> Result<(), NaiaError> {
+        let local_user = match self.local_users.get_mut(user_key) {
+            Some(local_user) => local_user,
+            None => return Err(NaiaError::NotConnected),
+        };
+        local_user.queue_command(
+            self.current_tick.wrapping_add(1),
+            command.get_typed_copy(),
+            self.current_tick,
+        );
+        return Ok(());
+    }
+
+    /// Receives the next Event handed over to a local User, or None if there
+    /// is no such local User, or it has no new Event
+    pub fn receive_local(&mut self, user_key: &UserKey) -> Option<HostEvent<T>> {
+        return self.local_users.get_mut(user_key)?.pop_event();
+    }
+
+    /// Gets the keys of every Entity in scope for a local User
+    pub fn get_local_entity_keys(&self, user_key: &UserKey) -> Vec<EntityKey> {
+        return match self.local_users.get(user_key) {
+            Some(local_user) => local_user.entities_iter().copied().collect(),
+            None => Vec::new(),
+        };
+    }
+
+    /// Given an EntityKey, gets a reference to an Entity in scope for a local
+    /// User, which it shares with the Server
+    pub fn get_local_entity(
+        &self,
+        user_key: &UserKey,
+        key: &EntityKey,
+    ) -> Option<&Rc<RefCell<dyn Entity<U>>>> {
+        if !self.local_users.get(user_key)?.has_entity(key) {
+            return None;
+        }
+        return self.global_entity_store.get(*key);
+    }
+
+    /// Iterate through all currently connected Users
+    pub fn users_iter(&self) -> slotmap::dense::Iter<UserKey, User> {
+        return self.users.iter();
+    }
+
+    /// Get a User, given the associated UserKey
+    pub fn get_user(&self, user_key: &UserKey) -> Option<&User> {
+        return self.users.get(*user_key);
+    }
+
+    /// Gets the index of the endpoint the Client associated with the given
+    /// UserKey connected through, 0 being the address the Server was created
+    /// with, and the others being added with `NaiaServer::add_endpoint()`
+    pub fn get_user_endpoint(&self, user_key: &UserKey) -> Option<usize> {
+        return self.users.get(*user_key).map(|user| user.endpoint);
+    }
+
+    /// Gets metadata about the connection of the Client associated with the
+    /// given UserKey, such as its address, the endpoint & transport it
+    /// connected through, when it connected, and the options negotiated
+    /// during the handshake
+    pub fn get_connection_info(&self, user_key: &UserKey) -> Option<ConnectionInfo> {
+        let user = self.users.get(*user_key)?;
+        let connection = self.client_connections.get(user_key)?;
+        return Some(ConnectionInfo {
+            address: user.address,
+            endpoint: user.endpoint,
+            endpoint_address: self.endpoints.get_address(user.endpoint)?,
+            transport: Transport::current(),
+            connected_at: connection.get_connected_at(),
+            client_id: connection.get_client_id(),
+            compact_headers: connection.has_compact_headers(),
+            capabilities: connection.get_capabilities(),
+            migrated: connection.is_migrated(),
+            authenticated: connection.get_auth_event().is_some(),
+        });
+    }
+
+    /// Gets the auth Event the Client associated with the given UserKey
+    /// presented when connecting, which the closure given to `on_auth()`
+    /// accepted, so that the identity it carries can be looked up later.
+    /// Returns None if no auth closure was registered
+    pub fn get_auth_event(&self, user_key: &UserKey) -> Option<&T> {
+        return self
+            .client_connections
+            .get(user_key)
+            .and_then(|connection| connection.get_auth_event());
+    }
+
+    /// Exports the state of every registered Entity, every Room & the Users
+    /// in it, and the Entities each connected Client has in scope, so that
+    /// another host can take over with `import_host_state()`. Each connected
+    /// User is given a migration token, retrieved with
+    /// `get_migration_token()`, which should be sent to the Client (for
+    /// example with `send_blob()`) for it to present to the new host with
+    /// `NaiaClient::migrate_host()`
+    pub fn export_host_state(&mut self) -> Box<[u8]> {
+        let mut entities = Vec::new();
+        for (key, entity) in self.global_entity_store.iter() {
+            entities.push(self.get_host_state_entity(key, entity));
+        }
+
+        let mut users = Vec::new();
+        for (user_key, connection) in self.client_connections.iter() {
+            let migration_token = self.generate_migration_token();
+            self.migration_tokens.insert(*user_key, migration_token);
+            users.push(self.get_host_state_user(migration_token, connection));
+        }
+
+        // Users are identified by their migration token, as their keys are
+        // only meaningful to this host
+        let mut rooms = Vec::new();
+        for (key, room) in self.rooms.iter() {
+            let entity_keys = room
+                .entities_iter()
+                .map(|entity_key| KeyData::from(*entity_key).as_ffi())
+                .collect();
+            let migration_tokens = room
+                .users_iter()
+                .filter(|user_key| self.client_connections.contains_key(user_key))
+                .filter_map(|user_key| self.migration_tokens.get(user_key).copied())
+                .collect();
+            rooms.push((KeyData::from(key).as_ffi(), entity_keys, migration_tokens));
+        }
+
+        let mut out_bytes = Vec::new();
+        HostState {
+            entities,
+            rooms,
+            users,
+        }
+        .write(&mut out_bytes);
+        return out_bytes.into_boxed_slice();
+    }
+
+    /// Gets the migration token given to the User associated with the given
+    /// UserKey by the last call to `export_host_state()`
+    pub fn get_migration_token(&self, user_key: &UserKey) -> Option<u64> {
+        return self.migration_tokens.get(user_key).copied();
+    }
+
+    /// Imports the state exported by another host with
+    /// `export_host_state()`, registering its Entities & creating its Rooms.
+    /// Clients which connect with a migration token rejoin the Rooms they
+    /// were in, & keep the Entities they already have, provided they are
+    /// still in scope by the time their first update is sent. Returns the new keys of the imported Entities & Rooms, or None if the
+    /// state is malformed
+    pub fn import_host_state(&mut self, state: &[u8]) -> Option<ImportedKeys> {
+        let host_state = HostState::read(state)?;
+
+        let mut entity_keys = HashMap::new();
+        for (old_key, prefab_id, naia_id, payload) in host_state.entities {
+            let entity = match self.manifest.create_entity(naia_id, &payload) {
+                Some(entity) => entity.get_entity_ref(),
+                None => {
+                    warn!("could not import entity with naia id {}", naia_id);
+                    continue;
+                }
+            };
+            let new_key = match prefab_id {
+                Some(prefab_id) => self.register_entity_from_prefab(prefab_id, entity),
+                None => self.register_entity(entity),
+            };
+            entity_keys.insert(EntityKey::from(KeyData::from_ffi(old_key)), new_key);
+        }
+
+        let mut room_keys = HashMap::new();
+        let mut user_room_keys: HashMap<u64, Vec<RoomKey>> = HashMap::new();
+        for (old_key, old_entity_keys, migration_tokens) in host_state.rooms {
+            let new_key = self.create_room();
+            for migration_token in migration_tokens {
+                user_room_keys
+                    .entry(migration_token)
+                    .or_insert_with(Vec::new)
+                    .push(new_key);
+            }
+            for old_entity_key in old_entity_keys {
+                if let Some(entity_key) =
+                    entity_keys.get(&EntityKey::from(KeyData::from_ffi(old_entity_key)))
+                {
+                    self.room_add_entity(&new_key, entity_key);
+                }
+            }
+            room_keys.insert(RoomKey::from(KeyData::from_ffi(old_key)), new_key);
+        }
+
+        for (migration_token, old_local_keys, next_event_id, events) in host_state.users {
+            let mut local_keys = Vec::new();
+            for (old_entity_key, local_key, confirmed) in old_local_keys {
+                if let Some(entity_key) =
+                    entity_keys.get(&EntityKey::from(KeyData::from_ffi(old_entity_key)))
+                {
+                    local_keys.push((*entity_key, local_key, confirmed));
+                }
+            }
+            let events = events
+                .into_iter()
+                .filter_map(|(event_id, naia_id, payload)| {
+                    self.manifest
+                        .create_event(naia_id, &payload)
+                        .map(|event| (event_id, event))
+                })
+                .collect();
+            self.migrated_users.insert(
+                migration_token,
+                MigratedUser {
+                    local_keys,
+                    room_keys: user_room_keys.remove(&migration_token).unwrap_or_default(),
+                    next_event_id,
+                    events,
+                },
+            );
+        }
+
+        return Some(ImportedKeys {
+            entity_keys,
+            room_keys,
+            user_keys: HashMap::new(),
+        });
+    }
+
+    /// Hands the Client associated with the given UserKey off to another
+    /// Server, for example as it crosses into another shard of the world.
+    /// Returns a transfer containing the Entities in the User's scope & the
+    /// Events yet to be delivered to it, which should be passed to the other
+    /// Server, to be accepted with `accept_handoff()` before the Client
+    /// arrives. The Client is told to connect to the other Server at the given
+    /// address, and emits a `ClientEvent::Handoff`. The User is disconnected
+    /// from this Server once the Client has left. Returns None if there is no
+    /// such User
+    pub fn handoff_user(&mut self, user_key: &UserKey, address: SocketAddr) -> Option<Box<[u8]>> {
+        let connection = self.client_connections.get(user_key)?;
+
+        let mut entities = Vec::new();
+        for (entity_key, _, _) in connection.get_local_entity_keys() {
+            if let Some(entity) = self.global_entity_store.get(entity_key) {
+                entities.push(self.get_host_state_entity(entity_key, entity));
+            }
+        }
+        let transfer_token = self.generate_migration_token();
+        let users = vec![self.get_host_state_user(transfer_token, connection)];
+
+        let mut out_bytes = Vec::new();
+        HostState {
+            entities,
+            rooms: Vec::new(),
+            users,
+        }
+        .write(&mut out_bytes);
+
+        self.client_connections
+            .get_mut(user_key)
+            .unwrap()
+            .begin_handoff(&address, transfer_token);
+        return Some(out_bytes.into_boxed_slice());
+    }
+
+    /// Accepts a Client being handed off from another Server with
+    /// `handoff_user()`, registering the Entities in its scope. Once the
+    /// Client connects, it keeps the Entities which are in scope by the time
+    /// its first update is sent, and is sent the Events it had yet to receive.
+    /// Returns the new keys of the registered Entities, or None if the
+    /// transfer is malformed
+    pub fn accept_handoff(&mut self, transfer: &[u8]) -> Option<ImportedKeys> {
+        return self.import_host_state(transfer);
+    }
+
+    fn get_host_state_entity(
+        &self,
+        key: EntityKey,
+        entity: &Rc<RefCell<dyn Entity<U>>>,
+    ) -> HostStateEntity {
+        let entity = entity.as_ref().borrow();
+        let naia_id = self.manifest.get_entity_naia_id(&entity.get_type_id());
+        let mut payload = Vec::<u8>::new();
+        entity.write(&mut payload);
+        return (
+            KeyData::from(key).as_ffi(),
+            self.entity_prefab_ids.get(key).copied(),
+            naia_id,
+            payload.into_boxed_slice(),
+        );
+    }
+
+    fn get_host_state_user(
+        &self,
+        migration_token: u64,
+        connection: &ClientConnection<T, U>,
+    ) -> HostStateUser {
+        let local_keys = connection
+            .get_local_entity_keys()
+            .into_iter()
+            .map(|(entity_key, local_key, confirmed)| {
+                (KeyData::from(entity_key).as_ffi(), local_key, confirmed)
+            })
+            .collect();
+
+        let (next_event_id, undelivered_events) = connection.get_undelivered_events();
+        let mut events = Vec::new();
+        for (event_id, event) in undelivered_events {
+            let naia_id = self.manifest.get_event_naia_id(&event.get_type_id());
+            let mut payload = Vec::<u8>::new();
+            event.write(&mut payload);
+            events.push((event_id, naia_id, payload.into_boxed_slice()));
+        }
+
+        return (migration_token, local_keys, next_event_id, events);
+    }
+
+    /// Serializes the entire replicated world, being every registered Entity,
+    /// every Room & the Users in it, and the Entities each connected Client
+    /// has in scope, along with the current tick, so that it can be persisted
+    /// across a restart of the Server & restored with `load_world()`. As with
+    /// `export_host_state()`, each connected User is given a migration token,
+    /// which lets the Client keep its Entities & Rooms by reconnecting with
+    /// `NaiaClient::migrate_host()`
+    pub fn save_world(&mut self) -> Box<[u8]> {
+        let mut out_bytes = Vec::new();
+        out_bytes.write_u8(WORLD_SAVE_VERSION).unwrap();
+        // the Manifest is saved as well, so that a save is not loaded by a Server
+        // whose Entity types have since changed
+        self.manifest_descriptor.write(&mut out_bytes);
+        out_bytes.write_u16::<BigEndian>(self.current_tick).unwrap();
+        out_bytes.extend_from_slice(&self.export_host_state());
+        return out_bytes.into_boxed_slice();
+    }
+
+    /// Restores a world serialized with `save_world()`, registering its
+    /// Entities, creating its Rooms, and resuming from the saved tick. Returns
+    /// the new keys of the restored Entities & Rooms, or None if the save is
+    /// malformed, or was made by a Server with an incompatible Manifest
+    pub fn load_world(&mut self, save: &[u8]) -> Option<ImportedKeys> {
+        let mut reader = PacketReader::new(save);
+        let version = reader.get_cursor().read_u8().ok()?;
+        if version != WORLD_SAVE_VERSION {
+            warn!(
+                "world save has version {}, expected {}",
+                version, WORLD_SAVE_VERSION
+            );
+            return None;
+        }
+
+        let save_descriptor = ManifestDescriptor::read(&mut reader)?;
+        if let Some(report) = self.manifest_descriptor.compare(&save_descriptor) {
+            warn!("world save is incompatible with the manifest. {}", report);
+            return None;
+        }
+
+        let tick = reader.get_cursor().read_u16::<BigEndian>().ok()?;
+        let position = reader.get_cursor().position() as usize;
+        let imported_keys = self.import_host_state(&save[position..])?;
+        self.current_tick = tick;
+        return Some(imported_keys);
+    }
+
+    /// Serializes the world, as with `save_world()`, along with the state of
+    /// the connection to every Client: its id, packet sequence numbers,
+    /// undelivered Events, Entity keys & next tick batch index, so that a replacement Server process
+    /// on the same address can adopt the connections with
+    /// `import_live_state()`, without the Clients reconnecting. This Server
+    /// must stop listening before the replacement starts. Messages lost in
+    /// between are sent again, as Events which had yet to be acknowledged
+    /// are included
+    pub fn export_live_state(&mut self) -> Box<[u8]> {
+        let world = self.save_world();
+
+        let mut connections = Vec::new();
+        for (user_key, connection) in self.client_connections.iter() {
+            let user = match self.users.get(*user_key) {
+                Some(user) => user,
+                None => continue,
+            };
+            let migration_token = match self.migration_tokens.get(user_key) {
+                Some(migration_token) => *migration_token,
+                None => continue,
+            };
+            let auth_event = connection.get_auth_event().map(|auth_event| {
+                let naia_id = self.manifest.get_event_naia_id(&auth_event.get_type_id());
+                let mut payload = Vec::<u8>::new();
+                auth_event.get_boxed_event().write(&mut payload);
+                (naia_id, payload.into_boxed_slice())
+            });
+            connections.push(HostStateConnection {
+                migration_token,
+                user_key: KeyData::from(*user_key).as_ffi(),
+                address: user.address,
+                endpoint: user.endpoint,
+                timestamp: user.timestamp,
+                client_id: connection.get_client_id(),
+                capabilities: connection.get_capabilities().bits(),
+                sequence_state: connection.get_sequence_state(),
+                revisions: connection.get_revisions(),
+                next_batch_index: connection.get_next_batch_index(),
+                auth_event,
+                identity: self.user_identities.get(user_key).cloned(),
+            });
+        }
+
+        let mut out_bytes = Vec::new();
+        out_bytes.write_u8(LIVE_STATE_VERSION).unwrap();
+        out_bytes
+            .write_u32::<BigEndian>(world.len() as u32)
+            .unwrap();
+        out_bytes.extend_from_slice(&world);
+        out_bytes
+            .write_u32::<BigEndian>(connections.len() as u32)
+            .unwrap();
+        for connection in connections.iter() {
+            connection.write(&mut out_bytes);
+        }
+        return out_bytes.into_boxed_slice();
+    }
+
+    /// Restores the world & adopts the connections exported by a previous
+    /// Server process with `export_live_state()`. Each adopted Client carries
+    /// on where it left off, back in the Rooms it was in, & a Connection event
+    /// is emitted for it like for any Client. Returns the new keys of the
+    /// restored Entities, Rooms & Users, or None if the state is malformed,
+    /// or was exported by a Server with an incompatible Manifest
+    pub fn import_live_state(&mut self, state: &[u8]) -> Option<ImportedKeys> {
+        let mut reader = PacketReader::new(state);
+        let version = reader.get_cursor().read_u8().ok()?;
+        if version != LIVE_STATE_VERSION {
+            warn!(
+                "live state has version {}, expected {}",
+                version, LIVE_STATE_VERSION
+            );
+            return None;
+        }
+        let world_length = reader.get_cursor().read_u32::<BigEndian>().ok()? as usize;
+        let world_start = reader.get_cursor().position() as usize;
+        let world = state.get(world_start..world_start + world_length)?;
+        reader
+            .get_cursor()
+            .set_position((world_start + world_length) as u64);
+        let connection_count = reader.get_cursor().read_u32::<BigEndian>().ok()?;
+        let mut connections = Vec::new();
+        for _ in 0..connection_count {
+            connections.push(HostStateConnection::read(&mut reader)?);
+        }
+
+        let mut imported_keys = self.load_world(world)?;
+        for adopted in connections {
+            let migrated_user = match self.migrated_users.remove(&adopted.migration_token) {
+                Some(migrated_user) => migrated_user,
+                None => continue,
+            };
+            if self.address_to_user_key_map.contains_key(&adopted.address)
+                || self.client_ids.contains_key(&adopted.client_id)
+            {
+                warn!("could not adopt connection to {}", adopted.address);
+                continue;
+            }
+
+            let user = User::new(adopted.address, adopted.endpoint, adopted.timestamp);
+            let user_key = self.users.insert(user);
+            let mut connection = ClientConnection::new(
+                adopted.address,
+                Some(&self.mut_handler),
+                &self.config,
+                adopted.client_id,
+            );
+            connection.set_capabilities(Capabilities::from_bits(adopted.capabilities));
+            let (local_sequence, remote_sequence, ack_bitfield) = adopted.sequence_state;
+            connection.resume_sequences(local_sequence, remote_sequence, ack_bitfield);
+            connection.restore_revisions(adopted.revisions);
+            connection.restore_local_entity_keys(migrated_user.local_keys);
+            for room_key in migrated_user.room_keys.iter() {
+                self.room_add_user(room_key, &user_key);
+            }
+            connection.restore_next_batch_index(adopted.next_batch_index);
+            connection.restore_undelivered_events(
+                migrated_user.next_event_id,
+                migrated_user
+                    .events
+                    .iter()
+                    .map(|(event_id, event)| (*event_id, event.get_boxed_event()))
+                    .collect(),
+            );
+            if let Some((naia_id, payload)) = adopted.auth_event {
+                if let Some(auth_event) = self.manifest.create_auth_event(naia_id, &payload) {
+                    connection.set_auth_event(auth_event);
+                }
+            }
+            connection.set_current_tick(self.current_tick);
+            // the Client may have been ticking at the previous process's rate
+            connection.queue_tick_rate_change(self.current_tick, self.tick_interval);
+            connection.set_packet_framing(self.packet_framing.clone());
+
+            if let Some(identity) = adopted.identity {
+                self.identity_users
+                    .entry(identity.clone())
+                    .or_insert_with(Vec::new)
+                    .push(user_key);
+                self.user_identities.insert(user_key, identity);
+            }
+            self.address_to_user_key_map
+                .insert(adopted.address, user_key);
+            self.client_ids.insert(adopted.client_id, user_key);
+            self.client_connections.insert(user_key, connection);
+            self.outstanding_local_events
+                .push_back(ServerEvent::Connection(user_key));
+            imported_keys
+                .user_keys
+                .insert(UserKey::from(KeyData::from_ffi(adopted.user_key)), user_key);
+        }
+        return Some(imported_keys);
+    }
+
+    fn refresh_room_snapshots(&mut self) {
+        self.room_snapshots.clear();
+        for (room_key, room) in self.rooms.iter() {
+            let mut room_entities = Vec::new();
+            for entity_key in room.entities_iter() {
+                if let Some(entity) = self.global_entity_store.get(*entity_key) {
+                    room_entities.push((*entity_key, entity));
+                }
+            }
+            if !room_entities.is_empty() {
+                self.room_snapshots
+                    .insert(room_key, RoomSnapshot::new(&self.manifest, room_entities));
+            }
+        }
+    }
+
+    // Clients which have yet to receive their initial world snapshot are sent
+    // the cached snapshot of a Room they're in, in bulk
+    fn add_entities_from_room_snapshots(&mut self) {
+        let min_scope_priority = self
+            .load_shedder
+            .get_tier()
+            .map_or(0.0, |tier| tier.min_scope_priority);
+        if let Some(scope_func) = &self.scope_entity_func {
+            for (room_key, room) in self.rooms.iter() {
+                if let Some(snapshot) = self.room_snapshots.get(room_key) {
+                    for user_key in room.users_iter() {
+                        if let Some(user_connection) = self.client_connections.get_mut(user_key) {
+                            if !user_connection.is_snapshot_pending() {
+                                continue;
+                            }
+
+                            let mut snapshot_entities = Vec::new();
+                            for (index, entity_key) in snapshot.get_entity_keys().iter().enumerate()
+                            {
+                                if !room.has_entity(entity_key)
+                                    || user_connection.has_entity(entity_key)
+                                {
+                                    continue;
+                                }
+                                if let Some(entity) = self.global_entity_store.get(*entity_key) {
+                                    if (scope_func.as_ref().as_ref())(
+                                        &room_key,
+                                        user_key,
+                                        entity_key,
+                                        entity.as_ref().borrow().get_typed_copy(),
+                                    ) && !NaiaServer::<T, U>::is_shed_from_scope(
+                                        &self.entity_priority_func,
+                                        min_scope_priority,
+                                        user_key,
+                                        entity_key,
+                                        entity,
+                                    ) {
+                                        snapshot_entities.push((
+                                            index,
+                                            *entity_key,
+                                            entity.clone(),
+                                        ));
+                                    }
+                                }
+                            }
+
+                            for entity_key in user_connection
+                                .add_entities_from_snapshot(snapshot, snapshot_entities)
+                            {
+                                self.outstanding_scope_events.push_back(
+                                    ServerEvent::EntityEnteredScope(*user_key, entity_key),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_entity_scopes(&mut self) {
+        self.add_entities_from_room_snapshots();
+        let min_scope_priority = self
+            .load_shedder
+            .get_tier()
+            .map_or(0.0, |tier| tier.min_scope_priority);
+
+        for (room_key, room) in self.rooms.iter_mut() {
+            while let Some((removed_user, removed_entity)) = room.pop_removal_queue() {
+                let removed = match self.local_users.get_mut(&removed_user) {
+                    Some(local_user) => local_user.remove_entity(&removed_entity, None),
+                    None => match self.client_connections.get_mut(&removed_user) {
+                        Some(user_connection) => {
+                            user_connection.remove_entity(&removed_entity, None)
+                        }
+                        None => false,
+                    },
+                };
+                if removed {
+                    self.outstanding_scope_events
+                        .push_back(ServerEvent::EntityLeftScope(removed_user, removed_entity));
+                }
+            }
+
+            if let Some(scope_func) = &self.scope_entity_func {
+                for user_key in room.users_iter() {
                     for entity_key in room.entities_iter() {
                         if let Some(entity) = self.global_entity_store.get(*entity_key) {
+                            // the Entities of local Users are shared, not replicated
+                            if let Some(local_user) = self.local_users.get_mut(user_key) {
+                                let should_be_in_scope = (scope_func.as_ref().as_ref())(
+                                    &room_key,
+                                    user_key,
+                                    entity_key,
+                                    entity.as_ref().borrow().get_typed_copy(),
+                                );
+                                let changed = match should_be_in_scope {
+                                    true => local_user.add_entity(entity_key),
+                                    false => local_user.remove_entity(entity_key, None),
+                                };
+                                if changed {
+                                    self.outstanding_scope_events.push_back(
+                                        match should_be_in_scope {
+                                            true => ServerEvent::EntityEnteredScope(
+                                                *user_key,
+                                                *entity_key,
+                                            ),
+                                            false => {
+                                                ServerEvent::EntityLeftScope(*user_key, *entity_key)
+                                            }
+                                        },
+                                    );
+                                }
+                                continue;
+                            }
                             if let Some(user_connection) = self.client_connections.get_mut(user_key)
                             {
                                 let currently_in_scope = user_connection.has_entity(entity_key);
@@ -565,20 +3353,45 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
                                     user_key,
                                     entity_key,
                                     entity.as_ref().borrow().get_typed_copy(),
-                                );
+                                )
+                                    && !NaiaServer::<T, U>::is_shed_from_scope(
+                                        &self.entity_priority_func,
+                                        min_scope_priority,
+                                        user_key,
+                                        entity_key,
+                                        entity,
+                                    );
                                 if should_be_in_scope {
                                     if !currently_in_scope {
                                         // add entity to the connections local scope
                                         if let Some(entity) =
                                             self.global_entity_store.get(*entity_key)
                                         {
-                                            user_connection.add_entity(entity_key, entity);
+                                            if user_connection.add_entity(
+                                                entity_key,
+                                                entity,
+                                                self.entity_prefab_ids.get(*entity_key).copied(),
+                                            ) {
+                                                self.outstanding_scope_events.push_back(
+                                                    ServerEvent::EntityEnteredScope(
+                                                        *user_key,
+                                                        *entity_key,
+                                                    ),
+                                                );
+                                            }
                                         }
                                     }
                                 } else {
                                     if currently_in_scope {
                                         // remove entity from the connections local scope
-                                        user_connection.remove_entity(entity_key);
+                                        if user_connection.remove_entity(entity_key, None) {
+                                            self.outstanding_scope_events.push_back(
+                                                ServerEvent::EntityLeftScope(
+                                                    *user_key,
+                                                    *entity_key,
+                                                ),
+                                            );
+                                        }
                                     }
                                 }
                             }
@@ -589,20 +3402,570 @@ impl<T: EventType, U: EntityType> NaiaServer<T, U> {
         }
     }
 
+    // Whether the active tier of load shedding removes the Entity from the
+    // User's scope, as its priority for the User is below the tier's minimum
+    fn is_shed_from_scope(
+        priority_func: &Option<Rc<Box<dyn Fn(&UserKey, &EntityKey, U) -> f32>>>,
+        min_scope_priority: f32,
+        user_key: &UserKey,
+        entity_key: &EntityKey,
+        entity: &Rc<RefCell<dyn Entity<U>>>,
+    ) -> bool {
+        if min_scope_priority <= 0.0 {
+            return false;
+        }
+        let priority = match priority_func {
+            Some(priority_func) => (priority_func.as_ref().as_ref())(
+                user_key,
+                entity_key,
+                entity.as_ref().borrow().get_typed_copy(),
+            ),
+            None => 1.0,
+        };
+        return priority < min_scope_priority;
+    }
+
     async fn internal_send_connectionless(
-        sender: &mut MessageSender,
+        sender: &mut EndpointSender,
         packet_type: PacketType,
         packet: Packet,
-    ) {
+    ) -> Result<(), NaiaError> {
         let new_payload =
             naia_shared::utils::write_connectionless_payload(packet_type, packet.payload());
-        sender
+        if let Err(error) = sender
             .send(Packet::new_raw(packet.address(), new_payload))
             .await
-            .expect("send failed!");
+        {
+            return Err(NaiaError::Wrapped(error));
+        }
+        return Ok(());
+    }
+
+    async fn internal_send_unconnected_request(
+        sender: &mut EndpointSender,
+        address: SocketAddr,
+        kind: UnconnectedKind,
+        data: Vec<u8>,
+    ) -> Result<(), NaiaError> {
+        if let Some(request) = UnconnectedMessage::new(kind, 0, &data) {
+            let mut request_bytes = Vec::new();
+            request.write(&mut request_bytes);
+            return NaiaServer::<T, U>::internal_send_connectionless(
+                sender,
+                PacketType::UnconnectedRequest,
+                Packet::new(address, request_bytes),
+            )
+            .await;
+        }
+        return Err(NaiaError::PayloadTooLarge(data.len()));
+    }
+
+    // A random token identifying a User migrating or transferring to another
+    // Server
+    fn generate_migration_token(&self) -> u64 {
+        let mut token_bytes = [0; 8];
+        fill_random(self.random_source.as_ref(), &mut token_bytes);
+        return u64::from_be_bytes(token_bytes);
+    }
+
+    // The status sent to status queries & master servers, with the current
+    // user count
+    fn get_current_status(&self) -> ServerStatus {
+        let mut status = self.server_status.clone();
+        status.user_count = self.client_connections.len().min(65535) as u16;
+        return status;
     }
 }
 
 fn to_entity_mutator(eref: &Rc<RefCell<ServerEntityMutator>>) -> Rc<RefCell<dyn EntityMutator>> {
     eref.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        net::SocketAddr,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use naia_client::{ClientEvent, ConnectFailure, NaiaClient};
+    use naia_example_shared::{
+        manifest_load, AuthEvent, ExampleEntity, ExampleEvent, PointEntity, StringEvent,
+    };
+    use naia_shared::{find_my_ip_address, LinkConditionerConfig};
+
+    use super::{
+        super::{
+            bot_client::BotClient, duplicate_login_policy::DuplicateLoginPolicy,
+            host_event::HostEvent, listen_server::ListenServer,
+        },
+        Config, NaiaServer, RoomKey, ServerEvent, UserKey,
+    };
+
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.tick_interval = Duration::from_millis(10);
+        config.heartbeat_interval = Duration::from_millis(50);
+        config.disconnection_timeout_duration = Duration::from_millis(500);
+        return config;
+    }
+
+    async fn new_server(address: SocketAddr) -> NaiaServer<ExampleEvent, ExampleEntity> {
+        let mut server = NaiaServer::new(address, manifest_load(), Some(test_config())).await;
+        server.on_scope_entity(Rc::new(Box::new(|_, _, _, _| true)));
+        return server;
+    }
+
+    // Polls the Server until its next tick, which sends what was queued.
+    // Returns the Users which have connected
+    async fn poll_server(server: &mut NaiaServer<ExampleEvent, ExampleEntity>) -> Vec<UserKey> {
+        let mut connections = Vec::new();
+        loop {
+            match server.receive().await {
+                Ok(ServerEvent::Connection(user_key)) => connections.push(user_key),
+                Ok(ServerEvent::Tick) => {
+                    server.tick().await.unwrap();
+                    return connections;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Drains the Client. Returns the number of Entities created, and whether
+    // the Client has connected or disconnected since
+    fn poll_client(client: &mut NaiaClient<ExampleEvent, ExampleEntity>) -> (usize, bool) {
+        let mut created = 0;
+        let mut reconnected = false;
+        loop {
+            match client.receive() {
+                Ok(ClientEvent::None) => return (created, reconnected),
+                Ok(ClientEvent::CreateEntity(_)) => created += 1,
+                Ok(ClientEvent::Connection) | Ok(ClientEvent::Disconnection) => reconnected = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn room_has_user(
+        server: &NaiaServer<ExampleEvent, ExampleEntity>,
+        room_key: &RoomKey,
+        user_key: &UserKey,
+    ) -> bool {
+        return server
+            .rooms_iter()
+            .find(|(key, _)| key == room_key)
+            .map_or(false, |(_, room)| {
+                room.users_iter().any(|key| key == user_key)
+            });
+    }
+
+    #[tokio::test]
+    async fn live_state_keeps_users_in_their_rooms() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address = SocketAddr::new(ip_address, 14301);
+        let mut server = new_server(address).await;
+        let mut client = NaiaClient::new(address, manifest_load(), Some(test_config()), None);
+
+        let room_key = server.create_room();
+        let entity_key = server.register_entity(PointEntity::new(0, 0, "alpha", "red").wrap());
+        server.room_add_entity(&room_key, &entity_key);
+
+        let mut user_key = None;
+        let mut created = 0;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while created == 0 && Instant::now() < deadline {
+            for connected_key in poll_server(&mut server).await {
+                server.room_add_user(&room_key, &connected_key);
+                user_key = Some(connected_key);
+            }
+            created += poll_client(&mut client).0;
+        }
+        assert!(created == 1);
+        let user_key = user_key.unwrap();
+        // until the Server hears the Client has created the Entity
+        for _ in 0..10 {
+            poll_server(&mut server).await;
+            poll_client(&mut client);
+        }
+
+        // a replacement process on the same address adopts the connection
+        let state = server.export_live_state();
+        drop(server);
+        let mut server = new_server(address).await;
+        let imported_keys = server.import_live_state(&state).unwrap();
+        let new_room_key = imported_keys.room_keys[&room_key];
+        let new_user_key = imported_keys.user_keys[&user_key];
+        assert!(room_has_user(&server, &new_room_key, &new_user_key));
+
+        // & the Client, still in the Room, keeps the Entity it has & is sent
+        // those added since
+        let entity_key = server.register_entity(PointEntity::new(1, 0, "beta", "blue").wrap());
+        server.room_add_entity(&new_room_key, &entity_key);
+        let mut created = 0;
+        let mut reconnected = false;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while created == 0 && Instant::now() < deadline {
+            poll_server(&mut server).await;
+            let (client_created, client_reconnected) = poll_client(&mut client);
+            created += client_created;
+            reconnected |= client_reconnected;
+        }
+        assert!(created == 1);
+        assert!(!reconnected);
+    }
+
+    #[tokio::test]
+    async fn atomic_ticks_are_applied_whole_despite_loss() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address = SocketAddr::new(ip_address, 14302);
+        let mut config = test_config();
+        config.atomic_entity_ticks = true;
+        let mut server = NaiaServer::new(address, manifest_load(), Some(config.clone())).await;
+        server.on_scope_entity(Rc::new(Box::new(|_, _, _, _| true)));
+        config.link_conditioner = Some(LinkConditionerConfig {
+            incoming_packet_loss: 0.4,
+            seed: 7,
+        });
+        let mut client = NaiaClient::new(address, manifest_load(), Some(config), None);
+
+        let room_key = server.create_room();
+        let mut entity_keys = Vec::new();
+        let mut connected = false;
+        let mut live_entities = HashSet::new();
+        let mut unapplied_events = 0;
+        let mut applied_ticks = 0;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            for user_key in poll_server(&mut server).await {
+                server.room_add_user(&room_key, &user_key);
+                connected = true;
+            }
+            // an Entity is added each tick, & every other one is removed on the
+            // next tick, often before its lost Create has been resent
+            if connected && entity_keys.len() < 20 {
+                if entity_keys.len() % 2 == 1 {
+                    server.deregister_entity(entity_keys[entity_keys.len() - 1]);
+                }
+                let index = entity_keys.len() as u8;
+                let entity = PointEntity::new(index, 0, "alpha", "red").wrap();
+                let entity_key = server.register_entity(entity);
+                server.room_add_entity(&room_key, &entity_key);
+                entity_keys.push(entity_key);
+            }
+            loop {
+                match client.receive() {
+                    Ok(ClientEvent::None) => break,
+                    Ok(ClientEvent::CreateEntity(local_key)) => {
+                        live_entities.insert(local_key);
+                        unapplied_events += 1;
+                    }
+                    Ok(ClientEvent::DeleteEntity(local_key, _)) => {
+                        live_entities.remove(&local_key);
+                        unapplied_events += 1;
+                    }
+                    Ok(ClientEvent::UpdateEntity(_, _)) => unapplied_events += 1,
+                    Ok(ClientEvent::TickApplied(_)) => {
+                        applied_ticks += 1;
+                        unapplied_events = 0;
+                    }
+                    _ => {}
+                }
+            }
+            if entity_keys.len() == 20 && live_entities.len() == 10 {
+                break;
+            }
+        }
+
+        // every Entity still registered arrives, in batches closed by a
+        // TickApplied, without the lost packets stalling later ticks
+        assert!(live_entities.len() == 10);
+        assert!(applied_ticks > 0);
+        assert!(unapplied_events == 0);
+    }
+
+    #[tokio::test]
+    async fn listen_server_host_shares_the_world_with_remote_clients() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address = SocketAddr::new(ip_address, 14303);
+        let mut listen_server =
+            ListenServer::new(address, manifest_load(), Some(test_config())).await;
+        let host_key = listen_server.get_host_user_key();
+        let mut client = NaiaClient::new(address, manifest_load(), Some(test_config()), None);
+
+        let server = listen_server.get_server_mut();
+        server.on_scope_entity(Rc::new(Box::new(|_, _, _, _| true)));
+        let room_key = server.create_room();
+        let entity_key = server.register_entity(PointEntity::new(0, 0, "alpha", "red").wrap());
+        server.room_add_entity(&room_key, &entity_key);
+        listen_server
+            .host_send_event(&StringEvent::new("from host".to_string()))
+            .unwrap();
+
+        let mut host_connected = false;
+        let mut host_event_received = false;
+        let mut host_greeted = false;
+        let mut client_created = 0;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while (!host_event_received || !host_greeted || client_created == 0)
+            && Instant::now() < deadline
+        {
+            loop {
+                match listen_server.receive().await {
+                    Ok(ServerEvent::Connection(user_key)) => {
+                        // the host player connects first, without a socket
+                        host_connected |= user_key == host_key;
+                        assert!(host_connected);
+                        let server = listen_server.get_server_mut();
+                        server.room_add_user(&room_key, &user_key);
+                        server
+                            .queue_event(&user_key, &StringEvent::new("welcome".to_string()))
+                            .unwrap();
+                    }
+                    Ok(ServerEvent::Event(user_key, ExampleEvent::StringEvent(event))) => {
+                        host_event_received |=
+                            user_key == host_key && event.message.get() == "from host";
+                    }
+                    Ok(ServerEvent::Tick) => {
+                        listen_server.send_all_updates().await.unwrap();
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            while let Some(event) = listen_server.host_receive() {
+                if let HostEvent::Event(ExampleEvent::StringEvent(event)) = event {
+                    host_greeted |= event.message.get() == "welcome";
+                }
+            }
+            client_created += poll_client(&mut client).0;
+        }
+        assert!(host_event_received);
+        assert!(host_greeted);
+        assert!(client_created == 1);
+
+        // the host player shares the Server's Entity, rather than a replica
+        assert!(listen_server.host_entity_keys() == vec![entity_key]);
+        let host_entity = listen_server.host_get_entity(&entity_key).unwrap().clone();
+        let server_entity = listen_server
+            .get_server_mut()
+            .get_entity(entity_key)
+            .unwrap()
+            .clone();
+        assert!(Rc::ptr_eq(&host_entity, &server_entity));
+    }
+
+    #[tokio::test]
+    async fn bot_clients_are_served_like_remote_clients() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let mut server = new_server(SocketAddr::new(ip_address, 14304)).await;
+        let room_key = server.create_room();
+        let entity_key = server.register_entity(PointEntity::new(0, 0, "alpha", "red").wrap());
+        server.room_add_entity(&room_key, &entity_key);
+
+        // only the first bot is in the Room
+        let bot = BotClient::connect(&mut server);
+        let other_bot = BotClient::connect(&mut server);
+        server.room_add_user(&room_key, &bot.get_user_key());
+        bot.send_event(&mut server, &StringEvent::new("from bot".to_string()))
+            .unwrap();
+
+        let mut connections = Vec::new();
+        let mut bot_event_received = false;
+        loop {
+            match server.receive().await {
+                Ok(ServerEvent::Connection(user_key)) => {
+                    server
+                        .queue_event(&user_key, &StringEvent::new("welcome".to_string()))
+                        .unwrap();
+                    connections.push(user_key);
+                }
+                Ok(ServerEvent::Event(user_key, ExampleEvent::StringEvent(event))) => {
+                    bot_event_received =
+                        user_key == bot.get_user_key() && event.message.get() == "from bot";
+                }
+                Ok(ServerEvent::Tick) => {
+                    server.tick().await.unwrap();
+                    break;
+                }
+                _ => {}
+            }
+        }
+        assert!(connections == vec![bot.get_user_key(), other_bot.get_user_key()]);
+        assert!(bot_event_received);
+
+        let mut greeted = false;
+        let mut created = Vec::new();
+        while let Some(event) = bot.receive(&mut server) {
+            match event {
+                HostEvent::Event(ExampleEvent::StringEvent(event)) => {
+                    greeted = event.message.get() == "welcome"
+                }
+                HostEvent::CreateEntity(key) => created.push(key),
+                _ => {}
+            }
+        }
+        assert!(greeted);
+        assert!(created == vec![entity_key]);
+        assert!(bot.entity_keys(&server) == vec![entity_key]);
+        assert!(bot.get_entity(&server, &entity_key).is_some());
+        assert!(other_bot.entity_keys(&server).is_empty());
+        assert!(other_bot.get_entity(&server, &entity_key).is_none());
+
+        other_bot.disconnect(&mut server);
+        loop {
+            match server.receive().await {
+                Ok(ServerEvent::Disconnection(user_key, _)) => {
+                    assert!(user_key == other_bot.get_user_key());
+                    break;
+                }
+                Ok(ServerEvent::Tick) => {
+                    server.tick().await.unwrap();
+                }
+                _ => {}
+            }
+        }
+        assert!(server.is_local_user(&bot.get_user_key()));
+        assert!(!server.is_local_user(&other_bot.get_user_key()));
+    }
+
+    async fn new_login_server(
+        address: SocketAddr,
+        policy: DuplicateLoginPolicy,
+    ) -> NaiaServer<ExampleEvent, ExampleEntity> {
+        let mut server = new_server(address).await;
+        server.on_auth(Rc::new(Box::new(|_, _| true)));
+        server.on_identity(Rc::new(Box::new(|_, auth_event| match auth_event {
+            ExampleEvent::AuthEvent(auth_event) => Some(auth_event.username.get().clone()),
+            _ => None,
+        })));
+        server.set_duplicate_login_policy(policy);
+        return server;
+    }
+
+    fn new_login_client(address: SocketAddr) -> NaiaClient<ExampleEvent, ExampleEntity> {
+        let auth = ExampleEvent::AuthEvent(AuthEvent::new("charlie", "12345"));
+        return NaiaClient::new(address, manifest_load(), Some(test_config()), Some(auth));
+    }
+
+    // Polls the Server & the given Clients until each Client which is given
+    // an expected event has emitted a matching one, or a deadline passes.
+    // Returns the events of the Server which aren't Ticks
+    async fn poll_until(
+        server: &mut NaiaServer<ExampleEvent, ExampleEntity>,
+        clients: &mut [&mut NaiaClient<ExampleEvent, ExampleEntity>],
+        expected: &[Option<fn(&ClientEvent<ExampleEvent>) -> bool>],
+    ) -> Vec<ServerEvent<ExampleEvent>> {
+        let mut server_events = Vec::new();
+        let mut matched: Vec<bool> = expected.iter().map(Option::is_none).collect();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while matched.contains(&false) && Instant::now() < deadline {
+            loop {
+                match server.receive().await {
+                    Ok(ServerEvent::Tick) => {
+                        server.tick().await.unwrap();
+                        break;
+                    }
+                    Ok(event) => server_events.push(event),
+                    Err(_) => {}
+                }
+            }
+            for (index, client) in clients.iter_mut().enumerate() {
+                loop {
+                    match client.receive() {
+                        Ok(ClientEvent::None) => break,
+                        Ok(event) => {
+                            if let Some(expected) = expected[index] {
+                                matched[index] |= expected(&event);
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        assert!(!matched.contains(&false));
+        return server_events;
+    }
+
+    #[tokio::test]
+    async fn duplicate_login_kicks_the_old_client() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address = SocketAddr::new(ip_address, 14305);
+        let mut server = new_login_server(address, DuplicateLoginPolicy::KickOld).await;
+
+        let mut old_client = new_login_client(address);
+        let server_events = poll_until(
+            &mut server,
+            &mut [&mut old_client],
+            &[Some(|event| matches!(event, ClientEvent::Connection))],
+        )
+        .await;
+        let old_user_key = match server_events.as_slice() {
+            [ServerEvent::Connection(user_key)] => *user_key,
+            _ => panic!("expected one Connection"),
+        };
+        assert!(server.get_user_identity(&old_user_key) == Some("charlie"));
+
+        // the same identity logging in again replaces the old Client
+        let mut new_client = new_login_client(address);
+        let server_events = poll_until(
+            &mut server,
+            &mut [&mut old_client, &mut new_client],
+            &[
+                Some(|event| matches!(event, ClientEvent::Kicked)),
+                Some(|event| matches!(event, ClientEvent::Connection)),
+            ],
+        )
+        .await;
+        let mut new_user_key = None;
+        let mut old_disconnected = false;
+        for event in server_events {
+            match event {
+                ServerEvent::Connection(user_key) => new_user_key = Some(user_key),
+                ServerEvent::Disconnection(user_key, _) => {
+                    old_disconnected |= user_key == old_user_key
+                }
+                _ => {}
+            }
+        }
+        assert!(old_disconnected);
+        let new_user_key = new_user_key.unwrap();
+        assert!(new_user_key != old_user_key);
+        assert!(server.get_user_identity(&new_user_key) == Some("charlie"));
+    }
+
+    #[tokio::test]
+    async fn duplicate_login_refuses_the_new_client() {
+        let ip_address = find_my_ip_address().expect("can't find ip address");
+        let address = SocketAddr::new(ip_address, 14306);
+        let mut server = new_login_server(address, DuplicateLoginPolicy::RejectNew).await;
+
+        let mut old_client = new_login_client(address);
+        poll_until(
+            &mut server,
+            &mut [&mut old_client],
+            &[Some(|event| matches!(event, ClientEvent::Connection))],
+        )
+        .await;
+
+        // the same identity logging in again is refused, & the old Client kept
+        let mut new_client = new_login_client(address);
+        let server_events = poll_until(
+            &mut server,
+            &mut [&mut old_client, &mut new_client],
+            &[
+                None,
+                Some(|event| matches!(event, ClientEvent::ConnectFailed(ConnectFailure::Refused))),
+            ],
+        )
+        .await;
+        assert!(!server_events.iter().any(|event| matches!(
+            event,
+            ServerEvent::Connection(_) | ServerEvent::Disconnection(_, _)
+        )));
+        assert!(server.users_iter().count() == 1);
+    }
+}
@@ -0,0 +1,11 @@
+/// Determines what happens to the Entity updates of a Client whose
+/// replication has been paused with `NaiaServer::pause_replication()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PausePolicy {
+    /// Mutations accumulate while paused, and only the Properties which have
+    /// changed are sent once replication resumes
+    Buffer,
+    /// Mutations are discarded while paused, and the full state of every
+    /// Entity in scope is sent once replication resumes
+    Discard,
+}
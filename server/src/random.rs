@@ -0,0 +1,23 @@
+use ring::{
+    hmac,
+    rand::{self, SecureRandom},
+};
+
+use naia_shared::RandomSource;
+
+/// Fills the given buffer from the given RandomSource, or from the system's
+/// secure random number generator if there is none
+pub fn fill_random(random_source: Option<&RandomSource>, bytes: &mut [u8]) {
+    match random_source {
+        Some(random_source) => random_source.fill(bytes),
+        None => rand::SystemRandom::new().fill(bytes).unwrap(),
+    }
+}
+
+/// Generates a key to sign challenges with, from the given RandomSource, or
+/// from the system's secure random number generator if there is none
+pub fn generate_hmac_key(random_source: Option<&RandomSource>) -> hmac::Key {
+    let mut key_bytes = [0; 32];
+    fill_random(random_source, &mut key_bytes);
+    return hmac::Key::new(hmac::HMAC_SHA256, &key_bytes);
+}
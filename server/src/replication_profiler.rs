@@ -0,0 +1,354 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use super::{entities::entity_key::entity_key::EntityKey, user::user_key::UserKey};
+
+/// The bytes, packets & messages spent replicating Entities to Clients. Bytes
+/// count the Entity messages written into packets, but not packet headers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplicationCost {
+    /// The number of bytes of the Entity messages sent
+    pub bytes: usize,
+    /// The number of packets which carried at least one of the messages
+    pub packets: usize,
+    /// The number of Entity messages sent, creations, updates & deletions
+    pub messages: usize,
+}
+
+/// The cost of replicating a single Entity over a profiling window
+#[derive(Clone, Debug)]
+pub struct EntityReplicationCost {
+    /// The Entity which was replicated
+    pub entity_key: EntityKey,
+    /// The NaiaId of the Entity's type, or None if the Entity was only deleted
+    /// during the window, without its type having been recorded before
+    pub naia_id: Option<u16>,
+    /// The cost summed across every Client
+    pub total: ReplicationCost,
+    /// The cost for each Client the Entity was replicated to, most costly
+    /// first
+    pub users: Vec<(UserKey, ReplicationCost)>,
+}
+
+/// The cost of replicating every Entity of a type over a profiling window
+#[derive(Clone, Debug)]
+pub struct TypeReplicationCost {
+    /// The NaiaId of the type
+    pub naia_id: u16,
+    /// The name of the type, as registered in the Manifest
+    pub type_name: Option<&'static str>,
+    /// The number of Entities of the type which were replicated
+    pub entity_count: usize,
+    /// The cost summed across every Client. A packet which carried messages
+    /// of several Entities of the type is counted once
+    pub total: ReplicationCost,
+    /// The cost for each Client Entities of the type were replicated to, most
+    /// costly first
+    pub users: Vec<(UserKey, ReplicationCost)>,
+}
+
+/// A report of the Entities & types which cost the most to replicate over a
+/// profiling window, see `NaiaServer::enable_replication_profiler()`
+#[derive(Clone, Debug)]
+pub struct ReplicationProfile {
+    /// The length of the window the costs were recorded over
+    pub window: Duration,
+    /// The cost of replicating every Entity over the window, summed. A packet
+    /// which carried messages of several Entities is counted once
+    pub total: ReplicationCost,
+    /// The most costly Entities, by bytes, most costly first
+    pub entities: Vec<EntityReplicationCost>,
+    /// The most costly types, by bytes, most costly first
+    pub types: Vec<TypeReplicationCost>,
+}
+
+#[derive(Debug, Default)]
+struct ProfileWindow {
+    duration: Duration,
+    packets: usize,
+    entity_costs: HashMap<EntityKey, HashMap<UserKey, ReplicationCost>>,
+    type_costs: HashMap<u16, HashMap<UserKey, ReplicationCost>>,
+    entity_types: HashMap<EntityKey, u16>,
+}
+
+/// Records the bytes & packets each Entity consumes for each Client over a
+/// rolling window, so that the most costly can be reported
+#[derive(Debug)]
+pub struct ReplicationProfiler {
+    window: Duration,
+    window_start: Instant,
+    current: ProfileWindow,
+    completed: Option<ProfileWindow>,
+    // the type of each Entity, remembered so that its deletion can be counted
+    // once the Entity itself is gone
+    entity_types: HashMap<EntityKey, u16>,
+    removed_entities: HashSet<EntityKey>,
+    // the messages written into the packet being built, as (Entity, bytes)
+    packet_messages: Vec<(EntityKey, usize)>,
+}
+
+impl ReplicationProfiler {
+    /// Creates a new ReplicationProfiler, given the length of each window
+    pub fn new(window: Duration) -> Self {
+        ReplicationProfiler {
+            window,
+            window_start: Instant::now(),
+            current: ProfileWindow::default(),
+            completed: None,
+            entity_types: HashMap::new(),
+            removed_entities: HashSet::new(),
+            packet_messages: Vec::new(),
+        }
+    }
+
+    /// Completes the current window, once it has lasted the window's length
+    pub fn update(&mut self) {
+        let elapsed = self.window_start.elapsed();
+        if elapsed < self.window {
+            return;
+        }
+        let mut completed = std::mem::take(&mut self.current);
+        completed.duration = elapsed;
+        self.completed = Some(completed);
+        self.window_start = Instant::now();
+        for entity_key in self.removed_entities.drain() {
+            self.entity_types.remove(&entity_key);
+        }
+    }
+
+    /// Records an Entity message written into the packet being built, given
+    /// the NaiaId of the Entity's type, if the message carries the Entity
+    pub fn record_message(&mut self, entity_key: EntityKey, naia_id: Option<u16>, bytes: usize) {
+        if let Some(naia_id) = naia_id {
+            self.entity_types.insert(entity_key, naia_id);
+        }
+        self.packet_messages.push((entity_key, bytes));
+    }
+
+    /// Records that the packet being built has been completed, to be sent to
+    /// the given User, counting it once for each Entity & type it carried
+    pub fn record_packet(&mut self, user_key: UserKey) {
+        if self.packet_messages.is_empty() {
+            return;
+        }
+        self.current.packets += 1;
+        let mut packet_entities = HashSet::new();
+        let mut packet_types = HashSet::new();
+        for (entity_key, bytes) in self.packet_messages.drain(..) {
+            let naia_id = self.entity_types.get(&entity_key).copied();
+            let entity_cost = self
+                .current
+                .entity_costs
+                .entry(entity_key)
+                .or_default()
+                .entry(user_key)
+                .or_default();
+            entity_cost.bytes += bytes;
+            entity_cost.messages += 1;
+            if packet_entities.insert(entity_key) {
+                entity_cost.packets += 1;
+            }
+
+            if let Some(naia_id) = naia_id {
+                let type_cost = self
+                    .current
+                    .type_costs
+                    .entry(naia_id)
+                    .or_default()
+                    .entry(user_key)
+                    .or_default();
+                type_cost.bytes += bytes;
+                type_cost.messages += 1;
+                if packet_types.insert(naia_id) {
+                    type_cost.packets += 1;
+                }
+                self.current.entity_types.insert(entity_key, naia_id);
+            }
+        }
+    }
+
+    /// Records that an Entity has been deregistered. Its type is forgotten
+    /// once the current window completes, so its deletion is still counted
+    /// against its type within the window
+    pub fn remove_entity(&mut self, entity_key: &EntityKey) {
+        self.removed_entities.insert(*entity_key);
+    }
+
+    /// Gets a report of the given number of most costly Entities & types over
+    /// the last completed window, or None if no window has completed yet
+    pub fn get_profile(&self, top_count: usize) -> Option<ReplicationProfile> {
+        let window = self.completed.as_ref()?;
+
+        let mut total = ReplicationCost {
+            packets: window.packets,
+            ..ReplicationCost::default()
+        };
+        let mut entities: Vec<EntityReplicationCost> = window
+            .entity_costs
+            .iter()
+            .map(|(entity_key, user_costs)| {
+                let (entity_total, users) = sum_user_costs(user_costs);
+                total.bytes += entity_total.bytes;
+                total.messages += entity_total.messages;
+                EntityReplicationCost {
+                    entity_key: *entity_key,
+                    naia_id: window.entity_types.get(entity_key).copied(),
+                    total: entity_total,
+                    users,
+                }
+            })
+            .collect();
+        entities.sort_by(|a, b| b.total.bytes.cmp(&a.total.bytes));
+        entities.truncate(top_count);
+
+        let mut types: Vec<TypeReplicationCost> = window
+            .type_costs
+            .iter()
+            .map(|(naia_id, user_costs)| {
+                let (type_total, users) = sum_user_costs(user_costs);
+                TypeReplicationCost {
+                    naia_id: *naia_id,
+                    type_name: None,
+                    entity_count: window
+                        .entity_types
+                        .values()
+                        .filter(|entity_naia_id| *entity_naia_id == naia_id)
+                        .count(),
+                    total: type_total,
+                    users,
+                }
+            })
+            .collect();
+        types.sort_by(|a, b| b.total.bytes.cmp(&a.total.bytes));
+        types.truncate(top_count);
+
+        return Some(ReplicationProfile {
+            window: window.duration,
+            total,
+            entities,
+            types,
+        });
+    }
+}
+
+// Sums the costs of every User, & lists them most costly first
+fn sum_user_costs(
+    user_costs: &HashMap<UserKey, ReplicationCost>,
+) -> (ReplicationCost, Vec<(UserKey, ReplicationCost)>) {
+    let mut total = ReplicationCost::default();
+    let mut users: Vec<(UserKey, ReplicationCost)> = user_costs
+        .iter()
+        .map(|(user_key, cost)| {
+            total.bytes += cost.bytes;
+            total.packets += cost.packets;
+            total.messages += cost.messages;
+            (*user_key, *cost)
+        })
+        .collect();
+    users.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes));
+    return (total, users);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use slotmap::SlotMap;
+
+    use super::{EntityKey, ReplicationProfiler, UserKey};
+
+    #[test]
+    fn reports_most_costly_entities_and_types() {
+        let mut entity_keys = SlotMap::<EntityKey, ()>::with_key();
+        let (entity_a, entity_b, entity_c) = (
+            entity_keys.insert(()),
+            entity_keys.insert(()),
+            entity_keys.insert(()),
+        );
+        let mut user_keys = SlotMap::<UserKey, ()>::with_key();
+        let (user_a, user_b) = (user_keys.insert(()), user_keys.insert(()));
+
+        let mut profiler = ReplicationProfiler::new(Duration::from_secs(0));
+        profiler.record_message(entity_a, Some(1), 100);
+        profiler.record_message(entity_b, Some(1), 50);
+        profiler.record_message(entity_a, Some(1), 20);
+        profiler.record_packet(user_a);
+        profiler.record_message(entity_c, Some(2), 30);
+        profiler.record_packet(user_b);
+        assert!(profiler.get_profile(2).is_none());
+
+        profiler.update();
+        let profile = profiler.get_profile(2).unwrap();
+        assert!(profile.total.bytes == 200);
+        assert!(profile.total.packets == 2);
+        assert!(profile.total.messages == 4);
+
+        // only the given number of most costly are reported, most costly first
+        assert!(profile.entities.len() == 2);
+        let costliest_entity = &profile.entities[0];
+        assert!(costliest_entity.entity_key == entity_a);
+        assert!(costliest_entity.naia_id == Some(1));
+        assert!(costliest_entity.total.bytes == 120);
+        assert!(costliest_entity.total.packets == 1);
+        assert!(costliest_entity.total.messages == 2);
+        assert!(costliest_entity.users.len() == 1 && costliest_entity.users[0].0 == user_a);
+        assert!(profile.entities[1].entity_key == entity_b);
+
+        // a packet carrying several Entities of a type counts once for it
+        assert!(profile.types.len() == 2);
+        assert!(profile.types[0].naia_id == 1);
+        assert!(profile.types[0].entity_count == 2);
+        assert!(profile.types[0].total.bytes == 170);
+        assert!(profile.types[0].total.packets == 1);
+        assert!(profile.types[1].naia_id == 2);
+
+        let profile = profiler.get_profile(1).unwrap();
+        assert!(profile.entities.len() == 1 && profile.types.len() == 1);
+    }
+
+    #[test]
+    fn profiles_each_window_separately() {
+        let mut entity_keys = SlotMap::<EntityKey, ()>::with_key();
+        let (entity_a, entity_b) = (entity_keys.insert(()), entity_keys.insert(()));
+        let mut user_keys = SlotMap::<UserKey, ()>::with_key();
+        let user_key = user_keys.insert(());
+
+        // no window completes before its length has passed
+        let mut profiler = ReplicationProfiler::new(Duration::from_secs(3600));
+        profiler.record_message(entity_a, Some(1), 10);
+        profiler.record_packet(user_key);
+        profiler.update();
+        assert!(profiler.get_profile(10).is_none());
+
+        let mut profiler = ReplicationProfiler::new(Duration::from_secs(0));
+        profiler.record_message(entity_a, Some(1), 10);
+        profiler.record_packet(user_key);
+        profiler.update();
+
+        // the deletion of a deregistered Entity is counted against its type
+        // until the window completes
+        profiler.record_message(entity_b, Some(2), 5);
+        profiler.remove_entity(&entity_b);
+        profiler.record_message(entity_b, None, 3);
+        profiler.record_packet(user_key);
+
+        // the last completed window is reported until the next completes
+        let profile = profiler.get_profile(10).unwrap();
+        assert!(profile.entities.len() == 1 && profile.entities[0].entity_key == entity_a);
+
+        profiler.update();
+        let profile = profiler.get_profile(10).unwrap();
+        assert!(profile.entities.len() == 1 && profile.entities[0].entity_key == entity_b);
+        assert!(profile.types.len() == 1 && profile.types[0].total.bytes == 8);
+
+        // once forgotten, the type of the deregistered Entity is unknown
+        profiler.record_message(entity_b, None, 3);
+        profiler.record_packet(user_key);
+        profiler.update();
+        let profile = profiler.get_profile(10).unwrap();
+        assert!(profile.entities[0].naia_id.is_none());
+        assert!(profile.types.is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use std::{collections::HashMap, net::IpAddr, time::Instant};
+
+// The most IP addresses tracked at once. Once reached, addresses whose burst
+// has refilled are forgotten, and if none have, requests from addresses which
+// aren't tracked are refused
+const MAX_TRACKED_ADDRESSES: usize = 4096;
+
+/// Limits the rate at which unconnected requests from each IP address are
+/// answered, with a bucket of tokens per address which refills over time
+#[derive(Debug)]
+pub struct RequestRateLimiter {
+    rate: f32,
+    burst: f32,
+    buckets: HashMap<IpAddr, (f32, Instant)>,
+}
+
+impl RequestRateLimiter {
+    /// Creates a new RequestRateLimiter, given the number of requests per
+    /// second answered for each address, and the number answered in a burst
+    pub fn new(rate: f32, burst: u16) -> Self {
+        RequestRateLimiter {
+            rate: rate.max(0.0),
+            burst: burst as f32,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Returns whether a request from the given address should be answered,
+    /// and if so, counts it against the address
+    pub fn allow(&mut self, address: IpAddr) -> bool {
+        let now = Instant::now();
+        if !self.buckets.contains_key(&address) && self.buckets.len() >= MAX_TRACKED_ADDRESSES {
+            let rate = self.rate;
+            let burst = self.burst;
+            self.buckets.retain(|_, (tokens, refilled_at)| {
+                *tokens + now.duration_since(*refilled_at).as_secs_f32() * rate < burst
+            });
+            if self.buckets.len() >= MAX_TRACKED_ADDRESSES {
+                return false;
+            }
+        }
+
+        let (tokens, refilled_at) = self.buckets.entry(address).or_insert((self.burst, now));
+        *tokens =
+            (*tokens + now.duration_since(*refilled_at).as_secs_f32() * self.rate).min(self.burst);
+        *refilled_at = now;
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        return true;
+    }
+}
@@ -35,6 +35,10 @@ impl Room {
         }
     }
 
+    pub fn has_entity(&self, entity_key: &EntityKey) -> bool {
+        return self.entities.contains(entity_key);
+    }
+
     pub fn entities_iter(&self) -> Iter<EntityKey> {
         return self.entities.iter();
     }
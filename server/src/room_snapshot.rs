@@ -0,0 +1,99 @@
+use std::{cell::RefCell, rc::Rc};
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use naia_shared::{compress, Entity, EntityType, EventType, Manifest, StateMask};
+
+use super::entities::entity_key::entity_key::EntityKey;
+
+/// A compressed serialization of every Entity in a Room, taken periodically so
+/// that Clients joining the Room can receive it in bulk
+#[derive(Debug)]
+pub struct RoomSnapshot {
+    entity_keys: Vec<EntityKey>,
+    property_bytes: Vec<Vec<Vec<u8>>>,
+    compressed_bytes: Box<[u8]>,
+}
+
+impl RoomSnapshot {
+    /// Serializes the given Entities into a new snapshot. Entities too large
+    /// to be written in a single message are left out, and will be created
+    /// individually instead
+    pub fn new<T: EventType, U: EntityType>(
+        manifest: &Manifest<T, U>,
+        entities: Vec<(EntityKey, &Rc<RefCell<dyn Entity<U>>>)>,
+    ) -> Self {
+        let mut entity_keys = Vec::new();
+        let mut property_bytes = Vec::new();
+        let mut snapshot_bytes = Vec::<u8>::new();
+
+        for (key, entity) in entities {
+            let entity = entity.as_ref().borrow();
+
+            let mut entity_payload_bytes = Vec::<u8>::new();
+            entity.write(&mut entity_payload_bytes);
+            if entity_payload_bytes.len() > 255 {
+                continue;
+            }
+
+            let naia_id = manifest.get_entity_naia_id(&entity.get_type_id());
+            snapshot_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+            snapshot_bytes
+                .write_u8(entity_payload_bytes.len() as u8)
+                .unwrap(); // write payload length
+            snapshot_bytes.append(&mut entity_payload_bytes); // write payload
+
+            entity_keys.push(key);
+            property_bytes.push(RoomSnapshot::get_property_bytes(&*entity));
+        }
+
+        RoomSnapshot {
+            entity_keys,
+            property_bytes,
+            compressed_bytes: compress(&snapshot_bytes).into_boxed_slice(),
+        }
+    }
+
+    /// Gets the keys of the Entities in the snapshot, in the order they were
+    /// written
+    pub fn get_entity_keys(&self) -> &Vec<EntityKey> {
+        return &self.entity_keys;
+    }
+
+    /// Gets the compressed bytes of the snapshot
+    pub fn get_compressed_bytes(&self) -> &[u8] {
+        return &self.compressed_bytes;
+    }
+
+    /// Gets a StateMask with a bit set for every Property of the Entity at the
+    /// given position in the snapshot, which has changed since the snapshot
+    /// was taken
+    pub fn get_diff<U: EntityType>(&self, index: usize, entity: &dyn Entity<U>) -> StateMask {
+        let mut diff_mask = StateMask::new(entity.get_state_mask_size());
+        let current_property_bytes = RoomSnapshot::get_property_bytes(entity);
+        for (property_index, current_bytes) in current_property_bytes.iter().enumerate() {
+            let snapshot_bytes = self
+                .property_bytes
+                .get(index)
+                .and_then(|entity_bytes| entity_bytes.get(property_index));
+            if snapshot_bytes != Some(current_bytes) {
+                diff_mask.set_bit(property_index as u8, true);
+            }
+        }
+        return diff_mask;
+    }
+
+    fn get_property_bytes<U: EntityType>(entity: &dyn Entity<U>) -> Vec<Vec<u8>> {
+        let state_mask_size = entity.get_state_mask_size();
+        let mut output = Vec::new();
+        for property_index in 0..entity.get_property_count() {
+            let mut property_mask = StateMask::new(state_mask_size);
+            property_mask.set_bit(property_index, true);
+
+            let mut property_bytes = Vec::<u8>::new();
+            entity.write_partial(&property_mask, &mut property_bytes);
+            output.push(property_bytes);
+        }
+        return output;
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use futures_util::future::select_all;
+use naia_server_socket::{
+    Config as SocketConfig, MessageSender, NaiaServerSocketError, Packet, ServerSocket,
+    ServerSocketTrait, SocketEvent,
+};
+
+/// The sockets a Server listens on, each bound to its own address, whose
+/// packets are all received by the one Server. The first is the primary
+/// endpoint, which also times the Server's ticks
+#[derive(Debug)]
+pub struct ServerEndpoints {
+    sockets: Vec<(SocketAddr, ServerSocket)>,
+}
+
+impl ServerEndpoints {
+    /// Creates a new ServerEndpoints, listening on the primary endpoint
+    pub async fn listen(address: SocketAddr, socket_config: SocketConfig) -> Self {
+        let socket = ServerSocket::listen(address, Some(socket_config)).await;
+        ServerEndpoints {
+            sockets: vec![(address, socket)],
+        }
+    }
+
+    /// Listens on another endpoint, returning its index along with the sender
+    /// of packets through it
+    pub async fn add(
+        &mut self,
+        address: SocketAddr,
+        socket_config: SocketConfig,
+    ) -> (usize, MessageSender) {
+        let mut socket = ServerSocket::listen(address, Some(socket_config)).await;
+        let sender = socket.get_sender();
+        self.sockets.push((address, socket));
+        return (self.sockets.len() - 1, sender);
+    }
+
+    /// Gets the sender of packets through the primary endpoint
+    pub fn get_sender(&mut self) -> MessageSender {
+        return self.sockets[0].1.get_sender();
+    }
+
+    /// Gets the address the endpoint at the given index listens on
+    pub fn get_address(&self, index: usize) -> Option<SocketAddr> {
+        return self.sockets.get(index).map(|(address, _)| *address);
+    }
+
+    /// Gets the number of endpoints
+    pub fn len(&self) -> usize {
+        return self.sockets.len();
+    }
+
+    /// Receives the next event of any endpoint, along with the index of the
+    /// endpoint. Only the primary endpoint's ticks are returned
+    pub async fn receive(&mut self) -> Result<(usize, SocketEvent), NaiaServerSocketError> {
+        if self.sockets.len() == 1 {
+            return self.sockets[0].1.receive().await.map(|event| (0, event));
+        }
+        loop {
+            let receives = self.sockets.iter_mut().map(|(_, socket)| socket.receive());
+            let (result, index, _) = select_all(receives).await;
+            match result {
+                Ok(SocketEvent::Tick) if index != 0 => continue,
+                Ok(event) => return Ok((index, event)),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Sends each packet through the endpoint its address was last heard on, or
+/// through the primary endpoint if it has never been heard
+#[derive(Debug)]
+pub struct EndpointSender {
+    senders: Vec<MessageSender>,
+    routes: HashMap<SocketAddr, (usize, Instant)>,
+}
+
+impl EndpointSender {
+    /// Creates a new EndpointSender, given the sender of the primary endpoint
+    pub fn new(primary_sender: MessageSender) -> Self {
+        EndpointSender {
+            senders: vec![primary_sender],
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Adds the sender of another endpoint, in the order the endpoints were
+    /// added
+    pub fn add(&mut self, sender: MessageSender) {
+        self.senders.push(sender);
+    }
+
+    /// Records that a packet from the given address arrived on the endpoint
+    /// at the given index
+    pub fn record_route(&mut self, address: SocketAddr, index: usize) {
+        if self.senders.len() > 1 {
+            self.routes.insert(address, (index, Instant::now()));
+        }
+    }
+
+    /// Gets the index of the endpoint packets to the given address are sent
+    /// through
+    pub fn get_route(&self, address: &SocketAddr) -> usize {
+        return self.routes.get(address).map_or(0, |(index, _)| *index);
+    }
+
+    /// Forgets the endpoint of every address which hasn't been heard from for
+    /// the given duration
+    pub fn remove_stale_routes(&mut self, timeout: Duration) {
+        self.routes
+            .retain(|_, (_, heard_at)| heard_at.elapsed() < timeout);
+    }
+
+    /// Sends a packet through the endpoint of its address
+    pub async fn send(&mut self, packet: Packet) -> Result<(), Box<dyn Error + Send>> {
+        let index = self.get_route(&packet.address());
+        return self.senders[index].send(packet).await;
+    }
+}
@@ -1,4 +1,11 @@
-use super::user::{user_key::UserKey, User};
+use std::net::SocketAddr;
+
+use naia_shared::{BlobId, ConnectionQuality, DropReason, EventId, LinkState, RequestId};
+
+use super::{
+    entities::entity_key::entity_key::EntityKey,
+    user::{user_key::UserKey, User},
+};
 
 /// An Event that is emitted as a result of some communication with a Client, or
 /// a Tick event
@@ -9,8 +16,84 @@ pub enum ServerEvent<T> {
     /// Occurs when the Server has lost connection to a Client, usually as the
     /// result of a timeout
     Disconnection(UserKey, User),
+    /// Occurs once the Server has shut down, after `NaiaServer::shutdown()`
+    /// was called & every User has been disconnected
+    Shutdown,
+    /// Occurs when a Client has violated the protocol while
+    /// `Config::strict_protocol` is enabled, by sending a packet which would
+    /// otherwise be dropped for the given reason. The Client's packets are
+    /// ignored from then on, & a Disconnection event follows
+    ProtocolViolation(UserKey, DropReason),
+    /// Occurs when the quality of the connection to a Client, classified from
+    /// its Round Trip Time, jitter & packet loss by the thresholds in
+    /// `Config::connection_quality_thresholds`, has changed & held for
+    /// `CONNECTION_QUALITY_HOLD`. The first classification is emitted as soon
+    /// as Round Trip Time has been measured
+    ConnectionQualityChanged(UserKey, ConnectionQuality),
+    /// Occurs when the outgoing queues of the connection to a Client have
+    /// grown to `BackpressureThresholds::congested_depth` undelivered
+    /// messages, such as when the Client has stopped acknowledging packets
+    Congested(UserKey),
+    /// Occurs when the outgoing queues of a congested connection to a Client
+    /// have drained to `BackpressureThresholds::relieved_depth` undelivered
+    /// messages
+    Relieved(UserKey),
+    /// Occurs when the state of the link to a Client has changed, judged from
+    /// its acknowledgements by the thresholds in `Config::stall_thresholds`.
+    /// Entity updates are reduced while the link is degraded, and paused
+    /// while it is stalled
+    LinkStateChanged(UserKey, LinkState),
+    /// Occurs when the Server has moved to another tier of load shedding, as
+    /// its load has risen or fallen, given the index of the tier in those set
+    /// with `NaiaServer::set_load_shedding_tiers()`, sorted by load, or None
+    /// once the load has fallen back below the first tier
+    LoadSheddingChanged(Option<usize>),
+    /// The application data attached to a heartbeat a Client sent, set with
+    /// `NaiaClient::set_heartbeat_payload()`
+    HeartbeatPayload(UserKey, Box<[u8]>),
     /// An Event emitted to the Server from a Client
     Event(UserKey, T),
+    /// The command a Client sent with `NaiaClient::send_command()` for the
+    /// given tick, emitted just before the Tick event of that tick. Commands
+    /// are buffered until their tick is reached, and if a command hasn't
+    /// arrived by then, the Client's last command is repeated, depending on
+    /// `NaiaServer::set_missing_command_policy()`
+    Command(UserKey, u16, T),
+    /// Occurs in place of a Command when a Client's command for the given
+    /// tick hasn't arrived in time, and either the missing command policy is
+    /// `MissingCommandPolicy::Neutral`, or there is no previous command to
+    /// repeat
+    MissingCommand(UserKey, u16),
+    /// Occurs when an Event queued with `NaiaServer::queue_event()` has been
+    /// confirmed as delivered to the Client
+    DeliveryConfirmed(UserKey, EventId),
+    /// Occurs when an Event queued with `NaiaServer::queue_event_with_ttl()`
+    /// was dropped, because its time-to-live ran out before it could be sent
+    EventExpired(UserKey, EventId),
+    /// A raw payload sent by a Client with `NaiaClient::send_raw()`
+    RawPayload(UserKey, Box<[u8]>),
+    /// Another chunk of a blob sent by a Client has been received, as (blob id,
+    /// chunks received, total chunks)
+    BlobProgress(UserKey, BlobId, u16, u16),
+    /// A blob sent by a Client has been completely received
+    BlobReceived(UserKey, BlobId, Box<[u8]>),
+    /// A blob sent with `NaiaServer::send_blob()` has been completely delivered
+    /// to the Client
+    BlobDelivered(UserKey, BlobId),
+    /// A request sent to the Server from a Client, which should be answered
+    /// with either `NaiaServer::respond()` or `NaiaServer::reject_request()`
+    Request(UserKey, RequestId, T),
+    /// Occurs when an Entity has come into scope for a Client. The Entity's
+    /// creation has been queued, so any Event queued for the Client in response
+    /// will arrive together with, or after, the Entity
+    EntityEnteredScope(UserKey, EntityKey),
+    /// Occurs when an Entity has left the scope of a Client, or has been
+    /// deregistered while in scope
+    EntityLeftScope(UserKey, EntityKey),
+    /// An unconnected message sent from the given address with
+    /// `ServerQuery::send_message()`, along with its token, which may be
+    /// answered with `NaiaServer::send_unconnected_response()`
+    UnconnectedMessage(SocketAddr, u32, Box<[u8]>),
     /// A Tick Event, the duration between Tick events is defined in the Config
     /// object passed to the Server on initialization
     Tick,
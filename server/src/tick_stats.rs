@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+/// Statistics about the work done by a single call to `NaiaServer::tick()`
+#[derive(Clone, Debug, Default)]
+pub struct TickStats {
+    /// The tick which was sent
+    pub tick: u16,
+    /// The number of connected Users which were sent to
+    pub user_count: usize,
+    /// The number of times an Entity came into, or left, the scope of a User
+    pub scope_changes: usize,
+    /// The number of mutated Entities whose updates were collected, summed
+    /// across all Users
+    pub entity_updates: usize,
+    /// The number of mutated Entities whose updates were deferred to a later
+    /// tick by `Config::max_entity_updates_per_tick`, summed across all Users
+    pub deferred_entity_updates: usize,
+    /// The number of Users whose Entity updates weren't collected this tick,
+    /// as the active tier of load shedding collects them less often
+    pub shed_users: usize,
+    /// The number of packets sent
+    pub packets_sent: usize,
+    /// The number of bytes sent, including packet headers
+    pub bytes_sent: usize,
+    /// How long the tick took to process
+    pub duration: Duration,
+    /// How long each phase of the tick took
+    pub timings: TickTimings,
+}
+
+/// How long the Server spent in each phase of a tick, so that a slow tick can
+/// be attributed to the phase responsible. Every phase but `receive` is part
+/// of the call to `NaiaServer::tick()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickTimings {
+    /// Time spent in `NaiaServer::receive()` since the previous tick was
+    /// sent, processing packets, timers & events, excluding the time spent
+    /// waiting on the socket
+    pub receive: Duration,
+    /// Time spent refreshing Room snapshots & updating which Entities are in
+    /// the scope of each User
+    pub scope: Duration,
+    /// Time spent scanning the Entities in scope of each User for mutations
+    pub dirty_scan: Duration,
+    /// Time spent evaluating the priority of mutated Entities, & choosing
+    /// which of them to update this tick
+    pub prioritize: Duration,
+    /// Time spent writing messages into packets
+    pub serialize: Duration,
+    /// Time spent handing packets to the socket
+    pub send: Duration,
+}
+
+impl TickTimings {
+    /// Gets the time spent across every phase
+    pub fn total(&self) -> Duration {
+        return self.receive
+            + self.scope
+            + self.dirty_scan
+            + self.prioritize
+            + self.serialize
+            + self.send;
+    }
+}
@@ -11,11 +11,16 @@ pub mod user_key {
 #[derive(Clone)]
 pub struct User {
     pub address: SocketAddr,
+    pub endpoint: usize,
     pub timestamp: Timestamp,
 }
 
 impl User {
-    pub fn new(address: SocketAddr, timestamp: Timestamp) -> User {
-        User { address, timestamp }
+    pub fn new(address: SocketAddr, endpoint: usize, timestamp: Timestamp) -> User {
+        User {
+            address,
+            endpoint,
+            timestamp,
+        }
     }
 }
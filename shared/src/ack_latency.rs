@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+/// The range of latencies counted by each bucket of an AckLatencyHistogram
+pub const ACK_LATENCY_BUCKET_WIDTH: Duration = Duration::from_millis(5);
+
+/// The number of buckets of an AckLatencyHistogram, not counting the final
+/// bucket, which counts every latency beyond the others
+pub const ACK_LATENCY_BUCKET_COUNT: usize = 100;
+
+/// A histogram of the time between sending each packet & receiving its ack.
+/// Unlike the Round Trip Time, which is measured with dedicated pings, this
+/// includes the time a packet waits to be acked by the remote host's next
+/// outgoing packet, and so reflects how long the delivery of sent data
+/// actually takes to be confirmed
+#[derive(Clone, Debug)]
+pub struct AckLatencyHistogram {
+    buckets: Vec<u32>,
+    sample_count: u32,
+    total: Duration,
+    max: Duration,
+}
+
+impl AckLatencyHistogram {
+    /// Creates a new, empty AckLatencyHistogram
+    pub fn new() -> Self {
+        AckLatencyHistogram {
+            buckets: vec![0; ACK_LATENCY_BUCKET_COUNT + 1],
+            sample_count: 0,
+            total: Duration::from_secs(0),
+            max: Duration::from_secs(0),
+        }
+    }
+
+    /// Records the latency of an acked packet
+    pub fn record(&mut self, latency: Duration) {
+        let index = (latency.as_micros() / ACK_LATENCY_BUCKET_WIDTH.as_micros()) as usize;
+        self.buckets[index.min(ACK_LATENCY_BUCKET_COUNT)] += 1;
+        self.sample_count += 1;
+        self.total += latency;
+        if latency > self.max {
+            self.max = latency;
+        }
+    }
+
+    /// Clears every recorded latency, so that the histogram can be sampled
+    /// over a chosen period
+    pub fn clear(&mut self) {
+        *self = AckLatencyHistogram::new();
+    }
+
+    /// Gets the number of latencies recorded
+    pub fn get_sample_count(&self) -> u32 {
+        return self.sample_count;
+    }
+
+    /// Gets the count of each bucket. The bucket at index `i` counts latencies
+    /// from `i` up to `i + 1` times `ACK_LATENCY_BUCKET_WIDTH`, and the final
+    /// bucket counts every latency beyond the others
+    pub fn get_buckets(&self) -> &[u32] {
+        return &self.buckets;
+    }
+
+    /// Gets the mean of the recorded latencies, or None if there are none
+    pub fn get_mean(&self) -> Option<Duration> {
+        if self.sample_count == 0 {
+            return None;
+        }
+        return Some(self.total / self.sample_count);
+    }
+
+    /// Gets the highest recorded latency, or None if there are none
+    pub fn get_max(&self) -> Option<Duration> {
+        if self.sample_count == 0 {
+            return None;
+        }
+        return Some(self.max);
+    }
+
+    /// Gets the latency which the given fraction of recorded latencies, from 0
+    /// to 1, are at or below, to the precision of a bucket. Returns None if
+    /// there are no recorded latencies
+    pub fn get_percentile(&self, percentile: f32) -> Option<Duration> {
+        if self.sample_count == 0 {
+            return None;
+        }
+        let rank = ((percentile * self.sample_count as f32).ceil() as u32).max(1);
+        let mut counted: u32 = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            counted += count;
+            if counted >= rank {
+                if index == ACK_LATENCY_BUCKET_COUNT {
+                    return Some(self.max);
+                }
+                let upper_bound = ACK_LATENCY_BUCKET_WIDTH * (index as u32 + 1);
+                return Some(upper_bound.min(self.max));
+            }
+        }
+        return Some(self.max);
+    }
+}
+
+impl Default for AckLatencyHistogram {
+    fn default() -> Self {
+        return AckLatencyHistogram::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{AckLatencyHistogram, ACK_LATENCY_BUCKET_COUNT};
+
+    #[test]
+    fn buckets_and_percentiles() {
+        let mut histogram = AckLatencyHistogram::new();
+        assert!(histogram.get_percentile(0.5).is_none());
+        assert!(histogram.get_mean().is_none());
+
+        for millis in 1..=100u64 {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert!(histogram.get_sample_count() == 100);
+        assert!(histogram.get_buckets()[0] == 4);
+        assert!(histogram.get_buckets()[1] == 5);
+        assert!(histogram.get_percentile(0.5) == Some(Duration::from_millis(55)));
+        assert!(histogram.get_percentile(0.95) == Some(Duration::from_millis(100)));
+        assert!(histogram.get_percentile(1.0) == Some(Duration::from_millis(100)));
+        assert!(histogram.get_mean() == Some(Duration::from_micros(50500)));
+    }
+
+    #[test]
+    fn overflow() {
+        let mut histogram = AckLatencyHistogram::new();
+        histogram.record(Duration::from_millis(10));
+        histogram.record(Duration::from_secs(3));
+        assert!(histogram.get_buckets()[ACK_LATENCY_BUCKET_COUNT] == 1);
+        assert!(histogram.get_percentile(1.0) == Some(Duration::from_secs(3)));
+        assert!(histogram.get_max() == Some(Duration::from_secs(3)));
+
+        histogram.clear();
+        assert!(histogram.get_sample_count() == 0);
+    }
+}
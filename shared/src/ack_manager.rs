@@ -1,11 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
 
 use super::{
-    sequence_buffer::{sequence_greater_than, SequenceBuffer, SequenceNumber},
+    ack_latency::AckLatencyHistogram,
+    instant::Instant,
+    sequence_buffer::{sequence_greater_than, sequence_less_than, SequenceBuffer, SequenceNumber},
     standard_header::StandardHeader,
 };
 
 use super::{
+    blobs::blob_manager::BlobManager,
     entities::entity_notifiable::EntityNotifiable,
     events::{event_manager::EventManager, event_type::EventType},
     packet_type::PacketType,
@@ -13,6 +19,7 @@ use super::{
 
 const REDUNDANT_PACKET_ACKS_SIZE: u16 = 32;
 const DEFAULT_SEND_PACKETS_SIZE: usize = 256;
+const PACKET_LOSS_WINDOW_SIZE: usize = 128;
 
 /// Keeps track of sent & received packets, and contains ack information that is
 /// copied into the standard header on each outgoing packet
@@ -28,6 +35,12 @@ pub struct AckManager {
     // However, we can only reasonably ack up to `REDUNDANT_PACKET_ACKS_SIZE + 1` packets on each
     // message we send so this should be that large.
     received_packets: SequenceBuffer<ReceivedPacket>,
+    // Whether outgoing packets are written with compact headers
+    compact_headers: bool,
+    // Whether each of the most recently resolved outgoing packets was lost
+    packet_outcomes: VecDeque<bool>,
+    // The time between sending each packet & receiving its ack
+    ack_latency: AckLatencyHistogram,
 }
 
 impl AckManager {
@@ -38,7 +51,55 @@ impl AckManager {
             remote_ack_sequence_num: u16::max_value(),
             sent_packets: HashMap::with_capacity(DEFAULT_SEND_PACKETS_SIZE),
             received_packets: SequenceBuffer::with_capacity(REDUNDANT_PACKET_ACKS_SIZE + 1),
+            compact_headers: false,
+            packet_outcomes: VecDeque::with_capacity(PACKET_LOSS_WINDOW_SIZE),
+            ack_latency: AckLatencyHistogram::new(),
+        }
+    }
+
+    /// Set whether outgoing packets are written with compact headers, which
+    /// both hosts agree on during the handshake
+    pub fn set_compact_headers(&mut self, compact_headers: bool) {
+        self.compact_headers = compact_headers;
+    }
+
+    /// Returns whether outgoing packets are written with compact headers
+    pub fn has_compact_headers(&self) -> bool {
+        self.compact_headers
+    }
+
+    /// Get the fraction of the most recently sent packets which were lost,
+    /// from 0 to 1, or None if no sent packet has been acked or dropped yet
+    pub fn get_packet_loss(&self) -> Option<f32> {
+        if self.packet_outcomes.is_empty() {
+            return None;
         }
+        let lost = self.packet_outcomes.iter().filter(|lost| **lost).count();
+        return Some(lost as f32 / self.packet_outcomes.len() as f32);
+    }
+
+    /// Get the time since the oldest packet which hasn't been acked or dropped
+    /// yet was sent, or None if every sent packet has been
+    pub fn get_oldest_unacked_age(&self) -> Option<Duration> {
+        return self
+            .sent_packets
+            .values()
+            .map(|sent_packet| {
+                let elapsed = sent_packet.sent_at.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos())
+            })
+            .max();
+    }
+
+    /// Get the histogram of the time between sending each packet & receiving
+    /// its ack
+    pub fn get_ack_latency(&self) -> &AckLatencyHistogram {
+        &self.ack_latency
+    }
+
+    /// Clear the histogram of ack latencies
+    pub fn clear_ack_latency(&mut self) {
+        self.ack_latency.clear();
     }
 
     /// Get the index of the next outgoing packet
@@ -46,15 +107,73 @@ impl AckManager {
         self.sequence_number
     }
 
+    /// Get the sequence numbers the AckManager has reached, being the index of
+    /// the next outgoing packet, the sequence number of the most recent
+    /// incoming packet & the ack bitfield of the incoming packets before it,
+    /// so that another AckManager can take over with `resume()`
+    pub fn get_sequence_state(&self) -> (SequenceNumber, SequenceNumber, u32) {
+        return (
+            self.sequence_number,
+            self.remote_sequence_num(),
+            self.ack_bitfield(),
+        );
+    }
+
+    /// Resumes from the sequence numbers another AckManager had reached, as
+    /// given by `get_sequence_state()`, so that the remote host sees no gap.
+    /// Packets the other AckManager sent are no longer tracked, so their
+    /// acks are ignored
+    pub fn resume(
+        &mut self,
+        local_sequence: SequenceNumber,
+        remote_sequence: SequenceNumber,
+        ack_bitfield: u32,
+    ) {
+        self.sequence_number = local_sequence;
+        self.remote_ack_sequence_num = local_sequence.wrapping_sub(1);
+        self.sent_packets.clear();
+        self.received_packets = SequenceBuffer::with_capacity(REDUNDANT_PACKET_ACKS_SIZE + 1);
+        for i in (1..=REDUNDANT_PACKET_ACKS_SIZE).rev() {
+            if ack_bitfield & (1 << (i - 1)) != 0 {
+                self.received_packets
+                    .insert(remote_sequence.wrapping_sub(i), ReceivedPacket {});
+            }
+        }
+        self.received_packets
+            .insert(remote_sequence, ReceivedPacket {});
+    }
+
+    /// Get the sequence number of an incoming packet, expanded if written in
+    /// a compact header, without processing the packet. Returns None if the
+    /// packet is too short to hold its header
+    pub fn get_incoming_sequence(&self, payload: &[u8]) -> Option<SequenceNumber> {
+        let (mut header, _) = StandardHeader::read(payload)?;
+        header.expand_sequences(
+            self.remote_sequence_num(),
+            self.sequence_number.wrapping_sub(1),
+        );
+        return Some(header.sequence());
+    }
+
     /// Process an incoming packet, handle notifications of delivered / dropped
-    /// packets
+    /// packets, and return the packet's header, with the sequence numbers of a
+    /// compact header expanded, along with the payload. Returns None, without
+    /// processing the packet, if it is too short to hold its header, or it
+    /// acknowledges a packet which hasn't been sent yet
     pub fn process_incoming<T: EventType>(
         &mut self,
         payload: &[u8],
         event_manager: &mut EventManager<T>,
+        blob_manager: &mut BlobManager,
         entity_notifiable: &mut Option<&mut dyn EntityNotifiable>,
-    ) -> Box<[u8]> {
-        let (header, stripped_message) = StandardHeader::read(payload);
+    ) -> Option<(StandardHeader, Box<[u8]>)> {
+        let (mut header, stripped_message) = StandardHeader::read(payload)?;
+        let last_sent_seq = self.sequence_number.wrapping_sub(1);
+        header.expand_sequences(self.remote_sequence_num(), last_sent_seq);
+        if sequence_greater_than(header.ack_seq(), last_sent_seq) {
+            return None;
+        }
+        let ack_field_size = header.ack_field_size();
         let remote_seq_num = header.sequence();
         let remote_ack_seq = header.ack_seq();
         let mut remote_ack_field = header.ack_field();
@@ -71,16 +190,23 @@ impl AckManager {
         // the current `remote_ack_seq` was (clearly) received so we should remove it
         if let Some(sent_packet) = self.sent_packets.get(&remote_ack_seq) {
             if sent_packet.packet_type == PacketType::Data {
-                self.notify_packet_delivered(remote_ack_seq, event_manager, entity_notifiable);
+                self.notify_packet_delivered(
+                    remote_ack_seq,
+                    event_manager,
+                    blob_manager,
+                    entity_notifiable,
+                );
             }
 
-            self.sent_packets.remove(&remote_ack_seq);
+            if let Some(sent_packet) = self.sent_packets.remove(&remote_ack_seq) {
+                self.record_delivery(&sent_packet);
+            }
         }
 
         // The `remote_ack_field` is going to include whether or not the past 32 packets
-        // have been received successfully. If so, we have no need to resend old
-        // packets.
-        for i in 1..=REDUNDANT_PACKET_ACKS_SIZE {
+        // (16 for a compact header) have been received successfully. If so, we have no
+        // need to resend old packets.
+        for i in 1..=ack_field_size {
             let ack_sequence = remote_ack_seq.wrapping_sub(i);
             if let Some(sent_packet) = self.sent_packets.get(&ack_sequence) {
                 if remote_ack_field & 1 == 1 {
@@ -88,28 +214,70 @@ impl AckManager {
                         self.notify_packet_delivered(
                             ack_sequence,
                             event_manager,
+                            blob_manager,
                             entity_notifiable,
                         );
                     }
 
-                    self.sent_packets.remove(&ack_sequence);
+                    if let Some(sent_packet) = self.sent_packets.remove(&ack_sequence) {
+                        self.record_delivery(&sent_packet);
+                    }
                 } else {
                     if sent_packet.packet_type == PacketType::Data {
-                        self.notify_packet_dropped(ack_sequence, event_manager, entity_notifiable);
+                        self.notify_packet_dropped(
+                            ack_sequence,
+                            event_manager,
+                            blob_manager,
+                            entity_notifiable,
+                        );
                     }
                     self.sent_packets.remove(&ack_sequence);
+                    self.record_outcome(true);
                 }
             }
 
             remote_ack_field >>= 1;
         }
 
-        stripped_message
+        // packets older than the redundant acks of the most recent ack can no
+        // longer be acked, as every packet which could have acked them has been
+        // lost, so they are considered dropped rather than tracked forever
+        let oldest_ackable_seq = self.remote_ack_sequence_num.wrapping_sub(ack_field_size);
+        let mut expired_seqs: Vec<u16> = self
+            .sent_packets
+            .keys()
+            .filter(|seq| sequence_less_than(**seq, oldest_ackable_seq))
+            .copied()
+            .collect();
+        expired_seqs.sort_by_key(|seq| seq.wrapping_sub(oldest_ackable_seq));
+        for expired_seq in expired_seqs {
+            if let Some(sent_packet) = self.sent_packets.remove(&expired_seq) {
+                self.record_outcome(true);
+                if sent_packet.packet_type == PacketType::Data {
+                    self.notify_packet_dropped(
+                        expired_seq,
+                        event_manager,
+                        blob_manager,
+                        entity_notifiable,
+                    );
+                }
+            }
+        }
+
+        Some((header, stripped_message))
     }
 
     /// Process an outgoing packet, adding the correct header which includes ack
-    /// information, and returning the bytes needed to send over the wire
-    pub fn process_outgoing(&mut self, packet_type: PacketType, payload: &[u8]) -> Box<[u8]> {
+    /// information, along with the tick of this host & the time dilation
+    /// requested of the remote host, and returning the bytes needed to send
+    /// over the wire
+    pub fn process_outgoing(
+        &mut self,
+        packet_type: PacketType,
+        payload: &[u8],
+        host_tick: Option<u16>,
+        time_dilation: i8,
+    ) -> Box<[u8]> {
         // Add Ack Header onto message!
         let mut header_bytes = Vec::new();
 
@@ -117,7 +285,15 @@ impl AckManager {
         let last_seq = self.remote_sequence_num();
         let bit_field = self.ack_bitfield();
 
-        let header = StandardHeader::new(packet_type, seq_num, last_seq, bit_field);
+        let header = StandardHeader::new(
+            packet_type,
+            seq_num,
+            last_seq,
+            bit_field,
+            host_tick,
+            time_dilation,
+            self.compact_headers,
+        );
         header.write(&mut header_bytes);
 
         // Ack stuff //
@@ -126,6 +302,7 @@ impl AckManager {
             SentPacket {
                 id: self.sequence_number as u32,
                 packet_type,
+                sent_at: Instant::now(),
             },
         );
 
@@ -142,9 +319,11 @@ impl AckManager {
         &self,
         packet_sequence_number: u16,
         event_manager: &mut EventManager<T>,
+        blob_manager: &mut BlobManager,
         entity_notifiable: &mut Option<&mut dyn EntityNotifiable>,
     ) {
         event_manager.notify_packet_delivered(packet_sequence_number);
+        blob_manager.notify_packet_delivered(packet_sequence_number);
         if let Some(notifiable) = entity_notifiable {
             notifiable.notify_packet_delivered(packet_sequence_number);
         }
@@ -154,14 +333,30 @@ impl AckManager {
         &self,
         packet_sequence_number: u16,
         event_manager: &mut EventManager<T>,
+        blob_manager: &mut BlobManager,
         entity_notifiable: &mut Option<&mut dyn EntityNotifiable>,
     ) {
         event_manager.notify_packet_dropped(packet_sequence_number);
+        blob_manager.notify_packet_dropped(packet_sequence_number);
         if let Some(notifiable) = entity_notifiable {
             notifiable.notify_packet_dropped(packet_sequence_number);
         }
     }
 
+    fn record_delivery(&mut self, sent_packet: &SentPacket) {
+        self.record_outcome(false);
+        let elapsed = sent_packet.sent_at.elapsed();
+        self.ack_latency
+            .record(Duration::new(elapsed.as_secs(), elapsed.subsec_nanos()));
+    }
+
+    fn record_outcome(&mut self, lost: bool) {
+        if self.packet_outcomes.len() == PACKET_LOSS_WINDOW_SIZE {
+            self.packet_outcomes.pop_front();
+        }
+        self.packet_outcomes.push_back(lost);
+    }
+
     fn remote_sequence_num(&self) -> SequenceNumber {
         self.received_packets.sequence_num().wrapping_sub(1)
     }
@@ -189,7 +384,34 @@ impl AckManager {
 pub struct SentPacket {
     pub id: u32,
     pub packet_type: PacketType,
+    pub sent_at: Instant,
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct ReceivedPacket;
+
+#[cfg(test)]
+mod tests {
+    use super::{AckManager, ReceivedPacket};
+    use crate::PacketType;
+
+    #[test]
+    fn resumes_sequences() {
+        let mut manager = AckManager::new();
+        for _ in 0..5 {
+            manager.process_outgoing(PacketType::Heartbeat, &[], None, 0);
+        }
+        for sequence in [65530, 65532, 65534, 2] {
+            manager.received_packets.insert(sequence, ReceivedPacket {});
+        }
+        let sequence_state = manager.get_sequence_state();
+        assert!(sequence_state.0 == 5);
+        assert!(sequence_state.1 == 2);
+
+        let mut resumed = AckManager::new();
+        let (local_sequence, remote_sequence, ack_bitfield) = sequence_state;
+        resumed.resume(local_sequence, remote_sequence, ack_bitfield);
+        assert!(resumed.get_sequence_state() == sequence_state);
+        assert!(resumed.local_sequence_num() == 5);
+    }
+}
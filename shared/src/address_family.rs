@@ -0,0 +1,119 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// The family of an IP address
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AddressFamily {
+    /// IPv4
+    Ipv4,
+    /// IPv6
+    Ipv6,
+}
+
+impl AddressFamily {
+    /// Gets the family of the given IP address. An IPv4-mapped IPv6 address,
+    /// such as `::ffff:192.0.2.1`, is of the IPv4 family
+    pub fn of(ip: &IpAddr) -> Self {
+        match to_canonical_ip(ip) {
+            IpAddr::V4(_) => return AddressFamily::Ipv4,
+            IpAddr::V6(_) => return AddressFamily::Ipv6,
+        }
+    }
+
+    /// Gets the unspecified address of the family, which a socket binds to in
+    /// order to listen on every local address. On most platforms, a socket
+    /// bound to the unspecified IPv6 address also accepts IPv4 traffic
+    pub fn unspecified(&self) -> IpAddr {
+        match self {
+            AddressFamily::Ipv4 => return IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            AddressFamily::Ipv6 => return IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        }
+    }
+}
+
+/// Converts an IPv4-mapped IPv6 socket address, which a dual-stack socket
+/// receives IPv4 traffic from, into the IPv4 socket address it maps. Other
+/// addresses are returned unchanged
+pub fn to_canonical_address(address: SocketAddr) -> SocketAddr {
+    return SocketAddr::new(to_canonical_ip(&address.ip()), address.port());
+}
+
+fn to_canonical_ip(ip: &IpAddr) -> IpAddr {
+    if let IpAddr::V6(ipv6) = ip {
+        let segments = ipv6.segments();
+        if segments[..5] == [0; 5] && segments[5] == 0xffff {
+            let octets = ipv6.octets();
+            return IpAddr::V4(Ipv4Addr::new(
+                octets[12], octets[13], octets[14], octets[15],
+            ));
+        }
+    }
+    return *ip;
+}
+
+/// Helper method to find the local IP address of the given family which
+/// outgoing traffic is routed from, if the host has one. No packets are sent
+pub fn find_my_ip_address_in(family: AddressFamily) -> Option<IpAddr> {
+    // a documentation address, only used to look up the route
+    let remote_address: SocketAddr = match family {
+        AddressFamily::Ipv4 => "192.0.2.1:9".parse().unwrap(),
+        AddressFamily::Ipv6 => "[2001:db8::1]:9".parse().unwrap(),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(family.unspecified(), 0)).ok()?;
+    socket.connect(remote_address).ok()?;
+    let local_ip = socket.local_addr().ok()?.ip();
+    if local_ip.is_unspecified() {
+        return None;
+    }
+    return Some(local_ip);
+}
+
+/// Resolves a host name, or an IP address in text, to a socket address with
+/// the given port. When the host has addresses of both families, one of the
+/// preferred family is returned. Returns None if the host can't be resolved
+pub fn resolve_address(host: &str, port: u16, preferred: AddressFamily) -> Option<SocketAddr> {
+    let addresses: Vec<SocketAddr> = (host, port).to_socket_addrs().ok()?.collect();
+    return select_address(&addresses, preferred);
+}
+
+fn select_address(addresses: &[SocketAddr], preferred: AddressFamily) -> Option<SocketAddr> {
+    return addresses
+        .iter()
+        .find(|address| AddressFamily::of(&address.ip()) == preferred)
+        .or_else(|| addresses.first())
+        .map(|address| to_canonical_address(*address));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::{resolve_address, select_address, to_canonical_address, AddressFamily};
+
+    #[test]
+    fn canonical_address() {
+        let mapped: SocketAddr = "[::ffff:192.0.2.7]:14191".parse().unwrap();
+        let ipv4: SocketAddr = "192.0.2.7:14191".parse().unwrap();
+        let ipv6: SocketAddr = "[2001:db8::7]:14191".parse().unwrap();
+        assert!(to_canonical_address(mapped) == ipv4);
+        assert!(to_canonical_address(ipv4) == ipv4);
+        assert!(to_canonical_address(ipv6) == ipv6);
+        assert!(AddressFamily::of(&mapped.ip()) == AddressFamily::Ipv4);
+        assert!(AddressFamily::of(&ipv6.ip()) == AddressFamily::Ipv6);
+    }
+
+    #[test]
+    fn preferred_family() {
+        let ipv4: SocketAddr = "192.0.2.7:14191".parse().unwrap();
+        let ipv6: SocketAddr = "[2001:db8::7]:14191".parse().unwrap();
+        let both = [ipv4, ipv6];
+        assert!(select_address(&both, AddressFamily::Ipv4) == Some(ipv4));
+        assert!(select_address(&both, AddressFamily::Ipv6) == Some(ipv6));
+        assert!(select_address(&[ipv4], AddressFamily::Ipv6) == Some(ipv4));
+        assert!(select_address(&[], AddressFamily::Ipv4).is_none());
+
+        assert!(
+            resolve_address("::1", 3000, AddressFamily::Ipv4)
+                == Some("[::1]:3000".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,107 @@
+/// A change in whether a connection's outgoing queues have grown beyond what
+/// the remote host is acknowledging
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Congestion {
+    /// The number of undelivered outgoing messages has reached
+    /// `BackpressureThresholds::congested_depth`
+    Congested,
+    /// The number of undelivered outgoing messages has fallen back to
+    /// `BackpressureThresholds::relieved_depth`
+    Relieved,
+}
+
+/// The outgoing queue depths at which a connection is reported as congested,
+/// and as relieved again
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackpressureThresholds {
+    /// The number of undelivered outgoing messages, queued or sent without
+    /// yet being acknowledged, at which a connection becomes congested
+    pub congested_depth: usize,
+    /// The number of undelivered outgoing messages at or below which a
+    /// congested connection is relieved. Lower than `congested_depth`, so
+    /// that a queue hovering around a single depth doesn't flicker between
+    /// the two
+    pub relieved_depth: usize,
+    /// Whether Events which aren't guaranteed are dropped while the
+    /// connection is congested, both those already queued & those queued
+    /// afterwards, leaving the bandwidth to guaranteed messages
+    pub drop_unguaranteed_events: bool,
+}
+
+impl Default for BackpressureThresholds {
+    fn default() -> Self {
+        BackpressureThresholds {
+            congested_depth: 1024,
+            relieved_depth: 256,
+            drop_unguaranteed_events: false,
+        }
+    }
+}
+
+/// Tracks whether a connection is congested, from the depth of its outgoing
+/// queues
+#[derive(Debug)]
+pub struct BackpressureMonitor {
+    thresholds: BackpressureThresholds,
+    congested: bool,
+}
+
+impl BackpressureMonitor {
+    /// Creates a new BackpressureMonitor, given the thresholds of congestion
+    pub fn new(thresholds: BackpressureThresholds) -> Self {
+        BackpressureMonitor {
+            thresholds,
+            congested: false,
+        }
+    }
+
+    /// Records the current number of undelivered outgoing messages, and
+    /// returns the change in congestion, if any
+    pub fn update(&mut self, queue_depth: usize) -> Option<Congestion> {
+        if !self.congested && queue_depth >= self.thresholds.congested_depth {
+            self.congested = true;
+            return Some(Congestion::Congested);
+        }
+        if self.congested && queue_depth <= self.thresholds.relieved_depth {
+            self.congested = false;
+            return Some(Congestion::Relieved);
+        }
+        return None;
+    }
+
+    /// Returns whether the connection is currently congested
+    pub fn is_congested(&self) -> bool {
+        return self.congested;
+    }
+
+    /// Returns whether Events which aren't guaranteed should currently be
+    /// dropped
+    pub fn should_drop_unguaranteed(&self) -> bool {
+        return self.congested && self.thresholds.drop_unguaranteed_events;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackpressureMonitor, BackpressureThresholds, Congestion};
+
+    #[test]
+    fn congestion_hysteresis() {
+        let mut monitor = BackpressureMonitor::new(BackpressureThresholds {
+            congested_depth: 10,
+            relieved_depth: 4,
+            drop_unguaranteed_events: true,
+        });
+        assert!(monitor.update(9).is_none());
+        assert!(!monitor.should_drop_unguaranteed());
+        assert!(monitor.update(10) == Some(Congestion::Congested));
+        assert!(monitor.is_congested() && monitor.should_drop_unguaranteed());
+
+        // still congested until the queue drains to the lower threshold
+        assert!(monitor.update(12).is_none());
+        assert!(monitor.update(5).is_none());
+        assert!(monitor.update(4) == Some(Congestion::Relieved));
+        assert!(!monitor.is_congested());
+        assert!(monitor.update(9).is_none());
+    }
+}
@@ -0,0 +1,426 @@
+use byteorder::{BigEndian, ReadBytesExt};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Cursor,
+    rc::Rc,
+};
+
+use crate::{
+    connection_stats::{DropLog, DropReason},
+    instant::Instant,
+    packet_reader::PacketReader,
+    sequence_buffer::SequenceBuffer,
+};
+
+use super::blob_message::{BlobId, BlobMessage};
+
+/// The maximum number of bytes of a blob which are sent in a single chunk
+pub const BLOB_CHUNK_SIZE: usize = 255;
+
+/// The number of most recently completed incoming BlobIds which are remembered
+/// in order to discard chunks which have been delivered more than once
+const COMPLETED_BLOB_IDS_SIZE: u16 = 256;
+
+/// The maximum number of incoming blobs which are reassembled at once. Chunks
+/// of further blobs are discarded, & received again once the sender
+/// retransmits them
+const MAX_INCOMING_BLOBS: usize = 16;
+
+/// How many seconds a partially received blob is kept without receiving any
+/// more of its chunks, before it is discarded
+const INCOMING_BLOB_TIMEOUT_SECONDS: u64 = 30;
+
+/// A piece of a blob, small enough to be written into a single packet
+#[derive(Clone, Debug)]
+pub struct BlobChunk {
+    blob_id: BlobId,
+    chunk_index: u16,
+    total_chunks: u16,
+    data: Rc<Box<[u8]>>,
+}
+
+impl BlobChunk {
+    /// Gets the id of the blob this chunk is a part of
+    pub fn get_blob_id(&self) -> BlobId {
+        return self.blob_id;
+    }
+
+    /// Gets the position of this chunk within the blob
+    pub fn get_chunk_index(&self) -> u16 {
+        return self.chunk_index;
+    }
+
+    /// Gets the total number of chunks the blob has been split into
+    pub fn get_total_chunks(&self) -> u16 {
+        return self.total_chunks;
+    }
+
+    /// Gets the bytes of the blob contained in this chunk
+    pub fn get_data(&self) -> &[u8] {
+        return self.data.as_ref();
+    }
+}
+
+#[derive(Debug)]
+struct OutgoingBlob {
+    total_chunks: u16,
+    delivered_chunks: u16,
+}
+
+#[derive(Debug)]
+struct IncomingBlob {
+    chunks: Vec<Option<Box<[u8]>>>,
+    received_chunks: u16,
+    last_received: Instant,
+}
+
+/// Handles the transfer of large blobs of data, by splitting them into chunks
+/// which are sent as bandwidth allows. Chunks are guaranteed, and only the
+/// chunks that were lost are re-transmitted.
+#[derive(Debug)]
+pub struct BlobManager {
+    bytes_per_second: u32,
+    available_bytes: f32,
+    last_refill: Instant,
+    next_blob_id: BlobId,
+    outgoing_blobs: HashMap<BlobId, OutgoingBlob>,
+    queued_outgoing_chunks: VecDeque<BlobChunk>,
+    sent_chunks: HashMap<u16, Vec<BlobChunk>>,
+    incoming_blobs: HashMap<BlobId, IncomingBlob>,
+    completed_incoming_blobs: SequenceBuffer<bool>,
+    queued_messages: VecDeque<BlobMessage>,
+}
+
+impl BlobManager {
+    /// Creates a new BlobManager, which will spend at most the given number of
+    /// bytes per second on sending blob chunks
+    pub fn new(bytes_per_second: u32) -> Self {
+        BlobManager {
+            bytes_per_second,
+            available_bytes: 0.0,
+            last_refill: Instant::now(),
+            next_blob_id: 0,
+            outgoing_blobs: HashMap::new(),
+            queued_outgoing_chunks: VecDeque::new(),
+            sent_chunks: HashMap::new(),
+            incoming_blobs: HashMap::new(),
+            completed_incoming_blobs: SequenceBuffer::with_capacity(COMPLETED_BLOB_IDS_SIZE),
+            queued_messages: VecDeque::new(),
+        }
+    }
+
+    /// Occurs when a packet has been notified as delivered. Records the
+    /// delivery of any chunks in that packet.
+    pub fn notify_packet_delivered(&mut self, packet_index: u16) {
+        if let Some(delivered_chunks_list) = self.sent_chunks.remove(&packet_index) {
+            for chunk in delivered_chunks_list.into_iter() {
+                let mut finished = false;
+                if let Some(outgoing_blob) = self.outgoing_blobs.get_mut(&chunk.blob_id) {
+                    outgoing_blob.delivered_chunks += 1;
+                    finished = outgoing_blob.delivered_chunks == outgoing_blob.total_chunks;
+                }
+                if finished {
+                    self.outgoing_blobs.remove(&chunk.blob_id);
+                    self.queued_messages
+                        .push_back(BlobMessage::Delivered(chunk.blob_id));
+                }
+            }
+        }
+    }
+
+    /// Occurs when a packet has been notified as having been dropped. Queues up
+    /// any chunks that were lost in the packet for retransmission.
+    pub fn notify_packet_dropped(&mut self, packet_index: u16) {
+        if let Some(dropped_chunks_list) = self.sent_chunks.remove(&packet_index) {
+            for chunk in dropped_chunks_list.into_iter() {
+                self.queued_outgoing_chunks.push_back(chunk);
+            }
+        }
+    }
+
+    /// Splits a blob into chunks, and queues them to be transmitted to the
+    /// remote host. Returns None if the blob is too large to be sent.
+    pub fn queue_outgoing_blob(&mut self, data: &[u8]) -> Option<BlobId> {
+        let total_chunks = (data.len() + BLOB_CHUNK_SIZE - 1) / BLOB_CHUNK_SIZE;
+        if total_chunks == 0 || total_chunks > u16::max_value() as usize {
+            return None;
+        }
+
+        let blob_id = self.next_blob_id;
+        self.next_blob_id = self.next_blob_id.wrapping_add(1);
+
+        for (chunk_index, chunk_data) in data.chunks(BLOB_CHUNK_SIZE).enumerate() {
+            self.queued_outgoing_chunks.push_back(BlobChunk {
+                blob_id,
+                chunk_index: chunk_index as u16,
+                total_chunks: total_chunks as u16,
+                data: Rc::new(chunk_data.to_vec().into_boxed_slice()),
+            });
+        }
+        self.outgoing_blobs.insert(
+            blob_id,
+            OutgoingBlob {
+                total_chunks: total_chunks as u16,
+                delivered_chunks: 0,
+            },
+        );
+
+        return Some(blob_id);
+    }
+
+    /// Gets the progress of an outgoing blob, as a tuple of (chunks delivered,
+    /// total chunks). Returns None if there is no such blob still being sent.
+    pub fn get_outgoing_progress(&self, blob_id: BlobId) -> Option<(u16, u16)> {
+        return self
+            .outgoing_blobs
+            .get(&blob_id)
+            .map(|blob| (blob.delivered_chunks, blob.total_chunks));
+    }
+
+    /// Returns whether there is a queued chunk which can be transmitted to the
+    /// remote host without exceeding the bandwidth limit
+    pub fn has_outgoing_chunks(&mut self) -> bool {
+        self.refill();
+        match self.queued_outgoing_chunks.front() {
+            Some(chunk) => return self.available_bytes >= chunk.data.len() as f32,
+            None => return false,
+        }
+    }
+
+    /// Gets the next queued chunk to be transmitted, if the bandwidth limit
+    /// allows it
+    pub fn pop_outgoing_chunk(&mut self, packet_index: u16) -> Option<BlobChunk> {
+        if !self.has_outgoing_chunks() {
+            return None;
+        }
+
+        let chunk = self.queued_outgoing_chunks.pop_front()?;
+        self.available_bytes -= chunk.data.len() as f32;
+        self.sent_chunks
+            .entry(packet_index)
+            .or_default()
+            .push(chunk.clone());
+        return Some(chunk);
+    }
+
+    /// If the last popped chunk from the queue somehow wasn't able to be
+    /// written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_chunk(&mut self, packet_index: u16, chunk: &BlobChunk) {
+        if let Some(sent_chunks_list) = self.sent_chunks.get_mut(&packet_index) {
+            sent_chunks_list.pop();
+            if sent_chunks_list.is_empty() {
+                self.sent_chunks.remove(&packet_index);
+            }
+        }
+
+        self.available_bytes += chunk.data.len() as f32;
+        self.queued_outgoing_chunks.push_front(chunk.clone());
+    }
+
+    /// Get the next notification regarding the progress of a blob transfer
+    pub fn pop_message(&mut self) -> Option<BlobMessage> {
+        return self.queued_messages.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted chunks, and reassemble
+    /// any blobs which have been completely received. Returns false if the
+    /// section extends past the end of the packet, in which case the rest of
+    /// the packet can't be read
+    pub fn process_data(&mut self, reader: &mut PacketReader, drop_log: &mut DropLog) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        // discard blobs the sender has stopped sending, so that they don't
+        // hold on to memory forever
+        self.incoming_blobs.retain(|_, blob| {
+            blob.last_received.elapsed().as_secs() < INCOMING_BLOB_TIMEOUT_SECONDS
+        });
+
+        let chunk_count = match cursor.read_u8() {
+            Ok(chunk_count) => chunk_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..chunk_count {
+            let (blob_id, chunk_index, total_chunks, payload_length) =
+                match read_chunk_header(cursor) {
+                    Some(header) => header,
+                    None => {
+                        drop_log.record(DropReason::OversizedLength);
+                        return false;
+                    }
+                };
+            let payload_start_position: usize = cursor.position() as usize;
+            let payload_end_position: usize = payload_start_position + (payload_length as usize);
+            if payload_end_position > buffer.len() {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+
+            let chunk_payload = buffer[payload_start_position..payload_end_position]
+                .to_vec()
+                .into_boxed_slice();
+            cursor.set_position(payload_end_position as u64);
+
+            if self.completed_incoming_blobs.exists(blob_id) || chunk_index >= total_chunks {
+                continue;
+            }
+            if !self.incoming_blobs.contains_key(&blob_id)
+                && self.incoming_blobs.len() >= MAX_INCOMING_BLOBS
+            {
+                continue;
+            }
+
+            let incoming_blob =
+                self.incoming_blobs
+                    .entry(blob_id)
+                    .or_insert_with(|| IncomingBlob {
+                        chunks: vec![None; total_chunks as usize],
+                        received_chunks: 0,
+                        last_received: Instant::now(),
+                    });
+            incoming_blob.last_received = Instant::now();
+            let chunk_slot = match incoming_blob.chunks.get_mut(chunk_index as usize) {
+                Some(chunk_slot) => chunk_slot,
+                None => continue,
+            };
+            if chunk_slot.is_some() {
+                // chunk has been received more than once
+                continue;
+            }
+            *chunk_slot = Some(chunk_payload);
+            incoming_blob.received_chunks += 1;
+
+            let received_chunks = incoming_blob.received_chunks;
+            self.queued_messages.push_back(BlobMessage::Progress(
+                blob_id,
+                received_chunks,
+                total_chunks,
+            ));
+
+            if received_chunks == total_chunks {
+                let incoming_blob = self.incoming_blobs.remove(&blob_id).unwrap();
+                let mut blob_bytes = Vec::<u8>::new();
+                for chunk in incoming_blob.chunks.into_iter() {
+                    blob_bytes.extend_from_slice(chunk.unwrap().as_ref());
+                }
+                self.completed_incoming_blobs.insert(blob_id, true);
+                self.queued_messages.push_back(BlobMessage::Received(
+                    blob_id,
+                    blob_bytes.into_boxed_slice(),
+                ));
+            }
+        }
+        return true;
+    }
+
+    // Adds to the bytes available for sending chunks, according to the time
+    // elapsed since the last refill. At most a tenth of a second's worth of
+    // bytes is accumulated, so that chunks are sent steadily rather than in
+    // bursts
+    fn refill(&mut self) {
+        let elapsed = self.last_refill.elapsed();
+        let elapsed_seconds =
+            elapsed.as_secs() as f32 + (elapsed.subsec_nanos() as f32 / 1_000_000_000.0);
+        self.last_refill = Instant::now();
+
+        let max_available_bytes = (self.bytes_per_second as f32 / 10.0).max(BLOB_CHUNK_SIZE as f32);
+        self.available_bytes = (self.available_bytes
+            + (elapsed_seconds * self.bytes_per_second as f32))
+            .min(max_available_bytes);
+    }
+}
+
+// Reads the blob id, chunk index, total number of chunks & payload length
+// which precede the payload of a chunk. Returns None if the packet ends
+// before they do
+fn read_chunk_header(cursor: &mut Cursor<&[u8]>) -> Option<(BlobId, u16, u16, u8)> {
+    let blob_id: BlobId = cursor.read_u16::<BigEndian>().ok()?;
+    let chunk_index = cursor.read_u16::<BigEndian>().ok()?;
+    let total_chunks = cursor.read_u16::<BigEndian>().ok()?;
+    let payload_length = cursor.read_u8().ok()?;
+    return Some((blob_id, chunk_index, total_chunks, payload_length));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{BlobChunk, BlobManager, MAX_INCOMING_BLOBS};
+    use crate::{connection_stats::DropLog, BlobMessage, ManagerType, PacketReader, PacketWriter};
+
+    fn chunk_bytes(blob_id: u16, chunk_index: u16, total_chunks: u16, data: &[u8]) -> Box<[u8]> {
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_blob_chunk(&BlobChunk {
+            blob_id,
+            chunk_index,
+            total_chunks,
+            data: Rc::new(data.to_vec().into_boxed_slice()),
+        }));
+        return writer.get_bytes();
+    }
+
+    fn receive(manager: &mut BlobManager, drop_log: &mut DropLog, bytes: &[u8]) -> bool {
+        let mut reader = PacketReader::new(bytes);
+        assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::Blob);
+        return manager.process_data(&mut reader, drop_log);
+    }
+
+    #[test]
+    fn reassembles_blobs() {
+        let mut manager = BlobManager::new(1024);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        assert!(receive(
+            &mut manager,
+            &mut drop_log,
+            &chunk_bytes(3, 1, 2, &[3, 4])
+        ));
+        assert!(receive(
+            &mut manager,
+            &mut drop_log,
+            &chunk_bytes(3, 0, 2, &[1, 2])
+        ));
+        let mut received = None;
+        while let Some(message) = manager.pop_message() {
+            if let BlobMessage::Received(blob_id, data) = message {
+                received = Some((blob_id, data));
+            }
+        }
+        assert!(received == Some((3, vec![1, 2, 3, 4].into_boxed_slice())));
+    }
+
+    #[test]
+    fn rejects_truncated_chunks() {
+        let mut manager = BlobManager::new(1024);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        let bytes = chunk_bytes(0, 0, 1, &[1, 2, 3]);
+        assert!(!receive(
+            &mut manager,
+            &mut drop_log,
+            &bytes[..bytes.len() - 1]
+        ));
+        assert!(drop_log.get_stats().oversized_length == 1);
+        assert!(manager.pop_message().is_none());
+    }
+
+    #[test]
+    fn limits_partial_blobs() {
+        let mut manager = BlobManager::new(1024);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        for blob_id in 0..(MAX_INCOMING_BLOBS as u16 + 4) {
+            let bytes = chunk_bytes(blob_id, 0, u16::MAX, &[0]);
+            assert!(receive(&mut manager, &mut drop_log, &bytes));
+        }
+        assert!(manager.incoming_blobs.len() == MAX_INCOMING_BLOBS);
+
+        // chunks of blobs already being reassembled are still accepted
+        assert!(receive(
+            &mut manager,
+            &mut drop_log,
+            &chunk_bytes(0, 1, u16::MAX, &[0])
+        ));
+        assert!(manager.incoming_blobs[&0].received_chunks == 2);
+    }
+}
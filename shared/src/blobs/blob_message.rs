@@ -0,0 +1,15 @@
+/// Identifies a blob being transferred to or from a remote host
+pub type BlobId = u16;
+
+/// A notification regarding the progress of a blob transfer, to be handed to
+/// the application
+#[derive(Debug)]
+pub enum BlobMessage {
+    /// Another chunk of an incoming blob has been received, as (blob id,
+    /// chunks received, total chunks)
+    Progress(BlobId, u16, u16),
+    /// An incoming blob has been completely received
+    Received(BlobId, Box<[u8]>),
+    /// Every chunk of an outgoing blob has been delivered to the remote host
+    Delivered(BlobId),
+}
@@ -0,0 +1,2 @@
+pub(crate) mod blob_manager;
+pub(crate) mod blob_message;
@@ -0,0 +1,116 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{config::Config, packet_reader::PacketReader};
+
+/// A set of optional protocol features. During the handshake, the Client
+/// offers the features it has enabled, & the Server answers with those it has
+/// enabled too, which both hosts then use. A feature either host doesn't
+/// support or has disabled is simply left off, so hosts of differing versions
+/// still connect, without it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    bits: u32,
+}
+
+impl Capabilities {
+    /// Packets are written with compact headers, see `Config::compact_headers`
+    pub const COMPACT_HEADERS: Capabilities = Capabilities { bits: 1 << 0 };
+
+    /// Creates an empty set of features
+    pub fn none() -> Self {
+        return Capabilities { bits: 0 };
+    }
+
+    /// Creates a set of features from its bits, as written during the
+    /// handshake. Bits of features unknown to this host are kept, but never
+    /// agreed on, as this host never offers them
+    pub fn from_bits(bits: u32) -> Self {
+        return Capabilities { bits };
+    }
+
+    /// Gets the features this host offers, as enabled in its Config
+    pub fn offered(config: &Config) -> Self {
+        let mut capabilities = Capabilities::none();
+        if config.compact_headers {
+            capabilities.insert(Capabilities::COMPACT_HEADERS);
+        }
+        return capabilities;
+    }
+
+    /// Gets the bits of the set, as written during the handshake
+    pub fn bits(&self) -> u32 {
+        return self.bits;
+    }
+
+    /// Returns whether every feature of the given set is in this one
+    pub fn contains(&self, other: Capabilities) -> bool {
+        return self.bits & other.bits == other.bits;
+    }
+
+    /// Adds every feature of the given set to this one
+    pub fn insert(&mut self, other: Capabilities) {
+        self.bits |= other.bits;
+    }
+
+    /// Removes every feature of the given set from this one
+    pub fn remove(&mut self, other: Capabilities) {
+        self.bits &= !other.bits;
+    }
+
+    /// Gets the features which are in both this set & the given one, which
+    /// are those two hosts agree on
+    pub fn intersection(&self, other: Capabilities) -> Capabilities {
+        return Capabilities::from_bits(self.bits & other.bits);
+    }
+
+    /// Returns whether the set has no features
+    pub fn is_empty(&self) -> bool {
+        return self.bits == 0;
+    }
+
+    /// Writes the set into an outgoing handshake payload
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u32::<BigEndian>(self.bits).unwrap();
+    }
+
+    /// Reads a set from an incoming handshake payload, or returns None if the
+    /// payload is too short
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        return reader
+            .get_cursor()
+            .read_u32::<BigEndian>()
+            .ok()
+            .map(Capabilities::from_bits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Capabilities, Config, PacketReader};
+
+    #[test]
+    fn negotiation() {
+        let mut config = Config::default();
+        assert!(Capabilities::offered(&config).is_empty());
+        config.compact_headers = true;
+        let offered = Capabilities::offered(&config);
+        assert!(offered.contains(Capabilities::COMPACT_HEADERS));
+
+        // a feature from a newer host is never agreed on
+        let mut requested = Capabilities::from_bits(1 << 31);
+        assert!(offered.intersection(requested).is_empty());
+        requested.insert(Capabilities::COMPACT_HEADERS);
+        assert!(offered.intersection(requested) == Capabilities::COMPACT_HEADERS);
+        requested.remove(Capabilities::COMPACT_HEADERS);
+        assert!(!requested.contains(Capabilities::COMPACT_HEADERS));
+    }
+
+    #[test]
+    fn read_write() {
+        let capabilities = Capabilities::from_bits(0x8000_0001);
+        let mut bytes = Vec::new();
+        capabilities.write(&mut bytes);
+        assert!(Capabilities::read(&mut PacketReader::new(&bytes)) == Some(capabilities));
+        assert!(Capabilities::read(&mut PacketReader::new(&bytes[..2])).is_none());
+    }
+}
@@ -0,0 +1,5 @@
+/// A public id which the Server assigns to each connected Client, unique
+/// among the Clients connected at once, so that a Client can address Events
+/// to another Client through the Server without knowing its address. The id
+/// of a Client may be reused once it has disconnected
+pub type ClientId = u16;
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::client_id::ClientId;
+
+/// A change to the set of connected Clients, which the Server sends to every
+/// Client, along with the revision of the roster it was made at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClientRosterChange {
+    /// The Client with the given id connected
+    Joined(ClientId),
+    /// The Client with the given id disconnected, & its id may be reused
+    Left(ClientId),
+}
+
+impl ClientRosterChange {
+    /// Gets the id of the Client which joined or left
+    pub fn get_client_id(&self) -> ClientId {
+        match self {
+            ClientRosterChange::Joined(client_id) | ClientRosterChange::Left(client_id) => {
+                return *client_id
+            }
+        }
+    }
+}
+
+/// The set of Clients connected to the Server, as seen by a Client. Changes
+/// are resent when lost, so they may arrive out of order, & each is applied
+/// only if it is newer than the last change applied for the same Client
+#[derive(Debug, Default)]
+pub struct ClientRoster {
+    clients: HashMap<ClientId, (u32, bool)>,
+}
+
+impl ClientRoster {
+    /// Creates a new, empty ClientRoster
+    pub fn new() -> Self {
+        ClientRoster {
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Applies a change made at the given revision of the roster. Returns
+    /// false if a newer change has already been applied for the same Client,
+    /// or if the change has no effect
+    pub fn apply(&mut self, revision: u32, change: ClientRosterChange) -> bool {
+        let client_id = change.get_client_id();
+        let joined = match change {
+            ClientRosterChange::Joined(_) => true,
+            ClientRosterChange::Left(_) => false,
+        };
+        let was_connected = match self.clients.get(&client_id) {
+            Some((last_revision, _)) if *last_revision >= revision => return false,
+            Some((_, connected)) => *connected,
+            None => false,
+        };
+        // the entry of a Client which left is kept, so that an older change
+        // arriving later can't bring it back
+        self.clients.insert(client_id, (revision, joined));
+        return was_connected != joined;
+    }
+
+    /// Returns whether the Client with the given id is connected
+    pub fn contains(&self, client_id: ClientId) -> bool {
+        return self
+            .clients
+            .get(&client_id)
+            .map_or(false, |(_, connected)| *connected);
+    }
+
+    /// Gets the ids of every connected Client, in ascending order
+    pub fn get_client_ids(&self) -> Vec<ClientId> {
+        let mut output: Vec<ClientId> = self
+            .clients
+            .iter()
+            .filter(|(_, (_, connected))| *connected)
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        output.sort_unstable();
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientRoster, ClientRosterChange};
+
+    #[test]
+    fn apply_out_of_order() {
+        let mut roster = ClientRoster::new();
+        assert!(roster.apply(1, ClientRosterChange::Joined(4)));
+        assert!(roster.apply(2, ClientRosterChange::Joined(9)));
+        assert!(roster.get_client_ids() == vec![4, 9]);
+
+        // the Client leaves & its id is reused, but the changes arrive in the
+        // opposite order, so the Client is still seen as connected
+        assert!(!roster.apply(4, ClientRosterChange::Joined(4)));
+        assert!(!roster.apply(3, ClientRosterChange::Left(4)));
+        assert!(roster.contains(4));
+        assert!(roster.apply(5, ClientRosterChange::Left(4)));
+        assert!(!roster.apply(1, ClientRosterChange::Joined(4)));
+        assert!(!roster.contains(4));
+        assert!(roster.get_client_ids() == vec![9]);
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::instant::Instant;
+
+/// The number of most recent clock samples which the offset & skew of the
+/// remote clock are estimated from
+pub const CLOCK_SYNC_SAMPLE_COUNT: usize = 32;
+
+/// The largest skew which is applied to the local clock, as a fraction of its
+/// rate. Hardware clocks drift by a few dozen parts per million, so a larger
+/// estimate comes from samples skewed by changes in latency
+pub const MAX_CLOCK_SKEW: f64 = 0.001;
+
+// The number of seconds the samples must span before the skew is estimated,
+// as over a shorter span, the jitter of the samples outweighs the drift
+const MIN_SKEW_SPAN: f64 = 30.0;
+
+/// Estimates the clock of a remote host, from the timestamps it sends, as an
+/// offset from the local clock & a skew in the rate the two clocks run at, so
+/// that the remote time can be followed between timestamps, and over long
+/// sessions in which the clocks drift apart
+#[derive(Debug)]
+pub struct ClockSync {
+    start: Instant,
+    // (local time, remote time) of each sample, in seconds
+    samples: VecDeque<(f64, f64)>,
+    offset: f64,
+    skew: f64,
+}
+
+impl ClockSync {
+    /// Creates a new ClockSync, with no samples
+    pub fn new() -> Self {
+        ClockSync {
+            start: Instant::now(),
+            samples: VecDeque::new(),
+            offset: 0.0,
+            skew: 0.0,
+        }
+    }
+
+    /// Records a timestamp of the remote clock which has just been received,
+    /// given the current round trip time in milliseconds. The timestamp was
+    /// taken half a round trip ago
+    pub fn record(&mut self, remote_time: Duration, rtt_ms: f32) {
+        let remote_time = remote_time + Duration::from_secs_f32(rtt_ms.max(0.0) / 2000.0);
+        self.record_sample(self.get_local_time(), remote_time);
+    }
+
+    /// Gets the current time of the remote clock, as estimated from the
+    /// recorded timestamps. Returns None until a timestamp has been recorded
+    pub fn get_remote_time(&self) -> Option<Duration> {
+        return self.estimate_remote_time(self.get_local_time());
+    }
+
+    /// Gets how much faster the remote clock runs than the local clock, as a
+    /// fraction of its rate, such as 0.00005 for 50 parts per million. Limited
+    /// to `MAX_CLOCK_SKEW`, & 0 until the recorded timestamps span long enough
+    /// for the drift to outweigh their jitter
+    pub fn get_skew(&self) -> f64 {
+        return self.skew;
+    }
+
+    /// Returns whether a timestamp has been recorded
+    pub fn is_synced(&self) -> bool {
+        return !self.samples.is_empty();
+    }
+
+    fn get_local_time(&self) -> Duration {
+        let elapsed = self.start.elapsed();
+        return Duration::new(elapsed.as_secs(), elapsed.subsec_nanos());
+    }
+
+    fn record_sample(&mut self, local_time: Duration, remote_time: Duration) {
+        self.samples
+            .push_back((local_time.as_secs_f64(), remote_time.as_secs_f64()));
+        if self.samples.len() > CLOCK_SYNC_SAMPLE_COUNT {
+            self.samples.pop_front();
+        }
+
+        // a least squares fit of the remote time against the local time
+        let count = self.samples.len() as f64;
+        let mean_local = self.samples.iter().map(|(local, _)| local).sum::<f64>() / count;
+        let mean_remote = self.samples.iter().map(|(_, remote)| remote).sum::<f64>() / count;
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (local, remote) in self.samples.iter() {
+            covariance += (local - mean_local) * (remote - mean_remote);
+            variance += (local - mean_local) * (local - mean_local);
+        }
+        let span = match (self.samples.front(), self.samples.back()) {
+            (Some((first, _)), Some((last, _))) => last - first,
+            _ => 0.0,
+        };
+        self.skew = match variance > 0.0 && span >= MIN_SKEW_SPAN {
+            true => (covariance / variance - 1.0).clamp(-MAX_CLOCK_SKEW, MAX_CLOCK_SKEW),
+            false => 0.0,
+        };
+        self.offset = mean_remote - mean_local * (1.0 + self.skew);
+    }
+
+    fn estimate_remote_time(&self, local_time: Duration) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let remote_time = self.offset + local_time.as_secs_f64() * (1.0 + self.skew);
+        return Some(Duration::from_secs_f64(remote_time.max(0.0)));
+    }
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        return ClockSync::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ClockSync, MAX_CLOCK_SKEW};
+
+    fn seconds(seconds: f64) -> Duration {
+        return Duration::from_secs_f64(seconds);
+    }
+
+    #[test]
+    fn follows_offset_and_skew() {
+        let mut clock_sync = ClockSync::new();
+        assert!(clock_sync.estimate_remote_time(seconds(1.0)).is_none());
+
+        // the remote clock started 100 seconds earlier, & runs 50ppm faster
+        for index in 0..40 {
+            let local = index as f64 * 2.0;
+            let remote = 100.0 + local * 1.00005;
+            clock_sync.record_sample(seconds(local), seconds(remote));
+        }
+        assert!((clock_sync.get_skew() - 0.00005).abs() < 0.000001);
+        let estimate = clock_sync.estimate_remote_time(seconds(3600.0)).unwrap();
+        assert!((estimate.as_secs_f64() - (100.0 + 3600.0 * 1.00005)).abs() < 0.001);
+    }
+
+    #[test]
+    fn single_sample_is_an_offset() {
+        let mut clock_sync = ClockSync::new();
+        clock_sync.record_sample(seconds(5.0), seconds(20.0));
+        assert!(clock_sync.get_skew() == 0.0);
+        let estimate = clock_sync.estimate_remote_time(seconds(7.0)).unwrap();
+        assert!((estimate.as_secs_f64() - 22.0).abs() < 0.000001);
+    }
+
+    #[test]
+    fn limits_skew() {
+        let mut clock_sync = ClockSync::new();
+        clock_sync.record_sample(seconds(0.0), seconds(0.0));
+        clock_sync.record_sample(seconds(1.0), seconds(1.5));
+        // too short a span to tell drift from jitter
+        assert!(clock_sync.get_skew() == 0.0);
+        clock_sync.record_sample(seconds(40.0), seconds(60.0));
+        assert!(clock_sync.get_skew() == MAX_CLOCK_SKEW);
+    }
+}
@@ -0,0 +1,102 @@
+/// The longest run of repeated bytes, or of literal bytes, which is encoded by
+/// a single header byte
+const MAX_RUN_LENGTH: usize = 128;
+
+/// Compresses bytes with a run-length encoding (PackBits). A header byte below
+/// 128 is followed by that many plus one literal bytes, and a header byte of
+/// 128 or above is followed by a single byte, repeated the header minus 125
+/// times. Serialized Entities are mostly made up of zeroed & repeated bytes,
+/// which this encodes compactly
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut literal_start = 0;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let mut run_length = 1;
+        while index + run_length < bytes.len()
+            && bytes[index + run_length] == bytes[index]
+            && run_length < MAX_RUN_LENGTH + 2
+        {
+            run_length += 1;
+        }
+
+        if run_length >= 3 {
+            write_literals(&mut output, &bytes[literal_start..index]);
+            output.push((run_length + 125) as u8);
+            output.push(bytes[index]);
+            index += run_length;
+            literal_start = index;
+        } else {
+            index += run_length;
+        }
+    }
+    write_literals(&mut output, &bytes[literal_start..]);
+
+    return output;
+}
+
+/// Decompresses bytes which were compressed with `compress()`. Returns None if
+/// the bytes are malformed
+pub fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let header = bytes[index] as usize;
+        index += 1;
+        if header < MAX_RUN_LENGTH {
+            let literals = bytes.get(index..index + header + 1)?;
+            output.extend_from_slice(literals);
+            index += header + 1;
+        } else {
+            let repeated_byte = *bytes.get(index)?;
+            for _ in 0..(header - 125) {
+                output.push(repeated_byte);
+            }
+            index += 1;
+        }
+    }
+
+    return Some(output);
+}
+
+fn write_literals(output: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_RUN_LENGTH) {
+        output.push((chunk.len() - 1) as u8);
+        output.extend_from_slice(chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compress, decompress};
+
+    #[test]
+    fn round_trip() {
+        let bytes: Vec<u8> = vec![1, 2, 3, 0, 0, 0, 0, 0, 4, 4, 5, 6, 6, 6, 7];
+        let compressed = compress(&bytes);
+        assert!(decompress(&compressed).unwrap() == bytes);
+    }
+
+    #[test]
+    fn long_runs() {
+        let mut bytes: Vec<u8> = vec![9; 1000];
+        bytes.extend((0..300).map(|n| n as u8));
+        let compressed = compress(&bytes);
+        assert!(compressed.len() < bytes.len());
+        assert!(decompress(&compressed).unwrap() == bytes);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(compress(&[]).is_empty());
+        assert!(decompress(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(decompress(&[4, 1, 2]).is_none());
+        assert!(decompress(&[200]).is_none());
+    }
+}
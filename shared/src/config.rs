@@ -1,19 +1,50 @@
 use std::{default::Default, time::Duration};
 
+use crate::{
+    address_family::AddressFamily,
+    backpressure::BackpressureThresholds,
+    connection_quality::ConnectionQualityThresholds,
+    entities::{
+        correction::CorrectionCurve, extrapolation::ExtrapolationModel,
+        late_update::LateUpdatePolicy,
+    },
+    link_conditioner::LinkConditionerConfig,
+    link_stall::StallThresholds,
+    random_source::RandomSource,
+};
+
 /// Contains Config properties which will be used by a Server or Client
 #[derive(Clone, Debug)]
 pub struct Config {
-    /// The duration between each tick to be emitted by the Server (Client does
-    /// not emit Tick events just yet)
+    /// The duration between each tick to be emitted by the Server. On the
+    /// Client, the length of the ticks counted by `NaiaClient::frame_begin()`
     pub tick_interval: Duration,
     /// The duration between the resend of certain connection handshake messages
     pub send_handshake_interval: Duration,
+    /// The duration the Client waits for the Server to accept each attempt to
+    /// connect, before it retries or gives up. It should allow for the two
+    /// steps of the handshake, each sent every `send_handshake_interval`
+    pub connect_timeout: Duration,
+    /// The number of times the Client retries connecting after its first
+    /// attempt has timed out, before emitting a `ClientEvent::ConnectFailed`.
+    /// None, the default, retries forever
+    pub connect_retries: Option<u16>,
+    /// The duration the Client waits before its first retry to connect. It
+    /// doubles with every later retry, up to 32 times this duration
+    pub connect_retry_backoff: Duration,
     /// The duration to wait for communication from a remote host before
     /// initiating a disconnect
     pub disconnection_timeout_duration: Duration,
     /// The duration to wait before sending a heartbeat message to a remote
     /// host, if the host has not already sent another message within that time.
     pub heartbeat_interval: Duration,
+    /// The Server's heartbeats carry its clock, from which each Client
+    /// estimates how far its own clock is offset from, & drifts from, the
+    /// Server's. To keep the estimate current, the Server sends a heartbeat
+    /// to each Client at least this often, even while it is sending other
+    /// data. It is checked as often as the heartbeats are. None only sends
+    /// the clock with the regular heartbeats
+    pub clock_sync_interval: Option<Duration>,
     /// Value that specifies the factor used to smooth out network jitter. It
     /// defaults to 10% of the round-trip time. It is expressed as a ratio, with
     /// 0 equal to 0% and 1 equal to 100%.
@@ -21,6 +52,167 @@ pub struct Config {
     /// Value which specifies the maximum round trip time before we consider it
     /// a problem. This is expressed in milliseconds.
     pub rtt_max_value: u16,
+    /// The number of most recent Round Trip Time samples which statistics are
+    /// computed from, for `NaiaClient::get_rtt_stats()` &
+    /// `NaiaServer::get_rtt_stats()`
+    pub rtt_history_size: usize,
+    /// How far the target interpolation delay of the Client must fall below
+    /// the current delay before `NaiaClient::get_interpolation_delay()` is
+    /// lowered, so that it does not fluctuate with every small change in
+    /// jitter
+    pub interpolation_delay_hysteresis: Duration,
+    /// The number of ticks ahead of the Server which the input of each Client
+    /// should arrive. The Server asks each Client to speed up or slow down its
+    /// tick clock, by a time dilation written in the packet header, to keep
+    /// its input buffer at this depth
+    pub input_buffer_target_depth: u16,
+    /// The number of most recent unacknowledged commands sent with
+    /// `NaiaClient::send_command()` which every packet carries, so that the
+    /// command of a tick still arrives when the packet first carrying it is
+    /// lost
+    pub command_redundancy: u8,
+    /// Whether to write packet headers in a compact form, which carries only
+    /// the low byte of each sequence number & acks only the last 16 packets,
+    /// shrinking the header from 13 bytes to as few as 5. Negotiated during
+    /// the handshake as one of the connection's Capabilities, so it is used
+    /// only if both the Client & the Server enable it. Compact headers suit low-rate connections, where packets never
+    /// arrive more than 128 sequence numbers out of order
+    pub compact_headers: bool,
+    /// The Round Trip Time, jitter & packet loss thresholds used to classify
+    /// the ConnectionQuality of each connection
+    pub connection_quality_thresholds: ConnectionQualityThresholds,
+    /// The depths of the outgoing queues of a connection at which it is
+    /// reported as congested, with a `ServerEvent::Congested` or
+    /// `ClientEvent::Congested`, and as relieved again once the remote host
+    /// catches up
+    pub backpressure_thresholds: BackpressureThresholds,
+    /// The thresholds at which the Server reduces, or pauses, the Entity
+    /// updates sent to a Client whose link is losing packets, or has stopped
+    /// acknowledging them, rather than sending them into a link which isn't
+    /// delivering them. Heartbeats keep being sent, so that the link is
+    /// noticed recovering. None, by default, always sends Entity updates
+    pub stall_thresholds: Option<StallThresholds>,
+    /// The maximum number of Entity updates the Server will send to a given
+    /// Client each tick. When more Entities than this have been mutated, the
+    /// ones with the highest accumulated priority are sent first, and the rest
+    /// wait for a later tick. None means there is no limit.
+    pub max_entity_updates_per_tick: Option<u16>,
+    /// The duration to wait for a response to an RPC request before it is
+    /// considered to have failed
+    pub request_timeout: Duration,
+    /// The maximum number of bytes per second that will be spent on sending
+    /// chunks of blobs, so that large transfers do not starve other traffic
+    pub blob_bandwidth_limit: u32,
+    /// The maximum number of bytes of raw payloads which will be attached to
+    /// a single outgoing packet, leaving the rest of the packet for Events &
+    /// Entity updates. Each payload takes a byte for its length, so payloads
+    /// larger than one byte less than the budget can't be sent
+    pub raw_payload_budget: usize,
+    /// The greatest fraction of each outgoing packet, from 0 to 1, which
+    /// Events may fill, including RPCs, timed Events, Events targeted at an
+    /// Entity & relayed Events, so that a flood of Events can't starve Entity
+    /// updates. 1 by default, which doesn't limit them
+    pub event_packet_share: f32,
+    /// The greatest fraction of each outgoing packet, from 0 to 1, which
+    /// Entity messages may fill, such as 0.6, so that a flood of Entity
+    /// updates can't starve Events. 1 by default, which doesn't limit them
+    pub entity_packet_share: f32,
+    /// The duration for which the key of a deleted Entity is retired. During
+    /// this period the Server will not reuse the key, and the Client ignores
+    /// late Updates addressed to it
+    pub entity_tombstone_duration: Duration,
+    /// Whether the Client should keep a copy of each Entity as it was before
+    /// its most recent Update, retrievable with
+    /// `NaiaClient::get_previous_entity()`. This costs a copy of the Entity
+    /// for every Update received, so it is disabled by default
+    pub keep_previous_entity_values: bool,
+    /// What the Client does with an Entity Update which arrives after a newer
+    /// Update of the same Properties, for Entity types without a policy set
+    /// with `NaiaClient::set_late_update_policy()`
+    pub late_update_policy: LateUpdatePolicy,
+    /// The duration over which the offset recorded by
+    /// `NaiaClient::record_correction()` decays to zero, and over which an
+    /// extrapolated Entity blends onto the path reported by a new update
+    pub correction_duration: Duration,
+    /// The curve over which the offset recorded by
+    /// `NaiaClient::record_correction()` decays to zero, and over which an
+    /// extrapolated Entity blends onto the path reported by a new update
+    pub correction_curve: CorrectionCurve,
+    /// The model by which the Client extrapolates the position of remote
+    /// Entities, see `NaiaClient::on_sample_motion()`
+    pub extrapolation_model: ExtrapolationModel,
+    /// The longest duration past an update for which the Client extrapolates
+    /// the position of a remote Entity, after which it stops moving until the
+    /// next update, so that a stalled connection doesn't send Entities off
+    /// into the distance
+    pub max_extrapolation: Duration,
+    /// Whether the Server should group the Entity messages of each tick into
+    /// a batch, which the Client applies all at once, so that it never
+    /// observes a partially applied tick. A batch is held back by the Client
+    /// until every message in it has arrived, so a dropped packet delays the
+    /// whole batch
+    pub atomic_entity_ticks: bool,
+    /// How long the Client holds a packet from the Server which arrives ahead
+    /// of an earlier packet, waiting for the earlier one, so that packets are
+    /// applied in the order they were sent. Set to a few milliseconds, around
+    /// the jitter of the link, this trades a little latency for fewer late
+    /// Entity updates & the corrections they cause. None, by default, applies
+    /// every packet as soon as it arrives
+    pub reorder_buffer_duration: Option<Duration>,
+    /// How often the Server serializes the Entities of each Room into a
+    /// compressed snapshot. A Client joining a Room with a snapshot receives
+    /// it in bulk, followed by updates for whatever has changed since it was
+    /// taken, instead of every Entity's creation individually. None disables
+    /// the snapshot cache
+    pub snapshot_cache_interval: Option<Duration>,
+    /// The rate at which the Server answers unconnected requests, such as
+    /// pings & status queries, from any one IP address, per second. Requests
+    /// beyond the rate are ignored, so that the Server can't be used to flood
+    /// another host by forging its address
+    pub unconnected_request_rate: f32,
+    /// The number of unconnected requests from one IP address which the
+    /// Server answers in a burst, before `unconnected_request_rate` applies
+    pub unconnected_request_burst: u16,
+    /// Whether the Server listens on every local address of both IPv4 &
+    /// IPv6, at the port of the address it is given, instead of only that
+    /// address. IPv4 Clients then appear with IPv4-mapped IPv6 addresses.
+    /// This relies on the platform letting an IPv6 socket accept IPv4
+    /// traffic, which is the default on Linux & macOS, but not on Windows
+    pub dual_stack: bool,
+    /// The address family preferred when a host has addresses of both, such
+    /// as when resolving the Server's host name with `resolve_address()`, or
+    /// finding a local address with `find_my_ip_address_in()`
+    pub preferred_address_family: AddressFamily,
+    /// Whether to log a warning when a packet received on a connection is
+    /// dropped for being malformed, at most once every
+    /// `DROP_WARNING_INTERVAL` per connection. Dropped packets are counted in
+    /// the connection's `ConnectionStats` either way
+    pub log_dropped_packets: bool,
+    /// Whether a connection is closed as soon as the remote host violates the
+    /// protocol, by sending a packet which would otherwise be dropped for
+    /// being malformed, instead of skipping what can't be read. The Server
+    /// emits a `ServerEvent::ProtocolViolation`, and the Client a
+    /// `ClientEvent::ProtocolViolation`, before the Disconnection
+    pub strict_protocol: bool,
+    /// Whether each connection rejects a packet whose sequence number it has
+    /// already received, or which is older than the newest packet received
+    /// by `REPLAY_WINDOW_SIZE` or more, so that captured packets can't be
+    /// replayed into the session. Rejected packets are counted in the
+    /// connection's `ConnectionStats`, but aren't a protocol violation, as
+    /// the network may duplicate packets too. Packets are only protected
+    /// against being forged with a new sequence number by a transport which
+    /// authenticates them, such as WebRTC's DTLS
+    pub replay_protection: bool,
+    /// Simulates a lossy network by dropping a portion of incoming packets,
+    /// for testing. None disables the link conditioner, and it should not be
+    /// enabled in production
+    pub link_conditioner: Option<LinkConditionerConfig>,
+    /// The source of the random bytes used for the Server's handshake &
+    /// master server challenge keys, & for its migration & transfer tokens.
+    /// None uses the system's secure random number generator. A seeded
+    /// source makes tests & replays deterministic, and should not be used in
+    /// production
+    pub random_source: Option<RandomSource>,
 }
 
 impl Default for Config {
@@ -29,9 +221,46 @@ impl Default for Config {
             tick_interval: Duration::from_secs(1),
             disconnection_timeout_duration: Duration::from_secs(10),
             heartbeat_interval: Duration::from_secs(4),
+            clock_sync_interval: Some(Duration::from_secs(2)),
             send_handshake_interval: Duration::from_secs(1),
+            connect_timeout: Duration::from_secs(5),
+            connect_retries: None,
+            connect_retry_backoff: Duration::from_secs(1),
             rtt_smoothing_factor: 0.10,
             rtt_max_value: 250,
+            rtt_history_size: 64,
+            interpolation_delay_hysteresis: Duration::from_millis(10),
+            input_buffer_target_depth: 2,
+            command_redundancy: 4,
+            compact_headers: false,
+            connection_quality_thresholds: ConnectionQualityThresholds::default(),
+            backpressure_thresholds: BackpressureThresholds::default(),
+            stall_thresholds: None,
+            max_entity_updates_per_tick: None,
+            request_timeout: Duration::from_secs(10),
+            blob_bandwidth_limit: 32 * 1024,
+            raw_payload_budget: 256,
+            event_packet_share: 1.0,
+            entity_packet_share: 1.0,
+            entity_tombstone_duration: Duration::from_secs(2),
+            keep_previous_entity_values: false,
+            late_update_policy: LateUpdatePolicy::ApplyNewerFields,
+            correction_duration: Duration::from_millis(200),
+            correction_curve: CorrectionCurve::EaseOut,
+            extrapolation_model: ExtrapolationModel::Linear,
+            max_extrapolation: Duration::from_millis(250),
+            atomic_entity_ticks: false,
+            reorder_buffer_duration: None,
+            snapshot_cache_interval: None,
+            unconnected_request_rate: 2.0,
+            unconnected_request_burst: 8,
+            dual_stack: false,
+            preferred_address_family: AddressFamily::Ipv4,
+            log_dropped_packets: true,
+            strict_protocol: false,
+            replay_protection: false,
+            link_conditioner: None,
+            random_source: None,
         }
     }
 }
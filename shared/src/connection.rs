@@ -1,19 +1,49 @@
-use std::{net::SocketAddr, rc::Rc};
+use std::{any::TypeId, net::SocketAddr, rc::Rc, time::Duration};
 
 use crate::Timer;
 
 use super::{
+    ack_latency::AckLatencyHistogram,
     ack_manager::AckManager,
-    entities::{entity_notifiable::EntityNotifiable, entity_type::EntityType},
-    events::{event::Event, event_manager::EventManager, event_type::EventType},
+    backpressure::{BackpressureMonitor, Congestion},
+    blobs::{
+        blob_manager::{BlobChunk, BlobManager},
+        blob_message::{BlobId, BlobMessage},
+    },
+    capabilities::Capabilities,
+    client_id::ClientId,
+    client_roster::ClientRosterChange,
+    connection_quality::{ConnectionQuality, ConnectionQualityMonitor},
+    connection_stats::{ConnectionStats, DropLog, DropReason},
+    entities::{
+        entity_notifiable::EntityNotifiable, entity_type::EntityType,
+        local_entity_key::LocalEntityKey,
+    },
+    events::{
+        command_stats::CommandStats,
+        event::{Event, EventId},
+        event_manager::EventManager,
+        event_queue_stats::EventQueueStats,
+        event_type::EventType,
+        rpc_message::RpcMessage,
+    },
+    link_stall::{LinkState, StallMonitor},
+    manager_type::ManagerType,
     manifest::Manifest,
+    manifest_descriptor::ManifestDescriptor,
     packet_reader::PacketReader,
     packet_type::PacketType,
-    rtt::rtt_tracker::RttTracker,
+    raw_payload_manager::RawPayloadManager,
+    replay_window::ReplayWindow,
+    rtt::{rtt_history::RttStats, rtt_tracker::RttTracker},
     sequence_buffer::SequenceNumber,
-    standard_header::StandardHeader,
+    tick_rate_change::TickRateChange,
 };
 
+/// The maximum number of bytes of application data which can be attached to
+/// heartbeat packets
+pub const MAX_HEARTBEAT_PAYLOAD_SIZE: usize = 32;
+
 /// Represents a connection to a remote host, and provides functionality to
 /// manage the connection and the communications to it
 #[derive(Debug)]
@@ -24,6 +54,20 @@ pub struct Connection<T: EventType> {
     ack_manager: AckManager,
     rtt_tracker: RttTracker,
     event_manager: EventManager<T>,
+    blob_manager: BlobManager,
+    raw_payload_manager: RawPayloadManager,
+    quality_monitor: ConnectionQualityMonitor,
+    drop_log: DropLog,
+    backpressure_monitor: BackpressureMonitor,
+    stall_monitor: StallMonitor,
+    heartbeat_payload: Box<[u8]>,
+    host_tick: Option<u16>,
+    time_dilation: i8,
+    remote_tick: Option<u16>,
+    remote_sequence: Option<SequenceNumber>,
+    remote_time_dilation: i8,
+    capabilities: Capabilities,
+    replay_window: Option<ReplayWindow>,
 }
 
 impl<T: EventType> Connection<T> {
@@ -35,6 +79,13 @@ impl<T: EventType> Connection<T> {
         ack_manager: AckManager,
         rtt_tracker: RttTracker,
         event_manager: EventManager<T>,
+        blob_manager: BlobManager,
+        raw_payload_manager: RawPayloadManager,
+        quality_monitor: ConnectionQualityMonitor,
+        drop_log: DropLog,
+        backpressure_monitor: BackpressureMonitor,
+        stall_monitor: StallMonitor,
+        replay_window: Option<ReplayWindow>,
     ) -> Self {
         return Connection {
             address,
@@ -43,6 +94,20 @@ impl<T: EventType> Connection<T> {
             ack_manager,
             rtt_tracker,
             event_manager,
+            blob_manager,
+            raw_payload_manager,
+            quality_monitor,
+            drop_log,
+            backpressure_monitor,
+            stall_monitor,
+            heartbeat_payload: Box::new([]),
+            host_tick: None,
+            time_dilation: 0,
+            remote_tick: None,
+            remote_sequence: None,
+            remote_time_dilation: 0,
+            capabilities: Capabilities::none(),
+            replay_window,
         };
     }
 
@@ -57,6 +122,23 @@ impl<T: EventType> Connection<T> {
         return self.heartbeat_manager.ringing();
     }
 
+    /// Set the application data attached to every heartbeat sent to the remote
+    /// host. Returns false if the payload is larger than
+    /// `MAX_HEARTBEAT_PAYLOAD_SIZE`
+    pub fn set_heartbeat_payload(&mut self, payload: &[u8]) -> bool {
+        if payload.len() > MAX_HEARTBEAT_PAYLOAD_SIZE {
+            return false;
+        }
+        self.heartbeat_payload = payload.to_vec().into_boxed_slice();
+        return true;
+    }
+
+    /// Get the application data attached to every heartbeat sent to the
+    /// remote host
+    pub fn get_heartbeat_payload(&self) -> &[u8] {
+        return &self.heartbeat_payload;
+    }
+
     /// Record that a message has been received from a remote host (to prevent
     /// disconnecting from the remote host)
     pub fn mark_heard(&mut self) {
@@ -71,19 +153,88 @@ impl<T: EventType> Connection<T> {
 
     /// Process an incoming packet, pulling out the packet index number to keep
     /// track of the current RTT, and sending the packet to the AckManager to
-    /// handle packet notification events
+    /// handle packet notification events. A packet too short to hold its
+    /// header, acknowledging a packet which hasn't been sent yet, or rejected
+    /// as a replay, is dropped, and an empty payload returned
     pub fn process_incoming_header(
         &mut self,
         payload: &[u8],
         entity_notifiable: &mut Option<&mut dyn EntityNotifiable>,
     ) -> Box<[u8]> {
-        let incoming_sequence_number = StandardHeader::get_sequence(payload);
-        self.rtt_tracker.process_incoming(incoming_sequence_number);
-        return self.ack_manager.process_incoming(
+        let sequence = match self.ack_manager.get_incoming_sequence(payload) {
+            Some(sequence) => sequence,
+            None => {
+                self.drop_log.record(DropReason::OversizedLength);
+                return Box::new([]);
+            }
+        };
+        if let Some(replay_window) = &self.replay_window {
+            if replay_window.is_replayed(sequence) {
+                self.drop_log.record(DropReason::ReplayedPacket);
+                return Box::new([]);
+            }
+        }
+        let (header, stripped_payload) = match self.ack_manager.process_incoming(
             payload,
             &mut self.event_manager,
+            &mut self.blob_manager,
             entity_notifiable,
-        );
+        ) {
+            Some(incoming) => incoming,
+            None => {
+                self.drop_log.record(DropReason::ImpossibleSequence);
+                return Box::new([]);
+            }
+        };
+        if let Some(replay_window) = &mut self.replay_window {
+            replay_window.record(sequence);
+        }
+        self.rtt_tracker.process_incoming(header.sequence());
+        self.rtt_tracker.process_ack(header.ack_seq());
+        self.remote_tick = header.host_tick();
+        self.remote_sequence = Some(header.sequence());
+        self.remote_time_dilation = header.time_dilation();
+        return stripped_payload;
+    }
+
+    /// Set the optional features which both hosts agreed on during the
+    /// handshake, such as whether outgoing packets are written with compact
+    /// headers
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+        self.ack_manager
+            .set_compact_headers(capabilities.contains(Capabilities::COMPACT_HEADERS));
+    }
+
+    /// Get the optional features which both hosts agreed on during the
+    /// handshake
+    pub fn get_capabilities(&self) -> Capabilities {
+        return self.capabilities;
+    }
+
+    /// Returns whether outgoing packets are written with compact headers
+    pub fn has_compact_headers(&self) -> bool {
+        return self.ack_manager.has_compact_headers();
+    }
+
+    /// Get the sequence numbers the connection has reached, as the index of
+    /// the next outgoing packet, the most recent incoming packet & the ack
+    /// bitfield of those before it
+    pub fn get_sequence_state(&self) -> (u16, u16, u32) {
+        return self.ack_manager.get_sequence_state();
+    }
+
+    /// Resume from the sequence numbers another connection to the same remote
+    /// host had reached, as given by `get_sequence_state()`
+    pub fn resume_sequences(
+        &mut self,
+        local_sequence: u16,
+        remote_sequence: u16,
+        ack_bitfield: u32,
+    ) {
+        return self
+            .ack_manager
+            .resume(local_sequence, remote_sequence, ack_bitfield);
     }
 
     /// Given a packet payload, start tracking the packet via it's index, attach
@@ -96,7 +247,126 @@ impl<T: EventType> Connection<T> {
     ) -> Box<[u8]> {
         self.rtt_tracker
             .process_outgoing(self.ack_manager.local_sequence_num());
-        return self.ack_manager.process_outgoing(packet_type, payload);
+        return self.ack_manager.process_outgoing(
+            packet_type,
+            payload,
+            self.host_tick,
+            self.time_dilation,
+        );
+    }
+
+    /// Set the tick of this host, which is written into the header of every
+    /// outgoing packet
+    pub fn set_host_tick(&mut self, host_tick: Option<u16>) {
+        self.host_tick = host_tick;
+    }
+
+    /// Set the time dilation requested of the remote host, in thousandths of
+    /// its tick rate, which is written into the header of every outgoing
+    /// packet
+    pub fn set_time_dilation(&mut self, time_dilation: i8) {
+        self.time_dilation = time_dilation;
+    }
+
+    /// Get the tick of the remote host written in the most recently received
+    /// packet, or None if the remote host did not write one
+    pub fn get_remote_tick(&self) -> Option<u16> {
+        return self.remote_tick;
+    }
+
+    /// Get the sequence number of the most recently received packet, or None
+    /// if no packet has been received
+    pub fn get_remote_sequence(&self) -> Option<SequenceNumber> {
+        return self.remote_sequence;
+    }
+
+    /// Get the time dilation requested by the remote host in the most recently
+    /// received packet, in thousandths of this host's tick rate
+    pub fn get_remote_time_dilation(&self) -> i8 {
+        return self.remote_time_dilation;
+    }
+
+    /// Get the fraction of the most recently sent packets which were lost,
+    /// from 0 to 1, or None if no sent packet has been acked or dropped yet
+    pub fn get_packet_loss(&self) -> Option<f32> {
+        return self.ack_manager.get_packet_loss();
+    }
+
+    /// Get the histogram of the time between sending each packet & receiving
+    /// its ack
+    pub fn get_ack_latency(&self) -> &AckLatencyHistogram {
+        return self.ack_manager.get_ack_latency();
+    }
+
+    /// Clear the histogram of ack latencies, so that it can be sampled over a
+    /// chosen period
+    pub fn clear_ack_latency(&mut self) {
+        self.ack_manager.clear_ack_latency();
+    }
+
+    /// Classify the quality of the connection from its current Round Trip
+    /// Time, jitter & packet loss, returning the new quality if it has changed
+    pub fn update_quality(&mut self) -> Option<ConnectionQuality> {
+        if !self.quality_monitor.should_update() {
+            return None;
+        }
+        let rtt_stats = self.rtt_tracker.get_rtt_stats();
+        let packet_loss = self.ack_manager.get_packet_loss();
+        return self.quality_monitor.update(rtt_stats.as_ref(), packet_loss);
+    }
+
+    /// Get the current quality of the connection, or None if it has not been
+    /// classified yet
+    pub fn get_quality(&self) -> Option<ConnectionQuality> {
+        return self.quality_monitor.get_quality();
+    }
+
+    /// Measure the depth of the outgoing queues, adding the given number of
+    /// undelivered messages the Connection doesn't manage itself, such as
+    /// Entity messages, and return the change in congestion, if any. Queued
+    /// Events which aren't guaranteed are dropped as the connection becomes
+    /// congested, if the thresholds say so
+    pub fn update_backpressure(&mut self, extra_depth: usize) -> Option<Congestion> {
+        let queue_depth = self.event_manager.get_undelivered_count() + extra_depth;
+        let change = self.backpressure_monitor.update(queue_depth);
+        if change == Some(Congestion::Congested)
+            && self.backpressure_monitor.should_drop_unguaranteed()
+        {
+            self.event_manager.drop_queued_unguaranteed_events();
+        }
+        return change;
+    }
+
+    /// Returns whether the outgoing queues of the connection are congested
+    pub fn is_congested(&self) -> bool {
+        return self.backpressure_monitor.is_congested();
+    }
+
+    /// Returns whether Events which aren't guaranteed should be dropped
+    /// instead of queued, because the connection is congested
+    pub fn should_drop_unguaranteed(&self) -> bool {
+        return self.backpressure_monitor.should_drop_unguaranteed();
+    }
+
+    /// Judge the state of the link from its packet loss & the age of its
+    /// oldest unacknowledged packet, and return the new state if it has
+    /// changed
+    pub fn update_link_state(&mut self) -> Option<LinkState> {
+        return self.stall_monitor.update(
+            self.ack_manager.get_packet_loss(),
+            self.ack_manager.get_oldest_unacked_age(),
+        );
+    }
+
+    /// Get the current state of the link
+    pub fn get_link_state(&self) -> LinkState {
+        return self.stall_monitor.get_state();
+    }
+
+    /// Returns whether Entity updates should be sent on the given tick, given
+    /// the state of the link
+    pub fn should_send_entity_updates(&self, tick: u16) -> bool {
+        return self.stall_monitor.should_send_updates(tick);
     }
 
     /// Get the next outgoing packet's index
@@ -105,36 +375,57 @@ impl<T: EventType> Connection<T> {
     }
 
     /// Queue up an event to be sent to the remote host
-    pub fn queue_event(&mut self, event: &impl Event<T>) {
+    pub fn queue_event(&mut self, event: &dyn Event<T>) -> EventId {
         return self.event_manager.queue_outgoing_event(event);
     }
 
+    /// Queue up an event to be sent to the remote host, which is dropped if it
+    /// can't be sent within the given time-to-live
+    pub fn queue_event_with_ttl(&mut self, event: &dyn Event<T>, ttl: Duration) -> EventId {
+        return self.event_manager.queue_outgoing_event_with_ttl(event, ttl);
+    }
+
+    /// Queue up an already boxed event to be sent to the remote host
+    pub fn queue_boxed_event(&mut self, event: Box<dyn Event<T>>) -> EventId {
+        return self.event_manager.queue_outgoing_boxed_event(event);
+    }
+
     /// Returns whether there are events to be sent to the remote host
     pub fn has_outgoing_events(&self) -> bool {
         return self.event_manager.has_outgoing_events();
     }
 
     /// Pop the next outgoing event from the queue
-    pub fn pop_outgoing_event(&mut self, next_packet_index: u16) -> Option<Rc<Box<dyn Event<T>>>> {
+    pub fn pop_outgoing_event(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(EventId, Rc<Box<dyn Event<T>>>)> {
         return self.event_manager.pop_outgoing_event(next_packet_index);
     }
 
     /// If for some reason the next outgoing event could not be written into a
     /// message and sent, place it back into the front of the queue
-    pub fn unpop_outgoing_event(&mut self, next_packet_index: u16, event: &Rc<Box<dyn Event<T>>>) {
+    pub fn unpop_outgoing_event(
+        &mut self,
+        next_packet_index: u16,
+        event: &(EventId, Rc<Box<dyn Event<T>>>),
+    ) {
         return self
             .event_manager
             .unpop_outgoing_event(next_packet_index, event);
     }
 
     /// Given an incoming packet which has been identified as an event, send the
-    /// data to the EventManager for processing
+    /// data to the EventManager for processing. Returns false if the rest of
+    /// the packet can't be read
     pub fn process_event_data<U: EntityType>(
         &mut self,
         reader: &mut PacketReader,
         manifest: &Manifest<T, U>,
-    ) {
-        return self.event_manager.process_data(reader, manifest);
+    ) -> bool {
+        return self
+            .event_manager
+            .process_data(reader, manifest, &mut self.drop_log);
     }
 
     /// Get the most recent event that has been received from a remote host
@@ -142,6 +433,616 @@ impl<T: EventType> Connection<T> {
         return self.event_manager.pop_incoming_event();
     }
 
+    /// Get every guaranteed event which has yet to be delivered to the remote
+    /// host, along with the id the next queued event will be given
+    pub fn get_undelivered_events(&self) -> (EventId, Vec<(EventId, Rc<Box<dyn Event<T>>>)>) {
+        return self.event_manager.get_undelivered_events();
+    }
+
+    /// Queue up events which were undelivered on a connection with another
+    /// host, keeping their ids
+    pub fn restore_undelivered_events(
+        &mut self,
+        next_event_id: EventId,
+        events: Vec<(EventId, Box<dyn Event<T>>)>,
+    ) {
+        return self
+            .event_manager
+            .restore_undelivered_events(next_event_id, events);
+    }
+
+    /// Take over the record of which events have been received from a previous
+    /// connection, so that they aren't received twice
+    pub fn carry_over_received_events(&mut self, previous: &mut Connection<T>) {
+        return self
+            .event_manager
+            .carry_over_received_event_ids(&mut previous.event_manager);
+    }
+
+    /// Get the id of the next event which has been confirmed as delivered to
+    /// the remote host
+    pub fn get_delivered_event_id(&mut self) -> Option<EventId> {
+        return self.event_manager.pop_delivered_event_id();
+    }
+
+    /// Get the id of the next event which was dropped because its
+    /// time-to-live ran out before it could be sent
+    pub fn get_expired_event_id(&mut self) -> Option<EventId> {
+        return self.event_manager.pop_expired_event_id();
+    }
+
+    /// Queue up an RPC message to be sent to the remote host
+    pub fn queue_rpc(&mut self, rpc: RpcMessage<&dyn Event<T>>) {
+        return self.event_manager.queue_outgoing_rpc(rpc);
+    }
+
+    /// Returns whether there are RPC messages to be sent to the remote host
+    pub fn has_outgoing_rpcs(&self) -> bool {
+        return self.event_manager.has_outgoing_rpcs();
+    }
+
+    /// Pop the next outgoing RPC message from the queue
+    pub fn pop_outgoing_rpc(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<RpcMessage<Rc<Box<dyn Event<T>>>>> {
+        return self.event_manager.pop_outgoing_rpc(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing RPC message could not be written
+    /// into a message and sent, place it back into the front of the queue
+    pub fn unpop_outgoing_rpc(
+        &mut self,
+        next_packet_index: u16,
+        rpc: &RpcMessage<Rc<Box<dyn Event<T>>>>,
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_rpc(next_packet_index, rpc);
+    }
+
+    /// Given an incoming packet which has been identified as containing RPC
+    /// messages, send the data to the EventManager for processing. Returns
+    /// false if the rest of the packet can't be read
+    pub fn process_rpc_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+    ) -> bool {
+        return self
+            .event_manager
+            .process_rpc_data(reader, manifest, &mut self.drop_log);
+    }
+
+    /// Get the most recent RPC message that has been received from a remote
+    /// host
+    pub fn get_incoming_rpc(&mut self) -> Option<RpcMessage<T>> {
+        return self.event_manager.pop_incoming_rpc();
+    }
+
+    /// Queue up an event to be sent to the remote host, which will be handed
+    /// to the remote application at the given tick
+    pub fn queue_timed_event(&mut self, tick: u16, event: &impl Event<T>) {
+        return self.event_manager.queue_outgoing_timed_event(tick, event);
+    }
+
+    /// Returns whether there are timed events to be sent to the remote host
+    pub fn has_outgoing_timed_events(&self) -> bool {
+        return self.event_manager.has_outgoing_timed_events();
+    }
+
+    /// Pop the next outgoing timed event from the queue
+    pub fn pop_outgoing_timed_event(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(EventId, u16, Rc<Box<dyn Event<T>>>)> {
+        return self
+            .event_manager
+            .pop_outgoing_timed_event(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing timed event could not be written
+    /// into a message and sent, place it back into the front of the queue
+    pub fn unpop_outgoing_timed_event(
+        &mut self,
+        next_packet_index: u16,
+        timed_event: &(EventId, u16, Rc<Box<dyn Event<T>>>),
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_timed_event(next_packet_index, timed_event);
+    }
+
+    /// Given an incoming packet which has been identified as containing timed
+    /// events, send the data to the EventManager for processing. Returns
+    /// false if the rest of the packet can't be read
+    pub fn process_timed_event_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+    ) -> bool {
+        return self
+            .event_manager
+            .process_timed_event_data(reader, manifest, &mut self.drop_log);
+    }
+
+    /// Queue up an event to be sent to the remote host, targeted at the
+    /// Entity with the given key on the remote host
+    pub fn queue_entity_event(&mut self, local_key: LocalEntityKey, event: &dyn Event<T>) {
+        return self
+            .event_manager
+            .queue_outgoing_entity_event(local_key, event);
+    }
+
+    /// Returns whether there are Entity events to be sent to the remote host
+    pub fn has_outgoing_entity_events(&self) -> bool {
+        return self.event_manager.has_outgoing_entity_events();
+    }
+
+    /// Pop the next outgoing Entity event from the queue
+    pub fn pop_outgoing_entity_event(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>)> {
+        return self
+            .event_manager
+            .pop_outgoing_entity_event(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing Entity event could not be written
+    /// into a message and sent, place it back into the front of the queue
+    pub fn unpop_outgoing_entity_event(
+        &mut self,
+        next_packet_index: u16,
+        entity_event: &(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>),
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_entity_event(next_packet_index, entity_event);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// Entity events, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_entity_event_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+    ) -> bool {
+        return self
+            .event_manager
+            .process_entity_event_data(reader, manifest, &mut self.drop_log);
+    }
+
+    /// Get the most recent Entity event that has been received from a remote
+    /// host, along with the key of the Entity it targets
+    pub fn get_incoming_entity_event(&mut self) -> Option<(LocalEntityKey, T)> {
+        return self.event_manager.pop_incoming_entity_event();
+    }
+
+    /// Queue up an event to be sent to the remote host, to be relayed by the
+    /// Server between Clients. The id is that of the Client the event is
+    /// addressed to, or was sent by
+    pub fn queue_relay_event(&mut self, client_id: ClientId, event: &dyn Event<T>) {
+        return self
+            .event_manager
+            .queue_outgoing_relay_event(client_id, event);
+    }
+
+    /// Returns whether there are relayed events to be sent to the remote host
+    pub fn has_outgoing_relay_events(&self) -> bool {
+        return self.event_manager.has_outgoing_relay_events();
+    }
+
+    /// Pop the next outgoing relayed event from the queue
+    pub fn pop_outgoing_relay_event(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(EventId, ClientId, Rc<Box<dyn Event<T>>>)> {
+        return self
+            .event_manager
+            .pop_outgoing_relay_event(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing relayed event could not be
+    /// written into a message and sent, place it back into the front of the
+    /// queue
+    pub fn unpop_outgoing_relay_event(
+        &mut self,
+        next_packet_index: u16,
+        relay_event: &(EventId, ClientId, Rc<Box<dyn Event<T>>>),
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_relay_event(next_packet_index, relay_event);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// relayed events, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_relay_event_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+    ) -> bool {
+        return self
+            .event_manager
+            .process_relay_event_data(reader, manifest, &mut self.drop_log);
+    }
+
+    /// Get the most recent relayed event that has been received from a
+    /// remote host, along with the id of the Client it is addressed to, or
+    /// was sent by
+    pub fn get_incoming_relay_event(&mut self) -> Option<(ClientId, T)> {
+        return self.event_manager.pop_incoming_relay_event();
+    }
+
+    /// Queue up a change to the set of connected Clients, made at the given
+    /// revision of the roster, to be sent to the remote host
+    pub fn queue_roster_change(&mut self, revision: u32, change: ClientRosterChange) {
+        return self
+            .event_manager
+            .queue_outgoing_roster_change(revision, change);
+    }
+
+    /// Returns whether there are roster changes to be sent to the remote host
+    pub fn has_outgoing_roster_changes(&self) -> bool {
+        return self.event_manager.has_outgoing_roster_changes();
+    }
+
+    /// Pop the next outgoing roster change from the queue
+    pub fn pop_outgoing_roster_change(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(u32, ClientRosterChange)> {
+        return self
+            .event_manager
+            .pop_outgoing_roster_change(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing roster change could not be
+    /// written into a message and sent, place it back into the front of the
+    /// queue
+    pub fn unpop_outgoing_roster_change(
+        &mut self,
+        next_packet_index: u16,
+        roster_change: (u32, ClientRosterChange),
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_roster_change(next_packet_index, roster_change);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// roster changes, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_roster_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self
+            .event_manager
+            .process_roster_data(reader, &mut self.drop_log);
+    }
+
+    /// Get the most recent roster change that has been received from the
+    /// remote host, along with the revision it was made at
+    pub fn get_incoming_roster_change(&mut self) -> Option<(u32, ClientRosterChange)> {
+        return self.event_manager.pop_incoming_roster_change();
+    }
+
+    /// Queue up a description of the types registered in the Manifest at the
+    /// given revision of it, to be sent to the remote host
+    pub fn queue_manifest_delta(&mut self, revision: u32, delta: ManifestDescriptor) {
+        return self
+            .event_manager
+            .queue_outgoing_manifest_delta(revision, delta);
+    }
+
+    /// Returns whether there are Manifest deltas to be sent to the remote host
+    pub fn has_outgoing_manifest_deltas(&self) -> bool {
+        return self.event_manager.has_outgoing_manifest_deltas();
+    }
+
+    /// Pop the next outgoing Manifest delta from the queue
+    pub fn pop_outgoing_manifest_delta(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<(u32, ManifestDescriptor)> {
+        return self
+            .event_manager
+            .pop_outgoing_manifest_delta(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing Manifest delta could not be
+    /// written into a message and sent, place it back into the front of the
+    /// queue
+    pub fn unpop_outgoing_manifest_delta(
+        &mut self,
+        next_packet_index: u16,
+        manifest_delta: (u32, ManifestDescriptor),
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_manifest_delta(next_packet_index, manifest_delta);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// Manifest deltas, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_manifest_delta_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self
+            .event_manager
+            .process_manifest_delta_data(reader, &mut self.drop_log);
+    }
+
+    /// Get the most recent Manifest delta that has been received from the
+    /// remote host, along with the revision of the Manifest it describes
+    pub fn get_incoming_manifest_delta(&mut self) -> Option<(u32, ManifestDescriptor)> {
+        return self.event_manager.pop_incoming_manifest_delta();
+    }
+
+    /// Queue up a notice that the Server is shutting down, with the reason,
+    /// to be sent to the remote host
+    pub fn queue_shutdown(&mut self, reason: String) {
+        return self.event_manager.queue_outgoing_shutdown(reason);
+    }
+
+    /// Returns whether there are shutdown notices to be sent to the remote
+    /// host
+    pub fn has_outgoing_shutdowns(&self) -> bool {
+        return self.event_manager.has_outgoing_shutdowns();
+    }
+
+    /// Pop the next outgoing shutdown notice from the queue
+    pub fn pop_outgoing_shutdown(&mut self, next_packet_index: u16) -> Option<String> {
+        return self.event_manager.pop_outgoing_shutdown(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing shutdown notice could not be
+    /// written into a message and sent, place it back into the front of the
+    /// queue
+    pub fn unpop_outgoing_shutdown(&mut self, next_packet_index: u16, reason: String) {
+        return self
+            .event_manager
+            .unpop_outgoing_shutdown(next_packet_index, reason);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// shutdown notices, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_shutdown_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self
+            .event_manager
+            .process_shutdown_data(reader, &mut self.drop_log);
+    }
+
+    /// Get the most recent shutdown notice that has been received from the
+    /// remote host, with its reason
+    pub fn get_incoming_shutdown(&mut self) -> Option<String> {
+        return self.event_manager.pop_incoming_shutdown();
+    }
+
+    /// Queue up a change to the rate at which the Server ticks, to be sent to
+    /// the remote host
+    pub fn queue_tick_rate_change(&mut self, change: TickRateChange) {
+        return self.event_manager.queue_outgoing_tick_rate_change(change);
+    }
+
+    /// Returns whether there are tick rate changes to be sent to the remote
+    /// host
+    pub fn has_outgoing_tick_rate_changes(&self) -> bool {
+        return self.event_manager.has_outgoing_tick_rate_changes();
+    }
+
+    /// Pop the next outgoing tick rate change from the queue
+    pub fn pop_outgoing_tick_rate_change(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Option<TickRateChange> {
+        return self
+            .event_manager
+            .pop_outgoing_tick_rate_change(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing tick rate change could not be
+    /// written into a message and sent, place it back into the front of the
+    /// queue
+    pub fn unpop_outgoing_tick_rate_change(
+        &mut self,
+        next_packet_index: u16,
+        change: TickRateChange,
+    ) {
+        return self
+            .event_manager
+            .unpop_outgoing_tick_rate_change(next_packet_index, change);
+    }
+
+    /// Given an incoming packet which has been identified as containing tick
+    /// rate changes, send the data to the EventManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_tick_rate_change_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self
+            .event_manager
+            .process_tick_rate_change_data(reader, &mut self.drop_log);
+    }
+
+    /// Get the next tick rate change that has been received from the remote
+    /// host
+    pub fn get_incoming_tick_rate_change(&mut self) -> Option<TickRateChange> {
+        return self.event_manager.pop_incoming_tick_rate_change();
+    }
+
+    /// Gets the number of messages managed by the Connection which are
+    /// queued, or have been sent without yet being acknowledged
+    pub fn get_undelivered_count(&self) -> usize {
+        return self.event_manager.get_undelivered_count();
+    }
+
+    /// Queue up the command of the given tick to be sent to the remote host, in
+    /// every packet until one carrying it is acknowledged
+    pub fn queue_command(&mut self, tick: u16, command: &impl Event<T>) {
+        return self.event_manager.queue_outgoing_command(tick, command);
+    }
+
+    /// Returns whether a command has been queued since the last packet was
+    /// written
+    pub fn has_new_outgoing_command(&self) -> bool {
+        return self.event_manager.has_new_outgoing_command();
+    }
+
+    /// Get every unacknowledged command to be written into the given packet,
+    /// newest first
+    pub fn get_outgoing_commands(
+        &mut self,
+        next_packet_index: u16,
+    ) -> Vec<(u16, Rc<Box<dyn Event<T>>>)> {
+        return self.event_manager.get_outgoing_commands(next_packet_index);
+    }
+
+    /// Given an incoming packet which has been identified as containing
+    /// commands, send the data to the EventManager for processing. Returns
+    /// false if the rest of the packet can't be read
+    pub fn process_command_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+    ) -> bool {
+        return self
+            .event_manager
+            .process_command_data(reader, manifest, &mut self.drop_log);
+    }
+
+    /// Get the next command received from the remote host, along with its
+    /// tick
+    pub fn get_incoming_command(&mut self) -> Option<(u16, T)> {
+        return self.event_manager.pop_incoming_command();
+    }
+
+    /// Get statistics about the commands received from the remote host
+    pub fn get_command_stats(&self) -> CommandStats {
+        return self.event_manager.get_command_stats();
+    }
+
+    /// Get statistics about the outgoing messages of the given kind which
+    /// have yet to be delivered to the remote host
+    pub fn get_event_queue_stats(&self, manager_type: ManagerType) -> EventQueueStats {
+        return self.event_manager.get_queue_stats(manager_type);
+    }
+
+    /// Get statistics about the outgoing Events of the type with the given
+    /// TypeId which have yet to be delivered to the remote host
+    pub fn get_event_type_queue_stats(&self, type_id: TypeId) -> EventQueueStats {
+        return self.event_manager.get_event_type_queue_stats(type_id);
+    }
+
+    /// Get the most recent timed event that has been received from a remote
+    /// host, as a tuple of (scheduled tick, ticks remaining when sent, event)
+    pub fn get_incoming_timed_event(&mut self) -> Option<(u16, i16, T)> {
+        return self.event_manager.pop_incoming_timed_event();
+    }
+
+    /// Queue up a blob to be sent to the remote host in chunks. Returns None if
+    /// the blob is too large to be sent
+    pub fn queue_blob(&mut self, data: &[u8]) -> Option<BlobId> {
+        return self.blob_manager.queue_outgoing_blob(data);
+    }
+
+    /// Get the progress of an outgoing blob, as (chunks delivered, total
+    /// chunks)
+    pub fn get_outgoing_blob_progress(&self, blob_id: BlobId) -> Option<(u16, u16)> {
+        return self.blob_manager.get_outgoing_progress(blob_id);
+    }
+
+    /// Returns whether there are blob chunks which can be sent to the remote
+    /// host right now
+    pub fn has_outgoing_blob_chunks(&mut self) -> bool {
+        return self.blob_manager.has_outgoing_chunks();
+    }
+
+    /// Pop the next outgoing blob chunk from the queue, if bandwidth allows
+    pub fn pop_outgoing_blob_chunk(&mut self, next_packet_index: u16) -> Option<BlobChunk> {
+        return self.blob_manager.pop_outgoing_chunk(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing blob chunk could not be written
+    /// into a message and sent, place it back into the front of the queue
+    pub fn unpop_outgoing_blob_chunk(&mut self, next_packet_index: u16, chunk: &BlobChunk) {
+        return self
+            .blob_manager
+            .unpop_outgoing_chunk(next_packet_index, chunk);
+    }
+
+    /// Given an incoming packet which has been identified as containing blob
+    /// chunks, send the data to the BlobManager for processing. Returns false
+    /// if the rest of the packet can't be read
+    pub fn process_blob_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self.blob_manager.process_data(reader, &mut self.drop_log);
+    }
+
+    /// Get the next notification regarding the progress of a blob transfer
+    pub fn get_blob_message(&mut self) -> Option<BlobMessage> {
+        return self.blob_manager.pop_message();
+    }
+
+    /// Queue up a raw payload to be attached to an outgoing packet. Returns
+    /// false if the payload is too large, for `MAX_RAW_PAYLOAD_SIZE` or the
+    /// raw payload budget of a packet
+    pub fn queue_raw_payload(&mut self, payload: &[u8]) -> bool {
+        return self.raw_payload_manager.queue_outgoing_payload(payload);
+    }
+
+    /// Returns whether there are raw payloads to be sent to the remote host
+    pub fn has_outgoing_raw_payloads(&self) -> bool {
+        return self.raw_payload_manager.has_outgoing_payloads();
+    }
+
+    /// Pop the next outgoing raw payload from the queue
+    pub fn pop_outgoing_raw_payload(&mut self) -> Option<Box<[u8]>> {
+        return self.raw_payload_manager.pop_outgoing_payload();
+    }
+
+    /// If for some reason the next outgoing raw payload could not be written
+    /// into the packet, place it back into the front of the queue
+    pub fn unpop_outgoing_raw_payload(&mut self, payload: Box<[u8]>) {
+        return self.raw_payload_manager.unpop_outgoing_payload(payload);
+    }
+
+    /// Given an incoming packet which has been identified as containing raw
+    /// payloads, send the data to the RawPayloadManager for processing.
+    /// Returns false if the rest of the packet can't be read
+    pub fn process_raw_data(&mut self, reader: &mut PacketReader) -> bool {
+        return self
+            .raw_payload_manager
+            .process_data(reader, &mut self.drop_log);
+    }
+
+    /// Records that a packet received from the remote host, or part of one,
+    /// was dropped for the given reason
+    pub fn record_drop(&mut self, reason: DropReason) {
+        self.drop_log.record(reason);
+    }
+
+    /// Gets the log of the packets received from the remote host which were
+    /// dropped, for the parts of a packet read outside of the Connection
+    pub fn get_drop_log_mut(&mut self) -> &mut DropLog {
+        return &mut self.drop_log;
+    }
+
+    /// Get statistics about the packets received from the remote host which
+    /// were dropped for being malformed
+    pub fn get_connection_stats(&self) -> ConnectionStats {
+        return self.drop_log.get_stats();
+    }
+
+    /// Get the reason the first packet received from the remote host, or part
+    /// of one, was dropped, if any has been
+    pub fn get_first_drop(&self) -> Option<DropReason> {
+        return self.drop_log.get_first_reason();
+    }
+
+    /// Get the next raw payload that has been received from the remote host
+    pub fn get_incoming_raw_payload(&mut self) -> Option<Box<[u8]>> {
+        return self.raw_payload_manager.pop_incoming_payload();
+    }
+
     /// Get the address of the remote host
     pub fn get_address(&self) -> SocketAddr {
         return self.address;
@@ -151,4 +1052,10 @@ impl<T: EventType> Connection<T> {
     pub fn get_rtt(&self) -> f32 {
         return self.rtt_tracker.get_rtt();
     }
+
+    /// Get statistics of the most recent Round Trip Time samples to the
+    /// remote host
+    pub fn get_rtt_stats(&self) -> Option<RttStats> {
+        return self.rtt_tracker.get_rtt_stats();
+    }
 }
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use crate::{instant::Instant, rtt::rtt_history::RttStats};
+
+/// How long a connection must stay in a new quality class before the change
+/// is reported, so that a single spike does not flicker a warning on & off
+pub const CONNECTION_QUALITY_HOLD: Duration = Duration::from_secs(1);
+
+// How often a connection is classified
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A coarse classification of how well a connection is performing, from its
+/// Round Trip Time, jitter & packet loss
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionQuality {
+    /// Every measure is within the `excellent` thresholds
+    Excellent,
+    /// Every measure is within the `good` thresholds
+    Good,
+    /// Every measure is within the `poor` thresholds
+    Poor,
+    /// At least one measure is beyond the `poor` thresholds
+    Bad,
+}
+
+/// The greatest Round Trip Time, jitter & packet loss a connection may have
+/// and still be classified at a given quality
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityThreshold {
+    /// The greatest median Round Trip Time
+    pub rtt: Duration,
+    /// The greatest jitter, measured as the spread between the median & 95th
+    /// percentile Round Trip Time
+    pub jitter: Duration,
+    /// The greatest fraction of recent packets which were lost, from 0 to 1
+    pub packet_loss: f32,
+}
+
+/// The thresholds used to classify the ConnectionQuality of a connection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConnectionQualityThresholds {
+    /// The thresholds of an Excellent connection
+    pub excellent: QualityThreshold,
+    /// The thresholds of a Good connection
+    pub good: QualityThreshold,
+    /// The thresholds of a Poor connection. Anything beyond them is Bad
+    pub poor: QualityThreshold,
+}
+
+impl Default for ConnectionQualityThresholds {
+    fn default() -> Self {
+        ConnectionQualityThresholds {
+            excellent: QualityThreshold {
+                rtt: Duration::from_millis(60),
+                jitter: Duration::from_millis(10),
+                packet_loss: 0.01,
+            },
+            good: QualityThreshold {
+                rtt: Duration::from_millis(120),
+                jitter: Duration::from_millis(25),
+                packet_loss: 0.03,
+            },
+            poor: QualityThreshold {
+                rtt: Duration::from_millis(250),
+                jitter: Duration::from_millis(60),
+                packet_loss: 0.1,
+            },
+        }
+    }
+}
+
+impl ConnectionQualityThresholds {
+    /// Classifies a connection, given its Round Trip Time statistics & the
+    /// fraction of recent packets which were lost
+    pub fn classify(&self, rtt_stats: &RttStats, packet_loss: f32) -> ConnectionQuality {
+        let rtt_ms = rtt_stats.median;
+        let jitter_ms = (rtt_stats.p95 - rtt_stats.median).max(0.0);
+        let within = |threshold: &QualityThreshold| {
+            return rtt_ms <= threshold.rtt.as_secs_f32() * 1000.0
+                && jitter_ms <= threshold.jitter.as_secs_f32() * 1000.0
+                && packet_loss <= threshold.packet_loss;
+        };
+        if within(&self.excellent) {
+            return ConnectionQuality::Excellent;
+        }
+        if within(&self.good) {
+            return ConnectionQuality::Good;
+        }
+        if within(&self.poor) {
+            return ConnectionQuality::Poor;
+        }
+        return ConnectionQuality::Bad;
+    }
+}
+
+/// Continuously classifies the quality of a connection, reporting a change
+/// once the new quality has held for `CONNECTION_QUALITY_HOLD`
+#[derive(Debug)]
+pub struct ConnectionQualityMonitor {
+    thresholds: ConnectionQualityThresholds,
+    quality: Option<ConnectionQuality>,
+    pending: Option<(ConnectionQuality, Duration)>,
+    last_update: Option<Instant>,
+}
+
+impl ConnectionQualityMonitor {
+    /// Creates a new ConnectionQualityMonitor, given the thresholds of each
+    /// quality class
+    pub fn new(thresholds: ConnectionQualityThresholds) -> Self {
+        ConnectionQualityMonitor {
+            thresholds,
+            quality: None,
+            pending: None,
+            last_update: None,
+        }
+    }
+
+    /// Returns whether enough time has passed since the last classification to
+    /// classify the connection again
+    pub fn should_update(&self) -> bool {
+        return match &self.last_update {
+            Some(last_update) => {
+                let elapsed = last_update.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos()) >= UPDATE_INTERVAL
+            }
+            None => true,
+        };
+    }
+
+    /// Classifies the connection from its current measures, and returns the
+    /// new quality if it has changed. The first classification, once Round
+    /// Trip Time has been measured, is reported immediately
+    pub fn update(
+        &mut self,
+        rtt_stats: Option<&RttStats>,
+        packet_loss: Option<f32>,
+    ) -> Option<ConnectionQuality> {
+        let elapsed = match &self.last_update {
+            Some(last_update) => {
+                let elapsed = last_update.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos())
+            }
+            None => Duration::from_secs(0),
+        };
+        self.last_update = Some(Instant::now());
+        return self.advance(elapsed, rtt_stats, packet_loss);
+    }
+
+    /// Gets the current quality of the connection, or None if it has not been
+    /// classified yet
+    pub fn get_quality(&self) -> Option<ConnectionQuality> {
+        return self.quality;
+    }
+
+    fn advance(
+        &mut self,
+        elapsed: Duration,
+        rtt_stats: Option<&RttStats>,
+        packet_loss: Option<f32>,
+    ) -> Option<ConnectionQuality> {
+        let rtt_stats = rtt_stats?;
+        let classified = self
+            .thresholds
+            .classify(rtt_stats, packet_loss.unwrap_or(0.0));
+
+        let current = match self.quality {
+            Some(current) => current,
+            None => {
+                self.quality = Some(classified);
+                return Some(classified);
+            }
+        };
+        if classified == current {
+            self.pending = None;
+            return None;
+        }
+
+        let held_for = match self.pending {
+            Some((pending, held_for)) if pending == classified => held_for + elapsed,
+            _ => Duration::from_secs(0),
+        };
+        if held_for >= CONNECTION_QUALITY_HOLD {
+            self.quality = Some(classified);
+            self.pending = None;
+            return Some(classified);
+        }
+        self.pending = Some((classified, held_for));
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        ConnectionQuality, ConnectionQualityMonitor, ConnectionQualityThresholds, RttStats,
+        CONNECTION_QUALITY_HOLD,
+    };
+
+    fn stats(median: f32, p95: f32) -> RttStats {
+        return RttStats {
+            sample_count: 64,
+            min: median,
+            median,
+            p95,
+            max: p95,
+        };
+    }
+
+    #[test]
+    fn classification() {
+        let thresholds = ConnectionQualityThresholds::default();
+        assert!(thresholds.classify(&stats(40.0, 45.0), 0.0) == ConnectionQuality::Excellent);
+        assert!(thresholds.classify(&stats(40.0, 60.0), 0.0) == ConnectionQuality::Good);
+        assert!(thresholds.classify(&stats(40.0, 45.0), 0.05) == ConnectionQuality::Poor);
+        assert!(thresholds.classify(&stats(300.0, 305.0), 0.0) == ConnectionQuality::Bad);
+    }
+
+    #[test]
+    fn change_holds() {
+        let frame = Duration::from_millis(100);
+        let mut monitor = ConnectionQualityMonitor::new(ConnectionQualityThresholds::default());
+        assert!(monitor.advance(frame, None, None).is_none());
+        assert!(
+            monitor.advance(frame, Some(&stats(40.0, 45.0)), Some(0.0))
+                == Some(ConnectionQuality::Excellent)
+        );
+
+        // a single spike is not reported
+        assert!(monitor
+            .advance(frame, Some(&stats(300.0, 305.0)), Some(0.0))
+            .is_none());
+        assert!(monitor
+            .advance(frame, Some(&stats(40.0, 45.0)), Some(0.0))
+            .is_none());
+
+        // a lasting change is
+        let mut elapsed = Duration::from_secs(0);
+        while elapsed < CONNECTION_QUALITY_HOLD {
+            assert!(monitor
+                .advance(frame, Some(&stats(300.0, 305.0)), Some(0.0))
+                .is_none());
+            elapsed += frame;
+        }
+        assert!(
+            monitor.advance(frame, Some(&stats(300.0, 305.0)), Some(0.0))
+                == Some(ConnectionQuality::Bad)
+        );
+        assert!(monitor.get_quality() == Some(ConnectionQuality::Bad));
+    }
+}
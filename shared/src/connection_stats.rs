@@ -0,0 +1,181 @@
+use std::{net::SocketAddr, time::Duration};
+
+use crate::Timer;
+
+/// The shortest interval between two warnings logged for dropped packets on a
+/// connection. Drops in between are counted, and reported with the next
+/// warning
+pub const DROP_WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The reason a packet received from a remote host, or part of one, was
+/// dropped
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DropReason {
+    /// A section of the packet had an unknown ManagerType, or one the
+    /// receiver doesn't expect, so neither it nor the rest of the packet could
+    /// be read
+    UnknownManagerType,
+    /// A length written in the packet extended past the end of the packet,
+    /// or the packet ended partway through a section, so the rest of the
+    /// packet could not be read
+    OversizedLength,
+    /// An Event or Entity had a NaiaId which isn't registered in the Manifest,
+    /// so it was skipped
+    UnknownNaiaId,
+    /// The packet acknowledged a packet which hasn't been sent yet, so the
+    /// packet was not read
+    ImpossibleSequence,
+    /// An Event or Entity had a payload which could not be read, or, for an
+    /// Event of a compressed type, decompressed, so it was skipped
+    MalformedPayload,
+    /// The packet had a sequence number which was already received, or was
+    /// too old to tell, while `Config::replay_protection` is enabled, so the
+    /// packet was not read
+    ReplayedPacket,
+}
+
+/// Statistics about the packets received from a remote host which were
+/// dropped, in whole or in part, for being malformed or replayed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConnectionStats {
+    /// The number of packets dropped for an unknown ManagerType
+    pub unknown_manager_type: u64,
+    /// The number of packets dropped for a length extending past their end,
+    /// or for ending partway through a section
+    pub oversized_length: u64,
+    /// The number of Events & Entities dropped for an unknown NaiaId
+    pub unknown_naia_id: u64,
+    /// The number of packets dropped for acknowledging a packet which hasn't
+    /// been sent yet
+    pub impossible_sequence: u64,
+    /// The number of Events & Entities dropped for a payload which could not
+    /// be read or decompressed
+    pub malformed_payload: u64,
+    /// The number of packets rejected as replays
+    pub replayed_packets: u64,
+}
+
+impl ConnectionStats {
+    /// Gets the number of drops for the given reason
+    pub fn get_count(&self, reason: DropReason) -> u64 {
+        match reason {
+            DropReason::UnknownManagerType => return self.unknown_manager_type,
+            DropReason::OversizedLength => return self.oversized_length,
+            DropReason::UnknownNaiaId => return self.unknown_naia_id,
+            DropReason::ImpossibleSequence => return self.impossible_sequence,
+            DropReason::MalformedPayload => return self.malformed_payload,
+            DropReason::ReplayedPacket => return self.replayed_packets,
+        }
+    }
+
+    /// Gets the number of drops for every reason
+    pub fn get_total(&self) -> u64 {
+        return self.unknown_manager_type
+            + self.oversized_length
+            + self.unknown_naia_id
+            + self.impossible_sequence
+            + self.malformed_payload
+            + self.replayed_packets;
+    }
+}
+
+/// Counts the packets dropped on a connection, and logs a warning for them,
+/// at most once every `DROP_WARNING_INTERVAL`
+#[derive(Debug)]
+pub struct DropLog {
+    address: SocketAddr,
+    stats: ConnectionStats,
+    first_reason: Option<DropReason>,
+    log_drops: bool,
+    warning_timer: Timer,
+    unlogged_drops: u64,
+}
+
+impl DropLog {
+    /// Creates a new DropLog, given the address of the remote host, and
+    /// whether to log warnings
+    pub fn new(address: SocketAddr, log_drops: bool) -> Self {
+        let mut warning_timer = Timer::new(DROP_WARNING_INTERVAL);
+        warning_timer.ring_manual();
+        DropLog {
+            address,
+            stats: ConnectionStats::default(),
+            first_reason: None,
+            log_drops,
+            warning_timer,
+            unlogged_drops: 0,
+        }
+    }
+
+    /// Records a drop for the given reason
+    pub fn record(&mut self, reason: DropReason) {
+        match reason {
+            DropReason::UnknownManagerType => self.stats.unknown_manager_type += 1,
+            DropReason::OversizedLength => self.stats.oversized_length += 1,
+            DropReason::UnknownNaiaId => self.stats.unknown_naia_id += 1,
+            DropReason::ImpossibleSequence => self.stats.impossible_sequence += 1,
+            DropReason::MalformedPayload => self.stats.malformed_payload += 1,
+            DropReason::ReplayedPacket => self.stats.replayed_packets += 1,
+        }
+        // the network may duplicate a packet, so a replay isn't a violation
+        if self.first_reason.is_none() && reason != DropReason::ReplayedPacket {
+            self.first_reason = Some(reason);
+        }
+        if !self.log_drops {
+            return;
+        }
+        if !self.warning_timer.ringing() {
+            self.unlogged_drops += 1;
+            return;
+        }
+        self.warning_timer.reset();
+        if self.unlogged_drops == 0 {
+            warn!("dropped packet from {}: {:?}", self.address, reason);
+        } else {
+            warn!(
+                "dropped packet from {}: {:?}, and {} more since the last warning",
+                self.address, reason, self.unlogged_drops
+            );
+        }
+        self.unlogged_drops = 0;
+    }
+
+    /// Gets the statistics of the drops recorded so far
+    pub fn get_stats(&self) -> ConnectionStats {
+        return self.stats;
+    }
+
+    /// Gets the reason of the first drop recorded, if any
+    pub fn get_first_reason(&self) -> Option<DropReason> {
+        return self.first_reason;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DropLog, DropReason};
+
+    #[test]
+    fn count_drops() {
+        let mut drop_log = DropLog::new("127.0.0.1:14191".parse().unwrap(), true);
+        assert!(drop_log.get_first_reason().is_none());
+        drop_log.record(DropReason::OversizedLength);
+        drop_log.record(DropReason::UnknownNaiaId);
+        drop_log.record(DropReason::UnknownNaiaId);
+
+        let stats = drop_log.get_stats();
+        assert!(stats.get_count(DropReason::UnknownManagerType) == 0);
+        assert!(stats.get_count(DropReason::OversizedLength) == 1);
+        assert!(stats.get_count(DropReason::UnknownNaiaId) == 2);
+        assert!(stats.get_total() == 3);
+        assert!(drop_log.get_first_reason() == Some(DropReason::OversizedLength));
+    }
+
+    #[test]
+    fn replays_are_not_violations() {
+        let mut drop_log = DropLog::new("127.0.0.1:14191".parse().unwrap(), false);
+        drop_log.record(DropReason::ReplayedPacket);
+        assert!(drop_log.get_stats().get_count(DropReason::ReplayedPacket) == 1);
+        assert!(drop_log.get_first_reason().is_none());
+    }
+}
@@ -0,0 +1,202 @@
+use byteorder::ReadBytesExt;
+use std::fmt;
+
+use crate::{packet_reader::PacketReader, packet_writer::PacketWriter};
+
+/// The maximum number of bytes a custom section can write into a single
+/// packet
+pub const MAX_CUSTOM_SECTION_SIZE: usize = 255;
+
+/// An application-defined section of data packets, which is written & read
+/// by callbacks rather than by one of naia's own managers. The remote host is
+/// identified by `K`, which is the Server's address on the Client, and the
+/// User's key on the Server. Bytes written into a section are unreliable, and
+/// are never retransmitted
+pub trait CustomSection<K> {
+    /// Returns whether there are bytes waiting to be sent to the given remote
+    /// host, in which case a data packet is sent even if naia has nothing else
+    /// to send
+    fn has_outgoing(&self, remote: &K) -> bool;
+
+    /// Returns the bytes to attach to the next data packet sent to the given
+    /// remote host, which must be no longer than `max_bytes`, or None if
+    /// there is nothing to attach
+    fn write(&mut self, remote: &K, max_bytes: usize) -> Option<Vec<u8>>;
+
+    /// Reads the bytes of this section from a data packet received from the
+    /// given remote host
+    fn read(&mut self, remote: &K, bytes: &[u8]);
+}
+
+/// The custom sections registered by an application, each identified by a
+/// section id which both hosts agree on
+pub struct CustomSections<K> {
+    sections: Vec<(u8, Box<dyn CustomSection<K>>)>,
+}
+
+impl<K> CustomSections<K> {
+    /// Creates an empty set of custom sections
+    pub fn new() -> Self {
+        CustomSections {
+            sections: Vec::new(),
+        }
+    }
+
+    /// Registers a custom section under the given id. Sections are written
+    /// into outgoing packets in the order they were registered. Returns false
+    /// if a section with the same id has already been registered
+    pub fn register(&mut self, section_id: u8, section: Box<dyn CustomSection<K>>) -> bool {
+        if self.sections.iter().any(|(id, _)| *id == section_id) {
+            return false;
+        }
+        self.sections.push((section_id, section));
+        return true;
+    }
+
+    /// Returns whether any section has bytes waiting to be sent to the given
+    /// remote host
+    pub fn has_outgoing(&self, remote: &K) -> bool {
+        return self
+            .sections
+            .iter()
+            .any(|(_, section)| section.has_outgoing(remote));
+    }
+
+    /// Writes the outgoing bytes of every section into a packet to the given
+    /// remote host, for as long as there is room left in the packet
+    pub fn write_outgoing(&mut self, remote: &K, writer: &mut PacketWriter) {
+        for (section_id, section) in self.sections.iter_mut() {
+            let max_bytes = writer.get_custom_section_capacity();
+            if max_bytes == 0 {
+                break;
+            }
+            if let Some(bytes) = section.write(remote, max_bytes) {
+                if !writer.write_custom_section(*section_id, &bytes) {
+                    warn!(
+                        "custom section {} wrote {} bytes, more than the {} available",
+                        section_id,
+                        bytes.len(),
+                        max_bytes
+                    );
+                }
+            }
+        }
+    }
+
+    /// Given incoming packet data, read the custom sections and pass each to
+    /// the section registered under its id. Sections with an unknown id are
+    /// skipped. Returns false if a section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_data(&mut self, remote: &K, reader: &mut PacketReader) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let section_count = match cursor.read_u8() {
+            Ok(section_count) => section_count,
+            Err(_) => return false,
+        };
+        for _x in 0..section_count {
+            let (section_id, section_length) = match (cursor.read_u8(), cursor.read_u8()) {
+                (Ok(section_id), Ok(section_length)) => (section_id, section_length),
+                _ => return false,
+            };
+            let section_start_position: usize = cursor.position() as usize;
+            let section_end_position: usize = section_start_position + (section_length as usize);
+            if section_end_position > buffer.len() {
+                return false;
+            }
+
+            if let Some((_, section)) = self.sections.iter_mut().find(|(id, _)| *id == section_id) {
+                section.read(
+                    remote,
+                    &buffer[section_start_position..section_end_position],
+                );
+            }
+
+            cursor.set_position(section_end_position as u64);
+        }
+        return true;
+    }
+}
+
+impl<K> fmt::Debug for CustomSections<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let section_ids: Vec<u8> = self.sections.iter().map(|(id, _)| *id).collect();
+        return f
+            .debug_struct("CustomSections")
+            .field("section_ids", &section_ids)
+            .finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        custom_section::{CustomSection, CustomSections},
+        manager_type::ManagerType,
+        packet_reader::PacketReader,
+        packet_writer::PacketWriter,
+    };
+
+    struct Presence {
+        outgoing: Option<Vec<u8>>,
+        received: Rc<RefCell<Vec<(u8, Vec<u8>)>>>,
+    }
+
+    impl CustomSection<u8> for Presence {
+        fn has_outgoing(&self, _: &u8) -> bool {
+            return self.outgoing.is_some();
+        }
+
+        fn write(&mut self, _: &u8, _: usize) -> Option<Vec<u8>> {
+            return self.outgoing.take();
+        }
+
+        fn read(&mut self, remote: &u8, bytes: &[u8]) {
+            self.received.borrow_mut().push((*remote, bytes.to_vec()));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut sender: CustomSections<u8> = CustomSections::new();
+        sender.register(
+            3,
+            Box::new(Presence {
+                outgoing: Some(vec![1, 2, 3]),
+                received: received.clone(),
+            }),
+        );
+        assert!(!sender.register(
+            3,
+            Box::new(Presence {
+                outgoing: None,
+                received: received.clone(),
+            })
+        ));
+        assert!(sender.has_outgoing(&0));
+
+        let mut writer = PacketWriter::new();
+        sender.write_outgoing(&0, &mut writer);
+        assert!(!sender.has_outgoing(&0));
+        let bytes = writer.get_bytes();
+
+        let mut receiver: CustomSections<u8> = CustomSections::new();
+        receiver.register(
+            3,
+            Box::new(Presence {
+                outgoing: None,
+                received: received.clone(),
+            }),
+        );
+        let mut reader = PacketReader::new(&bytes);
+        let manager_type: ManagerType = reader.read_u8().unwrap().into();
+        assert!(manager_type == ManagerType::Custom);
+        receiver.process_data(&7, &mut reader);
+        assert!(!reader.has_more());
+        assert!(*received.borrow() == vec![(7, vec![1, 2, 3])]);
+    }
+}
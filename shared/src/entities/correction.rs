@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+/// The shape over which a correction offset decays to zero
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CorrectionCurve {
+    /// The offset shrinks at a constant rate
+    Linear,
+    /// The offset shrinks quickly at first, then slows as it approaches zero,
+    /// which hides most of the error early without a visible stop
+    EaseOut,
+}
+
+/// The visual error of a predicted Entity after a correction, which decays
+/// over a duration. When reconciliation moves an Entity, the error is the
+/// position it was rendered at minus the corrected position, and rendering
+/// the Entity at its corrected position plus the offset makes it glide to the
+/// correction rather than snap to it
+#[derive(Clone, Debug)]
+pub struct Correction {
+    initial_offset: Vec<f32>,
+    offset: Vec<f32>,
+    elapsed: Duration,
+    duration: Duration,
+    curve: CorrectionCurve,
+}
+
+impl Correction {
+    /// Creates a new Correction, given the error along each axis, and the
+    /// duration & curve over which it decays
+    pub fn new(error: &[f32], duration: Duration, curve: CorrectionCurve) -> Self {
+        let mut correction = Correction {
+            initial_offset: error.to_vec(),
+            offset: error.to_vec(),
+            elapsed: Duration::from_secs(0),
+            duration,
+            curve,
+        };
+        correction.update_offset();
+        return correction;
+    }
+
+    /// Adds the error of another correction made while this one is still
+    /// decaying. The remaining offset is kept, so the Entity doesn't jump, and
+    /// the combined offset decays over a full duration again
+    pub fn add(&mut self, error: &[f32]) {
+        let length = self.offset.len().max(error.len());
+        self.initial_offset = (0..length)
+            .map(|axis| {
+                self.offset.get(axis).copied().unwrap_or(0.0)
+                    + error.get(axis).copied().unwrap_or(0.0)
+            })
+            .collect();
+        self.elapsed = Duration::from_secs(0);
+        self.update_offset();
+    }
+
+    /// Advances the decay by the given duration, and returns whether the
+    /// offset has reached zero
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        self.elapsed += elapsed;
+        self.update_offset();
+        return self.elapsed >= self.duration;
+    }
+
+    /// Gets the current offset along each axis, which should be added to the
+    /// Entity's position when rendering it
+    pub fn get_offset(&self) -> &[f32] {
+        return &self.offset;
+    }
+
+    fn update_offset(&mut self) {
+        let remaining = get_remaining_fraction(self.curve, self.elapsed, self.duration);
+        self.offset = self
+            .initial_offset
+            .iter()
+            .map(|initial| initial * remaining)
+            .collect();
+    }
+}
+
+// The fraction of the initial offset which remains after the given time
+fn get_remaining_fraction(curve: CorrectionCurve, elapsed: Duration, duration: Duration) -> f32 {
+    if elapsed >= duration {
+        return 0.0;
+    }
+    let progress = elapsed.as_secs_f32() / duration.as_secs_f32();
+    match curve {
+        CorrectionCurve::Linear => return 1.0 - progress,
+        CorrectionCurve::EaseOut => return (1.0 - progress) * (1.0 - progress),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Correction, CorrectionCurve};
+
+    fn approx_eq(a: &[f32], b: &[f32]) -> bool {
+        return a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 0.001);
+    }
+
+    #[test]
+    fn linear_decay() {
+        let mut correction = Correction::new(
+            &[4.0, -2.0],
+            Duration::from_millis(100),
+            CorrectionCurve::Linear,
+        );
+        assert!(approx_eq(correction.get_offset(), &[4.0, -2.0]));
+        assert!(!correction.advance(Duration::from_millis(25)));
+        assert!(approx_eq(correction.get_offset(), &[3.0, -1.5]));
+        assert!(correction.advance(Duration::from_millis(75)));
+        assert!(approx_eq(correction.get_offset(), &[0.0, 0.0]));
+    }
+
+    #[test]
+    fn ease_out_decay() {
+        let mut correction =
+            Correction::new(&[4.0], Duration::from_millis(100), CorrectionCurve::EaseOut);
+        correction.advance(Duration::from_millis(50));
+        assert!(approx_eq(correction.get_offset(), &[1.0]));
+    }
+
+    #[test]
+    fn combined_corrections() {
+        let mut correction = Correction::new(
+            &[4.0, 0.0],
+            Duration::from_millis(100),
+            CorrectionCurve::Linear,
+        );
+        correction.advance(Duration::from_millis(50));
+        correction.add(&[1.0, 1.0, 1.0]);
+        assert!(approx_eq(correction.get_offset(), &[3.0, 1.0, 1.0]));
+        assert!(!correction.advance(Duration::from_millis(50)));
+        assert!(approx_eq(correction.get_offset(), &[1.5, 0.5, 0.5]));
+    }
+}
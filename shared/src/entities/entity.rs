@@ -7,11 +7,34 @@ use std::{
 
 use super::{entity_mutator::EntityMutator, entity_type::EntityType, state_mask::StateMask};
 
+/// An application-defined code sent along with the deletion of an Entity,
+/// describing why it was despawned (for example, destroyed vs. picked up)
+pub type DespawnReason = u8;
+
+/// A provisional key given to an Entity which the Client has spawned locally
+/// in prediction of the Server spawning it, used to link the Server's Entity
+/// to the predicted one
+pub type PredictionKey = u16;
+
+/// Identifies a template Entity registered with `Manifest::register_prefab()`
+pub type PrefabId = u16;
+
+/// Set on the type of an Entity message which belongs to a tick batch, in
+/// which case the index of the batch & the message's slot within it follow
+/// the type
+pub const BATCHED_ENTITY_MESSAGE_FLAG: u8 = 0x80;
+
 /// An Entity is a container of Properties that can be scoped, tracked, and
 /// synced, with a remote host
 pub trait Entity<T: EntityType> {
     /// Gets the number of bytes of the Entity's State Mask
     fn get_state_mask_size(&self) -> u8;
+    /// Gets the number of Properties of the Entity
+    fn get_property_count(&self) -> u8;
+    /// Gets a State Mask with a bit set for every unguaranteed Property. When
+    /// an update of an unguaranteed Property is dropped, it is not
+    /// retransmitted, and will simply be superseded by the next update
+    fn get_unguaranteed_state_mask(&self) -> StateMask;
     /// Gets a copy of the Entity, wrapped in an EntityType enum (which is the
     /// common protocol between the server/host)
     fn get_typed_copy(&self) -> T;
@@ -25,8 +48,9 @@ pub trait Entity<T: EntityType> {
     /// mutated Properties of the Entity on the client
     fn write_partial(&self, state_mask: &StateMask, out_bytes: &mut Vec<u8>);
     /// Reads data from an incoming packet, sufficient to sync the in-memory
-    /// Entity with it's state on the Server
-    fn read_partial(&mut self, state_mask: &StateMask, in_bytes: &[u8]);
+    /// Entity with it's state on the Server. Returns false, leaving the Entity
+    /// untouched, if a Property could not be read
+    fn read_partial(&mut self, state_mask: &StateMask, in_bytes: &[u8]) -> bool;
     /// Set the Entity's EntityMutator, which keeps track of which Properties
     /// have been mutated, necessary to sync only the Properties that have
     /// changed with the client
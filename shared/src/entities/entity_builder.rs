@@ -1,3 +1,5 @@
+use crate::manifest_descriptor::PropertyDescriptor;
+
 use super::entity_type::EntityType;
 
 use std::{
@@ -7,10 +9,21 @@ use std::{
 
 /// Handles the creation of new Entity instances
 pub trait EntityBuilder<T: EntityType> {
-    /// Create a new Entity instance
-    fn build(&self, in_bytes: &[u8]) -> T;
+    /// Create a new Entity instance. Returns None if a Property of it could not
+    /// be read
+    fn build(&self, in_bytes: &[u8]) -> Option<T>;
     /// Gets the TypeId of the Entity the builder is able to build
     fn get_type_id(&self) -> TypeId;
+    /// Gets the name of the Entity type, used to describe the Manifest
+    fn get_type_name(&self) -> &'static str;
+    /// Gets the number of Properties the Entity has
+    fn get_property_count(&self) -> u8;
+    /// Gets a description of each Property of the Entity, in the order they are
+    /// written
+    fn get_properties(&self) -> Vec<PropertyDescriptor>;
+    /// Gets the version of the Entity type, which is incremented as Properties
+    /// are appended to it
+    fn get_version(&self) -> u8;
 }
 
 impl<T: EntityType> Debug for Box<dyn EntityBuilder<T>> {
@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result},
+};
+
+use super::{local_entity_key::LocalEntityKey, state_mask::StateMask};
+
+/// Whether the creation or deletion of an Entity is still in flight
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EntityDebugStatus {
+    /// The Entity's creation has been queued or sent, but not yet acknowledged
+    Creating,
+    /// The Entity has been created on the Client
+    Created,
+    /// The Entity's deletion has been queued or sent, but not yet acknowledged
+    Deleting,
+}
+
+/// The replication state of a single Entity, as reported in an
+/// EntityDebugDump
+#[derive(Clone, Debug)]
+pub struct EntityDebugRecord {
+    /// The key of the Entity, which the Server & the Client share
+    pub local_key: LocalEntityKey,
+    /// The NaiaId of the Entity's type
+    pub naia_id: u16,
+    /// The name of the Entity's type
+    pub type_name: Option<&'static str>,
+    /// Whether the creation or deletion of the Entity is still in flight.
+    /// Always `Created` on the Client
+    pub status: EntityDebugStatus,
+    /// The Properties which have been mutated but not yet sent. Only known on
+    /// the Server
+    pub pending_mask: Option<StateMask>,
+    /// The Properties sent in updates which haven't been acknowledged yet.
+    /// Only known on the Server
+    pub last_sent_mask: Option<StateMask>,
+}
+
+/// A structured report of the Entities a Server has in scope for a Client, or
+/// a Client has received from a Server, to debug replication mismatches
+#[derive(Clone, Debug)]
+pub struct EntityDebugDump {
+    /// Every Entity, sorted by LocalEntityKey
+    pub entities: Vec<EntityDebugRecord>,
+    /// On the Server, the number of Entity messages queued, or sent without
+    /// yet being acknowledged. On the Client, the number of Entity messages
+    /// received but not yet emitted as events, such as those of incomplete
+    /// tick batches
+    pub pending_messages: usize,
+}
+
+impl EntityDebugDump {
+    /// Creates a new EntityDebugDump, sorting the given records
+    pub fn new(mut entities: Vec<EntityDebugRecord>, pending_messages: usize) -> Self {
+        entities.sort_by_key(|record| {
+            (
+                record.local_key.get_index(),
+                record.local_key.get_generation(),
+            )
+        });
+        EntityDebugDump {
+            entities,
+            pending_messages,
+        }
+    }
+
+    /// Gets the record of the Entity with the given key
+    pub fn get(&self, local_key: &LocalEntityKey) -> Option<&EntityDebugRecord> {
+        return self
+            .entities
+            .iter()
+            .find(|record| record.local_key == *local_key);
+    }
+}
+
+impl Display for EntityDebugDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(
+            f,
+            "{} entities, {} pending messages",
+            self.entities.len(),
+            self.pending_messages
+        )?;
+        for record in &self.entities {
+            write!(
+                f,
+                "  {} {} (NaiaId {}) {:?}",
+                record.local_key,
+                record.type_name.unwrap_or("?"),
+                record.naia_id,
+                record.status
+            )?;
+            if let Some(pending_mask) = &record.pending_mask {
+                write!(f, " pending {}", pending_mask)?;
+            }
+            if let Some(last_sent_mask) = &record.last_sent_mask {
+                write!(f, " sent {}", last_sent_mask)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+/// A mismatch between the Entities a Server has created for a Client, and the
+/// Entities the Client has
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntityDiff {
+    /// The Client has acknowledged the Entity's creation, but doesn't have it
+    MissingOnClient(LocalEntityKey),
+    /// The Client has an Entity which the Server hasn't created for it
+    UnexpectedOnClient(LocalEntityKey),
+    /// The Client's Entity is of a different type than the Server's, as
+    /// (key, Server NaiaId, Client NaiaId)
+    TypeMismatch(LocalEntityKey, u16, u16),
+}
+
+/// Compares the Entities a Server has in scope for a Client with the Entities
+/// the Client has, given a dump of each. Entities whose creation or deletion
+/// is still in flight are not reported as missing or unexpected
+pub fn diff_entity_dumps(
+    server_dump: &EntityDebugDump,
+    client_dump: &EntityDebugDump,
+) -> Vec<EntityDiff> {
+    let client_records: HashMap<LocalEntityKey, &EntityDebugRecord> = client_dump
+        .entities
+        .iter()
+        .map(|record| (record.local_key, record))
+        .collect();
+
+    let mut diffs = Vec::new();
+    for server_record in &server_dump.entities {
+        match client_records.get(&server_record.local_key) {
+            Some(client_record) => {
+                if client_record.naia_id != server_record.naia_id {
+                    diffs.push(EntityDiff::TypeMismatch(
+                        server_record.local_key,
+                        server_record.naia_id,
+                        client_record.naia_id,
+                    ));
+                }
+            }
+            None => {
+                if server_record.status == EntityDebugStatus::Created {
+                    diffs.push(EntityDiff::MissingOnClient(server_record.local_key));
+                }
+            }
+        }
+    }
+    for client_record in &client_dump.entities {
+        if server_dump.get(&client_record.local_key).is_none() {
+            diffs.push(EntityDiff::UnexpectedOnClient(client_record.local_key));
+        }
+    }
+    return diffs;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        diff_entity_dumps, EntityDebugDump, EntityDebugRecord, EntityDebugStatus, EntityDiff,
+    };
+    use crate::LocalEntityKey;
+
+    fn record(index: u16, naia_id: u16, status: EntityDebugStatus) -> EntityDebugRecord {
+        return EntityDebugRecord {
+            local_key: LocalEntityKey::new(index, 0),
+            naia_id,
+            type_name: None,
+            status,
+            pending_mask: None,
+            last_sent_mask: None,
+        };
+    }
+
+    #[test]
+    fn matching_dumps() {
+        let server_dump = EntityDebugDump::new(
+            vec![
+                record(1, 0, EntityDebugStatus::Created),
+                record(0, 1, EntityDebugStatus::Created),
+                // in flight, so the Client may or may not have them
+                record(2, 0, EntityDebugStatus::Creating),
+                record(3, 0, EntityDebugStatus::Deleting),
+            ],
+            2,
+        );
+        let client_dump = EntityDebugDump::new(
+            vec![
+                record(0, 1, EntityDebugStatus::Created),
+                record(1, 0, EntityDebugStatus::Created),
+                record(3, 0, EntityDebugStatus::Created),
+            ],
+            0,
+        );
+        assert!(server_dump.entities[0].local_key == LocalEntityKey::new(0, 0));
+        assert!(diff_entity_dumps(&server_dump, &client_dump).is_empty());
+    }
+
+    #[test]
+    fn mismatched_dumps() {
+        let server_dump = EntityDebugDump::new(
+            vec![
+                record(0, 0, EntityDebugStatus::Created),
+                record(1, 0, EntityDebugStatus::Created),
+            ],
+            0,
+        );
+        let client_dump = EntityDebugDump::new(
+            vec![
+                record(1, 1, EntityDebugStatus::Created),
+                record(2, 0, EntityDebugStatus::Created),
+            ],
+            0,
+        );
+        assert!(
+            diff_entity_dumps(&server_dump, &client_dump)
+                == vec![
+                    EntityDiff::MissingOnClient(LocalEntityKey::new(0, 0)),
+                    EntityDiff::TypeMismatch(LocalEntityKey::new(1, 0), 0, 1),
+                    EntityDiff::UnexpectedOnClient(LocalEntityKey::new(2, 0)),
+                ]
+        );
+    }
+}
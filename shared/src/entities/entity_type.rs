@@ -1,9 +1,20 @@
-use super::state_mask::StateMask;
+use std::{any::TypeId, cell::RefCell, rc::Rc};
+
+use super::{entity::Entity, state_mask::StateMask};
 
 /// An Enum with a variant for every Entity that can be synced between
 /// Client/Host
 pub trait EntityType {
     /// Read bytes from an incoming packet, updating the Properties which have
-    /// been mutated on the Server
-    fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]);
+    /// been mutated on the Server. Returns false, leaving the Entity untouched,
+    /// if a Property could not be read
+    fn read_partial(&mut self, state_mask: &StateMask, bytes: &[u8]) -> bool;
+    /// Gets the TypeId of the Entity contained in the variant
+    fn get_type_id(&self) -> TypeId;
+    /// Gets a deep copy of the contained Entity, which does not share state
+    /// with the original
+    fn get_copy(&self) -> Self;
+    /// Gets a reference to the contained Entity, as a trait object which can
+    /// be registered with the Server
+    fn get_entity_ref(&self) -> Rc<RefCell<dyn Entity<Self>>>;
 }
@@ -0,0 +1,253 @@
+use std::{
+    fmt::{Debug, Formatter, Result},
+    rc::Rc,
+    time::Duration,
+};
+
+use super::correction::{Correction, CorrectionCurve};
+
+/// The model by which the position of a remote Entity is extrapolated from
+/// its most recent motion sample
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtrapolationModel {
+    /// The position is held where it was sampled, without extrapolation
+    Hold,
+    /// The position moves at the sampled velocity
+    Linear,
+    /// The position moves at the sampled velocity, which changes at the
+    /// sampled acceleration
+    Quadratic,
+}
+
+/// The motion of a remote Entity as of an update from the Server, given as
+/// a position along each axis, along with its derivatives. A derivative
+/// which is shorter than the position is treated as zero on the remaining
+/// axes. A sample flagged as a teleport is snapped to, rather than blended
+/// onto
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MotionSample {
+    /// The position along each axis
+    pub position: Vec<f32>,
+    /// The rate of change of the position along each axis, per second
+    pub velocity: Vec<f32>,
+    /// The rate of change of the velocity along each axis, per second
+    pub acceleration: Vec<f32>,
+    /// Whether the Entity was teleported, see
+    /// `ReplicatedTransform::is_teleport()`
+    pub teleport: bool,
+}
+
+impl MotionSample {
+    /// Creates a new MotionSample, given a position & its velocity
+    pub fn new(position: &[f32], velocity: &[f32]) -> Self {
+        MotionSample {
+            position: position.to_vec(),
+            velocity: velocity.to_vec(),
+            acceleration: Vec::new(),
+            teleport: false,
+        }
+    }
+
+    /// Gets the position after the given time, according to the model
+    pub fn extrapolate(&self, model: ExtrapolationModel, elapsed: Duration) -> Vec<f32> {
+        let seconds = elapsed.as_secs_f32();
+        return self
+            .position
+            .iter()
+            .enumerate()
+            .map(|(axis, position)| {
+                let velocity = self.velocity.get(axis).copied().unwrap_or(0.0);
+                let acceleration = self.acceleration.get(axis).copied().unwrap_or(0.0);
+                match model {
+                    ExtrapolationModel::Hold => return *position,
+                    ExtrapolationModel::Linear => return position + velocity * seconds,
+                    ExtrapolationModel::Quadratic => {
+                        return position
+                            + velocity * seconds
+                            + 0.5 * acceleration * seconds * seconds;
+                    }
+                }
+            })
+            .collect();
+    }
+}
+
+/// A function which takes a motion sample of a remote Entity whenever it is
+/// created or updated, or returns None for an Entity which isn't
+/// extrapolated
+pub struct MotionSampler<U> {
+    sample_func: Rc<Box<dyn Fn(&U) -> Option<MotionSample>>>,
+}
+
+impl<U> MotionSampler<U> {
+    /// Creates a new MotionSampler, given the sampling function
+    pub fn new(sample_func: Rc<Box<dyn Fn(&U) -> Option<MotionSample>>>) -> Self {
+        MotionSampler { sample_func }
+    }
+
+    /// Takes a motion sample of the given Entity
+    pub fn sample(&self, entity: &U) -> Option<MotionSample> {
+        return (self.sample_func.as_ref().as_ref())(entity);
+    }
+}
+
+impl<U> Debug for MotionSampler<U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        return f.write_str("MotionSampler");
+    }
+}
+
+/// Dead reckoning of a remote Entity, which extrapolates its position from
+/// the most recent motion sample between & beyond updates. When a new sample
+/// arrives, the difference between the position which was being rendered and
+/// the newly extrapolated one becomes a Correction, so the Entity blends back
+/// onto its reported path rather than snapping to it
+#[derive(Clone, Debug)]
+pub struct Extrapolation {
+    sample: MotionSample,
+    elapsed: Duration,
+    model: ExtrapolationModel,
+    max_duration: Duration,
+    blend: Option<Correction>,
+}
+
+impl Extrapolation {
+    /// Creates a new Extrapolation, given the first motion sample, the model
+    /// to extrapolate with, and the longest duration past a sample for which
+    /// to extrapolate, after which the position stops moving until the next
+    /// sample
+    pub fn new(sample: MotionSample, model: ExtrapolationModel, max_duration: Duration) -> Self {
+        Extrapolation {
+            sample,
+            elapsed: Duration::from_secs(0),
+            model,
+            max_duration,
+            blend: None,
+        }
+    }
+
+    /// Replaces the motion sample with a newer one, blending from the
+    /// current position onto the new one over the given duration & curve,
+    /// unless the new sample is a teleport
+    pub fn set_sample(&mut self, sample: MotionSample, duration: Duration, curve: CorrectionCurve) {
+        if sample.teleport {
+            self.sample = sample;
+            self.elapsed = Duration::from_secs(0);
+            self.blend = None;
+            return;
+        }
+        let current_position = self.get_position();
+        self.sample = sample;
+        self.elapsed = Duration::from_secs(0);
+        let new_position = self.sample.extrapolate(self.model, self.elapsed);
+        let error: Vec<f32> = current_position
+            .iter()
+            .enumerate()
+            .map(|(axis, current)| current - new_position.get(axis).copied().unwrap_or(0.0))
+            .collect();
+        match &mut self.blend {
+            Some(blend) => blend.add(&error),
+            None => self.blend = Some(Correction::new(&error, duration, curve)),
+        }
+    }
+
+    /// Advances the extrapolation, and any blend in progress, by the given
+    /// duration
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+        if let Some(blend) = &mut self.blend {
+            if blend.advance(elapsed) {
+                self.blend = None;
+            }
+        }
+    }
+
+    /// Gets the extrapolated position along each axis, including the offset
+    /// of any blend in progress
+    pub fn get_position(&self) -> Vec<f32> {
+        let elapsed = self.elapsed.min(self.max_duration);
+        let mut position = self.sample.extrapolate(self.model, elapsed);
+        if let Some(blend) = &self.blend {
+            for (axis, offset) in blend.get_offset().iter().enumerate() {
+                if let Some(value) = position.get_mut(axis) {
+                    *value += offset;
+                }
+            }
+        }
+        return position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{CorrectionCurve, Extrapolation, ExtrapolationModel, MotionSample};
+
+    fn approx_eq(a: &[f32], b: &[f32]) -> bool {
+        return a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 0.001);
+    }
+
+    #[test]
+    fn extrapolation_models() {
+        let mut sample = MotionSample::new(&[1.0, 2.0], &[2.0]);
+        sample.acceleration = vec![0.0, 4.0];
+        let elapsed = Duration::from_millis(500);
+        assert!(approx_eq(
+            &sample.extrapolate(ExtrapolationModel::Hold, elapsed),
+            &[1.0, 2.0]
+        ));
+        assert!(approx_eq(
+            &sample.extrapolate(ExtrapolationModel::Linear, elapsed),
+            &[2.0, 2.0]
+        ));
+        assert!(approx_eq(
+            &sample.extrapolate(ExtrapolationModel::Quadratic, elapsed),
+            &[2.0, 2.5]
+        ));
+    }
+
+    #[test]
+    fn extrapolation_limit() {
+        let mut extrapolation = Extrapolation::new(
+            MotionSample::new(&[0.0], &[10.0]),
+            ExtrapolationModel::Linear,
+            Duration::from_millis(200),
+        );
+        extrapolation.advance(Duration::from_millis(100));
+        assert!(approx_eq(&extrapolation.get_position(), &[1.0]));
+        extrapolation.advance(Duration::from_millis(300));
+        assert!(approx_eq(&extrapolation.get_position(), &[2.0]));
+    }
+
+    #[test]
+    fn blend_to_new_sample() {
+        let mut extrapolation = Extrapolation::new(
+            MotionSample::new(&[0.0], &[10.0]),
+            ExtrapolationModel::Linear,
+            Duration::from_secs(1),
+        );
+        extrapolation.advance(Duration::from_millis(100));
+
+        // the update reports the Entity further along than was extrapolated
+        extrapolation.set_sample(
+            MotionSample::new(&[3.0], &[10.0]),
+            Duration::from_millis(100),
+            CorrectionCurve::Linear,
+        );
+        assert!(approx_eq(&extrapolation.get_position(), &[1.0]));
+        extrapolation.advance(Duration::from_millis(50));
+        assert!(approx_eq(&extrapolation.get_position(), &[2.5]));
+        extrapolation.advance(Duration::from_millis(50));
+        assert!(approx_eq(&extrapolation.get_position(), &[4.0]));
+
+        let mut teleport = MotionSample::new(&[50.0], &[0.0]);
+        teleport.teleport = true;
+        extrapolation.set_sample(
+            teleport,
+            Duration::from_millis(100),
+            CorrectionCurve::Linear,
+        );
+        assert!(approx_eq(&extrapolation.get_position(), &[50.0]));
+    }
+}
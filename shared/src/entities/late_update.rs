@@ -0,0 +1,140 @@
+use super::state_mask::StateMask;
+
+/// The most ticks an Update can be behind a newer Update of the same Property
+/// & still be considered late. Ticks wrap around, so a Property which hasn't
+/// been updated for longer would otherwise seem to have been updated in the
+/// future
+pub const MAX_LATE_UPDATE_TICKS: u16 = 1024;
+
+/// What the Client does with an Entity Update which arrives after an Update
+/// of the same Properties from a newer Server tick has already been applied,
+/// such as when packets are reordered in flight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LateUpdatePolicy {
+    /// The late Update is dropped entirely
+    Discard,
+    /// Only the Properties of the late Update which haven't been updated
+    /// since its tick are applied, & the rest are dropped
+    ApplyNewerFields,
+    /// The late Update isn't applied to the Entity, but is handed to the
+    /// application as a `ClientEvent::LateUpdateEntity`, with the late state
+    /// retrievable through `NaiaClient::get_late_entity()`
+    DeliverToApp,
+}
+
+impl Default for LateUpdatePolicy {
+    fn default() -> Self {
+        return LateUpdatePolicy::ApplyNewerFields;
+    }
+}
+
+/// Counts of the late Entity Updates the Client has received, & how they
+/// were handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LateUpdateStats {
+    /// The number of Updates which arrived after a newer Update of any of
+    /// their Properties
+    pub late: u64,
+    /// The number of late Updates which were dropped entirely
+    pub discarded: u64,
+    /// The number of late Updates of which only some Properties were applied
+    pub partially_applied: u64,
+    /// The number of late Updates handed to the application
+    pub delivered: u64,
+}
+
+/// The Server tick at which each Property of an Entity was last updated, so
+/// that Updates which arrive out of order can be recognized
+#[derive(Debug, Clone)]
+pub struct PropertyTicks {
+    ticks: Vec<Option<u16>>,
+}
+
+impl PropertyTicks {
+    /// Creates a new PropertyTicks for an Entity with the given number of
+    /// Properties, which was created at the given tick, or None if unknown
+    pub fn new(property_count: u8, tick: Option<u16>) -> Self {
+        PropertyTicks {
+            ticks: vec![tick; property_count as usize],
+        }
+    }
+
+    /// Gets the number of Properties whose ticks are tracked
+    pub fn get_property_count(&self) -> u8 {
+        return self.ticks.len() as u8;
+    }
+
+    /// Records that the Properties set in the StateMask were updated at the
+    /// given tick
+    pub fn record(&mut self, state_mask: &StateMask, tick: u16) {
+        for index in state_mask.get_set_indices() {
+            if let Some(property_tick) = self.ticks.get_mut(index as usize) {
+                *property_tick = Some(tick);
+            }
+        }
+    }
+
+    /// Gets the Properties set in the StateMask which were updated after the
+    /// given tick, & so are stale in an Update from that tick
+    pub fn get_stale_mask(&self, state_mask: &StateMask, tick: u16) -> StateMask {
+        let mut stale_mask = StateMask::new(state_mask.byte_number());
+        for index in state_mask.get_set_indices() {
+            if let Some(Some(property_tick)) = self.ticks.get(index as usize) {
+                let ticks_newer = property_tick.wrapping_sub(tick);
+                if ticks_newer > 0 && ticks_newer <= MAX_LATE_UPDATE_TICKS {
+                    stale_mask.set_bit(index, true);
+                }
+            }
+        }
+        return stale_mask;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PropertyTicks, MAX_LATE_UPDATE_TICKS};
+    use crate::StateMask;
+
+    fn mask(indices: &[u8]) -> StateMask {
+        let mut state_mask = StateMask::new(1);
+        for index in indices {
+            state_mask.set_bit(*index, true);
+        }
+        return state_mask;
+    }
+
+    #[test]
+    fn stale_properties() {
+        let mut property_ticks = PropertyTicks::new(3, Some(10));
+        property_ticks.record(&mask(&[0, 1]), 14);
+        property_ticks.record(&mask(&[1]), 16);
+
+        assert!(property_ticks
+            .get_stale_mask(&mask(&[0, 1, 2]), 16)
+            .is_clear());
+        let stale_mask = property_ticks.get_stale_mask(&mask(&[0, 1, 2]), 12);
+        assert!(stale_mask.get_set_indices() == vec![0, 1]);
+        let stale_mask = property_ticks.get_stale_mask(&mask(&[0, 1, 2]), 15);
+        assert!(stale_mask.get_set_indices() == vec![1]);
+    }
+
+    #[test]
+    fn wrapping_ticks() {
+        let mut property_ticks = PropertyTicks::new(1, Some(65530));
+        property_ticks.record(&mask(&[0]), 2);
+        assert!(!property_ticks.get_stale_mask(&mask(&[0]), 65534).is_clear());
+        assert!(property_ticks.get_stale_mask(&mask(&[0]), 3).is_clear());
+
+        // long after the last Update, a newer tick may wrap around past it
+        let newer_tick = 2u16.wrapping_sub(MAX_LATE_UPDATE_TICKS + 1);
+        assert!(property_ticks
+            .get_stale_mask(&mask(&[0]), newer_tick)
+            .is_clear());
+    }
+
+    #[test]
+    fn unknown_ticks() {
+        let property_ticks = PropertyTicks::new(2, None);
+        assert!(property_ticks.get_stale_mask(&mask(&[0, 1]), 5).is_clear());
+    }
+}
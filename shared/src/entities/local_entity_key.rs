@@ -1,3 +1,63 @@
+use std::{
+    fmt::{Display, Formatter, Result},
+    io::Cursor,
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
 /// The key that represents an Entity in the Client's scope, that is being
-/// synced to the Client
-pub type LocalEntityKey = u16;
+/// synced to the Client. Indices are reused once an Entity leaves scope, but
+/// each reuse increments the generation, so a key held onto after its Entity
+/// has been deleted will not address whichever Entity takes its place.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LocalEntityKey {
+    index: u16,
+    generation: u8,
+}
+
+impl LocalEntityKey {
+    /// Create a new LocalEntityKey, given an index & a generation
+    pub fn new(index: u16, generation: u8) -> Self {
+        LocalEntityKey { index, generation }
+    }
+
+    /// Gets the index of the key, which may be shared by keys of different
+    /// generations
+    pub fn get_index(&self) -> u16 {
+        return self.index;
+    }
+
+    /// Gets the generation of the key
+    pub fn get_generation(&self) -> u8 {
+        return self.generation;
+    }
+
+    /// Gets a key with the same index, but the next generation, to be used when
+    /// the index is reused for another Entity
+    pub fn next_generation(&self) -> Self {
+        LocalEntityKey {
+            index: self.index,
+            generation: self.generation.wrapping_add(1),
+        }
+    }
+
+    /// Writes the key into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u16::<BigEndian>(self.index).unwrap();
+        out_bytes.write_u8(self.generation).unwrap();
+    }
+
+    /// Reads a key from an incoming byte stream. Returns None if the stream
+    /// ends before the key does
+    pub fn read(cursor: &mut Cursor<&[u8]>) -> Option<Self> {
+        let index = cursor.read_u16::<BigEndian>().ok()?;
+        let generation = cursor.read_u8().ok()?;
+        return Some(LocalEntityKey { index, generation });
+    }
+}
+
+impl Display for LocalEntityKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
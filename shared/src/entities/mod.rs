@@ -1,9 +1,16 @@
+pub(crate) mod correction;
 pub(crate) mod entity;
 pub(crate) mod entity_builder;
+pub(crate) mod entity_debug_dump;
 pub(crate) mod entity_mutator;
 pub(crate) mod entity_notifiable;
 pub(crate) mod entity_type;
+pub(crate) mod extrapolation;
+pub(crate) mod late_update;
 pub(crate) mod local_entity_key;
 pub(crate) mod property;
 pub(crate) mod property_io;
+pub(crate) mod replicated_animation;
+pub(crate) mod replicated_collection;
+pub(crate) mod replicated_transform;
 pub(crate) mod state_mask;
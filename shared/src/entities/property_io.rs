@@ -1,4 +1,4 @@
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 use nanoserde::{DeBin, SerBin};
 
@@ -11,8 +11,11 @@ pub trait PropertyIo<T> {
     /// Writes contained value into outgoing byte stream
     fn write(&self, buffer: &mut Vec<u8>);
     /// Given a cursor into incoming packet data, updates the Property with the
-    /// synced value
-    fn read(&mut self, cursor: &mut Cursor<&[u8]>);
+    /// synced value. If the data has run out, which happens when it was written
+    /// by an older version of the type, the Property keeps its current value.
+    /// Returns None, leaving the Property untouched, if the data ends partway
+    /// through the value, or the value can't be deserialized
+    fn read(&mut self, cursor: &mut Cursor<&[u8]>) -> Option<()>;
 }
 
 impl<T: Clone + DeBin + SerBin> PropertyIo<T> for Property<T> {
@@ -22,12 +25,47 @@ impl<T: Clone + DeBin + SerBin> PropertyIo<T> for Property<T> {
         buffer.append(encoded);
     }
 
-    fn read(&mut self, cursor: &mut Cursor<&[u8]>) {
-        let length = cursor.read_u8().unwrap();
-        let mut buffer = Vec::with_capacity(length as usize);
-        for _ in 0..length {
-            buffer.push(cursor.read_u8().unwrap());
+    fn read(&mut self, cursor: &mut Cursor<&[u8]>) -> Option<()> {
+        if cursor.position() as usize >= cursor.get_ref().len() {
+            return Some(());
         }
-        self.inner = DeBin::deserialize_bin(&buffer[..]).unwrap();
+        let length = cursor.read_u8().ok()?;
+        let mut buffer = vec![0; length as usize];
+        cursor.read_exact(&mut buffer).ok()?;
+        self.inner = DeBin::deserialize_bin(&buffer[..]).ok()?;
+        return Some(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{super::property::Property, PropertyIo};
+
+    #[test]
+    fn rejects_truncated_or_malformed_values() {
+        let mut written = Vec::<u8>::new();
+        PropertyIo::write(&Property::<u32>::new(7, 0), &mut written);
+
+        let mut property = Property::<u32>::new(3, 0);
+        assert!(property.read(&mut Cursor::new(&written[..])).is_some());
+        assert!(*property.get() == 7);
+
+        // data which ran out before the Property, as written by an older
+        // version of the type, keeps the current value
+        let mut property = Property::<u32>::new(3, 0);
+        assert!(property.read(&mut Cursor::new(&[][..])).is_some());
+        assert!(*property.get() == 3);
+
+        // data which ends partway through the value
+        let truncated = &written[..written.len() - 1];
+        assert!(property.read(&mut Cursor::new(truncated)).is_none());
+        assert!(*property.get() == 3);
+
+        // a value too short to be deserialized
+        let malformed: &[u8] = &[1, 7];
+        assert!(property.read(&mut Cursor::new(malformed)).is_none());
+        assert!(*property.get() == 3);
     }
 }
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use nanoserde::{DeBin, DeBinErr, SerBin};
+
+// The largest value of the u16 a normalized time is encoded as
+const NORMALIZED_TIME_SCALE: f32 = u16::MAX as f32;
+
+/// The state of an Entity's animation, to be used as the value of a
+/// Property. Rather than the animation's time being replicated every frame,
+/// the state records the Server tick at which it was at a given normalized
+/// time & the speed at which it plays, so that both hosts can advance the
+/// time on their own, & an update is only needed when the state, speed or
+/// parameters change
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplicatedAnimation {
+    state_id: u16,
+    start_tick: u16,
+    start_time: f32,
+    speed: f32,
+    looping: bool,
+    parameters: Vec<f32>,
+}
+
+impl ReplicatedAnimation {
+    /// Creates a new ReplicatedAnimation, playing the given state from its
+    /// beginning at the given Server tick, see `play()`
+    pub fn new(state_id: u16, speed: f32, looping: bool, tick: u16) -> Self {
+        let mut animation = ReplicatedAnimation::default();
+        animation.play(state_id, speed, looping, tick);
+        return animation;
+    }
+
+    /// Plays the given state from its beginning, as of the given Server tick.
+    /// The speed is in normalized time per second, which is the playback
+    /// rate divided by the length of the animation in seconds. A state which
+    /// isn't looping stops at its end
+    pub fn play(&mut self, state_id: u16, speed: f32, looping: bool, tick: u16) {
+        self.state_id = state_id;
+        self.start_tick = tick;
+        self.start_time = 0.0;
+        self.speed = speed;
+        self.looping = looping;
+    }
+
+    /// Changes the speed of the current state as of the given Server tick,
+    /// continuing from the time it had reached
+    pub fn set_speed(&mut self, speed: f32, tick: u16, tick_interval: Duration) {
+        self.start_time = self.get_normalized_time(tick, 0.0, tick_interval);
+        self.start_tick = tick;
+        self.speed = speed;
+    }
+
+    /// Moves the current state to the given normalized time, as of the given
+    /// Server tick
+    pub fn seek(&mut self, normalized_time: f32, tick: u16) {
+        self.start_time = normalized_time.max(0.0).min(1.0);
+        self.start_tick = tick;
+    }
+
+    /// Gets the id of the current state
+    pub fn get_state_id(&self) -> u16 {
+        return self.state_id;
+    }
+
+    /// Gets the speed of the current state, in normalized time per second
+    pub fn get_speed(&self) -> f32 {
+        return self.speed;
+    }
+
+    /// Returns whether the current state loops
+    pub fn is_looping(&self) -> bool {
+        return self.looping;
+    }
+
+    /// Gets the normalized time of the current state, from 0 to 1, at the
+    /// given Server tick plus a fraction of a tick, given the Server's tick
+    /// interval. Ticks before the state started are treated as its start.
+    /// Ticks are compared as wrapping sequence numbers, so a state playing
+    /// for more than 32767 ticks should be anchored anew, for example with
+    /// `seek()` at the time it has reached
+    pub fn get_normalized_time(
+        &self,
+        tick: u16,
+        tick_fraction: f32,
+        tick_interval: Duration,
+    ) -> f32 {
+        let elapsed_ticks = (tick.wrapping_sub(self.start_tick) as i16).max(0) as f32
+            + tick_fraction.max(0.0).min(1.0);
+        let normalized_time =
+            self.start_time + self.speed * elapsed_ticks * tick_interval.as_secs_f32();
+        if self.looping {
+            return normalized_time.rem_euclid(1.0);
+        }
+        return normalized_time.max(0.0).min(1.0);
+    }
+
+    /// Sets the parameter at the given index, such as a blend weight, adding
+    /// parameters of zero before it as needed. At most 255 parameters are
+    /// replicated
+    pub fn set_parameter(&mut self, index: u8, value: f32) {
+        let index = index as usize;
+        if self.parameters.len() <= index {
+            self.parameters.resize(index + 1, 0.0);
+        }
+        self.parameters[index] = value;
+    }
+
+    /// Gets the parameter at the given index, or zero if it hasn't been set
+    pub fn get_parameter(&self, index: u8) -> f32 {
+        return self.parameters.get(index as usize).copied().unwrap_or(0.0);
+    }
+
+    /// Gets every parameter
+    pub fn get_parameters(&self) -> &[f32] {
+        return &self.parameters;
+    }
+}
+
+impl SerBin for ReplicatedAnimation {
+    fn ser_bin(&self, output: &mut Vec<u8>) {
+        self.state_id.ser_bin(output);
+        self.start_tick.ser_bin(output);
+        ((self.start_time.max(0.0).min(1.0) * NORMALIZED_TIME_SCALE).round() as u16)
+            .ser_bin(output);
+        self.speed.ser_bin(output);
+        self.looping.ser_bin(output);
+        (self.parameters.len() as u8).ser_bin(output);
+        for parameter in self.parameters.iter() {
+            parameter.ser_bin(output);
+        }
+    }
+}
+
+impl DeBin for ReplicatedAnimation {
+    fn de_bin(offset: &mut usize, bytes: &[u8]) -> Result<Self, DeBinErr> {
+        let state_id = u16::de_bin(offset, bytes)?;
+        let start_tick = u16::de_bin(offset, bytes)?;
+        let start_time = u16::de_bin(offset, bytes)? as f32 / NORMALIZED_TIME_SCALE;
+        let speed = f32::de_bin(offset, bytes)?;
+        let looping = bool::de_bin(offset, bytes)?;
+        let parameter_count = u8::de_bin(offset, bytes)?;
+        let mut parameters = Vec::with_capacity(parameter_count as usize);
+        for _ in 0..parameter_count {
+            parameters.push(f32::de_bin(offset, bytes)?);
+        }
+        return Ok(ReplicatedAnimation {
+            state_id,
+            start_tick,
+            start_time,
+            speed,
+            looping,
+            parameters,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nanoserde::{DeBin, SerBin};
+
+    use crate::ReplicatedAnimation;
+
+    const TICK_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        return (a - b).abs() < 0.001;
+    }
+
+    #[test]
+    fn advance_with_ticks() {
+        // a one second loop, started at tick 65530 so that the tick wraps
+        let animation = ReplicatedAnimation::new(3, 1.0, true, 65530);
+        assert!(approx_eq(
+            animation.get_normalized_time(65530, 0.0, TICK_INTERVAL),
+            0.0
+        ));
+        assert!(approx_eq(
+            animation.get_normalized_time(4, 0.5, TICK_INTERVAL),
+            0.525
+        ));
+        assert!(approx_eq(
+            animation.get_normalized_time(24, 0.0, TICK_INTERVAL),
+            0.5
+        ));
+        assert!(approx_eq(
+            animation.get_normalized_time(65520, 0.0, TICK_INTERVAL),
+            0.0
+        ));
+
+        let mut once = ReplicatedAnimation::new(1, 2.0, false, 0);
+        assert!(approx_eq(
+            once.get_normalized_time(5, 0.0, TICK_INTERVAL),
+            0.5
+        ));
+        assert!(approx_eq(
+            once.get_normalized_time(40, 0.0, TICK_INTERVAL),
+            1.0
+        ));
+
+        // halving the speed midway continues from the time reached
+        once.set_speed(1.0, 5, TICK_INTERVAL);
+        assert!(approx_eq(
+            once.get_normalized_time(10, 0.0, TICK_INTERVAL),
+            0.75
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut animation = ReplicatedAnimation::new(7, 0.5, false, 100);
+        animation.seek(0.25, 120);
+        animation.set_parameter(2, -1.5);
+        let received = ReplicatedAnimation::deserialize_bin(&animation.serialize_bin()).unwrap();
+        assert!(received.get_state_id() == 7);
+        assert!(!received.is_looping());
+        assert!(received.get_parameters() == &[0.0, 0.0, -1.5]);
+        assert!(approx_eq(
+            received.get_normalized_time(140, 0.0, TICK_INTERVAL),
+            animation.get_normalized_time(140, 0.0, TICK_INTERVAL)
+        ));
+    }
+}
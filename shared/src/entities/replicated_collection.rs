@@ -0,0 +1,348 @@
+use std::{collections::HashMap, hash::Hash};
+
+use nanoserde::{DeBin, DeBinErr, SerBin};
+
+use crate::sequence_buffer::sequence_greater_than;
+
+/// The revision of a ReplicatedCollection at which an entry last changed,
+/// used to discard changes which arrive out of order
+pub type CollectionRevision = u16;
+
+/// A change to a single entry of a ReplicatedCollection, to be carried as
+/// the value of a Property of a guaranteed Event. Each change is written on
+/// its own, so an entry's key & value together must serialize to no more
+/// than 255 bytes, less the revision & the kind of change
+#[derive(Clone, Debug, PartialEq)]
+pub enum CollectionChange<K, V> {
+    /// An entry was added under the given key
+    Add(CollectionRevision, K, V),
+    /// The value of an existing entry was changed
+    Change(CollectionRevision, K, V),
+    /// The entry under the given key was removed
+    Remove(CollectionRevision, K),
+}
+
+impl<K, V> CollectionChange<K, V> {
+    /// Gets the revision of the collection at which the change was made
+    pub fn get_revision(&self) -> CollectionRevision {
+        match self {
+            CollectionChange::Add(revision, _, _)
+            | CollectionChange::Change(revision, _, _)
+            | CollectionChange::Remove(revision, _) => return *revision,
+        }
+    }
+
+    /// Gets the key of the entry which was changed
+    pub fn get_key(&self) -> &K {
+        match self {
+            CollectionChange::Add(_, key, _)
+            | CollectionChange::Change(_, key, _)
+            | CollectionChange::Remove(_, key) => return key,
+        }
+    }
+}
+
+impl<K: Default, V> Default for CollectionChange<K, V> {
+    fn default() -> Self {
+        return CollectionChange::Remove(0, K::default());
+    }
+}
+
+impl<K: SerBin, V: SerBin> SerBin for CollectionChange<K, V> {
+    fn ser_bin(&self, output: &mut Vec<u8>) {
+        match self {
+            CollectionChange::Add(revision, key, value) => {
+                0u8.ser_bin(output);
+                revision.ser_bin(output);
+                key.ser_bin(output);
+                value.ser_bin(output);
+            }
+            CollectionChange::Change(revision, key, value) => {
+                1u8.ser_bin(output);
+                revision.ser_bin(output);
+                key.ser_bin(output);
+                value.ser_bin(output);
+            }
+            CollectionChange::Remove(revision, key) => {
+                2u8.ser_bin(output);
+                revision.ser_bin(output);
+                key.ser_bin(output);
+            }
+        }
+    }
+}
+
+impl<K: DeBin, V: DeBin> DeBin for CollectionChange<K, V> {
+    fn de_bin(offset: &mut usize, bytes: &[u8]) -> Result<Self, DeBinErr> {
+        let change_kind = u8::de_bin(offset, bytes)?;
+        let revision = CollectionRevision::de_bin(offset, bytes)?;
+        let key = K::de_bin(offset, bytes)?;
+        match change_kind {
+            0 => {
+                return Ok(CollectionChange::Add(
+                    revision,
+                    key,
+                    V::de_bin(offset, bytes)?,
+                ))
+            }
+            1 => {
+                return Ok(CollectionChange::Change(
+                    revision,
+                    key,
+                    V::de_bin(offset, bytes)?,
+                ))
+            }
+            2 => return Ok(CollectionChange::Remove(revision, key)),
+            _ => {
+                return Err(DeBinErr {
+                    o: *offset,
+                    l: 1,
+                    s: bytes.len(),
+                })
+            }
+        }
+    }
+}
+
+// A change to an entry which has yet to be taken with `take_changes()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PendingChange {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// A keyed collection, such as the slots of an inventory or a container,
+/// which is replicated entry by entry rather than as a whole. The Server
+/// owns the authoritative collection, & tracks which entries have been
+/// added, changed or removed since the changes were last taken with
+/// `take_changes()`, to be sent to every Client which can see the
+/// collection. A Client which comes to see the collection later is sent
+/// `get_full_sync()` instead. The Client keeps a mirror of the collection,
+/// to which it applies each change it receives with `apply_change()`
+#[derive(Clone, Debug)]
+pub struct ReplicatedCollection<K: Copy + Eq + Hash, V: Clone> {
+    entries: HashMap<K, V>,
+    revisions: HashMap<K, CollectionRevision>,
+    pending: HashMap<K, PendingChange>,
+    revision: CollectionRevision,
+}
+
+impl<K: Copy + Eq + Hash, V: Clone> ReplicatedCollection<K, V> {
+    /// Creates a new, empty ReplicatedCollection
+    pub fn new() -> Self {
+        ReplicatedCollection {
+            entries: HashMap::new(),
+            revisions: HashMap::new(),
+            pending: HashMap::new(),
+            revision: 0,
+        }
+    }
+
+    /// Inserts an entry under the given key, returning the value it replaced,
+    /// if any
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let replaced = self.entries.insert(key, value);
+        let pending = match (self.pending.get(&key), replaced.is_some()) {
+            (Some(PendingChange::Added), _) => PendingChange::Added,
+            (Some(_), _) | (None, true) => PendingChange::Changed,
+            (None, false) => PendingChange::Added,
+        };
+        self.pending.insert(key, pending);
+        return replaced;
+    }
+
+    /// Removes the entry under the given key, returning its value, if any
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.entries.remove(key)?;
+        if let Some(PendingChange::Added) = self.pending.get(key) {
+            // the entry was never sent, so there is nothing to remove
+            self.pending.remove(key);
+        } else {
+            self.pending.insert(*key, PendingChange::Removed);
+        }
+        return Some(removed);
+    }
+
+    /// Modifies the entry under the given key in place, marking it as
+    /// changed. Returns false if there is no such entry
+    pub fn modify(&mut self, key: &K, modify_func: impl FnOnce(&mut V)) -> bool {
+        match self.entries.get_mut(key) {
+            Some(value) => {
+                modify_func(value);
+                if !self.pending.contains_key(key) {
+                    self.pending.insert(*key, PendingChange::Changed);
+                }
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    /// Gets the value of the entry under the given key
+    pub fn get(&self, key: &K) -> Option<&V> {
+        return self.entries.get(key);
+    }
+
+    /// Returns whether there is an entry under the given key
+    pub fn contains_key(&self, key: &K) -> bool {
+        return self.entries.contains_key(key);
+    }
+
+    /// Gets an iterator over every entry
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        return self.entries.iter();
+    }
+
+    /// Gets the number of entries
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Returns whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Returns whether any entry has been added, changed or removed since the
+    /// changes were last taken
+    pub fn has_changes(&self) -> bool {
+        return !self.pending.is_empty();
+    }
+
+    /// Takes a change for every entry which has been added, changed or
+    /// removed since the changes were last taken, all at a new revision.
+    /// Several changes to the same entry are merged into one, so a Client
+    /// only receives the latest value
+    pub fn take_changes(&mut self) -> Vec<CollectionChange<K, V>> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        self.revision = self.revision.wrapping_add(1);
+        let revision = self.revision;
+        let mut output = Vec::with_capacity(self.pending.len());
+        for (key, pending) in self.pending.drain() {
+            match pending {
+                PendingChange::Added => {
+                    if let Some(value) = self.entries.get(&key) {
+                        self.revisions.insert(key, revision);
+                        output.push(CollectionChange::Add(revision, key, value.clone()));
+                    }
+                }
+                PendingChange::Changed => {
+                    if let Some(value) = self.entries.get(&key) {
+                        self.revisions.insert(key, revision);
+                        output.push(CollectionChange::Change(revision, key, value.clone()));
+                    }
+                }
+                PendingChange::Removed => {
+                    self.revisions.remove(&key);
+                    output.push(CollectionChange::Remove(revision, key));
+                }
+            }
+        }
+        return output;
+    }
+
+    /// Gets an Add change for every entry, to be sent to a Client which has
+    /// just come to see the collection. Each entry is added at the revision
+    /// it last changed, so changes taken afterwards supersede it
+    pub fn get_full_sync(&self) -> Vec<CollectionChange<K, V>> {
+        return self
+            .entries
+            .iter()
+            .map(|(key, value)| {
+                let revision = self.revisions.get(key).copied().unwrap_or(self.revision);
+                return CollectionChange::Add(revision, *key, value.clone());
+            })
+            .collect();
+    }
+
+    /// Applies a change received from the Server. A change to an entry which
+    /// is older than the last one applied to that entry has arrived out of
+    /// order, & is discarded, in which case false is returned. Revisions
+    /// wrap, so changes to an entry more than 32767 revisions apart are
+    /// taken to be in the opposite order
+    pub fn apply_change(&mut self, change: CollectionChange<K, V>) -> bool {
+        let revision = change.get_revision();
+        if let Some(last_revision) = self.revisions.get(change.get_key()) {
+            if !sequence_greater_than(revision, *last_revision) {
+                return false;
+            }
+        }
+        match change {
+            CollectionChange::Add(_, key, value) | CollectionChange::Change(_, key, value) => {
+                self.entries.insert(key, value);
+                self.revisions.insert(key, revision);
+            }
+            CollectionChange::Remove(_, key) => {
+                // the revision is kept, so that an older change to the entry
+                // arriving later can't bring it back
+                self.entries.remove(&key);
+                self.revisions.insert(key, revision);
+            }
+        }
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nanoserde::{DeBin, SerBin};
+
+    use crate::{CollectionChange, ReplicatedCollection};
+
+    #[test]
+    fn merge_pending_changes() {
+        let mut server: ReplicatedCollection<u8, u32> = ReplicatedCollection::new();
+        server.insert(0, 10);
+        server.insert(0, 11);
+        server.insert(1, 20);
+        server.remove(&1);
+        let changes = server.take_changes();
+        assert!(changes == vec![CollectionChange::Add(1, 0, 11)]);
+        assert!(!server.has_changes());
+
+        server.modify(&0, |value| *value += 1);
+        server.remove(&0);
+        server.insert(0, 30);
+        assert!(server.take_changes() == vec![CollectionChange::Change(2, 0, 30)]);
+        server.remove(&0);
+        assert!(server.take_changes() == vec![CollectionChange::Remove(3, 0)]);
+        assert!(server.take_changes().is_empty());
+    }
+
+    #[test]
+    fn apply_out_of_order() {
+        let mut server: ReplicatedCollection<u8, u32> = ReplicatedCollection::new();
+        server.insert(4, 1);
+        let added = server.take_changes();
+        server.modify(&4, |value| *value = 2);
+        let changed = server.take_changes();
+        server.remove(&4);
+        let removed = server.take_changes();
+        server.insert(5, 7);
+
+        let mut client: ReplicatedCollection<u8, u32> = ReplicatedCollection::new();
+        for change in server.get_full_sync() {
+            assert!(client.apply_change(change));
+        }
+        assert!(client.get(&5) == Some(&7));
+
+        // the removal overtakes the earlier changes, which are then discarded
+        assert!(client.apply_change(changed[0].clone()));
+        assert!(client.apply_change(removed[0].clone()));
+        assert!(!client.apply_change(added[0].clone()));
+        assert!(!client.contains_key(&4));
+    }
+
+    #[test]
+    fn round_trip() {
+        let change: CollectionChange<u8, String> = CollectionChange::Change(9, 3, "sword".into());
+        let received = CollectionChange::deserialize_bin(&change.serialize_bin()).unwrap();
+        assert!(received == change);
+        let removal: CollectionChange<u8, String> = CollectionChange::Remove(10, 3);
+        let received = CollectionChange::deserialize_bin(&removal.serialize_bin()).unwrap();
+        assert!(received == removal);
+    }
+}
@@ -0,0 +1,378 @@
+use nanoserde::{DeBin, DeBinErr, SerBin};
+
+use super::extrapolation::MotionSample;
+
+/// The default number of fractional bits of the fixed-point encoding of
+/// positions & scales, giving a precision of 1/256
+pub const DEFAULT_TRANSFORM_FRACTION_BITS: u8 = 8;
+
+// The number of bits of each of the three smallest components of an encoded
+// rotation, which along with the 2 bit index of the largest fill a u32
+const ROTATION_COMPONENT_BITS: u32 = 10;
+
+// The largest magnitude of any but the largest component of a unit
+// quaternion, 1/sqrt(2)
+const ROTATION_COMPONENT_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+const POSITION_AXES_SHIFT: u8 = 0;
+const ROTATION_FLAG: u8 = 0x08;
+const SCALE_AXES_SHIFT: u8 = 4;
+const TELEPORT_FLAG: u8 = 0x80;
+
+/// Which parts of a ReplicatedTransform are replicated. Axes which aren't
+/// replicated aren't written at all, and are received as their identity
+/// value, so that for example a game on a plane doesn't pay for a
+/// vertical axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransformAxes {
+    /// Whether the x, y & z axes of the position are replicated
+    pub position: [bool; 3],
+    /// Whether the rotation is replicated
+    pub rotation: bool,
+    /// Whether the x, y & z axes of the scale are replicated
+    pub scale: [bool; 3],
+}
+
+impl Default for TransformAxes {
+    fn default() -> Self {
+        TransformAxes {
+            position: [true; 3],
+            rotation: true,
+            scale: [true; 3],
+        }
+    }
+}
+
+impl TransformAxes {
+    fn to_flags(&self) -> u8 {
+        let mut flags = 0;
+        for axis in 0..3 {
+            if self.position[axis] {
+                flags |= 1 << (POSITION_AXES_SHIFT + axis as u8);
+            }
+            if self.scale[axis] {
+                flags |= 1 << (SCALE_AXES_SHIFT + axis as u8);
+            }
+        }
+        if self.rotation {
+            flags |= ROTATION_FLAG;
+        }
+        return flags;
+    }
+
+    fn from_flags(flags: u8) -> Self {
+        let mut axes = TransformAxes {
+            position: [false; 3],
+            rotation: flags & ROTATION_FLAG != 0,
+            scale: [false; 3],
+        };
+        for axis in 0..3 {
+            axes.position[axis] = flags & (1 << (POSITION_AXES_SHIFT + axis as u8)) != 0;
+            axes.scale[axis] = flags & (1 << (SCALE_AXES_SHIFT + axis as u8)) != 0;
+        }
+        return axes;
+    }
+}
+
+/// The position, rotation & scale of an Entity, to be used as the value of
+/// a Property. Positions & scales are quantized to fixed-point with a given
+/// number of fractional bits, and the rotation, a unit quaternion, is
+/// compressed to 32 bits by writing only its three smallest components. A
+/// transform can be flagged as a teleport, telling the Client to snap to it
+/// rather than interpolate towards it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicatedTransform {
+    position: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+    axes: TransformAxes,
+    fraction_bits: u8,
+    teleport: bool,
+}
+
+impl Default for ReplicatedTransform {
+    fn default() -> Self {
+        return ReplicatedTransform::new(TransformAxes::default(), DEFAULT_TRANSFORM_FRACTION_BITS);
+    }
+}
+
+impl ReplicatedTransform {
+    /// Creates a new identity ReplicatedTransform, given which axes to
+    /// replicate, and the number of fractional bits to quantize positions &
+    /// scales to, at most 16
+    pub fn new(axes: TransformAxes, fraction_bits: u8) -> Self {
+        ReplicatedTransform {
+            position: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+            axes,
+            fraction_bits: fraction_bits.min(16),
+            teleport: false,
+        }
+    }
+
+    /// Gets the position along the x, y & z axes
+    pub fn get_position(&self) -> [f32; 3] {
+        return self.position;
+    }
+
+    /// Gets the rotation, as a unit quaternion of x, y, z & w
+    pub fn get_rotation(&self) -> [f32; 4] {
+        return self.rotation;
+    }
+
+    /// Gets the scale along the x, y & z axes
+    pub fn get_scale(&self) -> [f32; 3] {
+        return self.scale;
+    }
+
+    /// Gets which parts of the transform are replicated
+    pub fn get_axes(&self) -> TransformAxes {
+        return self.axes;
+    }
+
+    /// Returns whether the transform was moved by `teleport()`, and should be
+    /// snapped to rather than interpolated towards
+    pub fn is_teleport(&self) -> bool {
+        return self.teleport;
+    }
+
+    /// Moves the transform to the given position
+    pub fn set_position(&mut self, position: [f32; 3]) {
+        self.position = position;
+        self.teleport = false;
+    }
+
+    /// Moves the transform to the given position, flagging the move as a
+    /// teleport, so that the Client snaps to it instead of interpolating
+    pub fn teleport(&mut self, position: [f32; 3]) {
+        self.position = position;
+        self.teleport = true;
+    }
+
+    /// Sets the rotation, given a quaternion of x, y, z & w, which is
+    /// normalized
+    pub fn set_rotation(&mut self, rotation: [f32; 4]) {
+        self.rotation = normalize_quaternion(rotation);
+    }
+
+    /// Sets the scale along the x, y & z axes
+    pub fn set_scale(&mut self, scale: [f32; 3]) {
+        self.scale = scale;
+    }
+
+    /// Gets a MotionSample of the position, given its velocity, for
+    /// extrapolation with `NaiaClient::on_sample_motion()`. The sample of a
+    /// teleport is snapped to
+    pub fn get_motion_sample(&self, velocity: &[f32]) -> MotionSample {
+        let mut sample = MotionSample::new(&self.position, velocity);
+        sample.teleport = self.teleport;
+        return sample;
+    }
+
+    fn quantize(&self, value: f32) -> i32 {
+        let scaled = (value * (1 << self.fraction_bits) as f32).round();
+        return scaled.max(i32::MIN as f32).min(i32::MAX as f32) as i32;
+    }
+
+    fn dequantize(&self, value: i32) -> f32 {
+        return value as f32 / (1 << self.fraction_bits) as f32;
+    }
+}
+
+impl SerBin for ReplicatedTransform {
+    fn ser_bin(&self, output: &mut Vec<u8>) {
+        let mut flags = self.axes.to_flags();
+        if self.teleport {
+            flags |= TELEPORT_FLAG;
+        }
+        flags.ser_bin(output);
+        self.fraction_bits.ser_bin(output);
+        for axis in 0..3 {
+            if self.axes.position[axis] {
+                self.quantize(self.position[axis]).ser_bin(output);
+            }
+        }
+        if self.axes.rotation {
+            encode_rotation(self.rotation).ser_bin(output);
+        }
+        for axis in 0..3 {
+            if self.axes.scale[axis] {
+                self.quantize(self.scale[axis]).ser_bin(output);
+            }
+        }
+    }
+}
+
+impl DeBin for ReplicatedTransform {
+    fn de_bin(offset: &mut usize, bytes: &[u8]) -> Result<Self, DeBinErr> {
+        let flags = u8::de_bin(offset, bytes)?;
+        let fraction_bits = u8::de_bin(offset, bytes)?;
+        let mut transform =
+            ReplicatedTransform::new(TransformAxes::from_flags(flags), fraction_bits);
+        transform.teleport = flags & TELEPORT_FLAG != 0;
+        for axis in 0..3 {
+            if transform.axes.position[axis] {
+                transform.position[axis] = transform.dequantize(i32::de_bin(offset, bytes)?);
+            }
+        }
+        if transform.axes.rotation {
+            transform.rotation = decode_rotation(u32::de_bin(offset, bytes)?);
+        }
+        for axis in 0..3 {
+            if transform.axes.scale[axis] {
+                transform.scale[axis] = transform.dequantize(i32::de_bin(offset, bytes)?);
+            }
+        }
+        return Ok(transform);
+    }
+}
+
+fn normalize_quaternion(rotation: [f32; 4]) -> [f32; 4] {
+    let length = rotation
+        .iter()
+        .map(|value| value * value)
+        .sum::<f32>()
+        .sqrt();
+    if length <= f32::EPSILON || !length.is_finite() {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    return [
+        rotation[0] / length,
+        rotation[1] / length,
+        rotation[2] / length,
+        rotation[3] / length,
+    ];
+}
+
+// Encodes a unit quaternion as the index of its largest component, followed
+// by the three other components, quantized. The largest component can be
+// recovered from the others, and since a quaternion & its negation are the
+// same rotation, the largest is made positive so that its sign needn't be
+// written
+fn encode_rotation(rotation: [f32; 4]) -> u32 {
+    let rotation = normalize_quaternion(rotation);
+    let mut largest_index = 0;
+    for index in 1..4 {
+        if rotation[index].abs() > rotation[largest_index].abs() {
+            largest_index = index;
+        }
+    }
+    let sign = if rotation[largest_index] < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let max_quantized = ((1 << ROTATION_COMPONENT_BITS) - 1) as f32;
+    let mut packed = largest_index as u32;
+    for index in 0..4 {
+        if index == largest_index {
+            continue;
+        }
+        let normalized = (rotation[index] * sign / ROTATION_COMPONENT_RANGE)
+            .max(-1.0)
+            .min(1.0);
+        let quantized = ((normalized + 1.0) / 2.0 * max_quantized).round() as u32;
+        packed = (packed << ROTATION_COMPONENT_BITS) | quantized;
+    }
+    return packed;
+}
+
+fn decode_rotation(packed: u32) -> [f32; 4] {
+    let max_quantized = ((1 << ROTATION_COMPONENT_BITS) - 1) as u32;
+    let largest_index = (packed >> (ROTATION_COMPONENT_BITS * 3)) as usize & 3;
+    let mut rotation = [0.0; 4];
+    let mut shift = ROTATION_COMPONENT_BITS * 3;
+    let mut sum_of_squares = 0.0;
+    for index in 0..4 {
+        if index == largest_index {
+            continue;
+        }
+        shift -= ROTATION_COMPONENT_BITS;
+        let quantized = (packed >> shift) & max_quantized;
+        let normalized = quantized as f32 / max_quantized as f32 * 2.0 - 1.0;
+        rotation[index] = normalized * ROTATION_COMPONENT_RANGE;
+        sum_of_squares += rotation[index] * rotation[index];
+    }
+    rotation[largest_index] = (1.0 - sum_of_squares).max(0.0).sqrt();
+    return normalize_quaternion(rotation);
+}
+
+#[cfg(test)]
+mod tests {
+    use nanoserde::{DeBin, SerBin};
+
+    use crate::{ReplicatedTransform, TransformAxes};
+
+    fn approx_eq(a: &[f32], b: &[f32], tolerance: f32) -> bool {
+        return a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance);
+    }
+
+    fn round_trip(transform: &ReplicatedTransform) -> ReplicatedTransform {
+        return ReplicatedTransform::deserialize_bin(&transform.serialize_bin()).unwrap();
+    }
+
+    #[test]
+    fn quantized_round_trip() {
+        let mut transform = ReplicatedTransform::new(TransformAxes::default(), 4);
+        transform.set_position([1.03, -250.5, 0.0]);
+        transform.set_scale([2.0, 0.5, 1.0]);
+        let received = round_trip(&transform);
+        assert!(approx_eq(
+            &received.get_position(),
+            &[1.0, -250.5, 0.0],
+            0.0
+        ));
+        assert!(approx_eq(&received.get_scale(), &[2.0, 0.5, 1.0], 0.0));
+        assert!(!received.is_teleport());
+    }
+
+    #[test]
+    fn rotation_smallest_three() {
+        // a quarter turn about the y axis, & its negation which is the same
+        // rotation
+        let half_sqrt = std::f32::consts::FRAC_1_SQRT_2;
+        let mut transform = ReplicatedTransform::default();
+        transform.set_rotation([0.0, half_sqrt, 0.0, half_sqrt]);
+        let received = round_trip(&transform);
+        assert!(approx_eq(
+            &received.get_rotation(),
+            &[0.0, half_sqrt, 0.0, half_sqrt],
+            0.002
+        ));
+
+        transform.set_rotation([-0.1, -0.2, 0.3, -0.9]);
+        let expected = transform.get_rotation();
+        let received = round_trip(&transform).get_rotation();
+        let negated: Vec<f32> = expected.iter().map(|value| -value).collect();
+        assert!(approx_eq(&received, &negated, 0.002) || approx_eq(&received, &expected, 0.002));
+    }
+
+    #[test]
+    fn disabled_axes() {
+        let axes = TransformAxes {
+            position: [true, false, true],
+            rotation: false,
+            scale: [false; 3],
+        };
+        let mut transform = ReplicatedTransform::new(axes, 8);
+        transform.teleport([1.0, 2.0, 3.0]);
+        transform.set_rotation([1.0, 0.0, 0.0, 0.0]);
+        transform.set_scale([3.0; 3]);
+
+        let bytes = transform.serialize_bin();
+        // flags & fraction bits, followed by two position axes
+        assert!(bytes.len() == 2 + 2 * 4);
+        let received = ReplicatedTransform::deserialize_bin(&bytes).unwrap();
+        assert!(received.get_axes() == axes);
+        assert!(received.is_teleport());
+        assert!(approx_eq(&received.get_position(), &[1.0, 0.0, 3.0], 0.0));
+        assert!(approx_eq(
+            &received.get_rotation(),
+            &[0.0, 0.0, 0.0, 1.0],
+            0.0
+        ));
+        assert!(approx_eq(&received.get_scale(), &[1.0; 3], 0.0));
+        assert!(received.get_motion_sample(&[]).teleport);
+    }
+}
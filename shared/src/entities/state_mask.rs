@@ -58,6 +58,21 @@ impl StateMask {
         return true;
     }
 
+    /// Gets the indices of every bit which has been set in the StateMask, in
+    /// ascending order
+    pub fn get_set_indices(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        for n in 0..self.bytes {
+            let byte = self.mask[n as usize];
+            for bit in 0..8 {
+                if byte & (1 << bit) != 0 {
+                    output.push((n * 8) + bit);
+                }
+            }
+        }
+        return output;
+    }
+
     /// Get the number of bytes required to represent the StateMask
     pub fn byte_number(&self) -> u8 {
         return self.bytes;
@@ -106,14 +121,15 @@ impl StateMask {
         }
     }
 
-    /// Reads the StateMask from an incoming packet
-    pub fn read(cursor: &mut Cursor<&[u8]>) -> StateMask {
-        let bytes: u8 = cursor.read_u8().unwrap().into();
+    /// Reads the StateMask from an incoming packet. Returns None if the
+    /// packet ends before the StateMask does
+    pub fn read(cursor: &mut Cursor<&[u8]>) -> Option<StateMask> {
+        let bytes: u8 = cursor.read_u8().ok()?;
         let mut mask: Vec<u8> = Vec::new();
         for _ in 0..bytes {
-            mask.push(cursor.read_u8().unwrap().into());
+            mask.push(cursor.read_u8().ok()?);
         }
-        StateMask { bytes, mask }
+        return Some(StateMask { bytes, mask });
     }
 
     /// Copies the StateMask into another StateMask
@@ -199,6 +215,17 @@ mod single_byte_tests {
         assert!(mask.is_clear() == true);
     }
 
+    #[test]
+    fn get_set_indices() {
+        let mut mask = StateMask::new(1);
+        assert!(mask.get_set_indices().is_empty());
+
+        mask.set_bit(1, true);
+        mask.set_bit(5, true);
+
+        assert!(mask.get_set_indices() == vec![1, 5]);
+    }
+
     #[test]
     fn bytes() {
         let mut mask = StateMask::new(1);
@@ -0,0 +1,82 @@
+use std::{error::Error, fmt};
+
+use crate::{events::dynamic_event::MAX_DYNAMIC_KEY_LENGTH, packet_writer::MAX_EVENT_SIZE};
+
+/// An error returned by the Client or the Server, when an operation could not
+/// be carried out
+#[derive(Debug)]
+pub enum NaiaError {
+    /// A wrapped error from another library/codebase, such as a failure of
+    /// the underlying socket
+    Wrapped(Box<dyn Error>),
+    /// The Client is not connected to the Server, or there is no Client
+    /// associated with the given UserKey
+    NotConnected,
+    /// The type of the Event has not been registered in the Manifest
+    UnregisteredEvent,
+    /// The Event serializes to the given number of bytes, more than
+    /// `MAX_EVENT_SIZE`
+    EventTooLarge(usize),
+    /// The payload is of the given number of bytes, more than can be sent
+    PayloadTooLarge(usize),
+    /// The Event was dropped by an outgoing event middleware
+    FilteredOut,
+    /// The Event isn't guaranteed, and was dropped because the connection is
+    /// congested, see `BackpressureThresholds::drop_unguaranteed_events`
+    Congested,
+    /// The Client has no tick to send a command for yet, see
+    /// `NaiaClient::get_client_tick()`
+    NoClientTick,
+    /// The Entity which the Event targets isn't in scope for the Client, see
+    /// `NaiaServer::queue_entity_event()`
+    EntityNotInScope,
+    /// The NaiaId or the type has already been registered in the Manifest
+    AlreadyRegistered,
+    /// The Manifest is shared with another Client or Server in the process,
+    /// so types can't be registered in it once created
+    ManifestShared,
+    /// The kind of a DynamicEvent, or a key of one of its values, is empty,
+    /// or longer than `MAX_DYNAMIC_KEY_LENGTH` bytes
+    InvalidDynamicKey,
+}
+
+impl fmt::Display for NaiaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            NaiaError::Wrapped(boxed_err) => fmt::Display::fmt(boxed_err.as_ref(), f),
+            NaiaError::NotConnected => write!(f, "Naia Error: not connected"),
+            NaiaError::UnregisteredEvent => {
+                write!(f, "Naia Error: Event type not registered in the Manifest")
+            }
+            NaiaError::EventTooLarge(size) => write!(
+                f,
+                "Naia Error: Event of {} bytes is larger than {} bytes",
+                size, MAX_EVENT_SIZE
+            ),
+            NaiaError::PayloadTooLarge(size) => {
+                write!(f, "Naia Error: payload of {} bytes is too large", size)
+            }
+            NaiaError::FilteredOut => write!(f, "Naia Error: Event dropped by middleware"),
+            NaiaError::Congested => {
+                write!(f, "Naia Error: Event dropped, the connection is congested")
+            }
+            NaiaError::NoClientTick => write!(f, "Naia Error: no Client tick yet"),
+            NaiaError::EntityNotInScope => {
+                write!(f, "Naia Error: Entity isn't in scope for the Client")
+            }
+            NaiaError::AlreadyRegistered => {
+                write!(f, "Naia Error: type already registered in the Manifest")
+            }
+            NaiaError::ManifestShared => {
+                write!(f, "Naia Error: the Manifest is shared, & can't be modified")
+            }
+            NaiaError::InvalidDynamicKey => write!(
+                f,
+                "Naia Error: DynamicEvent key is empty, or longer than {} bytes",
+                MAX_DYNAMIC_KEY_LENGTH
+            ),
+        }
+    }
+}
+
+impl Error for NaiaError {}
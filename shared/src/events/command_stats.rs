@@ -0,0 +1,12 @@
+/// Statistics about the per-tick commands received from a remote host
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CommandStats {
+    /// The number of distinct ticks a command has been received for
+    pub received: u64,
+    /// The number of ticks whose command only arrived as a redundant copy in
+    /// a later packet, because the packet which first carried it was lost
+    pub recovered: u64,
+    /// The number of redundant copies received of commands which had already
+    /// arrived
+    pub duplicates: u64,
+}
@@ -0,0 +1,543 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{any::TypeId, io::Cursor, marker::PhantomData};
+
+use crate::{
+    error::NaiaError, manifest_descriptor::PropertyDescriptor, packet_writer::MAX_EVENT_SIZE,
+};
+
+use super::{event::Event, event_builder::EventBuilder, event_type::EventType};
+
+/// The most bytes the kind of a DynamicEvent, or the key of any of its
+/// values, may be long
+pub const MAX_DYNAMIC_KEY_LENGTH: usize = 32;
+
+/// A value carried by a DynamicEvent
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynamicValue {
+    /// A boolean
+    Bool(bool),
+    /// A signed integer
+    Int(i64),
+    /// A floating point number
+    Float(f64),
+    /// A UTF-8 string, of at most 255 bytes
+    String(String),
+    /// Raw bytes, at most 255 of them
+    Bytes(Vec<u8>),
+}
+
+impl DynamicValue {
+    fn get_tag(&self) -> u8 {
+        match self {
+            DynamicValue::Bool(_) => return 0,
+            DynamicValue::Int(_) => return 1,
+            DynamicValue::Float(_) => return 2,
+            DynamicValue::String(_) => return 3,
+            DynamicValue::Bytes(_) => return 4,
+        }
+    }
+
+    // the number of bytes the value is written as, after its tag
+    fn bytes_number(&self) -> usize {
+        match self {
+            DynamicValue::Bool(_) => return 1,
+            DynamicValue::Int(_) | DynamicValue::Float(_) => return 8,
+            DynamicValue::String(string) => return 1 + string.len(),
+            DynamicValue::Bytes(bytes) => return 1 + bytes.len(),
+        }
+    }
+
+    fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u8(self.get_tag()).unwrap();
+        match self {
+            DynamicValue::Bool(value) => out_bytes.write_u8(*value as u8).unwrap(),
+            DynamicValue::Int(value) => out_bytes.write_i64::<BigEndian>(*value).unwrap(),
+            DynamicValue::Float(value) => out_bytes.write_f64::<BigEndian>(*value).unwrap(),
+            DynamicValue::String(string) => write_short_bytes(out_bytes, string.as_bytes()),
+            DynamicValue::Bytes(bytes) => write_short_bytes(out_bytes, bytes),
+        }
+    }
+
+    fn read(cursor: &mut Cursor<&[u8]>) -> Option<Self> {
+        match cursor.read_u8().ok()? {
+            0 => match cursor.read_u8().ok()? {
+                0 => return Some(DynamicValue::Bool(false)),
+                1 => return Some(DynamicValue::Bool(true)),
+                _ => return None,
+            },
+            1 => return Some(DynamicValue::Int(cursor.read_i64::<BigEndian>().ok()?)),
+            2 => return Some(DynamicValue::Float(cursor.read_f64::<BigEndian>().ok()?)),
+            3 => {
+                let bytes = read_short_bytes(cursor)?;
+                return Some(DynamicValue::String(String::from_utf8(bytes).ok()?));
+            }
+            4 => return Some(DynamicValue::Bytes(read_short_bytes(cursor)?)),
+            _ => return None,
+        }
+    }
+}
+
+impl From<bool> for DynamicValue {
+    fn from(value: bool) -> Self {
+        return DynamicValue::Bool(value);
+    }
+}
+
+impl From<i64> for DynamicValue {
+    fn from(value: i64) -> Self {
+        return DynamicValue::Int(value);
+    }
+}
+
+impl From<i32> for DynamicValue {
+    fn from(value: i32) -> Self {
+        return DynamicValue::Int(value as i64);
+    }
+}
+
+impl From<f64> for DynamicValue {
+    fn from(value: f64) -> Self {
+        return DynamicValue::Float(value);
+    }
+}
+
+impl From<f32> for DynamicValue {
+    fn from(value: f32) -> Self {
+        return DynamicValue::Float(value as f64);
+    }
+}
+
+impl From<&str> for DynamicValue {
+    fn from(value: &str) -> Self {
+        return DynamicValue::String(value.to_string());
+    }
+}
+
+impl From<String> for DynamicValue {
+    fn from(value: String) -> Self {
+        return DynamicValue::String(value);
+    }
+}
+
+impl From<Vec<u8>> for DynamicValue {
+    fn from(value: Vec<u8>) -> Self {
+        return DynamicValue::Bytes(value);
+    }
+}
+
+/// A built-in Event carrying a kind & a small map of typed values, rather
+/// than Properties declared on a Rust struct, so that scripting layers & mods
+/// can send ad-hoc messages without new Event types being compiled in. Every
+/// value is checked as it's inserted, so that the Event never serializes to
+/// more than `MAX_EVENT_SIZE` bytes.
+///
+/// To be sent, the application's EventType enum needs a variant holding a
+/// DynamicEvent, & to implement `From<DynamicEvent>`, & the Event needs to be
+/// registered in the Manifest with `DynamicEvent::get_builder()`
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicEvent {
+    kind: String,
+    values: Vec<(String, DynamicValue)>,
+    guaranteed: bool,
+}
+
+impl DynamicEvent {
+    /// Creates a new, empty DynamicEvent of the given kind, such as "chat",
+    /// which is guaranteed for delivery. Returns an error if the kind is
+    /// empty or longer than `MAX_DYNAMIC_KEY_LENGTH` bytes
+    pub fn new(kind: &str) -> Result<Self, NaiaError> {
+        if !is_valid_key(kind) {
+            return Err(NaiaError::InvalidDynamicKey);
+        }
+        return Ok(DynamicEvent {
+            kind: kind.to_string(),
+            values: Vec::new(),
+            guaranteed: true,
+        });
+    }
+
+    /// Gets a builder for DynamicEvents, to register in the Manifest
+    pub fn get_builder<T: EventType + From<DynamicEvent> + 'static>() -> Box<dyn EventBuilder<T>> {
+        return Box::new(DynamicEventBuilder::<T> {
+            phantom: PhantomData,
+        });
+    }
+
+    /// Gets the kind of the Event
+    pub fn get_kind(&self) -> &str {
+        return &self.kind;
+    }
+
+    /// Sets whether the Event is guaranteed for eventual delivery to the
+    /// remote host, which it is by default
+    pub fn set_guaranteed(&mut self, guaranteed: bool) {
+        self.guaranteed = guaranteed;
+    }
+
+    /// Inserts a value under the given key, replacing any value the key
+    /// already has. Returns an error, leaving the Event as it was, if the key
+    /// is empty or longer than `MAX_DYNAMIC_KEY_LENGTH` bytes, if a String or
+    /// Bytes value is longer than 255 bytes, or if the Event would serialize
+    /// to more than `MAX_EVENT_SIZE` bytes
+    pub fn insert(&mut self, key: &str, value: impl Into<DynamicValue>) -> Result<(), NaiaError> {
+        if !is_valid_key(key) {
+            return Err(NaiaError::InvalidDynamicKey);
+        }
+        let value = value.into();
+        let value_length = match &value {
+            DynamicValue::String(string) => string.len(),
+            DynamicValue::Bytes(bytes) => bytes.len(),
+            _ => 0,
+        };
+        if value_length > u8::MAX as usize {
+            return Err(NaiaError::PayloadTooLarge(value_length));
+        }
+
+        let replaced_size = match self.get(key) {
+            Some(old_value) => 2 + key.len() + old_value.bytes_number(),
+            None => 0,
+        };
+        let size = self.bytes_number() - replaced_size + 2 + key.len() + value.bytes_number();
+        if size > MAX_EVENT_SIZE {
+            return Err(NaiaError::EventTooLarge(size));
+        }
+
+        match self.values.iter_mut().find(|(old_key, _)| old_key == key) {
+            Some((_, old_value)) => *old_value = value,
+            None => self.values.push((key.to_string(), value)),
+        }
+        return Ok(());
+    }
+
+    /// Removes the value under the given key, returning it
+    pub fn remove(&mut self, key: &str) -> Option<DynamicValue> {
+        let index = self.values.iter().position(|(old_key, _)| old_key == key)?;
+        return Some(self.values.remove(index).1);
+    }
+
+    /// Gets the value under the given key
+    pub fn get(&self, key: &str) -> Option<&DynamicValue> {
+        return self
+            .values
+            .iter()
+            .find(|(old_key, _)| old_key == key)
+            .map(|(_, value)| value);
+    }
+
+    /// Gets the value under the given key, if it's a Bool
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key) {
+            Some(DynamicValue::Bool(value)) => return Some(*value),
+            _ => return None,
+        }
+    }
+
+    /// Gets the value under the given key, if it's an Int
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        match self.get(key) {
+            Some(DynamicValue::Int(value)) => return Some(*value),
+            _ => return None,
+        }
+    }
+
+    /// Gets the value under the given key, if it's a Float
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        match self.get(key) {
+            Some(DynamicValue::Float(value)) => return Some(*value),
+            _ => return None,
+        }
+    }
+
+    /// Gets the value under the given key, if it's a String
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(DynamicValue::String(value)) => return Some(value),
+            _ => return None,
+        }
+    }
+
+    /// Gets the value under the given key, if it's Bytes
+    pub fn get_bytes(&self, key: &str) -> Option<&[u8]> {
+        match self.get(key) {
+            Some(DynamicValue::Bytes(value)) => return Some(value),
+            _ => return None,
+        }
+    }
+
+    /// Iterates over the keys & values of the Event, in the order they were
+    /// first inserted
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DynamicValue)> {
+        return self.values.iter().map(|(key, value)| (key.as_str(), value));
+    }
+
+    /// Gets the number of values the Event carries
+    pub fn len(&self) -> usize {
+        return self.values.len();
+    }
+
+    /// Returns whether the Event carries no values
+    pub fn is_empty(&self) -> bool {
+        return self.values.is_empty();
+    }
+
+    /// Gets the TypeId of DynamicEvent, the same for every EventType
+    pub fn get_type_id(&self) -> TypeId {
+        return TypeId::of::<DynamicEvent>();
+    }
+
+    /// Writes the Event into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        write_short_bytes(out_bytes, self.kind.as_bytes());
+        out_bytes.write_u8(self.values.len() as u8).unwrap();
+        for (key, value) in self.values.iter() {
+            write_short_bytes(out_bytes, key.as_bytes());
+            value.write(out_bytes);
+        }
+    }
+
+    /// Reads an Event from an incoming byte stream. Returns None if the
+    /// stream is malformed, or holds anything a DynamicEvent can't be
+    /// created with, such as a key which is too long, or repeated
+    pub fn read(in_bytes: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor::new(in_bytes);
+        let kind = read_key(&mut cursor)?;
+        let mut event = DynamicEvent::new(&kind).ok()?;
+        let value_count = cursor.read_u8().ok()?;
+        for _ in 0..value_count {
+            let key = read_key(&mut cursor)?;
+            let value = DynamicValue::read(&mut cursor)?;
+            if event.get(&key).is_some() {
+                return None;
+            }
+            event.insert(&key, value).ok()?;
+        }
+        if cursor.position() as usize != in_bytes.len() {
+            return None;
+        }
+        return Some(event);
+    }
+
+    // the number of bytes the Event is written as
+    fn bytes_number(&self) -> usize {
+        let values_size: usize = self
+            .values
+            .iter()
+            .map(|(key, value)| 2 + key.len() + value.bytes_number())
+            .sum();
+        return 2 + self.kind.len() + values_size;
+    }
+}
+
+impl<T: EventType + From<DynamicEvent>> Event<T> for DynamicEvent {
+    fn is_guaranteed(&self) -> bool {
+        return self.guaranteed;
+    }
+
+    fn write(&self, out_bytes: &mut Vec<u8>) {
+        DynamicEvent::write(self, out_bytes);
+    }
+
+    fn get_typed_copy(&self) -> T {
+        return T::from(self.clone());
+    }
+
+    fn get_type_id(&self) -> TypeId {
+        return TypeId::of::<DynamicEvent>();
+    }
+}
+
+/// Handles the creation of DynamicEvents, within the given EventType
+pub struct DynamicEventBuilder<T> {
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: EventType + From<DynamicEvent>> EventBuilder<T> for DynamicEventBuilder<T> {
+    fn get_type_id(&self) -> TypeId {
+        return TypeId::of::<DynamicEvent>();
+    }
+
+    /// A malformed payload is read as an empty DynamicEvent, with an empty
+    /// kind, which can't otherwise be created
+    fn build(&self, in_bytes: &[u8]) -> Option<T> {
+        let event = DynamicEvent::read(in_bytes).unwrap_or(DynamicEvent {
+            kind: String::new(),
+            values: Vec::new(),
+            guaranteed: true,
+        });
+        return Some(T::from(event));
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        return "DynamicEvent";
+    }
+
+    fn get_property_count(&self) -> u8 {
+        return 0;
+    }
+
+    fn get_properties(&self) -> Vec<PropertyDescriptor> {
+        return Vec::new();
+    }
+
+    fn get_version(&self) -> u8 {
+        return 0;
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    return !key.is_empty() && key.len() <= MAX_DYNAMIC_KEY_LENGTH;
+}
+
+fn write_short_bytes(out_bytes: &mut Vec<u8>, bytes: &[u8]) {
+    out_bytes.write_u8(bytes.len() as u8).unwrap();
+    out_bytes.extend_from_slice(bytes);
+}
+
+fn read_short_bytes(cursor: &mut Cursor<&[u8]>) -> Option<Vec<u8>> {
+    let length = cursor.read_u8().ok()? as usize;
+    let start = cursor.position() as usize;
+    let bytes = cursor.get_ref().get(start..start + length)?.to_vec();
+    cursor.set_position((start + length) as u64);
+    return Some(bytes);
+}
+
+fn read_key(cursor: &mut Cursor<&[u8]>) -> Option<String> {
+    return String::from_utf8(read_short_bytes(cursor)?).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::TypeId, cell::RefCell, rc::Rc};
+
+    use crate::{
+        DynamicEvent, Entity, EntityType, Event, EventType, Manifest, NaiaError, StateMask,
+        MAX_EVENT_SIZE,
+    };
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum TestEvents {
+        DynamicEvent(DynamicEvent),
+    }
+
+    impl From<DynamicEvent> for TestEvents {
+        fn from(event: DynamicEvent) -> Self {
+            return TestEvents::DynamicEvent(event);
+        }
+    }
+
+    impl EventType for TestEvents {
+        fn write(&mut self, buffer: &mut Vec<u8>) {
+            match self {
+                TestEvents::DynamicEvent(event) => event.write(buffer),
+            }
+        }
+
+        fn get_type_id(&self) -> TypeId {
+            match self {
+                TestEvents::DynamicEvent(event) => return event.get_type_id(),
+            }
+        }
+
+        fn get_boxed_event(&self) -> Box<dyn Event<Self>> {
+            match self {
+                TestEvents::DynamicEvent(event) => return Box::new(event.clone()),
+            }
+        }
+    }
+
+    enum NoEntities {}
+
+    impl EntityType for NoEntities {
+        fn read_partial(&mut self, _: &StateMask, _: &[u8]) -> bool {
+            match *self {}
+        }
+
+        fn get_type_id(&self) -> TypeId {
+            match *self {}
+        }
+
+        fn get_copy(&self) -> Self {
+            match *self {}
+        }
+
+        fn get_entity_ref(&self) -> Rc<RefCell<dyn Entity<Self>>> {
+            match *self {}
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut manifest = Manifest::<TestEvents, NoEntities>::new();
+        manifest.register_event(DynamicEvent::get_builder());
+
+        let mut event = DynamicEvent::new("chat").unwrap();
+        event.insert("text", "hello").unwrap();
+        event.insert("channel", 3).unwrap();
+        event.insert("loud", true).unwrap();
+        event.insert("volume", 0.5).unwrap();
+        event.insert("attachment", vec![1, 2, 3]).unwrap();
+        event.insert("channel", 4).unwrap();
+        assert!(manifest.validate_event(&event).is_ok());
+
+        let naia_id = manifest.get_event_naia_id(&TypeId::of::<DynamicEvent>());
+        let payload = manifest.write_event_payload(naia_id, &event);
+        let TestEvents::DynamicEvent(received) = manifest.read_event(naia_id, &payload).unwrap();
+        assert!(received == event);
+        assert!(received.get_kind() == "chat");
+        assert!(received.get_int("channel") == Some(4));
+        assert!(received.get_str("text") == Some("hello"));
+        assert!(received.get_float("text").is_none());
+        assert!(received.len() == 5);
+    }
+
+    #[test]
+    fn bounded_size() {
+        assert!(DynamicEvent::new("").is_err());
+        let mut event = DynamicEvent::new("mod_message").unwrap();
+        assert!(matches!(
+            event.insert(&"k".repeat(33), 1),
+            Err(NaiaError::InvalidDynamicKey)
+        ));
+        assert!(event.insert("blob", vec![0; 256]).is_err());
+        event.insert("blob", vec![0; 200]).unwrap();
+        assert!(matches!(
+            event.insert("more", vec![0; 100]),
+            Err(NaiaError::EventTooLarge(_))
+        ));
+        assert!(event.get("more").is_none());
+
+        let mut bytes = Vec::new();
+        event.write(&mut bytes);
+        assert!(bytes.len() <= MAX_EVENT_SIZE);
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        let mut event = DynamicEvent::new("chat").unwrap();
+        event.insert("text", "hello").unwrap();
+        let mut bytes = Vec::new();
+        event.write(&mut bytes);
+        assert!(DynamicEvent::read(&bytes) == Some(event.clone()));
+
+        // truncated, with trailing bytes, & with an unknown type of value
+        assert!(DynamicEvent::read(&bytes[..bytes.len() - 1]).is_none());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(DynamicEvent::read(&trailing).is_none());
+        let mut unknown = bytes.clone();
+        unknown[11] = 9;
+        assert!(DynamicEvent::read(&unknown).is_none());
+
+        // a key repeated
+        let mut repeated = bytes.clone();
+        repeated[5] = 2;
+        repeated.extend_from_slice(&bytes[6..]);
+        assert!(DynamicEvent::read(&repeated).is_none());
+
+        let mut manifest = Manifest::<TestEvents, NoEntities>::new();
+        manifest.register_event(DynamicEvent::get_builder());
+        let TestEvents::DynamicEvent(received) = manifest.create_event(0, &trailing).unwrap();
+        assert!(received.get_kind().is_empty());
+        assert!(received.get("text").is_none());
+    }
+}
@@ -5,6 +5,10 @@ use std::{
 
 use super::event_type::EventType;
 
+/// Identifies an Event which has been queued for transmission, so that the
+/// application can be notified once it has been delivered
+pub type EventId = u16;
+
 /// An Event is a struct of data that can be sent and recreated on the connected
 /// remote host
 pub trait Event<T: EventType>: EventClone<T> {
@@ -17,6 +21,17 @@ pub trait Event<T: EventType>: EventClone<T> {
     fn get_typed_copy(&self) -> T;
     /// Gets the TypeId of the Event
     fn get_type_id(&self) -> TypeId;
+    /// Whether a later Event of the same type may be merged into this Event
+    /// with `merge()`, while this Event is queued & hasn't been sent yet
+    fn is_mergeable(&self) -> bool {
+        return false;
+    }
+    /// Merges a later Event of the same type, queued for the same remote host,
+    /// into this Event, so that only this Event is sent. Returns false if the
+    /// Events could not be merged, in which case both are sent
+    fn merge(&mut self, _later: &T) -> bool {
+        return false;
+    }
 }
 
 /// A Boxed Event must be able to clone itself
@@ -3,14 +3,26 @@ use std::{
     fmt::{Debug, Formatter, Result},
 };
 
+use crate::manifest_descriptor::PropertyDescriptor;
+
 use super::event_type::EventType;
 
 /// Handles the creation of new Events
 pub trait EventBuilder<T: EventType> {
     /// Gets the TypeId of the Event it is able to build
     fn get_type_id(&self) -> TypeId;
-    /// Creates a new Event
-    fn build(&self, in_bytes: &[u8]) -> T;
+    /// Creates a new Event. Returns None if a Property of it could not be read
+    fn build(&self, in_bytes: &[u8]) -> Option<T>;
+    /// Gets the name of the Event type, used to describe the Manifest
+    fn get_type_name(&self) -> &'static str;
+    /// Gets the number of Properties the Event has
+    fn get_property_count(&self) -> u8;
+    /// Gets a description of each Property of the Event, in the order they are
+    /// written
+    fn get_properties(&self) -> Vec<PropertyDescriptor>;
+    /// Gets the version of the Event type, which is incremented as Properties
+    /// are appended to it
+    fn get_version(&self) -> u8;
 }
 
 impl<T: EventType> Debug for Box<dyn EventBuilder<T>> {
@@ -1,54 +1,308 @@
 use byteorder::{BigEndian, ReadBytesExt};
 use std::{
+    any::TypeId,
     collections::{HashMap, VecDeque},
+    io::Cursor,
     rc::Rc,
+    time::Duration,
     vec::Vec,
 };
 
 use crate::{
-    entities::entity_type::EntityType,
+    client_id::ClientId,
+    client_roster::ClientRosterChange,
+    connection_stats::{DropLog, DropReason},
+    entities::{entity_type::EntityType, local_entity_key::LocalEntityKey},
     events::{
-        event::{Event, EventClone},
+        command_stats::CommandStats,
+        event::{Event, EventClone, EventId},
+        event_queue_stats::EventQueueStats,
         event_type::EventType,
+        outgoing_queue::OutgoingQueue,
+        rpc_message::{RequestId, RpcMessage},
     },
+    instant::Instant,
+    manager_type::ManagerType,
     manifest::Manifest,
+    manifest_descriptor::ManifestDescriptor,
     packet_reader::PacketReader,
+    sequence_buffer::{sequence_greater_than, SequenceBuffer},
+    tick_rate_change::TickRateChange,
+    utils::read_string,
 };
 
+/// The number of most recently received EventIds which are remembered in
+/// order to discard Events which have been delivered more than once
+const RECEIVED_EVENT_IDS_SIZE: u16 = 8192;
+
+/// The number of most recently received RequestIds which are remembered, for
+/// requests & for responses separately, in order to discard RPC messages which
+/// have been delivered more than once
+const RECEIVED_REQUEST_IDS_SIZE: u16 = 8192;
+
+/// The number of most recently received command ticks which are remembered in
+/// order to discard the redundant copies of commands
+const RECEIVED_COMMAND_TICKS_SIZE: usize = 64;
+
 /// Handles incoming/outgoing events, tracks the delivery status of Events so
-/// that guaranteed Events can be re-transmitted to the remote host
+/// that guaranteed Events can be re-transmitted to the remote host. RPC
+/// messages and Events scheduled for a specific tick are handled here as well,
+/// and are always guaranteed, as are changes to the set of connected Clients.
+/// Events targeted at an Entity, and Events relayed between Clients, are
+/// retransmitted if they are guaranteed themselves. Per-tick commands are not
+/// guaranteed, but each
+/// is sent redundantly in every packet until one carrying it is acknowledged.
 #[derive(Debug)]
 pub struct EventManager<T: EventType> {
-    queued_outgoing_events: VecDeque<Rc<Box<dyn Event<T>>>>,
+    queued_outgoing_events: OutgoingQueue<(EventId, Rc<Box<dyn Event<T>>>)>,
     queued_incoming_events: VecDeque<T>,
-    sent_events: HashMap<u16, Vec<Rc<Box<dyn Event<T>>>>>,
+    received_event_ids: SequenceBuffer<bool>,
+    received_request_ids: SequenceBuffer<bool>,
+    received_response_ids: SequenceBuffer<bool>,
+    unacked_events: HashMap<EventId, UnackedEvent<T>>,
+    sent_event_ids: HashMap<u16, Vec<EventId>>,
+    delivered_event_ids: VecDeque<EventId>,
+    event_expirations: HashMap<EventId, (Instant, Duration)>,
+    expired_event_ids: VecDeque<EventId>,
+    next_event_id: EventId,
+    queued_outgoing_rpcs: OutgoingQueue<RpcMessage<Rc<Box<dyn Event<T>>>>>,
+    queued_incoming_rpcs: VecDeque<RpcMessage<T>>,
+    sent_rpcs: HashMap<u16, Vec<RpcMessage<Rc<Box<dyn Event<T>>>>>>,
+    queued_outgoing_timed_events: OutgoingQueue<(EventId, u16, Rc<Box<dyn Event<T>>>)>,
+    queued_incoming_timed_events: VecDeque<(u16, i16, T)>,
+    sent_timed_events: HashMap<u16, Vec<(EventId, u16, Rc<Box<dyn Event<T>>>)>>,
+    queued_outgoing_entity_events: OutgoingQueue<(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>)>,
+    queued_incoming_entity_events: VecDeque<(LocalEntityKey, T)>,
+    sent_entity_events: HashMap<u16, Vec<(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>)>>,
+    queued_outgoing_relay_events: OutgoingQueue<(EventId, ClientId, Rc<Box<dyn Event<T>>>)>,
+    queued_incoming_relay_events: VecDeque<(ClientId, T)>,
+    sent_relay_events: HashMap<u16, Vec<(EventId, ClientId, Rc<Box<dyn Event<T>>>)>>,
+    queued_outgoing_roster_changes: OutgoingQueue<(u32, ClientRosterChange)>,
+    queued_incoming_roster_changes: VecDeque<(u32, ClientRosterChange)>,
+    sent_roster_changes: HashMap<u16, Vec<(u32, ClientRosterChange)>>,
+    queued_outgoing_manifest_deltas: OutgoingQueue<(u32, ManifestDescriptor)>,
+    queued_incoming_manifest_deltas: VecDeque<(u32, ManifestDescriptor)>,
+    sent_manifest_deltas: HashMap<u16, Vec<(u32, ManifestDescriptor)>>,
+    queued_outgoing_shutdowns: OutgoingQueue<String>,
+    queued_incoming_shutdowns: VecDeque<String>,
+    sent_shutdowns: HashMap<u16, Vec<String>>,
+    queued_outgoing_tick_rate_changes: OutgoingQueue<TickRateChange>,
+    queued_incoming_tick_rate_changes: VecDeque<TickRateChange>,
+    sent_tick_rate_changes: HashMap<u16, Vec<TickRateChange>>,
+    command_redundancy: usize,
+    unacked_outgoing_commands: VecDeque<(u16, Rc<Box<dyn Event<T>>>)>,
+    has_new_outgoing_command: bool,
+    sent_commands: HashMap<u16, u16>,
+    queued_incoming_commands: VecDeque<(u16, T)>,
+    received_command_ticks: VecDeque<u16>,
+    command_stats: CommandStats,
+    retransmissions: HashMap<ManagerType, u64>,
+    type_retransmissions: HashMap<TypeId, u64>,
 }
 
 impl<T: EventType> EventManager<T> {
-    /// Creates a new EventManager
-    pub fn new() -> Self {
+    /// Creates a new EventManager, given the number of most recent
+    /// unacknowledged commands to send in every packet
+    pub fn new(command_redundancy: u8) -> Self {
         EventManager {
-            queued_outgoing_events: VecDeque::new(),
+            queued_outgoing_events: OutgoingQueue::new(),
             queued_incoming_events: VecDeque::new(),
-            sent_events: HashMap::new(),
+            received_event_ids: SequenceBuffer::with_capacity(RECEIVED_EVENT_IDS_SIZE),
+            received_request_ids: SequenceBuffer::with_capacity(RECEIVED_REQUEST_IDS_SIZE),
+            received_response_ids: SequenceBuffer::with_capacity(RECEIVED_REQUEST_IDS_SIZE),
+            unacked_events: HashMap::new(),
+            sent_event_ids: HashMap::new(),
+            delivered_event_ids: VecDeque::new(),
+            event_expirations: HashMap::new(),
+            expired_event_ids: VecDeque::new(),
+            next_event_id: 0,
+            queued_outgoing_rpcs: OutgoingQueue::new(),
+            queued_incoming_rpcs: VecDeque::new(),
+            sent_rpcs: HashMap::new(),
+            queued_outgoing_timed_events: OutgoingQueue::new(),
+            queued_incoming_timed_events: VecDeque::new(),
+            sent_timed_events: HashMap::new(),
+            queued_outgoing_entity_events: OutgoingQueue::new(),
+            queued_incoming_entity_events: VecDeque::new(),
+            sent_entity_events: HashMap::new(),
+            queued_outgoing_relay_events: OutgoingQueue::new(),
+            queued_incoming_relay_events: VecDeque::new(),
+            sent_relay_events: HashMap::new(),
+            queued_outgoing_roster_changes: OutgoingQueue::new(),
+            queued_incoming_roster_changes: VecDeque::new(),
+            sent_roster_changes: HashMap::new(),
+            queued_outgoing_manifest_deltas: OutgoingQueue::new(),
+            queued_incoming_manifest_deltas: VecDeque::new(),
+            sent_manifest_deltas: HashMap::new(),
+            queued_outgoing_shutdowns: OutgoingQueue::new(),
+            queued_incoming_shutdowns: VecDeque::new(),
+            sent_shutdowns: HashMap::new(),
+            queued_outgoing_tick_rate_changes: OutgoingQueue::new(),
+            queued_incoming_tick_rate_changes: VecDeque::new(),
+            sent_tick_rate_changes: HashMap::new(),
+            command_redundancy: (command_redundancy as usize).max(1),
+            unacked_outgoing_commands: VecDeque::new(),
+            has_new_outgoing_command: false,
+            sent_commands: HashMap::new(),
+            queued_incoming_commands: VecDeque::new(),
+            received_command_ticks: VecDeque::new(),
+            command_stats: CommandStats::default(),
+            retransmissions: HashMap::new(),
+            type_retransmissions: HashMap::new(),
         }
     }
 
     /// Occurs when a packet has been notified as delivered. Stops tracking the
-    /// status of Events in that packet.
+    /// status of Events in that packet, and records their delivery receipts.
+    /// An Event which was already delivered by another packet is ignored, so
+    /// its receipt is only recorded once
     pub fn notify_packet_delivered(&mut self, packet_index: u16) {
-        self.sent_events.remove(&packet_index);
+        if let Some(delivered_ids_list) = self.sent_event_ids.remove(&packet_index) {
+            for event_id in delivered_ids_list {
+                if self.unacked_events.remove(&event_id).is_some() {
+                    self.event_expirations.remove(&event_id);
+                    self.delivered_event_ids.push_back(event_id);
+                }
+            }
+        }
+        self.sent_rpcs.remove(&packet_index);
+        self.sent_timed_events.remove(&packet_index);
+        self.sent_entity_events.remove(&packet_index);
+        self.sent_relay_events.remove(&packet_index);
+        self.sent_roster_changes.remove(&packet_index);
+        self.sent_manifest_deltas.remove(&packet_index);
+        self.sent_shutdowns.remove(&packet_index);
+        self.sent_tick_rate_changes.remove(&packet_index);
+
+        // every command up to the newest in the packet has either arrived, or
+        // is too old to be sent again
+        if let Some(newest_tick) = self.sent_commands.remove(&packet_index) {
+            self.unacked_outgoing_commands
+                .retain(|(tick, _)| sequence_greater_than(*tick, newest_tick));
+        }
     }
 
     /// Occurs when a packet has been notified as having been dropped. Queues up
-    /// any guaranteed Events that were lost in the packet for retransmission.
+    /// the guaranteed Events that were lost in the packet for retransmission,
+    /// leaving out any which have already been delivered, or are still in
+    /// flight, in another packet
     pub fn notify_packet_dropped(&mut self, packet_index: u16) {
-        if let Some(dropped_events_list) = self.sent_events.get(&packet_index) {
-            for dropped_event in dropped_events_list.into_iter() {
-                self.queued_outgoing_events.push_back(dropped_event.clone());
+        self.sent_commands.remove(&packet_index);
+
+        if let Some(dropped_ids_list) = self.sent_event_ids.remove(&packet_index) {
+            for event_id in dropped_ids_list {
+                let unacked_event = match self.unacked_events.get_mut(&event_id) {
+                    Some(unacked_event) => unacked_event,
+                    None => continue,
+                };
+                unacked_event.packets_in_flight -= 1;
+                if unacked_event.packets_in_flight > 0 {
+                    continue;
+                }
+                if Event::is_guaranteed(unacked_event.event.as_ref().as_ref()) {
+                    let event = unacked_event.event.clone();
+                    Self::record_retransmission(
+                        &mut self.retransmissions,
+                        &mut self.type_retransmissions,
+                        ManagerType::Event,
+                        Some(&event),
+                    );
+                    self.queued_outgoing_events.push_back((event_id, event));
+                } else {
+                    self.unacked_events.remove(&event_id);
+                    self.event_expirations.remove(&event_id);
+                }
+            }
+        }
+
+        if let Some(dropped_rpcs_list) = self.sent_rpcs.remove(&packet_index) {
+            for dropped_rpc in dropped_rpcs_list.into_iter() {
+                Self::record_retransmission(
+                    &mut self.retransmissions,
+                    &mut self.type_retransmissions,
+                    ManagerType::Rpc,
+                    dropped_rpc.payload(),
+                );
+                self.queued_outgoing_rpcs.push_back(dropped_rpc);
+            }
+        }
+
+        if let Some(dropped_timed_events_list) = self.sent_timed_events.remove(&packet_index) {
+            for dropped_timed_event in dropped_timed_events_list.into_iter() {
+                Self::record_retransmission(
+                    &mut self.retransmissions,
+                    &mut self.type_retransmissions,
+                    ManagerType::TimedEvent,
+                    Some(&dropped_timed_event.2),
+                );
+                self.queued_outgoing_timed_events
+                    .push_back(dropped_timed_event);
+            }
+        }
+
+        if let Some(dropped_entity_events_list) = self.sent_entity_events.remove(&packet_index) {
+            for dropped_entity_event in dropped_entity_events_list.into_iter() {
+                if Event::is_guaranteed(dropped_entity_event.2.as_ref().as_ref()) {
+                    Self::record_retransmission(
+                        &mut self.retransmissions,
+                        &mut self.type_retransmissions,
+                        ManagerType::EntityEvent,
+                        Some(&dropped_entity_event.2),
+                    );
+                    self.queued_outgoing_entity_events
+                        .push_back(dropped_entity_event);
+                }
+            }
+        }
+
+        if let Some(dropped_relay_events_list) = self.sent_relay_events.remove(&packet_index) {
+            for dropped_relay_event in dropped_relay_events_list.into_iter() {
+                if Event::is_guaranteed(dropped_relay_event.2.as_ref().as_ref()) {
+                    Self::record_retransmission(
+                        &mut self.retransmissions,
+                        &mut self.type_retransmissions,
+                        ManagerType::RelayEvent,
+                        Some(&dropped_relay_event.2),
+                    );
+                    self.queued_outgoing_relay_events
+                        .push_back(dropped_relay_event);
+                }
             }
+        }
+
+        if let Some(dropped_roster_changes) = self.sent_roster_changes.remove(&packet_index) {
+            *self
+                .retransmissions
+                .entry(ManagerType::ClientRoster)
+                .or_default() += dropped_roster_changes.len() as u64;
+            self.queued_outgoing_roster_changes
+                .extend(dropped_roster_changes);
+        }
+
+        if let Some(dropped_manifest_deltas) = self.sent_manifest_deltas.remove(&packet_index) {
+            *self
+                .retransmissions
+                .entry(ManagerType::ManifestDelta)
+                .or_default() += dropped_manifest_deltas.len() as u64;
+            self.queued_outgoing_manifest_deltas
+                .extend(dropped_manifest_deltas);
+        }
+
+        if let Some(dropped_shutdowns) = self.sent_shutdowns.remove(&packet_index) {
+            *self
+                .retransmissions
+                .entry(ManagerType::Shutdown)
+                .or_default() += dropped_shutdowns.len() as u64;
+            self.queued_outgoing_shutdowns.extend(dropped_shutdowns);
+        }
 
-            self.sent_events.remove(&packet_index);
+        if let Some(dropped_changes) = self.sent_tick_rate_changes.remove(&packet_index) {
+            *self
+                .retransmissions
+                .entry(ManagerType::TickRate)
+                .or_default() += dropped_changes.len() as u64;
+            self.queued_outgoing_tick_rate_changes
+                .extend(dropped_changes);
         }
     }
 
@@ -58,23 +312,40 @@ impl<T: EventType> EventManager<T> {
         return self.queued_outgoing_events.len() != 0;
     }
 
-    /// Gets the next queued Event to be transmitted
-    pub fn pop_outgoing_event(&mut self, packet_index: u16) -> Option<Rc<Box<dyn Event<T>>>> {
+    /// Gets the next queued Event to be transmitted, along with its EventId.
+    /// Queued Events whose time-to-live has run out are dropped on the way,
+    /// and their EventIds given back via `pop_expired_event_id()`
+    pub fn pop_outgoing_event(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(EventId, Rc<Box<dyn Event<T>>>)> {
+        while let Some((event_id, _)) = self.queued_outgoing_events.front() {
+            let event_id = *event_id;
+            if !self.is_expired(&event_id) {
+                break;
+            }
+            self.queued_outgoing_events.pop_front();
+            self.unacked_events.remove(&event_id);
+            self.event_expirations.remove(&event_id);
+            self.expired_event_ids.push_back(event_id);
+        }
         match self.queued_outgoing_events.pop_front() {
-            Some(event) => {
-                //place in transmission record if this is a gauranteed event
-                if Event::is_guaranteed(event.as_ref().as_ref()) {
-                    if !self.sent_events.contains_key(&packet_index) {
-                        let sent_events_list: Vec<Rc<Box<dyn Event<T>>>> = Vec::new();
-                        self.sent_events.insert(packet_index, sent_events_list);
-                    }
+            Some((event_id, event)) => {
+                //place in transmission record, to be able to retransmit a
+                //guaranteed event, and give a receipt once it is delivered
+                self.unacked_events
+                    .entry(event_id)
+                    .or_insert_with(|| UnackedEvent {
+                        event: event.clone(),
+                        packets_in_flight: 0,
+                    })
+                    .packets_in_flight += 1;
+                self.sent_event_ids
+                    .entry(packet_index)
+                    .or_default()
+                    .push(event_id);
 
-                    if let Some(sent_events_list) = self.sent_events.get_mut(&packet_index) {
-                        sent_events_list.push(event.clone());
-                    }
-                }
-
-                Some(event)
+                Some((event_id, event))
             }
             None => None,
         }
@@ -82,25 +353,457 @@ impl<T: EventType> EventManager<T> {
 
     /// If  the last popped Event from the queue somehow wasn't able to be
     /// written into a packet, put the Event back into the front of the queue
-    pub fn unpop_outgoing_event(&mut self, packet_index: u16, event: &Rc<Box<dyn Event<T>>>) {
+    pub fn unpop_outgoing_event(
+        &mut self,
+        packet_index: u16,
+        event: &(EventId, Rc<Box<dyn Event<T>>>),
+    ) {
         let cloned_event = event.clone();
 
-        if Event::is_guaranteed(event.as_ref().as_ref()) {
-            if let Some(sent_events_list) = self.sent_events.get_mut(&packet_index) {
-                sent_events_list.pop();
-                if sent_events_list.len() == 0 {
-                    self.sent_events.remove(&packet_index);
-                }
+        if let Some(unacked_event) = self.unacked_events.get_mut(&event.0) {
+            unacked_event.packets_in_flight -= 1;
+        }
+
+        if let Some(sent_ids_list) = self.sent_event_ids.get_mut(&packet_index) {
+            sent_ids_list.pop();
+            if sent_ids_list.is_empty() {
+                self.sent_event_ids.remove(&packet_index);
             }
         }
 
         self.queued_outgoing_events.push_front(cloned_event);
     }
 
-    /// Queues an Event to be transmitted to the remote host
-    pub fn queue_outgoing_event(&mut self, event: &impl Event<T>) {
+    /// Queues an Event to be transmitted to the remote host, returning an
+    /// EventId which will be given back via `pop_delivered_event_id()` once the
+    /// Event has been delivered. A mergeable Event is merged into the most
+    /// recently queued Event of the same type, if that Event hasn't been sent
+    /// yet, in which case the EventId of that Event is returned
+    pub fn queue_outgoing_event(&mut self, event: &dyn Event<T>) -> EventId {
+        if let Some(event_id) = self.merge_outgoing_event(event) {
+            return event_id;
+        }
         let clone = Rc::new(EventClone::clone_box(event));
-        self.queued_outgoing_events.push_back(clone);
+        let event_id = self.get_next_event_id();
+        self.queued_outgoing_events.push_back((event_id, clone));
+        return event_id;
+    }
+
+    /// Queues an Event to be transmitted to the remote host, like
+    /// `queue_outgoing_event()`, but drops it if it hasn't been sent within the
+    /// given time-to-live. A guaranteed Event whose packet is lost is likewise
+    /// not resent after its time-to-live
+    pub fn queue_outgoing_event_with_ttl(
+        &mut self,
+        event: &dyn Event<T>,
+        ttl: Duration,
+    ) -> EventId {
+        let event_id = self.queue_outgoing_event(event);
+        self.event_expirations
+            .insert(event_id, (Instant::now(), ttl));
+        return event_id;
+    }
+
+    /// Queues an already boxed Event to be transmitted to the remote host
+    pub fn queue_outgoing_boxed_event(&mut self, event: Box<dyn Event<T>>) -> EventId {
+        let event_id = self.get_next_event_id();
+        self.queued_outgoing_events
+            .push_back((event_id, Rc::new(event)));
+        return event_id;
+    }
+
+    /// Gets every guaranteed Event which has yet to be delivered to the remote
+    /// host, whether it has been sent already or not, along with the EventId
+    /// the next queued Event will be given. Used to carry the Events over to a
+    /// connection with another host
+    pub fn get_undelivered_events(&self) -> (EventId, Vec<(EventId, Rc<Box<dyn Event<T>>>)>) {
+        // an Event which has been sent is either in flight, or queued again
+        let mut undelivered_events = Vec::new();
+        for (event_id, unacked_event) in self.unacked_events.iter() {
+            if unacked_event.packets_in_flight > 0
+                && Event::is_guaranteed(unacked_event.event.as_ref().as_ref())
+            {
+                undelivered_events.push((*event_id, unacked_event.event.clone()));
+            }
+        }
+        for (event_id, event) in self.queued_outgoing_events.iter() {
+            if Event::is_guaranteed(event.as_ref().as_ref()) {
+                undelivered_events.push((*event_id, event.clone()));
+            }
+        }
+        // keep the Events in the order they were originally queued
+        let next_event_id = self.next_event_id;
+        undelivered_events
+            .sort_by_key(|(event_id, _)| std::cmp::Reverse(next_event_id.wrapping_sub(*event_id)));
+        return (self.next_event_id, undelivered_events);
+    }
+
+    /// Queues up Events which were undelivered on a connection with another
+    /// host, keeping their EventIds, and continuing on from that connection's
+    /// next EventId
+    pub fn restore_undelivered_events(
+        &mut self,
+        next_event_id: EventId,
+        events: Vec<(EventId, Box<dyn Event<T>>)>,
+    ) {
+        self.next_event_id = next_event_id;
+        for (event_id, event) in events {
+            self.queued_outgoing_events
+                .push_back((event_id, Rc::new(event)));
+        }
+    }
+
+    /// Takes over the record of which Events & RPC messages have been received
+    /// from the EventManager of a previous connection, so that those it
+    /// received aren't emitted again when they're carried over to this
+    /// connection
+    pub fn carry_over_received_event_ids(&mut self, previous: &mut EventManager<T>) {
+        std::mem::swap(
+            &mut self.received_event_ids,
+            &mut previous.received_event_ids,
+        );
+        std::mem::swap(
+            &mut self.received_request_ids,
+            &mut previous.received_request_ids,
+        );
+        std::mem::swap(
+            &mut self.received_response_ids,
+            &mut previous.received_response_ids,
+        );
+    }
+
+    /// Gets the number of Events, RPC messages, timed Events, Entity Events,
+    /// relayed Events, roster changes, Manifest deltas, shutdown notices &
+    /// tick rate changes which are queued, or have been sent without yet
+    /// being acknowledged
+    pub fn get_undelivered_count(&self) -> usize {
+        let sent_rpc_count: usize = self.sent_rpcs.values().map(|rpcs| rpcs.len()).sum();
+        let sent_timed_event_count: usize = self
+            .sent_timed_events
+            .values()
+            .map(|timed_events| timed_events.len())
+            .sum();
+        let sent_entity_event_count: usize = self
+            .sent_entity_events
+            .values()
+            .map(|entity_events| entity_events.len())
+            .sum();
+        let sent_relay_event_count: usize = self
+            .sent_relay_events
+            .values()
+            .map(|relay_events| relay_events.len())
+            .sum();
+        let sent_roster_change_count: usize = self
+            .sent_roster_changes
+            .values()
+            .map(|roster_changes| roster_changes.len())
+            .sum();
+        let sent_manifest_delta_count: usize = self
+            .sent_manifest_deltas
+            .values()
+            .map(|manifest_deltas| manifest_deltas.len())
+            .sum();
+        let sent_shutdown_count: usize = self
+            .sent_shutdowns
+            .values()
+            .map(|shutdowns| shutdowns.len())
+            .sum();
+        let sent_tick_rate_change_count: usize = self
+            .sent_tick_rate_changes
+            .values()
+            .map(|changes| changes.len())
+            .sum();
+        // an Event queued again after being lost is still tracked as unacked
+        let unsent_event_count = self
+            .queued_outgoing_events
+            .iter()
+            .filter(|(event_id, _)| !self.unacked_events.contains_key(event_id))
+            .count();
+        return unsent_event_count
+            + self.unacked_events.len()
+            + self.queued_outgoing_rpcs.len()
+            + sent_rpc_count
+            + self.queued_outgoing_timed_events.len()
+            + sent_timed_event_count
+            + self.queued_outgoing_entity_events.len()
+            + sent_entity_event_count
+            + self.queued_outgoing_relay_events.len()
+            + sent_relay_event_count
+            + self.queued_outgoing_roster_changes.len()
+            + sent_roster_change_count
+            + self.queued_outgoing_manifest_deltas.len()
+            + sent_manifest_delta_count
+            + self.queued_outgoing_shutdowns.len()
+            + sent_shutdown_count
+            + self.queued_outgoing_tick_rate_changes.len()
+            + sent_tick_rate_change_count;
+    }
+
+    /// Gets statistics about the outgoing messages of the given kind which
+    /// have yet to be delivered. Only the kinds handled by the EventManager
+    /// which are retransmitted when lost are tracked: Events, RPC messages,
+    /// timed Events, Entity Events, relayed Events, roster changes, Manifest
+    /// deltas, shutdown notices & tick rate changes
+    pub fn get_queue_stats(&self, manager_type: ManagerType) -> EventQueueStats {
+        let (queued, in_flight, oldest_queued_age) = match manager_type {
+            ManagerType::Event => (
+                self.queued_outgoing_events.len(),
+                self.unacked_events
+                    .values()
+                    .filter(|unacked_event| unacked_event.packets_in_flight > 0)
+                    .count(),
+                self.queued_outgoing_events.get_oldest_age(|_| true),
+            ),
+            ManagerType::Rpc => (
+                self.queued_outgoing_rpcs.len(),
+                self.sent_rpcs.values().map(|rpcs| rpcs.len()).sum(),
+                self.queued_outgoing_rpcs.get_oldest_age(|_| true),
+            ),
+            ManagerType::TimedEvent => (
+                self.queued_outgoing_timed_events.len(),
+                self.sent_timed_events
+                    .values()
+                    .map(|timed_events| timed_events.len())
+                    .sum(),
+                self.queued_outgoing_timed_events.get_oldest_age(|_| true),
+            ),
+            ManagerType::EntityEvent => (
+                self.queued_outgoing_entity_events.len(),
+                self.sent_entity_events
+                    .values()
+                    .map(|entity_events| entity_events.len())
+                    .sum(),
+                self.queued_outgoing_entity_events.get_oldest_age(|_| true),
+            ),
+            ManagerType::RelayEvent => (
+                self.queued_outgoing_relay_events.len(),
+                self.sent_relay_events
+                    .values()
+                    .map(|relay_events| relay_events.len())
+                    .sum(),
+                self.queued_outgoing_relay_events.get_oldest_age(|_| true),
+            ),
+            ManagerType::ClientRoster => (
+                self.queued_outgoing_roster_changes.len(),
+                self.sent_roster_changes
+                    .values()
+                    .map(|roster_changes| roster_changes.len())
+                    .sum(),
+                self.queued_outgoing_roster_changes.get_oldest_age(|_| true),
+            ),
+            ManagerType::ManifestDelta => (
+                self.queued_outgoing_manifest_deltas.len(),
+                self.sent_manifest_deltas
+                    .values()
+                    .map(|manifest_deltas| manifest_deltas.len())
+                    .sum(),
+                self.queued_outgoing_manifest_deltas
+                    .get_oldest_age(|_| true),
+            ),
+            ManagerType::Shutdown => (
+                self.queued_outgoing_shutdowns.len(),
+                self.sent_shutdowns
+                    .values()
+                    .map(|shutdowns| shutdowns.len())
+                    .sum(),
+                self.queued_outgoing_shutdowns.get_oldest_age(|_| true),
+            ),
+            ManagerType::TickRate => (
+                self.queued_outgoing_tick_rate_changes.len(),
+                self.sent_tick_rate_changes
+                    .values()
+                    .map(|changes| changes.len())
+                    .sum(),
+                self.queued_outgoing_tick_rate_changes
+                    .get_oldest_age(|_| true),
+            ),
+            _ => return EventQueueStats::default(),
+        };
+        return EventQueueStats {
+            queued,
+            in_flight,
+            oldest_queued_age,
+            retransmissions: self
+                .retransmissions
+                .get(&manager_type)
+                .copied()
+                .unwrap_or(0),
+        };
+    }
+
+    /// Gets statistics about the outgoing Events of the type with the given
+    /// TypeId which have yet to be delivered, whether sent as Events, RPC
+    /// messages, timed Events, Entity Events or relayed Events
+    pub fn get_event_type_queue_stats(&self, type_id: TypeId) -> EventQueueStats {
+        let is_of_type =
+            |event: &Rc<Box<dyn Event<T>>>| Event::get_type_id(event.as_ref().as_ref()) == type_id;
+
+        let queued = self
+            .queued_outgoing_events
+            .iter()
+            .filter(|(_, event)| is_of_type(event))
+            .count()
+            + self
+                .queued_outgoing_rpcs
+                .iter()
+                .filter(|rpc| rpc.payload().map_or(false, is_of_type))
+                .count()
+            + self
+                .queued_outgoing_timed_events
+                .iter()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count()
+            + self
+                .queued_outgoing_entity_events
+                .iter()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count()
+            + self
+                .queued_outgoing_relay_events
+                .iter()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count();
+
+        let in_flight = self
+            .unacked_events
+            .values()
+            .filter(|unacked_event| {
+                unacked_event.packets_in_flight > 0 && is_of_type(&unacked_event.event)
+            })
+            .count()
+            + self
+                .sent_rpcs
+                .values()
+                .flatten()
+                .filter(|rpc| rpc.payload().map_or(false, is_of_type))
+                .count()
+            + self
+                .sent_timed_events
+                .values()
+                .flatten()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count()
+            + self
+                .sent_entity_events
+                .values()
+                .flatten()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count()
+            + self
+                .sent_relay_events
+                .values()
+                .flatten()
+                .filter(|(_, _, event)| is_of_type(event))
+                .count();
+
+        let oldest_queued_age = [
+            self.queued_outgoing_events
+                .get_oldest_age(|(_, event)| is_of_type(event)),
+            self.queued_outgoing_rpcs
+                .get_oldest_age(|rpc| rpc.payload().map_or(false, is_of_type)),
+            self.queued_outgoing_timed_events
+                .get_oldest_age(|(_, _, event)| is_of_type(event)),
+            self.queued_outgoing_entity_events
+                .get_oldest_age(|(_, _, event)| is_of_type(event)),
+            self.queued_outgoing_relay_events
+                .get_oldest_age(|(_, _, event)| is_of_type(event)),
+        ]
+        .iter()
+        .flatten()
+        .max()
+        .copied();
+
+        return EventQueueStats {
+            queued,
+            in_flight,
+            oldest_queued_age,
+            retransmissions: self
+                .type_retransmissions
+                .get(&type_id)
+                .copied()
+                .unwrap_or(0),
+        };
+    }
+
+    // Counts a lost message being queued again, under its kind & the type of
+    // its Event, if it has one
+    fn record_retransmission(
+        retransmissions: &mut HashMap<ManagerType, u64>,
+        type_retransmissions: &mut HashMap<TypeId, u64>,
+        manager_type: ManagerType,
+        event: Option<&Rc<Box<dyn Event<T>>>>,
+    ) {
+        *retransmissions.entry(manager_type).or_default() += 1;
+        if let Some(event) = event {
+            let type_id = Event::get_type_id(event.as_ref().as_ref());
+            *type_retransmissions.entry(type_id).or_default() += 1;
+        }
+    }
+
+    /// Drops every queued Event which isn't guaranteed, and hasn't been sent
+    /// yet
+    pub fn drop_queued_unguaranteed_events(&mut self) {
+        let unacked_events = &mut self.unacked_events;
+        let event_expirations = &mut self.event_expirations;
+        self.queued_outgoing_events.retain(|(event_id, event)| {
+            if Event::is_guaranteed(event.as_ref().as_ref()) {
+                return true;
+            }
+            unacked_events.remove(event_id);
+            event_expirations.remove(event_id);
+            return false;
+        });
+    }
+
+    /// Get the EventId of the next Event which has been confirmed as delivered
+    /// to the remote host
+    pub fn pop_delivered_event_id(&mut self) -> Option<EventId> {
+        return self.delivered_event_ids.pop_front();
+    }
+
+    fn merge_outgoing_event(&mut self, event: &dyn Event<T>) -> Option<EventId> {
+        if !Event::is_mergeable(event) {
+            return None;
+        }
+        let type_id = Event::get_type_id(event);
+        let (event_id, queued_event) =
+            self.queued_outgoing_events
+                .iter_mut()
+                .rev()
+                .find(|(_, queued_event)| {
+                    Event::get_type_id(queued_event.as_ref().as_ref()) == type_id
+                })?;
+        // an Event queued again after being lost may already have arrived
+        if self.unacked_events.contains_key(event_id) {
+            return None;
+        }
+        let queued_event = Rc::get_mut(queued_event)?;
+        if !queued_event.merge(&event.get_typed_copy()) {
+            return None;
+        }
+        return Some(*event_id);
+    }
+
+    /// Get the EventId of the next Event which was dropped from the queue
+    /// because its time-to-live ran out before it could be sent
+    pub fn pop_expired_event_id(&mut self) -> Option<EventId> {
+        return self.expired_event_ids.pop_front();
+    }
+
+    fn is_expired(&self, event_id: &EventId) -> bool {
+        return match self.event_expirations.get(event_id) {
+            Some((queued_at, ttl)) => {
+                let elapsed = queued_at.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos()) >= *ttl
+            }
+            None => false,
+        };
+    }
+
+    fn get_next_event_id(&mut self) -> EventId {
+        let event_id = self.next_event_id;
+        self.next_event_id = self.next_event_id.wrapping_add(1);
+        return event_id;
     }
 
     /// Returns whether any Events have been received that must be handed to the
@@ -114,36 +817,1788 @@ impl<T: EventType> EventManager<T> {
         return self.queued_incoming_events.pop_front();
     }
 
-    /// Given incoming packet data, read transmitted Events and store them to be
-    /// returned to the application
-    pub fn process_data<U: EntityType>(
+    /// Queues an RPC message to be transmitted to the remote host
+    pub fn queue_outgoing_rpc(&mut self, rpc: RpcMessage<&dyn Event<T>>) {
+        let rpc = match rpc {
+            RpcMessage::Request(request_id, event) => {
+                RpcMessage::Request(request_id, Rc::new(EventClone::clone_box(event)))
+            }
+            RpcMessage::Response(request_id, event) => {
+                RpcMessage::Response(request_id, Rc::new(EventClone::clone_box(event)))
+            }
+            RpcMessage::Error(request_id) => RpcMessage::Error(request_id),
+        };
+        self.queued_outgoing_rpcs.push_back(rpc);
+    }
+
+    /// Returns whether the Manager has queued RPC messages that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_rpcs(&self) -> bool {
+        return !self.queued_outgoing_rpcs.is_empty();
+    }
+
+    /// Gets the next queued RPC message to be transmitted
+    pub fn pop_outgoing_rpc(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<RpcMessage<Rc<Box<dyn Event<T>>>>> {
+        match self.queued_outgoing_rpcs.pop_front() {
+            Some(rpc) => {
+                //place in transmission record, all rpc messages are guaranteed
+                self.sent_rpcs
+                    .entry(packet_index)
+                    .or_default()
+                    .push(rpc.clone());
+                Some(rpc)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped RPC message from the queue somehow wasn't able to be
+    /// written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_rpc(
+        &mut self,
+        packet_index: u16,
+        rpc: &RpcMessage<Rc<Box<dyn Event<T>>>>,
+    ) {
+        if let Some(sent_rpcs_list) = self.sent_rpcs.get_mut(&packet_index) {
+            sent_rpcs_list.pop();
+            if sent_rpcs_list.is_empty() {
+                self.sent_rpcs.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_rpcs.push_front(rpc.clone());
+    }
+
+    /// Get the most recently received RPC message
+    pub fn pop_incoming_rpc(&mut self) -> Option<RpcMessage<T>> {
+        return self.queued_incoming_rpcs.pop_front();
+    }
+
+    /// Queues an Event to be transmitted to the remote host, which will be
+    /// handed to the remote application once the given tick is reached. The
+    /// Event is given an EventId, so that the remote host can discard it if
+    /// it is delivered more than once
+    pub fn queue_outgoing_timed_event(&mut self, tick: u16, event: &impl Event<T>) {
+        let clone = Rc::new(EventClone::clone_box(event));
+        let event_id = self.get_next_event_id();
+        self.queued_outgoing_timed_events
+            .push_back((event_id, tick, clone));
+    }
+
+    /// Returns whether the Manager has queued timed Events that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_timed_events(&self) -> bool {
+        return !self.queued_outgoing_timed_events.is_empty();
+    }
+
+    /// Gets the next queued timed Event to be transmitted, along with its
+    /// EventId & the tick it is scheduled for
+    pub fn pop_outgoing_timed_event(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(EventId, u16, Rc<Box<dyn Event<T>>>)> {
+        match self.queued_outgoing_timed_events.pop_front() {
+            Some(timed_event) => {
+                //place in transmission record, all timed events are guaranteed
+                self.sent_timed_events
+                    .entry(packet_index)
+                    .or_default()
+                    .push(timed_event.clone());
+                Some(timed_event)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped timed Event from the queue somehow wasn't able to be
+    /// written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_timed_event(
+        &mut self,
+        packet_index: u16,
+        timed_event: &(EventId, u16, Rc<Box<dyn Event<T>>>),
+    ) {
+        if let Some(sent_timed_events_list) = self.sent_timed_events.get_mut(&packet_index) {
+            sent_timed_events_list.pop();
+            if sent_timed_events_list.is_empty() {
+                self.sent_timed_events.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_timed_events
+            .push_front(timed_event.clone());
+    }
+
+    /// Get the most recently received timed Event, as a tuple of (scheduled
+    /// tick, ticks remaining until the scheduled tick at the time the Event was
+    /// sent, Event)
+    pub fn pop_incoming_timed_event(&mut self) -> Option<(u16, i16, T)> {
+        return self.queued_incoming_timed_events.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted timed Events and store
+    /// them to be returned to the application. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_timed_event_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let event_count = match cursor.read_u8() {
+            Ok(event_count) => event_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..event_count {
+            let tick: u16 = match cursor.read_u16::<BigEndian>() {
+                Ok(tick) => tick,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let host_tick: u16 = match cursor.read_u16::<BigEndian>() {
+                Ok(host_tick) => host_tick,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let event_id: EventId = match cursor.read_u16::<BigEndian>() {
+                Ok(event_id) => event_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let (naia_id, event_payload) = match read_event_payload(buffer, cursor) {
+                Some(event) => event,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            // timed Events are numbered along with Events, & are likewise
+            // resent when the packet carrying them is considered dropped, even
+            // if it did in fact arrive
+            if self.received_event_ids.exists(event_id)
+                || self.received_event_ids.insert(event_id, true).is_none()
+            {
+                continue;
+            }
+
+            match manifest.read_event(naia_id, event_payload) {
+                Ok(new_event) => {
+                    let ticks_remaining = tick.wrapping_sub(host_tick) as i16;
+                    self.queued_incoming_timed_events
+                        .push_back((tick, ticks_remaining, new_event));
+                }
+                Err(reason) => drop_log.record(reason),
+            }
+        }
+        return true;
+    }
+
+    /// Queues an Event targeted at the Entity with the given key on the
+    /// remote host, to be transmitted to the remote host. The Event is given
+    /// an EventId, so that the remote host can discard it if it is delivered
+    /// more than once
+    pub fn queue_outgoing_entity_event(&mut self, local_key: LocalEntityKey, event: &dyn Event<T>) {
+        let clone = Rc::new(EventClone::clone_box(event));
+        let event_id = self.get_next_event_id();
+        self.queued_outgoing_entity_events
+            .push_back((event_id, local_key, clone));
+    }
+
+    /// Returns whether the Manager has queued Entity Events that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_entity_events(&self) -> bool {
+        return !self.queued_outgoing_entity_events.is_empty();
+    }
+
+    /// Gets the next queued Entity Event to be transmitted, along with its
+    /// EventId & the key of the Entity it targets
+    pub fn pop_outgoing_entity_event(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>)> {
+        match self.queued_outgoing_entity_events.pop_front() {
+            Some(entity_event) => {
+                //place in transmission record, guaranteed events are retransmitted if dropped
+                self.sent_entity_events
+                    .entry(packet_index)
+                    .or_default()
+                    .push(entity_event.clone());
+                Some(entity_event)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped Entity Event from the queue somehow wasn't able to
+    /// be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_entity_event(
+        &mut self,
+        packet_index: u16,
+        entity_event: &(EventId, LocalEntityKey, Rc<Box<dyn Event<T>>>),
+    ) {
+        if let Some(sent_entity_events_list) = self.sent_entity_events.get_mut(&packet_index) {
+            sent_entity_events_list.pop();
+            if sent_entity_events_list.is_empty() {
+                self.sent_entity_events.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_entity_events
+            .push_front(entity_event.clone());
+    }
+
+    /// Get the most recently received Entity Event, along with the key of the
+    /// Entity it targets
+    pub fn pop_incoming_entity_event(&mut self) -> Option<(LocalEntityKey, T)> {
+        return self.queued_incoming_entity_events.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted Entity Events and store
+    /// them to be returned to the application. Returns false if the section
+    /// extends past the end of the packet, in which case the rest of the
+    /// packet can't be read
+    pub fn process_entity_event_data<U: EntityType>(
         &mut self,
         reader: &mut PacketReader,
         manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let event_count = match cursor.read_u8() {
+            Ok(event_count) => event_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..event_count {
+            let event_id: EventId = match cursor.read_u16::<BigEndian>() {
+                Ok(event_id) => event_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let local_key = match LocalEntityKey::read(cursor) {
+                Some(local_key) => local_key,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let (naia_id, event_payload) = match read_event_payload(buffer, cursor) {
+                Some(event) => event,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            // Entity Events are numbered along with Events, & a guaranteed one is
+            // likewise resent when the packet carrying it is considered dropped,
+            // even if it did in fact arrive
+            if self.received_event_ids.exists(event_id)
+                || self.received_event_ids.insert(event_id, true).is_none()
+            {
+                continue;
+            }
+
+            match manifest.read_event(naia_id, event_payload) {
+                Ok(new_event) => {
+                    self.queued_incoming_entity_events
+                        .push_back((local_key, new_event));
+                }
+                Err(reason) => drop_log.record(reason),
+            }
+        }
+        return true;
+    }
+
+    /// Queues an Event to be relayed by the Server between Clients, to be
+    /// transmitted to the remote host. On the Client, the given id is that of
+    /// the Client the Event is addressed to, and on the Server, that of the
+    /// Client which sent it. The Event is given an EventId, so that the remote
+    /// host can discard it if it is delivered more than once
+    pub fn queue_outgoing_relay_event(&mut self, client_id: ClientId, event: &dyn Event<T>) {
+        let clone = Rc::new(EventClone::clone_box(event));
+        let event_id = self.get_next_event_id();
+        self.queued_outgoing_relay_events
+            .push_back((event_id, client_id, clone));
+    }
+
+    /// Returns whether the Manager has queued relayed Events that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_relay_events(&self) -> bool {
+        return !self.queued_outgoing_relay_events.is_empty();
+    }
+
+    /// Gets the next queued relayed Event to be transmitted, along with its
+    /// EventId & the id of the Client it is addressed to, or sent by
+    pub fn pop_outgoing_relay_event(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(EventId, ClientId, Rc<Box<dyn Event<T>>>)> {
+        match self.queued_outgoing_relay_events.pop_front() {
+            Some(relay_event) => {
+                //place in transmission record, guaranteed events are retransmitted if dropped
+                self.sent_relay_events
+                    .entry(packet_index)
+                    .or_default()
+                    .push(relay_event.clone());
+                Some(relay_event)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped relayed Event from the queue somehow wasn't able
+    /// to be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_relay_event(
+        &mut self,
+        packet_index: u16,
+        relay_event: &(EventId, ClientId, Rc<Box<dyn Event<T>>>),
     ) {
+        if let Some(sent_relay_events_list) = self.sent_relay_events.get_mut(&packet_index) {
+            sent_relay_events_list.pop();
+            if sent_relay_events_list.is_empty() {
+                self.sent_relay_events.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_relay_events
+            .push_front(relay_event.clone());
+    }
+
+    /// Get the most recently received relayed Event, along with the id of the
+    /// Client it is addressed to, or was sent by
+    pub fn pop_incoming_relay_event(&mut self) -> Option<(ClientId, T)> {
+        return self.queued_incoming_relay_events.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted relayed Events and store
+    /// them to be returned to the application. Returns false if the section
+    /// extends past the end of the packet, in which case the rest of the
+    /// packet can't be read
+    pub fn process_relay_event_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
         let buffer = reader.get_buffer();
         let cursor = reader.get_cursor();
 
-        let event_count = cursor.read_u8().unwrap();
+        let event_count = match cursor.read_u8() {
+            Ok(event_count) => event_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
         for _x in 0..event_count {
-            let naia_id: u16 = cursor.read_u16::<BigEndian>().unwrap().into();
-            let payload_length: u8 = cursor.read_u8().unwrap().into();
-            let payload_start_position: usize = cursor.position() as usize;
-            let payload_end_position: usize = payload_start_position + (payload_length as usize);
+            let event_id: EventId = match cursor.read_u16::<BigEndian>() {
+                Ok(event_id) => event_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let client_id: ClientId = match cursor.read_u16::<BigEndian>() {
+                Ok(client_id) => client_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let (naia_id, event_payload) = match read_event_payload(buffer, cursor) {
+                Some(event) => event,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
 
-            let event_payload = buffer[payload_start_position..payload_end_position]
-                .to_vec()
-                .into_boxed_slice();
+            // relayed Events are numbered along with Events, by the host which
+            // sends them on each hop, & a guaranteed one is likewise resent when
+            // the packet carrying it is considered dropped, even if it did in
+            // fact arrive
+            if self.received_event_ids.exists(event_id)
+                || self.received_event_ids.insert(event_id, true).is_none()
+            {
+                continue;
+            }
 
-            match manifest.create_event(naia_id, &event_payload) {
-                Some(new_event) => {
-                    //new_entity.read(&event_payload);
-                    self.queued_incoming_events.push_back(new_event);
+            match manifest.read_event(naia_id, event_payload) {
+                Ok(new_event) => {
+                    self.queued_incoming_relay_events
+                        .push_back((client_id, new_event));
                 }
-                _ => {}
+                Err(reason) => drop_log.record(reason),
+            }
+        }
+        return true;
+    }
+
+    /// Queues a change to the set of connected Clients, made at the given
+    /// revision of the roster, to be transmitted to the remote host
+    pub fn queue_outgoing_roster_change(&mut self, revision: u32, change: ClientRosterChange) {
+        self.queued_outgoing_roster_changes
+            .push_back((revision, change));
+    }
+
+    /// Returns whether the Manager has queued roster changes that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_roster_changes(&self) -> bool {
+        return !self.queued_outgoing_roster_changes.is_empty();
+    }
+
+    /// Gets the next queued roster change to be transmitted, along with the
+    /// revision it was made at
+    pub fn pop_outgoing_roster_change(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(u32, ClientRosterChange)> {
+        match self.queued_outgoing_roster_changes.pop_front() {
+            Some(roster_change) => {
+                //place in transmission record, roster changes are always retransmitted if dropped
+                self.sent_roster_changes
+                    .entry(packet_index)
+                    .or_default()
+                    .push(roster_change);
+                Some(roster_change)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped roster change from the queue somehow wasn't able
+    /// to be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_roster_change(
+        &mut self,
+        packet_index: u16,
+        roster_change: (u32, ClientRosterChange),
+    ) {
+        if let Some(sent_roster_changes) = self.sent_roster_changes.get_mut(&packet_index) {
+            sent_roster_changes.pop();
+            if sent_roster_changes.is_empty() {
+                self.sent_roster_changes.remove(&packet_index);
             }
+        }
+
+        self.queued_outgoing_roster_changes
+            .push_front(roster_change);
+    }
+
+    /// Get the most recently received roster change, along with the revision
+    /// it was made at
+    pub fn pop_incoming_roster_change(&mut self) -> Option<(u32, ClientRosterChange)> {
+        return self.queued_incoming_roster_changes.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted roster changes and store
+    /// them to be returned to the application. Returns false if a change
+    /// extends past the end of the packet, or is of an unknown kind, in which
+    /// case the rest of the packet can't be read
+    pub fn process_roster_data(
+        &mut self,
+        reader: &mut PacketReader,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let cursor = reader.get_cursor();
 
-            cursor.set_position(payload_end_position as u64);
+        let change_count = match cursor.read_u8() {
+            Ok(change_count) => change_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..change_count {
+            let revision: u32 = match cursor.read_u32::<BigEndian>() {
+                Ok(revision) => revision,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let kind: u8 = match cursor.read_u8() {
+                Ok(kind) => kind,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let client_id: ClientId = match cursor.read_u16::<BigEndian>() {
+                Ok(client_id) => client_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let change = match kind {
+                0 => ClientRosterChange::Joined(client_id),
+                1 => ClientRosterChange::Left(client_id),
+                _ => {
+                    drop_log.record(DropReason::MalformedPayload);
+                    return false;
+                }
+            };
+            self.queued_incoming_roster_changes
+                .push_back((revision, change));
         }
+        return true;
+    }
+
+    /// Queues a description of the types registered in the Manifest at the
+    /// given revision of it, to be transmitted to the remote host
+    pub fn queue_outgoing_manifest_delta(&mut self, revision: u32, delta: ManifestDescriptor) {
+        self.queued_outgoing_manifest_deltas
+            .push_back((revision, delta));
+    }
+
+    /// Returns whether the Manager has queued Manifest deltas that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_manifest_deltas(&self) -> bool {
+        return !self.queued_outgoing_manifest_deltas.is_empty();
+    }
+
+    /// Gets the next queued Manifest delta to be transmitted, along with the
+    /// revision of the Manifest it describes
+    pub fn pop_outgoing_manifest_delta(
+        &mut self,
+        packet_index: u16,
+    ) -> Option<(u32, ManifestDescriptor)> {
+        match self.queued_outgoing_manifest_deltas.pop_front() {
+            Some(manifest_delta) => {
+                //place in transmission record, Manifest deltas are always retransmitted if dropped
+                self.sent_manifest_deltas
+                    .entry(packet_index)
+                    .or_default()
+                    .push(manifest_delta.clone());
+                Some(manifest_delta)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped Manifest delta from the queue somehow wasn't able
+    /// to be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_manifest_delta(
+        &mut self,
+        packet_index: u16,
+        manifest_delta: (u32, ManifestDescriptor),
+    ) {
+        if let Some(sent_manifest_deltas) = self.sent_manifest_deltas.get_mut(&packet_index) {
+            sent_manifest_deltas.pop();
+            if sent_manifest_deltas.is_empty() {
+                self.sent_manifest_deltas.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_manifest_deltas
+            .push_front(manifest_delta);
+    }
+
+    /// Get the most recently received Manifest delta, along with the revision
+    /// of the remote Manifest it describes
+    pub fn pop_incoming_manifest_delta(&mut self) -> Option<(u32, ManifestDescriptor)> {
+        return self.queued_incoming_manifest_deltas.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted Manifest deltas and store
+    /// them to be returned to the application. Returns false if a delta
+    /// extends past the end of the packet, in which case the rest of the
+    /// packet can't be read
+    pub fn process_manifest_delta_data(
+        &mut self,
+        reader: &mut PacketReader,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let delta_count = match reader.read_u8() {
+            Some(delta_count) => delta_count,
+            None => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..delta_count {
+            let revision = match reader.get_cursor().read_u32::<BigEndian>() {
+                Ok(revision) => revision,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            match ManifestDescriptor::read(reader) {
+                Some(delta) => {
+                    self.queued_incoming_manifest_deltas
+                        .push_back((revision, delta));
+                }
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Queues a notice that the Server is shutting down, with the reason, to
+    /// be transmitted to the remote host
+    pub fn queue_outgoing_shutdown(&mut self, reason: String) {
+        self.queued_outgoing_shutdowns.push_back(reason);
+    }
+
+    /// Returns whether the Manager has queued shutdown notices that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_shutdowns(&self) -> bool {
+        return !self.queued_outgoing_shutdowns.is_empty();
+    }
+
+    /// Gets the next queued shutdown notice to be transmitted
+    pub fn pop_outgoing_shutdown(&mut self, packet_index: u16) -> Option<String> {
+        match self.queued_outgoing_shutdowns.pop_front() {
+            Some(reason) => {
+                //place in transmission record, shutdown notices are always retransmitted if dropped
+                self.sent_shutdowns
+                    .entry(packet_index)
+                    .or_default()
+                    .push(reason.clone());
+                Some(reason)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped shutdown notice from the queue somehow wasn't able
+    /// to be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_shutdown(&mut self, packet_index: u16, reason: String) {
+        if let Some(sent_shutdowns) = self.sent_shutdowns.get_mut(&packet_index) {
+            sent_shutdowns.pop();
+            if sent_shutdowns.is_empty() {
+                self.sent_shutdowns.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_shutdowns.push_front(reason);
+    }
+
+    /// Get the most recently received shutdown notice, with its reason
+    pub fn pop_incoming_shutdown(&mut self) -> Option<String> {
+        return self.queued_incoming_shutdowns.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted shutdown notices and store
+    /// them to be returned to the application. Returns false if a notice
+    /// extends past the end of the packet, in which case the rest of the
+    /// packet can't be read
+    pub fn process_shutdown_data(
+        &mut self,
+        reader: &mut PacketReader,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let shutdown_count = match reader.read_u8() {
+            Some(shutdown_count) => shutdown_count,
+            None => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..shutdown_count {
+            match read_string(reader) {
+                Some(reason) => {
+                    self.queued_incoming_shutdowns.push_back(reason);
+                }
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Queues a change to the rate at which the Server ticks, to be
+    /// transmitted to the remote host
+    pub fn queue_outgoing_tick_rate_change(&mut self, change: TickRateChange) {
+        self.queued_outgoing_tick_rate_changes.push_back(change);
+    }
+
+    /// Returns whether the Manager has queued tick rate changes that can be
+    /// transmitted to the remote host
+    pub fn has_outgoing_tick_rate_changes(&self) -> bool {
+        return !self.queued_outgoing_tick_rate_changes.is_empty();
+    }
+
+    /// Gets the next queued tick rate change to be transmitted
+    pub fn pop_outgoing_tick_rate_change(&mut self, packet_index: u16) -> Option<TickRateChange> {
+        match self.queued_outgoing_tick_rate_changes.pop_front() {
+            Some(change) => {
+                //place in transmission record, tick rate changes are always retransmitted if dropped
+                self.sent_tick_rate_changes
+                    .entry(packet_index)
+                    .or_default()
+                    .push(change);
+                Some(change)
+            }
+            None => None,
+        }
+    }
+
+    /// If the last popped tick rate change from the queue somehow wasn't able
+    /// to be written into a packet, put it back into the front of the queue
+    pub fn unpop_outgoing_tick_rate_change(&mut self, packet_index: u16, change: TickRateChange) {
+        if let Some(sent_changes) = self.sent_tick_rate_changes.get_mut(&packet_index) {
+            sent_changes.pop();
+            if sent_changes.is_empty() {
+                self.sent_tick_rate_changes.remove(&packet_index);
+            }
+        }
+
+        self.queued_outgoing_tick_rate_changes.push_front(change);
+    }
+
+    /// Get the next received tick rate change
+    pub fn pop_incoming_tick_rate_change(&mut self) -> Option<TickRateChange> {
+        return self.queued_incoming_tick_rate_changes.pop_front();
+    }
+
+    /// Given incoming packet data, read transmitted tick rate changes and
+    /// store them to be returned to the application. Returns false if a change
+    /// extends past the end of the packet, in which case the rest of the
+    /// packet can't be read
+    pub fn process_tick_rate_change_data(
+        &mut self,
+        reader: &mut PacketReader,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let change_count = match reader.read_u8() {
+            Some(change_count) => change_count,
+            None => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..change_count {
+            match TickRateChange::read(reader) {
+                Some(change) => {
+                    self.queued_incoming_tick_rate_changes.push_back(change);
+                }
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Queues the command of the given tick to be transmitted to the remote
+    /// host, replacing any command already queued for that tick. Only the
+    /// most recent commands, up to the redundancy, are kept
+    pub fn queue_outgoing_command(&mut self, tick: u16, command: &impl Event<T>) {
+        let clone = Rc::new(EventClone::clone_box(command));
+        self.unacked_outgoing_commands
+            .retain(|(queued_tick, _)| *queued_tick != tick);
+        self.unacked_outgoing_commands.push_back((tick, clone));
+        while self.unacked_outgoing_commands.len() > self.command_redundancy {
+            self.unacked_outgoing_commands.pop_front();
+        }
+        self.has_new_outgoing_command = true;
+    }
+
+    /// Returns whether a command has been queued since the last packet was
+    /// written
+    pub fn has_new_outgoing_command(&self) -> bool {
+        return self.has_new_outgoing_command;
+    }
+
+    /// Gets every unacknowledged command to be written into the given packet,
+    /// newest first, so that if they don't all fit, the oldest are left out
+    pub fn get_outgoing_commands(
+        &mut self,
+        packet_index: u16,
+    ) -> Vec<(u16, Rc<Box<dyn Event<T>>>)> {
+        self.has_new_outgoing_command = false;
+        if let Some((newest_tick, _)) = self.unacked_outgoing_commands.back() {
+            self.sent_commands.insert(packet_index, *newest_tick);
+        }
+        return self
+            .unacked_outgoing_commands
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+    }
+
+    /// Get the next received command, along with its tick. Each tick's
+    /// command is returned once, however many copies of it arrive
+    pub fn pop_incoming_command(&mut self) -> Option<(u16, T)> {
+        return self.queued_incoming_commands.pop_front();
+    }
+
+    /// Get statistics about the commands received
+    pub fn get_command_stats(&self) -> CommandStats {
+        return self.command_stats;
+    }
+
+    /// Given incoming packet data, read transmitted commands, and store the
+    /// first copy of each to be returned to the application. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_command_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let command_count = match cursor.read_u8() {
+            Ok(command_count) => command_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for index in 0..command_count {
+            let tick: u16 = match cursor.read_u16::<BigEndian>() {
+                Ok(tick) => tick,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let (naia_id, command_payload) = match read_event_payload(buffer, cursor) {
+                Some(command) => command,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            if self.received_command_ticks.contains(&tick) {
+                self.command_stats.duplicates += 1;
+                continue;
+            }
+            self.received_command_ticks.push_back(tick);
+            if self.received_command_ticks.len() > RECEIVED_COMMAND_TICKS_SIZE {
+                self.received_command_ticks.pop_front();
+            }
+
+            match manifest.read_event(naia_id, command_payload) {
+                Ok(new_command) => {
+                    self.command_stats.received += 1;
+                    // commands are written newest first, so any but the first is a
+                    // redundant copy, which has arrived because the packet first
+                    // carrying it did not
+                    if index != 0 {
+                        self.command_stats.recovered += 1;
+                    }
+                    self.queued_incoming_commands.push_back((tick, new_command));
+                }
+                Err(reason) => drop_log.record(reason),
+            }
+        }
+        return true;
+    }
+
+    /// Given incoming packet data, read transmitted RPC messages and store
+    /// them to be returned to the application. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_rpc_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let rpc_count = match cursor.read_u8() {
+            Ok(rpc_count) => rpc_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..rpc_count {
+            let message_type: u8 = match cursor.read_u8() {
+                Ok(message_type) => message_type,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let request_id: RequestId = match cursor.read_u16::<BigEndian>() {
+                Ok(request_id) => request_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            // an RPC message may arrive more than once, like a guaranteed Event,
+            // & must not be handled twice. Requests are numbered by the remote
+            // host, & responses by this one, so each has its own record
+            let received_ids = match message_type {
+                0 => &mut self.received_request_ids,
+                _ => &mut self.received_response_ids,
+            };
+            let is_duplicate =
+                received_ids.exists(request_id) || received_ids.insert(request_id, true).is_none();
+
+            if message_type == 2 {
+                if !is_duplicate {
+                    self.queued_incoming_rpcs
+                        .push_back(RpcMessage::Error(request_id));
+                }
+                continue;
+            }
+
+            let (naia_id, event_payload) = match read_event_payload(buffer, cursor) {
+                Some(event) => event,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            if is_duplicate {
+                continue;
+            }
+
+            match manifest.read_event(naia_id, event_payload) {
+                Ok(new_event) => match message_type {
+                    0 => self
+                        .queued_incoming_rpcs
+                        .push_back(RpcMessage::Request(request_id, new_event)),
+                    1 => self
+                        .queued_incoming_rpcs
+                        .push_back(RpcMessage::Response(request_id, new_event)),
+                    _ => {}
+                },
+                Err(reason) => drop_log.record(reason),
+            }
+        }
+        return true;
+    }
+
+    /// Given incoming packet data, read transmitted Events and store them to be
+    /// returned to the application. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_data<U: EntityType>(
+        &mut self,
+        reader: &mut PacketReader,
+        manifest: &Manifest<T, U>,
+        drop_log: &mut DropLog,
+    ) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let event_count = match cursor.read_u8() {
+            Ok(event_count) => event_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..event_count {
+            let event_id: EventId = match cursor.read_u16::<BigEndian>() {
+                Ok(event_id) => event_id,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let (naia_id, event_payload) = match read_event_payload(buffer, cursor) {
+                Some(event) => event,
+                None => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+
+            // a guaranteed Event may arrive more than once, if the packet carrying
+            // it was considered dropped but did in fact arrive. Events which are
+            // too old to be tracked are also considered duplicates.
+            let is_duplicate = self.received_event_ids.exists(event_id)
+                || self.received_event_ids.insert(event_id, true).is_none();
+
+            if !is_duplicate {
+                match manifest.read_event(naia_id, event_payload) {
+                    Ok(new_event) => {
+                        //new_entity.read(&event_payload);
+                        self.queued_incoming_events.push_back(new_event);
+                    }
+                    Err(reason) => drop_log.record(reason),
+                }
+            }
+        }
+        return true;
+    }
+}
+
+// An Event which has been sent, but not yet delivered
+#[derive(Debug)]
+struct UnackedEvent<T: EventType> {
+    event: Rc<Box<dyn Event<T>>>,
+    // The number of sent packets carrying the Event which have been neither
+    // delivered nor dropped
+    packets_in_flight: u16,
+}
+
+// Reads the NaiaId & payload of an Event, which follow the fields particular to
+// its section, & moves the cursor past the payload. Returns None if the packet
+// ends before the payload does
+fn read_event_payload<'b>(buffer: &'b [u8], cursor: &mut Cursor<&[u8]>) -> Option<(u16, &'b [u8])> {
+    let naia_id = cursor.read_u16::<BigEndian>().ok()?;
+    let payload_length = cursor.read_u8().ok()? as usize;
+    let payload_start_position = cursor.position() as usize;
+    let payload_end_position = payload_start_position + payload_length;
+    if payload_end_position > buffer.len() {
+        return None;
+    }
+    cursor.set_position(payload_end_position as u64);
+    return Some((
+        naia_id,
+        &buffer[payload_start_position..payload_end_position],
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::TypeId, cell::RefCell, rc::Rc, time::Duration};
+
+    use crate::{
+        connection_stats::{DropLog, DropReason},
+        ClientRosterChange, Entity, EntityType, Event, EventBuilder, EventManager, EventType,
+        LocalEntityKey, ManagerType, Manifest, ManifestDescriptor, PacketReader, PacketWriter,
+        PropertyDescriptor, RpcMessage, StateMask, TickRateChange, TypeDescriptor,
+    };
+
+    #[derive(Clone)]
+    struct TestEvent {
+        guaranteed: bool,
+    }
+
+    // an Event of which only the latest value matters
+    #[derive(Clone)]
+    struct LatestEvent {
+        value: u8,
+    }
+
+    #[derive(Clone)]
+    enum TestEvents {
+        TestEvent(TestEvent),
+        LatestEvent(LatestEvent),
+    }
+
+    impl EventType for TestEvents {
+        fn write(&mut self, _: &mut Vec<u8>) {}
+
+        fn get_type_id(&self) -> TypeId {
+            match self {
+                TestEvents::TestEvent(_) => return TypeId::of::<TestEvent>(),
+                TestEvents::LatestEvent(_) => return TypeId::of::<LatestEvent>(),
+            }
+        }
+
+        fn get_boxed_event(&self) -> Box<dyn Event<Self>> {
+            match self {
+                TestEvents::TestEvent(event) => return Box::new(event.clone()),
+                TestEvents::LatestEvent(event) => return Box::new(event.clone()),
+            }
+        }
+    }
+
+    impl Event<TestEvents> for TestEvent {
+        fn is_guaranteed(&self) -> bool {
+            return self.guaranteed;
+        }
+
+        fn write(&self, _: &mut Vec<u8>) {}
+
+        fn get_typed_copy(&self) -> TestEvents {
+            return TestEvents::TestEvent(self.clone());
+        }
+
+        fn get_type_id(&self) -> TypeId {
+            return TypeId::of::<TestEvent>();
+        }
+    }
+
+    impl Event<TestEvents> for LatestEvent {
+        fn is_guaranteed(&self) -> bool {
+            return true;
+        }
+
+        fn write(&self, _: &mut Vec<u8>) {}
+
+        fn get_typed_copy(&self) -> TestEvents {
+            return TestEvents::LatestEvent(self.clone());
+        }
+
+        fn get_type_id(&self) -> TypeId {
+            return TypeId::of::<LatestEvent>();
+        }
+
+        fn is_mergeable(&self) -> bool {
+            return true;
+        }
+
+        fn merge(&mut self, later: &TestEvents) -> bool {
+            if let TestEvents::LatestEvent(later) = later {
+                self.value = later.value;
+                return true;
+            }
+            return false;
+        }
+    }
+
+    struct TestEventBuilder;
+
+    impl EventBuilder<TestEvents> for TestEventBuilder {
+        fn get_type_id(&self) -> TypeId {
+            return TypeId::of::<TestEvent>();
+        }
+
+        fn build(&self, _: &[u8]) -> Option<TestEvents> {
+            return Some(TestEvents::TestEvent(TestEvent { guaranteed: true }));
+        }
+
+        fn get_type_name(&self) -> &'static str {
+            return "TestEvent";
+        }
+
+        fn get_property_count(&self) -> u8 {
+            return 0;
+        }
+
+        fn get_properties(&self) -> Vec<PropertyDescriptor> {
+            return Vec::new();
+        }
+
+        fn get_version(&self) -> u8 {
+            return 0;
+        }
+    }
+
+    enum NoEntities {}
+
+    impl EntityType for NoEntities {
+        fn read_partial(&mut self, _: &StateMask, _: &[u8]) -> bool {
+            match *self {}
+        }
+
+        fn get_type_id(&self) -> TypeId {
+            match *self {}
+        }
+
+        fn get_copy(&self) -> Self {
+            match *self {}
+        }
+
+        fn get_entity_ref(&self) -> Rc<RefCell<dyn Entity<Self>>> {
+            match *self {}
+        }
+    }
+
+    fn test_manifest() -> Manifest<TestEvents, NoEntities> {
+        let mut manifest = Manifest::new();
+        manifest.register_event(Box::new(TestEventBuilder));
+        return manifest;
+    }
+
+    fn pop_all(manager: &mut EventManager<TestEvents>, packet_index: u16) -> usize {
+        let mut count = 0;
+        while manager.pop_outgoing_event(packet_index).is_some() {
+            count += 1;
+        }
+        return count;
+    }
+
+    #[test]
+    fn resends_only_undelivered_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let first = manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        manager.queue_outgoing_event(&TestEvent { guaranteed: false });
+        assert!(pop_all(&mut manager, 0) == 2);
+
+        // the guaranteed Event is resent alone
+        manager.notify_packet_dropped(0);
+        let second = manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        assert!(manager.get_undelivered_events().1.len() == 2);
+        assert!(pop_all(&mut manager, 1) == 2);
+
+        manager.notify_packet_delivered(1);
+        assert!(manager.pop_delivered_event_id() == Some(first));
+        assert!(manager.pop_delivered_event_id() == Some(second));
+        assert!(manager.pop_delivered_event_id().is_none());
+        assert!(manager.get_undelivered_events().1.is_empty());
+
+        // notifications of the earlier packet change nothing
+        manager.notify_packet_dropped(0);
+        manager.notify_packet_delivered(0);
+        assert!(!manager.has_outgoing_events());
+        assert!(manager.pop_delivered_event_id().is_none());
+    }
+
+    #[test]
+    fn unpopped_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        let popped = manager.pop_outgoing_event(0).unwrap();
+        manager.unpop_outgoing_event(0, &popped);
+        assert!(manager.get_undelivered_events().1.len() == 1);
+
+        // the Event is only tracked in the packet it is finally sent in
+        manager.notify_packet_dropped(0);
+        assert!(pop_all(&mut manager, 1) == 1);
+        manager.notify_packet_delivered(1);
+        assert!(manager.pop_delivered_event_id() == Some(popped.0));
+        assert!(!manager.has_outgoing_events());
+    }
+
+    #[test]
+    fn resends_guaranteed_entity_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let local_key = LocalEntityKey::new(3, 1);
+        manager.queue_outgoing_entity_event(local_key, &TestEvent { guaranteed: true });
+        manager.queue_outgoing_entity_event(local_key, &TestEvent { guaranteed: false });
+        assert!(manager.pop_outgoing_entity_event(0).is_some());
+        assert!(manager.pop_outgoing_entity_event(0).is_some());
+        assert!(manager.get_undelivered_count() == 2);
+
+        // only the guaranteed Event is resent, still targeting the same Entity
+        manager.notify_packet_dropped(0);
+        let (_, resent_key, _) = manager.pop_outgoing_entity_event(1).unwrap();
+        assert!(resent_key == local_key);
+        assert!(!manager.has_outgoing_entity_events());
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+    }
+
+    #[test]
+    fn resends_guaranteed_relay_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_relay_event(7, &TestEvent { guaranteed: false });
+        manager.queue_outgoing_relay_event(8, &TestEvent { guaranteed: true });
+        assert!(manager.pop_outgoing_relay_event(0).is_some());
+        assert!(manager.pop_outgoing_relay_event(0).is_some());
+        assert!(manager.get_undelivered_count() == 2);
+
+        // only the guaranteed Event is resent, still addressed to the same Client
+        manager.notify_packet_dropped(0);
+        let (_, resent_id, _) = manager.pop_outgoing_relay_event(1).unwrap();
+        assert!(resent_id == 8);
+        assert!(!manager.has_outgoing_relay_events());
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+    }
+
+    #[test]
+    fn resends_roster_changes() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_roster_change(1, ClientRosterChange::Joined(2));
+        manager.queue_outgoing_roster_change(2, ClientRosterChange::Left(2));
+        assert!(manager.pop_outgoing_roster_change(0).is_some());
+        assert!(manager.pop_outgoing_roster_change(0).is_some());
+        assert!(manager.get_undelivered_count() == 2);
+
+        manager.notify_packet_dropped(0);
+        assert!(manager.pop_outgoing_roster_change(1) == Some((1, ClientRosterChange::Joined(2))));
+        assert!(manager.pop_outgoing_roster_change(1) == Some((2, ClientRosterChange::Left(2))));
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+    }
+
+    #[test]
+    fn resends_manifest_deltas() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let delta = ManifestDescriptor {
+            events: Vec::new(),
+            entities: vec![TypeDescriptor::new(20, "ModCrate", 2, 0)],
+        };
+        manager.queue_outgoing_manifest_delta(1, delta.clone());
+        assert!(manager.pop_outgoing_manifest_delta(0).is_some());
+        assert!(manager.get_undelivered_count() == 1);
+
+        manager.notify_packet_dropped(0);
+        assert!(manager.pop_outgoing_manifest_delta(1) == Some((1, delta)));
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+        assert!(
+            manager
+                .get_queue_stats(ManagerType::ManifestDelta)
+                .retransmissions
+                == 1
+        );
+    }
+
+    #[test]
+    fn reads_manifest_deltas() {
+        let delta = ManifestDescriptor {
+            events: vec![TypeDescriptor::new(21, "ModEvent", 1, 0)],
+            entities: vec![TypeDescriptor::new(20, "ModCrate", 2, 0)],
+        };
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_manifest_delta(7, &delta));
+        let bytes = writer.get_bytes();
+
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        let mut reader = PacketReader::new(&bytes);
+        assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::ManifestDelta);
+        assert!(manager.process_manifest_delta_data(&mut reader, &mut drop_log));
+        let (revision, received) = manager.pop_incoming_manifest_delta().unwrap();
+        assert!(revision == 7);
+        // only a hash of the name of each type is transmitted
+        assert!(received.entities[0].name.is_none());
+        assert!(received.entities[0].name_hash == delta.entities[0].name_hash);
+        assert!(received.events[0].naia_id == 21);
+
+        // a truncated delta can't be read
+        let mut reader = PacketReader::new(&bytes[..bytes.len() - 1]);
+        reader.read_u8();
+        assert!(!manager.process_manifest_delta_data(&mut reader, &mut drop_log));
+        assert!(manager.pop_incoming_manifest_delta().is_none());
+    }
+
+    #[test]
+    fn resends_shutdowns() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_shutdown("restarting".to_string());
+        assert!(manager.pop_outgoing_shutdown(0).is_some());
+        assert!(manager.get_undelivered_count() == 1);
+
+        manager.notify_packet_dropped(0);
+        assert!(manager.pop_outgoing_shutdown(1) == Some("restarting".to_string()));
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+        assert!(
+            manager
+                .get_queue_stats(ManagerType::Shutdown)
+                .retransmissions
+                == 1
+        );
+    }
+
+    #[test]
+    fn reads_shutdowns() {
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_shutdown("restarting"));
+        let bytes = writer.get_bytes();
+
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        let mut reader = PacketReader::new(&bytes);
+        assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::Shutdown);
+        assert!(manager.process_shutdown_data(&mut reader, &mut drop_log));
+        assert!(manager.pop_incoming_shutdown() == Some("restarting".to_string()));
+
+        // a truncated notice can't be read
+        let mut reader = PacketReader::new(&bytes[..bytes.len() - 1]);
+        reader.read_u8();
+        assert!(!manager.process_shutdown_data(&mut reader, &mut drop_log));
+        assert!(manager.pop_incoming_shutdown().is_none());
+    }
+
+    #[test]
+    fn discards_duplicate_rpcs() {
+        let manifest = test_manifest();
+        let event: Rc<Box<dyn Event<TestEvents>>> =
+            Rc::new(Box::new(TestEvent { guaranteed: true }));
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_rpc(&manifest, &RpcMessage::Request(7, event.clone())));
+        assert!(writer.write_rpc(&manifest, &RpcMessage::Response(7, event)));
+        assert!(writer.write_rpc(&manifest, &RpcMessage::Error(8)));
+        let bytes = writer.get_bytes();
+
+        // the same packet, received twice, is only handled once
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        for _ in 0..2 {
+            let mut reader = PacketReader::new(&bytes);
+            assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::Rpc);
+            assert!(manager.process_rpc_data(&mut reader, &manifest, &mut drop_log));
+        }
+        assert!(matches!(
+            manager.pop_incoming_rpc(),
+            Some(RpcMessage::Request(7, _))
+        ));
+        assert!(matches!(
+            manager.pop_incoming_rpc(),
+            Some(RpcMessage::Response(7, _))
+        ));
+        assert!(matches!(
+            manager.pop_incoming_rpc(),
+            Some(RpcMessage::Error(8))
+        ));
+        assert!(manager.pop_incoming_rpc().is_none());
+    }
+
+    #[test]
+    fn discards_duplicate_timed_events() {
+        let manifest = test_manifest();
+        let mut sender = EventManager::<TestEvents>::new(1);
+        sender.queue_outgoing_timed_event(12, &TestEvent { guaranteed: true });
+        sender.queue_outgoing_timed_event(12, &TestEvent { guaranteed: true });
+        let mut writer = PacketWriter::new();
+        while let Some((event_id, tick, event)) = sender.pop_outgoing_timed_event(0) {
+            assert!(writer.write_timed_event(&manifest, event_id, 10, tick, &event));
+        }
+        let bytes = writer.get_bytes();
+
+        // the same packet, received twice, is only handled once
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        for _ in 0..2 {
+            let mut reader = PacketReader::new(&bytes);
+            assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::TimedEvent);
+            assert!(manager.process_timed_event_data(&mut reader, &manifest, &mut drop_log));
+        }
+        assert!(matches!(
+            manager.pop_incoming_timed_event(),
+            Some((12, 2, _))
+        ));
+        assert!(matches!(
+            manager.pop_incoming_timed_event(),
+            Some((12, 2, _))
+        ));
+        assert!(manager.pop_incoming_timed_event().is_none());
+    }
+
+    #[test]
+    fn discards_late_entity_events_which_were_resent() {
+        let manifest = test_manifest();
+        let local_key = LocalEntityKey::new(3, 1);
+        let mut sender = EventManager::<TestEvents>::new(1);
+        sender.queue_outgoing_entity_event(local_key, &TestEvent { guaranteed: true });
+        let write_packet = |sender: &mut EventManager<TestEvents>, packet_index: u16| {
+            let mut writer = PacketWriter::new();
+            while let Some((event_id, local_key, event)) =
+                sender.pop_outgoing_entity_event(packet_index)
+            {
+                assert!(writer.write_entity_event(&manifest, event_id, local_key, &event));
+            }
+            return writer.get_bytes();
+        };
+
+        // the original packet is considered dropped, its ack having been lost,
+        // so the Event is resent, & then the original arrives late
+        let original = write_packet(&mut sender, 0);
+        sender.notify_packet_dropped(0);
+        let resend = write_packet(&mut sender, 1);
+        assert!(resend.len() == original.len());
+
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        for bytes in [resend, original].iter() {
+            let mut reader = PacketReader::new(bytes);
+            assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::EntityEvent);
+            assert!(manager.process_entity_event_data(&mut reader, &manifest, &mut drop_log));
+        }
+        assert!(matches!(manager.pop_incoming_entity_event(), Some((key, _)) if key == local_key));
+        assert!(manager.pop_incoming_entity_event().is_none());
+    }
+
+    #[test]
+    fn discards_late_relay_events_which_were_resent_on_either_hop() {
+        let manifest = test_manifest();
+        let write_packet = |sender: &mut EventManager<TestEvents>, packet_index: u16| {
+            let mut writer = PacketWriter::new();
+            while let Some((event_id, client_id, event)) =
+                sender.pop_outgoing_relay_event(packet_index)
+            {
+                assert!(writer.write_relay_event(&manifest, event_id, client_id, &event));
+            }
+            return writer.get_bytes();
+        };
+        let receive_packets = |receiver: &mut EventManager<TestEvents>, packets: &[Box<[u8]>]| {
+            let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+            for bytes in packets.iter() {
+                let mut reader = PacketReader::new(bytes);
+                assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::RelayEvent);
+                assert!(receiver.process_relay_event_data(&mut reader, &manifest, &mut drop_log));
+            }
+        };
+
+        // on each hop, the original packet is considered dropped, its ack
+        // having been lost, so the Event is resent, & then the original
+        // arrives late
+        let mut sending_client = EventManager::<TestEvents>::new(1);
+        let mut server_from_sender = EventManager::<TestEvents>::new(1);
+        sending_client.queue_outgoing_relay_event(8, &TestEvent { guaranteed: true });
+        let original = write_packet(&mut sending_client, 0);
+        sending_client.notify_packet_dropped(0);
+        let resend = write_packet(&mut sending_client, 1);
+        receive_packets(&mut server_from_sender, &[resend, original]);
+        let (recipient_id, event) = server_from_sender.pop_incoming_relay_event().unwrap();
+        assert!(recipient_id == 8);
+        assert!(server_from_sender.pop_incoming_relay_event().is_none());
+
+        let mut server_to_recipient = EventManager::<TestEvents>::new(1);
+        let mut receiving_client = EventManager::<TestEvents>::new(1);
+        server_to_recipient.queue_outgoing_relay_event(7, event.get_boxed_event().as_ref());
+        let original = write_packet(&mut server_to_recipient, 0);
+        server_to_recipient.notify_packet_dropped(0);
+        let resend = write_packet(&mut server_to_recipient, 1);
+        receive_packets(&mut receiving_client, &[resend, original]);
+        assert!(matches!(
+            receiving_client.pop_incoming_relay_event(),
+            Some((7, _))
+        ));
+        assert!(receiving_client.pop_incoming_relay_event().is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_sections() {
+        let manifest = test_manifest();
+        let event: Box<dyn Event<TestEvents>> = Box::new(TestEvent { guaranteed: true });
+        let mut sections: Vec<Box<[u8]>> = Vec::new();
+
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_event(&manifest, 3, &event));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_rpc(&manifest, &RpcMessage::Request(3, Rc::new(event.clone()))));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_timed_event(&manifest, 3, 10, 12, &event));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_entity_event(&manifest, 3, LocalEntityKey::new(3, 1), &event));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_relay_event(&manifest, 3, 4, &event));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_command(&manifest, 10, &event));
+        sections.push(writer.get_bytes());
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_roster_change(5, &ClientRosterChange::Joined(4)));
+        sections.push(writer.get_bytes());
+
+        // every section, cut short anywhere after its ManagerType, is rejected
+        // without anything being read from it
+        for bytes in sections {
+            for length in 1..bytes.len() {
+                let mut manager = EventManager::<TestEvents>::new(1);
+                let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+                let mut reader = PacketReader::new(&bytes[..length]);
+                let manager_type = ManagerType::from(reader.read_u8().unwrap());
+                let readable = match manager_type {
+                    ManagerType::Event => {
+                        manager.process_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::Rpc => {
+                        manager.process_rpc_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::TimedEvent => {
+                        manager.process_timed_event_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::EntityEvent => {
+                        manager.process_entity_event_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::RelayEvent => {
+                        manager.process_relay_event_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::Command => {
+                        manager.process_command_data(&mut reader, &manifest, &mut drop_log)
+                    }
+                    ManagerType::ClientRoster => {
+                        manager.process_roster_data(&mut reader, &mut drop_log)
+                    }
+                    _ => panic!("unexpected section"),
+                };
+                assert!(!readable);
+                assert!(drop_log.get_first_reason() == Some(DropReason::OversizedLength));
+                assert!(manager.pop_incoming_event().is_none());
+                assert!(manager.pop_incoming_rpc().is_none());
+                assert!(manager.pop_incoming_timed_event().is_none());
+                assert!(manager.pop_incoming_entity_event().is_none());
+                assert!(manager.pop_incoming_relay_event().is_none());
+                assert!(manager.pop_incoming_command().is_none());
+                assert!(manager.pop_incoming_roster_change().is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn resends_tick_rate_changes() {
+        let change = TickRateChange {
+            revision: 1,
+            tick: 300,
+            tick_interval: Duration::from_millis(50),
+        };
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_tick_rate_change(change);
+        assert!(manager.pop_outgoing_tick_rate_change(0).is_some());
+        assert!(manager.get_undelivered_count() == 1);
+
+        manager.notify_packet_dropped(0);
+        assert!(manager.pop_outgoing_tick_rate_change(1) == Some(change));
+        manager.notify_packet_delivered(1);
+        assert!(manager.get_undelivered_count() == 0);
+        assert!(
+            manager
+                .get_queue_stats(ManagerType::TickRate)
+                .retransmissions
+                == 1
+        );
+    }
+
+    #[test]
+    fn reads_tick_rate_changes() {
+        let change = TickRateChange {
+            revision: 2,
+            tick: 65535,
+            tick_interval: Duration::from_micros(33_333),
+        };
+        let mut writer = PacketWriter::new();
+        assert!(writer.write_tick_rate_change(&change));
+        let bytes = writer.get_bytes();
+
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+        let mut reader = PacketReader::new(&bytes);
+        assert!(ManagerType::from(reader.read_u8().unwrap()) == ManagerType::TickRate);
+        assert!(manager.process_tick_rate_change_data(&mut reader, &mut drop_log));
+        assert!(manager.pop_incoming_tick_rate_change() == Some(change));
+
+        // a truncated change can't be read
+        let mut reader = PacketReader::new(&bytes[..bytes.len() - 1]);
+        reader.read_u8();
+        assert!(!manager.process_tick_rate_change_data(&mut reader, &mut drop_log));
+        assert!(manager.pop_incoming_tick_rate_change().is_none());
+    }
+
+    #[test]
+    fn queue_stats() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        manager.queue_outgoing_event(&TestEvent { guaranteed: false });
+        manager.queue_outgoing_entity_event(LocalEntityKey::new(3, 1), &LatestEvent { value: 1 });
+        let stats = manager.get_queue_stats(ManagerType::Event);
+        assert!(stats.queued == 2 && stats.in_flight == 0);
+        assert!(stats.oldest_queued_age.is_some());
+        assert!(manager
+            .get_queue_stats(ManagerType::Rpc)
+            .oldest_queued_age
+            .is_none());
+
+        assert!(pop_all(&mut manager, 0) == 2);
+        assert!(manager.pop_outgoing_entity_event(0).is_some());
+        let stats = manager.get_queue_stats(ManagerType::Event);
+        assert!(stats.queued == 0 && stats.in_flight == 2);
+        assert!(stats.oldest_queued_age.is_none());
+
+        // only the guaranteed Events are queued again
+        manager.notify_packet_dropped(0);
+        let stats = manager.get_queue_stats(ManagerType::Event);
+        assert!(stats.queued == 1 && stats.in_flight == 0 && stats.retransmissions == 1);
+        assert!(
+            manager
+                .get_queue_stats(ManagerType::EntityEvent)
+                .retransmissions
+                == 1
+        );
+
+        // an Event type is tracked across every kind of message it's sent as
+        let stats = manager.get_event_type_queue_stats(TypeId::of::<TestEvent>());
+        assert!(stats.queued == 1 && stats.retransmissions == 1);
+        manager.queue_outgoing_relay_event(7, &TestEvent { guaranteed: true });
+        assert!(manager.pop_outgoing_relay_event(1).is_some());
+        let stats = manager.get_event_type_queue_stats(TypeId::of::<TestEvent>());
+        assert!(stats.queued == 1 && stats.in_flight == 1);
+        let stats = manager.get_event_type_queue_stats(TypeId::of::<LatestEvent>());
+        assert!(stats.queued == 1 && stats.in_flight == 0 && stats.retransmissions == 1);
+    }
+
+    #[test]
+    fn drops_queued_unguaranteed_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        manager.queue_outgoing_event(&TestEvent { guaranteed: false });
+        manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        assert!(pop_all(&mut manager, 0) == 2);
+        manager.queue_outgoing_event(&TestEvent { guaranteed: false });
+        manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        assert!(manager.get_undelivered_count() == 4);
+
+        // Events already in flight are left alone
+        manager.drop_queued_unguaranteed_events();
+        assert!(manager.get_undelivered_count() == 3);
+        manager.notify_packet_delivered(0);
+        assert!(manager.get_undelivered_count() == 1);
+        assert!(pop_all(&mut manager, 1) == 1);
+    }
+
+    #[test]
+    fn expired_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let stale = manager
+            .queue_outgoing_event_with_ttl(&TestEvent { guaranteed: true }, Duration::from_secs(0));
+        let fresh = manager.queue_outgoing_event_with_ttl(
+            &TestEvent { guaranteed: true },
+            Duration::from_secs(60),
+        );
+        assert!(manager.pop_outgoing_event(0).unwrap().0 == fresh);
+        assert!(manager.pop_expired_event_id() == Some(stale));
+        assert!(manager.pop_expired_event_id().is_none());
+
+        // a lost guaranteed Event is only resent within its time-to-live
+        let lost = manager.queue_outgoing_event_with_ttl(
+            &TestEvent { guaranteed: true },
+            Duration::from_millis(20),
+        );
+        assert!(manager.pop_outgoing_event(1).unwrap().0 == lost);
+        std::thread::sleep(Duration::from_millis(30));
+        manager.notify_packet_dropped(0);
+        manager.notify_packet_dropped(1);
+        assert!(manager.pop_outgoing_event(2).unwrap().0 == fresh);
+        assert!(manager.pop_outgoing_event(2).is_none());
+        assert!(manager.pop_expired_event_id() == Some(lost));
+        assert!(manager.get_undelivered_count() == 1);
+    }
+
+    #[test]
+    fn merged_events() {
+        let mut manager = EventManager::<TestEvents>::new(1);
+        let first = manager.queue_outgoing_event(&LatestEvent { value: 1 });
+        assert!(manager.queue_outgoing_event(&LatestEvent { value: 2 }) == first);
+        manager.queue_outgoing_event(&TestEvent { guaranteed: true });
+        assert!(manager.get_undelivered_count() == 2);
+
+        // Events of other types in between don't prevent merging
+        assert!(manager.queue_outgoing_event(&LatestEvent { value: 3 }) == first);
+        let (_, event) = manager.pop_outgoing_event(0).unwrap();
+        match event.get_typed_copy() {
+            TestEvents::LatestEvent(event) => assert!(event.value == 3),
+            _ => panic!("expected a LatestEvent"),
+        }
+
+        // an Event which has been sent is never merged into
+        assert!(pop_all(&mut manager, 0) == 1);
+        manager.notify_packet_dropped(0);
+        assert!(manager.queue_outgoing_event(&LatestEvent { value: 4 }) != first);
+        assert!(manager.get_undelivered_count() == 3);
     }
 }
@@ -0,0 +1,81 @@
+use std::{
+    fmt::{Debug, Formatter, Result},
+    rc::Rc,
+};
+
+/// An ordered chain of functions which every Event on a path (sent or
+/// received) passes through, given the key of the remote host. Each function
+/// can observe the Event, return a changed Event, or return None to drop it,
+/// in which case the rest of the chain isn't called
+pub struct EventMiddleware<K, T> {
+    functions: Vec<Rc<Box<dyn Fn(&K, T) -> Option<T>>>>,
+}
+
+impl<K, T> EventMiddleware<K, T> {
+    /// Creates a new, empty chain
+    pub fn new() -> Self {
+        EventMiddleware {
+            functions: Vec::new(),
+        }
+    }
+
+    /// Appends a function to the end of the chain
+    pub fn push(&mut self, function: Rc<Box<dyn Fn(&K, T) -> Option<T>>>) {
+        self.functions.push(function);
+    }
+
+    /// Returns whether the chain has no functions, so Events pass through it
+    /// unchanged
+    pub fn is_empty(&self) -> bool {
+        return self.functions.is_empty();
+    }
+
+    /// Passes an Event through every function of the chain in order,
+    /// returning the resulting Event, or None if a function dropped it
+    pub fn apply(&self, key: &K, event: T) -> Option<T> {
+        let mut event = event;
+        for function in self.functions.iter() {
+            event = (function.as_ref().as_ref())(key, event)?;
+        }
+        return Some(event);
+    }
+}
+
+impl<K, T> Debug for EventMiddleware<K, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        return write!(f, "EventMiddleware of {} functions", self.functions.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::EventMiddleware;
+
+    #[test]
+    fn chain_order() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let observed_clone = observed.clone();
+
+        let mut middleware = EventMiddleware::<u8, u8>::new();
+        assert!(middleware.is_empty());
+        assert!(middleware.apply(&0, 5) == Some(5));
+
+        middleware.push(Rc::new(Box::new(|_, event| Some(event * 2))));
+        middleware.push(Rc::new(Box::new(move |key, event| {
+            observed_clone.borrow_mut().push((*key, event));
+            return Some(event);
+        })));
+        middleware.push(Rc::new(Box::new(|_, event| {
+            if event > 10 {
+                return None;
+            }
+            return Some(event + 1);
+        })));
+
+        assert!(middleware.apply(&1, 3) == Some(7));
+        assert!(middleware.apply(&2, 6).is_none());
+        assert!(*observed.borrow() == vec![(1, 6), (2, 12)]);
+    }
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+/// Statistics about the outgoing messages of one kind, or of one Event type,
+/// which have yet to be delivered to a remote host. Used to detect when a
+/// particular class of message is backing up
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EventQueueStats {
+    /// The number of messages queued to be sent, including those queued again
+    /// to be retransmitted
+    pub queued: usize,
+    /// The number of messages which have been sent, & have been neither
+    /// acknowledged nor dropped
+    pub in_flight: usize,
+    /// How long the oldest queued message has been waiting to be sent, or
+    /// None if nothing is queued. A message queued again to be retransmitted
+    /// has waited since its packet was found to be lost
+    pub oldest_queued_age: Option<Duration>,
+    /// The number of times a message has been queued again to be
+    /// retransmitted, after the packet carrying it was lost
+    pub retransmissions: u64,
+}
@@ -1,5 +1,7 @@
 use std::any::TypeId;
 
+use super::event::Event;
+
 /// An Enum with a variant for every Event that can be sent to a remote host
 pub trait EventType: Clone {
     // write & get_type_id are ONLY currently used for reading/writing auth events..
@@ -8,4 +10,7 @@ pub trait EventType: Clone {
     fn write(&mut self, buffer: &mut Vec<u8>);
     /// Get the TypeId of the contained Event
     fn get_type_id(&self) -> TypeId;
+    /// Gets a boxed copy of the contained Event, as would be queued for
+    /// transmission
+    fn get_boxed_event(&self) -> Box<dyn Event<Self>>;
 }
@@ -1,4 +1,10 @@
+pub(crate) mod command_stats;
+pub(crate) mod dynamic_event;
 pub(crate) mod event;
 pub(crate) mod event_builder;
 pub(crate) mod event_manager;
+pub(crate) mod event_middleware;
+pub(crate) mod event_queue_stats;
 pub(crate) mod event_type;
+pub(crate) mod outgoing_queue;
+pub(crate) mod rpc_message;
@@ -0,0 +1,101 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::instant::Instant;
+
+/// A queue of outgoing messages, which remembers when each was queued, so
+/// that the time the oldest has been waiting to be sent can be reported
+#[derive(Debug)]
+pub struct OutgoingQueue<V> {
+    items: VecDeque<(Instant, V)>,
+    last_popped_at: Option<Instant>,
+}
+
+impl<V> OutgoingQueue<V> {
+    /// Creates a new, empty OutgoingQueue
+    pub fn new() -> Self {
+        OutgoingQueue {
+            items: VecDeque::new(),
+            last_popped_at: None,
+        }
+    }
+
+    /// Queues a message at the back of the queue
+    pub fn push_back(&mut self, item: V) {
+        self.items.push_back((Instant::now(), item));
+    }
+
+    /// Puts the last popped message back into the front of the queue, keeping
+    /// the time it was originally queued at
+    pub fn push_front(&mut self, item: V) {
+        let queued_at = self.last_popped_at.take().unwrap_or_else(Instant::now);
+        self.items.push_front((queued_at, item));
+    }
+
+    /// Takes the message at the front of the queue
+    pub fn pop_front(&mut self) -> Option<V> {
+        let (queued_at, item) = self.items.pop_front()?;
+        self.last_popped_at = Some(queued_at);
+        return Some(item);
+    }
+
+    /// Gets the message at the front of the queue
+    pub fn front(&self) -> Option<&V> {
+        return self.items.front().map(|(_, item)| item);
+    }
+
+    /// Gets the number of queued messages
+    pub fn len(&self) -> usize {
+        return self.items.len();
+    }
+
+    /// Returns whether no messages are queued
+    pub fn is_empty(&self) -> bool {
+        return self.items.is_empty();
+    }
+
+    /// Iterates over the queued messages, from the front
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &V> {
+        return self.items.iter().map(|(_, item)| item);
+    }
+
+    /// Iterates mutably over the queued messages, from the front
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> {
+        return self.items.iter_mut().map(|(_, item)| item);
+    }
+
+    /// Keeps only the queued messages for which the given function returns
+    /// true
+    pub fn retain<F: FnMut(&V) -> bool>(&mut self, mut f: F) {
+        self.items.retain(|(_, item)| f(item));
+    }
+
+    /// Gets how long the oldest of the queued messages for which the given
+    /// function returns true has been waiting, or None if there are none
+    pub fn get_oldest_age<F: Fn(&V) -> bool>(&self, f: F) -> Option<Duration> {
+        // messages put back into the front keep their original time, so the
+        // queue isn't strictly ordered by age
+        return self
+            .items
+            .iter()
+            .filter(|(_, item)| f(item))
+            .map(|(queued_at, _)| {
+                let elapsed = queued_at.elapsed();
+                Duration::new(elapsed.as_secs(), elapsed.subsec_nanos())
+            })
+            .max();
+    }
+}
+
+impl<V> Default for OutgoingQueue<V> {
+    fn default() -> Self {
+        return OutgoingQueue::new();
+    }
+}
+
+impl<V> Extend<V> for OutgoingQueue<V> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
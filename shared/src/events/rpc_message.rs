@@ -0,0 +1,56 @@
+use std::fmt::{Debug, Formatter, Result};
+
+/// Identifies an RPC request, so that the response sent by the remote host can
+/// be correlated with it
+pub type RequestId = u16;
+
+/// A message which is part of a request/response exchange. The payload is
+/// usually an Event, except in the case of an Error, which indicates that the
+/// remote host has rejected the request.
+#[derive(Clone)]
+pub enum RpcMessage<P> {
+    /// A request, which the remote host is expected to respond to
+    Request(RequestId, P),
+    /// A successful response to a previous request
+    Response(RequestId, P),
+    /// The remote host has rejected a previous request
+    Error(RequestId),
+}
+
+impl<P> RpcMessage<P> {
+    /// Gets the RequestId the message is correlated with
+    pub fn request_id(&self) -> RequestId {
+        match self {
+            RpcMessage::Request(request_id, _)
+            | RpcMessage::Response(request_id, _)
+            | RpcMessage::Error(request_id) => *request_id,
+        }
+    }
+
+    /// Gets the payload of the message, or None if it is an Error
+    pub fn payload(&self) -> Option<&P> {
+        match self {
+            RpcMessage::Request(_, payload) | RpcMessage::Response(_, payload) => Some(payload),
+            RpcMessage::Error(_) => None,
+        }
+    }
+
+    /// Gets the byte used to identify the kind of message on the wire
+    pub fn write_message_type(&self) -> u8 {
+        match self {
+            RpcMessage::Request(_, _) => 0,
+            RpcMessage::Response(_, _) => 1,
+            RpcMessage::Error(_) => 2,
+        }
+    }
+}
+
+impl<P> Debug for RpcMessage<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            RpcMessage::Request(request_id, _) => write!(f, "Request({})", request_id),
+            RpcMessage::Response(request_id, _) => write!(f, "Response({})", request_id),
+            RpcMessage::Error(request_id) => write!(f, "Error({})", request_id),
+        }
+    }
+}
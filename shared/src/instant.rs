@@ -9,7 +9,7 @@ cfg_if! {
         use js_sys::Date;
 
         /// Represents a specific moment in time
-        #[derive(Debug, Clone)]
+        #[derive(Debug, Clone, PartialEq)]
         pub struct Instant {
             inner: f64,
         }
@@ -34,7 +34,7 @@ cfg_if! {
     else {
         // Linux //
         /// Represents a specific moment in time
-        #[derive(Debug, Clone)]
+        #[derive(Debug, Clone, PartialEq)]
         pub struct Instant {
             inner: std::time::Instant,
         }
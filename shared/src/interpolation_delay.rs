@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use crate::rtt::rtt_history::RttStats;
+
+/// How long the target delay must stay below the current delay, by more than
+/// the hysteresis, before the delay is lowered. Raising the delay is immediate
+pub const INTERPOLATION_DELAY_HOLD: Duration = Duration::from_secs(2);
+
+/// Adapts how far behind the latest received state a Client renders, so that
+/// the state to interpolate toward has nearly always arrived. The delay is one
+/// tick interval, plus the jitter of the connection, measured as the spread
+/// between the median & 95th percentile Round Trip Time, plus a margin which
+/// grows with each reported buffer underrun & decays over time
+#[derive(Debug)]
+pub struct InterpolationDelay {
+    tick_interval: Duration,
+    hysteresis: Duration,
+    delay: Duration,
+    underrun_margin: Duration,
+    below_target_for: Duration,
+}
+
+impl InterpolationDelay {
+    /// Creates a new InterpolationDelay, given the duration of each tick & how
+    /// far the target delay must fall below the current delay before it is
+    /// lowered
+    pub fn new(tick_interval: Duration, hysteresis: Duration) -> Self {
+        InterpolationDelay {
+            tick_interval,
+            hysteresis,
+            // two ticks until the jitter of the connection has been measured
+            delay: tick_interval * 2,
+            underrun_margin: Duration::from_secs(0),
+            below_target_for: Duration::from_secs(0),
+        }
+    }
+
+    /// Adjusts the delay toward its target, given the time elapsed since the
+    /// last update, and the most recent Round Trip Time statistics
+    pub fn update(&mut self, elapsed: Duration, rtt_stats: Option<&RttStats>) {
+        // the margin from underruns decays at an eighth of real time
+        self.underrun_margin = self
+            .underrun_margin
+            .checked_sub(elapsed / 8)
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        let rtt_stats = match rtt_stats {
+            Some(rtt_stats) => rtt_stats,
+            None => return,
+        };
+        let jitter_ms = (rtt_stats.p95 - rtt_stats.median).max(0.0);
+        let jitter = Duration::from_micros((jitter_ms * 1000.0).round() as u64);
+        let target = self.tick_interval + jitter + self.underrun_margin;
+
+        if target > self.delay {
+            self.delay = target;
+            self.below_target_for = Duration::from_secs(0);
+        } else if target + self.hysteresis < self.delay {
+            self.below_target_for += elapsed;
+            if self.below_target_for >= INTERPOLATION_DELAY_HOLD {
+                self.delay = target;
+                self.below_target_for = Duration::from_secs(0);
+            }
+        } else {
+            self.below_target_for = Duration::from_secs(0);
+        }
+    }
+
+    /// Sets the duration of each tick, after the Server has changed its tick
+    /// rate. A longer tick raises the delay at once, by the difference, while
+    /// a shorter one lowers the target, which the delay follows once it has
+    /// held
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        if tick_interval > self.tick_interval {
+            self.delay += tick_interval - self.tick_interval;
+            self.below_target_for = Duration::from_secs(0);
+        }
+        self.tick_interval = tick_interval;
+    }
+
+    /// Records that the state to interpolate toward had not arrived in time,
+    /// raising the delay immediately by a quarter of a tick
+    pub fn record_underrun(&mut self) {
+        let step = self.tick_interval / 4;
+        if self.underrun_margin < self.tick_interval * 4 {
+            self.underrun_margin += step;
+        }
+        self.delay += step;
+        self.below_target_for = Duration::from_secs(0);
+    }
+
+    /// Gets the current interpolation delay
+    pub fn get_delay(&self) -> Duration {
+        return self.delay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{InterpolationDelay, RttStats, INTERPOLATION_DELAY_HOLD};
+
+    fn stats(median: f32, p95: f32) -> RttStats {
+        return RttStats {
+            sample_count: 64,
+            min: median,
+            median,
+            p95,
+            max: p95,
+        };
+    }
+
+    const FRAME: Duration = Duration::from_millis(10);
+    const TICK: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn follows_jitter() {
+        let mut delay = InterpolationDelay::new(TICK, Duration::from_millis(5));
+        delay.update(FRAME, Some(&stats(40.0, 80.0)));
+        assert!(delay.get_delay() == TICK * 2);
+
+        delay.update(FRAME, Some(&stats(40.0, 140.0)));
+        assert!(delay.get_delay() == Duration::from_millis(150));
+
+        // lowered only once the jitter has stayed low for the hold duration
+        let mut elapsed = Duration::from_secs(0);
+        while elapsed < INTERPOLATION_DELAY_HOLD - FRAME {
+            delay.update(FRAME, Some(&stats(40.0, 50.0)));
+            assert!(delay.get_delay() == Duration::from_millis(150));
+            elapsed += FRAME;
+        }
+        delay.update(FRAME, Some(&stats(40.0, 50.0)));
+        assert!(delay.get_delay() == Duration::from_millis(60));
+    }
+
+    #[test]
+    fn tick_interval_change() {
+        let mut delay = InterpolationDelay::new(TICK, Duration::from_millis(5));
+        delay.update(FRAME, Some(&stats(40.0, 50.0)));
+        assert!(delay.get_delay() == TICK * 2);
+
+        // a slower tick rate raises the delay straight away
+        delay.set_tick_interval(TICK * 2);
+        assert!(delay.get_delay() == TICK * 3);
+
+        // & a faster one lowers it after the hold
+        delay.set_tick_interval(TICK / 2);
+        assert!(delay.get_delay() == TICK * 3);
+        let mut elapsed = Duration::from_secs(0);
+        while elapsed <= INTERPOLATION_DELAY_HOLD {
+            delay.update(FRAME, Some(&stats(40.0, 50.0)));
+            elapsed += FRAME;
+        }
+        assert!(delay.get_delay() == Duration::from_millis(35));
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut delay = InterpolationDelay::new(TICK, Duration::from_millis(5));
+        delay.update(FRAME, Some(&stats(40.0, 60.0)));
+        assert!(delay.get_delay() == TICK * 2);
+        for _ in 0..1000 {
+            delay.update(FRAME, Some(&stats(40.0, 60.0)));
+        }
+        assert!(delay.get_delay() == Duration::from_millis(70));
+
+        // small dips within the hysteresis never lower the delay
+        for _ in 0..1000 {
+            delay.update(FRAME, Some(&stats(40.0, 56.0)));
+        }
+        assert!(delay.get_delay() == Duration::from_millis(70));
+    }
+
+    #[test]
+    fn underruns() {
+        let mut delay = InterpolationDelay::new(TICK, Duration::from_millis(5));
+        for _ in 0..1000 {
+            delay.update(FRAME, Some(&stats(40.0, 40.0)));
+        }
+        assert!(delay.get_delay() == TICK);
+
+        delay.record_underrun();
+        delay.record_underrun();
+        assert!(delay.get_delay() == TICK + TICK / 2);
+
+        // the margin decays, and the delay eventually returns to a single tick
+        for _ in 0..1000 {
+            delay.update(FRAME, Some(&stats(40.0, 40.0)));
+        }
+        assert!(delay.get_delay() == TICK);
+    }
+}
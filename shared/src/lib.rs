@@ -16,48 +16,151 @@ extern crate log;
 #[macro_use]
 extern crate cfg_if;
 
+mod ack_latency;
 mod ack_manager;
+mod address_family;
+mod backpressure;
+mod blobs;
+mod capabilities;
+mod client_id;
+mod client_roster;
+mod clock_sync;
+mod compression;
 mod config;
 mod connection;
+mod connection_quality;
+mod connection_stats;
+mod custom_section;
 mod duration;
 mod entities;
+mod error;
 mod events;
 mod instant;
+mod interpolation_delay;
+mod link_conditioner;
+mod link_stall;
 mod manager_type;
 mod manifest;
+mod manifest_descriptor;
+mod packet_framing;
 mod packet_reader;
 mod packet_type;
 mod packet_writer;
+mod peer_id;
+mod physics;
+mod protocol_doc;
+mod random_source;
+mod raw_payload_manager;
+mod reorder_buffer;
+mod replay_window;
 mod rtt;
 mod sequence_buffer;
+mod server_list;
+mod server_status;
 mod standard_header;
+mod tick_accumulator;
+mod tick_rate_change;
+mod time_dilation;
 mod timestamp;
+mod unconnected_message;
+#[cfg(test)]
+mod wire_vectors;
 
 /// Commonly used utility methods to be used by naia-server & naia-client
 pub mod utils;
 
 pub use naia_socket_shared::{find_my_ip_address, Timer};
 
+pub use ack_latency::{AckLatencyHistogram, ACK_LATENCY_BUCKET_COUNT, ACK_LATENCY_BUCKET_WIDTH};
 pub use ack_manager::AckManager;
+pub use address_family::{
+    find_my_ip_address_in, resolve_address, to_canonical_address, AddressFamily,
+};
+pub use backpressure::{BackpressureMonitor, BackpressureThresholds, Congestion};
+pub use blobs::{
+    blob_manager::{BlobChunk, BlobManager, BLOB_CHUNK_SIZE},
+    blob_message::{BlobId, BlobMessage},
+};
+pub use capabilities::Capabilities;
+pub use client_id::ClientId;
+pub use client_roster::{ClientRoster, ClientRosterChange};
+pub use clock_sync::{ClockSync, CLOCK_SYNC_SAMPLE_COUNT, MAX_CLOCK_SKEW};
+pub use compression::{compress, decompress};
 pub use config::Config;
-pub use connection::Connection;
+pub use connection::{Connection, MAX_HEARTBEAT_PAYLOAD_SIZE};
+pub use connection_quality::{
+    ConnectionQuality, ConnectionQualityMonitor, ConnectionQualityThresholds, QualityThreshold,
+    CONNECTION_QUALITY_HOLD,
+};
+pub use connection_stats::{ConnectionStats, DropLog, DropReason, DROP_WARNING_INTERVAL};
+pub use custom_section::{CustomSection, CustomSections, MAX_CUSTOM_SECTION_SIZE};
 pub use entities::{
-    entity::Entity, entity_builder::EntityBuilder, entity_mutator::EntityMutator,
-    entity_notifiable::EntityNotifiable, entity_type::EntityType, local_entity_key::LocalEntityKey,
-    property::Property, property_io::PropertyIo, state_mask::StateMask,
+    correction::{Correction, CorrectionCurve},
+    entity::{DespawnReason, Entity, PredictionKey, PrefabId, BATCHED_ENTITY_MESSAGE_FLAG},
+    entity_builder::EntityBuilder,
+    entity_debug_dump::{
+        diff_entity_dumps, EntityDebugDump, EntityDebugRecord, EntityDebugStatus, EntityDiff,
+    },
+    entity_mutator::EntityMutator,
+    entity_notifiable::EntityNotifiable,
+    entity_type::EntityType,
+    extrapolation::{Extrapolation, ExtrapolationModel, MotionSample, MotionSampler},
+    late_update::{LateUpdatePolicy, LateUpdateStats, PropertyTicks, MAX_LATE_UPDATE_TICKS},
+    local_entity_key::LocalEntityKey,
+    property::Property,
+    property_io::PropertyIo,
+    replicated_animation::ReplicatedAnimation,
+    replicated_collection::{CollectionChange, CollectionRevision, ReplicatedCollection},
+    replicated_transform::{ReplicatedTransform, TransformAxes, DEFAULT_TRANSFORM_FRACTION_BITS},
+    state_mask::StateMask,
 };
+pub use error::NaiaError;
 pub use events::{
-    event::{Event, EventClone},
+    command_stats::CommandStats,
+    dynamic_event::{DynamicEvent, DynamicEventBuilder, DynamicValue, MAX_DYNAMIC_KEY_LENGTH},
+    event::{Event, EventClone, EventId},
     event_builder::EventBuilder,
     event_manager::EventManager,
+    event_middleware::EventMiddleware,
+    event_queue_stats::EventQueueStats,
     event_type::EventType,
+    rpc_message::{RequestId, RpcMessage},
 };
 pub use instant::Instant;
+pub use interpolation_delay::{InterpolationDelay, INTERPOLATION_DELAY_HOLD};
+pub use link_conditioner::{LinkConditioner, LinkConditionerConfig};
+pub use link_stall::{LinkState, StallMonitor, StallThresholds};
 pub use manager_type::ManagerType;
 pub use manifest::Manifest;
+pub use manifest_descriptor::{
+    CompatibilityReport, ManifestDescriptor, PropertyDescriptor, TypeDescriptor,
+};
+pub use packet_framing::{PacketFraming, PacketFramingFactory, SectionFraming};
 pub use packet_reader::PacketReader;
 pub use packet_type::PacketType;
-pub use packet_writer::{PacketWriter, MTU_SIZE};
-pub use rtt::rtt_tracker::RttTracker;
-pub use sequence_buffer::SequenceNumber;
+pub use packet_writer::{PacketWriter, MAX_EVENT_SIZE, MAX_SHUTDOWN_REASON_LENGTH, MTU_SIZE};
+pub use peer_id::{assign_peer_ids, PeerId};
+#[cfg(feature = "rapier")]
+pub use physics::rapier_adapter::{read_rigid_body_state, write_rigid_body_state, RapierBridge};
+pub use physics::{rigid_body_state::RigidBodyState, rollback_history::RollbackHistory};
+pub use protocol_doc::ProtocolDocFormat;
+pub use random_source::RandomSource;
+pub use raw_payload_manager::{RawPayloadManager, MAX_RAW_PAYLOAD_SIZE};
+pub use reorder_buffer::ReorderBuffer;
+pub use replay_window::{ReplayWindow, REPLAY_WINDOW_SIZE};
+pub use rtt::{rtt_history::RttStats, rtt_tracker::RttTracker};
+pub use sequence_buffer::{
+    expand_sequence, sequence_greater_than, sequence_less_than, SequenceBuffer, SequenceHandle,
+    SequenceNumber,
+};
+pub use server_list::{ServerListPage, MAX_REGISTERED_STATUS_SIZE};
+pub use server_status::ServerStatus;
+pub use tick_accumulator::{TickAccumulator, MAX_TICKS_PER_FRAME};
+pub use tick_rate_change::TickRateChange;
+pub use time_dilation::{
+    time_dilation_scale, TimeDilation, MAX_TIME_DILATION, TIME_DILATION_PER_TICK,
+};
 pub use timestamp::Timestamp;
+pub use unconnected_message::{
+    UnconnectedKind, UnconnectedMessage, MAX_UNCONNECTED_DATA_SIZE, MAX_UNCONNECTED_MESSAGE_SIZE,
+};
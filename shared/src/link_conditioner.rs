@@ -0,0 +1,73 @@
+/// Configures a LinkConditioner, which simulates a lossy network for testing
+#[derive(Clone, Debug)]
+pub struct LinkConditionerConfig {
+    /// The probability that an incoming packet is dropped, from 0 to 1
+    pub incoming_packet_loss: f32,
+    /// The seed for the random sequence of drops, so that a test run can be
+    /// reproduced
+    pub seed: u64,
+}
+
+/// Drops a portion of incoming packets before they are processed, to simulate
+/// a lossy network. Used to test how an application holds up under loss, and
+/// should not be enabled in production
+#[derive(Debug)]
+pub struct LinkConditioner {
+    incoming_packet_loss: f32,
+    random_state: u64,
+}
+
+impl LinkConditioner {
+    /// Creates a new LinkConditioner, given its configuration
+    pub fn new(config: &LinkConditionerConfig) -> Self {
+        LinkConditioner {
+            incoming_packet_loss: config.incoming_packet_loss,
+            // the xorshift state must never be zero
+            random_state: config.seed.max(1),
+        }
+    }
+
+    /// Returns whether the next incoming packet should be dropped
+    pub fn should_drop_incoming(&mut self) -> bool {
+        return self.next_random() < self.incoming_packet_loss;
+    }
+
+    // A uniformly distributed number from 0 to 1, from an xorshift generator
+    fn next_random(&mut self) -> f32 {
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 7;
+        self.random_state ^= self.random_state << 17;
+        return (self.random_state >> 40) as f32 / (1u64 << 24) as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkConditioner, LinkConditionerConfig};
+
+    fn drop_count(incoming_packet_loss: f32) -> usize {
+        let mut conditioner = LinkConditioner::new(&LinkConditionerConfig {
+            incoming_packet_loss,
+            seed: 42,
+        });
+        return (0..10000)
+            .filter(|_| conditioner.should_drop_incoming())
+            .count();
+    }
+
+    #[test]
+    fn no_loss() {
+        assert!(drop_count(0.0) == 0);
+    }
+
+    #[test]
+    fn total_loss() {
+        assert!(drop_count(1.0) == 10000);
+    }
+
+    #[test]
+    fn partial_loss() {
+        let dropped = drop_count(0.25);
+        assert!(dropped > 2000 && dropped < 3000);
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+/// The state of the link to a remote host, judged from how its
+/// acknowledgements are arriving, which determines how often Entity updates
+/// are sent to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    /// Packets are being acknowledged, so Entity updates are sent every tick
+    Healthy,
+    /// Packet loss has reached `StallThresholds::degraded_packet_loss`, so
+    /// Entity updates are only sent every
+    /// `StallThresholds::degraded_update_interval` ticks
+    Degraded,
+    /// No packet has been acknowledged for `StallThresholds::ack_timeout`, so
+    /// Entity updates are paused, while heartbeats keep being sent
+    Stalled,
+}
+
+/// The thresholds at which the Entity updates sent over a link are reduced or
+/// paused, and at which the link has recovered. Mutations made while updates
+/// are held back accumulate, and are sent once they resume
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StallThresholds {
+    /// The fraction of recently sent packets which were lost, from 0 to 1, at
+    /// which the link is degraded
+    pub degraded_packet_loss: f32,
+    /// The fraction of recently sent packets which were lost at or below which
+    /// a degraded link has recovered. Lower than `degraded_packet_loss`, so
+    /// that a link hovering around a single loss doesn't flicker between the
+    /// two
+    pub recovered_packet_loss: f32,
+    /// The number of ticks between the Entity updates sent over a degraded
+    /// link
+    pub degraded_update_interval: u16,
+    /// The age of the oldest unacknowledged packet at which the link is
+    /// stalled. Should be longer than the remote host's heartbeat interval,
+    /// as a host which sends nothing else only acknowledges packets with its
+    /// heartbeats
+    pub ack_timeout: Duration,
+}
+
+impl Default for StallThresholds {
+    fn default() -> Self {
+        StallThresholds {
+            degraded_packet_loss: 0.25,
+            recovered_packet_loss: 0.1,
+            degraded_update_interval: 4,
+            ack_timeout: Duration::from_secs(6),
+        }
+    }
+}
+
+/// Tracks the state of a link, from its packet loss & the age of its oldest
+/// unacknowledged packet
+#[derive(Debug)]
+pub struct StallMonitor {
+    thresholds: Option<StallThresholds>,
+    state: LinkState,
+}
+
+impl StallMonitor {
+    /// Creates a new StallMonitor, given the thresholds of the link states, or
+    /// None to always consider the link healthy
+    pub fn new(thresholds: Option<StallThresholds>) -> Self {
+        StallMonitor {
+            thresholds,
+            state: LinkState::Healthy,
+        }
+    }
+
+    /// Records the current packet loss & the age of the oldest packet which
+    /// hasn't been acknowledged, and returns the new state of the link, if it
+    /// has changed
+    pub fn update(
+        &mut self,
+        packet_loss: Option<f32>,
+        oldest_unacked_age: Option<Duration>,
+    ) -> Option<LinkState> {
+        let thresholds = match &self.thresholds {
+            Some(thresholds) => thresholds,
+            None => return None,
+        };
+        let stalled = oldest_unacked_age.map_or(false, |age| age >= thresholds.ack_timeout);
+        let packet_loss = packet_loss.unwrap_or(0.0);
+        let state = if stalled {
+            LinkState::Stalled
+        } else if packet_loss >= thresholds.degraded_packet_loss {
+            LinkState::Degraded
+        } else if packet_loss <= thresholds.recovered_packet_loss {
+            LinkState::Healthy
+        } else {
+            // between the thresholds, a link which was held back stays so
+            match self.state {
+                LinkState::Healthy => LinkState::Healthy,
+                _ => LinkState::Degraded,
+            }
+        };
+        if state == self.state {
+            return None;
+        }
+        self.state = state;
+        return Some(state);
+    }
+
+    /// Gets the current state of the link
+    pub fn get_state(&self) -> LinkState {
+        return self.state;
+    }
+
+    /// Returns whether Entity updates should be sent over the link on the
+    /// given tick
+    pub fn should_send_updates(&self, tick: u16) -> bool {
+        match (self.state, &self.thresholds) {
+            (LinkState::Healthy, _) | (_, None) => return true,
+            (LinkState::Degraded, Some(thresholds)) => {
+                return tick % thresholds.degraded_update_interval.max(1) == 0;
+            }
+            (LinkState::Stalled, _) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LinkState, StallMonitor, StallThresholds};
+
+    #[test]
+    fn link_states() {
+        let mut monitor = StallMonitor::new(Some(StallThresholds {
+            degraded_packet_loss: 0.3,
+            recovered_packet_loss: 0.1,
+            degraded_update_interval: 4,
+            ack_timeout: Duration::from_secs(2),
+        }));
+        let fresh = Some(Duration::from_millis(100));
+        assert!(monitor.update(None, None).is_none());
+        assert!(monitor.update(Some(0.2), fresh).is_none());
+        assert!(monitor.should_send_updates(1));
+
+        assert!(monitor.update(Some(0.3), fresh) == Some(LinkState::Degraded));
+        assert!(monitor.should_send_updates(8) && !monitor.should_send_updates(9));
+
+        // still degraded until the loss falls to the lower threshold
+        assert!(monitor.update(Some(0.2), fresh).is_none());
+        assert!(
+            monitor.update(Some(0.2), Some(Duration::from_secs(2))) == Some(LinkState::Stalled)
+        );
+        assert!(!monitor.should_send_updates(8));
+        assert!(monitor.update(Some(0.2), fresh) == Some(LinkState::Degraded));
+        assert!(monitor.update(Some(0.1), fresh) == Some(LinkState::Healthy));
+        assert!(monitor.get_state() == LinkState::Healthy);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut monitor = StallMonitor::new(None);
+        assert!(monitor
+            .update(Some(1.0), Some(Duration::from_secs(60)))
+            .is_none());
+        assert!(monitor.should_send_updates(1));
+    }
+}
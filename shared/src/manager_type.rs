@@ -1,13 +1,44 @@
 /// Every data packet transmitted has data specific to either the Event or
 /// Entity managers. This value is written to differentiate those parts of the
 /// payload.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum ManagerType {
     /// An EventManager
     Event = 1,
     /// An EntityManager
     Entity = 2,
+    /// Request/response messages, handled by the EventManager
+    Rpc = 3,
+    /// Events scheduled for a specific Server tick, handled by the EventManager
+    TimedEvent = 4,
+    /// Chunks of large blobs, handled by the BlobManager
+    Blob = 5,
+    /// Opaque raw payloads, handled by the RawPayloadManager
+    Raw = 6,
+    /// Per-tick commands, each sent redundantly in several packets, handled
+    /// by the EventManager
+    Command = 7,
+    /// Application-defined sections, each written & read by a registered
+    /// CustomSection
+    Custom = 8,
+    /// Events targeted at a specific Entity, handled by the EventManager
+    EntityEvent = 9,
+    /// Events relayed by the Server from one Client to another, handled by
+    /// the EventManager
+    RelayEvent = 10,
+    /// Changes to the set of connected Clients, sent by the Server & handled
+    /// by the EventManager
+    ClientRoster = 11,
+    /// Descriptions of the Event & Entity types the Server has registered
+    /// since the connection was established, handled by the EventManager
+    ManifestDelta = 12,
+    /// Notices that the Server is shutting down, along with the reason,
+    /// handled by the EventManager
+    Shutdown = 13,
+    /// Changes to the rate at which the Server ticks, handled by the
+    /// EventManager
+    TickRate = 14,
     /// Unknown Manager
     Unknown = 255,
 }
@@ -17,6 +48,18 @@ impl From<u8> for ManagerType {
         match orig {
             1 => return ManagerType::Event,
             2 => return ManagerType::Entity,
+            3 => return ManagerType::Rpc,
+            4 => return ManagerType::TimedEvent,
+            5 => return ManagerType::Blob,
+            6 => return ManagerType::Raw,
+            7 => return ManagerType::Command,
+            8 => return ManagerType::Custom,
+            9 => return ManagerType::EntityEvent,
+            10 => return ManagerType::RelayEvent,
+            11 => return ManagerType::ClientRoster,
+            12 => return ManagerType::ManifestDelta,
+            13 => return ManagerType::Shutdown,
+            14 => return ManagerType::TickRate,
             _ => return ManagerType::Unknown,
         };
     }
@@ -1,8 +1,28 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use crate::{
-    entities::{entity_builder::EntityBuilder, entity_type::EntityType},
-    events::{event_builder::EventBuilder, event_type::EventType},
+    capabilities::Capabilities,
+    compression::{compress, decompress},
+    config::Config,
+    connection_stats::DropReason,
+    entities::{
+        entity::{Entity, PrefabId},
+        entity_builder::EntityBuilder,
+        entity_type::EntityType,
+        state_mask::StateMask,
+    },
+    error::NaiaError,
+    events::{event::Event, event_builder::EventBuilder, event_type::EventType},
+    manifest_descriptor::{ManifestDescriptor, PropertyDescriptor, TypeDescriptor},
+    packet_framing::SECTION_ORDER,
+    packet_writer::{MAX_EVENT_SIZE, MTU_SIZE},
+    protocol_doc::{ProtocolDoc, ProtocolDocFormat},
+    standard_header::StandardHeader,
 };
 
 /// Contains the shared protocol between Client & Server, with a data that is
@@ -14,10 +34,13 @@ pub struct Manifest<T: EventType, U: EntityType> {
     event_naia_id_count: u16,
     event_builder_map: HashMap<u16, Box<dyn EventBuilder<T>>>,
     event_type_map: HashMap<TypeId, u16>,
+    auth_event_naia_ids: HashSet<u16>,
+    compressed_event_naia_ids: HashSet<u16>,
     ////
     entity_naia_id_count: u16,
     entity_builder_map: HashMap<u16, Box<dyn EntityBuilder<U>>>,
     entity_type_map: HashMap<TypeId, u16>,
+    prefab_map: HashMap<PrefabId, Rc<RefCell<dyn Entity<U>>>>,
 }
 
 impl<T: EventType, U: EntityType> Manifest<T, U> {
@@ -27,20 +50,160 @@ impl<T: EventType, U: EntityType> Manifest<T, U> {
             event_naia_id_count: 0,
             event_builder_map: HashMap::new(),
             event_type_map: HashMap::new(),
+            auth_event_naia_ids: HashSet::new(),
+            compressed_event_naia_ids: HashSet::new(),
             ///
             entity_naia_id_count: 0,
             entity_builder_map: HashMap::new(),
             entity_type_map: HashMap::new(),
+            prefab_map: HashMap::new(),
         }
     }
 
-    /// Register an EventBuilder to handle the creation of Event instances
+    /// Register an EventBuilder to handle the creation of Event instances. The
+    /// Event is given the lowest NaiaId which has not yet been registered, so
+    /// NaiaIds depend on registration order. Use `register_event_with_id()`
+    /// to keep NaiaIds stable as Events are added.
     pub fn register_event(&mut self, event_builder: Box<dyn EventBuilder<T>>) {
+        while self
+            .event_builder_map
+            .contains_key(&self.event_naia_id_count)
+        {
+            self.event_naia_id_count += 1;
+        }
         let new_naia_id = self.event_naia_id_count;
+        self.register_event_with_id(new_naia_id, event_builder);
+    }
+
+    /// Register an EventBuilder to handle the creation of Event instances,
+    /// with an explicit NaiaId which will stay the same regardless of the
+    /// order in which Events are registered. Panics if the NaiaId or the Event
+    /// type has already been registered.
+    pub fn register_event_with_id(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) {
+        let type_id = event_builder.get_type_id();
+        if self.event_builder_map.contains_key(&naia_id) {
+            panic!("Event NaiaId {} has already been registered", naia_id);
+        }
+        if self.event_type_map.contains_key(&type_id) {
+            panic!("Event type has already been registered");
+        }
+        self.event_type_map.insert(type_id, naia_id);
+        self.event_builder_map.insert(naia_id, event_builder);
+    }
+
+    /// Register an EventBuilder with an explicit NaiaId, like
+    /// `register_event_with_id()`, but returning an error rather than
+    /// panicking if the NaiaId or the Event type has already been registered,
+    /// for types registered at runtime, such as by a mod
+    pub fn try_register_event_with_id(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) -> Result<(), NaiaError> {
+        if self.event_builder_map.contains_key(&naia_id)
+            || self
+                .event_type_map
+                .contains_key(&event_builder.get_type_id())
+        {
+            return Err(NaiaError::AlreadyRegistered);
+        }
+        self.register_event_with_id(naia_id, event_builder);
+        return Ok(());
+    }
+
+    /// Register an EventBuilder to handle the creation of Event instances which
+    /// may be sent during the connection handshake. Only Events registered this
+    /// way are accepted by the Server before a connection is established.
+    pub fn register_auth_event(&mut self, event_builder: Box<dyn EventBuilder<T>>) {
         let type_id = event_builder.get_type_id();
-        self.event_type_map.insert(type_id, new_naia_id);
-        self.event_builder_map.insert(new_naia_id, event_builder);
-        self.event_naia_id_count += 1;
+        self.register_event(event_builder);
+        let naia_id = self.get_event_naia_id(&type_id);
+        self.auth_event_naia_ids.insert(naia_id);
+    }
+
+    /// Register an EventBuilder to handle the creation of auth Event
+    /// instances, with an explicit NaiaId (see `register_event_with_id()`)
+    pub fn register_auth_event_with_id(
+        &mut self,
+        naia_id: u16,
+        event_builder: Box<dyn EventBuilder<T>>,
+    ) {
+        self.register_event_with_id(naia_id, event_builder);
+        self.auth_event_naia_ids.insert(naia_id);
+    }
+
+    /// Returns whether the Event with the given NaiaId has been registered as
+    /// an auth Event
+    pub fn is_auth_event(&self, naia_id: u16) -> bool {
+        return self.auth_event_naia_ids.contains(&naia_id);
+    }
+
+    /// Enables compression of the payload of every Event of the given type,
+    /// wherever it is sent: as an Event, an RPC message, a timed Event or a
+    /// command. Compression suits Events with long or repetitive payloads,
+    /// such as chat messages, but wastes CPU on small ones, such as quantized
+    /// positions, so it is disabled by default. Whether each type is
+    /// compressed is exchanged during the connection handshake, and must
+    /// match on Client & Server. Panics if the type hasn't been registered
+    pub fn enable_event_compression(&mut self, type_id: &TypeId) {
+        let naia_id = self.get_event_naia_id(type_id);
+        self.compressed_event_naia_ids.insert(naia_id);
+    }
+
+    /// Returns whether the payload of the Event with the given NaiaId is
+    /// compressed
+    pub fn is_event_compressed(&self, naia_id: u16) -> bool {
+        return self.compressed_event_naia_ids.contains(&naia_id);
+    }
+
+    /// Writes the payload of an Event to be sent, compressing it if its type
+    /// has compression enabled
+    pub fn write_event_payload(&self, naia_id: u16, event: &dyn Event<T>) -> Vec<u8> {
+        let mut payload_bytes = Vec::new();
+        event.write(&mut payload_bytes);
+        if self.is_event_compressed(naia_id) {
+            return compress(&payload_bytes);
+        }
+        return payload_bytes;
+    }
+
+    /// Creates an Event instance, given a NaiaId and a payload written with
+    /// `write_event_payload()`, from an incoming packet. Returns the reason
+    /// the Event should be dropped if the NaiaId isn't registered, or the
+    /// payload can't be decompressed or read
+    pub fn read_event(&self, naia_id: u16, bytes: &[u8]) -> Result<T, DropReason> {
+        let event_builder = self
+            .event_builder_map
+            .get(&naia_id)
+            .ok_or(DropReason::UnknownNaiaId)?;
+        if !self.is_event_compressed(naia_id) {
+            return event_builder
+                .build(bytes)
+                .ok_or(DropReason::MalformedPayload);
+        }
+        let payload_bytes = decompress(bytes).ok_or(DropReason::MalformedPayload)?;
+        return event_builder
+            .build(&payload_bytes)
+            .ok_or(DropReason::MalformedPayload);
+    }
+
+    /// Checks that an Event can be sent, that is, that its type has been
+    /// registered, & that its payload is at most `MAX_EVENT_SIZE` bytes, once
+    /// compressed if its type has compression enabled
+    pub fn validate_event(&self, event: &dyn Event<T>) -> Result<(), NaiaError> {
+        let naia_id = match self.event_type_map.get(&event.get_type_id()) {
+            Some(naia_id) => *naia_id,
+            None => return Err(NaiaError::UnregisteredEvent),
+        };
+        let event_bytes = self.write_event_payload(naia_id, event);
+        if event_bytes.len() > MAX_EVENT_SIZE {
+            return Err(NaiaError::EventTooLarge(event_bytes.len()));
+        }
+        return Ok(());
     }
 
     /// Given an Event's TypeId, get a NaiaId (that can be written/read from
@@ -54,11 +217,12 @@ impl<T: EventType, U: EntityType> Manifest<T, U> {
     }
 
     /// Creates an Event instance, given a NaiaId and a payload, typically from
-    /// an incoming packet
+    /// an incoming packet. Returns None if the NaiaId isn't registered, or the
+    /// payload can't be read
     pub fn create_event(&self, naia_id: u16, bytes: &[u8]) -> Option<T> {
         match self.event_builder_map.get(&naia_id) {
             Some(event_builder) => {
-                return Some(event_builder.as_ref().build(bytes));
+                return event_builder.as_ref().build(bytes);
             }
             None => {}
         }
@@ -66,13 +230,71 @@ impl<T: EventType, U: EntityType> Manifest<T, U> {
         return None;
     }
 
-    /// Register an EntityBuilder to handle the creation of Entity instances
+    /// Creates an auth Event instance, given a NaiaId and a payload, typically
+    /// from an incoming connection request. Returns None if the NaiaId does not
+    /// belong to an Event registered with `register_auth_event()`, without
+    /// deserializing the payload.
+    pub fn create_auth_event(&self, naia_id: u16, bytes: &[u8]) -> Option<T> {
+        if !self.is_auth_event(naia_id) {
+            return None;
+        }
+        return self.create_event(naia_id, bytes);
+    }
+
+    /// Register an EntityBuilder to handle the creation of Entity instances.
+    /// The Entity is given the lowest NaiaId which has not yet been
+    /// registered, so NaiaIds depend on registration order. Use
+    /// `register_entity_with_id()` to keep NaiaIds stable as Entities are
+    /// added.
     pub fn register_entity(&mut self, entity_builder: Box<dyn EntityBuilder<U>>) {
+        while self
+            .entity_builder_map
+            .contains_key(&self.entity_naia_id_count)
+        {
+            self.entity_naia_id_count += 1;
+        }
         let new_naia_id = self.entity_naia_id_count;
+        self.register_entity_with_id(new_naia_id, entity_builder);
+    }
+
+    /// Register an EntityBuilder to handle the creation of Entity instances,
+    /// with an explicit NaiaId which will stay the same regardless of the
+    /// order in which Entities are registered. Panics if the NaiaId or the
+    /// Entity type has already been registered.
+    pub fn register_entity_with_id(
+        &mut self,
+        naia_id: u16,
+        entity_builder: Box<dyn EntityBuilder<U>>,
+    ) {
         let type_id = entity_builder.get_type_id();
-        self.entity_type_map.insert(type_id, new_naia_id);
-        self.entity_builder_map.insert(new_naia_id, entity_builder);
-        self.entity_naia_id_count += 1;
+        if self.entity_builder_map.contains_key(&naia_id) {
+            panic!("Entity NaiaId {} has already been registered", naia_id);
+        }
+        if self.entity_type_map.contains_key(&type_id) {
+            panic!("Entity type has already been registered");
+        }
+        self.entity_type_map.insert(type_id, naia_id);
+        self.entity_builder_map.insert(naia_id, entity_builder);
+    }
+
+    /// Register an EntityBuilder with an explicit NaiaId, like
+    /// `register_entity_with_id()`, but returning an error rather than
+    /// panicking if the NaiaId or the Entity type has already been
+    /// registered, for types registered at runtime, such as by a mod
+    pub fn try_register_entity_with_id(
+        &mut self,
+        naia_id: u16,
+        entity_builder: Box<dyn EntityBuilder<U>>,
+    ) -> Result<(), NaiaError> {
+        if self.entity_builder_map.contains_key(&naia_id)
+            || self
+                .entity_type_map
+                .contains_key(&entity_builder.get_type_id())
+        {
+            return Err(NaiaError::AlreadyRegistered);
+        }
+        self.register_entity_with_id(naia_id, entity_builder);
+        return Ok(());
     }
 
     /// Given an Entity's TypeId, get a NaiaId (that can be written/read from
@@ -85,16 +307,381 @@ impl<T: EventType, U: EntityType> Manifest<T, U> {
         return *naia_id;
     }
 
-    /// Creates an Event instance, given a NaiaId and a payload, typically from
-    /// an incoming packet
+    /// Gets the name of the Entity type registered with the given NaiaId
+    pub fn get_entity_type_name(&self, naia_id: u16) -> Option<&'static str> {
+        return self
+            .entity_builder_map
+            .get(&naia_id)
+            .map(|builder| builder.get_type_name());
+    }
+
+    /// Creates an Entity instance, given a NaiaId and a payload, typically from
+    /// an incoming packet. Returns None if the NaiaId isn't registered, or the
+    /// payload can't be read
     pub fn create_entity(&self, naia_id: u16, bytes: &[u8]) -> Option<U> {
-        match self.entity_builder_map.get(&naia_id) {
-            Some(entity_builder) => {
-                return Some(entity_builder.as_ref().build(bytes));
+        return self.read_entity(naia_id, bytes).ok();
+    }
+
+    /// Creates an Entity instance, given a NaiaId and a payload, from an
+    /// incoming packet. Returns the reason the Entity should be dropped if the
+    /// NaiaId isn't registered, or the payload can't be read
+    pub fn read_entity(&self, naia_id: u16, bytes: &[u8]) -> Result<U, DropReason> {
+        let entity_builder = self
+            .entity_builder_map
+            .get(&naia_id)
+            .ok_or(DropReason::UnknownNaiaId)?;
+        return entity_builder
+            .build(bytes)
+            .ok_or(DropReason::MalformedPayload);
+    }
+
+    /// Register a template Entity, which Entities can be created from. An
+    /// Entity registered on the Server with
+    /// `NaiaServer::register_entity_from_prefab()` is created on the Client by
+    /// only writing the Properties which differ from its prefab. The Entity's
+    /// type must already have been registered, and prefabs must be registered
+    /// identically on Client & Server. Panics if the PrefabId has already been
+    /// registered.
+    pub fn register_prefab(&mut self, prefab_id: PrefabId, entity: Rc<RefCell<dyn Entity<U>>>) {
+        if self.prefab_map.contains_key(&prefab_id) {
+            panic!("PrefabId {} has already been registered", prefab_id);
+        }
+        self.get_entity_naia_id(&entity.as_ref().borrow().get_type_id());
+        self.prefab_map.insert(prefab_id, entity);
+    }
+
+    /// Creates an Entity instance, with the state of the prefab registered with
+    /// the given PrefabId
+    pub fn create_entity_from_prefab(&self, prefab_id: PrefabId) -> Option<U> {
+        let prefab = self.prefab_map.get(&prefab_id)?.as_ref().borrow();
+        let naia_id = self.get_entity_naia_id(&prefab.get_type_id());
+        let mut prefab_bytes = Vec::<u8>::new();
+        prefab.write(&mut prefab_bytes);
+        return self.create_entity(naia_id, &prefab_bytes);
+    }
+
+    /// Gets a StateMask with a bit set for every Property of the given Entity
+    /// which differs from the prefab registered with the given PrefabId.
+    /// Returns None if there is no such prefab, or if it is of a different
+    /// type than the Entity.
+    pub fn get_prefab_diff(
+        &self,
+        prefab_id: PrefabId,
+        entity: &dyn Entity<U>,
+    ) -> Option<StateMask> {
+        let prefab = self.prefab_map.get(&prefab_id)?.as_ref().borrow();
+        if prefab.get_type_id() != entity.get_type_id() {
+            return None;
+        }
+
+        let state_mask_size = entity.get_state_mask_size();
+        let mut diff_mask = StateMask::new(state_mask_size);
+        for property_index in 0..(u16::from(state_mask_size) * 8).min(256) {
+            let property_index = property_index as u8;
+            let mut property_mask = StateMask::new(state_mask_size);
+            property_mask.set_bit(property_index, true);
+
+            let mut prefab_bytes = Vec::<u8>::new();
+            prefab.write_partial(&property_mask, &mut prefab_bytes);
+            let mut entity_bytes = Vec::<u8>::new();
+            entity.write_partial(&property_mask, &mut entity_bytes);
+
+            if prefab_bytes != entity_bytes {
+                diff_mask.set_bit(property_index, true);
             }
-            None => {}
         }
+        return Some(diff_mask);
+    }
 
-        return None;
+    /// Get a compact description of every registered Event & Entity type,
+    /// which can be compared against the Manifest of a remote host to detect
+    /// incompatibilities
+    pub fn get_descriptor(&self) -> ManifestDescriptor {
+        let mut events: Vec<TypeDescriptor> = self
+            .event_builder_map
+            .keys()
+            .filter_map(|naia_id| self.get_event_descriptor(*naia_id))
+            .collect();
+        events.sort_by_key(|descriptor| descriptor.naia_id);
+
+        let mut entities: Vec<TypeDescriptor> = self
+            .entity_builder_map
+            .keys()
+            .filter_map(|naia_id| self.get_entity_descriptor(*naia_id))
+            .collect();
+        entities.sort_by_key(|descriptor| descriptor.naia_id);
+
+        return ManifestDescriptor { events, entities };
+    }
+
+    /// Get a compact description of the Event type registered with the given
+    /// NaiaId, or None if there is none
+    pub fn get_event_descriptor(&self, naia_id: u16) -> Option<TypeDescriptor> {
+        let builder = self.event_builder_map.get(&naia_id)?;
+        let mut descriptor = TypeDescriptor::new(
+            naia_id,
+            builder.get_type_name(),
+            builder.get_property_count(),
+            builder.get_version(),
+        );
+        descriptor.compressed = self.is_event_compressed(naia_id);
+        return Some(descriptor);
+    }
+
+    /// Get a compact description of the Entity type registered with the given
+    /// NaiaId, or None if there is none
+    pub fn get_entity_descriptor(&self, naia_id: u16) -> Option<TypeDescriptor> {
+        let builder = self.entity_builder_map.get(&naia_id)?;
+        return Some(TypeDescriptor::new(
+            naia_id,
+            builder.get_type_name(),
+            builder.get_property_count(),
+            builder.get_version(),
+        ));
+    }
+
+    /// Renders a reference of the protocol made up of the Manifest & the
+    /// given Config: the NaiaId, version & Properties of every registered
+    /// type, the order & size of the Properties as written, the layout of
+    /// packets & the settings which shape traffic. It can be written to a
+    /// file by a build script or a test, so that the teams working on the
+    /// Client & Server can check they share the same protocol
+    pub fn get_protocol_doc(&self, config: &Config, format: ProtocolDocFormat) -> String {
+        let mut doc = ProtocolDoc::new("Protocol reference");
+        doc.paragraph(
+            "Integers in packet headers & section framing are big-endian. Each Property is \
+             written as a byte holding the length of its value, followed by the value \
+             serialized with nanoserde's binary format, in which integers are little-endian. \
+             Sizes are those of a Property holding the default value of its type.",
+        );
+
+        let mut event_ids: Vec<u16> = self.event_builder_map.keys().copied().collect();
+        event_ids.sort_unstable();
+        doc.heading(2, "Events");
+        doc.table(
+            &[
+                "NaiaId",
+                "Event",
+                "Version",
+                "Properties",
+                "Delivery",
+                "Auth",
+                "Compressed",
+            ],
+            event_ids
+                .iter()
+                .map(|naia_id| {
+                    let builder = &self.event_builder_map[naia_id];
+                    let guaranteed = builder
+                        .build(&[])
+                        .expect("an Event with no data is built with default Properties")
+                        .get_boxed_event()
+                        .is_guaranteed();
+                    return vec![
+                        naia_id.to_string(),
+                        format!("`{}`", builder.get_type_name()),
+                        builder.get_version().to_string(),
+                        builder.get_property_count().to_string(),
+                        describe_delivery(guaranteed).to_string(),
+                        describe_flag(self.is_auth_event(*naia_id)).to_string(),
+                        describe_flag(self.is_event_compressed(*naia_id)).to_string(),
+                    ];
+                })
+                .collect(),
+        );
+        for naia_id in event_ids.iter() {
+            let builder = &self.event_builder_map[naia_id];
+            doc.heading(
+                3,
+                &format!("Event `{}` (NaiaId {})", builder.get_type_name(), naia_id),
+            );
+            let properties = builder.get_properties();
+            if properties.is_empty() {
+                doc.paragraph("No Properties.");
+                continue;
+            }
+            doc.table(
+                &["Index", "Property", "Type", "Size (bytes)"],
+                properties
+                    .iter()
+                    .enumerate()
+                    .map(|(index, property)| describe_property(index, property))
+                    .collect(),
+            );
+        }
+
+        let mut entity_ids: Vec<u16> = self.entity_builder_map.keys().copied().collect();
+        entity_ids.sort_unstable();
+        doc.heading(2, "Entities");
+        doc.paragraph(
+            "Updates to an Entity are preceded by a state mask, with a bit set for each \
+             Property written, by index.",
+        );
+        doc.table(
+            &[
+                "NaiaId",
+                "Entity",
+                "Version",
+                "Properties",
+                "State mask (bytes)",
+            ],
+            entity_ids
+                .iter()
+                .map(|naia_id| {
+                    let builder = &self.entity_builder_map[naia_id];
+                    let entity_ref = builder
+                        .build(&[])
+                        .expect("an Entity with no data is built with default Properties")
+                        .get_entity_ref();
+                    let state_mask_size = entity_ref.as_ref().borrow().get_state_mask_size();
+                    return vec![
+                        naia_id.to_string(),
+                        format!("`{}`", builder.get_type_name()),
+                        builder.get_version().to_string(),
+                        builder.get_property_count().to_string(),
+                        state_mask_size.to_string(),
+                    ];
+                })
+                .collect(),
+        );
+        for naia_id in entity_ids.iter() {
+            let builder = &self.entity_builder_map[naia_id];
+            doc.heading(
+                3,
+                &format!("Entity `{}` (NaiaId {})", builder.get_type_name(), naia_id),
+            );
+            let entity_ref = builder
+                .build(&[])
+                .expect("an Entity with no data is built with default Properties")
+                .get_entity_ref();
+            let unguaranteed_mask = entity_ref.as_ref().borrow().get_unguaranteed_state_mask();
+            doc.table(
+                &["Index", "Property", "Type", "Size (bytes)", "Delivery"],
+                builder
+                    .get_properties()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, property)| {
+                        let unguaranteed = unguaranteed_mask.get_bit(index as u8) == Some(true);
+                        let mut row = describe_property(index, property);
+                        row.push(describe_delivery(!unguaranteed).to_string());
+                        return row;
+                    })
+                    .collect(),
+            );
+        }
+
+        if !self.prefab_map.is_empty() {
+            let mut prefab_ids: Vec<PrefabId> = self.prefab_map.keys().copied().collect();
+            prefab_ids.sort_unstable();
+            doc.heading(2, "Prefabs");
+            doc.table(
+                &["PrefabId", "Entity"],
+                prefab_ids
+                    .iter()
+                    .map(|prefab_id| {
+                        let type_id = self.prefab_map[prefab_id].as_ref().borrow().get_type_id();
+                        let naia_id = self.get_entity_naia_id(&type_id);
+                        let type_name = self.get_entity_type_name(naia_id).unwrap_or_default();
+                        return vec![prefab_id.to_string(), format!("`{}`", type_name)];
+                    })
+                    .collect(),
+            );
+        }
+
+        doc.heading(2, "Packets");
+        doc.paragraph(&format!(
+            "Every packet starts with a header of {} bytes, or as few as 5 bytes when compact \
+             headers are negotiated. The payload of a data packet is at most {} bytes, & is \
+             made up of sections, each a byte holding the section's ManagerType, a byte \
+             holding the number of items in it, & the items. An Event may serialize to at \
+             most {} bytes. Sections are written in this order:",
+            StandardHeader::bytes_number(),
+            MTU_SIZE,
+            MAX_EVENT_SIZE
+        ));
+        doc.table(
+            &["Order", "Section", "ManagerType"],
+            SECTION_ORDER
+                .iter()
+                .enumerate()
+                .map(|(index, manager_type)| {
+                    return vec![
+                        (index + 1).to_string(),
+                        format!("{:?}", manager_type),
+                        (*manager_type as u8).to_string(),
+                    ];
+                })
+                .collect(),
+        );
+
+        doc.heading(2, "Configuration");
+        doc.table(
+            &["Setting", "Value"],
+            vec![
+                ("tick_interval", format!("{:?}", config.tick_interval)),
+                (
+                    "heartbeat_interval",
+                    format!("{:?}", config.heartbeat_interval),
+                ),
+                (
+                    "disconnection_timeout_duration",
+                    format!("{:?}", config.disconnection_timeout_duration),
+                ),
+                ("compact_headers", config.compact_headers.to_string()),
+                (
+                    "offered_capabilities",
+                    format!("{:#010x}", Capabilities::offered(config).bits()),
+                ),
+                ("command_redundancy", config.command_redundancy.to_string()),
+                (
+                    "max_entity_updates_per_tick",
+                    format!("{:?}", config.max_entity_updates_per_tick),
+                ),
+                (
+                    "atomic_entity_ticks",
+                    config.atomic_entity_ticks.to_string(),
+                ),
+                ("raw_payload_budget", config.raw_payload_budget.to_string()),
+                ("event_packet_share", config.event_packet_share.to_string()),
+                (
+                    "entity_packet_share",
+                    config.entity_packet_share.to_string(),
+                ),
+                (
+                    "blob_bandwidth_limit",
+                    config.blob_bandwidth_limit.to_string(),
+                ),
+                ("strict_protocol", config.strict_protocol.to_string()),
+            ]
+            .into_iter()
+            .map(|(setting, value)| vec![format!("`{}`", setting), value])
+            .collect(),
+        );
+
+        return doc.render(format);
+    }
+}
+
+fn describe_property(index: usize, property: &PropertyDescriptor) -> Vec<String> {
+    return vec![
+        index.to_string(),
+        format!("`{}`", property.name),
+        format!("`{}`", property.type_name),
+        property.default_size.to_string(),
+    ];
+}
+
+fn describe_delivery(guaranteed: bool) -> &'static str {
+    if guaranteed {
+        return "guaranteed";
+    }
+    return "unguaranteed";
+}
+
+fn describe_flag(flag: bool) -> &'static str {
+    if flag {
+        return "yes";
     }
+    return "no";
 }
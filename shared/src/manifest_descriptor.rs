@@ -0,0 +1,343 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use nanoserde::SerBin;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result},
+};
+
+use crate::packet_reader::PacketReader;
+
+/// A compact description of a single Event or Entity type registered in a
+/// Manifest
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDescriptor {
+    /// The NaiaId the type has been registered with
+    pub naia_id: u16,
+    /// The name of the type, which is only known for locally registered types
+    pub name: Option<String>,
+    /// A hash of the name of the type, used to compare types across hosts
+    pub name_hash: u32,
+    /// The number of Properties the type has
+    pub property_count: u8,
+    /// The version of the type, incremented as Properties are appended to it
+    pub version: u8,
+    /// Whether the payload of the type is compressed, see
+    /// `Manifest::enable_event_compression()`
+    pub compressed: bool,
+}
+
+impl TypeDescriptor {
+    /// Creates a new TypeDescriptor, given the NaiaId, name, number of
+    /// Properties & version of a registered type, which is not compressed
+    pub fn new(naia_id: u16, name: &str, property_count: u8, version: u8) -> Self {
+        TypeDescriptor {
+            naia_id,
+            name: Some(name.to_string()),
+            name_hash: hash_type_name(name),
+            property_count,
+            version,
+            compressed: false,
+        }
+    }
+
+    fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u16::<BigEndian>(self.naia_id).unwrap();
+        out_bytes.write_u32::<BigEndian>(self.name_hash).unwrap();
+        out_bytes.write_u8(self.property_count).unwrap();
+        out_bytes.write_u8(self.version).unwrap();
+        out_bytes.write_u8(self.compressed as u8).unwrap();
+    }
+
+    fn read(reader: &mut PacketReader) -> Option<Self> {
+        let cursor = reader.get_cursor();
+        let naia_id = cursor.read_u16::<BigEndian>().ok()?;
+        let name_hash = cursor.read_u32::<BigEndian>().ok()?;
+        let property_count = cursor.read_u8().ok()?;
+        let version = cursor.read_u8().ok()?;
+        let compressed = cursor.read_u8().ok()? != 0;
+        return Some(TypeDescriptor {
+            naia_id,
+            name: None,
+            name_hash,
+            property_count,
+            version,
+            compressed,
+        });
+    }
+
+    fn describe(&self, kind: &str) -> String {
+        match &self.name {
+            Some(name) => return format!("{} '{}' (NaiaId {})", kind, name, self.naia_id),
+            None => return format!("{} with NaiaId {}", kind, self.naia_id),
+        }
+    }
+}
+
+/// A description of a single Property of an Event or Entity type, generated
+/// by `#[derive(Event)]` & `#[derive(Entity)]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyDescriptor {
+    /// The name of the Property
+    pub name: &'static str,
+    /// The type of the value the Property holds
+    pub type_name: &'static str,
+    /// The number of bytes the Property is written in while it holds the
+    /// default value of its type, including the byte prefixing its length.
+    /// For a type of fixed size, such as an integer, this is its size for
+    /// every value, & for any other type, its smallest size
+    pub default_size: usize,
+}
+
+impl PropertyDescriptor {
+    /// Creates a new PropertyDescriptor, given the name of a Property & the
+    /// name of the type of its value
+    pub fn new<P: Default + SerBin>(name: &'static str, type_name: &'static str) -> Self {
+        PropertyDescriptor {
+            name,
+            type_name,
+            default_size: 1 + P::default().serialize_bin().len(),
+        }
+    }
+}
+
+/// A compact description of every Event & Entity type registered in a
+/// Manifest, which is exchanged during the connection handshake in order to
+/// detect incompatible protocols
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestDescriptor {
+    /// Descriptions of the registered Event types, sorted by NaiaId
+    pub events: Vec<TypeDescriptor>,
+    /// Descriptions of the registered Entity types, sorted by NaiaId
+    pub entities: Vec<TypeDescriptor>,
+}
+
+impl ManifestDescriptor {
+    /// Writes the descriptor into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes
+            .write_u16::<BigEndian>(self.events.len() as u16)
+            .unwrap();
+        for event in self.events.iter() {
+            event.write(out_bytes);
+        }
+        out_bytes
+            .write_u16::<BigEndian>(self.entities.len() as u16)
+            .unwrap();
+        for entity in self.entities.iter() {
+            entity.write(out_bytes);
+        }
+    }
+
+    /// Reads a descriptor from an incoming byte stream. Returns None if the
+    /// stream ends before the descriptor is complete
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let event_count = reader.get_cursor().read_u16::<BigEndian>().ok()?;
+        let mut events = Vec::new();
+        for _ in 0..event_count {
+            events.push(TypeDescriptor::read(reader)?);
+        }
+        let entity_count = reader.get_cursor().read_u16::<BigEndian>().ok()?;
+        let mut entities = Vec::new();
+        for _ in 0..entity_count {
+            entities.push(TypeDescriptor::read(reader)?);
+        }
+        return Some(ManifestDescriptor { events, entities });
+    }
+
+    /// Compares this (local) descriptor against one received from a remote
+    /// host. Returns None if the two are compatible, otherwise a report of
+    /// every difference found
+    pub fn compare(&self, remote: &ManifestDescriptor) -> Option<CompatibilityReport> {
+        let mut issues = Vec::new();
+        compare_types("Event", &self.events, &remote.events, &mut issues);
+        compare_types("Entity", &self.entities, &remote.entities, &mut issues);
+
+        if issues.is_empty() {
+            return None;
+        }
+        return Some(CompatibilityReport { issues });
+    }
+
+    /// Compares this (local) descriptor against a delta received from a
+    /// remote host, describing only the types it has registered since the
+    /// connection was established. Returns None if every type in the delta
+    /// is registered locally & compatible, otherwise a report of every
+    /// difference found
+    pub fn compare_delta(&self, delta: &ManifestDescriptor) -> Option<CompatibilityReport> {
+        let local = ManifestDescriptor {
+            events: filter_types(&self.events, &delta.events),
+            entities: filter_types(&self.entities, &delta.entities),
+        };
+        return local.compare(delta);
+    }
+}
+
+// Gets the local types which have the NaiaId of any of the given remote types
+fn filter_types(
+    local_types: &[TypeDescriptor],
+    remote_types: &[TypeDescriptor],
+) -> Vec<TypeDescriptor> {
+    return local_types
+        .iter()
+        .filter(|local_type| {
+            remote_types
+                .iter()
+                .any(|remote_type| remote_type.naia_id == local_type.naia_id)
+        })
+        .cloned()
+        .collect();
+}
+
+/// A human-readable list of the differences between the Manifests of two hosts
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompatibilityReport {
+    /// A description of each difference found
+    pub issues: Vec<String>,
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Manifest mismatch between local & remote host:")?;
+        for issue in self.issues.iter() {
+            writeln!(f, "  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+fn compare_types(
+    kind: &str,
+    local_types: &[TypeDescriptor],
+    remote_types: &[TypeDescriptor],
+    issues: &mut Vec<String>,
+) {
+    let remote_map: HashMap<u16, &TypeDescriptor> = remote_types
+        .iter()
+        .map(|descriptor| (descriptor.naia_id, descriptor))
+        .collect();
+
+    for local_type in local_types.iter() {
+        match remote_map.get(&local_type.naia_id) {
+            Some(remote_type) => {
+                if remote_type.name_hash != local_type.name_hash {
+                    issues.push(format!(
+                        "{} is a different type on the remote host",
+                        local_type.describe(kind)
+                    ));
+                } else if remote_type.compressed != local_type.compressed {
+                    issues.push(format!(
+                        "{} is {} locally, but {} on the remote host",
+                        local_type.describe(kind),
+                        describe_compression(local_type.compressed),
+                        describe_compression(remote_type.compressed)
+                    ));
+                } else if remote_type.version == local_type.version
+                    && remote_type.property_count != local_type.property_count
+                {
+                    // Properties may be appended to a type as long as its version is
+                    // incremented, in which case trailing Properties unknown to one of the
+                    // hosts are skipped, and missing ones keep their default values
+                    issues.push(format!(
+                        "{} has {} properties locally, but {} on the remote host, with the same version ({})",
+                        local_type.describe(kind),
+                        local_type.property_count,
+                        remote_type.property_count,
+                        local_type.version
+                    ));
+                }
+            }
+            None => {
+                issues.push(format!(
+                    "{} is missing on the remote host",
+                    local_type.describe(kind)
+                ));
+            }
+        }
+    }
+
+    for remote_type in remote_types.iter() {
+        if !local_types
+            .iter()
+            .any(|local_type| local_type.naia_id == remote_type.naia_id)
+        {
+            issues.push(format!(
+                "{} is registered on the remote host, but not locally",
+                remote_type.describe(kind)
+            ));
+        }
+    }
+}
+
+fn describe_compression(compressed: bool) -> &'static str {
+    if compressed {
+        return "compressed";
+    }
+    return "not compressed";
+}
+
+// FNV-1a, which is stable across hosts & builds, unlike the std Hasher
+fn hash_type_name(name: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in name.bytes() {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    return hash;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ManifestDescriptor, TypeDescriptor};
+    use crate::PacketReader;
+
+    #[test]
+    fn compression_mismatch() {
+        let mut compressed = TypeDescriptor::new(0, "ChatEvent", 1, 0);
+        compressed.compressed = true;
+        let local = ManifestDescriptor {
+            events: vec![compressed, TypeDescriptor::new(1, "MoveEvent", 2, 0)],
+            entities: Vec::new(),
+        };
+
+        let mut bytes = Vec::new();
+        local.write(&mut bytes);
+        let mut reader = PacketReader::new(&bytes);
+        let remote = ManifestDescriptor::read(&mut reader).unwrap();
+        assert!(remote.events[0].compressed && !remote.events[1].compressed);
+        assert!(local.compare(&remote).is_none());
+
+        let mut uncompressed = local.clone();
+        uncompressed.events[0].compressed = false;
+        let report = local.compare(&uncompressed).unwrap();
+        assert!(report.issues.len() == 1);
+        assert!(report.issues[0].contains("is compressed locally, but not compressed"));
+    }
+
+    #[test]
+    fn delta() {
+        let local = ManifestDescriptor {
+            events: vec![TypeDescriptor::new(0, "ChatEvent", 1, 0)],
+            entities: vec![
+                TypeDescriptor::new(0, "Player", 3, 0),
+                TypeDescriptor::new(20, "ModCrate", 2, 0),
+            ],
+        };
+        let delta = ManifestDescriptor {
+            events: Vec::new(),
+            entities: vec![TypeDescriptor::new(20, "ModCrate", 2, 0)],
+        };
+        assert!(local.compare_delta(&delta).is_none());
+
+        let mut missing = delta.clone();
+        missing
+            .events
+            .push(TypeDescriptor::new(21, "ModEvent", 1, 0));
+        let report = local.compare_delta(&missing).unwrap();
+        assert!(report.issues.len() == 1);
+        assert!(report.issues[0].contains("registered on the remote host, but not locally"));
+
+        let mut different = delta.clone();
+        different.entities[0] = TypeDescriptor::new(20, "OtherCrate", 2, 0);
+        assert!(local.compare_delta(&different).is_some());
+    }
+}
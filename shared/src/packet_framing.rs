@@ -0,0 +1,286 @@
+use byteorder::WriteBytesExt;
+use std::{fmt, rc::Rc};
+
+use crate::{manager_type::ManagerType, packet_writer::MTU_SIZE};
+
+/// Creates the PacketFraming used to assemble each outgoing data packet
+#[derive(Clone)]
+pub struct PacketFramingFactory {
+    create_func: Rc<dyn Fn() -> Box<dyn PacketFraming>>,
+}
+
+impl PacketFramingFactory {
+    /// Creates a new PacketFramingFactory, given a function which creates a
+    /// PacketFraming
+    pub fn new(create_func: impl Fn() -> Box<dyn PacketFraming> + 'static) -> Self {
+        PacketFramingFactory {
+            create_func: Rc::new(create_func),
+        }
+    }
+
+    /// Creates a PacketFraming for a new outgoing packet
+    pub fn create(&self) -> Box<dyn PacketFraming> {
+        return (self.create_func)();
+    }
+}
+
+impl fmt::Debug for PacketFramingFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str("PacketFramingFactory");
+    }
+}
+
+/// Assembles the serialized items of every manager (Events, RPC messages,
+/// Entity messages, blob chunks, etc.) into the payload of an outgoing data
+/// packet. The payload is made up of sections, each a manager type byte, a
+/// byte counting the items which follow, then the items themselves. Sections
+/// may appear in any order, and the same manager type may appear in more than
+/// one section, so an implementation is free to order or interleave items as
+/// it likes, while the ack & connection layers are reused as they are
+pub trait PacketFraming {
+    /// Adds a serialized item belonging to the section of the given manager
+    /// type. Returns false if the item doesn't fit in the packet, in which
+    /// case it is sent in a later packet
+    fn write_item(&mut self, manager_type: ManagerType, item: Vec<u8>) -> bool;
+
+    /// Gets the number of bytes the payload currently occupies, including the
+    /// 2 byte "header" of each section
+    fn bytes_number(&self) -> usize;
+
+    /// Returns whether any item has been written
+    fn has_bytes(&self) -> bool;
+
+    /// Gets the payload of the packet, and resets the framing
+    fn get_bytes(&mut self) -> Box<[u8]>;
+}
+
+// The order in which `SectionFraming` writes sections. Commands come first, so
+// the command of the current tick is never left out, and blob chunks last, so
+// they only use the space other messages have left. Entity Events follow the
+// Entity messages, so an Entity created in the same packet exists by the time
+// its Events are read, & newly registered types precede any message of them.
+// A change of tick rate precedes the timed Events, whose scheduling depends on
+// it, & a shutdown notice follows the messages sent before it
+pub(crate) const SECTION_ORDER: [ManagerType; 14] = [
+    ManagerType::Command,
+    ManagerType::ManifestDelta,
+    ManagerType::TickRate,
+    ManagerType::Event,
+    ManagerType::Rpc,
+    ManagerType::TimedEvent,
+    ManagerType::Entity,
+    ManagerType::EntityEvent,
+    ManagerType::RelayEvent,
+    ManagerType::ClientRoster,
+    ManagerType::Raw,
+    ManagerType::Custom,
+    ManagerType::Shutdown,
+    ManagerType::Blob,
+];
+
+struct Section {
+    manager_type: ManagerType,
+    count: u8,
+    bytes: Vec<u8>,
+}
+
+/// The default PacketFraming, which writes a single section for each manager
+/// type, in a fixed order
+pub struct SectionFraming {
+    sections: Vec<Section>,
+}
+
+impl SectionFraming {
+    /// Creates a new SectionFraming
+    pub fn new() -> Self {
+        SectionFraming {
+            sections: SECTION_ORDER
+                .iter()
+                .map(|manager_type| Section {
+                    manager_type: *manager_type,
+                    count: 0,
+                    bytes: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PacketFraming for SectionFraming {
+    fn write_item(&mut self, manager_type: ManagerType, mut item: Vec<u8>) -> bool {
+        let mut hypothetical_next_payload_size = self.bytes_number() + item.len();
+        let index = match self
+            .sections
+            .iter()
+            .position(|section| section.manager_type == manager_type)
+        {
+            Some(index) => index,
+            None => {
+                self.sections.push(Section {
+                    manager_type,
+                    count: 0,
+                    bytes: Vec::new(),
+                });
+                self.sections.len() - 1
+            }
+        };
+        let section = &mut self.sections[index];
+        if section.count == 0 {
+            hypothetical_next_payload_size += 2;
+        }
+        if hypothetical_next_payload_size >= MTU_SIZE || section.count == u8::max_value() {
+            return false;
+        }
+        section.count += 1;
+        section.bytes.append(&mut item);
+        return true;
+    }
+
+    fn bytes_number(&self) -> usize {
+        return self
+            .sections
+            .iter()
+            .filter(|section| section.count != 0)
+            .map(|section| section.bytes.len() + 2)
+            .sum();
+    }
+
+    fn has_bytes(&self) -> bool {
+        return self.sections.iter().any(|section| section.count != 0);
+    }
+
+    fn get_bytes(&mut self) -> Box<[u8]> {
+        let mut out_bytes = Vec::<u8>::new();
+
+        for section in self.sections.iter_mut() {
+            //Write manager "header" (manager type & item count)
+            if section.count != 0 {
+                out_bytes.write_u8(section.manager_type as u8).unwrap(); // write manager type
+                out_bytes.write_u8(section.count).unwrap(); // write number of items
+                out_bytes.append(&mut section.bytes); // write items
+                section.count = 0;
+            }
+        }
+
+        out_bytes.into_boxed_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::WriteBytesExt;
+
+    use crate::{
+        manager_type::ManagerType,
+        packet_framing::{PacketFraming, SectionFraming},
+        packet_writer::{PacketWriter, MTU_SIZE},
+    };
+
+    #[test]
+    fn section_order() {
+        let mut framing = SectionFraming::new();
+        assert!(!framing.has_bytes());
+        assert!(framing.write_item(ManagerType::Blob, vec![9]));
+        assert!(framing.write_item(ManagerType::Event, vec![1]));
+        assert!(framing.write_item(ManagerType::Event, vec![2]));
+        assert!(framing.bytes_number() == 7);
+        assert!(
+            framing.get_bytes().as_ref()
+                == [
+                    ManagerType::Event as u8,
+                    2,
+                    1,
+                    2,
+                    ManagerType::Blob as u8,
+                    1,
+                    9
+                ]
+        );
+        assert!(!framing.has_bytes());
+    }
+
+    #[test]
+    fn packet_shares() {
+        let mut writer = PacketWriter::new();
+        writer.set_packet_shares(0.1, 0.5);
+        let event_budget = MTU_SIZE / 10;
+
+        // the first item of each kind is written whatever its size
+        assert!(writer.write_item(ManagerType::Event, vec![0; event_budget + 1]));
+        assert!(!writer.write_item(ManagerType::Rpc, vec![0]));
+        assert!(writer.write_item(ManagerType::Entity, vec![0; MTU_SIZE / 2 - 1]));
+        assert!(!writer.write_item(ManagerType::Entity, vec![0; 2]));
+        assert!(writer.write_item(ManagerType::Entity, vec![0]));
+
+        // other kinds fill the rest of the packet
+        assert!(writer.write_item(ManagerType::Raw, vec![0; 16]));
+
+        // the shares start over with each packet
+        writer.get_bytes();
+        assert!(writer.write_item(ManagerType::Event, vec![0; event_budget]));
+        assert!(!writer.write_item(ManagerType::TimedEvent, vec![0]));
+    }
+
+    #[test]
+    fn full_packet() {
+        let mut framing = SectionFraming::new();
+        assert!(framing.write_item(ManagerType::Raw, vec![0; MTU_SIZE - 3]));
+        assert!(!framing.write_item(ManagerType::Raw, vec![0]));
+    }
+
+    // Writes every item in a section of its own, in the order it was written
+    struct InterleavedFraming {
+        bytes: Vec<u8>,
+    }
+
+    impl PacketFraming for InterleavedFraming {
+        fn write_item(&mut self, manager_type: ManagerType, mut item: Vec<u8>) -> bool {
+            if self.bytes.len() + item.len() + 2 >= MTU_SIZE {
+                return false;
+            }
+            self.bytes.write_u8(manager_type as u8).unwrap();
+            self.bytes.write_u8(1).unwrap();
+            self.bytes.append(&mut item);
+            return true;
+        }
+
+        fn bytes_number(&self) -> usize {
+            return self.bytes.len();
+        }
+
+        fn has_bytes(&self) -> bool {
+            return !self.bytes.is_empty();
+        }
+
+        fn get_bytes(&mut self) -> Box<[u8]> {
+            return std::mem::replace(&mut self.bytes, Vec::new()).into_boxed_slice();
+        }
+    }
+
+    #[test]
+    fn custom_framing() {
+        let mut writer =
+            PacketWriter::with_framing(Box::new(InterleavedFraming { bytes: Vec::new() }));
+        assert!(writer.write_raw_payload(&[7], 16));
+        assert!(writer.write_custom_section(3, &[8]));
+        assert!(writer.write_raw_payload(&[9], 16));
+        assert!(
+            writer.get_bytes().as_ref()
+                == [
+                    ManagerType::Raw as u8,
+                    1,
+                    1,
+                    7,
+                    ManagerType::Custom as u8,
+                    1,
+                    3,
+                    1,
+                    8,
+                    ManagerType::Raw as u8,
+                    1,
+                    1,
+                    9
+                ]
+        );
+    }
+}
@@ -22,9 +22,10 @@ impl<'s> PacketReader<'s> {
         return (self.cursor.position() as usize) < self.buffer.len();
     }
 
-    /// Read a single byte from the payload
-    pub fn read_u8(&mut self) -> u8 {
-        return self.cursor.read_u8().unwrap();
+    /// Read a single byte from the payload, or None if every byte has
+    /// already been read
+    pub fn read_u8(&mut self) -> Option<u8> {
+        return self.cursor.read_u8().ok();
     }
 
     /// Get a reference to the Cursor
@@ -18,6 +18,31 @@ pub enum PacketType {
     /// The final handshake message sent by the Server, indicating that the
     /// connection has been established
     ServerConnectResponse = 6,
+    /// A heartbeat sent by a Client which is blocked in a long load. The
+    /// Server will hold back Entity messages until the Client sends a regular
+    /// Heartbeat or Data packet again
+    LoadingHeartbeat = 7,
+    /// A handshake message sent by the Server in response to a connect request
+    /// from a Client whose Manifest is incompatible, containing the timestamp
+    /// of the request, followed by the Server's ManifestDescriptor
+    ManifestMismatch = 8,
+    /// A packet sent by the Server to a connected Client which is being
+    /// handed off to another Server, containing the other Server's address &
+    /// the token the Client should present to it
+    Handoff = 9,
+    /// An out-of-band request sent to the Server without a connection, such
+    /// as a ping or a query of the Server's info
+    UnconnectedRequest = 10,
+    /// The Server's response to an out-of-band request
+    UnconnectedResponse = 11,
+    /// A handshake message sent by the Server in response to a connect request
+    /// it has refused, such as one whose auth Event was rejected, containing
+    /// the timestamp of the request
+    ServerRejectResponse = 12,
+    /// A packet sent by the Server to a connected Client which it has
+    /// disconnected, such as when the same identity has logged in again
+    /// elsewhere. The Client doesn't attempt to reconnect
+    Kick = 13,
     /// An unknown packet type
     Unknown = 255,
 }
@@ -31,6 +56,13 @@ impl From<u8> for PacketType {
             4 => return PacketType::ServerChallengeResponse,
             5 => return PacketType::ClientConnectRequest,
             6 => return PacketType::ServerConnectResponse,
+            7 => return PacketType::LoadingHeartbeat,
+            8 => return PacketType::ManifestMismatch,
+            9 => return PacketType::Handoff,
+            10 => return PacketType::UnconnectedRequest,
+            11 => return PacketType::UnconnectedResponse,
+            12 => return PacketType::ServerRejectResponse,
+            13 => return PacketType::Kick,
             _ => return PacketType::Unknown,
         };
     }
@@ -38,8 +70,8 @@ impl From<u8> for PacketType {
 
 impl PacketType {
     /// Given all the bytes of an incoming packet, get the PacketType from the
-    /// header
-    pub fn get_from_packet(payload: &[u8]) -> PacketType {
+    /// header. Returns None if the packet is too short to hold its header
+    pub fn get_from_packet(payload: &[u8]) -> Option<PacketType> {
         StandardHeader::get_packet_type(payload)
     }
 }
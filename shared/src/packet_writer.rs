@@ -1,117 +1,458 @@
 use byteorder::{BigEndian, WriteBytesExt};
+use std::rc::Rc;
 
 use crate::{
-    entities::entity_type::EntityType,
-    events::{event::Event, event_type::EventType},
+    blobs::blob_manager::BlobChunk,
+    client_id::ClientId,
+    client_roster::ClientRosterChange,
+    custom_section::MAX_CUSTOM_SECTION_SIZE,
+    entities::{entity_type::EntityType, local_entity_key::LocalEntityKey},
+    events::{
+        event::{Event, EventId},
+        event_type::EventType,
+        rpc_message::RpcMessage,
+    },
     manager_type::ManagerType,
     manifest::Manifest,
+    manifest_descriptor::ManifestDescriptor,
+    packet_framing::{PacketFraming, PacketFramingFactory, SectionFraming},
     standard_header::StandardHeader,
+    tick_rate_change::TickRateChange,
+    utils::write_string,
 };
 
 /// The maximum of bytes that can be used for the payload of a given packet. (See #38 of http://ithare.com/64-network-dos-and-donts-for-game-engines-part-v-udp/)
 pub const MTU_SIZE: usize = 508 - StandardHeader::bytes_number();
 
-/// Handles writing of Event & Entity data into an outgoing packet
+/// The maximum number of bytes an Event, or a command, may serialize to, as
+/// its length is written in a single byte
+pub const MAX_EVENT_SIZE: usize = u8::MAX as usize;
+
+/// The maximum number of bytes of the reason given in a notice that the
+/// Server is shutting down, as its length is written in a single byte
+pub const MAX_SHUTDOWN_REASON_LENGTH: usize = u8::MAX as usize;
+
+/// Handles writing of Event, RPC & Entity data into an outgoing packet. Each
+/// item is serialized here, then passed to a PacketFraming, which assembles
+/// the items into the packet's payload
 pub struct PacketWriter {
-    event_working_bytes: Vec<u8>,
-    event_count: u8,
-    /// bytes representing outgoing Entity messages / updates
-    pub entity_working_bytes: Vec<u8>,
-    /// number of Entity messages to be written
-    pub entity_message_count: u8,
+    framing: Box<dyn PacketFraming>,
+    raw_payload_bytes: usize,
+    event_budget: usize,
+    event_bytes: usize,
+    entity_budget: usize,
+    entity_bytes: usize,
 }
 
 impl PacketWriter {
-    /// Construct a new instance of `PacketReader`, the given `buffer` will be
-    /// used to read information from.
+    /// Construct a new instance of `PacketWriter`, which assembles packets
+    /// with the default SectionFraming
     pub fn new() -> PacketWriter {
+        return PacketWriter::with_framing(Box::new(SectionFraming::new()));
+    }
+
+    /// Construct a new instance of `PacketWriter`, which assembles packets
+    /// with the given PacketFraming
+    pub fn with_framing(framing: Box<dyn PacketFraming>) -> PacketWriter {
         PacketWriter {
-            event_working_bytes: Vec::<u8>::new(),
-            event_count: 0,
-            entity_working_bytes: Vec::<u8>::new(),
-            entity_message_count: 0,
+            framing,
+            raw_payload_bytes: 0,
+            event_budget: MTU_SIZE,
+            event_bytes: 0,
+            entity_budget: MTU_SIZE,
+            entity_bytes: 0,
         }
     }
 
+    /// Construct a new instance of `PacketWriter`, which assembles packets
+    /// with a PacketFraming created by the given factory, if there is one, or
+    /// the default SectionFraming otherwise
+    pub fn from_factory(factory: Option<&PacketFramingFactory>) -> PacketWriter {
+        return match factory {
+            Some(factory) => PacketWriter::with_framing(factory.create()),
+            None => PacketWriter::new(),
+        };
+    }
+
+    /// Limits the fractions of each packet, from 0 to 1, which Events &
+    /// Entity messages may fill, as described in `Config::event_packet_share`
+    /// & `Config::entity_packet_share`. The first Event & Entity message of a
+    /// packet are always written, so that no message is held back forever
+    pub fn set_packet_shares(&mut self, event_share: f32, entity_share: f32) {
+        self.event_budget = (event_share.max(0.0).min(1.0) * MTU_SIZE as f32) as usize;
+        self.entity_budget = (entity_share.max(0.0).min(1.0) * MTU_SIZE as f32) as usize;
+    }
+
     /// Returns whether the writer has bytes to write into the outgoing packet
     pub fn has_bytes(&self) -> bool {
-        return self.event_count != 0 || self.entity_message_count != 0;
+        return self.framing.has_bytes();
     }
 
     /// Gets the bytes to write into an outgoing packet
     pub fn get_bytes(&mut self) -> Box<[u8]> {
-        let mut out_bytes = Vec::<u8>::new();
+        self.raw_payload_bytes = 0;
+        self.event_bytes = 0;
+        self.entity_bytes = 0;
+        return self.framing.get_bytes();
+    }
 
-        let mut wrote_manager_type = false;
+    /// Get the number of bytes which is ready to be written into an outgoing
+    /// packet, including the 2 byte "header" of each non-empty section
+    pub fn bytes_number(&self) -> usize {
+        return self.framing.bytes_number();
+    }
 
-        //Write manager "header" (manager type & entity count)
-        if self.event_count != 0 {
-            out_bytes.write_u8(ManagerType::Event as u8).unwrap(); // write manager type
-            wrote_manager_type = true;
-            out_bytes.write_u8(self.event_count).unwrap(); // write number of events in the following message
-            out_bytes.append(&mut self.event_working_bytes); // write event payload
-            self.event_count = 0;
+    /// Writes an already serialized item into the section of the given manager
+    /// type, which will eventually be put into the outgoing packet. Returns
+    /// false if the item doesn't fit, or if it is an Event or Entity message
+    /// which would exceed the share of the packet set with
+    /// `set_packet_shares()`
+    pub fn write_item(&mut self, manager_type: ManagerType, item: Vec<u8>) -> bool {
+        let item_length = item.len();
+        let (bytes, budget) = match manager_type {
+            ManagerType::Event
+            | ManagerType::Rpc
+            | ManagerType::TimedEvent
+            | ManagerType::EntityEvent
+            | ManagerType::RelayEvent => (&mut self.event_bytes, self.event_budget),
+            ManagerType::Entity => (&mut self.entity_bytes, self.entity_budget),
+            _ => return self.framing.write_item(manager_type, item),
+        };
+        if *bytes > 0 && *bytes + item_length > budget {
+            return false;
         }
+        if self.framing.write_item(manager_type, item) {
+            *bytes += item_length;
+            return true;
+        }
+        return false;
+    }
 
-        //Write manager "header" (manager type & entity count)
-        if self.entity_message_count != 0 {
-            //info!("writing {} entity message, with {} bytes", self.entity_message_count,
-            // self.entity_working_bytes.len());
-            if !wrote_manager_type {
-                out_bytes.write_u8(ManagerType::Entity as u8).unwrap(); // write
-                                                                        // manager
-                                                                        // type
-            }
-            out_bytes.write_u8(self.entity_message_count).unwrap(); // write number of messages
-            out_bytes.append(&mut self.entity_working_bytes); // write event payload
+    /// Writes an Event into the Writer's internal buffer, which will eventually
+    /// be put into the outgoing packet
+    pub fn write_event<T: EventType, U: EntityType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        event_id: EventId,
+        event: &Box<dyn Event<T>>,
+    ) -> bool {
+        let type_id = event.as_ref().get_type_id();
+        let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
 
-            self.entity_message_count = 0;
+        //Write event payload
+        let mut event_payload_bytes = manifest.write_event_payload(naia_id, event.as_ref());
+        if event_payload_bytes.len() > MAX_EVENT_SIZE {
+            error!("cannot encode an event with more than 255 bytes, need to implement this");
         }
 
-        out_bytes.into_boxed_slice()
+        //Write event "header" (event id, naia id & payload length)
+        let mut event_total_bytes = Vec::<u8>::new();
+        event_total_bytes.write_u16::<BigEndian>(event_id).unwrap(); // write event id
+        event_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+        event_total_bytes
+            .write_u8(event_payload_bytes.len() as u8)
+            .unwrap(); // write payload length
+        event_total_bytes.append(&mut event_payload_bytes); // write payload
+
+        return self.write_item(ManagerType::Event, event_total_bytes);
     }
 
-    /// Get the number of bytes which is ready to be written into an outgoing
-    /// packet
-    pub fn bytes_number(&self) -> usize {
-        return self.event_working_bytes.len() + self.entity_working_bytes.len();
+    /// Writes an RPC message into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet
+    pub fn write_rpc<T: EventType, U: EntityType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        rpc: &RpcMessage<Rc<Box<dyn Event<T>>>>,
+    ) -> bool {
+        //Write rpc "header" (message type & request id)
+        let mut rpc_total_bytes = Vec::<u8>::new();
+        rpc_total_bytes.write_u8(rpc.write_message_type()).unwrap(); // write message type
+        rpc_total_bytes
+            .write_u16::<BigEndian>(rpc.request_id())
+            .unwrap(); // write request id
+
+        match rpc {
+            RpcMessage::Request(_, event) | RpcMessage::Response(_, event) => {
+                let type_id = event.as_ref().as_ref().get_type_id();
+                let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
+
+                let mut event_payload_bytes =
+                    manifest.write_event_payload(naia_id, event.as_ref().as_ref());
+                if event_payload_bytes.len() > MAX_EVENT_SIZE {
+                    error!(
+                        "cannot encode an event with more than 255 bytes, need to implement this"
+                    );
+                }
+
+                rpc_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+                rpc_total_bytes
+                    .write_u8(event_payload_bytes.len() as u8)
+                    .unwrap(); // write payload length
+                rpc_total_bytes.append(&mut event_payload_bytes); // write payload
+            }
+            RpcMessage::Error(_) => {}
+        }
+
+        return self.write_item(ManagerType::Rpc, rpc_total_bytes);
     }
 
-    /// Writes an Event into the Writer's internal buffer, which will eventually
-    /// be put into the outgoing packet
-    pub fn write_event<T: EventType, U: EntityType>(
+    /// Writes an Event scheduled for a given tick into the Writer's internal
+    /// buffer, along with its EventId & the sender's current tick, which will
+    /// eventually be put into the outgoing packet
+    pub fn write_timed_event<T: EventType, U: EntityType>(
         &mut self,
         manifest: &Manifest<T, U>,
+        event_id: EventId,
+        host_tick: u16,
+        tick: u16,
         event: &Box<dyn Event<T>>,
     ) -> bool {
+        let type_id = event.as_ref().get_type_id();
+        let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
+
         //Write event payload
-        let mut event_payload_bytes = Vec::<u8>::new();
-        event.as_ref().write(&mut event_payload_bytes);
-        if event_payload_bytes.len() > 255 {
+        let mut event_payload_bytes = manifest.write_event_payload(naia_id, event.as_ref());
+        if event_payload_bytes.len() > MAX_EVENT_SIZE {
             error!("cannot encode an event with more than 255 bytes, need to implement this");
         }
 
-        //Write event "header" (event id & payload length)
+        //Write timed event "header" (ticks, event id, naia id & payload length)
         let mut event_total_bytes = Vec::<u8>::new();
+        event_total_bytes.write_u16::<BigEndian>(tick).unwrap(); // write scheduled tick
+        event_total_bytes.write_u16::<BigEndian>(host_tick).unwrap(); // write current tick
+        event_total_bytes.write_u16::<BigEndian>(event_id).unwrap(); // write event id
+        event_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+        event_total_bytes
+            .write_u8(event_payload_bytes.len() as u8)
+            .unwrap(); // write payload length
+        event_total_bytes.append(&mut event_payload_bytes); // write payload
+
+        return self.write_item(ManagerType::TimedEvent, event_total_bytes);
+    }
 
+    /// Writes an Event targeted at an Entity into the Writer's internal
+    /// buffer, along with its EventId & the Entity's key on the receiving
+    /// host, which will eventually be put into the outgoing packet
+    pub fn write_entity_event<T: EventType, U: EntityType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        event_id: EventId,
+        local_key: LocalEntityKey,
+        event: &Box<dyn Event<T>>,
+    ) -> bool {
         let type_id = event.as_ref().get_type_id();
         let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
+
+        //Write event payload
+        let mut event_payload_bytes = manifest.write_event_payload(naia_id, event.as_ref());
+        if event_payload_bytes.len() > MAX_EVENT_SIZE {
+            error!("cannot encode an event with more than 255 bytes, need to implement this");
+        }
+
+        //Write entity event "header" (event id, entity key, naia id & payload length)
+        let mut event_total_bytes = Vec::<u8>::new();
+        event_total_bytes.write_u16::<BigEndian>(event_id).unwrap(); // write event id
+        local_key.write(&mut event_total_bytes); // write entity key
         event_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
         event_total_bytes
             .write_u8(event_payload_bytes.len() as u8)
             .unwrap(); // write payload length
         event_total_bytes.append(&mut event_payload_bytes); // write payload
 
-        let mut hypothetical_next_payload_size = self.bytes_number() + event_total_bytes.len();
-        if self.event_count == 0 {
-            hypothetical_next_payload_size += 2;
+        return self.write_item(ManagerType::EntityEvent, event_total_bytes);
+    }
+
+    /// Writes an Event relayed between Clients into the Writer's internal
+    /// buffer, along with its EventId & the id of the Client it is addressed
+    /// to, or sent by, which will eventually be put into the outgoing packet
+    pub fn write_relay_event<T: EventType, U: EntityType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        event_id: EventId,
+        client_id: ClientId,
+        event: &Box<dyn Event<T>>,
+    ) -> bool {
+        let type_id = event.as_ref().get_type_id();
+        let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
+
+        //Write event payload
+        let mut event_payload_bytes = manifest.write_event_payload(naia_id, event.as_ref());
+        if event_payload_bytes.len() > MAX_EVENT_SIZE {
+            error!("cannot encode an event with more than 255 bytes, need to implement this");
         }
-        if hypothetical_next_payload_size < MTU_SIZE {
-            self.event_count += 1;
-            self.event_working_bytes.append(&mut event_total_bytes);
-            return true;
-        } else {
+
+        //Write relay event "header" (event id, client id, naia id & payload length)
+        let mut event_total_bytes = Vec::<u8>::new();
+        event_total_bytes.write_u16::<BigEndian>(event_id).unwrap(); // write event id
+        event_total_bytes.write_u16::<BigEndian>(client_id).unwrap(); // write client id
+        event_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+        event_total_bytes
+            .write_u8(event_payload_bytes.len() as u8)
+            .unwrap(); // write payload length
+        event_total_bytes.append(&mut event_payload_bytes); // write payload
+
+        return self.write_item(ManagerType::RelayEvent, event_total_bytes);
+    }
+
+    /// Writes a change to the set of connected Clients into the Writer's
+    /// internal buffer, along with the revision of the roster it was made at,
+    /// which will eventually be put into the outgoing packet
+    pub fn write_roster_change(&mut self, revision: u32, change: &ClientRosterChange) -> bool {
+        let mut change_bytes = Vec::<u8>::new();
+        change_bytes.write_u32::<BigEndian>(revision).unwrap(); // write revision
+        let kind: u8 = match change {
+            ClientRosterChange::Joined(_) => 0,
+            ClientRosterChange::Left(_) => 1,
+        };
+        change_bytes.write_u8(kind).unwrap(); // write kind of change
+        change_bytes
+            .write_u16::<BigEndian>(change.get_client_id())
+            .unwrap(); // write client id
+
+        return self
+            .framing
+            .write_item(ManagerType::ClientRoster, change_bytes);
+    }
+
+    /// Writes a description of types registered in the Manifest since the
+    /// connection was established into the Writer's internal buffer, along
+    /// with its revision, which will eventually be put into the outgoing
+    /// packet
+    pub fn write_manifest_delta(&mut self, revision: u32, delta: &ManifestDescriptor) -> bool {
+        let mut delta_bytes = Vec::<u8>::new();
+        delta_bytes.write_u32::<BigEndian>(revision).unwrap(); // write revision
+        delta.write(&mut delta_bytes); // write descriptions of the types
+
+        return self
+            .framing
+            .write_item(ManagerType::ManifestDelta, delta_bytes);
+    }
+
+    /// Writes a notice that the Server is shutting down, with the reason, of
+    /// up to `MAX_SHUTDOWN_REASON_LENGTH` bytes, into the Writer's internal
+    /// buffer, which will eventually be put into the outgoing packet
+    pub fn write_shutdown(&mut self, reason: &str) -> bool {
+        let mut shutdown_bytes = Vec::<u8>::new();
+        write_string(reason, &mut shutdown_bytes); // write reason
+
+        return self
+            .framing
+            .write_item(ManagerType::Shutdown, shutdown_bytes);
+    }
+
+    /// Writes a change to the rate at which the Server ticks into the
+    /// Writer's internal buffer, which will eventually be put into the
+    /// outgoing packet
+    pub fn write_tick_rate_change(&mut self, change: &TickRateChange) -> bool {
+        let mut change_bytes = Vec::<u8>::new();
+        change.write(&mut change_bytes);
+
+        return self.framing.write_item(ManagerType::TickRate, change_bytes);
+    }
+
+    /// Writes the command of a tick into the Writer's internal buffer, which
+    /// will eventually be put into the outgoing packet
+    pub fn write_command<T: EventType, U: EntityType>(
+        &mut self,
+        manifest: &Manifest<T, U>,
+        tick: u16,
+        command: &Box<dyn Event<T>>,
+    ) -> bool {
+        let type_id = command.as_ref().get_type_id();
+        let naia_id = manifest.get_event_naia_id(&type_id); // get naia id
+
+        //Write command payload
+        let mut command_payload_bytes = manifest.write_event_payload(naia_id, command.as_ref());
+        if command_payload_bytes.len() > MAX_EVENT_SIZE {
+            error!("cannot encode a command with more than 255 bytes, need to implement this");
+        }
+
+        //Write command "header" (tick, naia id & payload length)
+        let mut command_total_bytes = Vec::<u8>::new();
+        command_total_bytes.write_u16::<BigEndian>(tick).unwrap(); // write tick
+        command_total_bytes.write_u16::<BigEndian>(naia_id).unwrap(); // write naia id
+        command_total_bytes
+            .write_u8(command_payload_bytes.len() as u8)
+            .unwrap(); // write payload length
+        command_total_bytes.append(&mut command_payload_bytes); // write payload
+
+        return self
+            .framing
+            .write_item(ManagerType::Command, command_total_bytes);
+    }
+
+    /// Writes a chunk of a blob into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet
+    pub fn write_blob_chunk(&mut self, chunk: &BlobChunk) -> bool {
+        //Write chunk "header" (blob id, chunk index, total chunks & payload length)
+        let mut chunk_total_bytes = Vec::<u8>::new();
+        chunk_total_bytes
+            .write_u16::<BigEndian>(chunk.get_blob_id())
+            .unwrap(); // write blob id
+        chunk_total_bytes
+            .write_u16::<BigEndian>(chunk.get_chunk_index())
+            .unwrap(); // write chunk index
+        chunk_total_bytes
+            .write_u16::<BigEndian>(chunk.get_total_chunks())
+            .unwrap(); // write total chunks
+        chunk_total_bytes
+            .write_u8(chunk.get_data().len() as u8)
+            .unwrap(); // write payload length
+        chunk_total_bytes.extend_from_slice(chunk.get_data()); // write payload
+
+        return self
+            .framing
+            .write_item(ManagerType::Blob, chunk_total_bytes);
+    }
+
+    /// Gets the most bytes a custom section could write into the outgoing
+    /// packet, given the room left in it
+    pub fn get_custom_section_capacity(&self) -> usize {
+        // each custom section has a 2 byte "header", and may need a 2 byte
+        // manager "header", depending on the framing
+        let overhead = self.bytes_number() + 4;
+        if overhead + 1 >= MTU_SIZE {
+            return 0;
+        }
+        return (MTU_SIZE - overhead - 1).min(MAX_CUSTOM_SECTION_SIZE);
+    }
+
+    /// Writes the bytes of a custom section into the Writer's internal buffer,
+    /// which will eventually be put into the outgoing packet. Returns false if
+    /// the bytes don't fit
+    pub fn write_custom_section(&mut self, section_id: u8, bytes: &[u8]) -> bool {
+        if bytes.len() > self.get_custom_section_capacity() {
+            return false;
+        }
+
+        //Write section "header" (section id & payload length)
+        let mut section_total_bytes = Vec::<u8>::new();
+        section_total_bytes.write_u8(section_id).unwrap(); // write section id
+        section_total_bytes.write_u8(bytes.len() as u8).unwrap(); // write payload length
+        section_total_bytes.extend_from_slice(bytes); // write payload
+
+        return self
+            .framing
+            .write_item(ManagerType::Custom, section_total_bytes);
+    }
+
+    /// Writes a raw payload into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet. The payload is not written
+    /// if the raw payloads in this packet would then exceed the given budget
+    pub fn write_raw_payload(&mut self, payload: &[u8], budget: usize) -> bool {
+        let mut raw_total_bytes = Vec::<u8>::new();
+        raw_total_bytes.write_u8(payload.len() as u8).unwrap(); // write payload length
+        raw_total_bytes.extend_from_slice(payload); // write payload
+
+        let raw_total_length = raw_total_bytes.len();
+        if self.raw_payload_bytes + raw_total_length > budget {
             return false;
         }
+
+        if self.framing.write_item(ManagerType::Raw, raw_total_bytes) {
+            self.raw_payload_bytes += raw_total_length;
+            return true;
+        }
+        return false;
     }
 }
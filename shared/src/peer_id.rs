@@ -0,0 +1,73 @@
+use std::net::SocketAddr;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{address_family::to_canonical_address, packet_reader::PacketReader};
+
+/// Identifies a node of a peer-to-peer mesh. Every node assigns the same ids,
+/// given the same list of peer addresses
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerId(u16);
+
+impl PeerId {
+    /// Returns whether this peer should connect to the other peer. Within each
+    /// pair of peers, the one with the higher id connects to the one with the
+    /// lower id, so that every pair shares exactly one connection
+    pub fn connects_to(&self, other: &PeerId) -> bool {
+        return other.0 < self.0;
+    }
+
+    /// Writes the id into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u16::<BigEndian>(self.0).unwrap();
+    }
+
+    /// Reads an id written with `write()`
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let id = reader.get_cursor().read_u16::<BigEndian>().ok()?;
+        return Some(PeerId(id));
+    }
+}
+
+/// Assigns an id to each of the given peer addresses, by sorting them, so
+/// that every peer given the same addresses, in any order, assigns the same
+/// ids. Duplicate addresses share an id
+pub fn assign_peer_ids(addresses: &[SocketAddr]) -> Vec<(PeerId, SocketAddr)> {
+    let mut sorted_addresses: Vec<SocketAddr> = addresses
+        .iter()
+        .map(|address| to_canonical_address(*address))
+        .collect();
+    sorted_addresses.sort();
+    sorted_addresses.dedup();
+    return sorted_addresses
+        .into_iter()
+        .take(u16::MAX as usize)
+        .enumerate()
+        .map(|(index, address)| (PeerId(index as u16), address))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::assign_peer_ids;
+
+    #[test]
+    fn deterministic_ids() {
+        let a: SocketAddr = "192.0.2.1:14191".parse().unwrap();
+        let b: SocketAddr = "192.0.2.1:14192".parse().unwrap();
+        let c: SocketAddr = "192.0.2.2:14191".parse().unwrap();
+        let mapped_b: SocketAddr = "[::ffff:192.0.2.1]:14192".parse().unwrap();
+
+        let ids = assign_peer_ids(&[c, a, b]);
+        assert!(ids == assign_peer_ids(&[b, c, a]));
+        assert!(ids == assign_peer_ids(&[a, mapped_b, c, a]));
+        assert!(ids.iter().map(|(_, address)| *address).collect::<Vec<_>>() == vec![a, b, c]);
+
+        let (first, second, third) = (ids[0].0, ids[1].0, ids[2].0);
+        assert!(third.connects_to(&first) && third.connects_to(&second));
+        assert!(second.connects_to(&first) && !first.connects_to(&second));
+        assert!(!first.connects_to(&first));
+    }
+}
@@ -0,0 +1,5 @@
+pub(crate) mod rigid_body_state;
+pub(crate) mod rollback_history;
+
+#[cfg(feature = "rapier")]
+pub(crate) mod rapier_adapter;
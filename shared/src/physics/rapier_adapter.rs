@@ -0,0 +1,210 @@
+use std::{collections::HashMap, hash::Hash};
+
+use rapier3d::{
+    dynamics::{RigidBody, RigidBodyHandle},
+    math::{Rotation, Vector},
+    pipeline::PhysicsWorld,
+};
+
+use super::{rigid_body_state::RigidBodyState, rollback_history::RollbackHistory};
+
+/// Reads the replicated state of the given rapier rigid body
+pub fn read_rigid_body_state(body: &RigidBody) -> RigidBodyState {
+    let position = body.translation();
+    let rotation = body.rotation();
+    let linear_velocity = body.linvel();
+    let angular_velocity = body.angvel();
+    return RigidBodyState {
+        position: [position.x, position.y, position.z],
+        rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        linear_velocity: [linear_velocity.x, linear_velocity.y, linear_velocity.z],
+        angular_velocity: [angular_velocity.x, angular_velocity.y, angular_velocity.z],
+        sleeping: body.is_sleeping(),
+    };
+}
+
+/// Writes the given replicated state onto a rapier rigid body, putting it to
+/// sleep or waking it as the state says
+pub fn write_rigid_body_state(state: &RigidBodyState, body: &mut RigidBody) {
+    let wake = !state.sleeping;
+    body.set_translation(Vector::from_array(state.position), wake);
+    body.set_rotation(
+        Rotation::from_xyzw(
+            state.rotation[0],
+            state.rotation[1],
+            state.rotation[2],
+            state.rotation[3],
+        )
+        .normalize(),
+        wake,
+    );
+    body.set_linvel(Vector::from_array(state.linear_velocity), wake);
+    body.set_angvel(Vector::from_array(state.angular_velocity), wake);
+    if state.sleeping {
+        body.sleep();
+    }
+}
+
+/// Keeps the rigid bodies of a rapier physics world in sync with replicated
+/// Entities, each of which holds a RigidBodyState Property.
+///
+/// On the Server, which is authoritative, `step_authoritative()` steps the
+/// world once per tick & returns the state of each linked body, to be set on
+/// its Entity's Property. On the Client, each tick's state is recorded with
+/// `record_snapshot()`, so that when the Server's state for a past tick
+/// arrives, `rollback()` can restore that tick, apply the Server's state, &
+/// simulate forward again to the current tick
+#[derive(Debug)]
+pub struct RapierBridge<K: Copy + Eq + Hash> {
+    handles: HashMap<K, RigidBodyHandle>,
+    history: RollbackHistory<Vec<(K, RigidBodyState)>>,
+}
+
+impl<K: Copy + Eq + Hash> RapierBridge<K> {
+    /// Creates a new RapierBridge, which keeps snapshots of at most the given
+    /// number of ticks to roll back to
+    pub fn new(history_size: usize) -> Self {
+        RapierBridge {
+            handles: HashMap::new(),
+            history: RollbackHistory::new(history_size),
+        }
+    }
+
+    /// Links the given key, usually an EntityKey or LocalEntityKey, to a
+    /// rigid body in the physics world
+    pub fn link(&mut self, key: K, handle: RigidBodyHandle) {
+        self.handles.insert(key, handle);
+    }
+
+    /// Unlinks the given key from its rigid body, returning the handle of the
+    /// body, which is left in the physics world
+    pub fn unlink(&mut self, key: &K) -> Option<RigidBodyHandle> {
+        return self.handles.remove(key);
+    }
+
+    /// Gets the handle of the rigid body linked to the given key
+    pub fn get_handle(&self, key: &K) -> Option<RigidBodyHandle> {
+        return self.handles.get(key).copied();
+    }
+
+    /// Reads the state of every linked rigid body which is still in the
+    /// physics world
+    pub fn read_states(&self, world: &PhysicsWorld) -> Vec<(K, RigidBodyState)> {
+        let mut output = Vec::new();
+        for (key, handle) in self.handles.iter() {
+            if let Some(body) = world.bodies.get(*handle) {
+                output.push((*key, read_rigid_body_state(body)));
+            }
+        }
+        return output;
+    }
+
+    /// Steps the physics world by one tick & reads the resulting state of
+    /// every linked rigid body, to be replicated by the Server
+    pub fn step_authoritative(&self, world: &mut PhysicsWorld) -> Vec<(K, RigidBodyState)> {
+        world.step();
+        return self.read_states(world);
+    }
+
+    /// Writes the given state onto the rigid body linked to the given key,
+    /// returning whether there was such a body
+    pub fn apply_state(&self, world: &mut PhysicsWorld, key: &K, state: &RigidBodyState) -> bool {
+        if let Some(handle) = self.handles.get(key) {
+            if let Some(body) = world.bodies.get_mut(*handle) {
+                write_rigid_body_state(state, body);
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Records the state of every linked rigid body as of the given tick
+    pub fn record_snapshot(&mut self, tick: u16, world: &PhysicsWorld) {
+        let snapshot = self.read_states(world);
+        self.history.record(tick, snapshot);
+    }
+
+    /// Rolls the physics world back to the given tick, applying the
+    /// authoritative states received for that tick over the recorded
+    /// snapshot, then re-simulates every tick up to & including the current
+    /// one, recording each. The given function steps the world for a tick,
+    /// & should re-apply any inputs recorded for it. Returns false, leaving
+    /// the world untouched, if the tick is no longer recorded
+    pub fn rollback(
+        &mut self,
+        world: &mut PhysicsWorld,
+        tick: u16,
+        authoritative: &[(K, RigidBodyState)],
+        current_tick: u16,
+        resimulate: &mut dyn FnMut(&mut PhysicsWorld, u16),
+    ) -> bool {
+        let snapshot = match self.history.get(tick) {
+            Some(snapshot) => snapshot.clone(),
+            None => return false,
+        };
+        for (key, state) in snapshot.iter() {
+            self.apply_state(world, key, state);
+        }
+        for (key, state) in authoritative.iter() {
+            self.apply_state(world, key, state);
+        }
+        self.record_snapshot(tick, world);
+
+        let mut resimulated_tick = tick;
+        while resimulated_tick != current_tick {
+            resimulated_tick = resimulated_tick.wrapping_add(1);
+            resimulate(world, resimulated_tick);
+            self.record_snapshot(resimulated_tick, world);
+        }
+        return true;
+    }
+
+    /// Discards the snapshots of every tick before the given one, such as
+    /// once the Server's state for a tick has been received
+    pub fn discard_before(&mut self, tick: u16) {
+        self.history.discard_before(tick);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::{dynamics::RigidBodyBuilder, math::Vector, pipeline::PhysicsWorld};
+
+    use crate::{RapierBridge, RigidBodyState};
+
+    #[test]
+    fn rollback_resimulates() {
+        let mut world = PhysicsWorld::default();
+        let handle = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .gravity_scale(0.0)
+                .linvel(Vector::new(1.0, 0.0, 0.0)),
+        );
+        let mut bridge = RapierBridge::new(8);
+        bridge.link(1u32, handle);
+
+        bridge.record_snapshot(0, &world);
+        for tick in 1..=3 {
+            bridge.step_authoritative(&mut world);
+            bridge.record_snapshot(tick, &world);
+        }
+        let before = bridge.read_states(&world)[0].1.position[0];
+        assert!(before > 0.0);
+
+        // the Server reports the body as having been stopped at tick 1
+        let mut state = RigidBodyState::default();
+        state.position = [0.5, 0.0, 0.0];
+        let mut steps = 0;
+        assert!(
+            bridge.rollback(&mut world, 1, &[(1, state)], 3, &mut |world, _| {
+                world.step();
+                steps += 1;
+            })
+        );
+        assert!(steps == 2);
+        let after = bridge.read_states(&world)[0].1.position[0];
+        assert!((after - 0.5).abs() < 0.001);
+
+        assert!(!bridge.rollback(&mut world, 10, &[], 3, &mut |_, _| {}));
+    }
+}
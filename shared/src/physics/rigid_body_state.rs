@@ -0,0 +1,32 @@
+use nanoserde::{DeBin, SerBin};
+
+/// The state of a rigid body in a physics simulation, to be used as the
+/// value of a Property, so that the Server's simulation can be mirrored on
+/// the Client. Values are written at full precision, as quantizing them
+/// would make the Client's simulation diverge from the Server's
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct RigidBodyState {
+    /// The position of the body's center along the x, y & z axes
+    pub position: [f32; 3],
+    /// The rotation of the body, as a unit quaternion of x, y, z & w
+    pub rotation: [f32; 4],
+    /// The linear velocity of the body along the x, y & z axes
+    pub linear_velocity: [f32; 3],
+    /// The angular velocity of the body about the x, y & z axes
+    pub angular_velocity: [f32; 3],
+    /// Whether the body has come to rest, & has been put to sleep by the
+    /// simulation
+    pub sleeping: bool,
+}
+
+impl Default for RigidBodyState {
+    fn default() -> Self {
+        RigidBodyState {
+            position: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            linear_velocity: [0.0; 3],
+            angular_velocity: [0.0; 3],
+            sleeping: false,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+use crate::sequence_buffer::sequence_less_than;
+
+/// A record of a simulation's state at each of the most recent ticks, so
+/// that when an authoritative state arrives for a past tick, the simulation
+/// can be rolled back to that tick & simulated forward again
+#[derive(Debug)]
+pub struct RollbackHistory<S> {
+    snapshots: VecDeque<(u16, S)>,
+    capacity: usize,
+}
+
+impl<S> RollbackHistory<S> {
+    /// Creates a new RollbackHistory, which keeps the snapshots of at most
+    /// the given number of ticks
+    pub fn new(capacity: usize) -> Self {
+        RollbackHistory {
+            snapshots: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records the snapshot of the given tick, replacing any snapshot of
+    /// that tick or a later one, which is from before a rollback. The oldest
+    /// snapshot is discarded once the capacity is reached
+    pub fn record(&mut self, tick: u16, snapshot: S) {
+        while let Some((last_tick, _)) = self.snapshots.back() {
+            if sequence_less_than(*last_tick, tick) {
+                break;
+            }
+            self.snapshots.pop_back();
+        }
+        self.snapshots.push_back((tick, snapshot));
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Gets the snapshot of the given tick, if it is still recorded
+    pub fn get(&self, tick: u16) -> Option<&S> {
+        return self
+            .snapshots
+            .iter()
+            .find(|(snapshot_tick, _)| *snapshot_tick == tick)
+            .map(|(_, snapshot)| snapshot);
+    }
+
+    /// Discards the snapshots of every tick before the given one, which can
+    /// no longer be rolled back to
+    pub fn discard_before(&mut self, tick: u16) {
+        while let Some((first_tick, _)) = self.snapshots.front() {
+            if !sequence_less_than(*first_tick, tick) {
+                break;
+            }
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Gets the number of ticks recorded
+    pub fn len(&self) -> usize {
+        return self.snapshots.len();
+    }
+
+    /// Returns whether no ticks are recorded
+    pub fn is_empty(&self) -> bool {
+        return self.snapshots.is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RollbackHistory;
+
+    #[test]
+    fn record_and_rewind() {
+        let mut history = RollbackHistory::new(3);
+        for tick in 65534..=65535 {
+            history.record(tick, tick as u32);
+        }
+        history.record(0, 0);
+        history.record(1, 1);
+        assert!(history.len() == 3);
+        assert!(history.get(65534).is_none());
+        assert!(history.get(65535) == Some(&65535));
+
+        // recording an earlier tick again, after a rollback, replaces the
+        // later ticks
+        history.record(0, 100);
+        assert!(history.get(0) == Some(&100));
+        assert!(history.get(1).is_none());
+
+        history.discard_before(0);
+        assert!(history.get(65535).is_none());
+        assert!(history.len() == 1);
+    }
+}
@@ -0,0 +1,171 @@
+/// The markup a protocol reference is rendered in, see
+/// `Manifest::get_protocol_doc()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProtocolDocFormat {
+    /// Markdown, with GitHub-flavored tables
+    Markdown,
+    /// A standalone HTML page
+    Html,
+}
+
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    Table(Vec<String>, Vec<Vec<String>>),
+}
+
+/// A document made of headings, paragraphs & tables, which can be rendered as
+/// Markdown or HTML. Text may contain inline code between backticks
+pub(crate) struct ProtocolDoc {
+    title: String,
+    blocks: Vec<Block>,
+}
+
+impl ProtocolDoc {
+    pub fn new(title: &str) -> Self {
+        ProtocolDoc {
+            title: title.to_string(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Adds a heading, at level 2 or lower, as the title is the only heading
+    /// at level 1
+    pub fn heading(&mut self, level: u8, text: &str) {
+        self.blocks.push(Block::Heading(level, text.to_string()));
+    }
+
+    pub fn paragraph(&mut self, text: &str) {
+        self.blocks.push(Block::Paragraph(text.to_string()));
+    }
+
+    pub fn table(&mut self, headers: &[&str], rows: Vec<Vec<String>>) {
+        let headers = headers.iter().map(|header| header.to_string()).collect();
+        self.blocks.push(Block::Table(headers, rows));
+    }
+
+    pub fn render(&self, format: ProtocolDocFormat) -> String {
+        match format {
+            ProtocolDocFormat::Markdown => return self.render_markdown(),
+            ProtocolDocFormat::Html => return self.render_html(),
+        }
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut output = format!("# {}\n", self.title);
+        for block in self.blocks.iter() {
+            output.push('\n');
+            match block {
+                Block::Heading(level, text) => {
+                    output.push_str(&format!("{} {}\n", "#".repeat(*level as usize), text));
+                }
+                Block::Paragraph(text) => {
+                    output.push_str(text);
+                    output.push('\n');
+                }
+                Block::Table(headers, rows) => {
+                    output.push_str(&markdown_row(headers));
+                    output.push_str(&markdown_row(
+                        &headers
+                            .iter()
+                            .map(|_| "---".to_string())
+                            .collect::<Vec<_>>(),
+                    ));
+                    for row in rows.iter() {
+                        output.push_str(&markdown_row(row));
+                    }
+                }
+            }
+        }
+        return output;
+    }
+
+    fn render_html(&self) -> String {
+        let title = html_text(&self.title);
+        let mut output = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n",
+            title, title
+        );
+        for block in self.blocks.iter() {
+            match block {
+                Block::Heading(level, text) => {
+                    output.push_str(&format!("<h{}>{}</h{}>\n", level, html_text(text), level));
+                }
+                Block::Paragraph(text) => {
+                    output.push_str(&format!("<p>{}</p>\n", html_text(text)));
+                }
+                Block::Table(headers, rows) => {
+                    output.push_str("<table>\n<tr>");
+                    for header in headers.iter() {
+                        output.push_str(&format!("<th>{}</th>", html_text(header)));
+                    }
+                    output.push_str("</tr>\n");
+                    for row in rows.iter() {
+                        output.push_str("<tr>");
+                        for cell in row.iter() {
+                            output.push_str(&format!("<td>{}</td>", html_text(cell)));
+                        }
+                        output.push_str("</tr>\n");
+                    }
+                    output.push_str("</table>\n");
+                }
+            }
+        }
+        output.push_str("</body>\n</html>\n");
+        return output;
+    }
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+    return format!("| {} |\n", cells.join(" | "));
+}
+
+// Escapes the text, & wraps each span between backticks in a code element
+fn html_text(text: &str) -> String {
+    let mut output = String::new();
+    let mut in_code = false;
+    for character in text.chars() {
+        match character {
+            '`' => {
+                output.push_str(if in_code { "</code>" } else { "<code>" });
+                in_code = !in_code;
+            }
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(character),
+        }
+    }
+    if in_code {
+        output.push_str("</code>");
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProtocolDoc, ProtocolDocFormat};
+
+    #[test]
+    fn render_table() {
+        let mut doc = ProtocolDoc::new("Protocol");
+        doc.heading(2, "Events");
+        doc.table(
+            &["Property", "Type"],
+            vec![vec![
+                "`flags`".to_string(),
+                "`Option<u8>` | none".to_string(),
+            ]],
+        );
+
+        let markdown = doc.render(ProtocolDocFormat::Markdown);
+        assert!(markdown.starts_with("# Protocol\n\n## Events\n\n| Property | Type |\n"));
+        assert!(markdown.contains("| --- | --- |\n| `flags` | `Option<u8>` \\| none |\n"));
+
+        let html = doc.render(ProtocolDocFormat::Html);
+        assert!(html.contains("<h2>Events</h2>"));
+        assert!(html.contains("<td><code>Option&lt;u8&gt;</code> | none</td>"));
+    }
+}
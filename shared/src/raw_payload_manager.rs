@@ -0,0 +1,152 @@
+use byteorder::ReadBytesExt;
+use std::collections::VecDeque;
+
+use super::{
+    connection_stats::{DropLog, DropReason},
+    instant::Instant,
+    packet_reader::PacketReader,
+};
+
+/// The maximum number of bytes a single raw payload can contain
+pub const MAX_RAW_PAYLOAD_SIZE: usize = 255;
+
+/// How many milliseconds a raw payload waits to be sent before it's considered
+/// stale & dropped, as happens when payloads are queued faster than the
+/// budget of each packet allows them to be sent
+const RAW_PAYLOAD_MAX_AGE_MILLIS: u64 = 1000;
+
+/// Handles opaque byte payloads which are attached to outgoing packets as-is,
+/// without going through the Manifest. Raw payloads are never retransmitted.
+#[derive(Debug)]
+pub struct RawPayloadManager {
+    budget: usize,
+    queued_outgoing_payloads: VecDeque<(Instant, Box<[u8]>)>,
+    popped_payload_queued_at: Option<Instant>,
+    queued_incoming_payloads: VecDeque<Box<[u8]>>,
+}
+
+impl RawPayloadManager {
+    /// Creates a new RawPayloadManager, given the number of bytes of raw
+    /// payloads which may be written into a single packet
+    pub fn new(budget: usize) -> Self {
+        RawPayloadManager {
+            budget,
+            queued_outgoing_payloads: VecDeque::new(),
+            popped_payload_queued_at: None,
+            queued_incoming_payloads: VecDeque::new(),
+        }
+    }
+
+    /// Queues a raw payload to be attached to an outgoing packet. Returns false
+    /// if the payload is larger than `MAX_RAW_PAYLOAD_SIZE`, or too large to
+    /// ever fit in the budget of a packet, along with its length byte
+    pub fn queue_outgoing_payload(&mut self, payload: &[u8]) -> bool {
+        if payload.len() > MAX_RAW_PAYLOAD_SIZE || payload.len() + 1 > self.budget {
+            return false;
+        }
+        self.queued_outgoing_payloads
+            .push_back((Instant::now(), payload.to_vec().into_boxed_slice()));
+        return true;
+    }
+
+    /// Returns whether there are raw payloads waiting to be sent
+    pub fn has_outgoing_payloads(&self) -> bool {
+        return !self.queued_outgoing_payloads.is_empty();
+    }
+
+    /// Gets the next raw payload to be sent, dropping any which have waited
+    /// too long to be sent to still be of use
+    pub fn pop_outgoing_payload(&mut self) -> Option<Box<[u8]>> {
+        while let Some((queued_at, payload)) = self.queued_outgoing_payloads.pop_front() {
+            let age = queued_at.elapsed();
+            let age_millis = age.as_secs() * 1000 + (age.subsec_nanos() / 1_000_000) as u64;
+            if age_millis >= RAW_PAYLOAD_MAX_AGE_MILLIS {
+                continue;
+            }
+            self.popped_payload_queued_at = Some(queued_at);
+            return Some(payload);
+        }
+        return None;
+    }
+
+    /// If the last popped raw payload did not fit in the outgoing packet, put
+    /// it back into the front of the queue
+    pub fn unpop_outgoing_payload(&mut self, payload: Box<[u8]>) {
+        let queued_at = self
+            .popped_payload_queued_at
+            .take()
+            .unwrap_or_else(Instant::now);
+        self.queued_outgoing_payloads
+            .push_front((queued_at, payload));
+    }
+
+    /// Gets the next raw payload received from the remote host
+    pub fn pop_incoming_payload(&mut self) -> Option<Box<[u8]>> {
+        return self.queued_incoming_payloads.pop_front();
+    }
+
+    /// Given incoming packet data, read raw payloads and store them to be
+    /// returned to the application. Returns false if the section extends past the end of the
+    /// packet, in which case the rest of the packet can't be read
+    pub fn process_data(&mut self, reader: &mut PacketReader, drop_log: &mut DropLog) -> bool {
+        let buffer = reader.get_buffer();
+        let cursor = reader.get_cursor();
+
+        let payload_count = match cursor.read_u8() {
+            Ok(payload_count) => payload_count,
+            Err(_) => {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+        };
+        for _x in 0..payload_count {
+            let payload_length: u8 = match cursor.read_u8() {
+                Ok(payload_length) => payload_length,
+                Err(_) => {
+                    drop_log.record(DropReason::OversizedLength);
+                    return false;
+                }
+            };
+            let payload_start_position: usize = cursor.position() as usize;
+            let payload_end_position: usize = payload_start_position + (payload_length as usize);
+            if payload_end_position > buffer.len() {
+                drop_log.record(DropReason::OversizedLength);
+                return false;
+            }
+
+            self.queued_incoming_payloads.push_back(
+                buffer[payload_start_position..payload_end_position]
+                    .to_vec()
+                    .into_boxed_slice(),
+            );
+
+            cursor.set_position(payload_end_position as u64);
+        }
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawPayloadManager;
+
+    #[test]
+    fn rejects_payloads_over_budget() {
+        let mut manager = RawPayloadManager::new(8);
+        assert!(manager.queue_outgoing_payload(&[0; 7]));
+        assert!(!manager.queue_outgoing_payload(&[0; 8]));
+        assert!(!RawPayloadManager::new(1024).queue_outgoing_payload(&[0; 256]));
+    }
+
+    #[test]
+    fn unpopped_payloads_keep_their_place() {
+        let mut manager = RawPayloadManager::new(256);
+        assert!(manager.queue_outgoing_payload(&[1]));
+        assert!(manager.queue_outgoing_payload(&[2]));
+        let first = manager.pop_outgoing_payload().unwrap();
+        manager.unpop_outgoing_payload(first);
+        assert!(manager.pop_outgoing_payload().unwrap().as_ref() == [1]);
+        assert!(manager.pop_outgoing_payload().unwrap().as_ref() == [2]);
+        assert!(manager.pop_outgoing_payload().is_none());
+    }
+}
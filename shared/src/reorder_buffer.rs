@@ -0,0 +1,192 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    instant::Instant,
+    sequence_buffer::{sequence_greater_than, sequence_less_than, SequenceNumber},
+};
+
+#[derive(Debug)]
+struct HeldPacket {
+    sequence: SequenceNumber,
+    tick: Option<u16>,
+    received_at: Instant,
+    payload: Option<Box<[u8]>>,
+}
+
+/// Holds incoming data packets which arrive ahead of an earlier packet, for
+/// up to a given duration, so that they're applied in sequence order once the
+/// earlier packet arrives. If it doesn't arrive in time, it's given up on, &
+/// the held packets are released
+#[derive(Debug)]
+pub struct ReorderBuffer {
+    hold_duration: Duration,
+    packets: VecDeque<HeldPacket>,
+    next_sequence: Option<SequenceNumber>,
+}
+
+impl ReorderBuffer {
+    /// Creates a new ReorderBuffer, given the longest duration a packet is
+    /// held while waiting for an earlier one
+    pub fn new(hold_duration: Duration) -> Self {
+        ReorderBuffer {
+            hold_duration,
+            packets: VecDeque::new(),
+            next_sequence: None,
+        }
+    }
+
+    /// Adds a received packet, given its sequence number & the tick of the
+    /// remote host it was sent at. Duplicates of a held packet are ignored
+    pub fn push(&mut self, sequence: SequenceNumber, tick: Option<u16>, payload: Box<[u8]>) {
+        self.insert(sequence, tick, Some(payload));
+    }
+
+    /// Records that a packet without data, such as a heartbeat, has been
+    /// received, so that the packets after it aren't held waiting for it
+    pub fn mark_received(&mut self, sequence: SequenceNumber) {
+        self.insert(sequence, None, None);
+    }
+
+    fn insert(&mut self, sequence: SequenceNumber, tick: Option<u16>, payload: Option<Box<[u8]>>) {
+        if self
+            .packets
+            .iter()
+            .any(|packet| packet.sequence == sequence)
+        {
+            return;
+        }
+        let packet = HeldPacket {
+            sequence,
+            tick,
+            received_at: Instant::now(),
+            payload,
+        };
+        match self
+            .packets
+            .iter()
+            .position(|held| sequence_less_than(sequence, held.sequence))
+        {
+            Some(index) => self.packets.insert(index, packet),
+            None => self.packets.push_back(packet),
+        }
+    }
+
+    /// Takes the next packet which is ready to be applied, along with the tick
+    /// it was sent at. A packet is ready once every earlier packet has been
+    /// applied, or once any held packet has waited out the hold duration.
+    /// Packets which arrive after a later one has been applied are released
+    /// straight away
+    pub fn pop_ready(&mut self) -> Option<(Option<u16>, Box<[u8]>)> {
+        loop {
+            let front_sequence = self.packets.front()?.sequence;
+            let ready = match self.next_sequence {
+                Some(next_sequence) => {
+                    !sequence_greater_than(front_sequence, next_sequence)
+                        || self.packets.iter().any(|packet| {
+                            let elapsed = packet.received_at.elapsed();
+                            Duration::new(elapsed.as_secs(), elapsed.subsec_nanos())
+                                >= self.hold_duration
+                        })
+                }
+                None => true,
+            };
+            if !ready {
+                return None;
+            }
+
+            let packet = self.packets.pop_front()?;
+            let following_sequence = packet.sequence.wrapping_add(1);
+            match self.next_sequence {
+                Some(next_sequence)
+                    if !sequence_greater_than(following_sequence, next_sequence) => {}
+                _ => self.next_sequence = Some(following_sequence),
+            }
+            if let Some(payload) = packet.payload {
+                return Some((packet.tick, payload));
+            }
+        }
+    }
+
+    /// Gets the number of packets being held
+    pub fn len(&self) -> usize {
+        return self.packets.len();
+    }
+
+    /// Returns whether no packets are being held
+    pub fn is_empty(&self) -> bool {
+        return self.packets.is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ReorderBuffer;
+
+    fn push(buffer: &mut ReorderBuffer, sequence: u16) {
+        buffer.push(sequence, Some(sequence), Box::new([sequence as u8]));
+    }
+
+    fn pop_all(buffer: &mut ReorderBuffer) -> Vec<u8> {
+        let mut output = Vec::new();
+        while let Some((_, payload)) = buffer.pop_ready() {
+            output.push(payload[0]);
+        }
+        return output;
+    }
+
+    #[test]
+    fn restores_order() {
+        let mut buffer = ReorderBuffer::new(Duration::from_secs(60));
+        push(&mut buffer, 1);
+        assert!(pop_all(&mut buffer) == vec![1]);
+
+        push(&mut buffer, 3);
+        push(&mut buffer, 4);
+        assert!(pop_all(&mut buffer).is_empty());
+        push(&mut buffer, 2);
+        push(&mut buffer, 2);
+        assert!(pop_all(&mut buffer) == vec![2, 3, 4]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn gives_up_on_missing_packets() {
+        let mut buffer = ReorderBuffer::new(Duration::from_secs(0));
+        push(&mut buffer, 10);
+        pop_all(&mut buffer);
+
+        // 11 never arrives
+        push(&mut buffer, 12);
+        assert!(pop_all(&mut buffer) == vec![12]);
+
+        // & arriving late, is released straight away
+        push(&mut buffer, 11);
+        assert!(pop_all(&mut buffer) == vec![11]);
+        push(&mut buffer, 13);
+        assert!(pop_all(&mut buffer) == vec![13]);
+    }
+
+    #[test]
+    fn wrapping_sequences() {
+        let mut buffer = ReorderBuffer::new(Duration::from_secs(60));
+        push(&mut buffer, 65534);
+        pop_all(&mut buffer);
+        push(&mut buffer, 0);
+        push(&mut buffer, 65535);
+        assert!(pop_all(&mut buffer) == vec![255, 0]);
+    }
+
+    #[test]
+    fn packets_without_data() {
+        let mut buffer = ReorderBuffer::new(Duration::from_secs(60));
+        push(&mut buffer, 1);
+        pop_all(&mut buffer);
+        push(&mut buffer, 3);
+        assert!(pop_all(&mut buffer).is_empty());
+        buffer.mark_received(2);
+        assert!(pop_all(&mut buffer) == vec![3]);
+        assert!(buffer.is_empty());
+    }
+}
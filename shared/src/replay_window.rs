@@ -0,0 +1,114 @@
+use crate::sequence_buffer::{sequence_greater_than, SequenceNumber};
+
+/// The number of most recent packet sequence numbers a ReplayWindow tracks.
+/// Packets older than the newest packet received by this many or more are
+/// rejected
+pub const REPLAY_WINDOW_SIZE: u16 = 64;
+
+/// Tracks the sequence numbers of the most recently received packets, so that
+/// a packet received again, whether duplicated by the network or captured &
+/// replayed into the session, is rejected, as is a packet too old to tell
+#[derive(Debug, Default)]
+pub struct ReplayWindow {
+    newest: Option<SequenceNumber>,
+    // bit i is set if the packet `i` sequence numbers before the newest was
+    // received
+    received: u64,
+}
+
+impl ReplayWindow {
+    /// Creates a new ReplayWindow, with no packets received
+    pub fn new() -> Self {
+        ReplayWindow {
+            newest: None,
+            received: 0,
+        }
+    }
+
+    /// Returns whether a packet with the given sequence number has already
+    /// been received, or is too old to tell
+    pub fn is_replayed(&self, sequence: SequenceNumber) -> bool {
+        let newest = match self.newest {
+            Some(newest) => newest,
+            None => return false,
+        };
+        if sequence_greater_than(sequence, newest) {
+            return false;
+        }
+        let age = newest.wrapping_sub(sequence);
+        if age >= REPLAY_WINDOW_SIZE {
+            return true;
+        }
+        return self.received & (1 << age) != 0;
+    }
+
+    /// Records that a packet with the given sequence number has been received
+    pub fn record(&mut self, sequence: SequenceNumber) {
+        let newest = match self.newest {
+            Some(newest) => newest,
+            None => {
+                self.newest = Some(sequence);
+                self.received = 1;
+                return;
+            }
+        };
+        if sequence_greater_than(sequence, newest) {
+            let shift = sequence.wrapping_sub(newest);
+            self.received = match shift < REPLAY_WINDOW_SIZE {
+                true => (self.received << shift) | 1,
+                false => 1,
+            };
+            self.newest = Some(sequence);
+            return;
+        }
+        let age = newest.wrapping_sub(sequence);
+        if age < REPLAY_WINDOW_SIZE {
+            self.received |= 1 << age;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplayWindow, REPLAY_WINDOW_SIZE};
+
+    fn receive(window: &mut ReplayWindow, sequence: u16) -> bool {
+        if window.is_replayed(sequence) {
+            return false;
+        }
+        window.record(sequence);
+        return true;
+    }
+
+    #[test]
+    fn rejects_replays() {
+        let mut window = ReplayWindow::new();
+        assert!(receive(&mut window, 10));
+        assert!(!receive(&mut window, 10));
+        assert!(receive(&mut window, 12));
+        // reordered, but not yet received
+        assert!(receive(&mut window, 11));
+        assert!(!receive(&mut window, 11));
+        assert!(!receive(&mut window, 12));
+    }
+
+    #[test]
+    fn rejects_packets_older_than_the_window() {
+        let mut window = ReplayWindow::new();
+        assert!(receive(&mut window, 100));
+        assert!(receive(&mut window, 100 + REPLAY_WINDOW_SIZE));
+        assert!(!receive(&mut window, 100));
+        assert!(receive(&mut window, 101));
+    }
+
+    #[test]
+    fn wrapping_sequences() {
+        let mut window = ReplayWindow::new();
+        assert!(receive(&mut window, 65534));
+        assert!(receive(&mut window, 1));
+        assert!(receive(&mut window, 65535));
+        assert!(!receive(&mut window, 65534));
+        assert!(receive(&mut window, 0));
+        assert!(!receive(&mut window, 1));
+    }
+}
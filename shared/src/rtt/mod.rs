@@ -1,3 +1,4 @@
 pub(crate) mod rtt_data;
+pub(crate) mod rtt_history;
 pub(crate) mod rtt_measurer;
 pub(crate) mod rtt_tracker;
@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+/// Statistics of the most recent Round Trip Time samples of a connection, in
+/// milliseconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RttStats {
+    /// The number of samples the statistics were computed from
+    pub sample_count: usize,
+    /// The lowest sample
+    pub min: f32,
+    /// The median sample
+    pub median: f32,
+    /// The 95th percentile sample, which all but the worst 5% of samples are
+    /// at or below
+    pub p95: f32,
+    /// The highest sample
+    pub max: f32,
+}
+
+/// A ring buffer of the most recent Round Trip Time samples of a connection
+#[derive(Debug)]
+pub struct RttHistory {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl RttHistory {
+    /// Creates a new RttHistory, which keeps the given number of samples
+    pub fn new(capacity: usize) -> Self {
+        RttHistory {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records a sample, replacing the oldest sample if the history is full
+    pub fn record(&mut self, sample: f32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Gets statistics of the recorded samples, or None if there are none
+    pub fn get_stats(&self) -> Option<RttStats> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<f32> = self.samples.iter().copied().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        return Some(RttStats {
+            sample_count: sorted.len(),
+            min: sorted[0],
+            median: get_percentile(&sorted, 0.5),
+            p95: get_percentile(&sorted, 0.95),
+            max: sorted[sorted.len() - 1],
+        });
+    }
+}
+
+// The nearest-rank percentile of sorted samples
+fn get_percentile(sorted: &[f32], percentile: f32) -> f32 {
+    let rank = (percentile * sorted.len() as f32).ceil() as usize;
+    return sorted[rank.max(1).min(sorted.len()) - 1];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rtt::rtt_history::RttHistory;
+
+    #[test]
+    fn percentiles() {
+        let mut history = RttHistory::new(100);
+        for sample in (1..=100).rev() {
+            history.record(sample as f32);
+        }
+        let stats = history.get_stats().unwrap();
+        assert!(stats.sample_count == 100);
+        assert!(stats.min == 1.0);
+        assert!(stats.median == 50.0);
+        assert!(stats.p95 == 95.0);
+        assert!(stats.max == 100.0);
+    }
+
+    #[test]
+    fn window() {
+        let mut history = RttHistory::new(4);
+        assert!(history.get_stats().is_none());
+        for sample in [500.0, 10.0, 20.0, 30.0, 40.0].iter() {
+            history.record(*sample);
+        }
+        let stats = history.get_stats().unwrap();
+        assert!(stats.sample_count == 4);
+        assert!(stats.min == 10.0);
+        assert!(stats.median == 20.0);
+        assert!(stats.max == 40.0);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
-    rtt::{rtt_data::RttData, rtt_measurer::RttMeasurer},
-    sequence_buffer::SequenceBuffer,
+    rtt::{
+        rtt_data::RttData,
+        rtt_history::{RttHistory, RttStats},
+        rtt_measurer::RttMeasurer,
+    },
+    sequence_buffer::{sequence_greater_than, SequenceBuffer},
 };
 
 /// Tracks the current Round Trip Time of the connection
@@ -8,18 +12,51 @@ use crate::{
 pub struct RttTracker {
     rtt_measurer: RttMeasurer,
     rtt_data: SequenceBuffer<RttData>,
+    rtt_history: RttHistory,
+    last_sampled_ack: Option<u16>,
 }
 
 impl RttTracker {
     /// Creates a new RttTracker which is used to keep track of the Round Trip
-    /// Time of a connection
-    pub fn new(rtt_smoothing_factor: f32, rtt_max_value: u16) -> RttTracker {
+    /// Time of a connection, keeping the given number of samples to compute
+    /// statistics from
+    pub fn new(
+        rtt_smoothing_factor: f32,
+        rtt_max_value: u16,
+        rtt_history_size: usize,
+    ) -> RttTracker {
         RttTracker {
             rtt_measurer: RttMeasurer::new(rtt_smoothing_factor, rtt_max_value),
             rtt_data: SequenceBuffer::with_capacity(<u16>::max_value()),
+            rtt_history: RttHistory::new(rtt_history_size),
+            last_sampled_ack: None,
         }
     }
 
+    /// Process the ack of an incoming packet, recording the time elapsed since
+    /// the acked packet was sent as a sample. Each sent packet is sampled at
+    /// most once, by the first packet to ack it
+    pub fn process_ack(&mut self, ack_seq: u16) {
+        if let Some(last_sampled_ack) = self.last_sampled_ack {
+            if !sequence_greater_than(ack_seq, last_sampled_ack) {
+                return;
+            }
+        }
+        if let Some(rtt_data) = self.rtt_data.get_mut(ack_seq) {
+            let elapsed = rtt_data.sending_time.elapsed();
+            let sample =
+                elapsed.as_secs() as f32 * 1000.0 + elapsed.subsec_nanos() as f32 / 1000000.0;
+            self.rtt_history.record(sample);
+            self.last_sampled_ack = Some(ack_seq);
+        }
+    }
+
+    /// Get statistics of the most recent Round Trip Time samples, or None if
+    /// no packet has been acked yet
+    pub fn get_rtt_stats(&self) -> Option<RttStats> {
+        return self.rtt_history.get_stats();
+    }
+
     /// Process an incoming packet, calculates Round Trip Time
     pub fn process_incoming(&mut self, incoming_seq: u16) {
         let rtt_data = self.rtt_data.get_mut(incoming_seq);
@@ -3,12 +3,36 @@ use std::clone::Clone;
 /// Used to index packets that have been sent & received
 pub type SequenceNumber = u16;
 
-/// Collection to store data of any kind.
+/// Identifies a single insertion into a SequenceBuffer. Once the entry has
+/// been removed, or its slot reused by a later sequence number, including the
+/// same sequence number after the buffer has wrapped around, the handle no
+/// longer refers to anything
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SequenceHandle {
+    sequence_num: SequenceNumber,
+    generation: u32,
+}
+
+impl SequenceHandle {
+    /// Returns the sequence number the entry was inserted at
+    pub fn sequence_num(&self) -> SequenceNumber {
+        self.sequence_num
+    }
+}
+
+/// A fixed-capacity ring of entries indexed by wrapping sequence numbers,
+/// which keeps only the entries of the most recent `capacity` sequence
+/// numbers. Inserting a sequence number newer than any before it advances the
+/// buffer, discarding the entries which fall out of the window. It is used to
+/// track acked packets, but suits any buffer keyed by a wrapping sequence
+/// number, such as jitter or input buffers
 #[derive(Debug)]
 pub struct SequenceBuffer<T: Clone + Default> {
     sequence_num: SequenceNumber,
     entry_sequences: Box<[Option<SequenceNumber>]>,
+    entry_generations: Box<[u32]>,
     entries: Box<[T]>,
+    next_generation: u32,
 }
 
 impl<T: Clone + Default> SequenceBuffer<T> {
@@ -17,15 +41,47 @@ impl<T: Clone + Default> SequenceBuffer<T> {
         Self {
             sequence_num: 0,
             entry_sequences: vec![None; size as usize].into_boxed_slice(),
+            entry_generations: vec![0; size as usize].into_boxed_slice(),
             entries: vec![T::default(); size as usize].into_boxed_slice(),
+            next_generation: 0,
         }
     }
 
-    /// Returns the most recently stored sequence number.
+    /// Returns the sequence number after the newest one the buffer has
+    /// advanced to, which is the next sequence number expected
     pub fn sequence_num(&self) -> SequenceNumber {
         self.sequence_num
     }
 
+    /// Returns the number of sequence numbers the buffer keeps entries for
+    pub fn capacity(&self) -> usize {
+        self.entry_sequences.len()
+    }
+
+    /// Returns the number of entries currently stored
+    pub fn len(&self) -> usize {
+        self.entry_sequences
+            .iter()
+            .filter(|sequence| sequence.is_some())
+            .count()
+    }
+
+    /// Returns whether no entries are stored
+    pub fn is_empty(&self) -> bool {
+        self.entry_sequences
+            .iter()
+            .all(|sequence| sequence.is_none())
+    }
+
+    /// Returns a reference to the entry with the given sequence number.
+    pub fn get(&self, sequence_num: SequenceNumber) -> Option<&T> {
+        if self.exists(sequence_num) {
+            let index = self.index(sequence_num);
+            return Some(&self.entries[index]);
+        }
+        None
+    }
+
     /// Returns a mutable reference to the entry with the given sequence number.
     pub fn get_mut(&mut self, sequence_num: SequenceNumber) -> Option<&mut T> {
         if self.exists(sequence_num) {
@@ -39,6 +95,19 @@ impl<T: Clone + Default> SequenceBuffer<T> {
     /// sequence number is "too old", the entry will not be inserted and no
     /// reference will be returned.
     pub fn insert(&mut self, sequence_num: SequenceNumber, entry: T) -> Option<&mut T> {
+        let handle = self.insert_with_handle(sequence_num, entry)?;
+        let index = self.index(handle.sequence_num);
+        Some(&mut self.entries[index])
+    }
+
+    /// Inserts the entry data into the sequence buffer, and returns a handle
+    /// which refers to this insertion only. Returns None if the requested
+    /// sequence number is "too old".
+    pub fn insert_with_handle(
+        &mut self,
+        sequence_num: SequenceNumber,
+        entry: T,
+    ) -> Option<SequenceHandle> {
         // sequence number is too old to insert into the buffer
         if sequence_less_than(
             sequence_num,
@@ -50,10 +119,36 @@ impl<T: Clone + Default> SequenceBuffer<T> {
 
         self.advance_sequence(sequence_num);
 
+        let generation = self.next_generation;
+        self.next_generation = self.next_generation.wrapping_add(1);
+
         let index = self.index(sequence_num);
         self.entry_sequences[index] = Some(sequence_num);
+        self.entry_generations[index] = generation;
         self.entries[index] = entry;
-        Some(&mut self.entries[index])
+        Some(SequenceHandle {
+            sequence_num,
+            generation,
+        })
+    }
+
+    /// Returns a reference to the entry of the given handle, or None if it
+    /// has since been removed or replaced
+    pub fn get_by_handle(&self, handle: &SequenceHandle) -> Option<&T> {
+        if self.is_current(handle) {
+            let index = self.index(handle.sequence_num);
+            return Some(&self.entries[index]);
+        }
+        None
+    }
+
+    /// Removes the entry of the given handle, unless it has since been
+    /// removed or replaced
+    pub fn remove_by_handle(&mut self, handle: &SequenceHandle) -> Option<T> {
+        if self.is_current(handle) {
+            return self.remove(handle.sequence_num);
+        }
+        None
     }
 
     /// Returns whether or not we have previously inserted an entry for the
@@ -77,6 +172,36 @@ impl<T: Clone + Default> SequenceBuffer<T> {
         None
     }
 
+    /// Iterates over the stored entries & their sequence numbers, from
+    /// oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = (SequenceNumber, &T)> + '_ {
+        let oldest = self
+            .sequence_num
+            .wrapping_sub(self.entry_sequences.len() as u16);
+        (0..self.entry_sequences.len() as u16)
+            .map(move |offset| oldest.wrapping_add(offset))
+            .filter_map(move |sequence_num| {
+                self.get(sequence_num).map(|entry| (sequence_num, entry))
+            })
+    }
+
+    /// Returns the oldest stored entry & its sequence number
+    pub fn oldest(&self) -> Option<(SequenceNumber, &T)> {
+        self.iter().next()
+    }
+
+    /// Returns the newest stored entry & its sequence number
+    pub fn newest(&self) -> Option<(SequenceNumber, &T)> {
+        (1..=self.entry_sequences.len() as u16)
+            .map(|offset| self.sequence_num.wrapping_sub(offset))
+            .find_map(|sequence_num| self.get(sequence_num).map(|entry| (sequence_num, entry)))
+    }
+
+    fn is_current(&self, handle: &SequenceHandle) -> bool {
+        let index = self.index(handle.sequence_num);
+        self.exists(handle.sequence_num) && self.entry_generations[index] == handle.generation
+    }
+
     // Advances the sequence number while removing older entries.
     fn advance_sequence(&mut self, sequence_num: SequenceNumber) {
         if sequence_greater_than(sequence_num.wrapping_add(1), self.sequence_num) {
@@ -92,8 +217,12 @@ impl<T: Clone + Default> SequenceBuffer<T> {
         }
 
         if finish_sequence - start_sequence < self.entry_sequences.len() as u32 {
+            // clear by slot, as a slot may still hold an entry from a sequence
+            // number older than the window
             for sequence in start_sequence..=finish_sequence {
-                self.remove(sequence as u16);
+                let index = self.index(sequence as u16);
+                self.entries[index] = T::default();
+                self.entry_sequences[index] = None;
             }
         } else {
             for index in 0..self.entry_sequences.len() {
@@ -109,10 +238,157 @@ impl<T: Clone + Default> SequenceBuffer<T> {
     }
 }
 
+/// Returns whether the first sequence number is more recent than the second,
+/// accounting for wrap-around
 pub fn sequence_greater_than(s1: u16, s2: u16) -> bool {
     ((s1 > s2) && (s1 - s2 <= 32768)) || ((s1 < s2) && (s2 - s1 > 32768))
 }
 
+/// Returns whether the first sequence number is older than the second,
+/// accounting for wrap-around
 pub fn sequence_less_than(s1: u16, s2: u16) -> bool {
     sequence_greater_than(s2, s1)
 }
+
+/// Expands the low byte of a sequence number into the full sequence number
+/// nearest to a reference, accounting for wrap-around
+pub fn expand_sequence(low_byte: u8, reference: u16) -> u16 {
+    let offset = low_byte.wrapping_sub(reference as u8) as i8;
+    reference.wrapping_add(offset as i16 as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{sequence_less_than, SequenceBuffer, SequenceNumber};
+
+    // A small deterministic generator, so that failures are reproducible
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) as u32
+        }
+    }
+
+    fn in_window(
+        sequence_num: SequenceNumber,
+        buffer_sequence: SequenceNumber,
+        capacity: u16,
+    ) -> bool {
+        !sequence_less_than(sequence_num, buffer_sequence.wrapping_sub(capacity))
+            && sequence_less_than(sequence_num, buffer_sequence)
+    }
+
+    #[test]
+    fn matches_model() {
+        for &capacity in [1u16, 8, 32, 256].iter() {
+            let mut rng = Lcg(u64::from(capacity));
+            let mut buffer = SequenceBuffer::<u32>::with_capacity(capacity);
+            let mut model: HashMap<SequenceNumber, u32> = HashMap::new();
+            let mut newest: SequenceNumber = 0;
+
+            for step in 0..20000u32 {
+                // mostly small steps around the newest sequence number, with
+                // occasional large jumps & wrap-around
+                let offset = match rng.next() % 10 {
+                    0 => rng.next() as u16,
+                    1..=3 => (rng.next() % (u32::from(capacity) * 2 + 1)) as u16,
+                    _ => (rng.next() % 4) as u16,
+                };
+                let sequence_num = if rng.next() % 3 == 0 {
+                    newest.wrapping_sub(offset)
+                } else {
+                    newest.wrapping_add(offset)
+                };
+
+                match rng.next() % 4 {
+                    0 => {
+                        assert!(buffer.remove(sequence_num) == model.remove(&sequence_num));
+                    }
+                    _ => {
+                        let too_old = sequence_less_than(
+                            sequence_num,
+                            buffer.sequence_num().wrapping_sub(capacity),
+                        );
+                        let inserted = buffer.insert(sequence_num, step).is_some();
+                        assert!(inserted == !too_old);
+                        if inserted {
+                            let buffer_sequence = buffer.sequence_num();
+                            model.retain(|key, _| in_window(*key, buffer_sequence, capacity));
+                            model.insert(sequence_num, step);
+                            newest = buffer_sequence.wrapping_sub(1);
+                        }
+                    }
+                }
+
+                assert!(buffer.len() == model.len());
+                for (key, value) in model.iter() {
+                    assert!(buffer.get(*key) == Some(value));
+                }
+                let probe = newest.wrapping_sub(rng.next() as u16 % (capacity * 2));
+                assert!(buffer.get(probe) == model.get(&probe));
+
+                let iterated: Vec<(SequenceNumber, u32)> =
+                    buffer.iter().map(|(key, value)| (key, *value)).collect();
+                assert!(iterated.len() == model.len());
+                for pair in iterated.windows(2) {
+                    assert!(sequence_less_than(pair[0].0, pair[1].0));
+                }
+                assert!(
+                    buffer.oldest().map(|(key, _)| key) == iterated.first().map(|(key, _)| *key)
+                );
+                assert!(
+                    buffer.newest().map(|(key, _)| key) == iterated.last().map(|(key, _)| *key)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn iteration_across_wrap() {
+        let mut buffer = SequenceBuffer::<u8>::with_capacity(8);
+        for sequence_num in [65533u16, 65535, 0, 2].iter() {
+            buffer.insert(*sequence_num, *sequence_num as u8);
+        }
+        let keys: Vec<SequenceNumber> = buffer.iter().map(|(key, _)| key).collect();
+        assert!(keys == vec![65533, 65535, 0, 2]);
+        assert!(buffer.oldest() == Some((65533, &253)));
+        assert!(buffer.newest() == Some((2, &2)));
+
+        // advancing past the window discards the older entries
+        buffer.insert(7, 7);
+        let keys: Vec<SequenceNumber> = buffer.iter().map(|(key, _)| key).collect();
+        assert!(keys == vec![0, 2, 7]);
+        assert!(buffer.get(65535).is_none());
+    }
+
+    #[test]
+    fn stale_handles() {
+        let mut buffer = SequenceBuffer::<u8>::with_capacity(4);
+        let first = buffer.insert_with_handle(1, 10).unwrap();
+        assert!(first.sequence_num() == 1);
+        assert!(buffer.get_by_handle(&first) == Some(&10));
+
+        // replacing the entry at the same sequence number invalidates the handle
+        let second = buffer.insert_with_handle(1, 20).unwrap();
+        assert!(buffer.get_by_handle(&first).is_none());
+        assert!(buffer.remove_by_handle(&first).is_none());
+        assert!(buffer.get_by_handle(&second) == Some(&20));
+
+        // as does the buffer wrapping around to the same sequence number
+        for sequence_num in 2..=65535u16 {
+            buffer.insert(sequence_num, 0);
+        }
+        buffer.insert(0, 0);
+        let third = buffer.insert_with_handle(1, 30).unwrap();
+        assert!(buffer.get_by_handle(&second).is_none());
+        assert!(buffer.remove_by_handle(&third) == Some(30));
+        assert!(buffer.get(1).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+use std::net::SocketAddr;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    packet_reader::PacketReader,
+    server_status::ServerStatus,
+    unconnected_message::MAX_UNCONNECTED_DATA_SIZE,
+    utils::{read_socket_address, write_socket_address},
+};
+
+// The total, start & entry count written before the entries of a page
+const SERVER_LIST_PAGE_HEADER_SIZE: usize = 5;
+
+// The most bytes a socket address is written in
+const MAX_SOCKET_ADDRESS_SIZE: usize = 19;
+
+/// The largest a registered Server's status can be, so that it always fits in
+/// a page of the server list along with the Server's address
+pub const MAX_REGISTERED_STATUS_SIZE: usize =
+    MAX_UNCONNECTED_DATA_SIZE - SERVER_LIST_PAGE_HEADER_SIZE - MAX_SOCKET_ADDRESS_SIZE;
+
+/// A page of the list of Servers registered with a master server, as many as
+/// fit in a single unconnected message
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerListPage {
+    /// The number of Servers registered in total
+    pub total: u16,
+    /// The index in the whole list of the first Server in this page
+    pub start: u16,
+    /// The address & status of each Server in this page
+    pub servers: Vec<(SocketAddr, ServerStatus)>,
+}
+
+impl ServerListPage {
+    /// Builds the page of the given list which starts at the given index,
+    /// including as many Servers as fit in a single unconnected message
+    pub fn build(list: &[(SocketAddr, ServerStatus)], start: u16) -> Self {
+        let mut size = SERVER_LIST_PAGE_HEADER_SIZE;
+        let mut servers = Vec::new();
+        for (address, status) in list.iter().skip(start as usize) {
+            let status_size = match status.get_size() {
+                Some(status_size) => status_size,
+                None => continue,
+            };
+            size += MAX_SOCKET_ADDRESS_SIZE + status_size;
+            if size > MAX_UNCONNECTED_DATA_SIZE || servers.len() == 255 {
+                break;
+            }
+            servers.push((*address, status.clone()));
+        }
+        return ServerListPage {
+            total: list.len().min(65535) as u16,
+            start,
+            servers,
+        };
+    }
+
+    /// Writes the page into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u16::<BigEndian>(self.total).unwrap();
+        out_bytes.write_u16::<BigEndian>(self.start).unwrap();
+        out_bytes.write_u8(self.servers.len() as u8).unwrap();
+        for (address, status) in self.servers.iter() {
+            write_socket_address(address, out_bytes);
+            status.write(out_bytes);
+        }
+    }
+
+    /// Reads a page written with `write()`. Returns None if the bytes are
+    /// malformed
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let cursor = reader.get_cursor();
+        let total = cursor.read_u16::<BigEndian>().ok()?;
+        let start = cursor.read_u16::<BigEndian>().ok()?;
+        let count = cursor.read_u8().ok()?;
+        let mut servers = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let address = read_socket_address(reader)?;
+            let status = ServerStatus::read(reader)?;
+            servers.push((address, status));
+        }
+        return Some(ServerListPage {
+            total,
+            start,
+            servers,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use crate::{
+        PacketReader, ServerListPage, ServerStatus, MAX_REGISTERED_STATUS_SIZE,
+        MAX_UNCONNECTED_DATA_SIZE,
+    };
+
+    fn server(port: u16, name_length: usize) -> (SocketAddr, ServerStatus) {
+        let address: SocketAddr = format!("[::1]:{}", port).parse().unwrap();
+        let mut status = ServerStatus::default();
+        status.name = "n".repeat(name_length);
+        return (address, status);
+    }
+
+    #[test]
+    fn pages() {
+        let list: Vec<(SocketAddr, ServerStatus)> =
+            (0..10).map(|index| server(1000 + index, 100)).collect();
+        let first = ServerListPage::build(&list, 0);
+        assert!(first.total == 10);
+        assert!(!first.servers.is_empty() && first.servers.len() < 10);
+
+        let mut bytes = Vec::new();
+        first.write(&mut bytes);
+        assert!(bytes.len() <= MAX_UNCONNECTED_DATA_SIZE);
+        let mut reader = PacketReader::new(&bytes);
+        assert!(ServerListPage::read(&mut reader) == Some(first.clone()));
+
+        // every Server appears in exactly one page
+        let mut start = 0;
+        let mut seen = Vec::new();
+        while (start as usize) < list.len() {
+            let page = ServerListPage::build(&list, start);
+            start += page.servers.len() as u16;
+            seen.extend(page.servers);
+        }
+        assert!(seen == list);
+    }
+
+    #[test]
+    fn largest_status_fits() {
+        let (address, mut status) = server(1, 0);
+        status.name = "n".repeat(255);
+        status.map = "m".repeat(MAX_REGISTERED_STATUS_SIZE - 5 - 2 - 255);
+        assert!(status.get_size() == Some(MAX_REGISTERED_STATUS_SIZE));
+        let page = ServerListPage::build(&[(address, status)], 0);
+        assert!(page.servers.len() == 1);
+    }
+}
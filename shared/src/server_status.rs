@@ -0,0 +1,133 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    packet_reader::PacketReader,
+    unconnected_message::MAX_UNCONNECTED_DATA_SIZE,
+    utils::{read_string, write_string},
+};
+
+/// The status of a Server, sent in answer to a status query so that server
+/// browsers can list Servers without connecting to them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerStatus {
+    /// The name of the Server
+    pub name: String,
+    /// The name of the map or level the Server is running
+    pub map: String,
+    /// The number of Clients connected to the Server. This is filled in by
+    /// the Server when it answers a query
+    pub user_count: u16,
+    /// The most Clients the Server accepts, or 0 if there is no limit
+    pub max_users: u16,
+    /// Application-defined keys & values, such as the game mode
+    pub properties: Vec<(String, String)>,
+}
+
+impl ServerStatus {
+    /// Gets the number of bytes the status is written in, or None if it can't
+    /// be written, because a string is longer than 255 bytes, there are more
+    /// than 255 properties, or the whole is longer than
+    /// `MAX_UNCONNECTED_DATA_SIZE`
+    pub fn get_size(&self) -> Option<usize> {
+        if self.properties.len() > 255 {
+            return None;
+        }
+        let mut strings = vec![&self.name, &self.map];
+        for (key, value) in self.properties.iter() {
+            strings.push(key);
+            strings.push(value);
+        }
+        // the user count, max users & property count
+        let mut size = 5;
+        for string in strings {
+            if string.len() > 255 {
+                return None;
+            }
+            size += 1 + string.len();
+        }
+        if size > MAX_UNCONNECTED_DATA_SIZE {
+            return None;
+        }
+        return Some(size);
+    }
+
+    /// Writes the status into an outgoing byte stream. Returns false, writing
+    /// nothing, if the status is too large, as described in `get_size()`
+    pub fn write(&self, out_bytes: &mut Vec<u8>) -> bool {
+        if self.get_size().is_none() {
+            return false;
+        }
+        write_string(&self.name, out_bytes);
+        write_string(&self.map, out_bytes);
+        out_bytes.write_u16::<BigEndian>(self.user_count).unwrap();
+        out_bytes.write_u16::<BigEndian>(self.max_users).unwrap();
+        out_bytes.write_u8(self.properties.len() as u8).unwrap();
+        for (key, value) in self.properties.iter() {
+            write_string(key, out_bytes);
+            write_string(value, out_bytes);
+        }
+        return true;
+    }
+
+    /// Reads a status written with `write()`. Returns None if the bytes are
+    /// malformed
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let name = read_string(reader)?;
+        let map = read_string(reader)?;
+        let cursor = reader.get_cursor();
+        let user_count = cursor.read_u16::<BigEndian>().ok()?;
+        let max_users = cursor.read_u16::<BigEndian>().ok()?;
+        let property_count = cursor.read_u8().ok()?;
+        let mut properties = Vec::with_capacity(property_count as usize);
+        for _ in 0..property_count {
+            let key = read_string(reader)?;
+            let value = read_string(reader)?;
+            properties.push((key, value));
+        }
+        return Some(ServerStatus {
+            name,
+            map,
+            user_count,
+            max_users,
+            properties,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PacketReader, ServerStatus, MAX_UNCONNECTED_DATA_SIZE};
+
+    #[test]
+    fn read_write() {
+        let status = ServerStatus {
+            name: "Naia Server".to_string(),
+            map: "de_dust".to_string(),
+            user_count: 12,
+            max_users: 16,
+            properties: vec![("mode".to_string(), "ctf".to_string())],
+        };
+        let mut bytes = Vec::new();
+        assert!(status.write(&mut bytes));
+        assert!(Some(bytes.len()) == status.get_size());
+        let mut reader = PacketReader::new(&bytes);
+        assert!(ServerStatus::read(&mut reader) == Some(status));
+
+        let mut reader = PacketReader::new(&bytes[..bytes.len() - 1]);
+        assert!(ServerStatus::read(&mut reader).is_none());
+    }
+
+    #[test]
+    fn too_large() {
+        let mut status = ServerStatus::default();
+        status.name = "n".repeat(256);
+        assert!(status.get_size().is_none());
+        assert!(!status.write(&mut Vec::new()));
+
+        status.name = String::new();
+        status.properties = (0..MAX_UNCONNECTED_DATA_SIZE / 8)
+            .map(|index| (format!("key{}", index), "value".to_string()))
+            .collect();
+        assert!(status.get_size().is_none());
+    }
+}
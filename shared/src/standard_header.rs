@@ -2,7 +2,14 @@ use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
 use std::io::Read;
 
-use crate::packet_type::PacketType;
+use crate::{packet_type::PacketType, sequence_buffer::expand_sequence};
+
+// The packet type occupies the low bits of the first byte of a header, and in
+// a compact header the high bits flag which optional fields follow
+const PACKET_TYPE_MASK: u8 = 0x1F;
+const COMPACT_FLAG: u8 = 0x80;
+const HOST_TICK_FLAG: u8 = 0x40;
+const TIME_DILATION_FLAG: u8 = 0x20;
 
 #[derive(Copy, Clone, Debug)]
 /// This header provides reliability information.
@@ -15,6 +22,14 @@ pub struct StandardHeader {
     ack_seq: u16,
     // This is an bitfield of all last 32 acknowledged packages
     ack_field: u32,
+    // This is the tick of the sending host, if it keeps one
+    host_tick: Option<u16>,
+    // This is the time dilation the Server asks of the Client, in thousandths
+    // of the Client's tick rate
+    time_dilation: i8,
+    // Whether this header is written in the compact form, which carries only
+    // the low byte of each sequence number & 16 bits of acks
+    compact: bool,
 }
 
 impl StandardHeader {
@@ -24,17 +39,36 @@ impl StandardHeader {
     /// packets, containing sequence numbers in the range [remote sequence - 32,
     /// remote sequence]. We set bit n (in [1,32]) in ack bits to 1 if the
     /// sequence number remote sequence - n is in the received queue.
-    pub fn new(p_type: PacketType, seq_num: u16, last_seq: u16, bit_field: u32) -> StandardHeader {
+    /// The header also carries the tick of the sending host & the time
+    /// dilation requested of the receiving host.
+    /// A compact header writes only the low byte of the sequence number & the
+    /// ack, the first 16 bits of the ack bitfield, and omits the tick & time
+    /// dilation when there are none, which the receiving host expands again
+    /// with `expand_sequences()`
+    pub fn new(
+        p_type: PacketType,
+        seq_num: u16,
+        last_seq: u16,
+        bit_field: u32,
+        host_tick: Option<u16>,
+        time_dilation: i8,
+        compact: bool,
+    ) -> StandardHeader {
         StandardHeader {
             p_type,
             seq: seq_num,
             ack_seq: last_seq,
             ack_field: bit_field,
+            host_tick,
+            time_dilation,
+            compact,
         }
     }
 
+    /// The size of a full header, which is also the largest size of a compact
+    /// header
     pub const fn bytes_number() -> usize {
-        return 9;
+        return 13;
     }
 
     /// Returns the sequence number from this packet.
@@ -52,40 +86,255 @@ impl StandardHeader {
         self.ack_seq
     }
 
+    /// Returns the tick of the sending host, if it keeps one
+    pub fn host_tick(&self) -> Option<u16> {
+        self.host_tick
+    }
+
+    /// Returns the time dilation requested of the receiving host
+    pub fn time_dilation(&self) -> i8 {
+        self.time_dilation
+    }
+
+    /// Returns whether this header is written in the compact form
+    pub fn is_compact(&self) -> bool {
+        self.compact
+    }
+
+    /// Returns the number of packets before the ack which the ack bitfield
+    /// covers
+    pub fn ack_field_size(&self) -> u16 {
+        if self.compact {
+            return 16;
+        }
+        return 32;
+    }
+
+    /// Expands the sequence number & ack of a compact header, which were read
+    /// as a single byte each, into full sequence numbers, given the most recent
+    /// sequence number received from the sending host & the most recent one
+    /// sent to it. This is only correct while packets arrive less than 128
+    /// sequence numbers out of order, which is why compact headers suit
+    /// low-rate connections
+    pub fn expand_sequences(&mut self, last_remote_seq: u16, last_local_seq: u16) {
+        if !self.compact {
+            return;
+        }
+        self.seq = expand_sequence(self.seq as u8, last_remote_seq);
+        self.ack_seq = expand_sequence(self.ack_seq as u8, last_local_seq);
+    }
+
     pub fn write(&self, buffer: &mut Vec<u8>) {
+        if self.compact {
+            self.write_compact(buffer);
+            return;
+        }
         buffer.write_u8(self.p_type as u8).unwrap();
         buffer.write_u16::<BigEndian>(self.seq).unwrap();
         buffer.write_u16::<BigEndian>(self.ack_seq).unwrap();
         buffer.write_u32::<BigEndian>(self.ack_field).unwrap();
+        match self.host_tick {
+            Some(host_tick) => {
+                buffer.write_u8(1).unwrap();
+                buffer.write_u16::<BigEndian>(host_tick).unwrap();
+            }
+            None => {
+                buffer.write_u8(0).unwrap();
+                buffer.write_u16::<BigEndian>(0).unwrap();
+            }
+        }
+        buffer.write_i8(self.time_dilation).unwrap();
     }
 
-    pub fn read(mut msg: &[u8]) -> (Self, Box<[u8]>) {
-        let p_type: PacketType = msg.read_u8().unwrap().into();
-        let seq = msg.read_u16::<BigEndian>().unwrap();
-        let ack_seq = msg.read_u16::<BigEndian>().unwrap();
-        let ack_field = msg.read_u32::<BigEndian>().unwrap();
+    fn write_compact(&self, buffer: &mut Vec<u8>) {
+        let mut first_byte = self.p_type as u8 | COMPACT_FLAG;
+        if self.host_tick.is_some() {
+            first_byte |= HOST_TICK_FLAG;
+        }
+        if self.time_dilation != 0 {
+            first_byte |= TIME_DILATION_FLAG;
+        }
+        buffer.write_u8(first_byte).unwrap();
+        buffer.write_u8(self.seq as u8).unwrap();
+        buffer.write_u8(self.ack_seq as u8).unwrap();
+        buffer
+            .write_u16::<BigEndian>(self.ack_field as u16)
+            .unwrap();
+        if let Some(host_tick) = self.host_tick {
+            buffer.write_u16::<BigEndian>(host_tick).unwrap();
+        }
+        if self.time_dilation != 0 {
+            buffer.write_i8(self.time_dilation).unwrap();
+        }
+    }
 
-        let mut buffer = Vec::new();
-        msg.read_to_end(&mut buffer).unwrap();
+    /// Gets the size of a header, given its first byte
+    fn get_size(first_byte: u8) -> usize {
+        if first_byte & COMPACT_FLAG == 0 {
+            return StandardHeader::bytes_number();
+        }
+        let mut size = 5;
+        if first_byte & HOST_TICK_FLAG != 0 {
+            size += 2;
+        }
+        if first_byte & TIME_DILATION_FLAG != 0 {
+            size += 1;
+        }
+        return size;
+    }
 
-        (
-            StandardHeader {
+    /// Reads a header in either form. The sequence number & ack of a compact
+    /// header hold only their low byte until `expand_sequences()` is called.
+    /// Returns None if the packet is too short to hold the header
+    pub fn read(mut msg: &[u8]) -> Option<(Self, Box<[u8]>)> {
+        let first_byte = *msg.first()?;
+        if msg.len() < StandardHeader::get_size(first_byte) {
+            return None;
+        }
+        msg = &msg[1..];
+        let p_type: PacketType = (first_byte & PACKET_TYPE_MASK).into();
+
+        let header = if first_byte & COMPACT_FLAG != 0 {
+            let seq = msg.read_u8().ok()? as u16;
+            let ack_seq = msg.read_u8().ok()? as u16;
+            let ack_field = msg.read_u16::<BigEndian>().ok()? as u32;
+            let host_tick = if first_byte & HOST_TICK_FLAG != 0 {
+                Some(msg.read_u16::<BigEndian>().ok()?)
+            } else {
+                None
+            };
+            let time_dilation = if first_byte & TIME_DILATION_FLAG != 0 {
+                msg.read_i8().ok()?
+            } else {
+                0
+            };
+            StandardHeader::new(
                 p_type,
                 seq,
                 ack_seq,
                 ack_field,
-            },
-            buffer.into_boxed_slice(),
-        )
+                host_tick,
+                time_dilation,
+                true,
+            )
+        } else {
+            let seq = msg.read_u16::<BigEndian>().ok()?;
+            let ack_seq = msg.read_u16::<BigEndian>().ok()?;
+            let ack_field = msg.read_u32::<BigEndian>().ok()?;
+            let has_host_tick = msg.read_u8().ok()? != 0;
+            let host_tick = msg.read_u16::<BigEndian>().ok()?;
+            let time_dilation = msg.read_i8().ok()?;
+            StandardHeader::new(
+                p_type,
+                seq,
+                ack_seq,
+                ack_field,
+                if has_host_tick { Some(host_tick) } else { None },
+                time_dilation,
+                false,
+            )
+        };
+
+        let mut buffer = Vec::new();
+        msg.read_to_end(&mut buffer).ok()?;
+
+        return Some((header, buffer.into_boxed_slice()));
+    }
+
+    /// Gets the PacketType of a packet from its header. Returns None if the
+    /// packet is too short to hold the header
+    pub fn get_packet_type(payload: &[u8]) -> Option<PacketType> {
+        let first_byte = *payload.first()?;
+        if payload.len() < StandardHeader::get_size(first_byte) {
+            return None;
+        }
+        return Some((first_byte & PACKET_TYPE_MASK).into());
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        packet_type::PacketType, sequence_buffer::expand_sequence, standard_header::StandardHeader,
+    };
 
-    pub fn get_packet_type(mut payload: &[u8]) -> PacketType {
-        payload.read_u8().unwrap().into()
+    #[test]
+    fn compact_round_trip() {
+        let header = StandardHeader::new(
+            PacketType::Data,
+            1000,
+            2000,
+            0xFFFF_0005,
+            Some(77),
+            -12,
+            true,
+        );
+        let mut bytes = Vec::new();
+        header.write(&mut bytes);
+        bytes.push(42);
+        assert!(bytes.len() == 9);
+        assert!(StandardHeader::get_packet_type(&bytes) == Some(PacketType::Data));
+
+        let (mut read_header, payload) = StandardHeader::read(&bytes).unwrap();
+        assert!(payload.as_ref() == [42]);
+        read_header.expand_sequences(990, 2003);
+        assert!(read_header.sequence() == 1000);
+        assert!(read_header.ack_seq() == 2000);
+        assert!(read_header.ack_field() == 5);
+        assert!(read_header.host_tick() == Some(77));
+        assert!(read_header.time_dilation() == -12);
+    }
+
+    #[test]
+    fn compact_omits_absent_fields() {
+        let header = StandardHeader::new(PacketType::Heartbeat, 3, 4, 0, None, 0, true);
+        let mut bytes = Vec::new();
+        header.write(&mut bytes);
+        assert!(bytes.len() == 5);
+
+        let (read_header, _) = StandardHeader::read(&bytes).unwrap();
+        assert!(read_header.is_compact());
+        assert!(read_header.host_tick().is_none());
+        assert!(read_header.time_dilation() == 0);
+    }
+
+    #[test]
+    fn rejects_truncated_compact_headers() {
+        let header = StandardHeader::new(PacketType::Data, 1, 2, 3, Some(4), 5, true);
+        let mut bytes = Vec::new();
+        header.write(&mut bytes);
+        assert!(bytes.len() == 8);
+
+        // every field but the first byte flags which of the optional ones
+        // follow, so a header cut short anywhere is rejected
+        for length in 0..bytes.len() {
+            assert!(StandardHeader::read(&bytes[..length]).is_none());
+            assert!(StandardHeader::get_packet_type(&bytes[..length]).is_none());
+        }
+        assert!(StandardHeader::read(&bytes).is_some());
+    }
+
+    #[test]
+    fn rejects_truncated_full_headers() {
+        let header = StandardHeader::new(PacketType::Heartbeat, 1, 2, 3, Some(4), 5, false);
+        let mut bytes = Vec::new();
+        header.write(&mut bytes);
+        assert!(bytes.len() == StandardHeader::bytes_number());
+
+        for length in 0..bytes.len() {
+            assert!(StandardHeader::read(&bytes[..length]).is_none());
+            assert!(StandardHeader::get_packet_type(&bytes[..length]).is_none());
+        }
+        let (read_header, payload) = StandardHeader::read(&bytes).unwrap();
+        assert!(read_header.host_tick() == Some(4));
+        assert!(payload.is_empty());
     }
 
-    pub fn get_sequence(mut payload: &[u8]) -> u16 {
-        let _ = payload.read_u8().unwrap();
-        let seq = payload.read_u16::<BigEndian>().unwrap();
-        return seq;
+    #[test]
+    fn sequence_expansion() {
+        assert!(expand_sequence(2, 65530) == 2);
+        assert!(expand_sequence(250, 4) == 65530);
+        assert!(expand_sequence(0x34, 0x1230) == 0x1234);
+        assert!(expand_sequence(0x20, 0x1230) == 0x1220);
     }
 }
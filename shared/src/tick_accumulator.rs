@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+/// The most ticks a single frame will process. If frames fall further behind
+/// than this, such as after the application was suspended, the excess time is
+/// discarded rather than processed in a burst which would only fall further
+/// behind
+pub const MAX_TICKS_PER_FRAME: u16 = 8;
+
+/// Accumulates the duration of variable-length frames into fixed-length ticks,
+/// so that an application which renders at one rate can process network ticks
+/// at another
+#[derive(Debug)]
+pub struct TickAccumulator {
+    tick_interval: Duration,
+    accumulated: Duration,
+    time_scale: f32,
+}
+
+impl TickAccumulator {
+    /// Creates a new TickAccumulator, given the duration of each tick
+    pub fn new(tick_interval: Duration) -> Self {
+        TickAccumulator {
+            tick_interval,
+            accumulated: Duration::from_secs(0),
+            time_scale: 1.0,
+        }
+    }
+
+    /// Sets the rate at which frame durations accumulate, so that a scale
+    /// above 1 produces ticks slightly faster than real time, and below 1
+    /// slightly slower
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+
+    /// Sets the duration of each tick, such as after the Server has changed
+    /// its tick rate. The progress toward the next tick is kept as the same
+    /// fraction of the new duration, so interpolation doesn't jump
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        let fraction = self.get_interpolation_fraction();
+        self.tick_interval = tick_interval;
+        self.accumulated = tick_interval.mul_f32(fraction);
+    }
+
+    /// Gets the duration of each tick
+    pub fn get_tick_interval(&self) -> Duration {
+        return self.tick_interval;
+    }
+
+    /// Adds the duration of a frame, and returns the number of whole ticks
+    /// which have accumulated & should be processed during the frame
+    pub fn frame_begin(&mut self, frame_duration: Duration) -> u16 {
+        if self.tick_interval == Duration::from_secs(0) {
+            return 0;
+        }
+
+        if self.time_scale == 1.0 {
+            self.accumulated += frame_duration;
+        } else {
+            self.accumulated += frame_duration.mul_f32(self.time_scale);
+        }
+        let mut tick_count: u16 = 0;
+        while self.accumulated >= self.tick_interval {
+            self.accumulated -= self.tick_interval;
+            tick_count += 1;
+            if tick_count == MAX_TICKS_PER_FRAME {
+                // only the progress toward the next tick is kept
+                let remainder = self.accumulated.as_nanos() % self.tick_interval.as_nanos();
+                self.accumulated = Duration::from_nanos(remainder as u64);
+                break;
+            }
+        }
+        return tick_count;
+    }
+
+    /// Gets how far the accumulated time is between the last processed tick &
+    /// the next one, from 0 to 1, which is used to interpolate rendering
+    /// between the state of the two ticks
+    pub fn get_interpolation_fraction(&self) -> f32 {
+        if self.tick_interval == Duration::from_secs(0) {
+            return 0.0;
+        }
+        return self.accumulated.as_secs_f32() / self.tick_interval.as_secs_f32();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{TickAccumulator, MAX_TICKS_PER_FRAME};
+
+    #[test]
+    fn fast_frames() {
+        // rendering at 4 frames per tick
+        let mut accumulator = TickAccumulator::new(Duration::from_millis(40));
+        let tick_counts: Vec<u16> = (0..8)
+            .map(|_| accumulator.frame_begin(Duration::from_millis(10)))
+            .collect();
+        assert!(tick_counts == vec![0, 0, 0, 1, 0, 0, 0, 1]);
+        assert!(accumulator.get_interpolation_fraction() == 0.0);
+
+        accumulator.frame_begin(Duration::from_millis(10));
+        assert!((accumulator.get_interpolation_fraction() - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn slow_frames() {
+        let mut accumulator = TickAccumulator::new(Duration::from_millis(50));
+        assert!(accumulator.frame_begin(Duration::from_millis(120)) == 2);
+        assert!((accumulator.get_interpolation_fraction() - 0.4).abs() < 0.001);
+        assert!(accumulator.frame_begin(Duration::from_millis(30)) == 1);
+        assert!(accumulator.get_interpolation_fraction() < 0.001);
+    }
+
+    #[test]
+    fn time_scale() {
+        let mut accumulator = TickAccumulator::new(Duration::from_millis(50));
+        accumulator.set_time_scale(1.1);
+        let tick_count: u16 = (0..100)
+            .map(|_| accumulator.frame_begin(Duration::from_millis(50)))
+            .sum();
+        assert!(tick_count == 110);
+    }
+
+    #[test]
+    fn tick_interval_change() {
+        let mut accumulator = TickAccumulator::new(Duration::from_millis(20));
+        assert!(accumulator.frame_begin(Duration::from_millis(30)) == 1);
+        accumulator.set_tick_interval(Duration::from_millis(40));
+        assert!((accumulator.get_interpolation_fraction() - 0.5).abs() < 0.001);
+        assert!(accumulator.frame_begin(Duration::from_millis(10)) == 0);
+        assert!(accumulator.frame_begin(Duration::from_millis(10)) == 1);
+        assert!(accumulator.get_tick_interval() == Duration::from_millis(40));
+    }
+
+    #[test]
+    fn long_stall() {
+        let mut accumulator = TickAccumulator::new(Duration::from_millis(50));
+        assert!(accumulator.frame_begin(Duration::from_secs(10)) == MAX_TICKS_PER_FRAME);
+        assert!(accumulator.get_interpolation_fraction() < 1.0);
+        assert!(accumulator.frame_begin(Duration::from_millis(10)) <= 1);
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::packet_reader::PacketReader;
+
+/// A change to the rate at which the Server ticks, sent to each Client so that
+/// its tick clock, interpolation delay & the scheduling of timed Events adapt
+/// to the new rate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickRateChange {
+    /// The revision of the tick rate sent to the Client, which increases with
+    /// each change, so that a change arriving after a newer one is ignored
+    pub revision: u32,
+    /// The Server tick at which the new rate took effect
+    pub tick: u16,
+    /// The new duration of each tick
+    pub tick_interval: Duration,
+}
+
+impl TickRateChange {
+    /// Writes the change into an outgoing byte stream, with the tick interval
+    /// in microseconds
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        let micros = self.tick_interval.as_micros().min(u32::MAX as u128) as u32;
+        out_bytes.write_u32::<BigEndian>(self.revision).unwrap(); // write revision
+        out_bytes.write_u16::<BigEndian>(self.tick).unwrap(); // write tick
+        out_bytes.write_u32::<BigEndian>(micros).unwrap(); // write tick interval
+    }
+
+    /// Reads a change from incoming bytes, or returns None if they are too
+    /// short
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let cursor = reader.get_cursor();
+        let revision = cursor.read_u32::<BigEndian>().ok()?;
+        let tick = cursor.read_u16::<BigEndian>().ok()?;
+        let micros = cursor.read_u32::<BigEndian>().ok()?;
+        return Some(TickRateChange {
+            revision,
+            tick,
+            tick_interval: Duration::from_micros(micros as u64),
+        });
+    }
+}
@@ -0,0 +1,108 @@
+/// The time dilation requested per tick that a Client's input arrives earlier
+/// or later than the target, in thousandths of the Client's tick rate
+pub const TIME_DILATION_PER_TICK: f32 = 20.0;
+
+/// The greatest time dilation the Server will request, in thousandths of the
+/// Client's tick rate, so that a Client never runs more than 10% faster or
+/// slower than the Server
+pub const MAX_TIME_DILATION: i8 = 100;
+
+// How much each new sample of a Client's lead moves the average
+const LEAD_SMOOTHING_FACTOR: f32 = 0.1;
+
+/// Tracks how many ticks ahead of the Server a Client's packets arrive, and
+/// computes the time dilation which will bring that lead to the target depth
+/// of the Server's input buffer. A positive time dilation asks the Client to
+/// speed up its tick clock, a negative one to slow it down
+#[derive(Debug)]
+pub struct TimeDilation {
+    target_lead: f32,
+    average_lead: Option<f32>,
+}
+
+impl TimeDilation {
+    /// Creates a new TimeDilation, given the number of ticks ahead of the
+    /// Server which a Client's packets should arrive
+    pub fn new(target_lead: u16) -> Self {
+        TimeDilation {
+            target_lead: target_lead as f32,
+            average_lead: None,
+        }
+    }
+
+    /// Records the lead of a packet, which is the Client's tick written in its
+    /// header minus the Server's tick when it arrived
+    pub fn record_lead(&mut self, lead: i16) {
+        let lead = lead as f32;
+        self.average_lead = match self.average_lead {
+            Some(average_lead) => {
+                Some(average_lead + (lead - average_lead) * LEAD_SMOOTHING_FACTOR)
+            }
+            None => Some(lead),
+        };
+    }
+
+    /// Gets the smoothed lead of the Client's packets, or None if no packet
+    /// carrying a tick has arrived yet
+    pub fn get_average_lead(&self) -> Option<f32> {
+        return self.average_lead;
+    }
+
+    /// Gets the time dilation the Client should apply, in thousandths of its
+    /// tick rate
+    pub fn get_factor(&self) -> i8 {
+        let average_lead = match self.average_lead {
+            Some(average_lead) => average_lead,
+            None => return 0,
+        };
+        let factor = ((self.target_lead - average_lead) * TIME_DILATION_PER_TICK).round();
+        return factor
+            .max(-MAX_TIME_DILATION as f32)
+            .min(MAX_TIME_DILATION as f32) as i8;
+    }
+}
+
+/// Converts a time dilation factor, in thousandths, into the scale to apply to
+/// the passage of time on the Client
+pub fn time_dilation_scale(factor: i8) -> f32 {
+    return 1.0 + factor as f32 / 1000.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{time_dilation_scale, TimeDilation, MAX_TIME_DILATION};
+
+    #[test]
+    fn steers_toward_target() {
+        let mut dilation = TimeDilation::new(2);
+        assert!(dilation.get_factor() == 0);
+
+        // arriving just in time, the Client is asked to speed up
+        dilation.record_lead(0);
+        assert!(dilation.get_factor() == 40);
+        assert!((time_dilation_scale(dilation.get_factor()) - 1.04).abs() < 0.0001);
+
+        // arriving far too early, it is asked to slow down, but only so much
+        for _ in 0..100 {
+            dilation.record_lead(30);
+        }
+        assert!(dilation.get_factor() == -MAX_TIME_DILATION);
+
+        for _ in 0..200 {
+            dilation.record_lead(2);
+        }
+        assert!(dilation.get_factor() == 0);
+    }
+
+    #[test]
+    fn smoothing() {
+        let mut dilation = TimeDilation::new(2);
+        for _ in 0..100 {
+            dilation.record_lead(2);
+        }
+        // a single late packet barely moves the average
+        dilation.record_lead(-8);
+        assert!(dilation.get_factor() == 20);
+        assert!((dilation.get_average_lead().unwrap() - 1.0).abs() < 0.01);
+    }
+}
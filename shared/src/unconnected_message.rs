@@ -0,0 +1,146 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::packet_reader::PacketReader;
+
+/// The maximum number of bytes of data an unconnected message can carry
+pub const MAX_UNCONNECTED_DATA_SIZE: usize = 512;
+
+/// The number of bytes of the largest unconnected message, including the
+/// kind, token & data length written before the data
+pub const MAX_UNCONNECTED_MESSAGE_SIZE: usize = MAX_UNCONNECTED_DATA_SIZE + 7;
+
+/// The kinds of unconnected message, which are exchanged with a Server
+/// without establishing a connection
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u8)]
+pub enum UnconnectedKind {
+    /// Answered by the Server at once, to measure the Round Trip Time to it
+    Ping = 0,
+    /// Answered by the Server at once with its ServerStatus, set with
+    /// `NaiaServer::set_server_status()`
+    Status = 1,
+    /// Handed to the Server application, which may answer it
+    Message = 2,
+    /// Sent periodically by a Server to a master server to be listed, along
+    /// with its status. Answered with a challenge the Server must send back
+    /// in later registrations, to prove it can receive at its address
+    Register = 3,
+    /// Sent by a Server to a master server to be removed from its list
+    Deregister = 4,
+    /// Answered by a master server with a page of its list of Servers
+    List = 5,
+    /// Answered by the Server at once with the address it received the
+    /// request from, so that a host behind a NAT can learn its public address
+    Reflect = 6,
+}
+
+impl UnconnectedKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => return Some(UnconnectedKind::Ping),
+            1 => return Some(UnconnectedKind::Status),
+            2 => return Some(UnconnectedKind::Message),
+            3 => return Some(UnconnectedKind::Register),
+            4 => return Some(UnconnectedKind::Deregister),
+            5 => return Some(UnconnectedKind::List),
+            6 => return Some(UnconnectedKind::Reflect),
+            _ => return None,
+        }
+    }
+}
+
+/// A small message exchanged with a Server without a connection, handled
+/// before any of the connection machinery. Each response carries the token
+/// of the request it answers
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnconnectedMessage {
+    /// The kind of the message
+    pub kind: UnconnectedKind,
+    /// Chosen by the sender of a request, and copied into the response
+    pub token: u32,
+    /// The data of the message, of at most `MAX_UNCONNECTED_DATA_SIZE` bytes
+    pub data: Box<[u8]>,
+}
+
+impl UnconnectedMessage {
+    /// Creates a new UnconnectedMessage, or None if the data is too long
+    pub fn new(kind: UnconnectedKind, token: u32, data: &[u8]) -> Option<Self> {
+        if data.len() > MAX_UNCONNECTED_DATA_SIZE {
+            return None;
+        }
+        return Some(UnconnectedMessage {
+            kind,
+            token,
+            data: data.into(),
+        });
+    }
+
+    /// Writes the message into an outgoing byte stream
+    pub fn write(&self, out_bytes: &mut Vec<u8>) {
+        out_bytes.write_u8(self.kind as u8).unwrap();
+        out_bytes.write_u32::<BigEndian>(self.token).unwrap();
+        out_bytes
+            .write_u16::<BigEndian>(self.data.len() as u16)
+            .unwrap();
+        out_bytes.extend_from_slice(&self.data);
+    }
+
+    /// Reads a message written with `write()`. Any bytes after the message,
+    /// such as padding, are ignored. Returns None if the bytes are malformed
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let cursor = reader.get_cursor();
+        let kind = UnconnectedKind::from_u8(cursor.read_u8().ok()?)?;
+        let token = cursor.read_u32::<BigEndian>().ok()?;
+        let data_length = cursor.read_u16::<BigEndian>().ok()? as usize;
+        if data_length > MAX_UNCONNECTED_DATA_SIZE {
+            return None;
+        }
+        let start = cursor.position() as usize;
+        let buffer = reader.get_buffer();
+        if start + data_length > buffer.len() {
+            return None;
+        }
+        let data = buffer[start..start + data_length].into();
+        reader
+            .get_cursor()
+            .set_position((start + data_length) as u64);
+        return Some(UnconnectedMessage { kind, token, data });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        PacketReader, UnconnectedKind, UnconnectedMessage, MAX_UNCONNECTED_DATA_SIZE,
+        MAX_UNCONNECTED_MESSAGE_SIZE,
+    };
+
+    #[test]
+    fn read_write() {
+        let message = UnconnectedMessage::new(UnconnectedKind::Status, 77, b"de_dust").unwrap();
+        let mut bytes = Vec::new();
+        message.write(&mut bytes);
+        bytes.extend_from_slice(&[0; 16]);
+        let mut reader = PacketReader::new(&bytes);
+        assert!(UnconnectedMessage::read(&mut reader) == Some(message));
+
+        let mut reader = PacketReader::new(&bytes[..10]);
+        assert!(UnconnectedMessage::read(&mut reader).is_none());
+        assert!(UnconnectedMessage::new(
+            UnconnectedKind::Message,
+            0,
+            &[0; MAX_UNCONNECTED_DATA_SIZE + 1]
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn largest_message() {
+        let message =
+            UnconnectedMessage::new(UnconnectedKind::Status, 0, &[1; MAX_UNCONNECTED_DATA_SIZE])
+                .unwrap();
+        let mut bytes = Vec::new();
+        message.write(&mut bytes);
+        assert!(bytes.len() == MAX_UNCONNECTED_MESSAGE_SIZE);
+    }
+}
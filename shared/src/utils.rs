@@ -1,4 +1,10 @@
-use crate::{packet_type::PacketType, standard_header::StandardHeader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    packet_reader::PacketReader, packet_type::PacketType, standard_header::StandardHeader,
+};
 
 /// Write a connectionless packet, that is, one that does not rely on
 /// information normally retrieved from the connection
@@ -6,7 +12,7 @@ pub fn write_connectionless_payload(packet_type: PacketType, payload: &[u8]) ->
     // Add Ack Header onto message!
     let mut header_bytes = Vec::new();
 
-    let header = StandardHeader::new(packet_type, 0, 0, 0);
+    let header = StandardHeader::new(packet_type, 0, 0, 0, None, 0, false);
     header.write(&mut header_bytes);
 
     [header_bytes.as_slice(), &payload]
@@ -15,8 +21,87 @@ pub fn write_connectionless_payload(packet_type: PacketType, payload: &[u8]) ->
 }
 
 /// Strip the standard header off of a packet's payload and retrieve the payload
-/// bytes
-pub fn read_headerless_payload(payload: &[u8]) -> Box<[u8]> {
-    let (_, stripped_message) = StandardHeader::read(payload);
-    stripped_message
+/// bytes. Returns None if the packet is too short to hold its header
+pub fn read_headerless_payload(payload: &[u8]) -> Option<Box<[u8]>> {
+    let (_, stripped_message) = StandardHeader::read(payload)?;
+    return Some(stripped_message);
+}
+
+/// Write a string of up to 255 bytes into an outgoing byte stream, prefixed
+/// with its length
+pub fn write_string(string: &str, out_bytes: &mut Vec<u8>) {
+    out_bytes.write_u8(string.len() as u8).unwrap();
+    out_bytes.extend_from_slice(string.as_bytes());
+}
+
+/// Read a string written with `write_string()`. Returns None if the bytes
+/// are malformed
+pub fn read_string(reader: &mut PacketReader) -> Option<String> {
+    let length = reader.get_cursor().read_u8().ok()? as usize;
+    let start = reader.get_cursor().position() as usize;
+    let buffer = reader.get_buffer();
+    if start + length > buffer.len() {
+        return None;
+    }
+    let string = String::from_utf8(buffer[start..start + length].to_vec()).ok()?;
+    reader.get_cursor().set_position((start + length) as u64);
+    return Some(string);
+}
+
+/// Write a socket address into an outgoing byte stream
+pub fn write_socket_address(address: &SocketAddr, out_bytes: &mut Vec<u8>) {
+    match address.ip() {
+        IpAddr::V4(ip) => {
+            out_bytes.write_u8(4).unwrap();
+            out_bytes.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            out_bytes.write_u8(6).unwrap();
+            out_bytes.extend_from_slice(&ip.octets());
+        }
+    }
+    out_bytes.write_u16::<BigEndian>(address.port()).unwrap();
+}
+
+/// Read a socket address written with `write_socket_address()`. Returns None
+/// if the bytes are malformed
+pub fn read_socket_address(reader: &mut PacketReader) -> Option<SocketAddr> {
+    let cursor = reader.get_cursor();
+    let ip = match cursor.read_u8().ok()? {
+        4 => IpAddr::V4(Ipv4Addr::from(cursor.read_u32::<BigEndian>().ok()?)),
+        6 => IpAddr::V6(Ipv6Addr::from(cursor.read_u128::<BigEndian>().ok()?)),
+        _ => return None,
+    };
+    let port = cursor.read_u16::<BigEndian>().ok()?;
+    return Some(SocketAddr::new(ip, port));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use crate::{
+        packet_reader::PacketReader,
+        utils::{read_socket_address, write_socket_address},
+    };
+
+    #[test]
+    fn socket_address_round_trip() {
+        let addresses: Vec<SocketAddr> = vec![
+            "127.0.0.1:14191".parse().unwrap(),
+            "[2001:db8::1]:3000".parse().unwrap(),
+        ];
+        for address in addresses {
+            let mut bytes = Vec::new();
+            write_socket_address(&address, &mut bytes);
+            let mut reader = PacketReader::new(&bytes);
+            assert!(read_socket_address(&mut reader) == Some(address));
+        }
+    }
+
+    #[test]
+    fn malformed_socket_address() {
+        let mut reader = PacketReader::new(&[5, 0, 0]);
+        assert!(read_socket_address(&mut reader).is_none());
+    }
 }
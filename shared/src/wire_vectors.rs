@@ -0,0 +1,241 @@
+// Round-trips the golden encodings in `wire_vectors.txt`, which pin the parts
+// of the wire format that don't depend on an application's Manifest
+
+use std::{any::TypeId, collections::HashMap, io::Cursor};
+
+use crate::{
+    connection_stats::DropLog,
+    entities::local_entity_key::LocalEntityKey,
+    events::{event::Event, event_manager::EventManager, event_type::EventType},
+    manager_type::ManagerType,
+    packet_reader::PacketReader,
+    packet_type::PacketType,
+    packet_writer::PacketWriter,
+    raw_payload_manager::RawPayloadManager,
+    standard_header::StandardHeader,
+    ClientRosterChange,
+};
+
+const WIRE_VECTORS: &str = include_str!("../wire_vectors.txt");
+
+// None of the vectors carry Events
+#[derive(Clone)]
+enum NoEvents {}
+
+impl EventType for NoEvents {
+    fn write(&mut self, _: &mut Vec<u8>) {
+        match *self {}
+    }
+
+    fn get_type_id(&self) -> TypeId {
+        match *self {}
+    }
+
+    fn get_boxed_event(&self) -> Box<dyn Event<Self>> {
+        match *self {}
+    }
+}
+
+struct WireVector {
+    kind: String,
+    name: String,
+    bytes: Vec<u8>,
+    fields: HashMap<String, String>,
+}
+
+impl WireVector {
+    fn field(&self, name: &str) -> &str {
+        return self
+            .fields
+            .get(name)
+            .map(|value| value.as_str())
+            .unwrap_or_else(|| panic!("vector {} has no field {}", self.name, name));
+    }
+
+    fn number(&self, name: &str) -> i64 {
+        let value = self.field(name);
+        let number = match value.strip_prefix("0x") {
+            Some(hex) => i64::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+        return number.unwrap_or_else(|_| panic!("vector {} has a bad {}", self.name, name));
+    }
+
+    fn list(&self, name: &str) -> Vec<&str> {
+        return match self.fields.get(name) {
+            Some(value) => value.split(',').collect(),
+            None => Vec::new(),
+        };
+    }
+}
+
+fn parse_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    assert!(digits.len() % 2 == 0, "odd number of hex digits in {}", hex);
+    return digits
+        .chunks(2)
+        .map(|pair| {
+            let byte: String = pair.iter().collect();
+            return u8::from_str_radix(&byte, 16).unwrap();
+        })
+        .collect();
+}
+
+fn parse_vectors() -> Vec<WireVector> {
+    let mut vectors: Vec<WireVector> = Vec::new();
+    for line in WIRE_VECTORS.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_at(line.find(':').unwrap());
+        let value = value[1..].trim();
+        match key {
+            "vector" => {
+                let mut words = value.split_whitespace();
+                vectors.push(WireVector {
+                    kind: words.next().unwrap().to_string(),
+                    name: words.next().unwrap().to_string(),
+                    bytes: Vec::new(),
+                    fields: HashMap::new(),
+                });
+            }
+            "hex" => vectors.last_mut().unwrap().bytes = parse_hex(value),
+            "fields" => {
+                let vector = vectors.last_mut().unwrap();
+                for pair in value.split_whitespace() {
+                    let (name, field) = pair.split_at(pair.find('=').unwrap());
+                    vector
+                        .fields
+                        .insert(name.to_string(), field[1..].to_string());
+                }
+            }
+            _ => panic!("unknown line in wire vectors: {}", line),
+        }
+    }
+    return vectors;
+}
+
+fn check_standard_header(vector: &WireVector) {
+    let host_tick = match vector.field("host_tick") {
+        "none" => None,
+        _ => Some(vector.number("host_tick") as u16),
+    };
+    let header = StandardHeader::new(
+        PacketType::from(vector.number("packet_type") as u8),
+        vector.number("seq") as u16,
+        vector.number("ack_seq") as u16,
+        vector.number("ack_field") as u32,
+        host_tick,
+        vector.number("time_dilation") as i8,
+        vector.field("compact") == "true",
+    );
+    let mut bytes = Vec::new();
+    header.write(&mut bytes);
+    assert!(
+        bytes == vector.bytes,
+        "vector {} encodes wrongly",
+        vector.name
+    );
+
+    let (read_header, payload) = StandardHeader::read(&vector.bytes).unwrap();
+    assert!(payload.is_empty());
+    assert!(
+        StandardHeader::get_packet_type(&vector.bytes).unwrap() as i64
+            == vector.number("packet_type")
+    );
+    assert!(read_header.sequence() as i64 == vector.number("seq"));
+    assert!(read_header.ack_seq() as i64 == vector.number("ack_seq"));
+    assert!(read_header.ack_field() as i64 == vector.number("ack_field"));
+    assert!(read_header.host_tick() == host_tick);
+    assert!(read_header.time_dilation() as i64 == vector.number("time_dilation"));
+    assert!(read_header.is_compact() == (vector.field("compact") == "true"));
+}
+
+fn check_local_entity_key(vector: &WireVector) {
+    let key = LocalEntityKey::new(
+        vector.number("index") as u16,
+        vector.number("generation") as u8,
+    );
+    let mut bytes = Vec::new();
+    key.write(&mut bytes);
+    assert!(
+        bytes == vector.bytes,
+        "vector {} encodes wrongly",
+        vector.name
+    );
+
+    let read_key = LocalEntityKey::read(&mut Cursor::new(&vector.bytes[..]));
+    assert!(read_key == Some(key));
+}
+
+fn parse_roster_change(entry: &str) -> (u32, ClientRosterChange) {
+    let parts: Vec<&str> = entry.split(':').collect();
+    let client_id = parts[2].parse().unwrap();
+    let change = match parts[1] {
+        "joined" => ClientRosterChange::Joined(client_id),
+        "left" => ClientRosterChange::Left(client_id),
+        _ => panic!("unknown roster change {}", entry),
+    };
+    return (parts[0].parse().unwrap(), change);
+}
+
+fn check_payload(vector: &WireVector) {
+    let raw_payloads: Vec<Vec<u8>> = vector.list("raw").into_iter().map(parse_hex).collect();
+    let roster_changes: Vec<(u32, ClientRosterChange)> = vector
+        .list("roster")
+        .into_iter()
+        .map(parse_roster_change)
+        .collect();
+
+    let mut writer = PacketWriter::new();
+    for payload in raw_payloads.iter() {
+        assert!(writer.write_raw_payload(payload, usize::MAX));
+    }
+    for (revision, change) in roster_changes.iter() {
+        assert!(writer.write_roster_change(*revision, change));
+    }
+    assert!(
+        writer.get_bytes().as_ref() == &vector.bytes[..],
+        "vector {} encodes wrongly",
+        vector.name
+    );
+
+    let mut raw_payload_manager = RawPayloadManager::new(usize::MAX);
+    let mut event_manager = EventManager::<NoEvents>::new(1);
+    let mut drop_log = DropLog::new("127.0.0.1:0".parse().unwrap(), false);
+    let mut reader = PacketReader::new(&vector.bytes);
+    while let Some(manager_type_byte) = reader.read_u8() {
+        let manager_type: ManagerType = manager_type_byte.into();
+        let readable = match manager_type {
+            ManagerType::Raw => raw_payload_manager.process_data(&mut reader, &mut drop_log),
+            ManagerType::ClientRoster => {
+                event_manager.process_roster_data(&mut reader, &mut drop_log)
+            }
+            _ => panic!("vector {} has an unexpected section", vector.name),
+        };
+        assert!(readable, "vector {} can't be read", vector.name);
+    }
+    for payload in raw_payloads.iter() {
+        assert!(raw_payload_manager.pop_incoming_payload().unwrap().as_ref() == &payload[..]);
+    }
+    assert!(raw_payload_manager.pop_incoming_payload().is_none());
+    for roster_change in roster_changes.iter() {
+        assert!(event_manager.pop_incoming_roster_change() == Some(*roster_change));
+    }
+    assert!(event_manager.pop_incoming_roster_change().is_none());
+}
+
+#[test]
+fn round_trip_wire_vectors() {
+    let vectors = parse_vectors();
+    assert!(!vectors.is_empty());
+    for vector in vectors.iter() {
+        match vector.kind.as_str() {
+            "standard_header" => check_standard_header(vector),
+            "local_entity_key" => check_local_entity_key(vector),
+            "payload" => check_payload(vector),
+            _ => panic!("unknown kind of wire vector {}", vector.kind),
+        }
+    }
+}